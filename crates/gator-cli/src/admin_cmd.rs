@@ -0,0 +1,45 @@
+//! `gator admin` commands: operator controls that span every plan.
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+use gator_core::orchestrator::stop_all;
+
+use crate::AdminCommands;
+
+/// Run an admin command.
+pub async fn run_admin(pool: &SqlitePool, command: AdminCommands) -> Result<()> {
+    match command {
+        AdminCommands::StopAll => run_stop_all(pool).await,
+    }
+}
+
+/// Stop every running plan and print what was stopped.
+async fn run_stop_all(pool: &SqlitePool) -> Result<()> {
+    let report = stop_all(pool).await?;
+
+    if report.plans.is_empty() && report.tasks.is_empty() {
+        println!("Nothing is running.");
+        return Ok(());
+    }
+
+    println!("Interrupted {} plan(s):", report.plans.len());
+    for plan in &report.plans {
+        println!("  {plan}");
+    }
+    println!("Reset {} task(s) to pending:", report.tasks.len());
+    for task in &report.tasks {
+        println!("  {task}");
+    }
+    println!(
+        "Killed {} agent process(es), removed {} container(s).",
+        report.killed_processes.len(),
+        report.removed_containers.len()
+    );
+    for error in &report.errors {
+        eprintln!("  warning: {error}");
+    }
+    println!();
+    println!("Resume a plan with: gator dispatch <plan-id>");
+    Ok(())
+}
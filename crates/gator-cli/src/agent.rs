@@ -1,13 +1,14 @@
 //! Agent-mode CLI command implementations.
 //!
 //! When `GATOR_AGENT_TOKEN` is set in the environment, the CLI restricts
-//! its command surface to the four agent-mode commands: `task`, `check`,
-//! `progress`, and `done`. This module contains the dispatch logic and
+//! its command surface to the five agent-mode commands: `task`, `check`,
+//! `progress`, `done`, and `ask`. This module contains the dispatch logic and
 //! each command's implementation.
 //!
 //! All commands validate the scoped token before doing any work. The token
 //! encodes a (task_id, attempt) pair that scopes the agent to exactly one
-//! task.
+//! task. Every invocation with a valid token, including denied operator
+//! commands, is recorded in the `agent_audit` table (see `gator audit`).
 
 use std::process::Stdio;
 use std::time::Instant;
@@ -15,7 +16,8 @@ use std::time::Instant;
 use anyhow::{Context, Result, bail};
 use gator_core::token::guard::{self, GuardError};
 use gator_core::token::{TokenClaims, TokenConfig};
-use gator_db::models::Invariant;
+use gator_db::models::{AuditOutcome, Invariant};
+use gator_db::queries::agent_audit::{self, NewAgentAudit};
 use sqlx::SqlitePool;
 
 use crate::Commands;
@@ -48,18 +50,84 @@ pub async fn run_agent_mode(command: Commands, pool: Option<&SqlitePool>) -> Res
         other => anyhow::anyhow!("{other}"),
     })?;
 
-    match command {
-        Commands::Task => cmd_task(&claims, pool).await,
-        Commands::Check => cmd_check(&claims, pool).await,
-        Commands::Progress { message } => cmd_progress(&claims, pool, &message).await,
-        Commands::Done => cmd_done(&claims, pool).await,
+    let name = command_name(&command);
+    let (result, denied) = match command {
+        Commands::Task { command: None } => (cmd_task(&claims, pool).await, false),
+        Commands::Check => (cmd_check(&claims, pool).await, false),
+        Commands::Progress { message } => (cmd_progress(&claims, pool, &message).await, false),
+        Commands::Done => (cmd_done(&claims, pool).await, false),
+        Commands::Ask { question } => (cmd_ask(&claims, pool, &question).await, false),
         // Any operator command is blocked in agent mode.
-        _ => {
-            bail!("Error: this command is not available in agent mode");
+        _ => (
+            Err(anyhow::anyhow!(
+                "Error: this command is not available in agent mode"
+            )),
+            true,
+        ),
+    };
+
+    let outcome = match (&result, denied) {
+        (Ok(()), _) => AuditOutcome::Ok,
+        (Err(_), false) => AuditOutcome::Error,
+        (Err(_), true) => AuditOutcome::Denied,
+    };
+    record_audit(pool, &claims, &name, outcome, result.as_ref().err()).await;
+    result
+}
+
+/// The command's name as typed, e.g. `done` or `plan`.
+fn command_name(command: &Commands) -> String {
+    match command {
+        Commands::Task { .. } => "task".to_string(),
+        Commands::Check => "check".to_string(),
+        Commands::Progress { .. } => "progress".to_string(),
+        Commands::Done => "done".to_string(),
+        Commands::Ask { .. } => "ask".to_string(),
+        // Operator commands: the variant name, in kebab case.
+        other => {
+            let debug = format!("{other:?}");
+            let variant = debug
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .next()
+                .unwrap_or_default();
+            let mut name = String::new();
+            for (i, c) in variant.chars().enumerate() {
+                if c.is_ascii_uppercase() && i > 0 {
+                    name.push('-');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            name
         }
     }
 }
 
+/// Record an invocation in the audit log. Best-effort: without a database,
+/// or if the insert fails, the command's own result still stands.
+async fn record_audit(
+    pool: Option<&SqlitePool>,
+    claims: &TokenClaims,
+    command: &str,
+    outcome: AuditOutcome,
+    error: Option<&anyhow::Error>,
+) {
+    let Some(pool) = pool else {
+        return;
+    };
+    let error = error.map(|e| format!("{e:#}"));
+    let new = NewAgentAudit {
+        task_id: claims.task_id,
+        attempt: claims.attempt as i32,
+        command,
+        token_version: &claims.version.to_string(),
+        outcome,
+        error: error.as_deref(),
+    };
+    if let Err(e) = agent_audit::insert_agent_audit(pool, &new).await {
+        tracing::warn!(error = %e, "failed to record agent audit entry");
+    }
+}
+
 // -----------------------------------------------------------------------
 // gator task
 // -----------------------------------------------------------------------
@@ -72,31 +140,14 @@ async fn cmd_task(claims: &TokenClaims, pool: Option<&SqlitePool>) -> Result<()>
     let pool = require_db(pool)?;
 
     // Look up the task by ID.
-    let task: gator_db::models::Task = sqlx::query_as(
-        "SELECT id, plan_id, name, description, scope_level, gate_policy, \
-         retry_max, status, assigned_harness, worktree_path, attempt, \
-         created_at, started_at, completed_at \
-         FROM tasks WHERE id = $1",
-    )
-    .bind(claims.task_id)
-    .fetch_optional(pool)
-    .await
-    .context("failed to query task")?
-    .with_context(|| format!("task {} not found", claims.task_id))?;
+    let task = gator_db::queries::tasks::get_task(pool, claims.task_id)
+        .await?
+        .with_context(|| format!("task {} not found", claims.task_id))?;
 
     // Look up linked invariants for this task.
-    let invariants: Vec<Invariant> = sqlx::query_as(
-        "SELECT i.id, i.name, i.description, i.kind, i.command, i.args, \
-         i.expected_exit_code, i.threshold, i.scope, i.created_at \
-         FROM invariants i \
-         INNER JOIN task_invariants ti ON ti.invariant_id = i.id \
-         WHERE ti.task_id = $1 \
-         ORDER BY i.name",
-    )
-    .bind(claims.task_id)
-    .fetch_all(pool)
-    .await
-    .context("failed to query invariants for task")?;
+    let invariants: Vec<Invariant> =
+        gator_db::queries::invariants::get_enabled_invariants_for_task(pool, claims.task_id)
+            .await?;
 
     // Print as clean markdown.
     println!("# Task: {}", task.name);
@@ -106,7 +157,7 @@ async fn cmd_task(claims: &TokenClaims, pool: Option<&SqlitePool>) -> Result<()>
     println!("## Details");
     println!();
     println!("- **Scope**: {}", task.scope_level);
-    println!("- **Gate policy**: {}", task.gate_policy);
+    println!("- **Gate policy**: {}", task.gate());
     println!("- **Attempt**: {}/{}", claims.attempt, task.retry_max);
     println!("- **Status**: {}", task.status);
     println!();
@@ -155,18 +206,9 @@ async fn cmd_check(claims: &TokenClaims, pool: Option<&SqlitePool>) -> Result<()
     let pool = require_db(pool)?;
 
     // Look up linked invariants.
-    let invariants: Vec<Invariant> = sqlx::query_as(
-        "SELECT i.id, i.name, i.description, i.kind, i.command, i.args, \
-         i.expected_exit_code, i.threshold, i.scope, i.created_at \
-         FROM invariants i \
-         INNER JOIN task_invariants ti ON ti.invariant_id = i.id \
-         WHERE ti.task_id = $1 \
-         ORDER BY i.name",
-    )
-    .bind(claims.task_id)
-    .fetch_all(pool)
-    .await
-    .context("failed to query invariants for task")?;
+    let invariants: Vec<Invariant> =
+        gator_db::queries::invariants::get_enabled_invariants_for_task(pool, claims.task_id)
+            .await?;
 
     if invariants.is_empty() {
         println!("No invariants linked to this task. Nothing to check.");
@@ -355,6 +397,33 @@ async fn cmd_done(claims: &TokenClaims, pool: Option<&SqlitePool>) -> Result<()>
     Ok(())
 }
 
+// -----------------------------------------------------------------------
+// gator ask
+// -----------------------------------------------------------------------
+
+/// `gator ask "question"` -- ask the operator a question.
+///
+/// Records a `question` event and blocks the task on a human
+/// (`running -> blocked_on_human`); the orchestrator notifies the operator.
+/// The agent ends its turn, and its session is resumed with the answer
+/// from `gator answer`.
+async fn cmd_ask(claims: &TokenClaims, pool: Option<&SqlitePool>, question: &str) -> Result<()> {
+    let pool = require_db(pool)?;
+
+    gator_core::state::dispatch::ask_question(
+        pool,
+        claims.task_id,
+        claims.attempt as i32,
+        question,
+    )
+    .await
+    .context("failed to ask the question")?;
+
+    println!("Question sent to the operator.");
+    println!("End your turn now without further changes; the answer arrives as your next message.");
+    Ok(())
+}
+
 // -----------------------------------------------------------------------
 // Helpers
 // -----------------------------------------------------------------------
@@ -483,7 +552,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_rejects_init_command() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -497,11 +566,7 @@ mod tests {
             )
         };
 
-        let result = super::run_agent_mode(
-            Commands::Init { force: false },
-            None,
-        )
-        .await;
+        let result = super::run_agent_mode(Commands::Init { force: false }, None).await;
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
         assert!(
@@ -515,7 +580,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_rejects_plan_command() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -531,7 +596,13 @@ mod tests {
 
         let result = super::run_agent_mode(
             Commands::Plan {
-                command: crate::PlanCommands::Show { plan_id: None },
+                command: crate::PlanCommands::Show {
+                    plan_id: None,
+                    full: false,
+                    limit: 50,
+                    offset: 0,
+                    archived: false,
+                },
             },
             None,
         )
@@ -549,7 +620,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_rejects_invariant_command() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -583,7 +654,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_task_requires_db() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -598,7 +669,7 @@ mod tests {
         };
 
         // Without a DB pool, the command should fail with a helpful message.
-        let result = super::run_agent_mode(Commands::Task, None).await;
+        let result = super::run_agent_mode(Commands::Task { command: None }, None).await;
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
         assert!(
@@ -612,7 +683,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_check_requires_db() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -640,7 +711,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_progress_requires_db() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -674,7 +745,7 @@ mod tests {
 
     #[tokio::test]
     async fn agent_mode_done_requires_db() {
-        let _lock = crate::test_util::lock_env();
+        let _lock = crate::test_util::lock_env_async().await;
         let config = test_config();
         let task_id = Uuid::new_v4();
         let token = generate_token(&config, task_id, 0);
@@ -700,6 +771,40 @@ mod tests {
         unsafe { std::env::remove_var("GATOR_TOKEN_SECRET") };
     }
 
+    #[tokio::test]
+    async fn agent_mode_ask_requires_db() {
+        let _lock = crate::test_util::lock_env_async().await;
+        let config = test_config();
+        let task_id = Uuid::new_v4();
+        let token = generate_token(&config, task_id, 0);
+
+        // SAFETY: serialized by mutex, test-only code.
+        unsafe { std::env::set_var(AGENT_TOKEN_ENV, &token) };
+        unsafe {
+            std::env::set_var(
+                "GATOR_TOKEN_SECRET",
+                "6167656e742d6d6f64652d746573742d736563726574",
+            )
+        };
+
+        let result = super::run_agent_mode(
+            Commands::Ask {
+                question: "Which port?".to_string(),
+            },
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+        let err_msg = result.unwrap_err().to_string();
+        assert!(
+            err_msg.contains("database connection required"),
+            "expected DB required message, got: {err_msg}"
+        );
+
+        unsafe { std::env::remove_var(AGENT_TOKEN_ENV) };
+        unsafe { std::env::remove_var("GATOR_TOKEN_SECRET") };
+    }
+
     #[test]
     fn truncate_string_within_limit() {
         let s = "hello world";
@@ -0,0 +1,111 @@
+//! `gator audit` command: show what an agent asked the control plane to do,
+//! which auto-approval rules passed the task without a human, and the
+//! compliance attestations of its changes.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::AuditOutcome;
+use gator_db::queries::agent_audit;
+use gator_db::queries::attestations;
+use gator_db::queries::auto_approvals;
+use gator_db::queries::tasks as task_db;
+
+/// Run the audit command.
+pub async fn run_audit(pool: &SqlitePool, task_id_str: &str, attempt: Option<i32>) -> Result<()> {
+    let task_id =
+        Uuid::parse_str(task_id_str).with_context(|| format!("invalid task ID: {task_id_str}"))?;
+
+    let task = task_db::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+
+    println!("Task: {} ({})", task.name, task.id);
+    println!("Status: {} (attempt {})", task.status, task.attempt);
+    println!();
+
+    let approvals = auto_approvals::list_auto_approvals_for_task(pool, task_id, attempt).await?;
+    if !approvals.is_empty() {
+        println!("Auto-approvals ({}):", approvals.len());
+        for approval in &approvals {
+            let time = crate::timefmt::format(approval.created_at, "%Y-%m-%d %H:%M:%S%.3f");
+            let files = approval.files.0.len();
+            if crate::output::is_plain() {
+                println!(
+                    "  {time}, attempt {}, rule {}, {} lines in {files} file(s)",
+                    approval.attempt, approval.rule, approval.diff_lines
+                );
+            } else {
+                println!(
+                    "  [{time}] [attempt {}] auto-approved by rule {:?} ({} lines in {files} file(s))",
+                    approval.attempt, approval.rule, approval.diff_lines
+                );
+            }
+            for file in &approval.files.0 {
+                println!("    {file}");
+            }
+        }
+        println!();
+    }
+
+    let attested = attestations::list_attestations_for_task(pool, task_id, attempt).await?;
+    if !attested.is_empty() {
+        println!("Attestations ({}):", attested.len());
+        for attestation in &attested {
+            let time = crate::timefmt::format(attestation.created_at, "%Y-%m-%d %H:%M:%S%.3f");
+            let harness = match &attestation.harness_version {
+                Some(version) => format!("{} {version}", attestation.harness),
+                None => attestation.harness.clone(),
+            };
+            if crate::output::is_plain() {
+                println!(
+                    "  {time}, attempt {}, {harness}, file {}",
+                    attestation.attempt, attestation.path
+                );
+            } else {
+                println!(
+                    "  [{time}] [attempt {}] {harness} -> {}",
+                    attestation.attempt, attestation.path
+                );
+            }
+            println!("    base commit: {}", attestation.base_commit);
+            println!("    prompt sha256: {}", attestation.prompt_sha256);
+            println!("    diff sha256: {}", attestation.diff_sha256);
+        }
+        println!();
+    }
+
+    let entries = agent_audit::list_agent_audit_for_task(pool, task_id, attempt).await?;
+    if entries.is_empty() {
+        println!("No agent commands recorded.");
+        return Ok(());
+    }
+
+    println!("Agent commands ({}):", entries.len());
+    for entry in &entries {
+        let time = crate::timefmt::format(entry.created_at, "%Y-%m-%d %H:%M:%S%.3f");
+        let detail = match (&entry.outcome, &entry.error) {
+            (AuditOutcome::Ok, _) | (_, None) => String::new(),
+            (_, Some(error)) => format!(": {}", first_line(error)),
+        };
+        if crate::output::is_plain() {
+            println!(
+                "  {time}, attempt {}, gator {}, {} ({} token){detail}",
+                entry.attempt, entry.command, entry.outcome, entry.token_version
+            );
+            continue;
+        }
+        println!(
+            "  [{time}] [attempt {}] gator {} -> {} ({} token){detail}",
+            entry.attempt, entry.command, entry.outcome, entry.token_version
+        );
+    }
+
+    Ok(())
+}
+
+/// The first line of a possibly multi-line error.
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or_default()
+}
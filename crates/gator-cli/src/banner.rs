@@ -0,0 +1,173 @@
+//! Startup banner for long-running commands.
+//!
+//! `gator dispatch` and `gator serve` log one structured line describing the
+//! build and environment they run with, so a log read after the fact shows
+//! which gator, schema, and agent CLIs produced it. When update checks are
+//! enabled (`GATOR_CHECK_UPDATES` env > `updates.check` in the config file),
+//! a background task also looks up the latest GitHub release and logs when a
+//! newer one exists. The lookup shells out to `curl` like the webhook
+//! transport and never fails the command.
+
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::process::Command;
+
+use gator_core::harness::HarnessRegistry;
+use gator_core::worktree::WorktreeManager;
+use gator_db::models::Plan;
+
+/// Version of this gator build.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// GitHub API endpoint for the newest published release.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/tftio/gator/releases/latest";
+
+/// Maximum time the update check may take.
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Log the banner for `gator dispatch` of `plan`.
+pub async fn log_dispatch_banner(
+    pool: &SqlitePool,
+    registry: &HarnessRegistry,
+    plan: &Plan,
+    max_agents: usize,
+) {
+    let schema = schema_version(pool).await;
+    let harnesses = harness_summary(registry).await;
+    let worktree_base = WorktreeManager::new(&plan.project_path, None)
+        .map(|mgr| mgr.worktree_base().display().to_string())
+        .unwrap_or_else(|_| "-".to_string());
+    tracing::info!(
+        version = VERSION,
+        schema_version = %schema,
+        harnesses = %harnesses,
+        isolation = %plan.isolation,
+        worktree_base = %worktree_base,
+        max_agents,
+        plan = %plan.name,
+        "gator dispatch starting"
+    );
+}
+
+/// Log the banner for `gator serve` listening on `bind:port`.
+pub async fn log_serve_banner(pool: &SqlitePool, bind: &str, port: u16) {
+    let schema = schema_version(pool).await;
+    tracing::info!(
+        version = VERSION,
+        schema_version = %schema,
+        bind,
+        port,
+        "gator serve starting"
+    );
+}
+
+/// Look for a newer release in the background when `enabled`.
+pub fn spawn_update_check(enabled: bool) {
+    if !enabled {
+        return;
+    }
+    tokio::spawn(async {
+        match latest_release().await {
+            Some(latest) if is_newer(&latest, VERSION) => {
+                tracing::info!(
+                    current = VERSION,
+                    latest = %latest,
+                    "newer gator release available"
+                );
+            }
+            Some(_) => tracing::debug!(current = VERSION, "gator is up to date"),
+            None => tracing::debug!("update check failed"),
+        }
+    });
+}
+
+async fn schema_version(pool: &SqlitePool) -> String {
+    match gator_db::pool::schema_version(pool).await {
+        Ok(Some(version)) => version.to_string(),
+        Ok(None) => "none".to_string(),
+        Err(e) => {
+            tracing::debug!(error = %e, "could not read schema version");
+            "unknown".to_string()
+        }
+    }
+}
+
+/// `name@version` for every registered harness, e.g.
+/// `claude-code@1.0.3 (Claude Code)`.
+async fn harness_summary(registry: &HarnessRegistry) -> String {
+    let mut parts = Vec::new();
+    for name in registry.list() {
+        let version = match registry.get(name) {
+            Some(harness) => harness.version().await,
+            None => None,
+        };
+        parts.push(format!(
+            "{name}@{}",
+            version.as_deref().unwrap_or("unknown")
+        ));
+    }
+    parts.join(", ")
+}
+
+/// Tag of the latest GitHub release, or `None` if it cannot be fetched.
+async fn latest_release() -> Option<String> {
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--fail",
+            "--location",
+            "--max-time",
+            &UPDATE_CHECK_TIMEOUT.as_secs().to_string(),
+            "--header",
+            "Accept: application/vnd.github+json",
+            "--user-agent",
+            &format!("gator/{VERSION}"),
+            LATEST_RELEASE_URL,
+        ])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let body: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    body.get("tag_name")?.as_str().map(str::to_string)
+}
+
+/// Whether release tag `latest` (e.g. `v0.3.0`) is newer than `current`.
+/// Tags that are not `MAJOR.MINOR.PATCH` never count as newer.
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let tag = tag.trim().trim_start_matches('v');
+    // Ignore pre-release and build metadata: `1.2.3-rc.1+abc` -> `1.2.3`.
+    let core = tag.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_tags_compare_by_semver() {
+        assert!(is_newer("v0.3.0", "0.2.0"));
+        assert!(is_newer("0.2.10", "0.2.9"));
+        assert!(is_newer("v1.0.0-rc.1", "0.9.9"));
+        assert!(!is_newer("v0.2.0", "0.2.0"));
+        assert!(!is_newer("v0.1.9", "0.2.0"));
+        assert!(!is_newer("nightly", "0.2.0"));
+        assert!(!is_newer("v1.2", "0.2.0"));
+    }
+}
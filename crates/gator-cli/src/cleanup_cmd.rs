@@ -1,15 +1,113 @@
-//! `gator cleanup <plan-id>` command: remove worktrees for completed tasks.
+//! `gator cleanup` command: remove worktrees for completed tasks, and
+//! strip old gate output.
+//!
+//! Branches of tasks that `gator merge` merged are deleted too: their
+//! commits are on the base branch. Other branches are kept.
+//!
+//! Worktrees are also garbage-collected by policy across all plans: those
+//! of finished tasks idle past `--max-age`, and, while the disk holding
+//! them has less than `--min-free` available, the least recently active
+//! ones. Only the worktree goes; its branch keeps the task's commits, and a
+//! retry checks it out again.
+//!
+//! Gate output is pruned separately from everything else: stdout and stderr
+//! of gate results -- and of the setup and warm-up results recorded beside
+//! them -- older than the retention window are dropped across all plans,
+//! while the results themselves -- pass/fail, exit code, timing -- are kept
+//! for good.
 
-use anyhow::{Context, Result};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Utc};
 use sqlx::SqlitePool;
 
 use gator_core::worktree::WorktreeManager;
+use gator_core::worktree::disk;
 use gator_db::models::TaskStatus;
+use gator_db::queries::gate_results as gate_db;
 use gator_db::queries::plans as plan_db;
+use gator_db::queries::setup_results as setup_db;
 use gator_db::queries::tasks as task_db;
+use gator_db::queries::warmup_results as warmup_db;
+
+/// When `gator cleanup` garbage-collects worktrees of finished tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorktreePolicy {
+    /// Remove worktrees idle for longer than this.
+    pub max_age: Option<Duration>,
+    /// Remove the least recently active worktrees while fewer bytes than
+    /// this are free on their filesystem.
+    pub min_free: Option<u64>,
+}
+
+impl WorktreePolicy {
+    fn is_set(&self) -> bool {
+        self.max_age.is_some() || self.min_free.is_some()
+    }
+}
+
+/// Parse an age such as `7d`, `12h`, `30m`, or `2w`.
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let unit_start = s.char_indices().last().map_or(0, |(i, _)| i);
+    let (number, unit) = s.split_at(unit_start);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid age {s:?}: expected e.g. 7d, 12h, or 30m"))?;
+    match unit {
+        "m" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "d" => Ok(Duration::days(number)),
+        "w" => Ok(Duration::weeks(number)),
+        _ => Err(format!("invalid age unit in {s:?}: use m, h, d, or w")),
+    }
+}
+
+/// Run the cleanup command: remove the worktrees of `plan_id_str`, if
+/// given, garbage-collect worktrees by `policy`, then strip gate output
+/// older than `gate_output_days`, if set.
+pub async fn run_cleanup(
+    pool: &SqlitePool,
+    plan_id_str: Option<&str>,
+    all: bool,
+    gate_output_days: Option<u32>,
+    policy: WorktreePolicy,
+) -> Result<()> {
+    if plan_id_str.is_none() && gate_output_days.is_none() && !policy.is_set() {
+        bail!(
+            "nothing to clean up: give a plan ID, or pass --max-age, --min-free, or \
+             --gate-output-days (or set them under [cleanup] in the config file)"
+        );
+    }
+    if let Some(plan_id_str) = plan_id_str {
+        remove_worktrees(pool, plan_id_str, all).await?;
+    }
+    if policy.is_set() {
+        collect_worktrees(pool, policy).await?;
+    }
+    if let Some(days) = gate_output_days {
+        let stripped = prune_gate_output(pool, days).await?;
+        println!(
+            "Stripped output from {stripped} gate, setup, and warm-up result(s) older than \
+             {days} day(s)."
+        );
+    }
+    Ok(())
+}
+
+/// Strip stdout and stderr from gate, setup, and warm-up results older
+/// than `days`, keeping their verdicts. Returns the number of results
+/// stripped.
+pub async fn prune_gate_output(pool: &SqlitePool, days: u32) -> Result<u64> {
+    let cutoff = Utc::now() - Duration::days(i64::from(days));
+    Ok(gate_db::strip_gate_output(pool, cutoff).await?
+        + setup_db::strip_setup_output(pool, cutoff).await?
+        + warmup_db::strip_warmup_output(pool, cutoff).await?)
+}
 
-/// Run the cleanup command.
-pub async fn run_cleanup(pool: &SqlitePool, plan_id_str: &str, all: bool) -> Result<()> {
+/// Remove the worktrees of a plan's tasks.
+async fn remove_worktrees(pool: &SqlitePool, plan_id_str: &str, all: bool) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     let plan = plan_db::get_plan(pool, plan_id)
@@ -23,6 +121,7 @@ pub async fn run_cleanup(pool: &SqlitePool, plan_id_str: &str, all: bool) -> Res
 
     let mut removed = 0;
     let mut skipped = 0;
+    let mut branches = 0;
 
     for task in &tasks {
         let dominated_by_policy = if all {
@@ -41,6 +140,7 @@ pub async fn run_cleanup(pool: &SqlitePool, plan_id_str: &str, all: bool) -> Res
             match worktree_manager.remove_worktree(path) {
                 Ok(()) => {
                     println!("  Removed: {} ({})", task.name, wt_path);
+                    task_db::clear_disk_usage(pool, task.id).await?;
                     removed += 1;
                 }
                 Err(e) => {
@@ -51,12 +151,110 @@ pub async fn run_cleanup(pool: &SqlitePool, plan_id_str: &str, all: bool) -> Res
                 }
             }
         }
+
+        if task.merged_at.is_some() {
+            let branch = WorktreeManager::branch_name(&plan.name, &task.name);
+            if worktree_manager.branch_exists(&branch).unwrap_or(false) {
+                match worktree_manager.delete_branch(&branch) {
+                    Ok(()) => {
+                        println!("  Deleted merged branch: {branch}");
+                        branches += 1;
+                    }
+                    Err(e) => eprintln!("  Warning: failed to delete branch {branch}: {e}"),
+                }
+            }
+        }
+    }
+
+    // The template worktree is only needed while tasks may still run.
+    if all || skipped == 0 {
+        let template = worktree_manager.template_path(&plan.name);
+        if template.exists() {
+            match worktree_manager.remove_template(&plan.name) {
+                Ok(()) => {
+                    println!("  Removed: template ({})", template.display());
+                    removed += 1;
+                }
+                Err(e) => eprintln!("  Warning: failed to remove the template worktree: {e}"),
+            }
+        }
     }
 
     // Prune any stale worktree references.
     let _ = worktree_manager.cleanup_stale();
 
-    println!("\nCleanup complete: {removed} worktree(s) removed, {skipped} skipped.");
+    println!(
+        "\nCleanup complete: {removed} worktree(s) removed, {branches} merged branch(es) \
+         deleted, {skipped} skipped."
+    );
 
     Ok(())
 }
+
+/// Remove the worktrees of finished tasks, across all plans, that `policy`
+/// says to: idle past `max_age`, or the least recently active while their
+/// filesystem has less than `min_free` available.
+async fn collect_worktrees(pool: &SqlitePool, policy: WorktreePolicy) -> Result<()> {
+    let now = Utc::now();
+    let mut removed = 0;
+    let mut freed = 0;
+
+    // Least recently active first, so low disk space claims those first.
+    for worktree in task_db::list_finished_worktrees(pool).await? {
+        let path = Path::new(&worktree.worktree_path);
+        if !path.exists() {
+            continue;
+        }
+        let idle = now - worktree.last_active_at;
+        let too_old = policy.max_age.is_some_and(|max_age| idle > max_age);
+        let low_on_space = policy
+            .min_free
+            .is_some_and(|min_free| disk::free_space(path).is_ok_and(|free| free < min_free));
+        if !too_old && !low_on_space {
+            continue;
+        }
+
+        let label = format!("{}/{}", worktree.plan_name, worktree.task_name);
+        let result = WorktreeManager::new(&worktree.project_path, None)
+            .and_then(|manager| manager.remove_worktree(path));
+        if let Err(e) = result {
+            eprintln!("  Warning: failed to remove worktree for {label}: {e}");
+            continue;
+        }
+        task_db::clear_disk_usage(pool, worktree.task_id).await?;
+        let size = worktree.disk_usage_bytes.map_or(0, |b| b.max(0) as u64);
+        println!(
+            "  Removed: {label} ({}, {}, last active {})",
+            worktree.worktree_path,
+            disk::format_size(size),
+            crate::timefmt::relative(worktree.last_active_at, now)
+        );
+        removed += 1;
+        freed += size;
+    }
+
+    println!(
+        "Garbage-collected {removed} worktree(s), freeing about {}.",
+        disk::format_size(freed)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_parse_with_a_unit() {
+        assert_eq!(parse_age("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_age("30m"), Ok(Duration::minutes(30)));
+        assert_eq!(parse_age("2w"), Ok(Duration::weeks(2)));
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("7µ").is_err());
+        assert!(parse_age("µ").is_err());
+        assert!(parse_age("").is_err());
+    }
+}
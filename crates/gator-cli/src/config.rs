@@ -4,13 +4,22 @@
 //! resolution chain: CLI flag > env var > config file > default.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
 
-use gator_core::token::TokenConfig;
+use gator_core::gate::auto_approve::{self, AutoApproveRule};
+use gator_core::harness::PromptConfig;
+use gator_core::notify::NotificationConfig;
+use gator_core::token::{self, TokenConfig, TokenKey};
+use gator_core::worktree::disk;
 use gator_db::config::DbConfig;
 
+use crate::cleanup_cmd::{self, WorktreePolicy};
+use crate::gc_cmd::DEFAULT_AGENT_EVENT_DAYS;
+use crate::timefmt::DisplayZone;
+
 // -----------------------------------------------------------------------
 // Config file types
 // -----------------------------------------------------------------------
@@ -19,17 +28,162 @@ use gator_db::config::DbConfig;
 pub struct ConfigFile {
     pub database: DatabaseSection,
     pub auth: AuthSection,
+    /// Webhook notifications for task and plan state changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationConfig>,
+    /// How timestamps are shown in human-readable output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplaySection>,
+    /// Language of the instructions wrapped around agent prompts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<PromptConfig>,
+    /// OpenTelemetry trace export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<TelemetrySection>,
+    /// Extra secret patterns redacted from stored agent and gate output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionSection>,
+    /// Whether long-running commands check for a newer gator release.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updates: Option<UpdatesSection>,
+    /// What `gator cleanup` prunes besides worktrees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleanup: Option<CleanupSection>,
+    /// Limits shared by every dispatch on this host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dispatch: Option<DispatchSection>,
+    /// Rules that approve low-risk `human_review` tasks without a human.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<GateSection>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatabaseSection {
     pub url: String,
+    /// Optional read replica used by status, report, and dashboard queries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_url: Option<String>,
+    /// Pool size override. When unset, `gator dispatch` sizes the pool from
+    /// `--max-agents`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u32>,
+    /// Seconds to wait for a pooled connection before failing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquire_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthSection {
     /// Hex-encoded token secret (64 hex chars = 32 bytes).
     pub token_secret: String,
+    /// Keep accepting v1 agent tokens (default true). Turn off once no
+    /// attempts started before the v2 upgrade are still running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_v1_tokens: Option<bool>,
+    /// ID of `token_secret`, shown by `gator secret list` (default `default`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_secret_id: Option<String>,
+    /// Sign each plan's agent tokens with a key derived from the secret and
+    /// the plan ID, and hand agents only that derived key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_plan_secrets: Option<bool>,
+    /// Secrets replaced by `gator secret rotate` that still validate tokens,
+    /// newest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_secrets: Vec<PreviousSecret>,
+}
+
+/// A rotated-out token secret (`[[auth.previous_secrets]]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviousSecret {
+    pub id: String,
+    /// Hex-encoded secret.
+    pub secret: String,
+    /// When `gator secret rotate` replaced it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DisplaySection {
+    /// Timezone for displayed timestamps: `local` (default), `UTC`, or an
+    /// IANA name such as `America/New_York`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Screen-reader friendly output (same as `--plain`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plain: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TelemetrySection {
+    /// OTLP/gRPC collector endpoint, e.g. `http://localhost:4317`. Trace
+    /// export is off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` resource attribute (default `gator`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RedactionSection {
+    /// Regexes whose matches are replaced with `[REDACTED]`, on top of the
+    /// built-in detectors for common API key formats.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UpdatesSection {
+    /// Look up the latest GitHub release when `dispatch` or `serve` starts
+    /// (default off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DispatchSection {
+    /// Most agents running at once across every plan dispatched on this
+    /// host, on top of each dispatch's `--max-agents` (default: no limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_agents: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GateSection {
+    /// `[[gate.auto_approve]]` rules, tried in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_approve: Vec<AutoApproveRule>,
+    /// Write a compliance attestation for every code task whose invariants
+    /// pass (default off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CleanupSection {
+    /// Strip stdout and stderr from gate, setup, and warm-up results older
+    /// than this many days, keeping their pass/fail rows (default: keep
+    /// output forever).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate_output_days: Option<u32>,
+    /// Warn in `gator status` when a project's task worktrees take more
+    /// than this much disk, e.g. `50G` (default: no quota).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_quota: Option<String>,
+    /// Remove finished tasks' worktrees idle longer than this, e.g. `7d`
+    /// (default for `gator cleanup --max-age`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<String>,
+    /// Remove finished tasks' worktrees, oldest first, while less than this
+    /// much disk is free, e.g. `20G` (default for `gator cleanup --min-free`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_free: Option<String>,
+    /// `gator gc` deletes agent events older than this many days, except
+    /// token usage (default: 30).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_event_days: Option<u32>,
 }
 
 // -----------------------------------------------------------------------
@@ -105,6 +259,196 @@ pub fn generate_token_secret() -> String {
     hex::encode(bytes)
 }
 
+// -----------------------------------------------------------------------
+// Display timezone
+// -----------------------------------------------------------------------
+
+/// Resolve the timezone for displayed timestamps.
+///
+/// `GATOR_TIMEZONE` env > `display.timezone` in the config file > local time.
+/// Unlike [`GatorConfig::resolve`] this never needs the token secret, so it
+/// also works before `gator init`.
+pub fn resolve_display_zone() -> Result<DisplayZone> {
+    if let Ok(tz) = std::env::var("GATOR_TIMEZONE") {
+        return tz.parse().context("invalid GATOR_TIMEZONE");
+    }
+    match load_config()
+        .ok()
+        .and_then(|cfg| cfg.display)
+        .and_then(|d| d.timezone)
+    {
+        Some(tz) => tz
+            .parse()
+            .context("invalid display.timezone in config file"),
+        None => Ok(DisplayZone::Local),
+    }
+}
+
+/// Install the config file's `redaction.patterns` for this process. The
+/// built-in detectors apply regardless, including before `gator init`.
+pub fn install_redaction() -> Result<()> {
+    let patterns = load_config()
+        .ok()
+        .and_then(|cfg| cfg.redaction)
+        .map(|r| r.patterns)
+        .unwrap_or_default();
+    gator_db::redact::set_patterns(&patterns).context("invalid redaction.patterns in config file")
+}
+
+/// Resolve the agent prompt settings without the rest of the config, so
+/// commands that never touch the database work before `gator init`.
+pub fn resolve_prompt() -> PromptConfig {
+    prompt_config(load_config().ok().as_ref())
+}
+
+/// Prompt locale: env overrides the config file's locale; the override file
+/// comes from the config file only.
+fn prompt_config(file_config: Option<&ConfigFile>) -> PromptConfig {
+    let mut prompt = file_config
+        .and_then(|cfg| cfg.prompt.clone())
+        .unwrap_or_default();
+    if let Ok(locale) = std::env::var("GATOR_PROMPT_LOCALE") {
+        prompt.locale = locale;
+    }
+    prompt
+}
+
+/// Resolve plain output: `--plain` flag > `GATOR_PLAIN` env > `display.plain`
+/// in the config file > off.
+pub fn resolve_plain(flag: bool) -> bool {
+    if flag {
+        return true;
+    }
+    if let Ok(value) = std::env::var("GATOR_PLAIN") {
+        return matches!(value.trim(), "1" | "true" | "yes");
+    }
+    load_config()
+        .ok()
+        .and_then(|cfg| cfg.display)
+        .and_then(|d| d.plain)
+        .unwrap_or(false)
+}
+
+/// Resolve whether to check for a newer release: `GATOR_CHECK_UPDATES` env >
+/// `updates.check` in the config file > off.
+pub fn resolve_update_check() -> bool {
+    if let Ok(value) = std::env::var("GATOR_CHECK_UPDATES") {
+        return matches!(value.trim(), "1" | "true" | "yes");
+    }
+    load_config()
+        .ok()
+        .and_then(|cfg| cfg.updates)
+        .and_then(|u| u.check)
+        .unwrap_or(false)
+}
+
+/// Resolve the machine-wide agent cap: `GATOR_MAX_TOTAL_AGENTS` env >
+/// `dispatch.max_total_agents` in the config file > no limit (`None`).
+pub fn resolve_max_total_agents() -> Result<Option<usize>> {
+    let max = match std::env::var("GATOR_MAX_TOTAL_AGENTS") {
+        Ok(value) => Some(
+            value
+                .trim()
+                .parse()
+                .context("GATOR_MAX_TOTAL_AGENTS is not a number")?,
+        ),
+        Err(_) => load_config()
+            .ok()
+            .and_then(|cfg| cfg.dispatch)
+            .and_then(|d| d.max_total_agents),
+    };
+    if max == Some(0) {
+        bail!("max_total_agents must be at least 1");
+    }
+    Ok(max)
+}
+
+/// Resolve the auto-approval rules for `human_review` gates from
+/// `gate.auto_approve` in the config file (default: none). Invalid rules are
+/// an error.
+pub fn resolve_auto_approve() -> Result<Vec<AutoApproveRule>> {
+    let rules = load_config()
+        .ok()
+        .and_then(|cfg| cfg.gate)
+        .map(|gate| gate.auto_approve)
+        .unwrap_or_default();
+    auto_approve::validate_rules(&rules).context("invalid [[gate.auto_approve]] rule")?;
+    Ok(rules)
+}
+
+/// Resolve whether to attest generated code: `gate.attestation` in the
+/// config file > off.
+pub fn resolve_attestation() -> bool {
+    load_config()
+        .ok()
+        .and_then(|cfg| cfg.gate)
+        .and_then(|gate| gate.attestation)
+        .unwrap_or(false)
+}
+
+/// Resolve the gate output retention: `--gate-output-days` flag >
+/// `cleanup.gate_output_days` in the config file > keep forever (`None`).
+pub fn resolve_gate_output_days(flag: Option<u32>) -> Option<u32> {
+    flag.or_else(|| {
+        load_config()
+            .ok()
+            .and_then(|cfg| cfg.cleanup)
+            .and_then(|c| c.gate_output_days)
+    })
+}
+
+/// Resolve the agent event retention of `gator gc`: `--event-days` flag >
+/// `cleanup.agent_event_days` in the config file > 30 days.
+pub fn resolve_agent_event_days(flag: Option<u32>) -> u32 {
+    flag.or_else(|| {
+        load_config()
+            .ok()
+            .and_then(|cfg| cfg.cleanup)
+            .and_then(|c| c.agent_event_days)
+    })
+    .unwrap_or(DEFAULT_AGENT_EVENT_DAYS)
+}
+
+/// Resolve the worktree garbage-collection policy: the `--max-age` and
+/// `--min-free` flags > `cleanup.max_age` and `cleanup.min_free` in the
+/// config file > off.
+pub fn resolve_worktree_policy(
+    max_age: Option<chrono::Duration>,
+    min_free: Option<u64>,
+) -> Result<WorktreePolicy> {
+    let section = load_config().ok().and_then(|cfg| cfg.cleanup);
+    let section = section.as_ref();
+    let max_age = match max_age {
+        Some(age) => Some(age),
+        None => section
+            .and_then(|c| c.max_age.as_deref())
+            .map(|age| cleanup_cmd::parse_age(age).map_err(anyhow::Error::msg))
+            .transpose()
+            .context("invalid cleanup.max_age in the config file")?,
+    };
+    let min_free = match min_free {
+        Some(bytes) => Some(bytes),
+        None => section
+            .and_then(|c| c.min_free.as_deref())
+            .map(|size| disk::parse_size(size).map_err(anyhow::Error::msg))
+            .transpose()
+            .context("invalid cleanup.min_free in the config file")?,
+    };
+    Ok(WorktreePolicy { max_age, min_free })
+}
+
+/// Resolve the worktree quota: `cleanup.worktree_quota` in the config file
+/// > none.
+pub fn resolve_worktree_quota() -> Result<Option<u64>> {
+    load_config()
+        .ok()
+        .and_then(|cfg| cfg.cleanup)
+        .and_then(|c| c.worktree_quota)
+        .map(|quota| disk::parse_size(&quota).map_err(anyhow::Error::msg))
+        .transpose()
+        .context("invalid cleanup.worktree_quota in the config file")
+}
+
 // -----------------------------------------------------------------------
 // Resolved config
 // -----------------------------------------------------------------------
@@ -113,19 +457,32 @@ pub fn generate_token_secret() -> String {
 #[derive(Debug)]
 pub struct GatorConfig {
     pub db_config: DbConfig,
+    /// Read-only replica, if configured. `None` means reads share the primary.
+    pub read_db_config: Option<DbConfig>,
     pub token_config: TokenConfig,
+    /// Pool size set explicitly in the config file, if any.
+    pub max_connections: Option<u32>,
+    /// Notification settings (config file only; empty when unset).
+    pub notifications: NotificationConfig,
+    /// Agent prompt locale (`GATOR_PROMPT_LOCALE` env > config file > `en`).
+    pub prompt: PromptConfig,
 }
 
 impl GatorConfig {
     /// Resolve configuration using the chain: CLI flag > env var > config file > default.
     ///
     /// - DB path: `cli_db_url` > `GATOR_DATABASE_URL` env > `config_file.database.url` > `DbConfig::from_env()`
+    /// - Read replica: `GATOR_READ_DATABASE_URL` env > `config_file.database.read_url` > none.
+    ///   Ignored when `cli_db_url` is given, since the replica belongs to the
+    ///   configured primary, not to an ad-hoc database.
     /// - Token secret: `GATOR_TOKEN_SECRET` env > `config_file.auth.token_secret` (hex-decoded) > error
+    /// - Token key ID: `GATOR_TOKEN_KEY_ID` env with an env secret, else
+    ///   `config_file.auth.token_secret_id` > `default`
     pub fn resolve(cli_db_url: Option<&str>) -> Result<Self> {
         let file_config = load_config().ok();
 
         // DB path resolution.
-        let db_config = if let Some(path) = cli_db_url {
+        let mut db_config = if let Some(path) = cli_db_url {
             DbConfig::new(path)
         } else if let Ok(path) = std::env::var("GATOR_DATABASE_URL") {
             DbConfig::new(path)
@@ -135,26 +492,123 @@ impl GatorConfig {
             DbConfig::from_env()
         };
 
+        // Read replica resolution.
+        let mut read_db_config = if cli_db_url.is_some() {
+            None
+        } else if let Ok(path) = std::env::var("GATOR_READ_DATABASE_URL") {
+            Some(DbConfig::new(path))
+        } else {
+            file_config
+                .as_ref()
+                .and_then(|cfg| cfg.database.read_url.as_deref())
+                .map(DbConfig::new)
+        };
+
+        // Pool settings (config file only).
+        let max_connections = file_config
+            .as_ref()
+            .and_then(|cfg| cfg.database.max_connections);
+        let acquire_timeout = file_config
+            .as_ref()
+            .and_then(|cfg| cfg.database.acquire_timeout_secs)
+            .map(Duration::from_secs);
+        for cfg in std::iter::once(&mut db_config).chain(read_db_config.as_mut()) {
+            if let Some(n) = max_connections {
+                *cfg = cfg.clone().with_max_connections(n);
+            }
+            if let Some(t) = acquire_timeout {
+                *cfg = cfg.clone().with_acquire_timeout(t);
+            }
+        }
+
         // Token secret resolution.
         let token_config = if let Ok(secret_hex) = std::env::var("GATOR_TOKEN_SECRET") {
             let bytes =
                 hex::decode(&secret_hex).context("GATOR_TOKEN_SECRET env var is not valid hex")?;
-            TokenConfig::new(bytes)
+            let key_id = std::env::var("GATOR_TOKEN_KEY_ID")
+                .unwrap_or_else(|_| token::DEFAULT_KEY_ID.to_string());
+            TokenConfig::new(bytes).with_key_id(key_id)
         } else if let Some(ref cfg) = file_config {
             let bytes = hex::decode(&cfg.auth.token_secret)
                 .context("invalid hex in config file token_secret")?;
-            TokenConfig::new(bytes)
+            let key_id = cfg
+                .auth
+                .token_secret_id
+                .clone()
+                .unwrap_or_else(|| token::DEFAULT_KEY_ID.to_string());
+            TokenConfig::new(bytes).with_key_id(key_id)
         } else {
             bail!(
                 "token secret not found; set GATOR_TOKEN_SECRET or run `gator init` to create a config file"
             );
         };
+        // v1 acceptance: env overrides the config file.
+        let accept_v1 = token::accept_v1_from_env()
+            .or_else(|| {
+                file_config
+                    .as_ref()
+                    .and_then(|cfg| cfg.auth.accept_v1_tokens)
+            })
+            .unwrap_or(true);
+        let mut token_config = token_config.with_accept_v1(accept_v1);
+        // Rotated-out secrets and per-plan derivation come from the config
+        // file only.
+        if let Some(ref cfg) = file_config {
+            let previous = cfg
+                .auth
+                .previous_secrets
+                .iter()
+                .map(|p| {
+                    let secret = hex::decode(&p.secret).with_context(|| {
+                        format!("invalid hex in config file previous secret {:?}", p.id)
+                    })?;
+                    Ok(TokenKey {
+                        id: p.id.clone(),
+                        secret,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            token_config = token_config
+                .with_previous(previous)
+                .with_per_plan(cfg.auth.per_plan_secrets.unwrap_or(false));
+        }
+
+        let prompt = prompt_config(file_config.as_ref());
+
+        let notifications = file_config
+            .and_then(|cfg| cfg.notifications)
+            .unwrap_or_default();
 
         Ok(Self {
             db_config,
+            read_db_config,
             token_config,
+            max_connections,
+            notifications,
+            prompt,
         })
     }
+
+    /// Size the primary pool for a fleet of `max_agents` concurrent agents.
+    ///
+    /// An explicit `database.max_connections` always wins; if it is smaller
+    /// than the recommended size a warning is printed, since agents will
+    /// contend for connections.
+    pub fn size_pool_for_agents(&mut self, max_agents: usize) {
+        let recommended = gator_db::pool::recommended_max_connections(max_agents);
+        match self.max_connections {
+            Some(n) if n < recommended => {
+                eprintln!(
+                    "Warning: database.max_connections = {n} is below the recommended {recommended} \
+                     for {max_agents} agents; expect pool acquire timeouts under load."
+                );
+            }
+            Some(_) => {}
+            None => {
+                self.db_config = self.db_config.clone().with_max_connections(recommended);
+            }
+        }
+    }
 }
 
 // -----------------------------------------------------------------------
@@ -165,7 +619,7 @@ impl GatorConfig {
 mod tests {
     use super::*;
 
-    fn lock_env() -> std::sync::MutexGuard<'static, ()> {
+    fn lock_env() -> tokio::sync::MutexGuard<'static, ()> {
         crate::test_util::lock_env()
     }
 
@@ -197,10 +651,26 @@ mod tests {
         let original = ConfigFile {
             database: DatabaseSection {
                 url: "postgresql://testhost:5432/testdb".to_string(),
+                read_url: None,
+                max_connections: None,
+                acquire_timeout_secs: None,
             },
             auth: AuthSection {
                 token_secret: "aa".repeat(32),
+                accept_v1_tokens: None,
+                token_secret_id: None,
+                per_plan_secrets: None,
+                previous_secrets: Vec::new(),
             },
+            notifications: None,
+            display: None,
+            prompt: None,
+            telemetry: None,
+            redaction: None,
+            updates: None,
+            cleanup: None,
+            dispatch: None,
+            gate: None,
         };
 
         std::fs::create_dir_all(&dir).unwrap();
@@ -304,6 +774,165 @@ mod tests {
         unsafe { std::env::remove_var("GATOR_TOKEN_SECRET") };
     }
 
+    #[test]
+    fn resolve_read_replica_from_env() {
+        let _lock = lock_env();
+
+        unsafe { std::env::set_var("GATOR_DATABASE_URL", "/tmp/env.db") };
+        unsafe { std::env::set_var("GATOR_READ_DATABASE_URL", "/tmp/replica.db") };
+        unsafe {
+            std::env::set_var(
+                "GATOR_TOKEN_SECRET",
+                "aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55",
+            )
+        };
+
+        let config = GatorConfig::resolve(None).unwrap();
+        let read = config.read_db_config.expect("replica should be resolved");
+        assert_eq!(read.database_url(), "sqlite:///tmp/replica.db?mode=rwc");
+
+        // An explicit --database-url bypasses the replica.
+        let config = GatorConfig::resolve(Some("/tmp/cli.db")).unwrap();
+        assert!(config.read_db_config.is_none());
+
+        unsafe { std::env::remove_var("GATOR_DATABASE_URL") };
+        unsafe { std::env::remove_var("GATOR_READ_DATABASE_URL") };
+        unsafe { std::env::remove_var("GATOR_TOKEN_SECRET") };
+    }
+
+    #[test]
+    fn config_file_without_read_url_parses() {
+        let contents = r#"
+[database]
+url = "/tmp/gator.db"
+
+[auth]
+token_secret = "aa"
+"#;
+        let cfg: ConfigFile = toml::from_str(contents).unwrap();
+        assert!(cfg.database.read_url.is_none());
+        assert!(cfg.database.max_connections.is_none());
+        assert!(cfg.database.acquire_timeout_secs.is_none());
+        let out = toml::to_string_pretty(&cfg).unwrap();
+        assert!(
+            !out.contains("read_url"),
+            "unset read_url should be omitted"
+        );
+        assert!(cfg.notifications.is_none());
+        assert!(!out.contains("notifications"));
+    }
+
+    #[test]
+    fn config_file_parses_notifications_section() {
+        let contents = r#"
+[database]
+url = "/tmp/gator.db"
+
+[auth]
+token_secret = "aa"
+
+[[notifications.webhooks]]
+url = "https://hooks.example.com/gator"
+events = ["task_escalated", "plan_completed"]
+"#;
+        let cfg: ConfigFile = toml::from_str(contents).unwrap();
+        let notifications = cfg.notifications.expect("notifications section");
+        assert_eq!(notifications.webhooks.len(), 1);
+        assert_eq!(
+            notifications.webhooks[0].url,
+            "https://hooks.example.com/gator"
+        );
+        assert_eq!(
+            notifications.max_attempts,
+            gator_core::notify::DEFAULT_MAX_ATTEMPTS
+        );
+    }
+
+    #[test]
+    fn display_timezone_env_overrides_config() {
+        let _lock = lock_env();
+
+        unsafe { std::env::set_var("GATOR_TIMEZONE", "Asia/Tokyo") };
+        let zone = resolve_display_zone();
+        unsafe { std::env::set_var("GATOR_TIMEZONE", "Nowhere/Special") };
+        let bad = resolve_display_zone();
+        unsafe { std::env::remove_var("GATOR_TIMEZONE") };
+
+        assert_eq!(zone.unwrap(), DisplayZone::Named(chrono_tz::Asia::Tokyo));
+        assert!(bad.is_err());
+
+        let cfg: ConfigFile = toml::from_str(
+            "[database]\nurl = \"/tmp/gator.db\"\n[auth]\ntoken_secret = \"aa\"\n[display]\ntimezone = \"UTC\"\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.display.unwrap().timezone.as_deref(), Some("UTC"));
+    }
+
+    #[test]
+    fn plain_flag_and_env() {
+        let _lock = lock_env();
+
+        unsafe { std::env::set_var("GATOR_PLAIN", "0") };
+        let flag = resolve_plain(true);
+        let env_off = resolve_plain(false);
+        unsafe { std::env::set_var("GATOR_PLAIN", "1") };
+        let env_on = resolve_plain(false);
+        unsafe { std::env::remove_var("GATOR_PLAIN") };
+
+        assert!(flag);
+        assert!(!env_off);
+        assert!(env_on);
+    }
+
+    #[test]
+    fn size_pool_for_agents_scales_default_pool() {
+        let _lock = lock_env();
+
+        unsafe {
+            std::env::set_var(
+                "GATOR_TOKEN_SECRET",
+                "aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55",
+            )
+        };
+
+        let mut config = GatorConfig::resolve(Some("/tmp/cli.db")).unwrap();
+        config.max_connections = None;
+        config.size_pool_for_agents(16);
+        assert_eq!(config.db_config.max_connections, 34);
+
+        // An explicit setting is respected even when undersized.
+        let mut config = GatorConfig::resolve(Some("/tmp/cli.db")).unwrap();
+        config.max_connections = Some(8);
+        config.db_config = config.db_config.clone().with_max_connections(8);
+        config.size_pool_for_agents(16);
+        assert_eq!(config.db_config.max_connections, 8);
+
+        unsafe { std::env::remove_var("GATOR_TOKEN_SECRET") };
+    }
+
+    #[test]
+    fn accept_v1_env_disables_legacy_tokens() {
+        let _lock = lock_env();
+
+        unsafe {
+            std::env::set_var(
+                "GATOR_TOKEN_SECRET",
+                "aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55aa55",
+            );
+            std::env::remove_var("GATOR_TOKEN_ACCEPT_V1");
+        };
+        let default = GatorConfig::resolve(Some("/tmp/cli.db")).unwrap();
+        unsafe { std::env::set_var("GATOR_TOKEN_ACCEPT_V1", "0") };
+        let strict = GatorConfig::resolve(Some("/tmp/cli.db")).unwrap();
+        unsafe {
+            std::env::remove_var("GATOR_TOKEN_ACCEPT_V1");
+            std::env::remove_var("GATOR_TOKEN_SECRET");
+        };
+
+        assert!(default.token_config.accept_v1);
+        assert!(!strict.token_config.accept_v1);
+    }
+
     #[test]
     fn resolve_errors_when_no_token_secret() {
         let _lock = lock_env();
@@ -0,0 +1,140 @@
+//! Plumbing shared by the commands that run plans unattended (`gator triage
+//! run`, `gator review run`, `gator fanout`): the agent fleet that runs their
+//! plans, graceful shutdown, and pacing between passes.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use gator_core::harness::{ClaudeCodeAdapter, HarnessRegistry, PromptConfig, PromptScaffold};
+use gator_core::isolation::Isolation;
+use gator_core::notify::NotificationConfig;
+use gator_core::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, OrchestratorConfig, OrchestratorResult, WatchdogConfig, run_orchestrator,
+};
+use gator_core::token::TokenConfig;
+
+/// Harness, isolation, and orchestrator settings for running plans of one
+/// repository.
+pub struct Fleet {
+    registry: Arc<HarnessRegistry>,
+    isolation: Arc<dyn Isolation>,
+    config: OrchestratorConfig,
+    token_config: TokenConfig,
+}
+
+impl Fleet {
+    pub fn new(
+        isolation: Arc<dyn Isolation>,
+        max_agents: usize,
+        task_timeout: Duration,
+        token_config: &TokenConfig,
+        notifications: &NotificationConfig,
+        prompt: &PromptConfig,
+    ) -> Result<Self> {
+        let scaffold = PromptScaffold::resolve(prompt)?;
+        let mut registry = HarnessRegistry::new();
+        registry.register(ClaudeCodeAdapter::new().with_scaffold(scaffold));
+        Ok(Self {
+            registry: Arc::new(registry),
+            isolation,
+            config: OrchestratorConfig {
+                max_agents,
+                max_total_agents: crate::config::resolve_max_total_agents()?,
+                task_timeout,
+                notifications: notifications.clone(),
+                watchdog: WatchdogConfig::default(),
+                drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+                prompt_budget: prompt.budget()?,
+                gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+                gate_cache: true,
+                selection: None,
+                auto_approve: crate::config::resolve_auto_approve()?,
+                attestation: crate::config::resolve_attestation(),
+            },
+            token_config: token_config.clone(),
+        })
+    }
+
+    /// Run a plan's tasks and report how it ended.
+    pub async fn dispatch(
+        &self,
+        pool: &SqlitePool,
+        plan_id: Uuid,
+        plan_name: &str,
+        cancel: CancellationToken,
+    ) -> Result<OrchestratorResult> {
+        let result = run_orchestrator(
+            pool,
+            plan_id,
+            &self.registry,
+            &self.isolation,
+            &self.token_config,
+            &self.config,
+            cancel,
+        )
+        .await?;
+        match &result {
+            OrchestratorResult::Completed => println!("Plan {plan_name} completed."),
+            OrchestratorResult::Failed { failed_tasks } => println!(
+                "Plan {plan_name} finished with {} escalated task(s).",
+                failed_tasks.len()
+            ),
+            OrchestratorResult::HumanRequired {
+                tasks_awaiting_review,
+            } => println!(
+                "Plan {plan_name}: {} task(s) awaiting review.",
+                tasks_awaiting_review.len()
+            ),
+            OrchestratorResult::BudgetExceeded { used, budget } => {
+                println!("Plan {plan_name} stopped: token budget exceeded ({used}/{budget}).")
+            }
+            OrchestratorResult::Interrupted => {
+                println!("Plan {plan_name} interrupted.")
+            }
+            OrchestratorResult::SelectionCompleted { remaining } => {
+                println!("Plan {plan_name}: selected tasks passed, {remaining} left.")
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// A token cancelled on the first Ctrl+C, so the current pass stops after
+/// in-flight agents drain. The second Ctrl+C force-exits.
+pub fn shutdown_token() -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let cancel_clone = cancel.clone();
+    let got_first_signal = Arc::new(AtomicBool::new(false));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::signal::ctrl_c().await.ok();
+            if got_first_signal.swap(true, Ordering::SeqCst) {
+                eprintln!("\nForce exit.");
+                std::process::exit(130);
+            }
+            eprintln!("\nShutting down gracefully (Ctrl+C again to force)...");
+            cancel_clone.cancel();
+        }
+    });
+
+    cancel
+}
+
+/// Sleep until the next pass is due. Returns `false` if shutdown was
+/// requested instead.
+pub async fn wait_for_next_pass(cancel: &CancellationToken, interval: Duration) -> bool {
+    if cancel.is_cancelled() {
+        return false;
+    }
+    tokio::select! {
+        _ = cancel.cancelled() => false,
+        _ = tokio::time::sleep(interval) => true,
+    }
+}
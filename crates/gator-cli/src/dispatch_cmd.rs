@@ -8,23 +8,70 @@ use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 use tokio_util::sync::CancellationToken;
 
-use gator_core::harness::{ClaudeCodeAdapter, HarnessRegistry};
+use gator_core::harness::{ClaudeCodeAdapter, HarnessRegistry, PromptConfig, PromptScaffold};
 use gator_core::isolation;
-use gator_core::orchestrator::{OrchestratorConfig, OrchestratorResult, run_orchestrator};
+use gator_core::notify::NotificationConfig;
+use gator_core::orchestrator::{
+    OrchestratorConfig, OrchestratorResult, TaskSelection, WatchdogConfig, preview_dispatch,
+    resolve_selection, run_orchestrator,
+};
 use gator_core::token::TokenConfig;
 use gator_db::queries::plans as plan_db;
 
+/// Options for the dispatch command.
+pub struct DispatchOptions {
+    /// Maximum number of concurrent agents.
+    pub max_agents: usize,
+    /// Per-task timeout.
+    pub task_timeout: Duration,
+    /// How long to wait for in-flight agents after Ctrl+C.
+    pub drain_timeout: Duration,
+    /// Stall detection for the orchestrator loop.
+    pub watchdog: WatchdogConfig,
+    /// How many invariants each gate runs at once.
+    pub gate_parallelism: usize,
+    /// Whether gates reuse results from earlier attempts with an identical
+    /// worktree.
+    pub gate_cache: bool,
+    /// The slice of the plan to run; empty runs all of it.
+    pub selection: TaskSelection,
+}
+
+/// The harnesses dispatch can run tasks with.
+pub(crate) fn harness_registry(scaffold: PromptScaffold) -> HarnessRegistry {
+    let mut registry = HarnessRegistry::new();
+    registry.register(ClaudeCodeAdapter::new().with_scaffold(scaffold));
+    registry
+}
+
 /// Run the dispatch command.
 pub async fn run_dispatch(
     pool: &SqlitePool,
     plan_id_str: &str,
-    max_agents: usize,
-    timeout_secs: u64,
+    options: DispatchOptions,
     token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
 ) -> Result<()> {
+    let DispatchOptions {
+        max_agents,
+        task_timeout,
+        drain_timeout,
+        watchdog,
+        gate_parallelism,
+        gate_cache,
+        selection,
+    } = options;
+
     // Parse plan ID (accepts UUID or path to plan.toml).
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
+    // Resolve the prompt scaffold up front so a bad locale fails fast.
+    let scaffold = PromptScaffold::resolve(prompt)?;
+    let max_total_agents = crate::config::resolve_max_total_agents()?;
+    let auto_approve = crate::config::resolve_auto_approve()?;
+    let attestation = crate::config::resolve_attestation();
+
     // Load plan to get project_path.
     let plan = plan_db::get_plan(pool, plan_id)
         .await?
@@ -32,24 +79,88 @@ pub async fn run_dispatch(
 
     println!("Dispatching plan: {} ({})", plan.name, plan.id);
     println!("  Max agents: {max_agents}");
-    println!("  Task timeout: {timeout_secs}s");
+    if let Some(max) = max_total_agents {
+        println!("  Max agents on this host: {max}");
+    }
+    println!("  Task timeout: {}s", task_timeout.as_secs());
+    println!("  Drain timeout: {}s", drain_timeout.as_secs());
+    println!("  Gate parallelism: {gate_parallelism}");
+    if !gate_cache {
+        println!("  Gate cache: off");
+    }
+    if !auto_approve.is_empty() {
+        let names: Vec<&str> = auto_approve.iter().map(|r| r.name.as_str()).collect();
+        println!("  Auto-approval rules: {}", names.join(", "));
+    }
+    if attestation {
+        println!("  Attestation: on");
+    }
+    match watchdog.stall_after {
+        Some(after) => println!(
+            "  Stall watchdog: {}s{}",
+            after.as_secs(),
+            if watchdog.self_heal {
+                " (self-heal)"
+            } else {
+                ""
+            }
+        ),
+        None => println!("  Stall watchdog: off"),
+    }
+    println!(
+        "  DB pool: {} connections",
+        gator_db::pool::pool_stats(pool).max_connections
+    );
+    if !notifications.webhooks.is_empty() {
+        println!("  Webhooks: {}", notifications.webhooks.len());
+    }
+    if notifications.desktop {
+        println!("  Desktop notifications: on");
+    }
+    if prompt.locale != gator_core::harness::scaffold::DEFAULT_LOCALE
+        || prompt.scaffold_file.is_some()
+    {
+        println!("  Prompt locale: {}", prompt.locale);
+    }
+
+    // Resolve the slice to run before touching anything, so an open
+    // dependency fails fast.
+    let selection = if selection.is_empty() {
+        None
+    } else {
+        let selected = resolve_selection(pool, plan_id, &selection).await?;
+        println!("  Selected tasks: {} of the plan", selected.len());
+        Some(selected)
+    };
+
+    // Warn about tasks likely to produce conflicting branches. Those that
+    // declare overlapping `paths` are run one at a time by the orchestrator.
+    let overlaps = gator_core::plan::analyze_plan_overlaps(pool, plan_id).await?;
+    for line in crate::plan_cmds::overlap_conflict_lines(&overlaps) {
+        println!("  Overlap: {line}");
+    }
 
     // Set up harness registry.
-    let mut registry = HarnessRegistry::new();
-    registry.register(ClaudeCodeAdapter::new());
-    let registry = Arc::new(registry);
+    let registry = Arc::new(harness_registry(scaffold));
+    crate::banner::log_dispatch_banner(pool, &registry, &plan, max_agents).await;
 
     // Set up isolation backend based on plan configuration.
-    let isolation = isolation::create_isolation(
-        &plan.isolation,
-        std::path::Path::new(&plan.project_path),
-        plan.container_image.as_deref(),
-    )?;
+    let isolation = isolation::create_plan_isolation(&plan)?;
 
     // Build config.
     let config = OrchestratorConfig {
         max_agents,
-        task_timeout: Duration::from_secs(timeout_secs),
+        max_total_agents,
+        task_timeout,
+        notifications: notifications.clone(),
+        watchdog,
+        drain_timeout,
+        prompt_budget: prompt.budget()?,
+        gate_parallelism,
+        gate_cache,
+        selection,
+        auto_approve,
+        attestation,
     };
 
     // Set up graceful shutdown: first signal cancels, second force-exits.
@@ -111,13 +222,92 @@ pub async fn run_dispatch(
         }
         OrchestratorResult::BudgetExceeded { used, budget } => {
             println!("\nPlan stopped: token budget exceeded ({used}/{budget} tokens used).");
+            println!("Resume with a higher budget: gator resume {plan_id} --budget <tokens>");
             std::process::exit(3);
         }
         OrchestratorResult::Interrupted => {
-            println!("\nPlan interrupted by signal. In-flight tasks drained.");
+            println!("\nPlan interrupted by signal; it is left approved and can be resumed.");
             println!("Re-run `gator dispatch {plan_id}` to resume.");
             std::process::exit(130);
         }
+        OrchestratorResult::SelectionCompleted { remaining } => {
+            println!("\nSelected tasks passed. {remaining} task(s) of the plan have not.");
+            println!("Run them with `gator dispatch {plan_id}`.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `gator dispatch --dry-run`: print the waves the plan would run in
+/// with `max_agents` agents, token estimates, and scheduling issues,
+/// without creating worktrees or spawning agents. `usd_per_mtok` prices
+/// the estimate.
+pub async fn run_dry_run(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    max_agents: usize,
+    usd_per_mtok: Option<f64>,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+    let registry = harness_registry(PromptScaffold::default());
+    let preview = preview_dispatch(pool, plan_id, max_agents, &registry.list()).await?;
+
+    println!(
+        "Dry run of plan: {} ({})",
+        preview.plan.name, preview.plan.id
+    );
+    println!("  Max agents: {max_agents}");
+    if preview.passed > 0 {
+        println!("  Already passed: {} task(s)", preview.passed);
+    }
+    println!();
+
+    if preview.waves.is_empty() {
+        println!("No tasks would run.");
+    }
+    for (i, wave) in preview.waves.iter().enumerate() {
+        println!("Wave {} ({} task(s)):", i + 1, wave.len());
+        for task in wave {
+            println!("  - {} (~{} prompt tokens)", task.name, task.prompt_tokens);
+        }
+    }
+    println!();
+
+    println!("Tokens:");
+    println!(
+        "  Prompts: ~{} across {} task(s)",
+        preview.prompt_tokens(),
+        preview.task_count()
+    );
+    match preview.estimated_tokens() {
+        Some(estimate) => {
+            println!(
+                "  Estimated usage: ~{estimate} ({} per attempt, averaged over {} past attempt(s) in this project)",
+                preview.average_attempt_tokens, preview.past_attempts
+            );
+            if let Some(price) = usd_per_mtok {
+                println!(
+                    "  Estimated cost: ${:.2} at ${price} per million tokens",
+                    estimate as f64 / 1_000_000.0 * price
+                );
+            }
+        }
+        None => println!("  Estimated usage: unknown (no past attempts in this project)"),
+    }
+    match preview.plan.token_budget {
+        Some(budget) => println!("  Budget: {} of {budget} used", preview.used_tokens),
+        None => println!("  Budget: unlimited ({} used)", preview.used_tokens),
+    }
+    println!();
+
+    if preview.issues.is_empty() {
+        println!("No scheduling issues found.");
+    } else {
+        println!("Scheduling issues:");
+        for issue in &preview.issues {
+            println!("  - {issue}");
+        }
     }
 
     Ok(())
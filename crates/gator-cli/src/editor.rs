@@ -0,0 +1,30 @@
+//! Editing text in the operator's editor.
+
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+/// Open `text` in `$VISUAL` or `$EDITOR` (falling back to `vi`) and return
+/// the saved result. `name` names the temporary file, so its extension can
+/// select the editor's syntax mode.
+pub(crate) fn edit_text(name: &str, text: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("empty editor command")?;
+
+    let path = std::env::temp_dir().join(format!("gator-{}-{name}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let status = status.with_context(|| format!("failed to run editor {editor:?}"))?;
+    if !status.success() {
+        bail!("editor {editor:?} exited with {status}");
+    }
+    edited.with_context(|| format!("failed to read {}", path.display()))
+}
@@ -0,0 +1,104 @@
+//! `gator eval` commands: the golden-task regression suite.
+//!
+//! Each case of a suite runs in its own repository and database under the
+//! eval working directory, never touching the regular gator database. The
+//! command fails when any case does, so it can guard releases in CI.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use gator_core::eval::{self, MOCK_HARNESS, ScriptedHarness};
+use gator_core::harness::{ClaudeCodeAdapter, PromptConfig, PromptScaffold};
+use gator_core::token::TokenConfig;
+
+use crate::EvalCommands;
+use crate::daemon;
+
+/// Run a `gator eval` subcommand.
+pub async fn run_eval(command: EvalCommands, prompt: &PromptConfig) -> Result<()> {
+    match command {
+        EvalCommands::Run {
+            suite,
+            workdir,
+            harness,
+            json,
+        } => {
+            cmd_run(
+                &suite,
+                Path::new(&workdir),
+                harness,
+                json.as_deref(),
+                prompt,
+            )
+            .await
+        }
+    }
+}
+
+async fn cmd_run(
+    suite_file: &str,
+    workdir: &Path,
+    harness: Option<String>,
+    json: Option<&str>,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    let content = std::fs::read_to_string(suite_file)
+        .with_context(|| format!("failed to read {suite_file}"))?;
+    let mut suite = eval::parse_suite(&content)?;
+    if let Some(harness) = harness {
+        suite.suite.harness = harness;
+    }
+    let harness = suite.suite.harness.clone();
+    let scaffold = match harness.as_str() {
+        MOCK_HARNESS => None,
+        "claude-code" => Some(PromptScaffold::resolve(prompt)?),
+        other => bail!("unknown harness {other:?} (expected \"mock\" or \"claude-code\")"),
+    };
+    std::fs::create_dir_all(workdir)
+        .with_context(|| format!("failed to create {}", workdir.display()))?;
+    let workdir = workdir
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", workdir.display()))?;
+
+    println!(
+        "Running {} case(s) of suite {} with the {harness} harness...",
+        suite.cases.len(),
+        suite.suite.name
+    );
+    // Agents get the signing secret, so cases never see the real one.
+    let token_config = TokenConfig::new(hex::decode(crate::config::generate_token_secret())?);
+    let cancel = daemon::shutdown_token();
+    let scorecard = eval::run_suite(
+        &suite,
+        &workdir,
+        &|case| {
+            Ok(match &scaffold {
+                None => Box::new(ScriptedHarness::new(case.mock.clone())),
+                Some(scaffold) => {
+                    Box::new(ClaudeCodeAdapter::new().with_scaffold(scaffold.clone()))
+                }
+            })
+        },
+        &token_config,
+        &cancel,
+    )
+    .await;
+
+    println!();
+    print!("{}", scorecard.render());
+    if let Some(path) = json {
+        let body = serde_json::to_string_pretty(&scorecard)?;
+        std::fs::write(path, body + "\n").with_context(|| format!("failed to write {path}"))?;
+        println!("Scorecard written to {path}");
+    }
+
+    let failed = scorecard.cases.len() - scorecard.passed();
+    if cancel.is_cancelled() {
+        bail!("eval interrupted after {} case(s)", scorecard.cases.len());
+    }
+    if failed > 0 {
+        bail!("{failed} of {} eval case(s) failed", scorecard.cases.len());
+    }
+    Ok(())
+}
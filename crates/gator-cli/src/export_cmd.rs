@@ -1,6 +1,10 @@
 use anyhow::Context;
 use sqlx::SqlitePool;
 
+use gator_db::queries::change_log;
+
+use crate::CdcCommands;
+
 /// Export plan/task data as CSV.
 pub async fn run_export_csv(
     pool: &SqlitePool,
@@ -45,7 +49,13 @@ pub async fn run_export_csv(
         writeln!(
             writer,
             "{},{},{},{},{},{}",
-            row.id, row.plan_id, row.name, row.status, row.attempt, row.created_at,
+            row.id,
+            row.plan_id,
+            row.name,
+            row.status,
+            row.attempt,
+            row.created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
         )?;
     }
 
@@ -56,6 +66,88 @@ pub async fn run_export_csv(
     Ok(())
 }
 
+/// Run a `gator export cdc` subcommand.
+///
+/// A downstream job syncs incrementally by remembering the last `seq` it
+/// read, passing it as `--after` next time, and pruning what it has synced.
+pub async fn run_cdc(pool: &SqlitePool, command: CdcCommands) -> anyhow::Result<()> {
+    match command {
+        CdcCommands::Enable => {
+            change_log::set_enabled(pool, true).await?;
+            println!("Change logging enabled.");
+        }
+        CdcCommands::Disable => {
+            change_log::set_enabled(pool, false).await?;
+            println!("Change logging disabled; logged changes are kept.");
+        }
+        CdcCommands::Status => {
+            let status = change_log::status(pool).await?;
+            println!(
+                "Change logging: {}",
+                if status.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            );
+            match (status.first_seq, status.last_seq) {
+                (Some(first), Some(last)) => {
+                    println!("  Entries:     {} (seq {first}-{last})", status.entries)
+                }
+                _ => println!("  Entries:     0"),
+            }
+        }
+        CdcCommands::Changes {
+            after,
+            limit,
+            output,
+        } => export_changes(pool, after, limit, output.as_deref()).await?,
+        CdcCommands::Prune { through } => {
+            let deleted = change_log::prune_changes(pool, through).await?;
+            println!("Pruned {deleted} change(s) through seq {through}.");
+        }
+    }
+    Ok(())
+}
+
+/// Write changes after `after` as JSON lines.
+async fn export_changes(
+    pool: &SqlitePool,
+    after: i64,
+    limit: i64,
+    output: Option<&str>,
+) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let changes = change_log::list_changes(pool, after, limit).await?;
+
+    let mut writer: Box<dyn Write> = if let Some(path) = output {
+        Box::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("cannot create output file: {path}"))?,
+        )
+    } else {
+        Box::new(std::io::stdout().lock())
+    };
+    for change in &changes {
+        writeln!(writer, "{}", serde_json::to_string(change)?)?;
+    }
+    writer.flush()?;
+
+    if let Some(path) = output {
+        match changes.last() {
+            Some(last) => println!(
+                "Exported {} change(s) to {path} (last seq {})",
+                changes.len(),
+                last.seq
+            ),
+            None => println!("No changes after seq {after}."),
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(sqlx::FromRow)]
 struct TaskRow {
     id: uuid::Uuid,
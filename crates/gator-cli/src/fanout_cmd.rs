@@ -0,0 +1,418 @@
+//! `gator fanout` command: run one templated plan across many repositories.
+//!
+//! Each repository in the list is cloned (or fetched) under the work
+//! directory, gets its own instance of the plan template, and runs it. For
+//! every plan that completes, the passed task branches are merged onto a
+//! `gator-fanout/<plan>` branch, pushed, and opened as a pull request. The
+//! run ends with one report across all repositories.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use futures::StreamExt;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use gator_core::forge;
+use gator_core::harness::PromptConfig;
+use gator_core::isolation;
+use gator_core::notify::NotificationConfig;
+use gator_core::plan::{
+    FanoutRepo, RepoSource, create_plan_from_toml, instantiate_template, parse_repo_list,
+};
+use gator_core::presets;
+use gator_core::token::TokenConfig;
+use gator_core::worktree::{MergeResult, WorktreeManager, sanitize_ref_component};
+use gator_db::models::{Plan, PlanStatus, TaskKind, TaskStatus};
+use gator_db::queries::agent_events;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use crate::daemon::{self, Fleet};
+
+/// Options for the fanout command.
+pub struct FanoutOptions {
+    /// File listing the repositories, one per line.
+    pub repos_file: String,
+    /// Plan template (TOML with placeholders).
+    pub plan_file: String,
+    /// Directory repositories are cloned into.
+    pub workdir: PathBuf,
+    /// How many repositories run at once.
+    pub parallel: usize,
+    /// Maximum concurrent agents per repository.
+    pub max_agents: usize,
+    /// Per-task timeout.
+    pub task_timeout: Duration,
+    /// Remote to push fan-out branches to.
+    pub remote: String,
+    /// Skip pushing branches and opening PRs.
+    pub no_pr: bool,
+    /// Open PRs as drafts.
+    pub draft: bool,
+    /// Also write the report as Markdown to this file.
+    pub report: Option<String>,
+}
+
+/// How one repository fared.
+#[derive(Debug, Clone, Default)]
+pub struct RepoOutcome {
+    /// Repository as written in the list.
+    pub repo: String,
+    pub plan: Option<(Uuid, String)>,
+    /// Final plan status, or `error` if the repository never got that far.
+    pub status: String,
+    pub passed: usize,
+    pub total: usize,
+    pub tokens: i64,
+    pub pr_url: Option<String>,
+    /// Set when the repository could not be cloned, planned, or merged, or
+    /// its PR could not be opened; also notes completed plans with nothing
+    /// to merge.
+    pub error: Option<String>,
+}
+
+/// Run the fanout command.
+pub async fn run_fanout(
+    pool: &SqlitePool,
+    options: &FanoutOptions,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    let list = std::fs::read_to_string(&options.repos_file)
+        .with_context(|| format!("failed to read {}", options.repos_file))?;
+    let repos = parse_repo_list(&list).with_context(|| format!("in {}", options.repos_file))?;
+    let template = std::fs::read_to_string(&options.plan_file)
+        .with_context(|| format!("failed to read {}", options.plan_file))?;
+    // Fail on template errors before cloning anything.
+    instantiate_template(&template, &repos[0], "main")?;
+
+    println!(
+        "Fanning out {} across {} repositories",
+        options.plan_file,
+        repos.len()
+    );
+    println!("  Workdir:     {}", options.workdir.display());
+    println!("  Parallel:    {}", options.parallel);
+    println!("  Max agents:  {} per repository", options.max_agents);
+
+    let cancel = daemon::shutdown_token();
+    let run = FanoutRun {
+        pool,
+        options,
+        template: &template,
+        token_config,
+        notifications,
+        prompt,
+    };
+
+    let mut outcomes: Vec<(usize, RepoOutcome)> = futures::stream::iter(repos.iter().enumerate())
+        .map(|(i, repo)| {
+            let run = &run;
+            let cancel = cancel.clone();
+            async move { (i, run.run_repo(repo, cancel).await) }
+        })
+        .buffer_unordered(options.parallel.max(1))
+        .collect()
+        .await;
+    outcomes.sort_by_key(|(i, _)| *i);
+    let outcomes: Vec<RepoOutcome> = outcomes.into_iter().map(|(_, o)| o).collect();
+
+    println!();
+    print_report(&outcomes);
+    if let Some(path) = &options.report {
+        std::fs::write(path, render_report_markdown(&outcomes))
+            .with_context(|| format!("failed to write report to {path}"))?;
+        println!("\nReport written to {path}");
+    }
+
+    let failed = outcomes
+        .iter()
+        .filter(|o| o.status != PlanStatus::Completed.to_string())
+        .count();
+    if failed > 0 {
+        bail!(
+            "{failed} of {} repositories did not complete",
+            outcomes.len()
+        );
+    }
+    Ok(())
+}
+
+/// Shared state for running each repository.
+struct FanoutRun<'a> {
+    pool: &'a SqlitePool,
+    options: &'a FanoutOptions,
+    template: &'a str,
+    token_config: &'a TokenConfig,
+    notifications: &'a NotificationConfig,
+    prompt: &'a PromptConfig,
+}
+
+impl FanoutRun<'_> {
+    /// Run the template in one repository. Never fails: problems are
+    /// recorded in the outcome so the other repositories carry on.
+    async fn run_repo(&self, repo: &FanoutRepo, cancel: CancellationToken) -> RepoOutcome {
+        let mut outcome = RepoOutcome {
+            repo: repo.spec.clone(),
+            status: "error".to_string(),
+            ..Default::default()
+        };
+        if let Err(e) = self.try_run_repo(repo, cancel, &mut outcome).await {
+            eprintln!("[{}] {e:#}", repo.spec);
+            outcome.error = Some(format!("{e:#}"));
+        }
+        outcome
+    }
+
+    async fn try_run_repo(
+        &self,
+        repo: &FanoutRepo,
+        cancel: CancellationToken,
+        outcome: &mut RepoOutcome,
+    ) -> Result<()> {
+        let path = repo.checkout_path(&self.options.workdir);
+        forge::clone_or_fetch(&repo.source, &path)?;
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("{} does not exist", path.display()))?;
+        let base_branch = presets::detect_base_branch(&path);
+        let manager = WorktreeManager::new(&path, None).map_err(|e| anyhow::anyhow!("{e}"))?;
+        if !matches!(repo.source, RepoSource::Local(_)) {
+            // Start gator-managed clones from the remote's latest commit.
+            manager
+                .checkout_new_branch(&base_branch, &format!("origin/{base_branch}"))
+                .map_err(|e| anyhow::anyhow!("{e}"))?;
+        }
+
+        let plan_toml = instantiate_template(self.template, repo, &base_branch)?;
+        let plan = create_plan_from_toml(self.pool, &plan_toml, &path.to_string_lossy()).await?;
+        let plan = plan_db::approve_plan(self.pool, plan.id).await?;
+        outcome.plan = Some((plan.id, plan.name.clone()));
+        println!("[{}] Created plan {} ({})", repo.spec, plan.name, plan.id);
+
+        let fleet = Fleet::new(
+            isolation::create_plan_isolation(&plan)?,
+            self.options.max_agents,
+            self.options.task_timeout,
+            self.token_config,
+            self.notifications,
+            self.prompt,
+        )?;
+        fleet
+            .dispatch(self.pool, plan.id, &plan.name, cancel)
+            .await?;
+
+        let plan = plan_db::get_plan(self.pool, plan.id)
+            .await?
+            .with_context(|| format!("plan {} not found", plan.id))?;
+        let tasks = task_db::list_tasks_for_plan(self.pool, plan.id).await?;
+        let (input, output) = agent_events::get_token_usage_for_plan(self.pool, plan.id).await?;
+        outcome.status = plan.status.to_string();
+        outcome.total = tasks.len();
+        outcome.passed = tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Passed)
+            .count();
+        outcome.tokens = input + output;
+
+        if plan.status == PlanStatus::Completed && !self.options.no_pr {
+            outcome.pr_url = self.open_pr(&manager, &plan, input, output).await?;
+            if outcome.pr_url.is_none() {
+                outcome.error = Some("no code changes to propose".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge the plan's passed task branches onto a fresh fan-out branch,
+    /// push it, and open a PR. `None` if there was nothing to merge.
+    async fn open_pr(
+        &self,
+        manager: &WorktreeManager,
+        plan: &Plan,
+        input_tokens: i64,
+        output_tokens: i64,
+    ) -> Result<Option<String>> {
+        let tasks = task_db::list_tasks_for_plan(self.pool, plan.id).await?;
+        let deps = crate::merge_cmd::build_dependency_map(self.pool, &tasks).await?;
+        let ordered = crate::merge_cmd::topological_sort(&tasks, &deps)?;
+
+        let branch = format!("gator-fanout/{}", sanitize_ref_component(&plan.name));
+        manager
+            .checkout_new_branch(&branch, &plan.base_branch)
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        let mut merged = 0;
+        for task in &ordered {
+            if task.status != TaskStatus::Passed || task.kind == TaskKind::Artifact {
+                continue;
+            }
+            let task_branch = WorktreeManager::branch_name(&plan.name, &task.name);
+            match manager
+                .merge_branch(&task_branch)
+                .map_err(|e| anyhow::anyhow!("{e}"))?
+            {
+                MergeResult::Success => merged += 1,
+                MergeResult::Conflict { details } => {
+                    bail!("merge conflict on {task_branch}: {details}")
+                }
+            }
+        }
+        if merged == 0 {
+            return Ok(None);
+        }
+
+        let duration = match (plan.approved_at, plan.completed_at) {
+            (Some(start), Some(end)) => {
+                let secs = (end - start).num_seconds();
+                format!("{}m {}s", secs / 60, secs % 60)
+            }
+            _ => "-".to_string(),
+        };
+        let body = crate::pr_cmd::build_pr_body(
+            &plan.name,
+            &tasks,
+            input_tokens,
+            output_tokens,
+            &duration,
+        );
+        let repo_path = manager.repo_path();
+        forge::push_branch(repo_path, &self.options.remote, &branch)?;
+        let url = forge::create_pull_request(
+            repo_path,
+            &branch,
+            &plan.base_branch,
+            &plan.name,
+            &body,
+            self.options.draft,
+        )?;
+        Ok(Some(url))
+    }
+}
+
+/// Print the cross-repository report and the list of opened PRs.
+fn print_report(outcomes: &[RepoOutcome]) {
+    let plain = crate::output::is_plain();
+    if !plain {
+        println!(
+            "{:<32} {:<12} {:>7} {:>10}  RESULT",
+            "REPOSITORY", "STATUS", "TASKS", "TOKENS"
+        );
+        println!("{}", "-".repeat(76));
+    }
+    for o in outcomes {
+        let result = o
+            .pr_url
+            .as_deref()
+            .or(o.error.as_deref())
+            .unwrap_or("-")
+            .lines()
+            .next()
+            .unwrap_or("");
+        if plain {
+            println!(
+                "Repository {}: {}, {} of {} tasks passed, {} tokens, result: {result}.",
+                o.repo, o.status, o.passed, o.total, o.tokens
+            );
+            continue;
+        }
+        println!(
+            "{:<32} {:<12} {:>7} {:>10}  {result}",
+            o.repo,
+            o.status,
+            format!("{}/{}", o.passed, o.total),
+            o.tokens
+        );
+    }
+
+    let prs: Vec<&RepoOutcome> = outcomes.iter().filter(|o| o.pr_url.is_some()).collect();
+    if !prs.is_empty() {
+        println!("\nPull requests:");
+        for o in prs {
+            println!("  {}", o.pr_url.as_deref().unwrap_or_default());
+        }
+    }
+}
+
+/// Render the cross-repository report as Markdown.
+///
+/// This is a pure function for easy unit testing.
+pub fn render_report_markdown(outcomes: &[RepoOutcome]) -> String {
+    let completed = outcomes
+        .iter()
+        .filter(|o| o.status == PlanStatus::Completed.to_string())
+        .count();
+    let tokens: i64 = outcomes.iter().map(|o| o.tokens).sum();
+
+    let mut out = String::from("# Fan-out report\n\n");
+    out.push_str(&format!(
+        "{completed} of {} repositories completed, {tokens} tokens used.\n\n",
+        outcomes.len()
+    ));
+    out.push_str("| Repository | Plan | Status | Tasks passed | Tokens | Result |\n");
+    out.push_str("|------------|------|--------|--------------|--------|--------|\n");
+    for o in outcomes {
+        let plan = o.plan.as_ref().map_or("-", |(_, name)| name.as_str());
+        let result = match (&o.pr_url, &o.error) {
+            (Some(url), _) => url.clone(),
+            (None, Some(error)) => error.lines().next().unwrap_or("").replace('|', "\\|"),
+            (None, None) => "-".to_string(),
+        };
+        out.push_str(&format!(
+            "| {} | {plan} | {} | {}/{} | {} | {result} |\n",
+            o.repo, o.status, o.passed, o.total, o.tokens
+        ));
+    }
+
+    let prs: Vec<&str> = outcomes
+        .iter()
+        .filter_map(|o| o.pr_url.as_deref())
+        .collect();
+    if !prs.is_empty() {
+        out.push_str("\n## Pull requests\n\n");
+        for url in prs {
+            out.push_str(&format!("- {url}\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_report_lists_every_repo_and_pr() {
+        let outcomes = vec![
+            RepoOutcome {
+                repo: "acme/api".to_string(),
+                plan: Some((Uuid::new_v4(), "bump-api".to_string())),
+                status: "completed".to_string(),
+                passed: 2,
+                total: 2,
+                tokens: 1200,
+                pr_url: Some("https://github.com/acme/api/pull/9".to_string()),
+                error: None,
+            },
+            RepoOutcome {
+                repo: "acme/web".to_string(),
+                status: "error".to_string(),
+                error: Some("gh repo clone failed: not found | 404\nmore".to_string()),
+                ..Default::default()
+            },
+        ];
+        let md = render_report_markdown(&outcomes);
+
+        assert!(md.contains("1 of 2 repositories completed, 1200 tokens used."));
+        assert!(md.contains(
+            "| acme/api | bump-api | completed | 2/2 | 1200 | https://github.com/acme/api/pull/9 |"
+        ));
+        assert!(md.contains(
+            "| acme/web | - | error | 0/0 | 0 | gh repo clone failed: not found \\| 404 |"
+        ));
+        assert!(md.ends_with("## Pull requests\n\n- https://github.com/acme/api/pull/9\n"));
+    }
+}
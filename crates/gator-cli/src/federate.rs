@@ -0,0 +1,430 @@
+//! Federated views: `gator status --federate` and `gator dashboard
+//! --federate` across several gator databases.
+//!
+//! Organizations that run a gator database per team can list the databases
+//! in a federation file and see every team's plans in one view:
+//!
+//! ```toml
+//! [[database]]
+//! name = "payments"
+//! url = "/srv/gator/payments.db"
+//!
+//! [[database]]
+//! name = "search"
+//! url = "search.db"   # relative to this file
+//! ```
+//!
+//! Each database is opened read-only, so a federated view can never change
+//! another team's state. A database that cannot be opened or read is shown
+//! as unavailable instead of failing the whole view.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use gator_core::schema::Versioned;
+use gator_core::schema::v1;
+use gator_db::config::DbConfig;
+use gator_db::models::Plan;
+use gator_db::pool;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks::{self as task_db, PlanProgress};
+
+use crate::OutputFormat;
+use crate::output;
+use crate::status_cmd::describe_plan_status;
+use crate::timefmt;
+use crate::tui::plain::{Source, run_plain_dashboard};
+
+/// Connections each federated database gets; views only read, one query
+/// at a time.
+const MAX_CONNECTIONS: u32 = 2;
+
+/// One `[[database]]` entry of a federation file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FederatedDatabase {
+    /// Label the database's plans are shown under.
+    pub name: String,
+    /// Path of the SQLite database file. A relative path is relative to the
+    /// federation file.
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FederationFile {
+    #[serde(default)]
+    database: Vec<FederatedDatabase>,
+}
+
+/// Read a federation file: at least one database, with unique names.
+/// Relative paths are resolved against the file's directory.
+pub fn load_federation(path: &Path) -> Result<Vec<FederatedDatabase>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read federation file {}", path.display()))?;
+    let file: FederationFile = toml::from_str(&content)
+        .with_context(|| format!("invalid federation file {}", path.display()))?;
+    if file.database.is_empty() {
+        bail!(
+            "federation file {} lists no [[database]] entries",
+            path.display()
+        );
+    }
+
+    let base = path.parent().unwrap_or(Path::new("."));
+    let mut names = HashSet::new();
+    let mut databases = Vec::with_capacity(file.database.len());
+    for mut db in file.database {
+        if db.name.trim().is_empty() || db.url.trim().is_empty() {
+            bail!("every federated database needs a name and a url");
+        }
+        if !names.insert(db.name.clone()) {
+            bail!("federated database {:?} is listed twice", db.name);
+        }
+        let url = db.url.strip_prefix("sqlite://").unwrap_or(&db.url);
+        let url = PathBuf::from(url.split('?').next().unwrap_or(url));
+        db.url = base.join(url).display().to_string();
+        databases.push(db);
+    }
+    Ok(databases)
+}
+
+/// A federated database, opened read-only, or why it could not be.
+pub struct Member {
+    pub name: String,
+    pub pool: Result<SqlitePool, String>,
+}
+
+/// The databases of a federation file, opened read-only.
+pub struct Federation {
+    pub members: Vec<Member>,
+}
+
+impl Federation {
+    /// Open every database of the federation file at `path`. Databases that
+    /// cannot be opened are kept as unavailable members.
+    pub async fn open(path: &Path) -> Result<Self> {
+        let mut members = Vec::new();
+        for db in load_federation(path)? {
+            let config = DbConfig::new(&db.url).with_max_connections(MAX_CONNECTIONS);
+            let pool = pool::create_read_only_pool(&config)
+                .await
+                .map_err(|e| format!("{e:#}"));
+            members.push(Member {
+                name: db.name,
+                pool,
+            });
+        }
+        Ok(Self { members })
+    }
+
+    /// Close every open pool.
+    pub async fn close(&self) {
+        for member in &self.members {
+            if let Ok(pool) = &member.pool {
+                pool.close().await;
+            }
+        }
+    }
+}
+
+/// Every plan of one federated database with its progress, or why the
+/// database could not be read.
+struct MemberPlans {
+    name: String,
+    plans: Result<Vec<(Plan, PlanProgress)>, String>,
+}
+
+async fn member_plans(pool: &SqlitePool) -> Result<Vec<(Plan, PlanProgress)>> {
+    let mut rows = Vec::new();
+    for plan in plan_db::list_plans(pool).await? {
+        let progress = task_db::get_plan_progress(pool, plan.id).await?;
+        rows.push((plan, progress));
+    }
+    Ok(rows)
+}
+
+async fn collect(federation: &Federation) -> Vec<MemberPlans> {
+    let mut all = Vec::with_capacity(federation.members.len());
+    for member in &federation.members {
+        let plans = match &member.pool {
+            Ok(pool) => member_plans(pool).await.map_err(|e| format!("{e:#}")),
+            Err(e) => Err(e.clone()),
+        };
+        all.push(MemberPlans {
+            name: member.name.clone(),
+            plans,
+        });
+    }
+    all
+}
+
+/// One database in `gator status --federate --output json`.
+#[derive(Debug, Serialize)]
+struct FederatedStatus {
+    database: String,
+    plans: Vec<Versioned<v1::PlanSummary>>,
+    /// Why the database could not be read; `plans` is empty then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// `gator status --federate <file>`: every plan of every federated
+/// database, labelled with its database.
+pub async fn run_federated_status(path: &Path, output: OutputFormat) -> Result<()> {
+    let federation = Federation::open(path).await?;
+    let all = collect(&federation).await;
+    federation.close().await;
+
+    if output == OutputFormat::Json {
+        let body: Vec<FederatedStatus> = all
+            .into_iter()
+            .map(|member| match member.plans {
+                Ok(plans) => FederatedStatus {
+                    database: member.name,
+                    plans: plans
+                        .into_iter()
+                        .map(|(plan, progress)| {
+                            Versioned::new(v1::PlanSummary {
+                                plan: plan.into(),
+                                progress: progress.into(),
+                            })
+                        })
+                        .collect(),
+                    error: None,
+                },
+                Err(e) => FederatedStatus {
+                    database: member.name,
+                    plans: Vec::new(),
+                    error: Some(e),
+                },
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let lines = if output::is_plain() {
+        plain_lines(&all, now)
+    } else {
+        table_lines(&all, now)
+    };
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// `gator dashboard --federate <file>`: the plain-text dashboard over every
+/// federated database. The TUI's approve, reject, and retry actions need
+/// write access, so a federated dashboard is always plain text.
+pub async fn run_federated_dashboard(
+    path: &Path,
+    interval: Duration,
+    desktop_notify: bool,
+) -> Result<()> {
+    let federation = Federation::open(path).await?;
+    let sources: Vec<Source<'_>> = federation
+        .members
+        .iter()
+        .map(|member| Source {
+            label: Some(&member.name),
+            pool: member.pool.as_ref().ok(),
+        })
+        .collect();
+    for member in &federation.members {
+        if let Err(e) = &member.pool {
+            eprintln!("Database {} is unavailable: {e}", member.name);
+        }
+    }
+    let result = run_plain_dashboard(&sources, interval, desktop_notify).await;
+    federation.close().await;
+    result
+}
+
+/// The federated status table: one row per plan, then the databases that
+/// could not be read.
+fn table_lines(all: &[MemberPlans], now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{:<16} {:<38} {:<30} {:<12} {:>10}  {:<10}",
+            "DATABASE", "ID", "NAME", "STATUS", "PROGRESS", "CREATED"
+        ),
+        "-".repeat(121),
+    ];
+    let mut unavailable = Vec::new();
+    for member in all {
+        let plans = match &member.plans {
+            Ok(plans) => plans,
+            Err(e) => {
+                unavailable.push(format!("  {}: {e}", member.name));
+                continue;
+            }
+        };
+        for (plan, progress) in plans {
+            let name_display = if plan.name.len() > 28 {
+                format!("{}...", &plan.name[..25])
+            } else {
+                plan.name.clone()
+            };
+            lines.push(format!(
+                "{:<16} {:<38} {:<30} {:<12} {:>10}  {:<10}",
+                member.name,
+                plan.id,
+                name_display,
+                plan.status,
+                format!("{}/{}", progress.passed, progress.total),
+                timefmt::relative(plan.created_at, now)
+            ));
+        }
+    }
+    if !unavailable.is_empty() {
+        lines.push(String::new());
+        lines.push("Unavailable databases:".to_string());
+        lines.extend(unavailable);
+    }
+    lines
+}
+
+/// The federated status as sentences, one database at a time.
+fn plain_lines(all: &[MemberPlans], now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for member in all {
+        match &member.plans {
+            Ok(plans) => {
+                let noun = if plans.len() == 1 { "plan" } else { "plans" };
+                lines.push(format!("Database {}: {} {noun}.", member.name, plans.len()));
+                for (plan, progress) in plans {
+                    lines.push(format!(
+                        "Plan {}: {}, {}, created {}. ID {}.",
+                        plan.name,
+                        describe_plan_status(plan),
+                        output::progress_sentence(progress),
+                        timefmt::relative(plan.created_at, now),
+                        plan.id
+                    ));
+                }
+            }
+            Err(e) => lines.push(format!("Database {} is unavailable: {e}.", member.name)),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gator_test_utils::{create_test_db, drop_test_db};
+
+    #[test]
+    fn load_resolves_relative_paths_and_rejects_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fleet.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[database]]
+name = "payments"
+url = "/srv/gator/payments.db"
+
+[[database]]
+name = "search"
+url = "sqlite://teams/search.db?mode=ro"
+"#,
+        )
+        .unwrap();
+        let dbs = load_federation(&path).unwrap();
+        assert_eq!(dbs[0].url, "/srv/gator/payments.db");
+        assert_eq!(
+            dbs[1].url,
+            dir.path().join("teams/search.db").display().to_string()
+        );
+
+        std::fs::write(
+            &path,
+            "[[database]]\nname = \"a\"\nurl = \"a.db\"\n\n[[database]]\nname = \"a\"\nurl = \"b.db\"\n",
+        )
+        .unwrap();
+        assert!(load_federation(&path).is_err());
+        std::fs::write(&path, "").unwrap();
+        assert!(load_federation(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn status_spans_databases_and_reports_unavailable_ones() {
+        let (pool_a, path_a) = create_test_db().await;
+        let (pool_b, path_b) = create_test_db().await;
+        for (pool, name) in [(&pool_a, "billing-plan"), (&pool_b, "search-plan")] {
+            plan_db::insert_plan(
+                pool,
+                name,
+                "/tmp/repo",
+                "main",
+                None,
+                "claude-code",
+                "worktree",
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fleet.toml");
+        std::fs::write(
+            &path,
+            format!(
+                "[[database]]\nname = \"billing\"\nurl = {:?}\n\n\
+                 [[database]]\nname = \"search\"\nurl = {:?}\n\n\
+                 [[database]]\nname = \"gone\"\nurl = \"missing.db\"\n",
+                path_a.display().to_string(),
+                path_b.display().to_string(),
+            ),
+        )
+        .unwrap();
+
+        let federation = Federation::open(&path).await.unwrap();
+        let all = collect(&federation).await;
+        federation.close().await;
+        let lines = plain_lines(&all, Utc::now());
+        assert_eq!(lines[0], "Database billing: 1 plan.");
+        assert!(lines[1].starts_with("Plan billing-plan: draft"));
+        assert_eq!(lines[2], "Database search: 1 plan.");
+        assert!(lines[3].starts_with("Plan search-plan: draft"));
+        assert!(lines[4].starts_with("Database gone is unavailable:"));
+
+        // Federated databases are opened read-only.
+        let reopened = Federation::open(&path).await.unwrap();
+        let Ok(pool) = &reopened.members[0].pool else {
+            panic!("billing should be available");
+        };
+        assert!(
+            plan_db::insert_plan(
+                pool,
+                "x",
+                "/tmp/repo",
+                "main",
+                None,
+                "claude-code",
+                "worktree",
+                None
+            )
+            .await
+            .is_err()
+        );
+        reopened.close().await;
+
+        pool_a.close().await;
+        pool_b.close().await;
+        drop_test_db(&path_a).await;
+        drop_test_db(&path_b).await;
+    }
+}
@@ -1,14 +1,21 @@
-//! `gator gate` command: view gate results for a task.
+//! `gator gate` command: view gate results for a task, its warm-up, and
+//! the artifacts collected from failing invariants. The attempt's setup
+//! commands are listed first, as synthetic `setup:` results.
 
 use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use gator_db::queries::gate_artifacts;
 use gator_db::queries::gate_results;
+use gator_db::queries::setup_results;
 use gator_db::queries::tasks as task_db;
+use gator_db::queries::warmup_results;
 
 /// Run the gate command: show invariant results for a task's current attempt.
-pub async fn run_gate(pool: &SqlitePool, task_id_str: &str) -> Result<()> {
+/// With `show_artifacts`, list each failure's collected files instead of
+/// just counting them.
+pub async fn run_gate(pool: &SqlitePool, task_id_str: &str, show_artifacts: bool) -> Result<()> {
     let task_id =
         Uuid::parse_str(task_id_str).with_context(|| format!("invalid task ID: {task_id_str}"))?;
 
@@ -19,44 +26,117 @@ pub async fn run_gate(pool: &SqlitePool, task_id_str: &str) -> Result<()> {
     println!("Task: {} (attempt {})", task.name, task.attempt);
     println!();
 
+    if let Some(w) = warmup_results::get_warmup_result(pool, task_id, task.attempt).await? {
+        let status = if w.passed { "PASS" } else { "FAIL" };
+        let exit_str = if w.timed_out {
+            "timed out".to_string()
+        } else {
+            w.exit_code
+                .map(|c| format!("exit {c}"))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        println!(
+            "Warm-up: [{}] {} ({}, {}ms)",
+            status, w.command, exit_str, w.duration_ms
+        );
+        if !w.passed {
+            print_stderr_snippet(w.stderr.as_deref());
+        }
+        println!();
+    }
+
+    let setup = setup_results::list_setup_results(pool, task_id, task.attempt).await?;
     let results = gate_results::get_latest_gate_results(pool, task_id).await?;
 
-    if results.is_empty() {
+    if setup.is_empty() && results.is_empty() {
         println!("No gate results yet.");
         return Ok(());
     }
 
+    let artifacts = gate_artifacts::list_gate_artifacts(pool, task_id, task.attempt).await?;
+
     println!("Gate results:");
+    for s in &setup {
+        let status = if s.passed { "PASS" } else { "FAIL" };
+        let exit_str = if s.timed_out {
+            "timed out".to_string()
+        } else {
+            s.exit_code
+                .map(|c| format!("exit {c}"))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        println!(
+            "  [{}] setup: {} ({}, {}ms)",
+            status, s.command, exit_str, s.duration_ms
+        );
+        if !s.passed {
+            print_stderr_snippet(s.stderr.as_deref());
+        }
+    }
     for r in &results {
         let status = if r.passed { "PASS" } else { "FAIL" };
-        let exit_str = r
-            .exit_code
-            .map(|c| format!("exit {c}"))
-            .unwrap_or_else(|| "-".to_string());
-        let duration_str = r
-            .duration_ms
-            .map(|ms| format!("{ms}ms"))
-            .unwrap_or_else(|| "-".to_string());
+        let exit_str = if r.timed_out {
+            "timed out".to_string()
+        } else {
+            r.exit_code
+                .map(|c| format!("exit {c}"))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let values_str = match (r.base_value, r.task_value) {
+            (Some(b), Some(t)) => format!(", base {b}, task {t}"),
+            (None, Some(t)) => format!(", value {t}"),
+            _ => String::new(),
+        };
+        let duration_str = match (r.duration_ms, r.cached_from) {
+            (_, Some(attempt)) => format!("cached from attempt {attempt}"),
+            (Some(ms), None) => format!("{ms}ms"),
+            (None, None) => "-".to_string(),
+        };
 
         println!(
-            "  [{}] {} ({}, {})",
-            status, r.invariant_name, exit_str, duration_str
+            "  [{}] {} ({}{}, {})",
+            status, r.invariant_name, exit_str, values_str, duration_str
         );
 
         if !r.passed {
-            if let Some(stderr) = &r.stderr {
-                let snippet = stderr.trim();
-                if !snippet.is_empty() {
-                    let display = if snippet.len() > 200 {
-                        format!("{}...", &snippet[..200])
-                    } else {
-                        snippet.to_string()
-                    };
-                    println!("    stderr: {display}");
-                }
+            print_stderr_snippet(r.stderr.as_deref());
+        }
+
+        let collected: Vec<_> = artifacts
+            .iter()
+            .filter(|a| a.gate_result_id == r.id)
+            .collect();
+        if collected.is_empty() {
+            continue;
+        }
+        if show_artifacts {
+            println!("    artifacts:");
+            for a in collected {
+                println!(
+                    "      {} ({} bytes) -> {}",
+                    a.path, a.size_bytes, a.stored_path
+                );
             }
+        } else {
+            println!(
+                "    artifacts: {} file(s) (gator gate {task_id} --artifacts)",
+                collected.len()
+            );
         }
     }
 
     Ok(())
 }
+
+/// Print the start of a failed check's stderr, if it has any.
+fn print_stderr_snippet(stderr: Option<&str>) {
+    let snippet = stderr.unwrap_or_default().trim();
+    if !snippet.is_empty() {
+        let display = if snippet.len() > 200 {
+            format!("{}...", &snippet[..200])
+        } else {
+            snippet.to_string()
+        };
+        println!("    stderr: {display}");
+    }
+}
@@ -0,0 +1,424 @@
+//! `gator gc` command: garbage-collect what finished work leaves behind,
+//! across every plan.
+//!
+//! Per project, gc removes the worktrees and branches of tasks that passed
+//! and were merged, prunes git's registrations of worktrees whose
+//! directories are gone, and deletes `gator/*` branches that no task --
+//! of any plan, archived and deleted plans included -- refers to, once
+//! they are merged into a base branch (unmerged ones only with `--force`:
+//! another database or a person may have made them). Then it
+//! removes workspace and ephemeral database containers that no task is
+//! using, and agent events older than the retention window. Containers of
+//! tasks in flight, or of plans whose dispatch may still be running, are
+//! left alone. Token usage events are kept: budgets and reports sum them.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use gator_core::ephemeral_db;
+use gator_core::isolation::container::{PLAN_LABEL, TASK_LABEL};
+use gator_core::orchestrator::dispatcher_alive;
+use gator_core::worktree::WorktreeManager;
+use gator_db::models::TaskStatus;
+use gator_db::queries::agent_events as event_db;
+use gator_db::queries::tasks::{self as task_db, TaskRef};
+
+/// Days of agent events kept when neither `--event-days` nor
+/// `cleanup.agent_event_days` is set.
+pub const DEFAULT_AGENT_EVENT_DAYS: u32 = 30;
+
+/// What one gc run removed, or would remove.
+#[derive(Debug, Default)]
+struct Collected {
+    worktrees: usize,
+    merged_branches: usize,
+    stale_registrations: usize,
+    dangling_branches: usize,
+    unmerged_branches: usize,
+    containers: usize,
+    events: u64,
+}
+
+/// Run the gc command. With `dry_run`, only report what would go; with
+/// `force`, delete dangling branches that are not merged as well.
+pub async fn run_gc(pool: &SqlitePool, dry_run: bool, force: bool, event_days: u32) -> Result<()> {
+    let tasks = task_db::list_task_refs(pool).await?;
+    let mut collected = Collected::default();
+
+    let mut projects: BTreeMap<&str, Vec<&TaskRef>> = BTreeMap::new();
+    for task in &tasks {
+        projects.entry(&task.project_path).or_default().push(task);
+    }
+    for (project, tasks) in &projects {
+        collect_project(pool, project, tasks, dry_run, force, &mut collected).await?;
+    }
+
+    collect_containers(pool, &tasks, dry_run, &mut collected).await?;
+
+    let cutoff = Utc::now() - Duration::days(i64::from(event_days));
+    collected.events = if dry_run {
+        event_db::count_prunable_events(pool, cutoff).await? as u64
+    } else {
+        event_db::prune_events(pool, cutoff).await?
+    };
+
+    let Collected {
+        worktrees,
+        merged_branches,
+        stale_registrations,
+        dangling_branches,
+        unmerged_branches,
+        containers,
+        events,
+    } = collected;
+    println!(
+        "\n{}: {worktrees} worktree(s), {merged_branches} merged branch(es), \
+         {stale_registrations} stale worktree registration(s), {dangling_branches} dangling \
+         branch(es), {containers} orphaned container(s), {events} agent event(s) older than \
+         {event_days} day(s).",
+        if dry_run { "Would remove" } else { "Removed" }
+    );
+    if unmerged_branches > 0 {
+        println!(
+            "Kept {unmerged_branches} dangling branch(es) not merged into a base branch; \
+             --force deletes them."
+        );
+    }
+    Ok(())
+}
+
+/// Collect one project's merged worktrees and branches, stale worktree
+/// registrations, and dangling branches.
+async fn collect_project(
+    pool: &SqlitePool,
+    project: &str,
+    tasks: &[&TaskRef],
+    dry_run: bool,
+    force: bool,
+    collected: &mut Collected,
+) -> Result<()> {
+    if !Path::new(project).exists() {
+        println!("{project}: skipped, the repository is gone");
+        return Ok(());
+    }
+    let manager = match WorktreeManager::new(project, None) {
+        Ok(manager) => manager,
+        Err(e) => {
+            eprintln!("{project}: skipped: {e}");
+            return Ok(());
+        }
+    };
+    println!("{project}:");
+    let would = if dry_run { "Would remove" } else { "Removed" };
+
+    let mut referenced = HashSet::new();
+    for task in tasks {
+        let branch = WorktreeManager::branch_name(&task.plan_name, &task.task_name);
+        referenced.insert(branch.clone());
+        if task.status != TaskStatus::Passed || task.merged_at.is_none() {
+            continue;
+        }
+
+        let worktree = task
+            .worktree_path
+            .as_ref()
+            .filter(|p| Path::new(p).exists());
+        if let Some(wt_path) = worktree {
+            let removed = dry_run || {
+                match manager.remove_worktree(Path::new(wt_path)) {
+                    Ok(()) => {
+                        task_db::clear_disk_usage(pool, task.task_id).await?;
+                        true
+                    }
+                    Err(e) => {
+                        eprintln!("  Warning: failed to remove worktree {wt_path}: {e}");
+                        false
+                    }
+                }
+            };
+            if removed {
+                println!("  {would} worktree: {wt_path}");
+                collected.worktrees += 1;
+            }
+        }
+
+        if manager.branch_exists(&branch).unwrap_or(false)
+            && delete_branch(&manager, &branch, dry_run)
+        {
+            println!("  {would} merged branch: {branch}");
+            collected.merged_branches += 1;
+        }
+    }
+
+    match manager.prune_stale(dry_run) {
+        Ok(pruned) => {
+            for name in &pruned {
+                println!("  {would} stale worktree registration: {name}");
+            }
+            collected.stale_registrations += pruned.len();
+        }
+        Err(e) => eprintln!("  Warning: failed to prune stale worktrees: {e}"),
+    }
+
+    let bases: BTreeSet<&str> = tasks.iter().map(|t| t.base_branch.as_str()).collect();
+    match manager.gator_branches() {
+        Ok(branches) => {
+            for branch in branches {
+                if referenced.contains(&branch) {
+                    continue;
+                }
+                let merged = bases
+                    .iter()
+                    .any(|base| manager.is_merged(&branch, base).unwrap_or(false));
+                if !merged && !force {
+                    println!("  Kept unmerged dangling branch: {branch}");
+                    collected.unmerged_branches += 1;
+                    continue;
+                }
+                if delete_branch(&manager, &branch, dry_run) {
+                    let unmerged = if merged { "" } else { "unmerged " };
+                    println!("  {would} {unmerged}dangling branch: {branch}");
+                    collected.dangling_branches += 1;
+                }
+            }
+        }
+        Err(e) => eprintln!("  Warning: failed to list gator branches: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Delete `branch` unless `dry_run`; a failure (e.g. the branch is checked
+/// out somewhere) is reported and returns false.
+fn delete_branch(manager: &WorktreeManager, branch: &str, dry_run: bool) -> bool {
+    if dry_run {
+        return true;
+    }
+    match manager.delete_branch(branch) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("  Warning: failed to delete branch {branch}: {e}");
+            false
+        }
+    }
+}
+
+/// Remove workspace and ephemeral database containers that no task is
+/// using. Skipped quietly when Docker is not available.
+async fn collect_containers(
+    pool: &SqlitePool,
+    tasks: &[TaskRef],
+    dry_run: bool,
+    collected: &mut Collected,
+) -> Result<()> {
+    // Containers and the tasks that may own them.
+    let mut orphans: Vec<(String, String, Vec<Uuid>)> = Vec::new();
+
+    let format =
+        format!("{{{{.ID}}}}\t{{{{.Label \"{PLAN_LABEL}\"}}}}\t{{{{.Label \"{TASK_LABEL}\"}}}}");
+    let Some(workspaces) = docker_ps(PLAN_LABEL, &format).await else {
+        return Ok(());
+    };
+    for line in workspaces.lines() {
+        let mut fields = line.split('\t');
+        let (Some(id), Some(plan), Some(task)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let owners = workspace_owners(tasks, plan, task);
+        if !owners.iter().any(|t| in_use(t)) {
+            let owners = owners.iter().map(|t| t.task_id).collect();
+            orphans.push((
+                id.to_string(),
+                format!("workspace of {plan}/{task}"),
+                owners,
+            ));
+        }
+    }
+
+    let format = format!("{{{{.ID}}}}\t{{{{.Label \"{}\"}}}}", ephemeral_db::LABEL);
+    for line in docker_ps(ephemeral_db::LABEL, &format)
+        .await
+        .unwrap_or_default()
+        .lines()
+    {
+        let Some((id, label)) = line.split_once('\t') else {
+            continue;
+        };
+        let owner = parse_attempt_label(label).map(|(task_id, _)| task_id);
+        if !tasks.iter().any(|t| Some(t.task_id) == owner && in_use(t)) {
+            orphans.push((
+                id.to_string(),
+                format!("ephemeral database of {label}"),
+                owner.into_iter().collect(),
+            ));
+        }
+    }
+
+    if orphans.is_empty() {
+        return Ok(());
+    }
+    println!("containers:");
+    for (id, what, owners) in orphans {
+        // The task list was read when gc started; a dispatch may have
+        // picked the task up since.
+        let mut claimed = false;
+        for owner in owners {
+            if task_db::get_task_ref(pool, owner)
+                .await?
+                .is_some_and(|t| in_use(&t))
+            {
+                claimed = true;
+                break;
+            }
+        }
+        if claimed {
+            println!("  Kept container {id} ({what}): its task is now in use");
+            continue;
+        }
+
+        let removed = dry_run || {
+            let output = Command::new("docker")
+                .args(["rm", "-f", &id])
+                .output()
+                .await;
+            match output {
+                Ok(output) if output.status.success() => true,
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    eprintln!(
+                        "  Warning: failed to remove container {id}: {}",
+                        stderr.trim()
+                    );
+                    false
+                }
+                Err(e) => {
+                    eprintln!("  Warning: failed to run docker rm: {e}");
+                    false
+                }
+            }
+        };
+        if removed {
+            let would = if dry_run { "Would remove" } else { "Removed" };
+            println!("  {would} container {id} ({what})");
+            collected.containers += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Tasks whose workspace container is labelled with `plan` and `task`.
+fn workspace_owners<'a>(tasks: &'a [TaskRef], plan: &str, task: &str) -> Vec<&'a TaskRef> {
+    tasks
+        .iter()
+        .filter(|t| t.plan_name == plan && t.task_name == task)
+        .collect()
+}
+
+/// Whether `task` may be using its containers: it is in flight, or its
+/// plan's dispatcher may still be running -- a task is `pending` while its
+/// workspace and database are provisioned, before it is assigned.
+fn in_use(task: &TaskRef) -> bool {
+    task.in_flight
+        || dispatcher_alive(
+            task.dispatched_host.as_deref(),
+            task.dispatched_pid,
+            task.plan_running,
+        )
+}
+
+/// Containers, running or not, that carry `label`, in docker's `format`.
+/// `None` when docker is missing or its daemon unreachable.
+async fn docker_ps(label: &str, format: &str) -> Option<String> {
+    let output = Command::new("docker")
+        .args(["ps", "-a", "--no-trunc", "--filter"])
+        .arg(format!("label={label}"))
+        .args(["--format", format])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse an ephemeral database label, `<task_id>-<attempt>`.
+fn parse_attempt_label(label: &str) -> Option<(Uuid, i32)> {
+    let (task_id, attempt) = label.rsplit_once('-')?;
+    Some((task_id.parse().ok()?, attempt.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use gator_core::orchestrator::DispatcherIdentity;
+
+    use super::*;
+
+    #[test]
+    fn ephemeral_db_labels_name_task_and_attempt() {
+        let task_id = Uuid::new_v4();
+        assert_eq!(
+            parse_attempt_label(&format!("{task_id}-2")),
+            Some((task_id, 2))
+        );
+        assert_eq!(parse_attempt_label(&task_id.to_string()), None);
+        assert_eq!(parse_attempt_label("not-a-task-0"), None);
+    }
+
+    fn task_ref(status: TaskStatus, plan_running: bool, dispatcher: (&str, i64)) -> TaskRef {
+        TaskRef {
+            task_id: Uuid::new_v4(),
+            task_name: "build".to_string(),
+            plan_name: "plan".to_string(),
+            project_path: "/tmp/project".to_string(),
+            base_branch: "main".to_string(),
+            status,
+            attempt: 0,
+            worktree_path: None,
+            merged_at: None,
+            in_flight: false,
+            plan_running,
+            dispatched_host: Some(dispatcher.0.to_string()),
+            dispatched_pid: Some(dispatcher.1),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn provisioning_containers_of_pending_tasks_are_kept() {
+        let this = DispatcherIdentity::current();
+        let live = (this.host.as_str(), i64::from(this.pid));
+
+        // A dispatch on this host is provisioning the pending task.
+        let tasks = [task_ref(TaskStatus::Pending, true, live)];
+        let owners = workspace_owners(&tasks, "plan", "build");
+        assert_eq!(owners.len(), 1);
+        assert!(owners.iter().any(|t| in_use(t)));
+
+        // Its dispatch died: the container is an orphan.
+        let tasks = [task_ref(
+            TaskStatus::Pending,
+            true,
+            (this.host.as_str(), -1),
+        )];
+        assert!(
+            !workspace_owners(&tasks, "plan", "build")
+                .iter()
+                .any(|t| in_use(t))
+        );
+
+        // A dispatch on another host cannot be checked; a running plan's is
+        // taken for alive.
+        let tasks = [task_ref(TaskStatus::Pending, true, ("elsewhere", 1))];
+        assert!(in_use(&tasks[0]));
+        let tasks = [task_ref(TaskStatus::Failed, false, ("elsewhere", 1))];
+        assert!(!in_use(&tasks[0]));
+    }
+}
@@ -4,15 +4,23 @@
 //! - `gator invariant add`           -- create a new invariant definition
 //! - `gator invariant list`          -- list all invariants in table format
 //! - `gator invariant test`          -- test-run an invariant in the current directory
+//! - `gator invariant edit`          -- change an invariant's definition
+//! - `gator invariant disable/enable` -- skip an invariant at the gate, or stop skipping it
+//! - `gator invariant remove`        -- soft-delete an invariant (restorable)
+//! - `gator invariant audit`         -- report unused, missing, and broken invariants
 //! - `gator invariant presets list`  -- list available preset invariants
-//! - `gator invariant presets install` -- register preset invariants in the database
+//! - `gator invariant presets install` -- register or refresh preset invariants in the database
+
+use std::collections::BTreeMap;
 
 use anyhow::{Context, Result, bail};
 use sqlx::SqlitePool;
 
+use gator_core::gate::{self, diff};
 use gator_core::invariant::runner::{self, InvariantResult};
 use gator_core::presets;
-use gator_db::models::{InvariantKind, InvariantScope};
+use gator_db::models::{Invariant, InvariantKind, InvariantScope};
+use gator_db::queries::gate_results;
 use gator_db::queries::invariants;
 
 use crate::{InvariantCommands, PresetCommands};
@@ -34,6 +42,12 @@ pub async fn run_invariant_command(command: InvariantCommands, pool: &SqlitePool
             threshold,
             scope,
             timeout,
+            diff_base,
+            lower_is_better,
+            env,
+            workdir,
+            after,
+            artifacts,
         } => {
             cmd_add(
                 pool,
@@ -47,17 +61,75 @@ pub async fn run_invariant_command(command: InvariantCommands, pool: &SqlitePool
                     threshold,
                     scope,
                     timeout,
+                    diff_base,
+                    lower_is_better,
+                    env,
+                    workdir,
+                    after,
+                    artifacts,
                 },
             )
             .await
         }
         InvariantCommands::List { verbose } => cmd_list(pool, verbose).await,
         InvariantCommands::Test { name } => cmd_test(pool, &name).await,
+        InvariantCommands::Edit {
+            name,
+            rename,
+            kind,
+            command,
+            args,
+            description,
+            expected_exit_code,
+            threshold,
+            clear_threshold,
+            scope,
+            timeout,
+            diff_base,
+            lower_is_better,
+            env,
+            unset_env,
+            workdir,
+            clear_workdir,
+            after,
+            artifacts,
+        } => {
+            cmd_edit(
+                pool,
+                EditParams {
+                    name,
+                    rename,
+                    kind,
+                    command,
+                    args,
+                    description,
+                    expected_exit_code,
+                    threshold,
+                    clear_threshold,
+                    scope,
+                    timeout,
+                    diff_base,
+                    lower_is_better,
+                    env,
+                    unset_env,
+                    workdir,
+                    clear_workdir,
+                    after,
+                    artifacts,
+                },
+            )
+            .await
+        }
+        InvariantCommands::Disable { name } => cmd_set_disabled(pool, &name, true).await,
+        InvariantCommands::Enable { name } => cmd_set_disabled(pool, &name, false).await,
+        InvariantCommands::Remove { name } => cmd_remove(pool, &name).await,
+        InvariantCommands::Audit { project_type } => cmd_audit(pool, project_type.as_deref()).await,
         InvariantCommands::Presets { command } => match command {
             PresetCommands::List { project_type } => cmd_presets_list(project_type.as_deref()),
-            PresetCommands::Install { project_type } => {
-                cmd_presets_install(pool, project_type.as_deref()).await
-            }
+            PresetCommands::Install {
+                project_type,
+                update,
+            } => cmd_presets_install(pool, project_type.as_deref(), update).await,
         },
     }
 }
@@ -77,32 +149,123 @@ struct AddParams {
     threshold: Option<f32>,
     scope: String,
     timeout: i32,
+    diff_base: bool,
+    lower_is_better: bool,
+    env: Vec<String>,
+    workdir: Option<String>,
+    after: Option<String>,
+    artifacts: Option<String>,
 }
 
-/// Create a new invariant definition and insert it into the database.
-async fn cmd_add(pool: &SqlitePool, params: AddParams) -> Result<()> {
-    // Parse the kind enum.
-    let kind: InvariantKind = params.kind.parse().map_err(|_| {
+fn parse_kind(kind: &str) -> Result<InvariantKind> {
+    kind.parse().map_err(|_| {
         anyhow::anyhow!(
             "invalid invariant kind {:?}; expected one of: \
              test_suite, typecheck, lint, coverage, custom",
-            params.kind,
+            kind,
         )
-    })?;
+    })
+}
 
-    // Parse the scope enum.
-    let scope: InvariantScope = params.scope.parse().map_err(|_| {
+fn parse_scope(scope: &str) -> Result<InvariantScope> {
+    scope.parse().map_err(|_| {
         anyhow::anyhow!(
             "invalid invariant scope {:?}; expected one of: global, project",
-            params.scope,
+            scope,
         )
-    })?;
+    })
+}
 
-    // Parse comma-separated args into a Vec<String>.
-    let args_vec: Vec<String> = match params.args.as_deref() {
+/// Split comma-separated args; `None` or an empty string gives no args.
+fn parse_args(args: Option<&str>) -> Vec<String> {
+    match args {
         Some(s) if !s.is_empty() => s.split(',').map(|a| a.to_owned()).collect(),
         _ => Vec::new(),
-    };
+    }
+}
+
+/// Parse `KEY=VALUE` pairs into `env`, later keys replacing earlier ones.
+pub(crate) fn parse_env(pairs: &[String], env: &mut BTreeMap<String, String>) -> Result<()> {
+    for pair in pairs {
+        match pair.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                env.insert(key.to_owned(), value.to_owned());
+            }
+            _ => bail!("invalid env {pair:?}; expected KEY=VALUE"),
+        }
+    }
+    Ok(())
+}
+
+/// Check a `run_after` list for invariant `name` (formerly `old_name`, on a
+/// rename): every entry must be another live invariant, and following
+/// `run_after` from them must not lead back to this one.
+async fn check_run_after(
+    pool: &SqlitePool,
+    name: &str,
+    old_name: Option<&str>,
+    after: &[String],
+) -> Result<()> {
+    let all = invariants::list_invariants(pool).await?;
+    let is_self = |n: &str| n == name || Some(n) == old_name;
+    for prerequisite in after {
+        if is_self(prerequisite) {
+            bail!("invariant {name:?} cannot run after itself");
+        }
+        if !all.iter().any(|i| &i.name == prerequisite) {
+            bail!("--after names unknown invariant {prerequisite:?}");
+        }
+    }
+
+    let mut stack: Vec<&str> = after.iter().map(String::as_str).collect();
+    let mut seen = std::collections::HashSet::new();
+    while let Some(current) = stack.pop() {
+        if !seen.insert(current) {
+            continue;
+        }
+        let Some(inv) = all.iter().find(|i| i.name == current) else {
+            continue;
+        };
+        for next in inv.run_after.iter() {
+            if is_self(next) {
+                bail!("--after would make {name:?} wait for itself (via {current:?})");
+            }
+            stack.push(next);
+        }
+    }
+    Ok(())
+}
+
+/// Parse a comma-separated `--artifacts` list, checking each glob.
+fn parse_artifacts(artifacts: Option<&str>) -> Result<Vec<String>> {
+    let globs = parse_args(artifacts);
+    for glob in &globs {
+        gate::artifacts::validate_pattern(glob)?;
+    }
+    Ok(globs)
+}
+
+/// One-line rendering of an invariant's env, e.g. `CI=1 RUST_LOG=debug`.
+pub(crate) fn format_env(env: &BTreeMap<String, String>) -> String {
+    env.iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Create a new invariant definition and insert it into the database.
+async fn cmd_add(pool: &SqlitePool, params: AddParams) -> Result<()> {
+    let kind = parse_kind(&params.kind)?;
+    let scope = parse_scope(&params.scope)?;
+    let args_vec = parse_args(params.args.as_deref());
+    let mut env = BTreeMap::new();
+    parse_env(&params.env, &mut env)?;
+    if let Some(workdir) = &params.workdir {
+        runner::validate_workdir(workdir)?;
+    }
+    let run_after = parse_args(params.after.as_deref());
+    check_run_after(pool, &params.name, None, &run_after).await?;
+    let artifacts = parse_artifacts(params.artifacts.as_deref())?;
 
     let new = invariants::NewInvariant {
         name: &params.name,
@@ -114,8 +277,25 @@ async fn cmd_add(pool: &SqlitePool, params: AddParams) -> Result<()> {
         threshold: params.threshold,
         scope,
         timeout_secs: params.timeout,
+        diff_base: params.diff_base,
+        lower_is_better: params.lower_is_better,
+        env: &env,
+        workdir: params.workdir.as_deref(),
+        run_after: &run_after,
+        artifacts: &artifacts,
     };
 
+    if invariants::get_deleted_invariant_by_name(pool, &params.name)
+        .await?
+        .is_some()
+    {
+        bail!(
+            "invariant {:?} was deleted; restore it with `gator restore invariant {}`",
+            params.name,
+            params.name,
+        );
+    }
+
     let invariant = invariants::insert_invariant(pool, &new)
         .await
         .with_context(|| {
@@ -134,6 +314,21 @@ async fn cmd_add(pool: &SqlitePool, params: AddParams) -> Result<()> {
         println!("  Args:    {}", invariant.args.join(" "));
     }
     println!("  Scope:   {}", invariant.scope);
+    if !invariant.env.is_empty() {
+        println!("  Env:     {}", format_env(&invariant.env));
+    }
+    if let Some(workdir) = &invariant.workdir {
+        println!("  Workdir: {workdir}");
+    }
+    if !invariant.run_after.is_empty() {
+        println!("  After:   {}", invariant.run_after.join(", "));
+    }
+    if !invariant.artifacts.is_empty() {
+        println!("  Artifacts: {}", invariant.artifacts.join(", "));
+    }
+    if invariant.diff_base {
+        println!("  Mode:    {}", diff_mode(&invariant));
+    }
 
     Ok(())
 }
@@ -154,7 +349,7 @@ async fn cmd_list(pool: &SqlitePool, verbose: bool) -> Result<()> {
     if verbose {
         for (i, inv) in invs.iter().enumerate() {
             if i > 0 {
-                println!("---");
+                println!("{}", if crate::output::is_plain() { "" } else { "---" });
             }
             println!("Name:              {}", inv.name);
             println!("ID:                {}", inv.id);
@@ -171,12 +366,58 @@ async fn cmd_list(pool: &SqlitePool, verbose: bool) -> Result<()> {
                 println!("Threshold:         {}", t);
             }
             println!("Scope:             {}", inv.scope);
-            println!("Created:           {}", inv.created_at);
+            if !inv.env.is_empty() {
+                println!("Env:               {}", format_env(&inv.env));
+            }
+            if let Some(workdir) = &inv.workdir {
+                println!("Workdir:           {workdir}");
+            }
+            if !inv.run_after.is_empty() {
+                println!("Runs after:        {}", inv.run_after.join(", "));
+            }
+            if !inv.artifacts.is_empty() {
+                println!("Failure artifacts: {}", inv.artifacts.join(", "));
+            }
+            if let Some(disabled_at) = inv.disabled_at {
+                println!(
+                    "Disabled:          {}",
+                    crate::timefmt::timestamp(disabled_at)
+                );
+            }
+            if inv.diff_base {
+                println!("Mode:              {}", diff_mode(inv));
+            }
+            println!(
+                "Created:           {}",
+                crate::timefmt::timestamp(inv.created_at)
+            );
+        }
+    } else if crate::output::is_plain() {
+        for inv in &invs {
+            let disabled = if inv.disabled_at.is_some() {
+                ", disabled"
+            } else {
+                ""
+            };
+            println!(
+                "Invariant {}: {}, scope {}, runs {}{disabled}.",
+                inv.name, inv.kind, inv.scope, inv.command
+            );
         }
     } else {
         // Table format: fixed-width columns.
+        let names: Vec<String> = invs
+            .iter()
+            .map(|i| {
+                if i.disabled_at.is_some() {
+                    format!("{} (disabled)", i.name)
+                } else {
+                    i.name.clone()
+                }
+            })
+            .collect();
         // Compute column widths.
-        let name_w = invs.iter().map(|i| i.name.len()).max().unwrap_or(4).max(4);
+        let name_w = names.iter().map(|n| n.len()).max().unwrap_or(4).max(4);
         let kind_w = invs
             .iter()
             .map(|i| i.kind.to_string().len())
@@ -203,10 +444,10 @@ async fn cmd_list(pool: &SqlitePool, verbose: bool) -> Result<()> {
         );
 
         // Rows
-        for inv in &invs {
+        for (inv, name) in invs.iter().zip(&names) {
             println!(
                 "{:<name_w$}  {:<kind_w$}  {:<cmd_w$}  {:<scope_w$}",
-                inv.name, inv.kind, inv.command, inv.scope,
+                name, inv.kind, inv.command, inv.scope,
             );
         }
     }
@@ -214,6 +455,22 @@ async fn cmd_list(pool: &SqlitePool, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// How a `diff_base` invariant is judged, e.g. "diff against base (higher
+/// is better, tolerance 2)".
+fn diff_mode(inv: &Invariant) -> String {
+    let direction = if inv.lower_is_better {
+        "lower"
+    } else {
+        "higher"
+    };
+    match inv.threshold {
+        Some(t) if t != 0.0 => {
+            format!("diff against base ({direction} is better, tolerance {t})")
+        }
+        _ => format!("diff against base ({direction} is better)"),
+    }
+}
+
 // -----------------------------------------------------------------------
 // gator invariant test <name>
 // -----------------------------------------------------------------------
@@ -236,7 +493,13 @@ async fn cmd_test(pool: &SqlitePool, name: &str) -> Result<()> {
         invariant.command,
         invariant.args.join(" "),
     );
-    println!("  working directory: {}", cwd.display());
+    println!(
+        "  working directory: {}",
+        runner::invariant_dir(&invariant, &cwd).display()
+    );
+    if !invariant.env.is_empty() {
+        println!("  env: {}", format_env(&invariant.env));
+    }
     println!();
 
     let result: InvariantResult = runner::run_invariant(&invariant, &cwd).await?;
@@ -244,13 +507,30 @@ async fn cmd_test(pool: &SqlitePool, name: &str) -> Result<()> {
     // Status line
     let status_label = if result.passed { "PASSED" } else { "FAILED" };
     println!("Result: {}", status_label);
-    println!(
-        "  Exit code: {}",
-        result
-            .exit_code
-            .map_or("unknown (signal)".to_owned(), |c| c.to_string()),
-    );
+    if result.timed_out {
+        println!(
+            "  Exit code: none (timed out after {}s)",
+            invariant.timeout_secs
+        );
+    } else {
+        println!(
+            "  Exit code: {}",
+            result
+                .exit_code
+                .map_or("unknown (signal)".to_owned(), |c| c.to_string()),
+        );
+    }
     println!("  Duration:  {}ms", result.duration_ms);
+    if invariant.diff_base {
+        match diff::parse_metric(&result.stdout) {
+            Some(value) => {
+                println!("  Value:     {value} (compared with the base branch at the gate)")
+            }
+            None => println!(
+                "  Value:     none (the gate fails a diff invariant that prints no number)"
+            ),
+        }
+    }
 
     // Stdout (truncated)
     if !result.stdout.is_empty() {
@@ -359,12 +639,395 @@ fn cmd_presets_list(project_type_filter: Option<&str>) -> Result<()> {
 }
 
 // -----------------------------------------------------------------------
-// gator invariant presets install [--project-type <type>]
+// gator invariant edit <name>
+// -----------------------------------------------------------------------
+
+/// Grouped parameters for the `edit` command. `None` keeps the current
+/// value.
+struct EditParams {
+    name: String,
+    rename: Option<String>,
+    kind: Option<String>,
+    command: Option<String>,
+    args: Option<String>,
+    description: Option<String>,
+    expected_exit_code: Option<i32>,
+    threshold: Option<f32>,
+    clear_threshold: bool,
+    scope: Option<String>,
+    timeout: Option<i32>,
+    diff_base: Option<bool>,
+    lower_is_better: Option<bool>,
+    env: Vec<String>,
+    unset_env: Vec<String>,
+    workdir: Option<String>,
+    clear_workdir: bool,
+    after: Option<String>,
+    artifacts: Option<String>,
+}
+
+/// Change the given fields of an invariant in place. Its id stays the same,
+/// so task links and past gate results follow the new definition.
+async fn cmd_edit(pool: &SqlitePool, params: EditParams) -> Result<()> {
+    let current = invariants::get_invariant_by_name(pool, &params.name)
+        .await?
+        .with_context(|| format!("invariant {:?} not found", params.name))?;
+
+    let name = params.rename.as_deref().unwrap_or(&current.name);
+    if name != current.name
+        && (invariants::get_invariant_by_name(pool, name)
+            .await?
+            .is_some()
+            || invariants::get_deleted_invariant_by_name(pool, name)
+                .await?
+                .is_some())
+    {
+        bail!("an invariant named {name:?} already exists");
+    }
+    let kind = match &params.kind {
+        Some(k) => parse_kind(k)?,
+        None => current.kind,
+    };
+    let scope = match &params.scope {
+        Some(s) => parse_scope(s)?,
+        None => current.scope,
+    };
+    let args = match &params.args {
+        Some(a) => parse_args(Some(a)),
+        None => current.args.0.clone(),
+    };
+    let threshold = if params.clear_threshold {
+        None
+    } else {
+        params.threshold.or(current.threshold)
+    };
+    let diff_base = params.diff_base.unwrap_or(current.diff_base);
+    let lower_is_better = params.lower_is_better.unwrap_or(current.lower_is_better);
+    if lower_is_better && !diff_base {
+        bail!("lower_is_better only applies to diff_base invariants");
+    }
+    let timeout_secs = params.timeout.unwrap_or(current.timeout_secs);
+    if timeout_secs <= 0 {
+        bail!("timeout must be positive, got {timeout_secs}");
+    }
+    let mut env = current.env.0.clone();
+    for key in &params.unset_env {
+        if env.remove(key).is_none() {
+            bail!("invariant {:?} has no env {key:?}", current.name);
+        }
+    }
+    parse_env(&params.env, &mut env)?;
+    let workdir = if params.clear_workdir {
+        None
+    } else if let Some(workdir) = &params.workdir {
+        runner::validate_workdir(workdir)?;
+        Some(workdir.as_str())
+    } else {
+        current.workdir.as_deref()
+    };
+    let run_after = match &params.after {
+        Some(a) => {
+            let after = parse_args(Some(a));
+            check_run_after(pool, name, Some(&current.name), &after).await?;
+            after
+        }
+        None => current.run_after.0.clone(),
+    };
+    let artifacts = match &params.artifacts {
+        Some(a) => parse_artifacts(Some(a))?,
+        None => current.artifacts.0.clone(),
+    };
+
+    let new = invariants::NewInvariant {
+        name,
+        description: params
+            .description
+            .as_deref()
+            .or(current.description.as_deref()),
+        kind,
+        command: params.command.as_deref().unwrap_or(&current.command),
+        args: &args,
+        expected_exit_code: params
+            .expected_exit_code
+            .unwrap_or(current.expected_exit_code),
+        threshold,
+        scope,
+        timeout_secs,
+        diff_base,
+        lower_is_better,
+        env: &env,
+        workdir,
+        run_after: &run_after,
+        artifacts: &artifacts,
+    };
+    let updated = invariants::update_invariant(pool, current.id, &new).await?;
+
+    let changes = describe_changes(&current, &updated);
+    if changes.is_empty() {
+        println!("Invariant {} unchanged.", updated.name);
+    } else {
+        println!("Invariant {} updated:", updated.name);
+        for change in changes {
+            println!("  {change}");
+        }
+    }
+    Ok(())
+}
+
+/// One line per field that differs between `before` and `after`, e.g.
+/// `command: cargo -> cargo-nextest`.
+fn describe_changes(before: &Invariant, after: &Invariant) -> Vec<String> {
+    let opt = |v: Option<String>| v.unwrap_or_else(|| "none".to_string());
+    let fields = [
+        ("name", before.name.clone(), after.name.clone()),
+        ("kind", before.kind.to_string(), after.kind.to_string()),
+        ("command", before.command.clone(), after.command.clone()),
+        (
+            "args",
+            opt(Some(before.args.join(" ")).filter(|a| !a.is_empty())),
+            opt(Some(after.args.join(" ")).filter(|a| !a.is_empty())),
+        ),
+        (
+            "description",
+            opt(before.description.clone()),
+            opt(after.description.clone()),
+        ),
+        (
+            "expected exit code",
+            before.expected_exit_code.to_string(),
+            after.expected_exit_code.to_string(),
+        ),
+        (
+            "threshold",
+            opt(before.threshold.map(|t| t.to_string())),
+            opt(after.threshold.map(|t| t.to_string())),
+        ),
+        ("scope", before.scope.to_string(), after.scope.to_string()),
+        (
+            "timeout",
+            format!("{}s", before.timeout_secs),
+            format!("{}s", after.timeout_secs),
+        ),
+        (
+            "diff_base",
+            before.diff_base.to_string(),
+            after.diff_base.to_string(),
+        ),
+        (
+            "lower_is_better",
+            before.lower_is_better.to_string(),
+            after.lower_is_better.to_string(),
+        ),
+        (
+            "env",
+            opt(Some(format_env(&before.env)).filter(|e| !e.is_empty())),
+            opt(Some(format_env(&after.env)).filter(|e| !e.is_empty())),
+        ),
+        (
+            "workdir",
+            opt(before.workdir.clone()),
+            opt(after.workdir.clone()),
+        ),
+        (
+            "run_after",
+            opt(Some(before.run_after.join(", ")).filter(|a| !a.is_empty())),
+            opt(Some(after.run_after.join(", ")).filter(|a| !a.is_empty())),
+        ),
+        (
+            "artifacts",
+            opt(Some(before.artifacts.join(", ")).filter(|a| !a.is_empty())),
+            opt(Some(after.artifacts.join(", ")).filter(|a| !a.is_empty())),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| format!("{field}: {old} -> {new}"))
+        .collect()
+}
+
+// -----------------------------------------------------------------------
+// gator invariant disable|enable <name>
+// -----------------------------------------------------------------------
+
+/// Disable or re-enable an invariant. Disabled invariants stay linked to
+/// their tasks, but the gate and `gator check` skip them.
+async fn cmd_set_disabled(pool: &SqlitePool, name: &str, disabled: bool) -> Result<()> {
+    let invariant = invariants::set_invariant_disabled(pool, name, disabled).await?;
+    if disabled {
+        println!("Invariant disabled: {}", invariant.name);
+        println!(
+            "Gates skip it until `gator invariant enable {}`.",
+            invariant.name
+        );
+    } else {
+        println!("Invariant enabled: {}", invariant.name);
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator invariant remove <name>
+// -----------------------------------------------------------------------
+
+/// Soft-delete an invariant. It stays restorable with `gator restore`.
+async fn cmd_remove(pool: &SqlitePool, name: &str) -> Result<()> {
+    crate::restore_cmd::prune_expired(pool).await?;
+
+    let invariant = invariants::soft_delete_invariant(pool, name).await?;
+
+    println!("Invariant removed: {}", invariant.name);
+    println!(
+        "Restorable for {} days with: gator restore invariant {}",
+        crate::restore_cmd::DELETED_RETENTION_DAYS,
+        invariant.name
+    );
+    let history = gate_results::count_gate_results_for_invariant(pool, invariant.id).await?;
+    if history > 0 {
+        println!(
+            "Its {history} gate result(s) are deleted with it when it is pruned. \
+             To keep them, restore it and run `gator invariant disable {}` instead.",
+            invariant.name
+        );
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator invariant audit [--project-type <type>]
+// -----------------------------------------------------------------------
+
+/// Report the health of the invariant registry:
+///
+/// - invariants no task links to;
+/// - presets for the project type that are not installed (removed ones are
+///   taken as deliberate);
+/// - invariants whose command is not on `PATH`;
+/// - tasks linked to removed invariants;
+/// - tasks linked to disabled invariants, which their gate skips.
+///
+/// Missing commands and removed invariants break gates, so they make the
+/// command fail.
+async fn cmd_audit(pool: &SqlitePool, project_type_override: Option<&str>) -> Result<()> {
+    let project_type = match project_type_override {
+        Some(pt) => {
+            let known = presets::available_project_types();
+            if !known.iter().any(|k| k == pt) {
+                bail!(
+                    "unknown project type {:?}; available types: {}",
+                    pt,
+                    known.join(", ")
+                );
+            }
+            Some(pt.to_string())
+        }
+        None => {
+            let cwd = std::env::current_dir().context("failed to get current directory")?;
+            presets::detect_project_type(&cwd)
+        }
+    };
+
+    let unreferenced = invariants::list_unreferenced_invariants(pool).await?;
+
+    let mut uninstalled = Vec::new();
+    if let Some(pt) = &project_type {
+        for preset in presets::presets_for_project_type(pt) {
+            if invariants::get_invariant_by_name(pool, &preset.name)
+                .await?
+                .is_none()
+                && invariants::get_deleted_invariant_by_name(pool, &preset.name)
+                    .await?
+                    .is_none()
+            {
+                uninstalled.push(preset.name);
+            }
+        }
+    }
+
+    let missing: Vec<Invariant> = invariants::list_invariants(pool)
+        .await?
+        .into_iter()
+        .filter(|inv| runner::command_available(&inv.command) == Some(false))
+        .collect();
+
+    let broken = invariants::list_links_to_deleted_invariants(pool).await?;
+    let skipped = invariants::list_links_to_disabled_invariants(pool).await?;
+
+    if !unreferenced.is_empty() {
+        println!("Invariants no task uses ({}):", unreferenced.len());
+        for inv in &unreferenced {
+            println!("  {}", inv.name);
+        }
+        println!();
+    }
+    match &project_type {
+        Some(pt) if !uninstalled.is_empty() => {
+            println!("{pt} presets not installed ({}):", uninstalled.len());
+            for name in &uninstalled {
+                println!("  {name}");
+            }
+            println!("Install them with `gator invariant presets install --project-type {pt}`.");
+            println!();
+        }
+        Some(_) => {}
+        None => {
+            println!("Project type not detected; use --project-type to check presets.");
+            println!();
+        }
+    }
+    if !missing.is_empty() {
+        println!("Commands not found on PATH ({}):", missing.len());
+        for inv in &missing {
+            println!("  {}: {}", inv.name, inv.command);
+        }
+        println!();
+    }
+    if !broken.is_empty() {
+        println!("Tasks linked to removed invariants ({}):", broken.len());
+        for link in &broken {
+            println!(
+                "  {}/{}: {} (restore with `gator restore invariant {}`)",
+                link.plan_name, link.task_name, link.invariant_name, link.invariant_name
+            );
+        }
+        println!();
+    }
+
+    if !skipped.is_empty() {
+        println!("Tasks linked to disabled invariants ({}):", skipped.len());
+        for link in &skipped {
+            println!(
+                "  {}/{}: {} (the gate skips it)",
+                link.plan_name, link.task_name, link.invariant_name
+            );
+        }
+        println!();
+    }
+
+    let problems = missing.len() + broken.len();
+    if problems > 0 {
+        bail!("{problems} invariant problem(s) would break gates");
+    }
+    if unreferenced.is_empty() && uninstalled.is_empty() && skipped.is_empty() {
+        println!("Invariant registry is healthy.");
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator invariant presets install [--project-type <type>] [--update]
 // -----------------------------------------------------------------------
 
 /// Detect project type (or use override) and register matching preset
-/// invariants in the database. Skips any that already exist.
-async fn cmd_presets_install(pool: &SqlitePool, project_type_override: Option<&str>) -> Result<()> {
+/// invariants in the database. Installed presets whose definition drifted
+/// from the embedded library are reported, and refreshed with `update`;
+/// soft-deleted ones are left alone.
+async fn cmd_presets_install(
+    pool: &SqlitePool,
+    project_type_override: Option<&str>,
+    update: bool,
+) -> Result<()> {
     let project_type = match project_type_override {
         Some(pt) => {
             let known = presets::available_project_types();
@@ -402,14 +1065,10 @@ async fn cmd_presets_install(pool: &SqlitePool, project_type_override: Option<&s
 
     let mut registered = vec![];
     let mut skipped = vec![];
+    let mut updated = vec![];
+    let mut drifted = vec![];
 
     for preset in &matching {
-        let existing = invariants::get_invariant_by_name(pool, &preset.name).await?;
-        if existing.is_some() {
-            skipped.push(preset.name.clone());
-            continue;
-        }
-
         let kind: InvariantKind = preset.kind.parse().map_err(|_| {
             anyhow::anyhow!(
                 "preset {:?} has invalid kind {:?}",
@@ -418,6 +1077,34 @@ async fn cmd_presets_install(pool: &SqlitePool, project_type_override: Option<&s
             )
         })?;
 
+        if let Some(existing) = invariants::get_invariant_by_name(pool, &preset.name).await? {
+            let drift = presets::preset_drift(preset, &existing);
+            if drift.is_empty() {
+                skipped.push(preset.name.clone());
+            } else if update {
+                invariants::update_invariant_definition(
+                    pool,
+                    existing.id,
+                    Some(&preset.description),
+                    kind,
+                    &preset.command,
+                    &preset.args,
+                )
+                .await?;
+                updated.push(format!("{} ({})", preset.name, drift.join(", ")));
+            } else {
+                drifted.push(format!("{} ({})", preset.name, drift.join(", ")));
+            }
+            continue;
+        }
+        if invariants::get_deleted_invariant_by_name(pool, &preset.name)
+            .await?
+            .is_some()
+        {
+            skipped.push(preset.name.clone());
+            continue;
+        }
+
         let new = invariants::NewInvariant {
             name: &preset.name,
             description: Some(&preset.description),
@@ -428,6 +1115,12 @@ async fn cmd_presets_install(pool: &SqlitePool, project_type_override: Option<&s
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         };
 
         invariants::insert_invariant(pool, &new).await?;
@@ -448,6 +1141,21 @@ async fn cmd_presets_install(pool: &SqlitePool, project_type_override: Option<&s
             skipped.join(", ")
         );
     }
+    if !updated.is_empty() {
+        println!(
+            "Updated {} invariant(s) to match the presets: {}",
+            updated.len(),
+            updated.join(", ")
+        );
+    }
+    if !drifted.is_empty() {
+        println!(
+            "{} invariant(s) differ from the presets: {}",
+            drifted.len(),
+            drifted.join(", ")
+        );
+        println!("Rerun with --update to refresh them.");
+    }
 
     Ok(())
 }
@@ -496,6 +1204,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clap_parses_invariant_rm_alias() {
+        for verb in ["remove", "rm"] {
+            let cli = TestCli::try_parse_from(["gator", "invariant", verb, "rust_lint"])
+                .expect("should parse");
+            match cli.command {
+                TestCommands::Invariant {
+                    command: InvariantCommands::Remove { name },
+                } => assert_eq!(name, "rust_lint"),
+                _ => panic!("expected Invariant Remove"),
+            }
+        }
+    }
+
     #[test]
     fn clap_parses_presets_list_with_type() {
         let cli = TestCli::try_parse_from([
@@ -528,10 +1250,15 @@ mod tests {
             TestCommands::Invariant {
                 command:
                     InvariantCommands::Presets {
-                        command: PresetCommands::Install { project_type },
+                        command:
+                            PresetCommands::Install {
+                                project_type,
+                                update,
+                            },
                     },
             } => {
                 assert!(project_type.is_none());
+                assert!(!update);
             }
             _ => panic!("expected Invariant Presets Install"),
         }
@@ -546,16 +1273,22 @@ mod tests {
             "install",
             "--project-type",
             "python",
+            "--update",
         ])
         .expect("should parse");
         match cli.command {
             TestCommands::Invariant {
                 command:
                     InvariantCommands::Presets {
-                        command: PresetCommands::Install { project_type },
+                        command:
+                            PresetCommands::Install {
+                                project_type,
+                                update,
+                            },
                     },
             } => {
                 assert_eq!(project_type.as_deref(), Some("python"));
+                assert!(update);
             }
             _ => panic!("expected Invariant Presets Install"),
         }
@@ -704,6 +1437,7 @@ mod tests {
                         threshold,
                         scope,
                         timeout,
+                        ..
                     },
             } => {
                 assert_eq!(name, "rust_build");
@@ -747,6 +1481,12 @@ mod tests {
                         threshold,
                         scope,
                         timeout,
+                        diff_base,
+                        lower_is_better,
+                        env,
+                        workdir,
+                        after,
+                        artifacts,
                     },
             } => {
                 assert_eq!(name, "my_check");
@@ -758,11 +1498,127 @@ mod tests {
                 assert!(threshold.is_none());
                 assert_eq!(scope, "project"); // default
                 assert_eq!(timeout, 300); // default
+                assert!(!diff_base && !lower_is_better);
+                assert!(env.is_empty() && workdir.is_none() && after.is_none());
+                assert!(artifacts.is_none());
             }
             _ => panic!("expected Invariant Add command"),
         }
     }
 
+    #[test]
+    fn clap_lower_is_better_requires_diff_base() {
+        let base = [
+            "gator",
+            "invariant",
+            "add",
+            "size",
+            "--kind",
+            "custom",
+            "--command",
+            "./size.sh",
+        ];
+        let with = |extra: &[&'static str]| {
+            TestCli::try_parse_from(base.iter().chain(extra).copied().collect::<Vec<_>>())
+        };
+        assert!(with(&["--lower-is-better"]).is_err());
+        assert!(with(&["--diff-base", "--lower-is-better"]).is_ok());
+    }
+
+    #[test]
+    fn clap_parses_edit_with_optional_fields() {
+        let cli = TestCli::try_parse_from([
+            "gator",
+            "invariant",
+            "edit",
+            "rust_test",
+            "--command",
+            "cargo-nextest",
+            "--args",
+            "",
+            "--clear-threshold",
+            "--diff-base",
+            "true",
+        ])
+        .expect("should parse");
+        match cli.command {
+            TestCommands::Invariant {
+                command:
+                    InvariantCommands::Edit {
+                        name,
+                        command,
+                        args,
+                        kind,
+                        threshold,
+                        clear_threshold,
+                        diff_base,
+                        lower_is_better,
+                        ..
+                    },
+            } => {
+                assert_eq!(name, "rust_test");
+                assert_eq!(command.as_deref(), Some("cargo-nextest"));
+                assert_eq!(args.as_deref(), Some(""));
+                assert!(kind.is_none() && threshold.is_none());
+                assert!(clear_threshold);
+                assert_eq!(diff_base, Some(true));
+                assert_eq!(lower_is_better, None);
+            }
+            _ => panic!("expected Invariant Edit"),
+        }
+        assert!(
+            TestCli::try_parse_from([
+                "gator",
+                "invariant",
+                "edit",
+                "x",
+                "--threshold",
+                "1",
+                "--clear-threshold",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn describe_changes_lists_changed_fields_only() {
+        let before = Invariant {
+            id: uuid::Uuid::new_v4(),
+            name: "tests".to_owned(),
+            description: None,
+            kind: InvariantKind::TestSuite,
+            command: "cargo".to_owned(),
+            args: sqlx::types::Json(vec!["test".to_owned()]),
+            expected_exit_code: 0,
+            threshold: Some(80.0),
+            scope: InvariantScope::Project,
+            timeout_secs: 300,
+            created_at: chrono::Utc::now(),
+            deleted_at: None,
+            diff_base: false,
+            lower_is_better: false,
+            disabled_at: None,
+            env: Default::default(),
+            workdir: None,
+            run_after: Default::default(),
+            artifacts: Default::default(),
+        };
+        let mut after = before.clone();
+        after.command = "cargo-nextest".to_owned();
+        after.args.0 = vec!["run".to_owned()];
+        after.threshold = None;
+
+        assert_eq!(
+            describe_changes(&before, &after),
+            [
+                "command: cargo -> cargo-nextest",
+                "args: test -> run",
+                "threshold: 80 -> none",
+            ]
+        );
+        assert!(describe_changes(&before, &before).is_empty());
+    }
+
     #[test]
     fn clap_add_missing_kind_fails() {
         let result =
@@ -1,29 +1,72 @@
-//! `gator log` command: show agent events for a task.
+//! `gator log` command: show agent events for a task, or export its
+//! transcript.
 
-use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use gator_db::models::AgentEvent;
-use gator_db::queries::agent_events;
+use gator_db::queries::agent_events::{self, EventFilter};
 use gator_db::queries::tasks as task_db;
 
+use crate::transcript::{self, TranscriptFormat};
+
 /// Run the log command.
-pub async fn run_log(pool: &SqlitePool, task_id_str: &str, attempt: Option<i32>) -> Result<()> {
-    let task_id =
-        Uuid::parse_str(task_id_str).with_context(|| format!("invalid task ID: {task_id_str}"))?;
+///
+/// Events are those of `task_id_str`, of the dispatch run `run_str`, or of
+/// the single task lifecycle `attempt_id_str`; filters given together must
+/// all match.
+pub async fn run_log(
+    pool: &SqlitePool,
+    task_id_str: Option<&str>,
+    attempt: Option<i32>,
+    run_str: Option<&str>,
+    attempt_id_str: Option<&str>,
+) -> Result<()> {
+    let filter = EventFilter {
+        task_id: task_id_str.map(|s| parse_id(s, "task")).transpose()?,
+        attempt,
+        run_id: run_str.map(|s| parse_id(s, "run")).transpose()?,
+        attempt_id: attempt_id_str.map(|s| parse_id(s, "attempt")).transpose()?,
+    };
+    if filter.task_id.is_none() && filter.run_id.is_none() && filter.attempt_id.is_none() {
+        bail!("give a task ID, --run, or --attempt-id");
+    }
 
-    let task = task_db::get_task(pool, task_id)
-        .await?
-        .with_context(|| format!("task {task_id} not found"))?;
+    if let Some(task_id) = filter.task_id {
+        let task = task_db::get_task(pool, task_id)
+            .await?
+            .with_context(|| format!("task {task_id} not found"))?;
 
-    println!("Task: {} ({})", task.name, task.id);
-    println!("Status: {} (attempt {})", task.status, task.attempt);
+        println!("Task: {} ({})", task.name, task.id);
+        println!("Status: {} (attempt {})", task.status, task.attempt);
+        if let (Some(run_id), Some(attempt_id)) = (task.run_id, task.attempt_id) {
+            println!("Run: {run_id} (attempt ID {attempt_id})");
+        }
+    }
+    if let Some(run_id) = filter.run_id {
+        println!("Run: {run_id}");
+    }
+    if let Some(attempt_id) = filter.attempt_id {
+        println!("Attempt ID: {attempt_id}");
+    }
     println!();
 
-    let events: Vec<AgentEvent> = match attempt {
-        Some(a) => agent_events::list_events_for_task(pool, task_id, a).await?,
-        None => agent_events::list_all_events_for_task(pool, task_id).await?,
+    let events: Vec<AgentEvent> = match filter {
+        EventFilter {
+            task_id: Some(task_id),
+            attempt,
+            run_id: None,
+            attempt_id: None,
+        } => match attempt {
+            Some(a) => agent_events::list_events_for_task(pool, task_id, a).await?,
+            None => agent_events::list_all_events_for_task(pool, task_id).await?,
+        },
+        _ => agent_events::list_matching_events(pool, &filter).await?,
     };
 
     if events.is_empty() {
@@ -31,12 +74,36 @@ pub async fn run_log(pool: &SqlitePool, task_id_str: &str, attempt: Option<i32>)
         return Ok(());
     }
 
+    // Events of a run span tasks, so name the task on each line.
+    let mut names: HashMap<Uuid, String> = HashMap::new();
+    if filter.task_id.is_none() {
+        for event in &events {
+            if let Entry::Vacant(slot) = names.entry(event.task_id) {
+                let name = task_db::get_task(pool, event.task_id)
+                    .await?
+                    .map_or_else(|| event.task_id.to_string(), |t| t.name);
+                slot.insert(name);
+            }
+        }
+    }
+
     println!("Events ({}):", events.len());
     for event in &events {
-        let time = event.recorded_at.format("%H:%M:%S%.3f");
+        let time = crate::timefmt::format(event.recorded_at, "%H:%M:%S%.3f");
         let summary = summarize_event_payload(&event.event_type, &event.payload);
+        let task = names
+            .get(&event.task_id)
+            .map(|name| format!("{name} "))
+            .unwrap_or_default();
+        if crate::output::is_plain() {
+            println!(
+                "  {time}, {task}attempt {}, {}: {summary}",
+                event.attempt, event.event_type
+            );
+            continue;
+        }
         println!(
-            "  [{time}] [attempt {}] {}: {summary}",
+            "  [{time}] [{task}attempt {}] {}: {summary}",
             event.attempt, event.event_type
         );
     }
@@ -44,6 +111,28 @@ pub async fn run_log(pool: &SqlitePool, task_id_str: &str, attempt: Option<i32>)
     Ok(())
 }
 
+/// Write the transcript of `task_id_str` (only attempt `attempt`, if given)
+/// to `path`.
+pub async fn run_export(
+    pool: &SqlitePool,
+    task_id_str: &str,
+    attempt: Option<i32>,
+    path: &Path,
+) -> Result<()> {
+    let task_id = parse_id(task_id_str, "task")?;
+    transcript::export_transcript(pool, task_id, attempt, path).await?;
+    let format = match TranscriptFormat::from_path(path) {
+        TranscriptFormat::Markdown => "Markdown",
+        TranscriptFormat::Html => "HTML",
+    };
+    println!("Wrote {format} transcript to {}", path.display());
+    Ok(())
+}
+
+fn parse_id(s: &str, what: &str) -> Result<Uuid> {
+    Uuid::parse_str(s).with_context(|| format!("invalid {what} ID: {s}"))
+}
+
 /// Generate a one-line summary from an event's type and payload.
 fn summarize_event_payload(event_type: &str, payload: &serde_json::Value) -> String {
     match event_type {
@@ -1,18 +1,41 @@
+mod admin_cmd;
 mod agent;
+mod audit_cmd;
+mod banner;
 mod cleanup_cmd;
 mod config;
+mod daemon;
 mod dispatch_cmd;
+mod editor;
+mod eval_cmd;
 mod export_cmd;
+mod fanout_cmd;
+mod federate;
 mod gate_cmd;
+mod gc_cmd;
 mod invariant_cmds;
 mod log_cmd;
 mod merge_cmd;
+mod note_cmd;
+mod output;
 mod plan_cmds;
 mod pr_cmd;
 mod report_cmd;
 pub(crate) mod resolve;
+mod restore_cmd;
+mod resume_cmd;
+mod review_cmd;
+mod schedule_cmd;
+mod secret_cmd;
 mod serve_cmd;
+mod snapshot;
+mod stats_cmd;
 mod status_cmd;
+mod task_cmd;
+mod telemetry;
+mod timefmt;
+mod transcript;
+mod triage_cmd;
 mod tui;
 
 use anyhow::Context;
@@ -21,6 +44,7 @@ use clap_complete::{Shell, generate};
 
 use gator_core::token::guard;
 use gator_db::pool;
+use gator_db::queries::stats::StatsPeriod;
 
 use config::GatorConfig;
 
@@ -31,6 +55,11 @@ struct Cli {
     #[arg(long, global = true)]
     database_url: Option<String>,
 
+    /// Screen-reader friendly output: sentences instead of tables and
+    /// glyphs, and no full-screen TUI (also GATOR_PLAIN=1)
+    #[arg(long, global = true)]
+    plain: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -63,29 +92,153 @@ pub enum Commands {
         /// Timeout per task in seconds
         #[arg(long, default_value_t = 1800)]
         timeout: u64,
+        /// Pop desktop notifications as tasks finish, fail, or need review
+        #[arg(long)]
+        notify: bool,
+        /// After Ctrl+C, wait this many seconds for running agents to
+        /// finish before stopping
+        #[arg(long, default_value_t = 10)]
+        drain_timeout: u64,
+        /// Report a stall after this many seconds without scheduling
+        /// progress or agent activity (0 disables the watchdog)
+        #[arg(long, default_value_t = 600)]
+        stall_timeout: u64,
+        /// On a stall, re-scan state and recover (fail tasks with no agent,
+        /// restore leaked agent slots)
+        #[arg(long)]
+        self_heal: bool,
+        /// Kill and retry an agent that emits no events and reports no
+        /// progress for this many seconds (0 disables; the per-task
+        /// --timeout still applies)
+        #[arg(long, default_value_t = 0)]
+        agent_stall_timeout: u64,
+        /// Maximum number of invariants each task's gate runs at once
+        #[arg(long, default_value_t = gator_core::gate::DEFAULT_GATE_PARALLELISM)]
+        gate_parallelism: usize,
+        /// Run every invariant, even when a retry left the worktree identical
+        /// to an earlier attempt whose results could be reused
+        #[arg(long)]
+        no_cache: bool,
+        /// Print the execution waves, token estimates, and scheduling issues
+        /// without creating worktrees or spawning agents
+        #[arg(long)]
+        dry_run: bool,
+        /// With --dry-run, estimate the cost at this many US dollars per
+        /// million tokens
+        #[arg(long, value_name = "USD", requires = "dry_run")]
+        usd_per_mtok: Option<f64>,
+        /// Run only these tasks (comma-separated names or IDs); each of
+        /// their dependencies must be selected too or already passed
+        #[arg(long, value_name = "TASKS", value_delimiter = ',', conflicts_with_all = ["from", "until", "dry_run"])]
+        only: Vec<String>,
+        /// Run this task and every task that depends on it
+        #[arg(long, value_name = "TASK", conflicts_with = "dry_run")]
+        from: Option<String>,
+        /// Run this task and every task it depends on
+        #[arg(long, value_name = "TASK", conflicts_with = "dry_run")]
+        until: Option<String>,
+    },
+    /// Resume a plan that stopped on its token budget, with a higher budget
+    Resume {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// New token budget for the whole plan (must exceed tokens used so far)
+        #[arg(long)]
+        budget: i64,
+        /// Maximum number of concurrent agents
+        #[arg(long, default_value_t = 4)]
+        max_agents: usize,
+        /// Timeout per task in seconds
+        #[arg(long, default_value_t = 1800)]
+        timeout: u64,
+        /// Pop desktop notifications as tasks finish, fail, or need review
+        #[arg(long)]
+        notify: bool,
     },
     /// Show plan status and task progress (omit plan_id to list all plans)
     Status {
         /// Plan ID (UUID or plan.toml path) to show status for (omit to list all plans)
         plan_id: Option<String>,
+        /// Output format (json keeps timestamps in ISO-8601 UTC)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Explain why each pending task is not running yet
+        #[arg(long, requires = "plan_id")]
+        why: bool,
+        /// List at most this many tasks in text output (0 lists all)
+        #[arg(long, default_value_t = 200, requires = "plan_id")]
+        limit: usize,
+        /// Write a markdown snapshot (plans, tasks, gate results, recent
+        /// errors) to this file instead of printing status
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["output", "why"])]
+        snapshot: Option<std::path::PathBuf>,
+        /// List the plans of every database in this federation file
+        /// (opened read-only) instead of the configured database
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["plan_id", "snapshot"])]
+        federate: Option<std::path::PathBuf>,
     },
-    /// Show agent event log for a task
+    /// Show agent event log for a task, dispatch run, or attempt
     Log {
         /// Task ID to show events for
+        task_id: Option<String>,
+        /// Filter to a specific attempt number
+        #[arg(long)]
+        attempt: Option<i32>,
+        /// Filter to one `gator dispatch` run (its `run_id` in the logs)
+        #[arg(long)]
+        run: Option<String>,
+        /// Filter to one task lifecycle (its `attempt_id` in the logs)
+        #[arg(long)]
+        attempt_id: Option<String>,
+        /// Write the task's transcript -- messages, tool calls and results,
+        /// token usage, and gate outcomes per attempt -- to this file, as
+        /// HTML for .html files and Markdown otherwise
+        #[arg(long, value_name = "PATH", requires = "task_id", conflicts_with_all = ["run", "attempt_id"])]
+        export: Option<std::path::PathBuf>,
+    },
+    /// Show the agent-mode commands an agent ran for a task
+    Audit {
+        /// Task ID to show agent commands for
         task_id: String,
         /// Filter to a specific attempt number
         #[arg(long)]
         attempt: Option<i32>,
     },
+    /// Add or list operator notes on a plan or task
+    Note {
+        #[command(subcommand)]
+        command: NoteCommands,
+    },
     /// Approve a task awaiting human review
     Approve {
         /// Task ID to approve
         task_id: String,
+        /// Commit only these worktree paths (comma-separated) and revert
+        /// the rest
+        #[arg(long, value_delimiter = ',')]
+        files: Vec<String>,
     },
     /// Reject a task awaiting human review (sends to failed for retry/escalation)
     Reject {
         /// Task ID to reject
         task_id: String,
+        /// What to fix; quoted in the next attempt's prompt
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Answer the question a task's agent asked with `gator ask`
+    Answer {
+        /// Task ID of the blocked task
+        task_id: String,
+        /// The answer; the agent's session resumes with it
+        response: String,
+    },
+    /// Cancel a task mid-flight: its dispatch kills the agent and frees
+    /// its slot, and the plan continues without it (same as `gator task
+    /// cancel`)
+    Cancel {
+        /// Task ID
+        task_id: String,
     },
     /// Retry a failed or escalated task
     Retry {
@@ -94,24 +247,87 @@ pub enum Commands {
         /// Override retry_max limit
         #[arg(long)]
         force: bool,
+        /// Environment variable for the retried attempt's agent and gate,
+        /// as KEY=VALUE (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Edit the task description in $EDITOR first; the old description
+        /// is kept as a revision (see `gator plan describe --history`)
+        #[arg(long)]
+        edit: bool,
     },
     /// View gate results for a task
     Gate {
         /// Task ID to view gate results for
         task_id: String,
+        /// List the files collected from failing invariants
+        #[arg(long)]
+        artifacts: bool,
     },
     /// Show token usage and duration report for a plan
     Report {
         /// Plan ID (UUID) or path to a plan TOML file
         plan_id: String,
+        /// Output format: text, json (the plan_report schema document),
+        /// csv (one row per task), or md
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+        /// Estimate the cost at this many US dollars per million tokens
+        #[arg(long, value_name = "USD")]
+        usd_per_mtok: Option<f64>,
     },
-    /// Remove worktrees for completed tasks in a plan
+    /// Show analytics across all plans: success rate per harness, attempts
+    /// per task scope, token usage over time, and failing invariants
+    Stats {
+        /// Only count work finished within this long, e.g. 30d
+        #[arg(long, value_name = "AGE", value_parser = cleanup_cmd::parse_age)]
+        since: Option<chrono::Duration>,
+        /// Sum token usage per day, week, or month
+        #[arg(long, default_value_t = StatsPeriod::Week)]
+        period: StatsPeriod,
+        /// Estimate the cost at this many US dollars per million tokens
+        #[arg(long, value_name = "USD")]
+        usd_per_mtok: Option<f64>,
+        /// List at most this many failing invariants
+        #[arg(long, default_value_t = 10)]
+        limit: i64,
+    },
+    /// Remove worktrees for completed tasks in a plan, and strip old gate output
     Cleanup {
         /// Plan ID (UUID) or path to a plan TOML file
-        plan_id: String,
+        plan_id: Option<String>,
         /// Remove worktrees for all tasks (not just passed)
-        #[arg(long)]
+        #[arg(long, requires = "plan_id")]
         all: bool,
+        /// Strip stdout/stderr from gate, setup, and warm-up results older than
+        /// this many days, across all plans (default: cleanup.gate_output_days
+        /// in the config file)
+        #[arg(long, value_name = "DAYS")]
+        gate_output_days: Option<u32>,
+        /// Remove the worktrees of finished tasks, across all plans, idle
+        /// longer than this, e.g. 7d (default: cleanup.max_age in the config file)
+        #[arg(long, value_name = "AGE", value_parser = cleanup_cmd::parse_age)]
+        max_age: Option<chrono::Duration>,
+        /// Remove the worktrees of finished tasks, oldest first, while less
+        /// than this is free on their disk, e.g. 20G (default:
+        /// cleanup.min_free in the config file)
+        #[arg(long, value_name = "SIZE", value_parser = gator_core::worktree::disk::parse_size)]
+        min_free: Option<u64>,
+    },
+    /// Garbage-collect merged worktrees and branches, dangling branches,
+    /// orphaned containers, and old agent events across all plans
+    Gc {
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete agent events (except token usage) older than this many
+        /// days (default: cleanup.agent_event_days in the config file, or 30)
+        #[arg(long, value_name = "DAYS")]
+        event_days: Option<u32>,
+        /// Also delete dangling gator/* branches that are not merged into a
+        /// base branch, losing their commits
+        #[arg(long)]
+        force: bool,
     },
     /// Merge passed task branches into the base branch
     Merge {
@@ -120,6 +336,18 @@ pub enum Commands {
         /// Show what would be merged without doing it
         #[arg(long)]
         dry_run: bool,
+        /// On a conflict, choose to resolve it in a worktree, skip the branch, or abort
+        #[arg(long, short, conflicts_with = "dry_run")]
+        interactive: bool,
+        /// Land the resolved conflict and merge the remaining branches
+        #[arg(long = "continue", conflicts_with_all = ["dry_run", "interactive", "skip", "abort"])]
+        resume: bool,
+        /// Drop the conflicted branch and merge the remaining ones
+        #[arg(long, conflicts_with_all = ["dry_run", "interactive", "abort"])]
+        skip: bool,
+        /// Drop the conflicted branch and stop
+        #[arg(long, conflicts_with_all = ["dry_run", "interactive"])]
+        abort: bool,
     },
     /// Create a GitHub PR from a completed plan
     Pr {
@@ -132,6 +360,79 @@ pub enum Commands {
         #[arg(long)]
         base: Option<String>,
     },
+    /// Restore a deleted plan or invariant (omit the target to list them)
+    Restore {
+        #[command(subcommand)]
+        target: Option<RestoreCommands>,
+    },
+    /// Operator controls that span every plan
+    Admin {
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+    /// Rotate, list, or retire agent token secrets
+    Secret {
+        #[command(subcommand)]
+        command: SecretCommands,
+    },
+    /// Maintain the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Triage new issues from a labelled queue on a schedule
+    Triage {
+        #[command(subcommand)]
+        command: TriageCommands,
+    },
+    /// Run one templated plan across many repositories and open a PR in each
+    Fanout {
+        /// File listing repositories, one per line (owner/name, git URL, or path)
+        #[arg(long)]
+        repos: String,
+        /// Plan template; may use {{repo}}, {{repo_name}}, and {{base_branch}}
+        #[arg(long)]
+        plan: String,
+        /// Directory to clone repositories into
+        #[arg(long, default_value = "gator-fanout")]
+        workdir: String,
+        /// Number of repositories to run at once
+        #[arg(long, default_value_t = 2)]
+        parallel: usize,
+        /// Maximum concurrent agents per repository
+        #[arg(long, default_value_t = 2)]
+        max_agents: usize,
+        /// Per-task timeout in seconds
+        #[arg(long, default_value_t = 1800)]
+        timeout: u64,
+        /// Remote to push fan-out branches to
+        #[arg(long, default_value = "origin")]
+        remote: String,
+        /// Run the plans but do not push branches or open PRs
+        #[arg(long)]
+        no_pr: bool,
+        /// Open PRs as drafts
+        #[arg(long)]
+        draft: bool,
+        /// Also write the cross-repository report as Markdown to this file
+        #[arg(long)]
+        report: Option<String>,
+    },
+    /// Run golden-task regression suites against gator itself
+    Eval {
+        #[command(subcommand)]
+        command: EvalCommands,
+    },
+    /// Review open pull requests with agents and post the reviews
+    Review {
+        #[command(subcommand)]
+        command: ReviewCommands,
+    },
+    /// Clone and dispatch plans on a cron schedule
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -147,14 +448,32 @@ pub enum Commands {
         bind: String,
     },
     /// Launch interactive TUI dashboard
-    Dashboard,
+    Dashboard {
+        /// Pop desktop notifications as tasks finish, fail, or need review
+        #[arg(long)]
+        notify: bool,
+        /// Print periodic plain-text summaries instead of the full-screen TUI
+        /// (implied by --plain)
+        #[arg(long)]
+        no_tui: bool,
+        /// Seconds between summaries with --no-tui
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+        /// Watch every database in this federation file (opened read-only)
+        /// instead of the configured database; implies --no-tui
+        #[arg(long, value_name = "FILE")]
+        federate: Option<std::path::PathBuf>,
+    },
     /// Export data in machine-readable formats
     Export {
         #[command(subcommand)]
         command: ExportCommands,
     },
-    /// Read your assigned task (agent mode)
-    Task,
+    /// Read your assigned task (agent mode), or act on a task (operator)
+    Task {
+        #[command(subcommand)]
+        command: Option<TaskCommands>,
+    },
     /// Run invariants for your task (agent mode)
     Check,
     /// Report progress (agent mode)
@@ -164,6 +483,11 @@ pub enum Commands {
     },
     /// Signal task completion (agent mode)
     Done,
+    /// Ask the operator a question and wait for the answer (agent mode)
+    Ask {
+        /// The question; end your turn after asking
+        question: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -212,18 +536,52 @@ pub enum PlanCommands {
     },
     /// Create a plan from a TOML file
     Create {
-        /// Path to the plan TOML file
+        /// Path to the plan TOML file, or `-` to read it from stdin
         file: String,
+        /// Make tasks whose declared paths overlap depend on each other,
+        /// in plan order, instead of running in parallel
+        #[arg(long)]
+        infer_deps: bool,
     },
     /// Show plan details (or list all plans)
     Show {
         /// Plan ID (UUID or plan.toml path) to show (omit to list all)
         plan_id: Option<String>,
+        /// Print task descriptions in full instead of the first 10 lines
+        #[arg(long)]
+        full: bool,
+        /// Show at most this many tasks (0 shows all)
+        #[arg(long, default_value_t = 50, requires = "plan_id")]
+        limit: usize,
+        /// Skip this many tasks, to page through a large plan
+        #[arg(long, default_value_t = 0, requires = "plan_id")]
+        offset: usize,
+        /// List archived plans instead of active ones
+        #[arg(long, conflicts_with = "plan_id")]
+        archived: bool,
+    },
+    /// Print a task's full description
+    Describe {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Task name or task ID
+        task: String,
+        /// List the descriptions replaced by `gator retry --edit` too
+        #[arg(long)]
+        history: bool,
     },
-    /// Approve a plan for execution
+    /// Predict file conflicts between tasks that can run in parallel
+    Analyze {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+    },
+    /// Approve a plan for execution and show its risk score
     Approve {
         /// Plan ID (UUID) or path to a plan TOML file
         plan_id: String,
+        /// Show the risk score without approving
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Export a plan from the database as TOML
     Export {
@@ -233,11 +591,196 @@ pub enum PlanCommands {
         #[arg(long)]
         output: Option<String>,
     },
+    /// Show how a created plan's TOML file differs from the stored plan
+    Diff {
+        /// Path to the plan TOML file (with the id `plan create` wrote)
+        file: String,
+    },
+    /// Apply a plan file's safe changes (descriptions of tasks not yet
+    /// started, new tasks) to a plan that is not running
+    Sync {
+        /// Path to the plan TOML file (with the id `plan create` wrote)
+        file: String,
+    },
     /// Reset a failed plan so it can be re-dispatched
     Reset {
         /// Plan ID (UUID) or path to a plan TOML file
         plan_id: String,
     },
+    /// Copy a plan, its tasks, dependencies, and invariant links into a new
+    /// draft plan
+    Clone {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Reset every task's attempt counter to 0 (full retry budget)
+        #[arg(long)]
+        reset: bool,
+        /// Name for the new plan (defaults to the original's)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Hide a finished plan from listings without deleting it
+    Archive {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+    },
+    /// Bring an archived plan back into listings
+    Unarchive {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+    },
+    /// Delete a plan and its tasks (restorable with `gator restore`)
+    Delete {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Also delete the plan's agent event logs now (not restorable)
+        #[arg(long)]
+        purge_events: bool,
+        /// Also remove the plan's task worktrees and branches
+        #[arg(long)]
+        remove_worktrees: bool,
+    },
+    /// Add, remove, or edit tasks of a draft plan
+    Task {
+        #[command(subcommand)]
+        command: PlanTaskCommands,
+    },
+    /// Generate a plan TOML from open GitHub issues (one task per issue)
+    ImportIssues {
+        /// Repository to import from (owner/name)
+        #[arg(long)]
+        repo: String,
+        /// Only import issues with this label
+        #[arg(long, default_value = "gator")]
+        label: String,
+        /// Output file path
+        #[arg(long, short, default_value = "plan.toml")]
+        output: String,
+        /// Plan name (defaults to "<repo-name>-issues")
+        #[arg(long)]
+        name: Option<String>,
+        /// Override auto-detected base branch
+        #[arg(long)]
+        base_branch: Option<String>,
+        /// Scope level for every task
+        #[arg(long, default_value = "medium")]
+        scope: String,
+        /// Gate policy for every task
+        #[arg(long, default_value = "auto")]
+        gate: String,
+        /// Invariant to link to every task (repeatable)
+        #[arg(long = "invariant")]
+        invariants: Vec<String>,
+        /// Maximum number of issues to fetch
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlanTaskCommands {
+    /// Add a task to a draft plan
+    Add {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Task name (unique within the plan)
+        name: String,
+        /// Task description
+        #[arg(long, conflicts_with = "description_file")]
+        description: Option<String>,
+        /// Read the task description from a file (`-` for stdin)
+        #[arg(long)]
+        description_file: Option<String>,
+        /// Scope level: narrow, medium, or broad
+        #[arg(long, default_value = "medium")]
+        scope: String,
+        /// Gate policy: auto, human_review, human_approve, script:<path>, or llm_review[:<harness>]
+        #[arg(long, default_value = "auto")]
+        gate: String,
+        /// Maximum retry attempts before escalation
+        #[arg(long, default_value_t = 3)]
+        retry_max: i32,
+        /// Task this one depends on (repeatable)
+        #[arg(long = "depends-on")]
+        depends_on: Vec<String>,
+        /// Task this one only runs after, without needing its changes
+        /// (repeatable)
+        #[arg(long = "ordering-only")]
+        ordering_only: Vec<String>,
+        /// Invariant to link (repeatable)
+        #[arg(long = "invariant")]
+        invariants: Vec<String>,
+        /// Harness override for this task
+        #[arg(long)]
+        harness: Option<String>,
+        /// Source issue reference (owner/repo#N)
+        #[arg(long)]
+        issue: Option<String>,
+        /// Task kind: code or artifact
+        #[arg(long, default_value = "code")]
+        kind: String,
+        /// File an artifact task must produce (repeatable)
+        #[arg(long = "artifact")]
+        artifacts: Vec<String>,
+        /// Path pattern the task may modify, e.g. crates/foo/** (repeatable)
+        #[arg(long = "path")]
+        paths: Vec<String>,
+    },
+    /// Remove a task from a draft plan
+    Remove {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Task name
+        name: String,
+    },
+    /// Change a task of a draft plan
+    Edit {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+        /// Task name
+        name: String,
+        /// Rename the task
+        #[arg(long)]
+        rename: Option<String>,
+        /// Replace the task description
+        #[arg(long, conflicts_with = "description_file")]
+        description: Option<String>,
+        /// Replace the task description with a file's contents (`-` for stdin)
+        #[arg(long)]
+        description_file: Option<String>,
+        /// Scope level: narrow, medium, or broad
+        #[arg(long)]
+        scope: Option<String>,
+        /// Gate policy: auto, human_review, human_approve, script:<path>, or llm_review[:<harness>]
+        #[arg(long)]
+        gate: Option<String>,
+        /// Maximum retry attempts before escalation
+        #[arg(long)]
+        retry_max: Option<i32>,
+        /// Harness override for this task
+        #[arg(long)]
+        harness: Option<String>,
+        /// Source issue reference (owner/repo#N)
+        #[arg(long)]
+        issue: Option<String>,
+        /// Add a dependency, or make an ordering-only one need its changes
+        /// (repeatable)
+        #[arg(long = "add-dep")]
+        add_deps: Vec<String>,
+        /// Add an ordering-only dependency, or make an existing one
+        /// ordering-only (repeatable)
+        #[arg(long = "add-ordering-dep")]
+        add_ordering_deps: Vec<String>,
+        /// Remove a dependency (repeatable)
+        #[arg(long = "remove-dep")]
+        remove_deps: Vec<String>,
+        /// Link an invariant (repeatable)
+        #[arg(long = "add-invariant")]
+        add_invariants: Vec<String>,
+        /// Unlink an invariant (repeatable)
+        #[arg(long = "remove-invariant")]
+        remove_invariants: Vec<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -270,6 +813,27 @@ pub enum InvariantCommands {
         /// Timeout in seconds (default: 300)
         #[arg(long, default_value_t = 300)]
         timeout: i32,
+        /// Also run on the base branch and fail only if the last number the
+        /// command prints regresses by more than --threshold
+        #[arg(long)]
+        diff_base: bool,
+        /// With --diff-base: a smaller number is better (e.g. binary size)
+        #[arg(long, requires = "diff_base")]
+        lower_is_better: bool,
+        /// Environment variable for the command, as KEY=VALUE (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Directory to run the command in, relative to the worktree root
+        #[arg(long)]
+        workdir: Option<String>,
+        /// Comma-separated invariants that must finish first when a gate
+        /// runs both (e.g. rust_build)
+        #[arg(long, value_name = "NAMES")]
+        after: Option<String>,
+        /// Comma-separated globs, relative to the worktree root, of files
+        /// to keep when the invariant fails (e.g. test-results/**/*.png)
+        #[arg(long, value_name = "GLOBS")]
+        artifacts: Option<String>,
     },
     /// List all invariants
     List {
@@ -282,6 +846,89 @@ pub enum InvariantCommands {
         /// Invariant name to test
         name: String,
     },
+    /// Change an invariant's definition; task links and gate results are kept
+    Edit {
+        /// Invariant name
+        name: String,
+        /// Rename the invariant
+        #[arg(long)]
+        rename: Option<String>,
+        /// Kind of invariant: test_suite, typecheck, lint, coverage, custom
+        #[arg(long)]
+        kind: Option<String>,
+        /// Command to execute
+        #[arg(long)]
+        command: Option<String>,
+        /// Comma-separated arguments; an empty string clears them
+        #[arg(long)]
+        args: Option<String>,
+        /// Human-readable description
+        #[arg(long)]
+        description: Option<String>,
+        /// Expected exit code
+        #[arg(long)]
+        expected_exit_code: Option<i32>,
+        /// Numeric threshold
+        #[arg(long, conflicts_with = "clear_threshold")]
+        threshold: Option<f32>,
+        /// Remove the threshold
+        #[arg(long)]
+        clear_threshold: bool,
+        /// Scope: global or project
+        #[arg(long)]
+        scope: Option<String>,
+        /// Timeout in seconds
+        #[arg(long)]
+        timeout: Option<i32>,
+        /// Compare against the base branch (true or false)
+        #[arg(long, value_name = "BOOL")]
+        diff_base: Option<bool>,
+        /// With diff_base: a smaller number is better (true or false)
+        #[arg(long, value_name = "BOOL")]
+        lower_is_better: Option<bool>,
+        /// Set an environment variable, as KEY=VALUE (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Unset an environment variable (repeatable)
+        #[arg(long = "unset-env", value_name = "KEY")]
+        unset_env: Vec<String>,
+        /// Directory to run the command in, relative to the worktree root
+        #[arg(long, conflicts_with = "clear_workdir")]
+        workdir: Option<String>,
+        /// Run the command in the worktree root again
+        #[arg(long)]
+        clear_workdir: bool,
+        /// Comma-separated invariants that must finish first ("" to clear)
+        #[arg(long, value_name = "NAMES")]
+        after: Option<String>,
+        /// Comma-separated globs of files to keep when the invariant fails
+        /// ("" to clear)
+        #[arg(long, value_name = "GLOBS")]
+        artifacts: Option<String>,
+    },
+    /// Stop running an invariant at the gate without unlinking it
+    Disable {
+        /// Invariant name
+        name: String,
+    },
+    /// Run a disabled invariant at the gate again
+    Enable {
+        /// Invariant name
+        name: String,
+    },
+    /// Remove an invariant (restorable with `gator restore`)
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// Invariant name to remove
+        name: String,
+    },
+    /// Report unused invariants, uninstalled presets, missing commands, and
+    /// tasks linked to removed or disabled invariants
+    Audit {
+        /// Project type whose presets to check (auto-detected if omitted)
+        #[arg(long)]
+        project_type: Option<String>,
+    },
     /// List or install preset invariants
     Presets {
         #[command(subcommand)]
@@ -289,11 +936,227 @@ pub enum InvariantCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum RestoreCommands {
+    /// Restore a deleted plan and its tasks
+    Plan {
+        /// Plan ID (UUID) or path to a plan TOML file
+        plan_id: String,
+    },
+    /// Restore a deleted invariant
+    Invariant {
+        /// Invariant name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NoteCommands {
+    /// Attach a timestamped note to a plan or task
+    Add {
+        /// Task ID, or plan ID (UUID) or path to a plan TOML file
+        target: String,
+        /// Note text
+        text: String,
+    },
+    /// List the notes of a plan (including its tasks' notes) or of a task
+    List {
+        /// Task ID, or plan ID (UUID) or path to a plan TOML file
+        target: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AdminCommands {
+    /// Stop all running plans, kill their agents, and reset in-flight tasks
+    StopAll,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TaskCommands {
+    /// Kill a task's agent and mark it cancelled; the plan continues
+    /// without it (pending tasks that depend on it are cancelled too)
+    Cancel {
+        /// Task ID
+        task_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Generate a new token secret, keeping the old one to validate tokens
+    /// already issued (same as `gator secret rotate`)
+    RotateSecret {
+        /// Number of previous secrets to keep accepting
+        #[arg(long, default_value_t = 1)]
+        keep: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SecretCommands {
+    /// Generate a new active secret, keeping the old one for validation
+    Rotate {
+        /// Number of previous secrets to keep accepting
+        #[arg(long, default_value_t = 1)]
+        keep: usize,
+    },
+    /// List secret IDs (never the secrets themselves)
+    List,
+    /// Stop accepting tokens signed with a previous secret
+    Retire {
+        /// Secret ID, as shown by `gator secret list`
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EvalCommands {
+    /// Run every case of a suite and print a scorecard
+    Run {
+        /// Path to the suite TOML file
+        suite: String,
+        /// Directory each case's repository and database are created in
+        #[arg(long, default_value = "gator-eval")]
+        workdir: String,
+        /// Override the suite's harness ("mock" or "claude-code")
+        #[arg(long)]
+        harness: Option<String>,
+        /// Also write the scorecard as JSON to this file
+        #[arg(long)]
+        json: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReviewCommands {
+    /// Run the review bot: queue new PR heads, review them, post reviews
+    Run {
+        /// Repository whose pull requests to review (owner/name)
+        #[arg(long)]
+        repo: String,
+        /// Only review PRs carrying this label (default: all open PRs)
+        #[arg(long)]
+        label: Option<String>,
+        /// Seconds between passes
+        #[arg(long, default_value_t = 900)]
+        interval: u64,
+        /// Run a single pass and exit
+        #[arg(long)]
+        once: bool,
+        /// Maximum number of PRs to fetch per pass
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+        /// Git remote to fetch PR heads from
+        #[arg(long, default_value = "origin")]
+        remote: String,
+        /// Also review draft PRs
+        #[arg(long)]
+        include_drafts: bool,
+        /// Submit the agent's verdict as an approval or change request
+        /// instead of a plain comment
+        #[arg(long)]
+        submit_verdict: bool,
+        /// Invariant to link to every review task (repeatable)
+        #[arg(long = "invariant")]
+        invariants: Vec<String>,
+        /// Maximum concurrent agents
+        #[arg(long, default_value_t = 2)]
+        max_agents: usize,
+        /// Per-task timeout in seconds
+        #[arg(long, default_value_t = 1800)]
+        timeout: u64,
+    },
+    /// List reviewed PR heads and whether their reviews were posted
+    Status {
+        /// Only show this repository (owner/name)
+        #[arg(long)]
+        repo: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommands {
+    /// Clone and dispatch a plan whenever a cron expression matches
+    Add {
+        /// Plan ID (UUID) or path to a plan TOML file; each run clones it
+        plan_id: String,
+        /// Five-field cron expression, in UTC (e.g. "0 2 * * *")
+        #[arg(long)]
+        cron: String,
+        /// Maximum concurrent agents per run
+        #[arg(long, default_value_t = 4)]
+        max_agents: usize,
+        /// Per-task timeout in seconds
+        #[arg(long, default_value_t = 1800)]
+        timeout: u64,
+    },
+    /// List schedules, when they next run, and how their last run went
+    List,
+    /// Remove a schedule (plans its runs created are kept)
+    Remove {
+        /// Schedule ID (UUID)
+        schedule_id: String,
+    },
+    /// Run the scheduler: start and dispatch schedules as they come due
+    Run {
+        /// Seconds between checks for due schedules
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Run a single pass and exit
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TriageCommands {
+    /// Run the triage daemon: queue new issues, triage them, post results
+    Run {
+        /// Repository to triage (owner/name)
+        #[arg(long)]
+        repo: String,
+        /// Queue label: open issues carrying it are triaged
+        #[arg(long, default_value = "triage")]
+        label: String,
+        /// Seconds between passes
+        #[arg(long, default_value_t = 900)]
+        interval: u64,
+        /// Run a single pass and exit
+        #[arg(long)]
+        once: bool,
+        /// Maximum number of issues to fetch per pass
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+        /// Label the agent may apply (repeatable; default: any)
+        #[arg(long = "allow-label")]
+        allowed_labels: Vec<String>,
+        /// Invariant to link to every triage task (repeatable)
+        #[arg(long = "invariant")]
+        invariants: Vec<String>,
+        /// Override auto-detected base branch
+        #[arg(long)]
+        base_branch: Option<String>,
+        /// Maximum concurrent agents
+        #[arg(long, default_value_t = 2)]
+        max_agents: usize,
+        /// Per-task timeout in seconds
+        #[arg(long, default_value_t = 1800)]
+        timeout: u64,
+    },
+    /// List triaged issues and whether their results were posted
+    Status {
+        /// Only show this repository (owner/name)
+        #[arg(long)]
+        repo: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum PresetCommands {
     /// List available preset invariants
     List {
-        /// Filter by project type (rust, node, python, go)
+        /// Filter by project type (rust, node, python, go, docs)
         #[arg(long)]
         project_type: Option<String>,
     },
@@ -302,9 +1165,36 @@ pub enum PresetCommands {
         /// Project type to install presets for (auto-detected if omitted)
         #[arg(long)]
         project_type: Option<String>,
+        /// Refresh installed presets whose definition differs from the
+        /// embedded library
+        #[arg(long)]
+        update: bool,
     },
 }
 
+/// Output format for commands that can emit machine-readable output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON with ISO-8601 UTC timestamps.
+    Json,
+}
+
+/// Output format of `gator report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable text.
+    Text,
+    /// The `plan_report` document of the JSON schema.
+    Json,
+    /// One row per task, with a header row.
+    Csv,
+    /// A Markdown summary and task table.
+    #[value(name = "md")]
+    Markdown,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum ExportCommands {
     /// Export plan/task data as CSV
@@ -315,6 +1205,87 @@ pub enum ExportCommands {
         #[arg(long)]
         output: Option<String>,
     },
+    /// Change feed of plans, tasks, attempt phases, and gate results, for
+    /// syncing them incrementally into a data warehouse
+    Cdc {
+        #[command(subcommand)]
+        command: CdcCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CdcCommands {
+    /// Start logging changes
+    Enable,
+    /// Stop logging changes (logged changes are kept)
+    Disable,
+    /// Show whether changes are logged and which are kept
+    Status,
+    /// Print logged changes as JSON lines, oldest first
+    Changes {
+        /// Only changes after this sequence number (the last one synced)
+        #[arg(long, default_value_t = 0)]
+        after: i64,
+        /// Most changes to print
+        #[arg(long, default_value_t = 10000)]
+        limit: i64,
+        /// Output file path (defaults to stdout)
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Delete logged changes up to and including a sequence number, once
+    /// they are synced
+    Prune {
+        /// Last sequence number to delete
+        #[arg(long)]
+        through: i64,
+    },
+}
+
+/// After approving an artifact task, bundle its artifacts from the worktree.
+/// Returns the bundle path, or `None` for code tasks.
+async fn bundle_approved_artifacts(
+    pool: &sqlx::SqlitePool,
+    task_id: uuid::Uuid,
+) -> anyhow::Result<Option<std::path::PathBuf>> {
+    let task = gator_db::queries::tasks::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+    if task.kind != gator_db::models::TaskKind::Artifact {
+        return Ok(None);
+    }
+    let worktree = task
+        .worktree_path
+        .as_deref()
+        .with_context(|| format!("task {task_id} has no worktree to bundle from"))?;
+    let dest =
+        gator_core::artifact::bundle_task(pool, &task, std::path::Path::new(worktree)).await?;
+    Ok(Some(dest))
+}
+
+/// For `gator retry --edit`: open the task's description in the editor and
+/// return the edited text. Refuses before opening the editor if the task
+/// cannot be retried, so no edit is lost.
+async fn edit_task_description(
+    pool: &sqlx::SqlitePool,
+    task_id: uuid::Uuid,
+) -> anyhow::Result<String> {
+    use gator_db::models::TaskStatus;
+
+    let task = gator_db::queries::tasks::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+    if !matches!(task.status, TaskStatus::Failed | TaskStatus::Escalated) {
+        anyhow::bail!(
+            "task {task_id} is {}, must be failed or escalated to retry",
+            task.status
+        );
+    }
+    let edited = editor::edit_text(&format!("{}.md", task.name), &task.description)?;
+    if edited.trim().is_empty() {
+        anyhow::bail!("empty description; retry aborted");
+    }
+    Ok(edited)
 }
 
 /// Execute the `gator init` command: write config file.
@@ -337,10 +1308,26 @@ async fn cmd_init(force: bool) -> anyhow::Result<()> {
     let cfg = config::ConfigFile {
         database: config::DatabaseSection {
             url: db_path_str.clone(),
+            read_url: None,
+            max_connections: None,
+            acquire_timeout_secs: None,
         },
         auth: config::AuthSection {
             token_secret: token_secret.clone(),
+            accept_v1_tokens: None,
+            token_secret_id: None,
+            per_plan_secrets: None,
+            previous_secrets: Vec::new(),
         },
+        notifications: None,
+        display: None,
+        prompt: None,
+        telemetry: None,
+        redaction: None,
+        updates: None,
+        cleanup: None,
+        dispatch: None,
+        gate: None,
     };
 
     config::save_config(&cfg)?;
@@ -364,14 +1351,24 @@ async fn cmd_init(force: bool) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Open the pool used for read-only queries (status, report, dashboard).
+///
+/// Connects to the configured read replica in read-only mode, or falls back
+/// to the primary database when no replica is configured.
+async fn create_read_pool(resolved: &GatorConfig) -> anyhow::Result<sqlx::SqlitePool> {
+    match resolved.read_db_config {
+        Some(ref read_config) => pool::create_read_only_pool(read_config).await,
+        None => pool::create_pool(&resolved.db_config).await,
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
+    let telemetry_section = config::load_config()
+        .ok()
+        .and_then(|cfg| cfg.telemetry)
+        .unwrap_or_default();
+    let _telemetry = telemetry::init(&telemetry_section)?;
 
     let cli = Cli::parse();
 
@@ -380,6 +1377,12 @@ async fn main() -> anyhow::Result<()> {
     // command surface to the four agent-mode commands.
     // -----------------------------------------------------------------
     if guard::is_agent_mode() {
+        // Agents' reports are redacted too; a broken pattern must not stop
+        // them from reporting.
+        if let Err(e) = config::install_redaction() {
+            eprintln!("Warning: {e:#}");
+        }
+
         // Resolve config for DB URL (best-effort).
         let db_config = GatorConfig::resolve(cli.database_url.as_deref())
             .map(|c| c.db_config)
@@ -405,6 +1408,13 @@ async fn main() -> anyhow::Result<()> {
     // -----------------------------------------------------------------
     // Operator mode (default): full command surface.
     // -----------------------------------------------------------------
+    config::install_redaction()?;
+    match config::resolve_display_zone() {
+        Ok(zone) => timefmt::set_display_zone(zone),
+        Err(e) => eprintln!("Warning: {e:#}; showing local time"),
+    }
+    output::set_plain(config::resolve_plain(cli.plain));
+
     match cli.command {
         Commands::Init { force } => {
             cmd_init(force).await?;
@@ -426,6 +1436,7 @@ async fn main() -> anyhow::Result<()> {
                     no_register: true,
                     ..
                 } | PlanCommands::Validate { .. }
+                    | PlanCommands::ImportIssues { .. }
             ) || (matches!(command, PlanCommands::Generate { .. })
                 && !needs_orchestrator);
 
@@ -461,91 +1472,469 @@ async fn main() -> anyhow::Result<()> {
         Commands::Dispatch {
             plan_id,
             max_agents,
-            timeout,
+            dry_run: true,
+            usd_per_mtok,
+            ..
         } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result =
+                dispatch_cmd::run_dry_run(&db_pool, &plan_id, max_agents, usd_per_mtok).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Dispatch {
+            plan_id,
+            max_agents,
+            timeout,
+            notify,
+            drain_timeout,
+            stall_timeout,
+            self_heal,
+            agent_stall_timeout,
+            gate_parallelism,
+            no_cache,
+            only,
+            from,
+            until,
+            ..
+        } => {
+            let mut resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            resolved.size_pool_for_agents(max_agents);
+            resolved.notifications.desktop |= notify;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let options = dispatch_cmd::DispatchOptions {
+                max_agents,
+                task_timeout: std::time::Duration::from_secs(timeout),
+                drain_timeout: std::time::Duration::from_secs(drain_timeout),
+                watchdog: gator_core::orchestrator::WatchdogConfig {
+                    stall_after: (stall_timeout > 0)
+                        .then(|| std::time::Duration::from_secs(stall_timeout)),
+                    self_heal,
+                    agent_stall_after: (agent_stall_timeout > 0)
+                        .then(|| std::time::Duration::from_secs(agent_stall_timeout)),
+                },
+                gate_parallelism,
+                gate_cache: !no_cache,
+                selection: gator_core::orchestrator::TaskSelection { only, from, until },
+            };
+            banner::spawn_update_check(config::resolve_update_check());
             let result = dispatch_cmd::run_dispatch(
                 &db_pool,
                 &plan_id,
+                options,
+                &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
+            )
+            .await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Resume {
+            plan_id,
+            budget,
+            max_agents,
+            timeout,
+            notify,
+        } => {
+            let mut resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            resolved.size_pool_for_agents(max_agents);
+            resolved.notifications.desktop |= notify;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let options = dispatch_cmd::DispatchOptions {
                 max_agents,
-                timeout,
+                task_timeout: std::time::Duration::from_secs(timeout),
+                drain_timeout: gator_core::orchestrator::DEFAULT_DRAIN_TIMEOUT,
+                watchdog: gator_core::orchestrator::WatchdogConfig::default(),
+                gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+                gate_cache: true,
+                selection: gator_core::orchestrator::TaskSelection::default(),
+            };
+            let result = resume_cmd::run_resume(
+                &db_pool,
+                &plan_id,
+                budget,
+                options,
                 &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
             )
             .await;
             db_pool.close().await;
             result?;
         }
-        Commands::Status { plan_id } => {
+        Commands::Status {
+            output,
+            federate: Some(path),
+            ..
+        } => {
+            federate::run_federated_status(&path, output).await?;
+        }
+        Commands::Status {
+            plan_id,
+            output,
+            why,
+            limit,
+            snapshot,
+            federate: None,
+        } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
-            let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = status_cmd::run_status(&db_pool, plan_id.as_deref()).await;
+            let db_pool = create_read_pool(&resolved).await?;
+            let result = match snapshot {
+                Some(path) => status_cmd::run_snapshot(&db_pool, plan_id.as_deref(), &path).await,
+                None => {
+                    status_cmd::run_status(&db_pool, plan_id.as_deref(), output, why, limit).await
+                }
+            };
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Audit { task_id, attempt } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = create_read_pool(&resolved).await?;
+            let result = audit_cmd::run_audit(&db_pool, &task_id, attempt).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Log {
+            task_id,
+            attempt,
+            run,
+            attempt_id,
+            export,
+        } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = create_read_pool(&resolved).await?;
+            let result = match (&task_id, &export) {
+                (Some(task_id), Some(path)) => {
+                    log_cmd::run_export(&db_pool, task_id, attempt, path).await
+                }
+                _ => {
+                    log_cmd::run_log(
+                        &db_pool,
+                        task_id.as_deref(),
+                        attempt,
+                        run.as_deref(),
+                        attempt_id.as_deref(),
+                    )
+                    .await
+                }
+            };
             db_pool.close().await;
             result?;
         }
-        Commands::Log { task_id, attempt } => {
+        Commands::Note { command } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = log_cmd::run_log(&db_pool, &task_id, attempt).await;
+            let result = note_cmd::run_note(&db_pool, command).await;
             db_pool.close().await;
             result?;
         }
-        Commands::Approve { task_id } => {
+        Commands::Approve { task_id, files } if !files.is_empty() => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let id = uuid::Uuid::parse_str(&task_id)
+                .with_context(|| format!("invalid task ID: {task_id}"))?;
+            let result =
+                gator_core::state::dispatch::approve_task_paths(&db_pool, id, &files).await;
+            db_pool.close().await;
+            let approval = result?;
+            println!(
+                "Task {task_id} approved: committed {} of {} changed path(s).",
+                approval.committed.len(),
+                approval.committed.len() + approval.discarded.len()
+            );
+            for path in &approval.discarded {
+                println!("  reverted {path}");
+            }
+        }
+        Commands::Approve { task_id, .. } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
             let id = uuid::Uuid::parse_str(&task_id)
                 .with_context(|| format!("invalid task ID: {task_id}"))?;
             let result = gator_core::state::dispatch::approve_task(&db_pool, id).await;
+            let bundle = match result {
+                Ok(()) => bundle_approved_artifacts(&db_pool, id).await,
+                Err(e) => Err(e),
+            };
             db_pool.close().await;
-            result?;
+            let bundle = bundle?;
             println!("Task {task_id} approved.");
+            if let Some(dest) = bundle {
+                println!("Artifact bundle: {}", dest.display());
+            }
         }
-        Commands::Reject { task_id } => {
+        Commands::Reject { task_id, reason } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
             let id = uuid::Uuid::parse_str(&task_id)
                 .with_context(|| format!("invalid task ID: {task_id}"))?;
-            let result = gator_core::state::dispatch::reject_task(&db_pool, id).await;
+            let result =
+                gator_core::state::dispatch::reject_task(&db_pool, id, reason.as_deref()).await;
             db_pool.close().await;
             result?;
-            println!("Task {task_id} rejected.");
+            if reason.is_some() {
+                println!("Task {task_id} rejected with feedback.");
+            } else {
+                println!("Task {task_id} rejected.");
+            }
         }
-        Commands::Retry { task_id, force } => {
+        Commands::Answer { task_id, response } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
             let id = uuid::Uuid::parse_str(&task_id)
                 .with_context(|| format!("invalid task ID: {task_id}"))?;
             let result =
-                gator_core::state::dispatch::operator_retry_task(&db_pool, id, force).await;
+                gator_core::state::dispatch::answer_question(&db_pool, id, &response).await;
+            db_pool.close().await;
+            result?;
+            println!("Task {task_id} answered; its agent resumes with the answer.");
+        }
+        Commands::Cancel { task_id } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = task_cmd::run_task(&db_pool, TaskCommands::Cancel { task_id }).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Retry {
+            task_id,
+            force,
+            env,
+            edit,
+        } => {
+            let mut overrides = std::collections::BTreeMap::new();
+            invariant_cmds::parse_env(&env, &mut overrides)?;
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let id = uuid::Uuid::parse_str(&task_id)
+                .with_context(|| format!("invalid task ID: {task_id}"))?;
+            let result = async {
+                let description = if edit {
+                    Some(edit_task_description(&db_pool, id).await?)
+                } else {
+                    None
+                };
+                gator_core::state::dispatch::operator_retry_task(
+                    &db_pool,
+                    id,
+                    force,
+                    &overrides,
+                    description.as_deref(),
+                )
+                .await
+            }
+            .await;
             db_pool.close().await;
             result?;
             println!("Task {task_id} reset to pending for retry.");
+            if !overrides.is_empty() {
+                println!(
+                    "The next attempt runs with: {}",
+                    invariant_cmds::format_env(&overrides)
+                );
+            }
+        }
+        Commands::Gate { task_id, artifacts } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = gate_cmd::run_gate(&db_pool, &task_id, artifacts).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Report {
+            plan_id,
+            format,
+            usd_per_mtok,
+        } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = create_read_pool(&resolved).await?;
+            let result = report_cmd::run_report(&db_pool, &plan_id, format, usd_per_mtok).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Stats {
+            since,
+            period,
+            usd_per_mtok,
+            limit,
+        } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = create_read_pool(&resolved).await?;
+            let result = stats_cmd::run_stats(&db_pool, since, period, usd_per_mtok, limit).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Restore { target } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = restore_cmd::run_restore(&db_pool, target).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Triage { command } => {
+            let mut resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            if let TriageCommands::Run { max_agents, .. } = &command {
+                resolved.size_pool_for_agents(*max_agents);
+            }
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = triage_cmd::run_triage(
+                &db_pool,
+                command,
+                &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
+            )
+            .await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Fanout {
+            repos,
+            plan,
+            workdir,
+            parallel,
+            max_agents,
+            timeout,
+            remote,
+            no_pr,
+            draft,
+            report,
+        } => {
+            let mut resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            resolved.size_pool_for_agents(parallel.max(1) * max_agents);
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let options = fanout_cmd::FanoutOptions {
+                repos_file: repos,
+                plan_file: plan,
+                workdir: workdir.into(),
+                parallel,
+                max_agents,
+                task_timeout: std::time::Duration::from_secs(timeout),
+                remote,
+                no_pr,
+                draft,
+                report,
+            };
+            let result = fanout_cmd::run_fanout(
+                &db_pool,
+                &options,
+                &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
+            )
+            .await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Eval { command } => {
+            eval_cmd::run_eval(command, &config::resolve_prompt()).await?;
+        }
+        Commands::Review { command } => {
+            let mut resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            if let ReviewCommands::Run { max_agents, .. } = &command {
+                resolved.size_pool_for_agents(*max_agents);
+            }
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = review_cmd::run_review(
+                &db_pool,
+                command,
+                &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
+            )
+            .await;
+            db_pool.close().await;
+            result?;
         }
-        Commands::Gate { task_id } => {
+        Commands::Schedule { command } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = gate_cmd::run_gate(&db_pool, &task_id).await;
+            let result = schedule_cmd::run_schedule(
+                &db_pool,
+                command,
+                &resolved.token_config,
+                &resolved.notifications,
+                &resolved.prompt,
+            )
+            .await;
             db_pool.close().await;
             result?;
         }
-        Commands::Report { plan_id } => {
+        Commands::Admin { command } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = report_cmd::run_report(&db_pool, &plan_id).await;
+            let result = admin_cmd::run_admin(&db_pool, command).await;
             db_pool.close().await;
             result?;
         }
-        Commands::Cleanup { plan_id, all } => {
+        Commands::Secret { command } => {
+            secret_cmd::run_secret(command)?;
+        }
+        Commands::Config {
+            command: ConfigCommands::RotateSecret { keep },
+        } => {
+            secret_cmd::run_secret(SecretCommands::Rotate { keep })?;
+        }
+        Commands::Cleanup {
+            plan_id,
+            all,
+            gate_output_days,
+            max_age,
+            min_free,
+        } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let gate_output_days = config::resolve_gate_output_days(gate_output_days);
+            let policy = config::resolve_worktree_policy(max_age, min_free)?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = cleanup_cmd::run_cleanup(&db_pool, &plan_id, all).await;
+            let result = cleanup_cmd::run_cleanup(
+                &db_pool,
+                plan_id.as_deref(),
+                all,
+                gate_output_days,
+                policy,
+            )
+            .await;
             db_pool.close().await;
             result?;
         }
-        Commands::Merge { plan_id, dry_run } => {
+        Commands::Gc {
+            dry_run,
+            event_days,
+            force,
+        } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let event_days = config::resolve_agent_event_days(event_days);
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = merge_cmd::run_merge(&db_pool, &plan_id, dry_run).await;
+            let result = gc_cmd::run_gc(&db_pool, dry_run, force, event_days).await;
+            db_pool.close().await;
+            result?;
+        }
+        Commands::Merge {
+            plan_id,
+            dry_run,
+            interactive,
+            resume,
+            skip,
+            abort,
+        } => {
+            let action = if resume {
+                merge_cmd::MergeAction::Continue
+            } else if skip {
+                merge_cmd::MergeAction::Skip
+            } else if abort {
+                merge_cmd::MergeAction::Abort
+            } else {
+                merge_cmd::MergeAction::Start {
+                    dry_run,
+                    interactive,
+                }
+            };
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = merge_cmd::run_merge(&db_pool, &plan_id, action).await;
             db_pool.close().await;
             result?;
         }
@@ -567,33 +1956,90 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::Serve { port, bind } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
-            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let db_pool = create_read_pool(&resolved).await?;
+            banner::log_serve_banner(&db_pool, &bind, port).await;
+            banner::spawn_update_check(config::resolve_update_check());
             let result = serve_cmd::run_serve(db_pool.clone(), &bind, port).await;
             db_pool.close().await;
             result?;
         }
-        Commands::Dashboard => {
+        Commands::Dashboard {
+            notify,
+            interval,
+            federate: Some(path),
+            ..
+        } => {
+            let desktop_notify = notify
+                || config::load_config()
+                    .ok()
+                    .and_then(|cfg| cfg.notifications)
+                    .is_some_and(|n| n.desktop);
+            federate::run_federated_dashboard(
+                &path,
+                std::time::Duration::from_secs(interval.max(1)),
+                desktop_notify,
+            )
+            .await?;
+        }
+        Commands::Dashboard {
+            notify,
+            no_tui,
+            interval,
+            federate: None,
+        } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
             let db_pool = pool::create_pool(&resolved.db_config).await?;
-            let result = tui::run_dashboard(db_pool.clone()).await;
+            let read_pool = create_read_pool(&resolved).await?;
+            let desktop_notify = notify || resolved.notifications.desktop;
+            let result = if no_tui || output::is_plain() {
+                tui::plain::run_plain_dashboard(
+                    &[tui::plain::Source {
+                        label: None,
+                        pool: Some(&read_pool),
+                    }],
+                    std::time::Duration::from_secs(interval.max(1)),
+                    desktop_notify,
+                )
+                .await
+            } else {
+                tui::run_dashboard(db_pool.clone(), read_pool.clone(), desktop_notify).await
+            };
+            read_pool.close().await;
             db_pool.close().await;
             result?;
         }
         Commands::Export { command } => {
             let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
-            let db_pool = pool::create_pool(&resolved.db_config).await?;
             let result = match command {
                 ExportCommands::Csv { plan_id, output } => {
-                    export_cmd::run_export_csv(&db_pool, plan_id.as_deref(), output.as_deref())
-                        .await
+                    let db_pool = create_read_pool(&resolved).await?;
+                    let result =
+                        export_cmd::run_export_csv(&db_pool, plan_id.as_deref(), output.as_deref())
+                            .await;
+                    db_pool.close().await;
+                    result
+                }
+                ExportCommands::Cdc { command } => {
+                    let db_pool = pool::create_pool(&resolved.db_config).await?;
+                    let result = export_cmd::run_cdc(&db_pool, command).await;
+                    db_pool.close().await;
+                    result
                 }
             };
-            db_pool.close().await;
             result?;
         }
-        Commands::Task => {
+        Commands::Task { command: None } => {
             println!("gator task: not available in operator mode (set GATOR_AGENT_TOKEN)");
         }
+        Commands::Task {
+            command: Some(command),
+        } => {
+            let resolved = GatorConfig::resolve(cli.database_url.as_deref())?;
+            let db_pool = pool::create_pool(&resolved.db_config).await?;
+            let result = task_cmd::run_task(&db_pool, command).await;
+            db_pool.close().await;
+            result?;
+        }
         Commands::Check => {
             println!("gator check: not available in operator mode (set GATOR_AGENT_TOKEN)");
         }
@@ -604,6 +2050,10 @@ async fn main() -> anyhow::Result<()> {
         Commands::Done => {
             println!("gator done: not available in operator mode (set GATOR_AGENT_TOKEN)");
         }
+        Commands::Ask { question } => {
+            println!("gator ask: not available in operator mode (set GATOR_AGENT_TOKEN)");
+            let _ = question;
+        }
     }
 
     Ok(())
@@ -612,13 +2062,18 @@ async fn main() -> anyhow::Result<()> {
 /// Shared test utilities for tests that modify process-global state (env vars).
 #[cfg(test)]
 pub(crate) mod test_util {
-    use std::sync::Mutex;
+    use tokio::sync::{Mutex, MutexGuard};
 
     /// Global mutex for all tests that read/write environment variables.
-    /// Use `lock_env()` to acquire, which recovers from poisoned state.
-    static ENV_MUTEX: Mutex<()> = Mutex::new(());
+    /// Sync tests acquire it with `lock_env()`; async tests use
+    /// `lock_env_async()`, whose guard may be held across `.await`.
+    static ENV_MUTEX: Mutex<()> = Mutex::const_new(());
+
+    pub(crate) fn lock_env() -> MutexGuard<'static, ()> {
+        ENV_MUTEX.blocking_lock()
+    }
 
-    pub(crate) fn lock_env() -> std::sync::MutexGuard<'static, ()> {
-        ENV_MUTEX.lock().unwrap_or_else(|e| e.into_inner())
+    pub(crate) async fn lock_env_async() -> MutexGuard<'static, ()> {
+        ENV_MUTEX.lock().await
     }
 }
@@ -1,16 +1,70 @@
 //! `gator merge <plan-id>` command: merge passed task branches into the base branch.
+//!
+//! Artifact tasks have no branch to merge; their bundles are listed instead.
+//! Each merge is recorded on its task (`merged_at`, `merge_commit`), and
+//! tasks recorded as merged are skipped when the command runs again. A task
+//! whose branch the base branch already contains (merged before merges were
+//! recorded) is skipped too, and recorded with its branch tip.
+//!
+//! With `--interactive`, a conflicted branch can be resolved instead of
+//! stopping the merge: the merge is redone in a worktree of its own, where
+//! the operator resolves it, and `gator merge --continue` lands it on the
+//! base branch and merges the remaining branches. `--skip` drops the
+//! conflicted branch and goes on; `--abort` drops it and stops.
+//!
+//! When the merge completes, the plan's `post_merge` hook runs; its failure
+//! is reported but does not undo the merge.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use gator_core::worktree::{MergeResult, WorktreeManager};
-use gator_db::models::{PlanStatus, TaskStatus};
+use gator_core::artifact;
+use gator_core::plan::hooks::{self, Hook};
+use gator_core::worktree::{self, MergeResult, WorktreeManager};
+use gator_db::models::{Plan, PlanStatus, Task, TaskKind, TaskStatus};
 use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 
+/// What `gator merge` is asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeAction {
+    /// Merge the passed task branches.
+    Start { dry_run: bool, interactive: bool },
+    /// Land the resolved conflict and merge the remaining branches.
+    Continue,
+    /// Drop the conflicted branch and merge the remaining ones.
+    Skip,
+    /// Drop the conflicted branch and stop.
+    Abort,
+}
+
+/// A merge stopped on a conflict the operator is resolving, stored next to
+/// the merge worktree until `--continue`, `--skip`, or `--abort`.
+#[derive(Debug, Serialize, Deserialize)]
+struct MergeSession {
+    /// The conflicted branch.
+    branch: String,
+    /// Whether later conflicts prompt again.
+    interactive: bool,
+    /// Branches the operator chose not to merge.
+    skipped: Vec<String>,
+}
+
+/// How the operator handles a conflicted branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConflictChoice {
+    Resolve,
+    Skip,
+    Abort,
+}
+
 /// Run the merge command.
-pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> Result<()> {
+pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, action: MergeAction) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     let plan = plan_db::get_plan(pool, plan_id)
@@ -28,19 +82,99 @@ pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> R
     let worktree_manager =
         WorktreeManager::new(&plan.project_path, None).map_err(|e| anyhow::anyhow!("{e}"))?;
 
+    let merge_path = worktree_manager.merge_path(&plan.name);
+    let session_file = session_path(&merge_path);
+    let session = load_session(&session_file)?;
+
+    let (dry_run, interactive, skipped) = match (action, session) {
+        (MergeAction::Start { dry_run, .. }, Some(session)) if !dry_run => bail!(
+            "the merge of {} is waiting for its conflict to be resolved in {} -- \
+             run gator merge {plan_id} --continue, --skip, or --abort",
+            session.branch,
+            merge_path.display()
+        ),
+        (
+            MergeAction::Start {
+                dry_run,
+                interactive,
+            },
+            _,
+        ) => (dry_run, interactive, Vec::new()),
+        (_, None) => bail!("plan {plan_id} has no conflicted merge to continue, skip, or abort"),
+        (MergeAction::Continue, Some(session)) => {
+            let conflicted = worktree::unmerged_paths(&merge_path)
+                .map_err(|e| anyhow::anyhow!("failed to inspect {}: {e}", merge_path.display()))?;
+            if !conflicted.is_empty() {
+                bail!(
+                    "{} still has conflicts in {} -- resolve them and `git add` them first",
+                    merge_path.display(),
+                    conflicted.join(", ")
+                );
+            }
+            let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+            let task = tasks
+                .iter()
+                .find(|t| WorktreeManager::branch_name(&plan.name, &t.name) == session.branch)
+                .with_context(|| {
+                    format!("no task of plan {plan_id} has branch {}", session.branch)
+                })?;
+            checkout_base(&worktree_manager, &plan)?;
+            let commit = land_merge(&worktree_manager, &merge_path)?;
+            task_db::mark_task_merged(pool, task.id, &commit).await?;
+            std::fs::remove_file(&session_file)?;
+            println!("Merged {} (conflict resolved)", session.branch);
+            (false, session.interactive, session.skipped)
+        }
+        (MergeAction::Skip, Some(mut session)) => {
+            discard_merge(&worktree_manager, &merge_path, &session_file)?;
+            println!("Skipped {}", session.branch);
+            session.skipped.push(session.branch);
+            (false, session.interactive, session.skipped)
+        }
+        (MergeAction::Abort, Some(session)) => {
+            discard_merge(&worktree_manager, &merge_path, &session_file)?;
+            println!(
+                "Aborted the merge of {}; branches merged before it stay merged.",
+                session.branch
+            );
+            return Ok(());
+        }
+    };
+
+    // Ensure we're on the base branch.
+    if !dry_run {
+        checkout_base(&worktree_manager, &plan)?;
+    }
+
     let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
 
     // Build dependency-ordered list using topological sort.
     let deps = build_dependency_map(pool, &tasks).await?;
     let ordered = topological_sort(&tasks, &deps)?;
 
-    // Ensure we're on the base branch.
-    if !dry_run {
-        worktree_manager
-            .checkout(&plan.base_branch)
-            .map_err(|e| anyhow::anyhow!("failed to checkout {}: {e}", plan.base_branch))?;
-    }
+    merge_branches(
+        pool,
+        &worktree_manager,
+        &plan,
+        &ordered,
+        dry_run,
+        interactive,
+        skipped,
+    )
+    .await
+}
 
+/// Merge the passed task branches of `ordered` that are not merged yet,
+/// recording each merge on its task.
+async fn merge_branches(
+    pool: &SqlitePool,
+    worktree_manager: &WorktreeManager,
+    plan: &Plan,
+    ordered: &[Task],
+    dry_run: bool,
+    interactive: bool,
+    mut skipped: Vec<String>,
+) -> Result<()> {
     println!(
         "Merging {} task branch(es) into {}",
         ordered.len(),
@@ -48,22 +182,60 @@ pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> R
     );
 
     let mut merged = 0;
-    for task in &ordered {
+    for task in ordered {
         if task.status != TaskStatus::Passed {
             continue;
         }
+        if task.kind == TaskKind::Artifact {
+            let root = artifact::bundle_root(Path::new(&plan.project_path));
+            let dest = artifact::bundle_dir(&root, &plan.name, &task.name);
+            println!(
+                "  Skipping {} (artifact bundle: {})",
+                task.name,
+                dest.display()
+            );
+            continue;
+        }
 
         let branch = WorktreeManager::branch_name(&plan.name, &task.name);
 
+        if skipped.contains(&branch) {
+            println!("  Skipping {branch} (skipped after a conflict)");
+            continue;
+        }
+
         if dry_run {
             println!("  Would merge: {branch}");
             merged += 1;
             continue;
         }
 
+        if let (Some(_), Some(commit)) = (task.merged_at, &task.merge_commit) {
+            println!("  {branch} already merged ({})", short_commit(commit));
+            continue;
+        }
+
+        // Merged before merges were recorded, or outside gator: record the
+        // branch tip, the commit the base branch already contains.
+        if worktree_manager
+            .is_merged(&branch, &plan.base_branch)
+            .map_err(|e| anyhow::anyhow!("failed to inspect {branch}: {e}"))?
+        {
+            let commit = worktree_manager
+                .rev_commit(&branch)
+                .map_err(|e| anyhow::anyhow!("failed to resolve {branch}: {e}"))?;
+            task_db::mark_task_merged(pool, task.id, &commit).await?;
+            println!("  {branch} already merged ({})", short_commit(&commit));
+            continue;
+        }
+
         print!("  Merging {branch}...");
         match worktree_manager.merge_branch(&branch) {
             Ok(MergeResult::Success) => {
+                let commit = worktree_manager
+                    .head_commit()
+                    .map_err(|e| anyhow::anyhow!("failed to read the merge commit: {e}"))?;
+                task_db::mark_task_merged(pool, task.id, &commit).await?;
                 println!(" ok");
                 merged += 1;
             }
@@ -71,8 +243,57 @@ pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> R
                 println!(" CONFLICT");
                 eprintln!("\nMerge conflict on branch {branch}:");
                 eprintln!("{details}");
-                eprintln!("\nStopping. Please resolve the conflict manually and re-run.");
-                bail!("merge conflict on branch {branch}");
+                if !interactive {
+                    eprintln!(
+                        "\nStopping. Re-run with --interactive to resolve it in a worktree, \
+                         or resolve it manually and re-run."
+                    );
+                    bail!("merge conflict on branch {branch}");
+                }
+                match prompt_conflict(&mut std::io::stdin().lock())? {
+                    ConflictChoice::Resolve => {
+                        let merge_path = worktree_manager.merge_path(&plan.name);
+                        let conflicted = worktree_manager
+                            .open_conflicted_merge(&merge_path, &plan.base_branch, &branch)
+                            .map_err(|e| {
+                                anyhow::anyhow!("failed to redo merge of {branch}: {e}")
+                            })?;
+                        if conflicted.is_empty() {
+                            let commit = land_merge(worktree_manager, &merge_path)?;
+                            task_db::mark_task_merged(pool, task.id, &commit).await?;
+                            println!("  Merged {branch}");
+                            merged += 1;
+                            continue;
+                        }
+                        let session = MergeSession {
+                            branch: branch.clone(),
+                            interactive,
+                            skipped,
+                        };
+                        std::fs::write(
+                            session_path(&merge_path),
+                            serde_json::to_string_pretty(&session)?,
+                        )?;
+                        println!("\nResolve the conflict in {}:", merge_path.display());
+                        for path in &conflicted {
+                            println!("  {path}");
+                        }
+                        println!(
+                            "then `git add` the files and run: gator merge {} --continue",
+                            plan.id
+                        );
+                        println!(
+                            "({merged} branch(es) merged so far; --skip drops {branch}, \
+                             --abort stops here)"
+                        );
+                        return Ok(());
+                    }
+                    ConflictChoice::Skip => {
+                        println!("  Skipping {branch}");
+                        skipped.push(branch);
+                    }
+                    ConflictChoice::Abort => bail!("merge conflict on branch {branch}"),
+                }
             }
             Err(e) => {
                 println!(" ERROR");
@@ -88,15 +309,123 @@ pub async fn run_merge(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> R
             "\nMerge complete: {merged} branch(es) merged into {}.",
             plan.base_branch
         );
+        if !skipped.is_empty() {
+            println!("Not merged: {}", skipped.join(", "));
+        }
+        run_post_merge_hook(worktree_manager, plan, merged, &skipped).await;
+    }
+
+    Ok(())
+}
+
+/// Run the plan's `post_merge` hook. The merge has already landed, so a
+/// failure is reported, not returned.
+async fn run_post_merge_hook(
+    worktree_manager: &WorktreeManager,
+    plan: &Plan,
+    merged: usize,
+    skipped: &[String],
+) {
+    if Hook::PostMerge.command(&plan.hooks).is_none() {
+        return;
+    }
+    let env = [
+        ("GATOR_MERGED", merged.to_string()),
+        (
+            "GATOR_MERGE_COMMIT",
+            worktree_manager.head_commit().unwrap_or_default(),
+        ),
+        ("GATOR_SKIPPED", skipped.join(",")),
+    ];
+    println!("Running post_merge hook...");
+    if let Err(e) = hooks::run_hook(plan, Hook::PostMerge, &env).await {
+        eprintln!("Warning: {e:#}");
     }
+}
+
+/// Check out the plan's base branch in the main repository.
+fn checkout_base(worktree_manager: &WorktreeManager, plan: &Plan) -> Result<()> {
+    worktree_manager
+        .checkout(&plan.base_branch)
+        .map_err(|e| anyhow::anyhow!("failed to checkout {}: {e}", plan.base_branch))
+}
+
+/// Commit the merge resolved in `merge_path`, fast-forward the base branch
+/// to it, and remove the merge worktree. Returns the merge commit.
+fn land_merge(worktree_manager: &WorktreeManager, merge_path: &Path) -> Result<String> {
+    let head = worktree_manager.commit_merge(merge_path).map_err(|e| {
+        anyhow::anyhow!(
+            "failed to commit the merge in {}: {e}",
+            merge_path.display()
+        )
+    })?;
+    worktree_manager
+        .fast_forward(&head)
+        .map_err(|e| anyhow::anyhow!("failed to fast-forward to the resolved merge: {e}"))?;
+    worktree_manager
+        .remove_worktree(merge_path)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    Ok(head)
+}
+
+/// The abbreviated form of a commit hash.
+pub(crate) fn short_commit(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
 
+/// Remove the merge worktree and its session.
+fn discard_merge(
+    worktree_manager: &WorktreeManager,
+    merge_path: &Path,
+    session_file: &Path,
+) -> Result<()> {
+    worktree_manager
+        .remove_worktree(merge_path)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    std::fs::remove_file(session_file)?;
     Ok(())
 }
 
+/// The session file stored next to the merge worktree at `merge_path`.
+fn session_path(merge_path: &Path) -> PathBuf {
+    let mut path = merge_path.as_os_str().to_owned();
+    path.push(".json");
+    PathBuf::from(path)
+}
+
+fn load_session(path: &Path) -> Result<Option<MergeSession>> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => Ok(Some(serde_json::from_str(&json).with_context(|| {
+            format!("failed to parse merge session {}", path.display())
+        })?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Ask the operator how to handle a conflicted branch. End of input aborts.
+fn prompt_conflict(input: &mut impl BufRead) -> Result<ConflictChoice> {
+    loop {
+        print!("[r]esolve it in a worktree, [s]kip the branch, or [a]bort? ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            println!();
+            return Ok(ConflictChoice::Abort);
+        }
+        match line.trim().to_ascii_lowercase().as_str() {
+            "r" | "resolve" => return Ok(ConflictChoice::Resolve),
+            "s" | "skip" => return Ok(ConflictChoice::Skip),
+            "a" | "abort" => return Ok(ConflictChoice::Abort),
+            _ => {}
+        }
+    }
+}
+
 /// Build a map of task_id -> list of dependency task_ids.
-async fn build_dependency_map(
+pub(crate) async fn build_dependency_map(
     pool: &SqlitePool,
-    tasks: &[gator_db::models::Task],
+    tasks: &[Task],
 ) -> Result<std::collections::HashMap<Uuid, Vec<Uuid>>> {
     let mut deps = std::collections::HashMap::new();
     for task in tasks {
@@ -107,14 +436,13 @@ async fn build_dependency_map(
 }
 
 /// Topological sort of tasks based on dependencies.
-fn topological_sort(
-    tasks: &[gator_db::models::Task],
+pub(crate) fn topological_sort(
+    tasks: &[Task],
     deps: &std::collections::HashMap<Uuid, Vec<Uuid>>,
-) -> Result<Vec<gator_db::models::Task>> {
+) -> Result<Vec<Task>> {
     use std::collections::{HashMap, HashSet, VecDeque};
 
-    let task_map: HashMap<Uuid, &gator_db::models::Task> =
-        tasks.iter().map(|t| (t.id, t)).collect();
+    let task_map: HashMap<Uuid, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
 
     // Compute in-degree (only counting edges within this task set).
     let task_ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
@@ -174,3 +502,21 @@ fn topological_sort(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_conflict_reads_choices_and_aborts_at_end_of_input() {
+        let mut input = "x\n  S \n".as_bytes();
+        assert_eq!(prompt_conflict(&mut input).unwrap(), ConflictChoice::Skip);
+        let mut input = "resolve\n".as_bytes();
+        assert_eq!(
+            prompt_conflict(&mut input).unwrap(),
+            ConflictChoice::Resolve
+        );
+        let mut input = "".as_bytes();
+        assert_eq!(prompt_conflict(&mut input).unwrap(), ConflictChoice::Abort);
+    }
+}
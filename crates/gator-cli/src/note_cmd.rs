@@ -0,0 +1,122 @@
+//! `gator note` command: operator notes on plans and tasks.
+//!
+//! Notes record context about a run -- a task retried by hand, a flaky
+//! invariant skipped, a branch fixed up before merging -- next to the plan
+//! instead of in chat. They are shown by `gator plan show`, `gator status`,
+//! and the dashboard's detail views.
+
+use anyhow::{Context, Result, bail};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{Note, Task};
+use gator_db::queries::notes as note_db;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use crate::NoteCommands;
+use crate::timefmt;
+
+/// Run a `gator note` subcommand.
+pub async fn run_note(pool: &SqlitePool, command: NoteCommands) -> Result<()> {
+    match command {
+        NoteCommands::Add { target, text } => {
+            let text = text.trim();
+            if text.is_empty() {
+                bail!("note text is empty");
+            }
+            let (plan_id, task) = resolve_target(pool, &target).await?;
+            note_db::insert_note(pool, plan_id, task.as_ref().map(|t| t.id), text).await?;
+            match task {
+                Some(task) => println!("Note added to task {} ({})", task.name, task.id),
+                None => println!("Note added to plan {plan_id}"),
+            }
+        }
+        NoteCommands::List { target } => {
+            let (plan_id, task) = resolve_target(pool, &target).await?;
+            let notes = match &task {
+                Some(task) => note_db::list_notes_for_task(pool, task.id).await?,
+                None => note_db::list_notes_for_plan(pool, plan_id).await?,
+            };
+            if notes.is_empty() {
+                println!("No notes.");
+                return Ok(());
+            }
+            let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+            for line in note_lines(&notes, &tasks) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a note target to its plan, and the task when it names one.
+///
+/// A UUID is looked up as a task first, then as a plan; anything else is
+/// resolved as a plan TOML path.
+async fn resolve_target(pool: &SqlitePool, target: &str) -> Result<(Uuid, Option<Task>)> {
+    if let Ok(id) = Uuid::parse_str(target) {
+        if let Some(task) = task_db::get_task(pool, id).await? {
+            return Ok((task.plan_id, Some(task)));
+        }
+    }
+    let plan_id = crate::resolve::resolve_plan_id(target)?;
+    plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("no plan or task with ID {plan_id}"))?;
+    Ok((plan_id, None))
+}
+
+/// One line per note, `<timestamp>  <text>`, with the task name in
+/// brackets for task notes. Continuation lines of multi-line notes are
+/// indented.
+pub(crate) fn note_lines(notes: &[Note], tasks: &[Task]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for note in notes {
+        let task = note
+            .task_id
+            .and_then(|id| tasks.iter().find(|t| t.id == id))
+            .map(|t| format!("[{}] ", t.name))
+            .unwrap_or_default();
+        let mut body = note.body.lines();
+        lines.push(format!(
+            "{}  {task}{}",
+            timefmt::timestamp_with_age(note.created_at),
+            body.next().unwrap_or_default()
+        ));
+        lines.extend(body.map(|line| format!("    {line}")));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn note(task_id: Option<Uuid>, body: &str) -> Note {
+        Note {
+            id: Uuid::new_v4(),
+            plan_id: Uuid::new_v4(),
+            task_id,
+            body: body.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn note_lines_label_task_notes_and_indent_continuations() {
+        let task_id = Uuid::new_v4();
+        let tasks: Vec<Task> = Vec::new();
+        let lines = note_lines(&[note(None, "paused\nresumed at 14:00")], &tasks);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("  paused"), "got: {}", lines[0]);
+        assert_eq!(lines[1], "    resumed at 14:00");
+
+        // A task that is not in `tasks` is not labelled.
+        let lines = note_lines(&[note(Some(task_id), "retried")], &tasks);
+        assert!(lines[0].ends_with("  retried"), "got: {}", lines[0]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Plain output mode for screen readers.
+//!
+//! With `--plain` (or `GATOR_PLAIN=1`, or `display.plain = true` in the
+//! config file), human-readable commands drop column-aligned tables, status
+//! glyphs, and separator lines in favour of one labelled sentence per line,
+//! and `gator dashboard` prints periodic text summaries instead of the TUI.
+//! The flag is process-wide and set once at startup.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gator_db::queries::tasks::PlanProgress;
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Turn plain output on or off for the rest of the process.
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Whether plain output is enabled.
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Describe plan progress in words, e.g.
+/// `3 of 7 tasks passed; 2 running, 1 failed, 1 pending`.
+///
+/// Only non-zero counts are mentioned after the passed total.
+pub fn progress_sentence(progress: &PlanProgress) -> String {
+    if progress.total == 0 {
+        return "no tasks".to_string();
+    }
+    let noun = if progress.total == 1 { "task" } else { "tasks" };
+    let mut sentence = format!("{} of {} {noun} passed", progress.passed, progress.total);
+    let others: Vec<String> = [
+        (progress.running, "running"),
+        (progress.blocked_on_human, "blocked on a human"),
+        (progress.checking, "checking"),
+        (progress.assigned, "assigned"),
+        (progress.pending, "pending"),
+        (progress.failed, "failed"),
+        (progress.escalated, "escalated"),
+        (progress.cancelled, "cancelled"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{count} {label}"))
+    .collect();
+    if !others.is_empty() {
+        sentence.push_str("; ");
+        sentence.push_str(&others.join(", "));
+    }
+    sentence
+}
+
+/// The slice of a long listing to render: up to `limit` items starting at
+/// `offset`, clamped to `total`. A `limit` of 0 means no limit.
+pub fn page(total: usize, offset: usize, limit: usize) -> std::ops::Range<usize> {
+    let start = offset.min(total);
+    let end = if limit == 0 {
+        total
+    } else {
+        start.saturating_add(limit).min(total)
+    };
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(passed: i64, running: i64, failed: i64, total: i64) -> PlanProgress {
+        PlanProgress {
+            pending: total - passed - running - failed,
+            assigned: 0,
+            running,
+            blocked_on_human: 0,
+            checking: 0,
+            passed,
+            failed,
+            escalated: 0,
+            cancelled: 0,
+            total,
+        }
+    }
+
+    #[test]
+    fn progress_sentence_lists_nonzero_counts() {
+        assert_eq!(
+            progress_sentence(&progress(3, 2, 1, 7)),
+            "3 of 7 tasks passed; 2 running, 1 pending, 1 failed"
+        );
+        assert_eq!(
+            progress_sentence(&progress(1, 0, 0, 1)),
+            "1 of 1 task passed"
+        );
+        assert_eq!(progress_sentence(&progress(0, 0, 0, 0)), "no tasks");
+    }
+
+    #[test]
+    fn page_clamps_to_the_listing() {
+        assert_eq!(page(400, 0, 50), 0..50);
+        assert_eq!(page(400, 380, 50), 380..400);
+        assert_eq!(page(400, 500, 50), 400..400);
+        assert_eq!(page(400, 10, 0), 10..400);
+        assert_eq!(page(3, 0, 50), 0..3);
+    }
+}
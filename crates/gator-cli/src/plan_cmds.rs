@@ -8,7 +8,14 @@
 //! - `gator plan show [plan-id]`    -- show plan details or list all plans
 //! - `gator plan approve <plan-id>` -- transition a plan from draft to approved
 //! - `gator plan export <plan-id>`  -- export a plan as TOML
+//! - `gator plan diff <file>`       -- compare a created plan's file with the database
+//! - `gator plan sync <file>`       -- apply a plan file's safe changes to the database
 //! - `gator plan reset <plan-id>`   -- reset a failed plan for re-dispatch
+//! - `gator plan archive <plan-id>` -- hide a plan from listings (`unarchive` undoes it)
+//! - `gator plan delete <plan-id>`  -- soft-delete a plan, optionally purging events
+//!   and worktrees
+//! - `gator plan task add|remove|edit <plan-id> <name>` -- edit a draft plan's tasks
+//! - `gator plan import-issues`     -- generate a plan TOML from GitHub issues
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -21,22 +28,32 @@ use sqlx::SqlitePool;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use gator_core::forge::GithubForge;
 use gator_core::harness::{ClaudeCodeAdapter, HarnessRegistry};
 use gator_core::isolation;
-use gator_core::orchestrator::{OrchestratorConfig, OrchestratorResult, run_orchestrator};
+use gator_core::notify::NotificationConfig;
+use gator_core::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, OrchestratorConfig, OrchestratorResult, WatchdogConfig, run_orchestrator,
+};
+use gator_core::plan::overlap::{FootprintSource, Overlap, OverlapReport};
 use gator_core::plan::{
-    GenerateContext, build_meta_plan, build_system_prompt, create_plan_from_toml, detect_context,
-    get_plan_with_tasks, invariants_from_presets, materialize_plan, parse_plan_toml,
-    validate_generated_plan,
+    GenerateContext, IssueImportOptions, PlanDiff, PlanRisk, RiskFactor, TaskChanges, TaskRisk,
+    TaskToml, analyze_plan_overlaps, assess_plan_risk, build_meta_plan, build_system_prompt,
+    clone_plan, create_plan_from_toml, description_size_warnings, detect_context, diff_plan,
+    get_plan_with_tasks, infer_dependencies, invariants_from_presets, materialize_plan,
+    parse_plan_toml, plan_from_issues, sync_plan, validate_generated_plan,
 };
 use gator_core::presets;
 use gator_core::token::TokenConfig;
-use gator_db::models::{InvariantKind, InvariantScope};
+use gator_core::worktree::WorktreeManager;
+use gator_db::models::{CacheKind, DependencyKind, InvariantKind, InvariantScope};
 use gator_db::queries::{
-    gate_results, invariants as inv_queries, plans as plan_queries, tasks as task_queries,
+    agent_events, gate_results, invariants as inv_queries, notes as note_queries,
+    plans as plan_queries, task_revisions, tasks as task_queries,
 };
 
-use crate::PlanCommands;
+use crate::timefmt;
+use crate::{PlanCommands, PlanTaskCommands};
 
 // -----------------------------------------------------------------------
 // Public entry point
@@ -93,29 +110,106 @@ pub async fn run_plan_command(
             .await
         }
         PlanCommands::Validate { file } => cmd_plan_validate(&file),
-        PlanCommands::Create { file } => {
+        PlanCommands::Create { file, infer_deps } => {
             let pool = pool.context("database connection required for plan create")?;
-            cmd_create(pool, &file).await
+            cmd_create(pool, &file, infer_deps).await
         }
-        PlanCommands::Show { plan_id } => {
+        PlanCommands::Show {
+            plan_id,
+            full,
+            limit,
+            offset,
+            archived,
+        } => {
             let pool = pool.context("database connection required for plan show")?;
             match plan_id {
-                Some(id) => cmd_show_one(pool, &id).await,
-                None => cmd_show_all(pool).await,
+                Some(id) => cmd_show_one(pool, &id, full, limit, offset).await,
+                None => cmd_show_all(pool, archived).await,
             }
         }
-        PlanCommands::Approve { plan_id } => {
+        PlanCommands::Describe {
+            plan_id,
+            task,
+            history,
+        } => {
+            let pool = pool.context("database connection required for plan describe")?;
+            cmd_describe(pool, &plan_id, &task, history).await
+        }
+        PlanCommands::Analyze { plan_id } => {
+            let pool = pool.context("database connection required for plan analyze")?;
+            cmd_analyze(pool, &plan_id).await
+        }
+        PlanCommands::Approve { plan_id, dry_run } => {
             let pool = pool.context("database connection required for plan approve")?;
-            cmd_approve(pool, &plan_id).await
+            cmd_approve(pool, &plan_id, dry_run).await
         }
         PlanCommands::Export { plan_id, output } => {
             let pool = pool.context("database connection required for plan export")?;
             cmd_export(pool, &plan_id, output.as_deref()).await
         }
+        PlanCommands::Diff { file } => {
+            let pool = pool.context("database connection required for plan diff")?;
+            cmd_diff(pool, &file).await
+        }
+        PlanCommands::Sync { file } => {
+            let pool = pool.context("database connection required for plan sync")?;
+            cmd_sync(pool, &file).await
+        }
         PlanCommands::Reset { plan_id } => {
             let pool = pool.context("database connection required for plan reset")?;
             cmd_reset(pool, &plan_id).await
         }
+        PlanCommands::Clone {
+            plan_id,
+            reset,
+            name,
+        } => {
+            let pool = pool.context("database connection required for plan clone")?;
+            cmd_clone(pool, &plan_id, reset, name.as_deref()).await
+        }
+        PlanCommands::Archive { plan_id } => {
+            let pool = pool.context("database connection required for plan archive")?;
+            cmd_archive(pool, &plan_id).await
+        }
+        PlanCommands::Unarchive { plan_id } => {
+            let pool = pool.context("database connection required for plan unarchive")?;
+            cmd_unarchive(pool, &plan_id).await
+        }
+        PlanCommands::Delete {
+            plan_id,
+            purge_events,
+            remove_worktrees,
+        } => {
+            let pool = pool.context("database connection required for plan delete")?;
+            cmd_delete(pool, &plan_id, purge_events, remove_worktrees).await
+        }
+        PlanCommands::Task { command } => {
+            let pool = pool.context("database connection required for plan task")?;
+            cmd_plan_task(pool, command).await
+        }
+        PlanCommands::ImportIssues {
+            repo,
+            label,
+            output,
+            name,
+            base_branch,
+            scope,
+            gate,
+            invariants,
+            limit,
+        } => {
+            let cwd = std::env::current_dir().context("failed to get current directory")?;
+            let repo_name = repo.rsplit('/').next().unwrap_or(&repo).to_string();
+            let options = IssueImportOptions {
+                plan_name: name.unwrap_or_else(|| format!("{repo_name}-issues")),
+                base_branch: base_branch.unwrap_or_else(|| presets::detect_base_branch(&cwd)),
+                repo,
+                scope,
+                gate,
+                invariants,
+            };
+            cmd_import_issues(&options, &label, limit, &output)
+        }
     }
 }
 
@@ -198,7 +292,8 @@ async fn cmd_plan_init(
 
     // 5. Generate the plan TOML content.
     let invariant_names: Vec<&str> = matching_presets.iter().map(|p| p.name.as_str()).collect();
-    let toml_content = generate_plan_toml(name, &base_branch, &invariant_names);
+    let caches = isolation::cache::caches_for_project_type(&project_type);
+    let toml_content = generate_plan_toml(name, &base_branch, &invariant_names, caches);
 
     // 6. Write to file.
     let output_path = match output_override {
@@ -277,6 +372,12 @@ async fn register_presets(
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         };
 
         inv_queries::insert_invariant(pool, &new).await?;
@@ -296,6 +397,9 @@ async fn register_presets(
 fn cmd_plan_validate(file: &str) -> Result<()> {
     match validate_generated_plan(file) {
         Ok(plan) => {
+            for warning in description_size_warnings(&plan) {
+                eprintln!("Warning: {warning}");
+            }
             println!("Valid. {} task(s).", plan.tasks.len());
             Ok(())
         }
@@ -323,6 +427,12 @@ async fn ensure_plan_gen_invariants(pool: &SqlitePool) -> Result<()> {
             threshold: None,
             scope: InvariantScope::Global,
             timeout_secs: 10,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
         inv_queries::NewInvariant {
             name: "_gator_plan_validates",
@@ -334,6 +444,12 @@ async fn ensure_plan_gen_invariants(pool: &SqlitePool) -> Result<()> {
             threshold: None,
             scope: InvariantScope::Global,
             timeout_secs: 30,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     ];
     for inv in &invariants {
@@ -352,7 +468,12 @@ async fn ensure_plan_gen_invariants(pool: &SqlitePool) -> Result<()> {
 /// The output is hand-built (not `toml::to_string`) to include comments,
 /// which the TOML serializer cannot produce. This is intentional -- the
 /// generated file is a template for human editing.
-fn generate_plan_toml(name: &str, base_branch: &str, invariant_names: &[&str]) -> String {
+fn generate_plan_toml(
+    name: &str,
+    base_branch: &str,
+    invariant_names: &[&str],
+    caches: &[CacheKind],
+) -> String {
     let mut out = String::new();
 
     // [plan] section
@@ -362,6 +483,24 @@ fn generate_plan_toml(name: &str, base_branch: &str, invariant_names: &[&str]) -
     out.push_str("# token_budget = 500000\n");
     out.push_str("# isolation = \"worktree\"\n");
     out.push_str("# container_image = \"gator-agent:latest\"\n");
+    out.push_str("# ephemeral_db = \"postgres:16-alpine\"\n");
+    out.push_str("# template_setup = \"cargo build\"\n");
+    out.push_str("# template_hardlinks = true\n");
+    out.push_str("# setup_commands = [\"npm ci\"]\n");
+    out.push_str("# warmup_command = \"cargo build\"\n");
+    if !caches.is_empty() {
+        let quoted: Vec<String> = caches.iter().map(|c| format!("\"{c}\"")).collect();
+        out.push_str(&format!("# caches = [{}]\n", quoted.join(", ")));
+    }
+    out.push_str("\n# Defaults for tasks that leave these fields out.\n");
+    out.push_str("# [plan.defaults]\n");
+    out.push_str("# scope = \"narrow\"\n");
+    out.push_str("# gate = \"auto\"\n");
+    out.push_str("# timeout = 1800\n");
+    out.push_str("\n# Commands run at points of the plan's life.\n");
+    out.push_str("# [hooks]\n");
+    out.push_str("# pre_dispatch = \"./scripts/setup.sh\"\n");
+    out.push_str("# post_plan = \"./scripts/notify.sh\"\n");
 
     // [[tasks]] stub
     out.push_str("\n[[tasks]]\n");
@@ -381,8 +520,10 @@ fn generate_plan_toml(name: &str, base_branch: &str, invariant_names: &[&str]) -
     }
 
     out.push_str("# depends_on = []\n");
+    out.push_str("# ordering_only = []\n");
     out.push_str("# retry_max = 3\n");
     out.push_str("# harness = \"claude-code\"\n");
+    out.push_str("# timeout = 1800\n");
 
     out
 }
@@ -514,7 +655,17 @@ async fn cmd_plan_generate(
 
     let config = OrchestratorConfig {
         max_agents: 1,
+        max_total_agents: crate::config::resolve_max_total_agents()?,
         task_timeout: Duration::from_secs(1800),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
     };
 
     // e. Graceful shutdown handler.
@@ -695,7 +846,7 @@ async fn handle_generate_result(
             eprintln!("Plan generation stopped: token budget exceeded ({used}/{budget}).");
             std::process::exit(3);
         }
-        OrchestratorResult::Interrupted => {
+        OrchestratorResult::Interrupted | OrchestratorResult::SelectionCompleted { .. } => {
             eprintln!("Plan generation interrupted.");
             eprintln!("Audit: gator status {plan_id}");
             std::process::exit(130);
@@ -715,19 +866,74 @@ fn copy_plan_from_worktree(worktree_path: Option<&str>, output: &str) -> Result<
     Ok(())
 }
 
+// -----------------------------------------------------------------------
+// gator plan import-issues
+// -----------------------------------------------------------------------
+
+/// Fetch open issues with `label` from GitHub and write a plan TOML with one
+/// task per issue.
+fn cmd_import_issues(
+    options: &IssueImportOptions,
+    label: &str,
+    limit: u32,
+    output: &str,
+) -> Result<()> {
+    if !gator_core::plan::parser::is_valid_issue_ref(&format!("{}#1", options.repo)) {
+        bail!("invalid --repo {:?} (expected owner/name)", options.repo);
+    }
+    if Path::new(output).exists() {
+        bail!(
+            "output file {:?} already exists. Remove it or use --output to specify a different path.",
+            output
+        );
+    }
+
+    let issues = GithubForge::new(&options.repo)?.list_issues(label, limit)?;
+    if issues.is_empty() {
+        bail!("no open issues labelled {:?} in {}", label, options.repo);
+    }
+
+    let plan = plan_from_issues(&issues, options);
+    let content = toml::to_string(&plan).context("failed to serialize plan")?;
+    // Re-parse so invalid --scope/--gate values are caught before writing.
+    parse_plan_toml(&content).context("generated plan failed validation")?;
+
+    std::fs::write(output, &content).with_context(|| format!("failed to write {output}"))?;
+
+    println!(
+        "Imported {} issue(s) from {} into {}",
+        plan.tasks.len(),
+        options.repo,
+        output
+    );
+    for task in &plan.tasks {
+        println!(
+            "  {:<50} {}",
+            task.name,
+            task.issue.as_deref().unwrap_or("")
+        );
+    }
+    println!();
+    println!("Next steps:");
+    println!("  1. Review {output} (add dependencies and invariants as needed)");
+    println!("  2. Run `gator plan create {output}` to load it into the database");
+
+    Ok(())
+}
+
 // -----------------------------------------------------------------------
 // gator plan create <file>
 // -----------------------------------------------------------------------
 
-/// Read a plan.toml from disk, parse and validate it, insert into the DB,
-/// and print a summary.
-async fn cmd_create(pool: &SqlitePool, file_path: &str) -> Result<()> {
+/// Read a plan.toml from disk (or standard input when `file_path` is
+/// `-`), parse and validate it, insert into the DB, and print a summary.
+async fn cmd_create(pool: &SqlitePool, file_path: &str, infer_deps: bool) -> Result<()> {
     // 1. Read the file.
-    let content = std::fs::read_to_string(file_path)
+    let content = read_input(file_path)
         .with_context(|| format!("failed to read plan file: {}", file_path))?;
 
     // 2. Parse and validate.
-    let plan_toml = parse_plan_toml(&content)
+    let mut plan_toml = parse_plan_toml(&content)
         .with_context(|| format!("failed to parse plan file: {}", file_path))?;
 
     // 3. Reject if the file already has a plan ID.
@@ -740,6 +946,20 @@ async fn cmd_create(pool: &SqlitePool, file_path: &str) -> Result<()> {
         );
     }
 
+    for warning in description_size_warnings(&plan_toml) {
+        eprintln!("Warning: {warning}");
+    }
+
+    if infer_deps {
+        for dep in infer_dependencies(&mut plan_toml.tasks) {
+            let (a, b) = &dep.pairs[0];
+            println!(
+                "Inferred dependency: {} depends on {} ({a} overlaps {b})",
+                dep.task, dep.depends_on
+            );
+        }
+    }
+
     // 4. Determine the project path (current working directory).
     let project_path = std::env::current_dir()
         .context("failed to get current directory")?
@@ -749,13 +969,16 @@ async fn cmd_create(pool: &SqlitePool, file_path: &str) -> Result<()> {
     // 5. Insert into DB.
     let plan = create_plan_from_toml(pool, &plan_toml, &project_path).await?;
 
-    // 6. Write plan ID back to the TOML file.
-    crate::resolve::write_plan_id_to_file(file_path, plan.id).with_context(|| {
-        format!(
-            "plan created (ID: {}), but failed to update {}",
-            plan.id, file_path
-        )
-    })?;
+    // 6. Write plan ID back to the TOML file. A plan read from stdin has
+    // no file to write to; it is referred to by ID instead.
+    if file_path != STDIN_PATH {
+        crate::resolve::write_plan_id_to_file(file_path, plan.id).with_context(|| {
+            format!(
+                "plan created (ID: {}), but failed to update {}",
+                plan.id, file_path
+            )
+        })?;
+    }
 
     // 7. Count dependency edges.
     let dep_edges = task_queries::count_dependency_edges(pool, plan.id).await?;
@@ -768,7 +991,12 @@ async fn cmd_create(pool: &SqlitePool, file_path: &str) -> Result<()> {
     println!("  Status:           {}", plan.status);
     println!("  Tasks:            {}", plan_toml.tasks.len());
     println!("  Dependency edges: {}", dep_edges);
-    println!("  Written to:       {}", file_path);
+    if file_path == STDIN_PATH {
+        println!();
+        println!("Read from stdin; refer to the plan by its ID.");
+    } else {
+        println!("  Written to:       {}", file_path);
+    }
 
     Ok(())
 }
@@ -777,12 +1005,20 @@ async fn cmd_create(pool: &SqlitePool, file_path: &str) -> Result<()> {
 // gator plan show (list all)
 // -----------------------------------------------------------------------
 
-/// List all plans with summary info.
-async fn cmd_show_all(pool: &SqlitePool) -> Result<()> {
-    let plans = plan_queries::list_plans(pool).await?;
+/// List all plans with summary info, or only archived ones.
+async fn cmd_show_all(pool: &SqlitePool, archived: bool) -> Result<()> {
+    let plans = if archived {
+        plan_queries::list_archived_plans(pool).await?
+    } else {
+        plan_queries::list_plans(pool).await?
+    };
 
     if plans.is_empty() {
-        println!("No plans found. Use `gator plan create <file>` to create one.");
+        if archived {
+            println!("No archived plans.");
+        } else {
+            println!("No plans found. Use `gator plan create <file>` to create one.");
+        }
         return Ok(());
     }
 
@@ -797,6 +1033,21 @@ async fn cmd_show_all(pool: &SqlitePool) -> Result<()> {
         task_counts.insert(plan.id, row.0);
     }
 
+    if crate::output::is_plain() {
+        for plan in &plans {
+            let count = task_counts.get(&plan.id).copied().unwrap_or(0);
+            println!(
+                "Plan {}: {}, {} tasks, created {}. ID {}.",
+                plan.name,
+                plan.status,
+                count,
+                crate::timefmt::format(plan.created_at, "%Y-%m-%d %H:%M"),
+                plan.id,
+            );
+        }
+        return Ok(());
+    }
+
     // Compute column widths for a clean table.
     // ID is always 36 chars (UUID). Status max is 9 (completed).
     let id_w = 36;
@@ -813,7 +1064,7 @@ async fn cmd_show_all(pool: &SqlitePool) -> Result<()> {
     // Rows
     for plan in &plans {
         let count = task_counts.get(&plan.id).copied().unwrap_or(0);
-        let created = plan.created_at.format("%Y-%m-%d %H:%M");
+        let created = crate::timefmt::format(plan.created_at, "%Y-%m-%d %H:%M");
         println!(
             "{:<id_w$}  {:<name_w$}  {:<status_w$}  {:>tasks_w$}  {}",
             plan.id, plan.name, plan.status, count, created,
@@ -827,63 +1078,131 @@ async fn cmd_show_all(pool: &SqlitePool) -> Result<()> {
 // gator plan show <plan-id>
 // -----------------------------------------------------------------------
 
+/// Number of description lines `plan show` prints without `--full`.
+const DESCRIPTION_PREVIEW_LINES: usize = 10;
+
 /// Show detailed info for a single plan.
-async fn cmd_show_one(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+///
+/// Task descriptions are cut to [`DESCRIPTION_PREVIEW_LINES`] lines unless
+/// `full` is set. Only `limit` tasks from `offset` are shown (a `limit` of 0
+/// shows all); dependencies and invariants are loaded for the whole plan in
+/// one query each.
+async fn cmd_show_one(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    full: bool,
+    limit: usize,
+    offset: usize,
+) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     let (plan, tasks) = get_plan_with_tasks(pool, plan_id).await?;
+    let mut dependencies = task_queries::list_plan_dependency_names(pool, plan_id).await?;
+    let mut invariants_by_task = inv_queries::list_invariants_for_plan(pool, plan_id).await?;
 
     // Plan header.
     println!("Plan: {}", plan.name);
     println!("  ID:           {}", plan.id);
-    println!("  Status:       {}", plan.status);
+    println!(
+        "  Status:       {}",
+        crate::status_cmd::describe_plan_status(&plan)
+    );
     println!("  Project:      {}", plan.project_path);
     println!("  Base branch:  {}", plan.base_branch);
     println!(
         "  Created:      {}",
-        plan.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        crate::timefmt::timestamp_with_age(plan.created_at)
     );
     if let Some(approved) = plan.approved_at {
         println!(
             "  Approved:     {}",
-            approved.format("%Y-%m-%d %H:%M:%S UTC")
+            crate::timefmt::timestamp_with_age(approved)
         );
     }
     if let Some(completed) = plan.completed_at {
         println!(
             "  Completed:    {}",
-            completed.format("%Y-%m-%d %H:%M:%S UTC")
+            crate::timefmt::timestamp_with_age(completed)
+        );
+    }
+    if let Some(archived) = plan.archived_at {
+        println!(
+            "  Archived:     {}",
+            crate::timefmt::timestamp_with_age(archived)
+        );
+    }
+    if let Some(interrupted) = plan.interrupted_at {
+        println!(
+            "  Interrupted:  {} (resume with gator dispatch)",
+            crate::timefmt::timestamp_with_age(interrupted)
         );
     }
     println!("  Tasks:        {}", tasks.len());
 
+    let notes = note_queries::list_notes_for_plan(pool, plan_id).await?;
+    let (plan_notes, task_notes): (Vec<_>, Vec<_>) =
+        notes.into_iter().partition(|n| n.task_id.is_none());
+    if !plan_notes.is_empty() {
+        println!();
+        println!("Notes:");
+        for line in crate::note_cmd::note_lines(&plan_notes, &tasks) {
+            println!("  {line}");
+        }
+    }
+
     if tasks.is_empty() {
         return Ok(());
     }
 
+    let shown = crate::output::page(tasks.len(), offset, limit);
     println!();
-    println!("Tasks:");
+    if shown.len() == tasks.len() {
+        println!("Tasks:");
+    } else {
+        println!(
+            "Tasks ({}-{} of {}):",
+            shown.start + 1,
+            shown.end,
+            tasks.len()
+        );
+    }
     println!();
 
-    for task in &tasks {
-        // Get dependencies.
-        let dep_names = task_queries::get_task_dependency_names(pool, task.id).await?;
-
-        // Get linked invariants.
-        let invariants = inv_queries::get_invariants_for_task(pool, task.id).await?;
+    for task in &tasks[shown.clone()] {
+        let dependencies = dependencies.remove(&task.id).unwrap_or_default();
+        let invariants = invariants_by_task.remove(&task.id).unwrap_or_default();
 
         println!("  [{}] {}", task.status, task.name);
         println!("    ID:          {}", task.id);
         println!("    Scope:       {}", task.scope_level);
-        println!("    Gate:        {}", task.gate_policy);
+        println!("    Gate:        {}", task.gate());
         println!("    Retry:       {}/{}", task.attempt, task.retry_max);
 
-        if !dep_names.is_empty() {
-            println!("    Depends on:  {}", dep_names.join(", "));
+        if !dependencies.is_empty() {
+            let deps: Vec<String> = dependencies
+                .iter()
+                .map(|(d, kind)| {
+                    if *kind == DependencyKind::OrderingOnly {
+                        format!("{d} (ordering only)")
+                    } else {
+                        d.clone()
+                    }
+                })
+                .collect();
+            println!("    Depends on:  {}", deps.join(", "));
         }
 
         if !invariants.is_empty() {
-            let inv_names: Vec<&str> = invariants.iter().map(|i| i.name.as_str()).collect();
+            let inv_names: Vec<String> = invariants
+                .iter()
+                .map(|i| {
+                    if i.disabled_at.is_some() {
+                        format!("{} (disabled)", i.name)
+                    } else {
+                        i.name.clone()
+                    }
+                })
+                .collect();
             println!("    Invariants:  {}", inv_names.join(", "));
         }
 
@@ -891,20 +1210,225 @@ async fn cmd_show_one(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
         let desc = task.description.trim();
         if !desc.is_empty() {
             println!("    Description:");
-            for line in desc.lines().take(10) {
+            let limit = if full {
+                usize::MAX
+            } else {
+                DESCRIPTION_PREVIEW_LINES
+            };
+            for line in desc.lines().take(limit) {
                 println!("      {}", line);
             }
-            if desc.lines().count() > 10 {
-                println!("      ...(truncated)");
+            let total = desc.lines().count();
+            if total > limit {
+                println!(
+                    "      ...({} more lines; see `gator plan describe {} {}`)",
+                    total - limit,
+                    plan.id,
+                    task.name
+                );
+            }
+        }
+
+        let notes: Vec<_> = task_notes
+            .iter()
+            .filter(|n| n.task_id == Some(task.id))
+            .cloned()
+            .collect();
+        if !notes.is_empty() {
+            println!("    Notes:");
+            for line in crate::note_cmd::note_lines(&notes, &[]) {
+                println!("      {line}");
             }
         }
 
         println!();
     }
 
+    if shown.end < tasks.len() {
+        println!(
+            "...{} more tasks; see `gator plan show {} --offset {}`",
+            tasks.len() - shown.end,
+            plan.id,
+            shown.end
+        );
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator plan describe <plan-id> <task>
+// -----------------------------------------------------------------------
+
+/// Print one task's description verbatim, so it can be paged or piped.
+async fn cmd_describe(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    task_ref: &str,
+    history: bool,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+
+    let (plan, tasks) = get_plan_with_tasks(pool, plan_id).await?;
+    let task = tasks
+        .iter()
+        .find(|t| t.name == task_ref || t.id.to_string() == task_ref)
+        .with_context(|| format!("no task {task_ref:?} in plan {:?}", plan.name))?;
+
+    if !history {
+        print_text(&task.description);
+        return Ok(());
+    }
+
+    // Oldest first: each revision replaced the text before it.
+    let revisions = task_revisions::list_task_revisions(pool, task.id).await?;
+    if let Some(first) = revisions.first() {
+        println!("== Original ==");
+        print_text(&first.previous);
+    }
+    for revision in &revisions {
+        println!();
+        println!(
+            "== Revised {} (from attempt {}) ==",
+            timefmt::timestamp(revision.created_at),
+            revision.attempt
+        );
+        print_text(&revision.description);
+    }
+    if revisions.is_empty() {
+        print_text(&task.description);
+        println!();
+        println!("(no revisions)");
+    }
+
+    Ok(())
+}
+
+/// Print `text`, ending it with a newline if it lacks one.
+fn print_text(text: &str) {
+    print!("{text}");
+    if !text.ends_with('\n') {
+        println!();
+    }
+}
+
+// -----------------------------------------------------------------------
+// gator plan analyze <plan-id>
+// -----------------------------------------------------------------------
+
+/// Print each task's expected footprint, the overlap matrix, and the pairs
+/// of tasks that can run in parallel but touch the same files.
+async fn cmd_analyze(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+    let report = analyze_plan_overlaps(pool, plan_id).await?;
+    if report.footprints.is_empty() {
+        println!("Plan has no tasks.");
+        return Ok(());
+    }
+
+    let plain = crate::output::is_plain();
+    let width = report
+        .footprints
+        .iter()
+        .map(|f| f.task.len())
+        .max()
+        .unwrap_or(0);
+
+    println!("Tasks:");
+    for (i, footprint) in report.footprints.iter().enumerate() {
+        let source = match footprint.source {
+            FootprintSource::Declared => "declared",
+            FootprintSource::Hinted => "hinted",
+            FootprintSource::Unknown => "unknown",
+        };
+        let patterns = if footprint.patterns.is_empty() {
+            "-".to_string()
+        } else {
+            footprint.patterns.join(", ")
+        };
+        if plain {
+            println!(
+                "  Task {} ({}): {source} paths {patterns}.",
+                i + 1,
+                footprint.task
+            );
+        } else {
+            println!(
+                "  {:>3}  {:<width$}  {source:<8}  {patterns}",
+                i + 1,
+                footprint.task
+            );
+        }
+    }
+
+    if !plain {
+        println!();
+        println!("Overlap matrix (X overlap, - disjoint, o ordered, ? unknown):");
+        let mut header = "     ".to_string();
+        for j in 0..report.footprints.len() {
+            header.push_str(&format!("{:>3}", j + 1));
+        }
+        println!("{header}");
+        for (i, row) in report.cells.iter().enumerate() {
+            let mut line = format!("  {:>3}", i + 1);
+            for cell in row {
+                let mark = match cell {
+                    Overlap::SameTask => ".",
+                    Overlap::Ordered => "o",
+                    Overlap::Unknown => "?",
+                    Overlap::Disjoint => "-",
+                    Overlap::Overlapping(_) => "X",
+                };
+                line.push_str(&format!("{mark:>3}"));
+            }
+            println!("{line}");
+        }
+    }
+
+    println!();
+    let conflicts = overlap_conflict_lines(&report);
+    if conflicts.is_empty() {
+        println!("No overlapping tasks can run in parallel.");
+    } else {
+        println!("Overlapping tasks that can run in parallel:");
+        for line in conflicts {
+            println!("  {line}");
+        }
+    }
+
     Ok(())
 }
 
+/// One line per pair of parallel tasks with overlapping footprints, saying
+/// whether dispatch serializes them (both declare `paths`) or only warns.
+pub(crate) fn overlap_conflict_lines(report: &OverlapReport) -> Vec<String> {
+    report
+        .conflicts()
+        .into_iter()
+        .map(|(i, j)| {
+            let (a, b) = (&report.footprints[i], &report.footprints[j]);
+            let shared: Vec<String> = match &report.cells[i][j] {
+                Overlap::Overlapping(pairs) => {
+                    pairs.iter().map(|(x, y)| format!("{x} ~ {y}")).collect()
+                }
+                _ => Vec::new(),
+            };
+            let outcome =
+                if a.source == FootprintSource::Declared && b.source == FootprintSource::Declared {
+                    "run one at a time"
+                } else {
+                    "may conflict; declare `paths` on both to run them one at a time"
+                };
+            format!(
+                "{} and {} ({}): {outcome}",
+                a.task,
+                b.task,
+                shared.join(", ")
+            )
+        })
+        .collect()
+}
+
 // -----------------------------------------------------------------------
 // gator plan approve <plan-id>
 // -----------------------------------------------------------------------
@@ -912,8 +1436,10 @@ async fn cmd_show_one(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
 /// Transition a plan from draft to approved.
 ///
 /// Validates that all tasks have at least one invariant linked before
-/// approving.
-async fn cmd_approve(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+/// approving, and prints the plan's risk score so operators can move risky
+/// `auto`-gated tasks to `human_review`. With `dry_run`, only the risk is
+/// shown and the plan stays a draft.
+async fn cmd_approve(pool: &SqlitePool, plan_id_str: &str, dry_run: bool) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     // Check that all tasks have at least one invariant.
@@ -926,6 +1452,14 @@ async fn cmd_approve(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
         );
     }
 
+    let risk = assess_plan_risk(pool, plan_id).await?;
+
+    if dry_run {
+        println!("Risk: {} (score {})", risk.level(), risk.score());
+        print_risk(&risk, Some(plan_id));
+        return Ok(());
+    }
+
     // Perform the approval transition.
     let plan = plan_queries::approve_plan(pool, plan_id).await?;
 
@@ -935,15 +1469,49 @@ async fn cmd_approve(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
     println!("  Name:        {}", plan.name);
     println!("  Status:      {}", plan.status);
     if let Some(approved) = plan.approved_at {
-        println!(
-            "  Approved at: {}",
-            approved.format("%Y-%m-%d %H:%M:%S UTC")
-        );
+        println!("  Approved at: {}", crate::timefmt::timestamp(approved));
     }
+    println!("  Risk:        {} (score {})", risk.level(), risk.score());
+
+    print_risk(&risk, None);
 
     Ok(())
 }
 
+/// Per-task risk factors, and the tasks that should be gated `human_review`.
+/// `draft_id` is set while the plan can still be edited, to show how.
+fn print_risk(risk: &PlanRisk, draft_id: Option<Uuid>) {
+    let scored: Vec<&TaskRisk> = risk.tasks.iter().filter(|t| t.score() > 0).collect();
+    if scored.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Risk factors:");
+    for task in &scored {
+        let factors: Vec<String> = task.factors.iter().map(RiskFactor::describe).collect();
+        println!("  {} ({}): {}", task.task, task.score(), factors.join("; "));
+    }
+
+    let review: Vec<&str> = scored
+        .iter()
+        .filter(|t| t.suggests_review())
+        .map(|t| t.task.as_str())
+        .collect();
+    if !review.is_empty() {
+        println!();
+        println!(
+            "Consider gate = \"human_review\" for: {}",
+            review.join(", ")
+        );
+        if let Some(plan_id) = draft_id {
+            for name in &review {
+                println!("  gator plan task edit {plan_id} {name} --gate human_review");
+            }
+        }
+    }
+}
+
 // -----------------------------------------------------------------------
 // gator plan export <plan-id> [--output <file>]
 // -----------------------------------------------------------------------
@@ -968,6 +1536,74 @@ async fn cmd_export(pool: &SqlitePool, plan_id_str: &str, output: Option<&str>)
     Ok(())
 }
 
+// -----------------------------------------------------------------------
+// gator plan diff|sync <file>
+// -----------------------------------------------------------------------
+
+/// Read a created plan's file: its id and its parsed contents.
+fn read_created_plan_file(file_path: &str) -> Result<(Uuid, gator_core::plan::PlanToml)> {
+    let content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("failed to read plan file: {}", file_path))?;
+    let plan_toml = parse_plan_toml(&content)
+        .with_context(|| format!("failed to parse plan file: {}", file_path))?;
+    let plan_id = plan_toml.plan.id.with_context(|| {
+        format!("plan file {file_path:?} has no id; create it with `gator plan create` first")
+    })?;
+    Ok((plan_id, plan_toml))
+}
+
+/// Print each change, with why sync skips it.
+fn print_plan_diff(diff: &PlanDiff) {
+    for change in &diff.changes {
+        match &change.blocked {
+            None => println!("  {}", change.change),
+            Some(reason) => println!("  {}  (not synced: {reason})", change.change),
+        }
+    }
+}
+
+/// Show how a created plan's file differs from the stored plan.
+async fn cmd_diff(pool: &SqlitePool, file_path: &str) -> Result<()> {
+    let (plan_id, plan_toml) = read_created_plan_file(file_path)?;
+    let diff = diff_plan(pool, plan_id, &plan_toml).await?;
+
+    if diff.changes.is_empty() {
+        println!("{file_path} matches plan {plan_id}.");
+        return Ok(());
+    }
+    println!("{file_path} differs from plan {plan_id}:");
+    print_plan_diff(&diff);
+    let safe = diff.safe().count();
+    if safe > 0 {
+        println!();
+        println!("`gator plan sync {file_path}` applies {safe} of these.");
+    }
+    Ok(())
+}
+
+/// Apply a created plan file's safe changes to the stored plan.
+async fn cmd_sync(pool: &SqlitePool, file_path: &str) -> Result<()> {
+    let (plan_id, plan_toml) = read_created_plan_file(file_path)?;
+    let diff = sync_plan(pool, plan_id, &plan_toml).await?;
+
+    if diff.changes.is_empty() {
+        println!("{file_path} matches plan {plan_id}; nothing to sync.");
+        return Ok(());
+    }
+    let synced = diff.safe().count();
+    println!("Synced {synced} change(s) from {file_path} to plan {plan_id}:");
+    print_plan_diff(&diff);
+    let skipped = diff.changes.len() - synced;
+    if skipped > 0 {
+        println!();
+        println!(
+            "{skipped} change(s) not synced. Edit draft plans with `gator plan task`, \
+             or clone the plan to change the rest."
+        );
+    }
+    Ok(())
+}
+
 // -----------------------------------------------------------------------
 // gator plan reset <plan-id>
 // -----------------------------------------------------------------------
@@ -1002,6 +1638,299 @@ async fn cmd_reset(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
     Ok(())
 }
 
+// -----------------------------------------------------------------------
+// gator plan clone <plan-id> [--reset] [--name <name>]
+// -----------------------------------------------------------------------
+
+/// Copy a plan into a new draft so it can be re-run without re-authoring
+/// its TOML.
+async fn cmd_clone(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    reset: bool,
+    name: Option<&str>,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+
+    let plan = clone_plan(pool, plan_id, name, reset).await?;
+    let tasks = task_queries::list_tasks_for_plan(pool, plan.id).await?;
+    let dep_edges = task_queries::count_dependency_edges(pool, plan.id).await?;
+
+    println!("Plan cloned.");
+    println!();
+    println!("  Plan ID:          {}", plan.id);
+    println!("  Cloned from:      {plan_id}");
+    println!("  Name:             {}", plan.name);
+    println!("  Status:           {}", plan.status);
+    println!("  Tasks:            {}", tasks.len());
+    println!("  Dependency edges: {}", dep_edges);
+    if reset {
+        println!("  Attempts:         reset");
+    }
+    println!();
+    println!("Next: gator plan approve {}", plan.id);
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator plan archive|unarchive <plan-id>
+// -----------------------------------------------------------------------
+
+/// Hide a plan from `plan show`, `status`, and the dashboard. Refuses
+/// running plans.
+async fn cmd_archive(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+
+    let plan = plan_queries::archive_plan(pool, plan_id).await?;
+
+    println!("Plan archived: {} ({})", plan.name, plan.id);
+    println!("List archived plans with: gator plan show --archived");
+
+    Ok(())
+}
+
+/// Bring an archived plan back into listings.
+async fn cmd_unarchive(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+
+    let plan = plan_queries::unarchive_plan(pool, plan_id).await?;
+
+    println!("Plan unarchived: {} ({})", plan.name, plan.id);
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator plan delete <plan-id>
+// -----------------------------------------------------------------------
+
+/// Soft-delete a plan and its tasks. Refuses running plans.
+///
+/// `purge_events` drops the plan's agent event logs right away instead of
+/// at the end of the retention window; `remove_worktrees` removes the task
+/// worktrees and branches. Neither comes back with `gator restore`.
+async fn cmd_delete(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    purge_events: bool,
+    remove_worktrees: bool,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+    crate::restore_cmd::prune_expired(pool).await?;
+
+    // Read the tasks first: they are hidden once the plan is deleted.
+    let tasks = task_queries::list_tasks_for_plan(pool, plan_id).await?;
+    let plan = plan_queries::soft_delete_plan(pool, plan_id).await?;
+
+    println!("Plan deleted: {} ({})", plan.name, plan.id);
+
+    if purge_events {
+        let purged = agent_events::delete_events_for_plan(pool, plan.id).await?;
+        println!("Agent events purged: {purged}");
+    }
+
+    if remove_worktrees {
+        match WorktreeManager::new(&plan.project_path, None) {
+            Ok(manager) => {
+                let (removed, failed) = remove_plan_worktrees(&manager, &plan.name, &tasks);
+                let _ = manager.cleanup_stale();
+                println!("Worktrees and branches removed: {removed}");
+                if failed > 0 {
+                    eprintln!("Warning: {failed} worktree(s) or branch(es) could not be removed");
+                }
+            }
+            Err(e) => eprintln!(
+                "Warning: cannot remove worktrees in {}: {e}",
+                plan.project_path
+            ),
+        }
+    }
+
+    println!(
+        "Restorable for {} days with: gator restore plan {}",
+        crate::restore_cmd::DELETED_RETENTION_DAYS,
+        plan.id
+    );
+
+    Ok(())
+}
+
+/// Remove each task's worktree and branch, and the plan's template
+/// worktree. Returns `(removed, failed)`.
+fn remove_plan_worktrees(
+    manager: &WorktreeManager,
+    plan_name: &str,
+    tasks: &[gator_db::models::Task],
+) -> (usize, usize) {
+    let (mut removed, mut failed) = (0, 0);
+    if manager.template_path(plan_name).exists() {
+        match manager.remove_template(plan_name) {
+            Ok(()) => removed += 1,
+            Err(e) => {
+                eprintln!("  Warning: failed to remove the template worktree: {e}");
+                failed += 1;
+            }
+        }
+    }
+    for task in tasks {
+        if let Some(ref path) = task.worktree_path {
+            let path = Path::new(path);
+            if path.exists() {
+                match manager.remove_worktree(path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => {
+                        eprintln!("  Warning: failed to remove worktree of {}: {e}", task.name);
+                        failed += 1;
+                    }
+                }
+            }
+        }
+        let branch = WorktreeManager::branch_name(plan_name, &task.name);
+        if manager.branch_exists(&branch).unwrap_or(false) {
+            match manager.delete_branch(&branch) {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    eprintln!("  Warning: failed to delete branch {branch}: {e}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+    (removed, failed)
+}
+
+// -----------------------------------------------------------------------
+// gator plan task add|remove|edit <plan-id> <name>
+// -----------------------------------------------------------------------
+
+/// Add, remove, or change a task of a draft plan.
+async fn cmd_plan_task(pool: &SqlitePool, command: PlanTaskCommands) -> Result<()> {
+    match command {
+        PlanTaskCommands::Add {
+            plan_id,
+            name,
+            description,
+            description_file,
+            scope,
+            gate,
+            retry_max,
+            mut depends_on,
+            ordering_only,
+            invariants,
+            harness,
+            issue,
+            kind,
+            artifacts,
+            paths,
+        } => {
+            let plan_id = crate::resolve::resolve_plan_id(&plan_id)?;
+            let description = read_description(description, description_file)?
+                .context("a task needs --description or --description-file")?;
+            for dep in &ordering_only {
+                if !depends_on.contains(dep) {
+                    depends_on.push(dep.clone());
+                }
+            }
+            let task = gator_core::plan::add_task(
+                pool,
+                plan_id,
+                TaskToml {
+                    name,
+                    description,
+                    scope,
+                    gate,
+                    retry_max,
+                    depends_on,
+                    ordering_only,
+                    invariants,
+                    harness,
+                    issue,
+                    services: Vec::new(),
+                    kind,
+                    artifacts,
+                    paths,
+                    timeout: None,
+                    setup_commands: Vec::new(),
+                },
+            )
+            .await?;
+            println!("Task added: {} ({})", task.name, task.id);
+        }
+        PlanTaskCommands::Remove { plan_id, name } => {
+            let plan_id = crate::resolve::resolve_plan_id(&plan_id)?;
+            gator_core::plan::remove_task(pool, plan_id, &name).await?;
+            println!("Task removed: {name}");
+        }
+        PlanTaskCommands::Edit {
+            plan_id,
+            name,
+            rename,
+            description,
+            description_file,
+            scope,
+            gate,
+            retry_max,
+            harness,
+            issue,
+            add_deps,
+            add_ordering_deps,
+            remove_deps,
+            add_invariants,
+            remove_invariants,
+        } => {
+            let plan_id = crate::resolve::resolve_plan_id(&plan_id)?;
+            let changes = TaskChanges {
+                rename,
+                description: read_description(description, description_file)?,
+                scope,
+                gate,
+                retry_max,
+                harness,
+                issue,
+                add_depends_on: add_deps,
+                add_ordering_only: add_ordering_deps,
+                remove_depends_on: remove_deps,
+                add_invariants,
+                remove_invariants,
+            };
+            let task = gator_core::plan::update_task(pool, plan_id, &name, &changes).await?;
+            println!("Task updated: {} ({})", task.name, task.id);
+        }
+    }
+    Ok(())
+}
+
+/// The description given inline or read from `--description-file`.
+fn read_description(inline: Option<String>, file: Option<String>) -> Result<Option<String>> {
+    match file {
+        Some(path) => read_input(&path)
+            .map(Some)
+            .with_context(|| format!("failed to read {path}")),
+        None => Ok(inline),
+    }
+}
+
+/// The path that names standard input, as in `gator plan create -`.
+const STDIN_PATH: &str = "-";
+
+/// Contents of the file at `path`, or of standard input when `path` is `-`.
+fn read_input(path: &str) -> Result<String> {
+    read_input_from(path, std::io::stdin().lock())
+}
+
+fn read_input_from(path: &str, mut stdin: impl std::io::Read) -> Result<String> {
+    if path == STDIN_PATH {
+        let mut content = String::new();
+        stdin
+            .read_to_string(&mut content)
+            .context("failed to read standard input")?;
+        Ok(content)
+    } else {
+        Ok(std::fs::read_to_string(path)?)
+    }
+}
+
 // -----------------------------------------------------------------------
 // Tests
 // -----------------------------------------------------------------------
@@ -1033,6 +1962,7 @@ mod tests {
             "my-feature",
             "main",
             &["rust_build", "rust_test", "rust_clippy"],
+            &[CacheKind::Cargo],
         );
 
         // Verify it parses as valid TOML.
@@ -1050,7 +1980,7 @@ mod tests {
 
     #[test]
     fn generate_plan_toml_no_invariants() {
-        let content = generate_plan_toml("empty-plan", "develop", &[]);
+        let content = generate_plan_toml("empty-plan", "develop", &[], &[]);
 
         let parsed: gator_core::plan::PlanToml =
             toml::from_str(&content).expect("generated TOML should parse");
@@ -1061,7 +1991,7 @@ mod tests {
 
     #[test]
     fn generate_plan_toml_contains_comments() {
-        let content = generate_plan_toml("test", "main", &[]);
+        let content = generate_plan_toml("test", "main", &[], &[CacheKind::Npm, CacheKind::Pnpm]);
         // Comments should be present for optional fields.
         assert!(content.contains("# token_budget"));
         assert!(content.contains("# isolation"));
@@ -1069,11 +1999,12 @@ mod tests {
         assert!(content.contains("# depends_on"));
         assert!(content.contains("# retry_max"));
         assert!(content.contains("# harness"));
+        assert!(content.contains("# caches = [\"npm\", \"pnpm\"]"));
     }
 
     #[test]
     fn generate_plan_toml_special_chars_in_name() {
-        let content = generate_plan_toml("add-user-auth", "main", &[]);
+        let content = generate_plan_toml("add-user-auth", "main", &[], &[]);
         let parsed: gator_core::plan::PlanToml =
             toml::from_str(&content).expect("generated TOML should parse");
         assert_eq!(parsed.plan.name, "add-user-auth");
@@ -1259,4 +2190,128 @@ mod tests {
             _ => panic!("expected Plan Validate"),
         }
     }
+
+    #[test]
+    fn clap_parses_plan_create_infer_deps() {
+        let cli = TestCli::try_parse_from(["gator", "plan", "create", "plan.toml", "--infer-deps"])
+            .expect("should parse");
+        match cli.command {
+            TestCommands::Plan {
+                command: PlanCommands::Create { file, infer_deps },
+            } => {
+                assert_eq!(file, "plan.toml");
+                assert!(infer_deps);
+            }
+            _ => panic!("expected Plan Create"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_plan_analyze() {
+        let cli = TestCli::try_parse_from(["gator", "plan", "analyze", "plan.toml"])
+            .expect("should parse");
+        match cli.command {
+            TestCommands::Plan {
+                command: PlanCommands::Analyze { plan_id },
+            } => assert_eq!(plan_id, "plan.toml"),
+            _ => panic!("expected Plan Analyze"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_plan_delete() {
+        let cli = TestCli::try_parse_from(["gator", "plan", "delete", "plan.toml"])
+            .expect("should parse");
+        match cli.command {
+            TestCommands::Plan {
+                command:
+                    PlanCommands::Delete {
+                        plan_id,
+                        purge_events,
+                        remove_worktrees,
+                    },
+            } => {
+                assert_eq!(plan_id, "plan.toml");
+                assert!(!purge_events && !remove_worktrees);
+            }
+            _ => panic!("expected Plan Delete"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_plan_delete_cleanup_flags() {
+        let cli = TestCli::try_parse_from([
+            "gator",
+            "plan",
+            "delete",
+            "plan.toml",
+            "--purge-events",
+            "--remove-worktrees",
+        ])
+        .expect("should parse");
+        match cli.command {
+            TestCommands::Plan {
+                command:
+                    PlanCommands::Delete {
+                        purge_events,
+                        remove_worktrees,
+                        ..
+                    },
+            } => assert!(purge_events && remove_worktrees),
+            _ => panic!("expected Plan Delete"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_plan_import_issues() {
+        let cli = TestCli::try_parse_from([
+            "gator",
+            "plan",
+            "import-issues",
+            "--repo",
+            "acme/widgets",
+            "--invariant",
+            "rust_test",
+            "--invariant",
+            "rust_clippy",
+        ])
+        .expect("should parse");
+        match cli.command {
+            TestCommands::Plan {
+                command:
+                    PlanCommands::ImportIssues {
+                        repo,
+                        label,
+                        output,
+                        name,
+                        invariants,
+                        limit,
+                        ..
+                    },
+            } => {
+                assert_eq!(repo, "acme/widgets");
+                assert_eq!(label, "gator");
+                assert_eq!(output, "plan.toml");
+                assert!(name.is_none());
+                assert_eq!(invariants, vec!["rust_test", "rust_clippy"]);
+                assert_eq!(limit, 100);
+            }
+            _ => panic!("expected Plan ImportIssues"),
+        }
+    }
+
+    #[test]
+    fn read_input_dash_reads_stdin() {
+        let content = read_input_from("-", "[plan]\nname = \"x\"\n".as_bytes()).unwrap();
+        assert_eq!(content, "[plan]\nname = \"x\"\n");
+    }
+
+    #[test]
+    fn read_input_path_reads_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("desc.md");
+        std::fs::write(&path, "from file").unwrap();
+        let content = read_input_from(path.to_str().unwrap(), "from stdin".as_bytes()).unwrap();
+        assert_eq!(content, "from file");
+    }
 }
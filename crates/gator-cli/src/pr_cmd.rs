@@ -146,6 +146,19 @@ pub fn build_pr_body(
             .count()
     ));
 
+    // Closing keywords for issues imported via `gator plan import-issues`.
+    let issues: Vec<&str> = tasks
+        .iter()
+        .filter(|t| t.status == gator_db::models::TaskStatus::Passed)
+        .filter_map(|t| t.issue.as_deref())
+        .collect();
+    if !issues.is_empty() {
+        body.push_str("\n## Issues\n\n");
+        for issue in issues {
+            body.push_str(&format!("Closes {issue}\n"));
+        }
+    }
+
     body.push_str("\n---\n");
     body.push_str("*Generated by [gator](https://github.com/gator-dev/gator)*\n");
 
@@ -156,7 +169,7 @@ pub fn build_pr_body(
 mod tests {
     use super::*;
     use chrono::Utc;
-    use gator_db::models::{GatePolicy, ScopeLevel, Task, TaskStatus};
+    use gator_db::models::{GatePolicy, ScopeLevel, Task, TaskKind, TaskStatus};
     use uuid::Uuid;
 
     fn mock_task(name: &str, scope: ScopeLevel, status: TaskStatus, attempt: i32) -> Task {
@@ -176,6 +189,29 @@ mod tests {
             created_at: Utc::now(),
             started_at: Some(Utc::now()),
             completed_at: Some(Utc::now()),
+            issue: None,
+            deleted_at: None,
+            services: sqlx::types::Json(Vec::new()),
+            kind: TaskKind::Code,
+            artifacts: sqlx::types::Json(Vec::new()),
+            paths: sqlx::types::Json(Vec::new()),
+            timeout_secs: None,
+            retry_env: Default::default(),
+            retry_env_attempt: None,
+            gate_script: None,
+            agent_pid: None,
+            container_id: None,
+            agent_host: None,
+            agent_started: None,
+            llm_review: false,
+            review_harness: None,
+            run_id: None,
+            attempt_id: None,
+            merged_at: None,
+            merge_commit: None,
+            setup_commands: Default::default(),
+            disk_usage_bytes: None,
+            disk_usage_at: None,
         }
     }
 
@@ -223,4 +259,17 @@ mod tests {
         assert!(body.contains("| Task | Scope | Status | Attempts |"));
         assert!(body.contains("|------|-------|--------|----------|"));
     }
+
+    #[test]
+    fn build_pr_body_closes_issues_of_passed_tasks() {
+        let mut fixed = mock_task("issue-4-fix", ScopeLevel::Narrow, TaskStatus::Passed, 1);
+        fixed.issue = Some("acme/widgets#4".to_string());
+        let mut open = mock_task("issue-5-open", ScopeLevel::Narrow, TaskStatus::Escalated, 3);
+        open.issue = Some("acme/widgets#5".to_string());
+
+        let body = build_pr_body("Issues", &[fixed, open], 0, 0, "-");
+
+        assert!(body.contains("Closes acme/widgets#4"));
+        assert!(!body.contains("acme/widgets#5"));
+    }
 }
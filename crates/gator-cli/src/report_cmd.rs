@@ -1,23 +1,57 @@
 //! `gator report` command: show token usage and duration report for a plan.
+//!
+//! Besides the text report, the per-task breakdown -- attempts, wall time,
+//! tokens, cost, gate pass rate, and why each retried attempt failed --
+//! can be written as JSON (the `plan_report` document of the public
+//! schema), CSV with one row per task, or Markdown.
+
+use std::fmt::Write as _;
 
 use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 
+use gator_core::schema::Versioned;
+use gator_core::schema::v1::{self, RetryReason, TaskReport, TokenUsage};
+use gator_db::models::{Plan, Task};
 use gator_db::queries::agent_events;
+use gator_db::queries::attempt_phases;
+use gator_db::queries::gate_results::{self as gate_db, GateResultWithName};
 use gator_db::queries::plans as plan_db;
+use gator_db::queries::prompt_trims;
+use gator_db::queries::review_feedback;
 use gator_db::queries::tasks as task_db;
 
-/// Run the report command.
-pub async fn run_report(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+use crate::ReportFormat;
+
+/// Run the report command. `usd_per_mtok`, if given, prices the tokens.
+pub async fn run_report(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    format: ReportFormat,
+    usd_per_mtok: Option<f64>,
+) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     let plan = plan_db::get_plan(pool, plan_id)
         .await?
         .with_context(|| format!("plan {plan_id} not found"))?;
 
+    if format != ReportFormat::Text {
+        let report = build_report(pool, plan, usd_per_mtok).await?;
+        match format {
+            ReportFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&Versioned::new(report))?)
+            }
+            ReportFormat::Csv => print!("{}", render_csv(&report.tasks)),
+            ReportFormat::Markdown => print!("{}", render_markdown(&report)),
+            ReportFormat::Text => unreachable!(),
+        }
+        return Ok(());
+    }
+
     // Plan summary.
     println!("Plan: {} ({})", plan.name, plan.id);
-    println!("Status: {}", plan.status);
+    println!("Status: {}", crate::status_cmd::describe_plan_status(&plan));
 
     if let (Some(approved), Some(completed)) = (plan.approved_at, plan.completed_at) {
         let duration = completed - approved;
@@ -43,6 +77,9 @@ pub async fn run_report(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
         };
         println!("  Budget:   {budget} ({pct:.1}% used)");
     }
+    if let Some(cost) = cost_usd(total, usd_per_mtok) {
+        println!("  Cost:     ${cost:.2}");
+    }
     println!();
 
     // Per-task breakdown.
@@ -51,11 +88,14 @@ pub async fn run_report(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
     let mut passed_count: usize = 0;
     let total_count = tasks.len();
 
-    println!(
-        "{:<30} {:<12} {:>8} {:>12} {:>12}",
-        "TASK", "STATUS", "ATTEMPT", "TOKENS", "WALL TIME"
-    );
-    println!("{}", "-".repeat(76));
+    let plain = crate::output::is_plain();
+    if !plain {
+        println!(
+            "{:<30} {:<12} {:>8} {:>12} {:>12}",
+            "TASK", "STATUS", "ATTEMPT", "TOKENS", "WALL TIME"
+        );
+        println!("{}", "-".repeat(76));
+    }
 
     for task in &tasks {
         if task.status == gator_db::models::TaskStatus::Passed {
@@ -79,6 +119,14 @@ pub async fn run_report(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
             _ => "-".to_string(),
         };
 
+        if plain {
+            println!(
+                "Task {}: {}, attempt {}, tokens {token_str}, wall time {wall_str}.",
+                task.name, task.status, task.attempt
+            );
+            continue;
+        }
+
         let name_display = if task.name.len() > 28 {
             format!("{}...", &task.name[..25])
         } else {
@@ -103,5 +151,350 @@ pub async fn run_report(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
         }
     );
 
+    // Where the wall time went, summed over every attempt.
+    let phases = attempt_phases::phase_totals_for_plan(pool, plan_id).await?;
+    if !phases.is_empty() {
+        let grand_total: i64 = phases.iter().map(|p| p.total_ms).sum();
+        println!();
+        println!("Lifecycle phases:");
+        if !plain {
+            println!(
+                "  {:<12} {:>8} {:>10} {:>10} {:>10} {:>7}",
+                "PHASE", "ATTEMPTS", "TOTAL", "AVG", "MAX", "SHARE"
+            );
+        }
+        for p in &phases {
+            let avg = p.total_ms / p.attempts.max(1);
+            let share = if grand_total > 0 {
+                (p.total_ms as f64 / grand_total as f64) * 100.0
+            } else {
+                0.0
+            };
+            if plain {
+                println!(
+                    "  Phase {}: {} attempts, total {}, average {}, max {}, {share:.0}% of time.",
+                    p.phase,
+                    p.attempts,
+                    format_ms(p.total_ms),
+                    format_ms(avg),
+                    format_ms(p.max_ms)
+                );
+            } else {
+                println!(
+                    "  {:<12} {:>8} {:>10} {:>10} {:>10} {:>6.0}%",
+                    p.phase.to_string(),
+                    p.attempts,
+                    format_ms(p.total_ms),
+                    format_ms(avg),
+                    format_ms(p.max_ms),
+                    share
+                );
+            }
+        }
+    }
+
+    // Context dropped from agent prompts to fit the prompt budget.
+    let trims = prompt_trims::list_trims_for_plan(pool, plan_id).await?;
+    if !trims.is_empty() {
+        println!();
+        println!("Prompt trims:");
+        for attempt in trims.chunk_by(|a, b| a.task_id == b.task_id && a.attempt == b.attempt) {
+            let task_name = tasks
+                .iter()
+                .find(|t| t.id == attempt[0].task_id)
+                .map_or("?", |t| t.name.as_str());
+            let sections: Vec<String> = attempt
+                .iter()
+                .map(|t| format!("{} (~{} tokens)", t.section, t.estimated_tokens))
+                .collect();
+            println!(
+                "  {task_name}, attempt {}: {}",
+                attempt[0].attempt,
+                sections.join(", ")
+            );
+        }
+    }
+
     Ok(())
 }
+
+/// Gather the per-task breakdown of `plan`.
+async fn build_report(
+    pool: &SqlitePool,
+    plan: Plan,
+    usd_per_mtok: Option<f64>,
+) -> Result<v1::PlanReport> {
+    let progress = task_db::get_plan_progress(pool, plan.id).await?;
+    let (input, output) = agent_events::get_token_usage_for_plan(pool, plan.id).await?;
+
+    let mut tasks = Vec::new();
+    for task in task_db::list_tasks_for_plan(pool, plan.id).await? {
+        tasks.push(task_report(pool, &task, usd_per_mtok).await?);
+    }
+
+    Ok(v1::PlanReport {
+        progress: progress.into(),
+        token_usage: TokenUsage::new(input, output),
+        cost_usd: cost_usd(input + output, usd_per_mtok),
+        duration_secs: plan
+            .approved_at
+            .zip(plan.completed_at)
+            .map(|(approved, completed)| (completed - approved).num_seconds()),
+        tasks,
+        plan: plan.into(),
+    })
+}
+
+async fn task_report(
+    pool: &SqlitePool,
+    task: &Task,
+    usd_per_mtok: Option<f64>,
+) -> Result<TaskReport> {
+    let (input, output) = agent_events::get_token_usage_for_task(pool, task.id).await?;
+    let gates = gate_db::list_gate_results_with_names(pool, task.id).await?;
+    let gate_checks = gates.len() as i64;
+    let gate_checks_passed = gates.iter().filter(|g| g.passed).count() as i64;
+
+    let mut retries = Vec::new();
+    for attempt in 0..task.attempt {
+        retries.push(RetryReason {
+            attempt,
+            reason: retry_reason(pool, task, attempt, &gates).await?,
+        });
+    }
+
+    Ok(TaskReport {
+        id: task.id,
+        name: task.name.clone(),
+        status: task.status.to_string(),
+        attempts: if task.started_at.is_some() || task.attempt > 0 {
+            task.attempt + 1
+        } else {
+            0
+        },
+        wall_time_secs: task
+            .started_at
+            .zip(task.completed_at)
+            .map(|(start, end)| (end - start).num_seconds()),
+        token_usage: TokenUsage::new(input, output),
+        cost_usd: cost_usd(input + output, usd_per_mtok),
+        gate_checks,
+        gate_checks_passed,
+        gate_pass_rate: (gate_checks > 0).then(|| gate_checks_passed as f64 / gate_checks as f64),
+        retries,
+    })
+}
+
+/// Why `attempt` of `task` did not pass: its failed invariants and review
+/// rejections, or else the agent's last error.
+async fn retry_reason(
+    pool: &SqlitePool,
+    task: &Task,
+    attempt: i32,
+    gates: &[GateResultWithName],
+) -> Result<String> {
+    let mut reasons = Vec::new();
+    let failed: Vec<&str> = gates
+        .iter()
+        .filter(|g| g.attempt == attempt && !g.passed)
+        .map(|g| g.invariant_name.as_str())
+        .collect();
+    if !failed.is_empty() {
+        reasons.push(format!("gate failed: {}", failed.join(", ")));
+    }
+    for feedback in review_feedback::get_review_feedback(pool, task.id, attempt).await? {
+        reasons.push(format!("rejected: {}", feedback.reason));
+    }
+    if reasons.is_empty() {
+        if let Some(error) =
+            agent_events::get_latest_event_of_type(pool, task.id, attempt, "error").await?
+        {
+            let message = error.payload["message"].as_str().unwrap_or("unknown error");
+            reasons.push(format!("error: {message}"));
+        }
+    }
+    if reasons.is_empty() {
+        reasons.push("no reason recorded".to_string());
+    }
+    Ok(reasons.join("; "))
+}
+
+/// The price of `tokens` at `usd_per_mtok` dollars per million.
+pub(crate) fn cost_usd(tokens: i64, usd_per_mtok: Option<f64>) -> Option<f64> {
+    usd_per_mtok.map(|price| tokens as f64 / 1_000_000.0 * price)
+}
+
+/// One row per task, with a header row.
+fn render_csv(tasks: &[TaskReport]) -> String {
+    let mut out = String::from(
+        "task_id,name,status,attempts,wall_time_secs,input_tokens,output_tokens,\
+         total_tokens,cost_usd,gate_checks,gate_checks_passed,gate_pass_rate,retries\n",
+    );
+    let opt = |value: Option<String>| value.unwrap_or_default();
+    for task in tasks {
+        let retries: Vec<String> = task
+            .retries
+            .iter()
+            .map(|r| format!("attempt {}: {}", r.attempt, r.reason))
+            .collect();
+        let fields = [
+            task.id.to_string(),
+            csv_field(&task.name),
+            task.status.clone(),
+            task.attempts.to_string(),
+            opt(task.wall_time_secs.map(|s| s.to_string())),
+            task.token_usage.input_tokens.to_string(),
+            task.token_usage.output_tokens.to_string(),
+            task.token_usage.total_tokens.to_string(),
+            opt(task.cost_usd.map(|c| format!("{c:.4}"))),
+            task.gate_checks.to_string(),
+            task.gate_checks_passed.to_string(),
+            opt(task.gate_pass_rate.map(|r| format!("{r:.3}"))),
+            csv_field(&retries.join(" | ")),
+        ];
+        let _ = writeln!(out, "{}", fields.join(","));
+    }
+    out
+}
+
+/// Quote a CSV field when it holds a comma, quote, or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// A summary, a task table, and the retried attempts.
+fn render_markdown(report: &v1::PlanReport) -> String {
+    let plan = &report.plan;
+    let mut out = format!("# Report: {}\n\n", plan.name);
+    let _ = writeln!(out, "- Plan: {}", plan.id);
+    let _ = writeln!(out, "- Status: {}", plan.status);
+    if let Some(secs) = report.duration_secs {
+        let _ = writeln!(out, "- Duration: {}", format_ms(secs * 1000));
+    }
+    let usage = &report.token_usage;
+    let _ = writeln!(
+        out,
+        "- Tokens: {} in, {} out, {} total",
+        usage.input_tokens, usage.output_tokens, usage.total_tokens
+    );
+    if let Some(cost) = report.cost_usd {
+        let _ = writeln!(out, "- Cost: ${cost:.2}");
+    }
+    let _ = writeln!(
+        out,
+        "- Passed: {} of {} task(s)",
+        report.progress.passed, report.progress.total
+    );
+
+    out.push_str(
+        "\n| Task | Status | Attempts | Wall time | Tokens in | Tokens out | Cost | Gate pass rate |\n\
+         |---|---|---:|---:|---:|---:|---:|---:|\n",
+    );
+    for task in &report.tasks {
+        let wall = task
+            .wall_time_secs
+            .map_or("-".to_string(), |s| format_ms(s * 1000));
+        let cost = task
+            .cost_usd
+            .map_or("-".to_string(), |c| format!("${c:.2}"));
+        let rate = task.gate_pass_rate.map_or("-".to_string(), |r| {
+            format!(
+                "{:.0}% ({}/{})",
+                r * 100.0,
+                task.gate_checks_passed,
+                task.gate_checks
+            )
+        });
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {wall} | {} | {} | {cost} | {rate} |",
+            task.name.replace('|', "\\|"),
+            task.status,
+            task.attempts,
+            task.token_usage.input_tokens,
+            task.token_usage.output_tokens,
+        );
+    }
+
+    let retried: Vec<&TaskReport> = report
+        .tasks
+        .iter()
+        .filter(|t| !t.retries.is_empty())
+        .collect();
+    if !retried.is_empty() {
+        out.push_str("\n## Retries\n\n");
+        for task in retried {
+            for retry in &task.retries {
+                let _ = writeln!(
+                    out,
+                    "- **{}**, attempt {}: {}",
+                    task.name, retry.attempt, retry.reason
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Format a millisecond duration as `850ms`, `12.3s` or `4m 05s`.
+fn format_ms(ms: i64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        let secs = ms / 1000;
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_ms_picks_a_readable_unit() {
+        assert_eq!(format_ms(850), "850ms");
+        assert_eq!(format_ms(12_345), "12.3s");
+        assert_eq!(format_ms(245_000), "4m 05s");
+    }
+
+    #[test]
+    fn csv_rows_quote_names_and_retry_reasons() {
+        let task = TaskReport {
+            id: uuid::Uuid::nil(),
+            name: "parse, then \"lex\"".to_string(),
+            status: "passed".to_string(),
+            attempts: 2,
+            wall_time_secs: Some(41),
+            token_usage: TokenUsage::new(1_500_000, 500_000),
+            cost_usd: cost_usd(2_000_000, Some(3.0)),
+            gate_checks: 2,
+            gate_checks_passed: 1,
+            gate_pass_rate: Some(0.5),
+            retries: vec![RetryReason {
+                attempt: 0,
+                reason: "gate failed: tests, lint".to_string(),
+            }],
+        };
+        let csv = render_csv(&[task]);
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("task_id,name,status,attempts,")
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "00000000-0000-0000-0000-000000000000,\"parse, then \"\"lex\"\"\",passed,2,41,\
+             1500000,500000,2000000,6.0000,2,1,0.500,\"attempt 0: gate failed: tests, lint\""
+        );
+        assert_eq!(lines.next(), None);
+        assert_eq!(cost_usd(2_000_000, None), None);
+    }
+}
@@ -46,11 +46,20 @@ fn read_plan_id_from_file(path: &str) -> Result<Uuid> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read plan file: {path}"))?;
 
-    let plan_toml: gator_core::plan::PlanToml =
-        toml::from_str(&content).with_context(|| format!("failed to parse TOML from {path}"))?;
-
-    match plan_toml.plan.id {
-        Some(id) => Ok(id),
+    // Only `[plan].id` is read: tasks may rely on `[plan.defaults]`, which
+    // the plan parser applies.
+    let doc: toml::Table = content
+        .parse()
+        .with_context(|| format!("failed to parse TOML from {path}"))?;
+    let id = doc
+        .get("plan")
+        .and_then(|plan| plan.get("id"))
+        .and_then(toml::Value::as_str);
+
+    match id {
+        Some(id) => {
+            Uuid::parse_str(id).with_context(|| format!("invalid plan id {id:?} in {path}"))
+        }
         None => bail!(
             "plan file {path:?} has no id field in [plan] section.\n\
              Run `gator plan create {path}` first to register it."
@@ -0,0 +1,170 @@
+//! `gator restore` command: recover soft-deleted plans and invariants.
+//!
+//! `gator plan delete` and `gator invariant remove` only stamp `deleted_at`.
+//! Deleted rows stay restorable for [`DELETED_RETENTION_DAYS`]; after that
+//! they are pruned for good the next time a delete or restore command runs.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+use gator_db::queries::invariants as inv_db;
+use gator_db::queries::plans as plan_db;
+
+use crate::RestoreCommands;
+
+/// How long soft-deleted rows are kept before they are pruned.
+pub const DELETED_RETENTION_DAYS: i64 = 30;
+
+/// Run the restore command. Without a target, list what can be restored.
+pub async fn run_restore(pool: &SqlitePool, target: Option<RestoreCommands>) -> Result<()> {
+    prune_expired(pool).await?;
+
+    match target {
+        None => list_deleted(pool).await,
+        Some(RestoreCommands::Plan { plan_id }) => {
+            let plan_id = crate::resolve::resolve_plan_id(&plan_id)?;
+            let plan = plan_db::restore_plan(pool, plan_id).await?;
+            println!("Restored plan {} ({})", plan.name, plan.id);
+            println!("  Status: {}", plan.status);
+            Ok(())
+        }
+        Some(RestoreCommands::Invariant { name }) => {
+            let invariant = inv_db::restore_invariant(pool, &name).await?;
+            println!("Restored invariant {}", invariant.name);
+            Ok(())
+        }
+    }
+}
+
+/// Permanently remove rows deleted more than [`DELETED_RETENTION_DAYS`] ago.
+///
+/// Prints a line when anything was pruned.
+pub async fn prune_expired(pool: &SqlitePool) -> Result<()> {
+    let cutoff = Utc::now() - Duration::days(DELETED_RETENTION_DAYS);
+    let plans = plan_db::purge_deleted_plans(pool, cutoff).await?;
+    let invariants = inv_db::purge_deleted_invariants(pool, cutoff).await?;
+    if plans + invariants > 0 {
+        println!(
+            "Pruned {plans} plan(s) and {invariants} invariant(s) deleted more than \
+             {DELETED_RETENTION_DAYS} days ago."
+        );
+    }
+    Ok(())
+}
+
+/// Print soft-deleted plans and invariants with their remaining lifetime.
+async fn list_deleted(pool: &SqlitePool) -> Result<()> {
+    let plans = plan_db::list_deleted_plans(pool).await?;
+    let invariants = inv_db::list_deleted_invariants(pool).await?;
+
+    if plans.is_empty() && invariants.is_empty() {
+        println!("Nothing to restore.");
+        return Ok(());
+    }
+
+    if crate::output::is_plain() {
+        let now = Utc::now();
+        for plan in &plans {
+            let deleted_at = plan.deleted_at.unwrap_or(now);
+            println!(
+                "Deleted plan {}: deleted {}, {}. ID {}.",
+                plan.name,
+                crate::timefmt::format(deleted_at, "%Y-%m-%d %H:%M"),
+                expires_in(deleted_at, now),
+                plan.id
+            );
+        }
+        for inv in &invariants {
+            let deleted_at = inv.deleted_at.unwrap_or(now);
+            println!(
+                "Deleted invariant {}: deleted {}, {}.",
+                inv.name,
+                crate::timefmt::format(deleted_at, "%Y-%m-%d %H:%M"),
+                expires_in(deleted_at, now)
+            );
+        }
+        println!("Restore with gator restore plan <plan-id> or gator restore invariant <name>.");
+        return Ok(());
+    }
+
+    if !plans.is_empty() {
+        println!("Deleted plans:");
+        println!("  {:<36}  {:<24}  {:<16}  EXPIRES", "ID", "NAME", "DELETED");
+        for plan in &plans {
+            let deleted_at = plan.deleted_at.unwrap_or_else(Utc::now);
+            println!(
+                "  {:<36}  {:<24}  {:<16}  {}",
+                plan.id,
+                truncate(&plan.name, 24),
+                crate::timefmt::format(deleted_at, "%Y-%m-%d %H:%M"),
+                expires_in(deleted_at, Utc::now())
+            );
+        }
+        println!();
+    }
+
+    if !invariants.is_empty() {
+        println!("Deleted invariants:");
+        println!("  {:<24}  {:<16}  EXPIRES", "NAME", "DELETED");
+        for inv in &invariants {
+            let deleted_at = inv.deleted_at.unwrap_or_else(Utc::now);
+            println!(
+                "  {:<24}  {:<16}  {}",
+                truncate(&inv.name, 24),
+                crate::timefmt::format(deleted_at, "%Y-%m-%d %H:%M"),
+                expires_in(deleted_at, Utc::now())
+            );
+        }
+        println!();
+    }
+
+    println!("Restore with: gator restore plan <plan-id>  |  gator restore invariant <name>");
+    Ok(())
+}
+
+/// Human-readable time left before a row deleted at `deleted_at` is pruned.
+fn expires_in(deleted_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let remaining = deleted_at + Duration::days(DELETED_RETENTION_DAYS) - now;
+    if remaining.num_days() >= 1 {
+        format!("in {} days", remaining.num_days())
+    } else if remaining.num_hours() >= 1 {
+        format!("in {} hours", remaining.num_hours())
+    } else {
+        "soon".to_string()
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max - 3).collect();
+        out.push_str("...");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_in_counts_down_from_retention() {
+        let now = Utc::now();
+        assert_eq!(
+            expires_in(now, now),
+            format!("in {DELETED_RETENTION_DAYS} days")
+        );
+        let almost = now - Duration::days(DELETED_RETENTION_DAYS) + Duration::hours(5);
+        assert_eq!(expires_in(almost, now), "in 5 hours");
+        let overdue = now - Duration::days(DELETED_RETENTION_DAYS + 1);
+        assert_eq!(expires_in(overdue, now), "soon");
+    }
+
+    #[test]
+    fn truncate_long_names() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a-very-long-plan-name", 10), "a-very-...");
+    }
+}
@@ -0,0 +1,51 @@
+//! `gator resume` command: raise the token budget of a plan that stopped on
+//! it, and dispatch it again from where it stopped.
+
+use anyhow::{Context, Result, bail};
+use sqlx::SqlitePool;
+
+use gator_core::harness::PromptConfig;
+use gator_core::notify::NotificationConfig;
+use gator_core::token::TokenConfig;
+use gator_db::queries::agent_events;
+use gator_db::queries::plans as plan_db;
+
+use crate::dispatch_cmd::{self, DispatchOptions};
+
+/// Run the resume command.
+pub async fn run_resume(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    budget: i64,
+    options: DispatchOptions,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+    plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+
+    let (input, output) = agent_events::get_token_usage_for_plan(pool, plan_id).await?;
+    let used = input + output;
+    if budget <= used {
+        bail!("budget {budget} does not cover the {used} tokens this plan has already used");
+    }
+
+    let plan = plan_db::resume_plan(pool, plan_id, budget).await?;
+    println!(
+        "Resuming plan {} with a budget of {budget} tokens ({used} used so far).",
+        plan.name
+    );
+
+    dispatch_cmd::run_dispatch(
+        pool,
+        &plan_id.to_string(),
+        options,
+        token_config,
+        notifications,
+        prompt,
+    )
+    .await
+}
@@ -0,0 +1,411 @@
+//! `gator review` commands: the PR review bot.
+//!
+//! On every pass the bot resumes review plans an earlier pass left
+//! unfinished and posts finished reviews, then fetches each open pull
+//! request head it has not reviewed yet into a local branch and runs a
+//! one-task review plan on it. A PR is reviewed again after new pushes;
+//! `pr_reviews` remembers which heads were queued.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use gator_core::artifact;
+use gator_core::forge::{self, GithubForge, GithubPullRequest};
+use gator_core::harness::PromptConfig;
+use gator_core::isolation;
+use gator_core::notify::NotificationConfig;
+use gator_core::plan::{create_plan_from_toml, parse_plan_toml};
+use gator_core::review::{self, REVIEW_FILE, Review, ReviewPlanOptions};
+use gator_core::token::TokenConfig;
+use gator_db::models::{PrReview, Task, TaskStatus};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::pr_reviews;
+use gator_db::queries::tasks as task_db;
+
+use crate::ReviewCommands;
+use crate::daemon::{self, Fleet};
+
+/// Settings for `gator review run`.
+struct RunOptions {
+    repo: String,
+    label: Option<String>,
+    interval: Duration,
+    once: bool,
+    limit: u32,
+    remote: String,
+    include_drafts: bool,
+    submit_verdict: bool,
+    invariants: Vec<String>,
+    max_agents: usize,
+    task_timeout: Duration,
+}
+
+/// Run a `gator review` subcommand.
+pub async fn run_review(
+    pool: &SqlitePool,
+    command: ReviewCommands,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    match command {
+        ReviewCommands::Run {
+            repo,
+            label,
+            interval,
+            once,
+            limit,
+            remote,
+            include_drafts,
+            submit_verdict,
+            invariants,
+            max_agents,
+            timeout,
+        } => {
+            let options = RunOptions {
+                repo,
+                label,
+                interval: Duration::from_secs(interval),
+                once,
+                limit,
+                remote,
+                include_drafts,
+                submit_verdict,
+                invariants,
+                max_agents,
+                task_timeout: Duration::from_secs(timeout),
+            };
+            cmd_run(pool, &options, token_config, notifications, prompt).await
+        }
+        ReviewCommands::Status { repo } => cmd_status(pool, repo.as_deref()).await,
+    }
+}
+
+async fn cmd_run(
+    pool: &SqlitePool,
+    options: &RunOptions,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    let forge = GithubForge::new(&options.repo)?;
+    let cwd = std::env::current_dir().context("failed to get current directory")?;
+    // Review plans hold a single task each, so each orchestrator run needs
+    // one agent; `max_agents` bounds how many run at once.
+    let fleet = Fleet::new(
+        isolation::create_isolation("worktree", &cwd, None)?,
+        1,
+        options.task_timeout,
+        token_config,
+        notifications,
+        prompt,
+    )?;
+
+    match &options.label {
+        Some(label) => println!("Reviewing {} (label {label:?})", options.repo),
+        None => println!("Reviewing {}", options.repo),
+    }
+    if !options.once {
+        println!("  Interval:    {}s", options.interval.as_secs());
+    }
+    println!("  Remote:      {}", options.remote);
+    println!("  Max agents:  {}", options.max_agents);
+    if options.submit_verdict {
+        println!("  Verdicts:    submitted as approvals / change requests");
+    }
+
+    let cancel = daemon::shutdown_token();
+
+    let pass = ReviewPass {
+        pool,
+        forge: &forge,
+        options,
+        repo_path: &cwd,
+        fleet: &fleet,
+    };
+
+    loop {
+        if let Err(e) = pass.run(cancel.clone()).await {
+            if options.once {
+                return Err(e);
+            }
+            eprintln!("Review pass failed: {e:#}");
+        }
+        if options.once || !daemon::wait_for_next_pass(&cancel, options.interval).await {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Everything one pass of the bot needs.
+struct ReviewPass<'a> {
+    pool: &'a SqlitePool,
+    forge: &'a GithubForge,
+    options: &'a RunOptions,
+    repo_path: &'a Path,
+    fleet: &'a Fleet,
+}
+
+impl ReviewPass<'_> {
+    async fn run(&self, cancel: CancellationToken) -> Result<()> {
+        let repo = self.forge.repo();
+
+        // 1. Finish plans an earlier pass left behind, then post what is done.
+        let unfinished = self.unfinished_plans().await?;
+        if !unfinished.is_empty() {
+            println!("Resuming {} review plan(s)", unfinished.len());
+            self.dispatch_all(unfinished, &cancel).await?;
+        }
+        self.post_results().await?;
+        if cancel.is_cancelled() {
+            return Ok(());
+        }
+
+        // 2. Queue PR heads not reviewed before.
+        let seen = pr_reviews::reviewed_heads(self.pool, repo).await?;
+        let prs: Vec<_> = self
+            .forge
+            .list_pull_requests(self.options.label.as_deref(), self.options.limit)?
+            .into_iter()
+            .filter(|pr| self.options.include_drafts || !pr.is_draft)
+            .filter(|pr| !seen.contains(&(pr.number as i64, pr.head_ref_oid.clone())))
+            .collect();
+        if prs.is_empty() {
+            println!(
+                "[{}] No new pull request heads in {repo}.",
+                crate::timefmt::format(chrono::Utc::now(), "%H:%M:%S"),
+            );
+            return Ok(());
+        }
+
+        let mut plans = Vec::new();
+        for pr in &prs {
+            match self.queue(pr).await {
+                Ok(Some(plan)) => plans.push(plan),
+                Ok(None) => {}
+                Err(e) => eprintln!("Skipping {repo}#{}: {e:#}", pr.number),
+            }
+        }
+
+        // 3. Review them.
+        self.dispatch_all(plans, &cancel).await?;
+
+        // 4. Post what passed.
+        self.post_results().await
+    }
+
+    /// Fetch one PR and create its review plan. `None` if the PR changes
+    /// no files relative to its base.
+    async fn queue(&self, pr: &GithubPullRequest) -> Result<Option<(Uuid, String)>> {
+        let repo = self.forge.repo();
+        let fetched = forge::fetch_pull_request(self.repo_path, &self.options.remote, pr)?;
+        let changed =
+            forge::changed_files(self.repo_path, &fetched.base_branch, &fetched.head_branch)?;
+        if changed.is_empty() {
+            println!("{repo}#{} changes no files; skipping.", pr.number);
+            return Ok(None);
+        }
+
+        let repo_name = repo.rsplit('/').next().unwrap_or(repo);
+        let sha: String = pr.head_ref_oid.chars().take(7).collect();
+        let plan_options = ReviewPlanOptions {
+            plan_name: format!("{repo_name}-review-pr-{}-{sha}", pr.number),
+            head_branch: fetched.head_branch,
+            base_branch: fetched.base_branch,
+            invariants: self.options.invariants.clone(),
+        };
+        let plan_toml = review::plan_for_pull_request(pr, &changed, &plan_options);
+        // Re-parse so bad --invariant names and the like fail before insert.
+        let content = toml::to_string(&plan_toml).context("failed to serialize review plan")?;
+        parse_plan_toml(&content).context("generated review plan failed validation")?;
+
+        let project_path = self.repo_path.to_string_lossy();
+        let plan = create_plan_from_toml(self.pool, &plan_toml, &project_path).await?;
+        let plan = plan_db::approve_plan(self.pool, plan.id).await?;
+        let task = task_db::list_tasks_for_plan(self.pool, plan.id)
+            .await?
+            .into_iter()
+            .next()
+            .context("review plan has no task")?;
+        pr_reviews::insert_pr_review(
+            self.pool,
+            repo,
+            pr.number as i64,
+            &pr.head_ref_oid,
+            plan.id,
+            task.id,
+            &changed,
+        )
+        .await?;
+        println!(
+            "Queued {repo}#{} at {sha} ({} file(s)) as plan {} ({})",
+            pr.number,
+            changed.len(),
+            plan.name,
+            plan.id
+        );
+        Ok(Some((plan.id, plan.name)))
+    }
+
+    /// Run review plans, at most `max_agents` at a time.
+    async fn dispatch_all(
+        &self,
+        plans: Vec<(Uuid, String)>,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        let results: Vec<Result<()>> = futures::stream::iter(plans)
+            .map(|(plan_id, plan_name)| {
+                let cancel = cancel.clone();
+                async move {
+                    self.fleet
+                        .dispatch(self.pool, plan_id, &plan_name, cancel)
+                        .await
+                        .map(drop)
+                        .with_context(|| format!("plan {plan_name} failed to run"))
+                }
+            })
+            .buffer_unordered(self.options.max_agents.max(1))
+            .collect()
+            .await;
+        results.into_iter().collect()
+    }
+
+    /// Plans with queued reviews whose task the orchestrator can still move
+    /// forward (not passed, escalated, cancelled, or waiting on a human).
+    async fn unfinished_plans(&self) -> Result<Vec<(Uuid, String)>> {
+        let queued =
+            pr_reviews::list_pr_reviews(self.pool, Some(self.forge.repo()), Some("queued")).await?;
+        let mut plans = Vec::new();
+        for item in queued {
+            let Some(task) = task_db::get_task(self.pool, item.task_id).await? else {
+                continue;
+            };
+            if matches!(
+                task.status,
+                TaskStatus::Passed
+                    | TaskStatus::Escalated
+                    | TaskStatus::Cancelled
+                    | TaskStatus::BlockedOnHuman
+                    | TaskStatus::Checking
+            ) {
+                continue;
+            }
+            if let Some(plan) = plan_db::get_plan(self.pool, item.plan_id).await? {
+                plans.push((plan.id, plan.name));
+            }
+        }
+        Ok(plans)
+    }
+
+    /// Post every queued review whose task has passed; mark escalated ones
+    /// failed. Tasks still in flight stay queued.
+    async fn post_results(&self) -> Result<()> {
+        let queued =
+            pr_reviews::list_pr_reviews(self.pool, Some(self.forge.repo()), Some("queued")).await?;
+        for item in queued {
+            let Some(task) = task_db::get_task(self.pool, item.task_id).await? else {
+                continue;
+            };
+            match task.status {
+                TaskStatus::Passed => match self.post_one(&item, &task).await {
+                    Ok(()) => {
+                        pr_reviews::mark_pr_review_posted(self.pool, item.id).await?;
+                        println!("Posted review for {}#{}", item.repo, item.pr_number);
+                    }
+                    Err(e) => {
+                        let message = format!("{e:#}");
+                        eprintln!(
+                            "Failed to post review for {}#{}: {message}",
+                            item.repo, item.pr_number
+                        );
+                        pr_reviews::mark_pr_review_failed(self.pool, item.id, &message).await?;
+                    }
+                },
+                TaskStatus::Escalated => {
+                    pr_reviews::mark_pr_review_failed(self.pool, item.id, "review task escalated")
+                        .await?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Post one review from its task's artifact bundle.
+    async fn post_one(&self, item: &PrReview, task: &Task) -> Result<()> {
+        let plan = plan_db::get_plan(self.pool, item.plan_id)
+            .await?
+            .with_context(|| format!("plan {} not found", item.plan_id))?;
+        let path = artifact::bundle_dir(
+            &artifact::bundle_root(Path::new(&plan.project_path)),
+            &plan.name,
+            &task.name,
+        )
+        .join(REVIEW_FILE);
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let review: Review = serde_json::from_str(&text)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        let sha: String = item.head_sha.chars().take(7).collect();
+        let body = format!(
+            "{}\n---\n_Reviewed by gator at {sha} (plan `{}`)._",
+            review::render_review(&review),
+            plan.name
+        );
+        let action = review.verdict.action(self.options.submit_verdict);
+        self.forge
+            .post_pr_review(item.pr_number as u64, action, &body)
+    }
+}
+
+/// `gator review status`: list queued, posted, and failed reviews.
+async fn cmd_status(pool: &SqlitePool, repo: Option<&str>) -> Result<()> {
+    let items = pr_reviews::list_pr_reviews(pool, repo, None).await?;
+    if items.is_empty() {
+        println!("No reviewed pull requests.");
+        return Ok(());
+    }
+
+    let plain = crate::output::is_plain();
+    if !plain {
+        println!(
+            "{:<32} {:<8} {:<8} {:<18} ERROR",
+            "PULL REQUEST", "HEAD", "STATUS", "QUEUED"
+        );
+    }
+    for item in &items {
+        if plain {
+            let error = item
+                .error
+                .as_deref()
+                .map(|e| format!(", error: {e}"))
+                .unwrap_or_default();
+            println!(
+                "Pull request {}#{} at {}: {}, queued {}{error}.",
+                item.repo,
+                item.pr_number,
+                item.head_sha.chars().take(7).collect::<String>(),
+                item.status,
+                crate::timefmt::format(item.created_at, "%Y-%m-%d %H:%M"),
+            );
+            continue;
+        }
+        println!(
+            "{:<32} {:<8} {:<8} {:<18} {}",
+            format!("{}#{}", item.repo, item.pr_number),
+            item.head_sha.chars().take(7).collect::<String>(),
+            item.status,
+            crate::timefmt::format(item.created_at, "%Y-%m-%d %H:%M"),
+            item.error.as_deref().unwrap_or("")
+        );
+    }
+    Ok(())
+}
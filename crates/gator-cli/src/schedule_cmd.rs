@@ -0,0 +1,315 @@
+//! `gator schedule` commands: scheduled dispatch.
+//!
+//! `gator schedule add` stores a plan and a cron expression in `schedules`.
+//! `gator schedule run` is the scheduler: on every pass it clones and
+//! approves the plan of each schedule that has come due and dispatches the
+//! clone. Runs are dispatched one at a time. A run an earlier pass created
+//! but did not finish dispatching (the scheduler was stopped) is resumed
+//! first.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use gator_core::harness::PromptConfig;
+use gator_core::isolation;
+use gator_core::notify::NotificationConfig;
+use gator_core::plan::{Cron, start_scheduled_run};
+use gator_core::token::TokenConfig;
+use gator_db::models::{Plan, PlanStatus, Schedule};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::schedules;
+
+use crate::ScheduleCommands;
+use crate::daemon::{self, Fleet};
+
+/// Run a `gator schedule` subcommand.
+pub async fn run_schedule(
+    pool: &SqlitePool,
+    command: ScheduleCommands,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    match command {
+        ScheduleCommands::Add {
+            plan_id,
+            cron,
+            max_agents,
+            timeout,
+        } => cmd_add(pool, &plan_id, &cron, max_agents, timeout).await,
+        ScheduleCommands::List => cmd_list(pool).await,
+        ScheduleCommands::Remove { schedule_id } => cmd_remove(pool, &schedule_id).await,
+        ScheduleCommands::Run { interval, once } => {
+            let settings = Settings {
+                token_config,
+                notifications,
+                prompt,
+            };
+            cmd_run(pool, Duration::from_secs(interval), once, &settings).await
+        }
+    }
+}
+
+// -----------------------------------------------------------------------
+// gator schedule add <plan-id> --cron <expr>
+// -----------------------------------------------------------------------
+
+async fn cmd_add(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    cron_expr: &str,
+    max_agents: usize,
+    timeout: u64,
+) -> Result<()> {
+    let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
+    let cron: Cron = cron_expr.parse()?;
+    let plan = plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+    if plan.deleted_at.is_some() {
+        anyhow::bail!("plan {plan_id} has been deleted");
+    }
+    // Each run's clone is approved without asking, so catch what would stop
+    // `gator plan approve` now.
+    let tasks_without = plan_db::count_tasks_without_invariants(pool, plan_id).await?;
+    if !tasks_without.is_empty() {
+        anyhow::bail!(
+            "cannot schedule plan: {} task(s) have no invariants linked: {}",
+            tasks_without.len(),
+            tasks_without.join(", "),
+        );
+    }
+    let next_run_at = cron
+        .next_after(Utc::now())
+        .with_context(|| format!("cron expression {cron_expr:?} never matches"))?;
+
+    let schedule = schedules::insert_schedule(
+        pool,
+        plan_id,
+        &cron.to_string(),
+        max_agents,
+        timeout,
+        next_run_at,
+    )
+    .await?;
+
+    println!("Schedule added.");
+    println!();
+    println!("  Schedule ID: {}", schedule.id);
+    println!("  Plan:        {} ({})", plan.name, plan.id);
+    println!("  Cron:        {} (UTC)", schedule.cron);
+    println!(
+        "  Next run:    {}",
+        crate::timefmt::timestamp_with_age(schedule.next_run_at)
+    );
+    println!("  Max agents:  {max_agents}");
+    println!();
+    println!("Runs are started by `gator schedule run`.");
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator schedule list
+// -----------------------------------------------------------------------
+
+async fn cmd_list(pool: &SqlitePool) -> Result<()> {
+    let all = schedules::list_schedules(pool).await?;
+    if all.is_empty() {
+        println!("No schedules.");
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let plain = crate::output::is_plain();
+    if !plain {
+        println!(
+            "{:<36} {:<24} {:<16} {:<14} {:<12} LAST RUN",
+            "ID", "PLAN", "CRON", "NEXT RUN", "LAST STATUS"
+        );
+    }
+    for schedule in &all {
+        let plan_name = plan_name(pool, schedule.plan_id).await?;
+        let last_status = if schedule.last_error.is_some() {
+            "error".to_string()
+        } else if let Some(id) = schedule.last_plan_id {
+            match plan_db::get_plan(pool, id).await? {
+                Some(plan) => plan.status.to_string(),
+                None => "-".to_string(),
+            }
+        } else {
+            "-".to_string()
+        };
+        let last_run = schedule
+            .last_run_at
+            .map(|at| crate::timefmt::format(at, "%Y-%m-%d %H:%M"))
+            .unwrap_or_else(|| "never".to_string());
+        if plain {
+            println!(
+                "Schedule {} for plan {plan_name}: cron {}, next run {}, last status {last_status}, last run {last_run}.",
+                schedule.id,
+                schedule.cron,
+                crate::timefmt::relative(schedule.next_run_at, now),
+            );
+            if let Some(error) = &schedule.last_error {
+                println!("Last error: {}", error.lines().next().unwrap_or_default());
+            }
+            continue;
+        }
+        println!(
+            "{:<36} {:<24} {:<16} {:<14} {:<12} {}",
+            schedule.id,
+            plan_name,
+            schedule.cron,
+            crate::timefmt::relative(schedule.next_run_at, now),
+            last_status,
+            last_run
+        );
+        if let Some(error) = &schedule.last_error {
+            println!("  last error: {}", error.lines().next().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+async fn plan_name(pool: &SqlitePool, plan_id: Uuid) -> Result<String> {
+    Ok(plan_db::get_plan(pool, plan_id)
+        .await?
+        .map(|plan| plan.name)
+        .unwrap_or_else(|| plan_id.to_string()))
+}
+
+// -----------------------------------------------------------------------
+// gator schedule remove <schedule-id>
+// -----------------------------------------------------------------------
+
+async fn cmd_remove(pool: &SqlitePool, schedule_id_str: &str) -> Result<()> {
+    let schedule_id = Uuid::parse_str(schedule_id_str)
+        .with_context(|| format!("invalid schedule ID: {schedule_id_str:?}"))?;
+    if !schedules::delete_schedule(pool, schedule_id).await? {
+        anyhow::bail!("schedule {schedule_id} not found");
+    }
+    println!("Schedule removed: {schedule_id}");
+    println!("Plans its runs created are kept.");
+    Ok(())
+}
+
+// -----------------------------------------------------------------------
+// gator schedule run [--interval <secs>] [--once]
+// -----------------------------------------------------------------------
+
+/// Settings every scheduled run is dispatched with.
+struct Settings<'a> {
+    token_config: &'a TokenConfig,
+    notifications: &'a NotificationConfig,
+    prompt: &'a PromptConfig,
+}
+
+async fn cmd_run(
+    pool: &SqlitePool,
+    interval: Duration,
+    once: bool,
+    settings: &Settings<'_>,
+) -> Result<()> {
+    println!("Running scheduled plans");
+    if !once {
+        println!("  Interval:    {}s", interval.as_secs());
+    }
+
+    let cancel = daemon::shutdown_token();
+    loop {
+        if let Err(e) = run_pass(pool, settings, &cancel).await {
+            if once {
+                return Err(e);
+            }
+            eprintln!("Schedule pass failed: {e:#}");
+        }
+        if once || !daemon::wait_for_next_pass(&cancel, interval).await {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// One pass of the scheduler: resume runs left approved by an earlier
+/// pass, then start and dispatch the schedules that are due.
+async fn run_pass(
+    pool: &SqlitePool,
+    settings: &Settings<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    for schedule in schedules::list_schedules(pool).await? {
+        if cancel.is_cancelled() {
+            return Ok(());
+        }
+        // A run whose dispatch failed is left for the operator.
+        let (Some(plan_id), None) = (schedule.last_plan_id, &schedule.last_error) else {
+            continue;
+        };
+        let Some(plan) = plan_db::get_plan(pool, plan_id).await? else {
+            continue;
+        };
+        if plan.status == PlanStatus::Approved && plan.deleted_at.is_none() {
+            println!("Resuming scheduled run {} ({})", plan.name, plan.id);
+            dispatch(pool, &schedule, &plan, settings, cancel).await?;
+        }
+    }
+
+    let now = Utc::now();
+    let due = schedules::list_due_schedules(pool, now).await?;
+    if due.is_empty() {
+        println!(
+            "[{}] No schedules due.",
+            crate::timefmt::format(now, "%H:%M:%S"),
+        );
+        return Ok(());
+    }
+    for schedule in &due {
+        if cancel.is_cancelled() {
+            return Ok(());
+        }
+        match start_scheduled_run(pool, schedule, now).await {
+            Ok(plan) => {
+                println!("Started scheduled run {} ({})", plan.name, plan.id);
+                dispatch(pool, schedule, &plan, settings, cancel).await?;
+            }
+            Err(e) => eprintln!("Schedule {} could not start: {e:#}", schedule.id),
+        }
+    }
+    Ok(())
+}
+
+/// Dispatch a scheduled run's plan, recording a failure to dispatch on the
+/// schedule.
+async fn dispatch(
+    pool: &SqlitePool,
+    schedule: &Schedule,
+    plan: &Plan,
+    settings: &Settings<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let dispatched = async {
+        let fleet = Fleet::new(
+            isolation::create_plan_isolation(plan)?,
+            usize::try_from(schedule.max_agents).unwrap_or(1).max(1),
+            Duration::from_secs(u64::try_from(schedule.timeout_secs).unwrap_or(0)),
+            settings.token_config,
+            settings.notifications,
+            settings.prompt,
+        )?;
+        fleet
+            .dispatch(pool, plan.id, &plan.name, cancel.clone())
+            .await
+    }
+    .await;
+    if let Err(e) = &dispatched {
+        eprintln!("Scheduled run {} failed: {e:#}", plan.name);
+        schedules::set_schedule_error(pool, schedule.id, Some(&format!("{e:#}"))).await?;
+    }
+    Ok(())
+}
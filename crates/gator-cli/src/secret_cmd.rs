@@ -0,0 +1,151 @@
+//! `gator secret` commands: rotate and retire agent token secrets.
+//!
+//! Rotation generates a new active secret and keeps the old one under
+//! `[[auth.previous_secrets]]`, so tokens already handed to running agents
+//! keep validating until the secret is retired (or trimmed by a later
+//! rotation). Secrets are never printed; only their IDs.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+
+use gator_core::token::DEFAULT_KEY_ID;
+
+use crate::SecretCommands;
+use crate::config::{self, ConfigFile, PreviousSecret};
+
+/// Run a `gator secret` subcommand against the config file.
+pub fn run_secret(command: SecretCommands) -> Result<()> {
+    let mut cfg = config::load_config().context("no config file; run `gator init` first")?;
+
+    match command {
+        SecretCommands::Rotate { keep } => {
+            let new_id = rotate(&mut cfg, Utc::now(), keep);
+            config::save_config(&cfg)?;
+            println!("Rotated token secret; active key is now {new_id}.");
+            println!(
+                "Previous keys still accepted: {}",
+                cfg.auth.previous_secrets.len()
+            );
+            if std::env::var("GATOR_TOKEN_SECRET").is_ok() {
+                eprintln!(
+                    "Warning: GATOR_TOKEN_SECRET is set and overrides the config file secret."
+                );
+            }
+        }
+        SecretCommands::List => {
+            let active = cfg
+                .auth
+                .token_secret_id
+                .as_deref()
+                .unwrap_or(DEFAULT_KEY_ID);
+            println!("{active}  active");
+            for prev in &cfg.auth.previous_secrets {
+                match prev.rotated_at {
+                    Some(at) => println!(
+                        "{}  previous (rotated {})",
+                        prev.id,
+                        crate::timefmt::format(at, "%Y-%m-%d %H:%M")
+                    ),
+                    None => println!("{}  previous", prev.id),
+                }
+            }
+        }
+        SecretCommands::Retire { id } => {
+            retire(&mut cfg, &id)?;
+            config::save_config(&cfg)?;
+            println!("Retired token secret {id}; tokens signed with it no longer validate.");
+        }
+    }
+    Ok(())
+}
+
+/// Replace the active secret with a fresh one, keeping at most `keep`
+/// previous secrets. Returns the new key ID.
+fn rotate(cfg: &mut ConfigFile, now: DateTime<Utc>, keep: usize) -> String {
+    let mut new_id = format!("k{}", now.format("%Y%m%d%H%M%S"));
+    let old_id = cfg
+        .auth
+        .token_secret_id
+        .clone()
+        .unwrap_or_else(|| DEFAULT_KEY_ID.to_string());
+    // Two rotations within a second would otherwise reuse the ID.
+    let mut n = 1;
+    while new_id == old_id || cfg.auth.previous_secrets.iter().any(|p| p.id == new_id) {
+        n += 1;
+        new_id = format!("k{}-{n}", now.format("%Y%m%d%H%M%S"));
+    }
+
+    let old_secret = std::mem::replace(&mut cfg.auth.token_secret, config::generate_token_secret());
+    cfg.auth.previous_secrets.insert(
+        0,
+        PreviousSecret {
+            id: old_id,
+            secret: old_secret,
+            rotated_at: Some(now),
+        },
+    );
+    cfg.auth.previous_secrets.truncate(keep);
+    cfg.auth.token_secret_id = Some(new_id.clone());
+    new_id
+}
+
+/// Drop a previous secret by ID. The active secret cannot be retired.
+fn retire(cfg: &mut ConfigFile, id: &str) -> Result<()> {
+    let active = cfg
+        .auth
+        .token_secret_id
+        .as_deref()
+        .unwrap_or(DEFAULT_KEY_ID);
+    if id == active {
+        bail!("{id} is the active secret; run `gator secret rotate` first");
+    }
+    let before = cfg.auth.previous_secrets.len();
+    cfg.auth.previous_secrets.retain(|p| p.id != id);
+    if cfg.auth.previous_secrets.len() == before {
+        bail!("no previous token secret with ID {id}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_secret(secret: &str) -> ConfigFile {
+        toml::from_str(&format!(
+            "[database]\nurl = \"/tmp/gator.db\"\n\n[auth]\ntoken_secret = \"{secret}\"\n"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn rotate_keeps_old_secret_and_trims() {
+        let mut cfg = config_with_secret(&"aa".repeat(32));
+        let t0 = "2026-01-02T03:04:05Z".parse().unwrap();
+
+        let first = rotate(&mut cfg, t0, 1);
+        assert_eq!(first, "k20260102030405");
+        assert_ne!(cfg.auth.token_secret, "aa".repeat(32));
+        assert_eq!(cfg.auth.previous_secrets.len(), 1);
+        assert_eq!(cfg.auth.previous_secrets[0].id, DEFAULT_KEY_ID);
+        assert_eq!(cfg.auth.previous_secrets[0].secret, "aa".repeat(32));
+
+        // Same second: IDs stay unique, and only one previous key is kept.
+        let second = rotate(&mut cfg, t0, 1);
+        assert_eq!(second, "k20260102030405-2");
+        assert_eq!(cfg.auth.previous_secrets.len(), 1);
+        assert_eq!(cfg.auth.previous_secrets[0].id, first);
+    }
+
+    #[test]
+    fn retire_refuses_active_and_unknown_ids() {
+        let mut cfg = config_with_secret(&"aa".repeat(32));
+        rotate(&mut cfg, Utc::now(), 3);
+
+        let active = cfg.auth.token_secret_id.clone().unwrap();
+        assert!(retire(&mut cfg, &active).is_err());
+        assert!(retire(&mut cfg, "nope").is_err());
+        retire(&mut cfg, DEFAULT_KEY_ID).unwrap();
+        assert!(cfg.auth.previous_secrets.is_empty());
+    }
+}
@@ -6,18 +6,15 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
-use serde::Serialize;
 use sqlx::SqlitePool;
 use tower_http::cors::CorsLayer;
 use uuid::Uuid;
 
 use axum::response::Html;
-use gator_db::models::{AgentEvent, Invariant, Plan, Task};
-use gator_db::queries::tasks::PlanProgress;
+use gator_core::schema::Versioned;
+use gator_core::schema::v1::{Invariant, PlanDetail, PlanSummary, TaskDetail, TokenUsage};
 use gator_db::queries::{
-    agent_events,
-    gate_results::{self, GateResultWithName},
-    invariants as invariant_db, plans as plan_db, tasks as task_db,
+    agent_events, gate_results, invariants as invariant_db, plans as plan_db, tasks as task_db,
 };
 
 // ---------------------------------------------------------------------------
@@ -52,71 +49,6 @@ impl IntoResponse for AppError {
     }
 }
 
-// ---------------------------------------------------------------------------
-// Response types
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Serialize)]
-pub struct ProgressResponse {
-    pub pending: i64,
-    pub assigned: i64,
-    pub running: i64,
-    pub checking: i64,
-    pub passed: i64,
-    pub failed: i64,
-    pub escalated: i64,
-    pub total: i64,
-}
-
-impl From<PlanProgress> for ProgressResponse {
-    fn from(p: PlanProgress) -> Self {
-        Self {
-            pending: p.pending,
-            assigned: p.assigned,
-            running: p.running,
-            checking: p.checking,
-            passed: p.passed,
-            failed: p.failed,
-            escalated: p.escalated,
-            total: p.total,
-        }
-    }
-}
-
-#[derive(Debug, Serialize)]
-pub struct TokenUsageResponse {
-    pub input_tokens: i64,
-    pub output_tokens: i64,
-    pub total_tokens: i64,
-}
-
-#[derive(Debug, Serialize)]
-pub struct PlanSummaryResponse {
-    #[serde(flatten)]
-    pub plan: Plan,
-    pub progress: ProgressResponse,
-}
-
-#[derive(Debug, Serialize)]
-pub struct PlanDetailResponse {
-    #[serde(flatten)]
-    pub plan: Plan,
-    pub progress: ProgressResponse,
-    pub token_usage: TokenUsageResponse,
-    pub tasks: Vec<Task>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct TaskDetailResponse {
-    #[serde(flatten)]
-    pub task: Task,
-    pub dependencies: Vec<Uuid>,
-    pub invariants: Vec<Invariant>,
-    pub events: Vec<AgentEvent>,
-    pub gate_results: Vec<GateResultWithName>,
-    pub token_usage: TokenUsageResponse,
-}
-
 // ---------------------------------------------------------------------------
 // Router
 // ---------------------------------------------------------------------------
@@ -202,10 +134,10 @@ async fn list_plans(State(pool): State<SqlitePool>) -> Result<axum::response::Re
         let progress = task_db::get_plan_progress(&pool, plan.id)
             .await
             .map_err(AppError::internal)?;
-        results.push(PlanSummaryResponse {
-            plan,
+        results.push(Versioned::new(PlanSummary {
+            plan: plan.into(),
             progress: progress.into(),
-        });
+        }));
     }
 
     Ok(Json(results).into_response())
@@ -232,16 +164,12 @@ async fn get_plan_detail(
         .await
         .map_err(AppError::internal)?;
 
-    Ok(Json(PlanDetailResponse {
-        plan,
+    Ok(Json(Versioned::new(PlanDetail {
+        plan: plan.into(),
         progress: progress.into(),
-        token_usage: TokenUsageResponse {
-            input_tokens,
-            output_tokens,
-            total_tokens: input_tokens + output_tokens,
-        },
-        tasks,
-    })
+        token_usage: TokenUsage::new(input_tokens, output_tokens),
+        tasks: tasks.into_iter().map(Into::into).collect(),
+    }))
     .into_response())
 }
 
@@ -274,18 +202,14 @@ async fn get_task_detail(
         .await
         .map_err(AppError::internal)?;
 
-    Ok(Json(TaskDetailResponse {
-        task,
+    Ok(Json(Versioned::new(TaskDetail {
+        task: task.into(),
         dependencies,
-        invariants,
-        events,
-        gate_results,
-        token_usage: TokenUsageResponse {
-            input_tokens,
-            output_tokens,
-            total_tokens: input_tokens + output_tokens,
-        },
-    })
+        invariants: invariants.into_iter().map(Into::into).collect(),
+        events: events.into_iter().map(Into::into).collect(),
+        gate_results: gate_results.into_iter().map(Into::into).collect(),
+        token_usage: TokenUsage::new(input_tokens, output_tokens),
+    }))
     .into_response())
 }
 
@@ -296,6 +220,10 @@ async fn list_invariants_handler(
         .await
         .map_err(AppError::internal)?;
 
+    let invariants: Vec<_> = invariants
+        .into_iter()
+        .map(|invariant| Versioned::new(Invariant::from(invariant)))
+        .collect();
     Ok(Json(invariants).into_response())
 }
 
@@ -395,6 +323,7 @@ mod tests {
         let arr = json.as_array().expect("response should be an array");
         assert_eq!(arr.len(), 1);
         assert_eq!(arr[0]["name"], plan.name);
+        assert_eq!(arr[0]["schema_version"], 1);
         assert!(
             arr[0].get("progress").is_some(),
             "each plan should have a progress object"
@@ -442,6 +371,7 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
         let json = body_json(resp).await;
         assert_eq!(json["name"], "detail-plan");
+        assert_eq!(json["schema_version"], 1);
         let tasks = json["tasks"].as_array().expect("should have tasks array");
         assert_eq!(tasks.len(), 1);
         assert!(
@@ -564,6 +494,12 @@ mod tests {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         };
         insert_invariant(&pool, &new_inv)
             .await
@@ -575,6 +511,7 @@ mod tests {
         let arr = json.as_array().expect("response should be an array");
         assert_eq!(arr.len(), 1);
         assert_eq!(arr[0]["name"], "cargo-check");
+        assert_eq!(arr[0]["schema_version"], 1);
 
         pool.close().await;
         drop_test_db(&db_name).await;
@@ -0,0 +1,462 @@
+//! Markdown snapshots of the fleet for incident reports.
+//!
+//! `gator status --snapshot <file>` and the dashboard's `s` key write the
+//! same document: every plan with its progress, then, for plans that are
+//! running, failed, or interrupted (or the one plan asked for), each task's
+//! status and latest gate results, the gate failures with their collected
+//! artifacts, recent agent errors, and operator notes.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{AgentEvent, GateArtifact, Note, Plan, PlanStatus, Task};
+use gator_db::queries::agent_events;
+use gator_db::queries::gate_artifacts;
+use gator_db::queries::gate_results::{self, GateResultWithName};
+use gator_db::queries::notes as note_db;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks::{self as task_db, PlanProgress};
+
+use crate::status_cmd::describe_plan_status;
+use crate::timefmt;
+
+/// Agent errors listed per plan.
+const MAX_ERRORS: i64 = 10;
+
+/// Characters of a failing invariant's stderr kept in the snapshot.
+const MAX_STDERR_CHARS: usize = 600;
+
+/// Everything a snapshot shows, read from the database at `taken_at`.
+#[derive(Debug)]
+pub struct FleetSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub plans: Vec<(Plan, PlanProgress)>,
+    pub details: Vec<PlanDetail>,
+}
+
+/// The detailed section of one plan.
+#[derive(Debug)]
+pub struct PlanDetail {
+    pub plan: Plan,
+    /// Each task with the gate results of its current attempt.
+    pub tasks: Vec<(Task, Vec<GateResultWithName>)>,
+    /// Files collected from those gate results' failures.
+    pub artifacts: Vec<GateArtifact>,
+    /// Recent `error` events, newest first.
+    pub errors: Vec<AgentEvent>,
+    pub notes: Vec<Note>,
+}
+
+/// Whether a plan gets a detailed section in a fleet-wide snapshot.
+fn needs_detail(plan: &Plan) -> bool {
+    matches!(plan.status, PlanStatus::Running | PlanStatus::Failed) || plan.interrupted_at.is_some()
+}
+
+/// Read a snapshot of every plan, or of `plan_id` only.
+pub async fn collect(pool: &SqlitePool, plan_id: Option<Uuid>) -> Result<FleetSnapshot> {
+    let plans = match plan_id {
+        Some(id) => vec![
+            plan_db::get_plan(pool, id)
+                .await?
+                .with_context(|| format!("plan {id} not found"))?,
+        ],
+        None => plan_db::list_plans(pool).await?,
+    };
+
+    let mut rows = Vec::with_capacity(plans.len());
+    let mut details = Vec::new();
+    for plan in plans {
+        let progress = task_db::get_plan_progress(pool, plan.id).await?;
+        if plan_id.is_some() || needs_detail(&plan) {
+            let mut tasks = Vec::new();
+            let mut artifacts = Vec::new();
+            for task in task_db::list_tasks_for_plan(pool, plan.id).await? {
+                let results = gate_results::get_latest_gate_results(pool, task.id).await?;
+                artifacts.extend(
+                    gate_artifacts::list_gate_artifacts(pool, task.id, task.attempt).await?,
+                );
+                tasks.push((task, results));
+            }
+            details.push(PlanDetail {
+                plan: plan.clone(),
+                tasks,
+                artifacts,
+                errors: agent_events::get_recent_errors_for_plan(pool, plan.id, MAX_ERRORS).await?,
+                notes: note_db::list_notes_for_plan(pool, plan.id).await?,
+            });
+        }
+        rows.push((plan, progress));
+    }
+
+    Ok(FleetSnapshot {
+        taken_at: Utc::now(),
+        plans: rows,
+        details,
+    })
+}
+
+/// Collect a snapshot and write it to `path` as markdown.
+pub async fn write_snapshot(pool: &SqlitePool, plan_id: Option<Uuid>, path: &Path) -> Result<()> {
+    let snapshot = collect(pool, plan_id).await?;
+    std::fs::write(path, render_markdown(&snapshot))
+        .with_context(|| format!("failed to write snapshot to {}", path.display()))
+}
+
+/// Render a snapshot as a markdown document.
+pub fn render_markdown(snapshot: &FleetSnapshot) -> String {
+    let mut md = String::new();
+    md.push_str("# Gator fleet snapshot\n\n");
+    md.push_str(&format!(
+        "Taken {}.\n\n",
+        timefmt::timestamp(snapshot.taken_at)
+    ));
+
+    md.push_str("## Plans\n\n");
+    if snapshot.plans.is_empty() {
+        md.push_str("No plans.\n");
+    } else {
+        md.push_str("| Plan | Status | Passed | Running | Failed | Escalated | ID |\n");
+        md.push_str("|------|--------|--------|---------|--------|-----------|----|\n");
+        for (plan, p) in &snapshot.plans {
+            md.push_str(&format!(
+                "| {} | {} | {}/{} | {} | {} | {} | `{}` |\n",
+                cell(&plan.name),
+                describe_plan_status(plan),
+                p.passed,
+                p.total,
+                p.assigned + p.running + p.checking,
+                p.failed,
+                p.escalated,
+                plan.id,
+            ));
+        }
+    }
+
+    for detail in &snapshot.details {
+        md.push('\n');
+        render_plan_detail(&mut md, detail, snapshot.taken_at);
+    }
+    md
+}
+
+fn render_plan_detail(md: &mut String, detail: &PlanDetail, now: DateTime<Utc>) {
+    let plan = &detail.plan;
+    md.push_str(&format!("## {}\n\n", plan.name));
+    md.push_str(&format!("- **ID:** `{}`\n", plan.id));
+    md.push_str(&format!("- **Status:** {}\n", describe_plan_status(plan)));
+    md.push_str(&format!("- **Base branch:** {}\n", plan.base_branch));
+    if let Some(interrupted_at) = plan.interrupted_at {
+        md.push_str(&format!(
+            "- **Interrupted:** {}\n",
+            timefmt::relative(interrupted_at, now)
+        ));
+    }
+
+    md.push_str("\n### Tasks\n\n");
+    if detail.tasks.is_empty() {
+        md.push_str("No tasks.\n");
+    } else {
+        md.push_str("| Task | Status | Attempt | Harness | Since | Gate |\n");
+        md.push_str("|------|--------|---------|---------|-------|------|\n");
+        for (task, results) in &detail.tasks {
+            let since = task
+                .completed_at
+                .or(task.started_at)
+                .map_or("-".to_string(), |t| timefmt::relative(t, now));
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                cell(&task.name),
+                task.status,
+                task.attempt,
+                task.assigned_harness.as_deref().unwrap_or("-"),
+                since,
+                gate_summary(results),
+            ));
+        }
+    }
+
+    let failures: Vec<(&Task, &GateResultWithName)> = detail
+        .tasks
+        .iter()
+        .flat_map(|(task, results)| results.iter().filter(|r| !r.passed).map(move |r| (task, r)))
+        .collect();
+    if !failures.is_empty() {
+        md.push_str("\n### Gate failures\n\n");
+        for (task, result) in failures {
+            let outcome = if result.timed_out {
+                "timed out".to_string()
+            } else {
+                result
+                    .exit_code
+                    .map_or("killed by signal".to_string(), |c| format!("exit {c}"))
+            };
+            md.push_str(&format!(
+                "**{}** / `{}` (attempt {}, {outcome})\n\n",
+                task.name, result.invariant_name, result.attempt
+            ));
+            let stderr = result.stderr.as_deref().unwrap_or_default().trim();
+            if !stderr.is_empty() {
+                md.push_str(&format!("```\n{}\n```\n\n", tail(stderr, MAX_STDERR_CHARS)));
+            }
+            let artifacts: Vec<&GateArtifact> = detail
+                .artifacts
+                .iter()
+                .filter(|a| a.gate_result_id == result.id)
+                .collect();
+            if !artifacts.is_empty() {
+                md.push_str("Artifacts:\n\n");
+                for artifact in artifacts {
+                    md.push_str(&format!(
+                        "- `{}` ({} bytes): `{}`\n",
+                        artifact.path, artifact.size_bytes, artifact.stored_path
+                    ));
+                }
+                md.push('\n');
+            }
+        }
+    }
+
+    if !detail.errors.is_empty() {
+        let names: HashMap<Uuid, &str> = detail
+            .tasks
+            .iter()
+            .map(|(t, _)| (t.id, t.name.as_str()))
+            .collect();
+        md.push_str("\n### Recent errors\n\n");
+        for event in &detail.errors {
+            let message = event
+                .payload
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map_or_else(|| event.payload.to_string(), str::to_string);
+            md.push_str(&format!(
+                "- {} **{}** (attempt {}): {}\n",
+                timefmt::timestamp(event.recorded_at),
+                names.get(&event.task_id).copied().unwrap_or("?"),
+                event.attempt,
+                message.lines().next().unwrap_or_default(),
+            ));
+        }
+    }
+
+    if !detail.notes.is_empty() {
+        let tasks: Vec<Task> = detail.tasks.iter().map(|(t, _)| t.clone()).collect();
+        md.push_str("\n### Notes\n\n");
+        for line in crate::note_cmd::note_lines(&detail.notes, &tasks) {
+            md.push_str(&format!("- {line}\n"));
+        }
+    }
+}
+
+/// E.g. "2/3 passed (failed: lint)", or "-" without results.
+fn gate_summary(results: &[GateResultWithName]) -> String {
+    if results.is_empty() {
+        return "-".to_string();
+    }
+    let passed = results.iter().filter(|r| r.passed).count();
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| !r.passed)
+        .map(|r| r.invariant_name.as_str())
+        .collect();
+    if failed.is_empty() {
+        format!("{passed}/{} passed", results.len())
+    } else {
+        format!(
+            "{passed}/{} passed (failed: {})",
+            results.len(),
+            cell(&failed.join(", "))
+        )
+    }
+}
+
+/// Escape a value for a markdown table cell.
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// The last `max_chars` characters of `s`, where errors usually end up.
+fn tail(s: &str, max_chars: usize) -> String {
+    let count = s.chars().count();
+    if count <= max_chars {
+        return s.to_string();
+    }
+    let rest: String = s.chars().skip(count - max_chars).collect();
+    format!("...{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gator_db::models::{GatePolicy, ScopeLevel, TaskKind, TaskStatus};
+
+    fn plan(name: &str, status: PlanStatus) -> Plan {
+        Plan {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            project_path: "/tmp/project".to_string(),
+            base_branch: "main".to_string(),
+            status,
+            token_budget: None,
+            default_harness: "claude-code".to_string(),
+            isolation: "worktree".to_string(),
+            container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            created_at: Utc::now(),
+            approved_at: None,
+            completed_at: None,
+            deleted_at: None,
+            archived_at: None,
+            interrupted_at: None,
+            failure_reason: None,
+            dispatched_host: None,
+            dispatched_pid: None,
+            dispatched_user: None,
+            dispatched_at: None,
+            warmup_command: None,
+            hooks: Default::default(),
+            setup_commands: Default::default(),
+            caches: Default::default(),
+        }
+    }
+
+    fn task(plan_id: Uuid, name: &str, status: TaskStatus) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            plan_id,
+            name: name.to_string(),
+            description: String::new(),
+            scope_level: ScopeLevel::Narrow,
+            gate_policy: GatePolicy::Auto,
+            retry_max: 3,
+            status,
+            assigned_harness: Some("claude-code".to_string()),
+            requested_harness: None,
+            worktree_path: None,
+            attempt: 1,
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            issue: None,
+            deleted_at: None,
+            services: sqlx::types::Json(Vec::new()),
+            kind: TaskKind::Code,
+            artifacts: sqlx::types::Json(Vec::new()),
+            paths: sqlx::types::Json(Vec::new()),
+            timeout_secs: None,
+            retry_env: Default::default(),
+            retry_env_attempt: None,
+            gate_script: None,
+            agent_pid: None,
+            container_id: None,
+            agent_host: None,
+            agent_started: None,
+            llm_review: false,
+            review_harness: None,
+            run_id: None,
+            attempt_id: None,
+            merged_at: None,
+            merge_commit: None,
+            setup_commands: Default::default(),
+            disk_usage_bytes: None,
+            disk_usage_at: None,
+        }
+    }
+
+    fn result(task: &Task, name: &str, passed: bool, stderr: &str) -> GateResultWithName {
+        GateResultWithName {
+            id: Uuid::new_v4(),
+            task_id: task.id,
+            invariant_id: Uuid::new_v4(),
+            attempt: task.attempt,
+            passed,
+            exit_code: Some(if passed { 0 } else { 101 }),
+            stdout: None,
+            stderr: Some(stderr.to_string()),
+            duration_ms: Some(1200),
+            checked_at: Utc::now(),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            cached_from: None,
+            invariant_name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn markdown_lists_plans_and_details_stuck_ones() {
+        let stuck = plan("stuck|plan", PlanStatus::Failed);
+        let done = plan("done", PlanStatus::Completed);
+        let failing = task(stuck.id, "api", TaskStatus::Escalated);
+        let passing = task(stuck.id, "docs", TaskStatus::Passed);
+        let tests = result(&failing, "tests", false, "thread panicked at src/lib.rs");
+        let snapshot = FleetSnapshot {
+            taken_at: Utc::now(),
+            plans: vec![
+                (stuck.clone(), PlanProgress::default()),
+                (done, PlanProgress::default()),
+            ],
+            details: vec![PlanDetail {
+                plan: stuck.clone(),
+                tasks: vec![
+                    (
+                        failing.clone(),
+                        vec![result(&failing, "build", true, ""), tests.clone()],
+                    ),
+                    (passing.clone(), Vec::new()),
+                ],
+                artifacts: vec![GateArtifact {
+                    id: Uuid::new_v4(),
+                    gate_result_id: tests.id,
+                    path: "test-results/login.png".to_string(),
+                    stored_path: "/tmp/failures/login.png".to_string(),
+                    size_bytes: 2048,
+                    collected_at: Utc::now(),
+                }],
+                errors: vec![AgentEvent {
+                    id: 1,
+                    task_id: failing.id,
+                    attempt: 1,
+                    event_type: "error".to_string(),
+                    payload: serde_json::json!({"message": "agent timed out after 1800s"}),
+                    recorded_at: Utc::now(),
+                    run_id: None,
+                    attempt_id: None,
+                }],
+                notes: Vec::new(),
+            }],
+        };
+
+        let md = render_markdown(&snapshot);
+
+        assert!(md.starts_with("# Gator fleet snapshot\n"));
+        assert!(md.contains("| stuck\\|plan | failed | 0/0 |"));
+        assert!(md.contains("| done | completed |"));
+        assert!(md.contains("## stuck|plan\n"));
+        assert!(!md.contains("## done\n"));
+        assert!(
+            md.contains("| api | escalated | 1 | claude-code | - | 1/2 passed (failed: tests) |")
+        );
+        assert!(md.contains("| docs | passed | 1 | claude-code | - | - |"));
+        assert!(md.contains(
+            "**api** / `tests` (attempt 1, exit 101)\n\n```\nthread panicked at src/lib.rs\n```\n"
+        ));
+        assert!(md.contains(
+            "Artifacts:\n\n- `test-results/login.png` (2048 bytes): `/tmp/failures/login.png`\n"
+        ));
+        assert!(md.contains("**api** (attempt 1): agent timed out after 1800s\n"));
+    }
+
+    #[test]
+    fn tail_keeps_the_end() {
+        assert_eq!(tail("short", 10), "short");
+        assert_eq!(tail("0123456789", 4), "...6789");
+    }
+}
@@ -0,0 +1,182 @@
+//! `gator stats` command: historical analytics aggregated across all plans.
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+use gator_db::queries::stats::{self as stats_db, StatsPeriod};
+
+use crate::report_cmd::cost_usd;
+
+/// Width of the token trend bar for the busiest period.
+const BAR_WIDTH: usize = 30;
+
+/// Run the stats command. `since` limits the stats to work finished within
+/// that long; `limit` caps the failing invariants listed.
+pub async fn run_stats(
+    pool: &SqlitePool,
+    since: Option<Duration>,
+    period: StatsPeriod,
+    usd_per_mtok: Option<f64>,
+    limit: i64,
+) -> Result<()> {
+    let since = since.map(|age| Utc::now() - age);
+    let plain = crate::output::is_plain();
+
+    match since {
+        Some(since) => println!(
+            "Stats since {}, across all plans",
+            crate::timefmt::timestamp(since)
+        ),
+        None => println!("Stats across all plans"),
+    }
+    println!();
+
+    // Success rate per harness.
+    let harnesses = stats_db::harness_stats(pool, since).await?;
+    println!("Success rate by harness:");
+    if harnesses.is_empty() {
+        println!("  No finished tasks.");
+    } else if plain {
+        for h in &harnesses {
+            println!(
+                "  Harness {}: {} passed, {} failed, {:.1}% success.",
+                h.harness,
+                h.passed,
+                h.failed,
+                h.success_rate() * 100.0
+            );
+        }
+    } else {
+        println!(
+            "  {:<20} {:>8} {:>8} {:>9}",
+            "HARNESS", "PASSED", "FAILED", "SUCCESS"
+        );
+        for h in &harnesses {
+            println!(
+                "  {:<20} {:>8} {:>8} {:>8.1}%",
+                h.harness,
+                h.passed,
+                h.failed,
+                h.success_rate() * 100.0
+            );
+        }
+    }
+    println!();
+
+    // Attempts per task scope.
+    let scopes = stats_db::scope_stats(pool, since).await?;
+    println!("Attempts by task scope:");
+    if scopes.is_empty() {
+        println!("  No finished tasks.");
+    } else if plain {
+        for s in &scopes {
+            println!(
+                "  Scope {}: {} task(s), {:.2} attempts on average, at most {}.",
+                s.scope_level, s.tasks, s.avg_attempts, s.max_attempts
+            );
+        }
+    } else {
+        println!("  {:<10} {:>8} {:>8} {:>8}", "SCOPE", "TASKS", "AVG", "MAX");
+        for s in &scopes {
+            println!(
+                "  {:<10} {:>8} {:>8.2} {:>8}",
+                s.scope_level, s.tasks, s.avg_attempts, s.max_attempts
+            );
+        }
+    }
+    println!();
+
+    // Token usage over time.
+    let usage = stats_db::token_usage_by_period(pool, period, since).await?;
+    println!("Token usage by {period}:");
+    if usage.is_empty() {
+        println!("  No token usage recorded.");
+    } else if plain {
+        for u in &usage {
+            let total = u.input_tokens + u.output_tokens;
+            let cost = cost_usd(total, usd_per_mtok)
+                .map(|cost| format!(", cost ${cost:.2}"))
+                .unwrap_or_default();
+            println!(
+                "  Period {}: {} input, {} output, {total} total{cost}.",
+                u.period, u.input_tokens, u.output_tokens
+            );
+        }
+    } else {
+        let busiest = usage
+            .iter()
+            .map(|u| u.input_tokens + u.output_tokens)
+            .max()
+            .unwrap_or(0);
+        println!(
+            "  {:<12} {:>12} {:>12} {:>12} {:>10}",
+            "PERIOD", "INPUT", "OUTPUT", "TOTAL", "COST"
+        );
+        for u in &usage {
+            let total = u.input_tokens + u.output_tokens;
+            let cost = cost_usd(total, usd_per_mtok)
+                .map(|cost| format!("${cost:.2}"))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "  {:<12} {:>12} {:>12} {:>12} {:>10} {}",
+                u.period,
+                u.input_tokens,
+                u.output_tokens,
+                total,
+                cost,
+                bar(total, busiest)
+            );
+        }
+    }
+    println!();
+
+    // Most frequently failing invariants.
+    let invariants = stats_db::failing_invariants(pool, since, limit).await?;
+    println!("Most frequently failing invariants:");
+    if invariants.is_empty() {
+        println!("  No invariant failures.");
+    } else if plain {
+        for i in &invariants {
+            println!(
+                "  Invariant {}: failed {} of {} check(s), for {} task(s).",
+                i.name, i.failures, i.checks, i.tasks
+            );
+        }
+    } else {
+        println!(
+            "  {:<30} {:>8} {:>8} {:>8}",
+            "INVARIANT", "FAILED", "CHECKS", "TASKS"
+        );
+        for i in &invariants {
+            println!(
+                "  {:<30} {:>8} {:>8} {:>8}",
+                i.name, i.failures, i.checks, i.tasks
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// A bar of `#` as long as `value` is relative to `max`.
+fn bar(value: i64, max: i64) -> String {
+    if max <= 0 {
+        return String::new();
+    }
+    let width = (value.max(0) as f64 / max as f64 * BAR_WIDTH as f64).round() as usize;
+    "#".repeat(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_scale_to_the_busiest_period() {
+        assert_eq!(bar(100, 100).len(), BAR_WIDTH);
+        assert_eq!(bar(50, 100).len(), BAR_WIDTH / 2);
+        assert_eq!(bar(0, 100), "");
+        assert_eq!(bar(0, 0), "");
+    }
+}
@@ -1,69 +1,282 @@
 //! `gator status` command: show plan progress and per-task status.
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 
+use gator_core::harness::PromptScaffold;
+use gator_core::schema::Versioned;
+use gator_core::schema::v1;
+use gator_core::state::dispatch;
+use gator_core::worktree::disk;
+use gator_db::models::{Plan, PlanFailureReason, PlanStatus, PlanThrottle, TaskStatus};
+use gator_db::queries::agent_events;
+use gator_db::queries::notes as note_db;
+use gator_db::queries::plan_throttles;
 use gator_db::queries::plans as plan_db;
-use gator_db::queries::tasks as task_db;
+use gator_db::queries::tasks::{self as task_db, PendingTask, PlanProgress};
+
+use crate::OutputFormat;
+use crate::config;
+use crate::dispatch_cmd;
+use crate::output;
+use crate::timefmt;
 
 /// Run the status command.
 ///
 /// When `plan_id_str` is `Some`, shows detailed status for that plan.
-/// When `None`, lists all plans with a progress summary.
-pub async fn run_status(pool: &SqlitePool, plan_id_str: Option<&str>) -> Result<()> {
+/// When `None`, lists all plans with a progress summary. JSON output keeps
+/// timestamps in ISO-8601 UTC; text output uses the display timezone.
+/// With `why`, also explains why each pending task of the plan is not
+/// running yet.
+pub async fn run_status(
+    pool: &SqlitePool,
+    plan_id_str: Option<&str>,
+    output: OutputFormat,
+    why: bool,
+    limit: usize,
+) -> Result<()> {
     match plan_id_str {
-        Some(id_str) => run_plan_status(pool, id_str).await,
-        None => run_fleet_status(pool).await,
+        Some(id_str) => run_plan_status(pool, id_str, output, why, limit).await,
+        None => run_fleet_status(pool, output).await,
+    }
+}
+
+/// Write a markdown snapshot of every plan, or of one plan, to `path`.
+pub async fn run_snapshot(
+    pool: &SqlitePool,
+    plan_id_str: Option<&str>,
+    path: &std::path::Path,
+) -> Result<()> {
+    let plan_id = plan_id_str
+        .map(crate::resolve::resolve_plan_id)
+        .transpose()?;
+    crate::snapshot::write_snapshot(pool, plan_id, path).await?;
+    println!("Snapshot written to {}", path.display());
+    Ok(())
+}
+
+/// The plan's status in words, with the reason if it failed, e.g.
+/// `failed (token budget exceeded)`.
+pub(crate) fn describe_plan_status(plan: &Plan) -> String {
+    match (plan.status, plan.failure_reason) {
+        (PlanStatus::Failed, Some(PlanFailureReason::BudgetExceeded)) => {
+            "failed (token budget exceeded)".to_string()
+        }
+        (PlanStatus::Failed, Some(PlanFailureReason::TasksEscalated)) => {
+            "failed (tasks escalated)".to_string()
+        }
+        (status, _) => status.to_string(),
+    }
+}
+
+/// Who last dispatched the plan, e.g. `<time> by alice@build-1 (pid 4242)`.
+/// `None` if it has never been dispatched.
+fn dispatcher_line(plan: &Plan) -> Option<String> {
+    let at = plan.dispatched_at?;
+    Some(format!(
+        "{} by {}@{} (pid {})",
+        timefmt::timestamp_with_age(at),
+        plan.dispatched_user.as_deref().unwrap_or("unknown"),
+        plan.dispatched_host.as_deref().unwrap_or("unknown"),
+        plan.dispatched_pid
+            .map_or_else(|| "unknown".to_string(), |pid| pid.to_string())
+    ))
+}
+
+/// How the plan's running dispatch is holding back after rate limits, if
+/// it is. A row left by a dispatch that has since ended is ignored.
+pub(crate) async fn active_throttle(
+    pool: &SqlitePool,
+    plan: &Plan,
+) -> Result<Option<PlanThrottle>> {
+    if plan.status != PlanStatus::Running {
+        return Ok(None);
+    }
+    plan_throttles::get_throttle(pool, plan.id).await
+}
+
+/// A throttle in words, e.g. `2 of 4 agents after 3 rate limit(s), paused
+/// until 12:00:00 UTC (in 45s)`.
+pub(crate) fn describe_throttle(throttle: &PlanThrottle, now: DateTime<Utc>) -> String {
+    let mut line = format!(
+        "{} of {} agents after {} rate limit(s)",
+        throttle.agent_limit, throttle.max_agents, throttle.rate_limits
+    );
+    if let Some(until) = throttle.paused_until.filter(|until| *until > now) {
+        line.push_str(&format!(
+            ", paused until {} ({})",
+            timefmt::timestamp(until),
+            timefmt::relative(until, now)
+        ));
     }
+    line
 }
 
-/// Show detailed status for a single plan.
-async fn run_plan_status(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
+/// Show detailed status for a single plan. Text output lists at most
+/// `limit` tasks (0 lists all); JSON always has every task.
+async fn run_plan_status(
+    pool: &SqlitePool,
+    plan_id_str: &str,
+    output: OutputFormat,
+    why: bool,
+    limit: usize,
+) -> Result<()> {
     let plan_id = crate::resolve::resolve_plan_id(plan_id_str)?;
 
     let plan = plan_db::get_plan(pool, plan_id)
         .await?
         .with_context(|| format!("plan {plan_id} not found"))?;
 
+    if output == OutputFormat::Json {
+        let progress = task_db::get_plan_progress(pool, plan_id).await?;
+        let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+        let why = if why {
+            Some(
+                explain_pending(pool, &plan, &progress)
+                    .await?
+                    .into_iter()
+                    .map(|(pending, reasons)| v1::PendingTask {
+                        ready: pending.is_ready(),
+                        task: pending.task.name,
+                        reasons,
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let notes = note_db::list_notes_for_plan(pool, plan_id).await?;
+        let throttle = active_throttle(pool, &plan).await?;
+        let body = Versioned::new(v1::PlanStatus {
+            plan: plan.into(),
+            progress: progress.into(),
+            tasks: tasks.into_iter().map(Into::into).collect(),
+            notes: notes.into_iter().map(Into::into).collect(),
+            why,
+            throttle: throttle.map(Into::into),
+        });
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
     println!("Plan: {} ({})", plan.name, plan.id);
-    println!("Status: {}", plan.status);
+    println!("Status: {}", describe_plan_status(&plan));
+    println!("Created: {}", timefmt::timestamp_with_age(plan.created_at));
     if let Some(approved_at) = plan.approved_at {
-        println!("Approved: {}", approved_at.format("%Y-%m-%d %H:%M:%S UTC"));
+        println!("Approved: {}", timefmt::timestamp_with_age(approved_at));
     }
     if let Some(completed_at) = plan.completed_at {
+        println!("Completed: {}", timefmt::timestamp_with_age(completed_at));
+    }
+    if let Some(interrupted_at) = plan.interrupted_at {
         println!(
-            "Completed: {}",
-            completed_at.format("%Y-%m-%d %H:%M:%S UTC")
+            "Interrupted: {} (resume with `gator dispatch {}`)",
+            timefmt::timestamp_with_age(interrupted_at),
+            plan.id
         );
     }
+    if let Some(line) = dispatcher_line(&plan) {
+        println!("Dispatched: {line}");
+    }
+    if let Some(throttle) = active_throttle(pool, &plan).await? {
+        println!("Throttled: {}", describe_throttle(&throttle, Utc::now()));
+        if let Some(error) = &throttle.last_error {
+            println!(
+                "  last rate limit: {}",
+                error.lines().next().unwrap_or_default()
+            );
+        }
+    }
     if let Some(budget) = plan.token_budget {
         println!("Token budget: {budget}");
     }
+    if plan.failure_reason == Some(PlanFailureReason::BudgetExceeded) {
+        println!(
+            "Resume with a higher budget: gator resume {} --budget <tokens>",
+            plan.id
+        );
+    }
+    let usage = task_db::worktree_disk_usage(pool, &plan.project_path).await?;
+    if usage > 0 {
+        println!("Worktrees: {} on disk", disk::format_size(usage));
+    }
+    if let Some(warning) = quota_warning(&plan.project_path, usage)? {
+        eprintln!("{warning}");
+    }
     println!();
 
     // Progress summary.
     let progress = task_db::get_plan_progress(pool, plan_id).await?;
-    println!("Progress: {}/{} passed", progress.passed, progress.total);
-    println!(
-        "  pending={} assigned={} running={} checking={} passed={} failed={} escalated={}",
-        progress.pending,
-        progress.assigned,
-        progress.running,
-        progress.checking,
-        progress.passed,
-        progress.failed,
-        progress.escalated,
-    );
+    let plain = output::is_plain();
+    if plain {
+        println!("Progress: {}.", output::progress_sentence(&progress));
+    } else {
+        println!("Progress: {}/{} passed", progress.passed, progress.total);
+        println!(
+            "  pending={} assigned={} running={} blocked_on_human={} checking={} passed={} failed={} escalated={} cancelled={}",
+            progress.pending,
+            progress.assigned,
+            progress.running,
+            progress.blocked_on_human,
+            progress.checking,
+            progress.passed,
+            progress.failed,
+            progress.escalated,
+            progress.cancelled,
+        );
+    }
     println!();
 
     // Per-task listing.
     let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+    let now = Utc::now();
     println!("Tasks:");
-    for task in &tasks {
+    let shown = output::page(tasks.len(), 0, limit);
+    for task in &tasks[shown.clone()] {
+        let mut age = match (task.started_at, task.completed_at) {
+            (_, Some(done)) => format!(", finished {}", timefmt::relative(done, now)),
+            (Some(started), None) => format!(", started {}", timefmt::relative(started, now)),
+            (None, None) => String::new(),
+        };
+        if task.status == TaskStatus::Running {
+            if let Some(at) = agent_events::last_event_at(pool, task.id, task.attempt).await? {
+                age.push_str(&format!(", last activity {}", timefmt::relative(at, now)));
+            }
+        }
+        if let (Some(_), Some(bytes)) = (&task.worktree_path, task.disk_usage_bytes) {
+            age.push_str(&format!(
+                ", {} on disk",
+                disk::format_size(u64::try_from(bytes).unwrap_or(0))
+            ));
+        }
+        if let (Some(at), Some(commit)) = (task.merged_at, &task.merge_commit) {
+            age.push_str(&format!(
+                ", merged {} as {}",
+                timefmt::relative(at, now),
+                crate::merge_cmd::short_commit(commit)
+            ));
+        }
+        let question = if task.status == TaskStatus::BlockedOnHuman {
+            dispatch::latest_question(pool, task.id, task.attempt).await?
+        } else {
+            None
+        };
+        if plain {
+            println!(
+                "  Task {}: {}, attempt {}{}.",
+                task.name, task.status, task.attempt, age
+            );
+            if let Some(question) = &question {
+                println!("  The agent asks: {question}");
+            }
+            continue;
+        }
         let status_icon = match task.status.to_string().as_str() {
             "pending" => ".",
             "assigned" => ">",
             "running" => "*",
+            "blocked_on_human" => "Q",
             "checking" => "?",
             "passed" => "+",
             "failed" => "!",
@@ -71,28 +284,230 @@ async fn run_plan_status(pool: &SqlitePool, plan_id_str: &str) -> Result<()> {
             _ => " ",
         };
         println!(
-            "  [{}] {} (attempt {}, {})",
-            status_icon, task.name, task.attempt, task.status
+            "  [{}] {} (attempt {}, {}{})",
+            status_icon, task.name, task.attempt, task.status, age
         );
+        if let Some(question) = &question {
+            println!("      asks: {question}");
+            println!("      answer with: gator answer {} \"...\"", task.id);
+        }
+    }
+    let hidden = tasks.len() - shown.len();
+    if hidden > 0 {
+        println!("  ... {hidden} more tasks (use --limit 0 to list all)");
+    }
+
+    let notes = note_db::list_notes_for_plan(pool, plan_id).await?;
+    if !notes.is_empty() {
+        println!();
+        println!("Notes:");
+        for line in crate::note_cmd::note_lines(&notes, &tasks) {
+            println!("  {line}");
+        }
+    }
+
+    if why {
+        println!();
+        let explained = explain_pending(pool, &plan, &progress).await?;
+        if explained.is_empty() {
+            println!("No pending tasks.");
+        } else {
+            println!("Why pending tasks are not running:");
+        }
+        for (pending, reasons) in &explained {
+            if plain {
+                println!("  Task {}: {}.", pending.task.name, reasons.join("; "));
+                continue;
+            }
+            println!("  {}", pending.task.name);
+            for reason in reasons {
+                println!("    - {reason}");
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Pending tasks of `plan`, by name, each with the reasons it is not
+/// running yet.
+async fn explain_pending(
+    pool: &SqlitePool,
+    plan: &Plan,
+    progress: &PlanProgress,
+) -> Result<Vec<(PendingTask, Vec<String>)>> {
+    let registry = dispatch_cmd::harness_registry(PromptScaffold::default());
+    let mut harnesses = registry.list();
+    harnesses.sort_unstable();
+    let throttle = active_throttle(pool, plan).await?;
+    let mut pending = task_db::explain_pending_tasks(pool, plan.id).await?;
+    pending.sort_by(|a, b| a.task.name.cmp(&b.task.name));
+    Ok(pending
+        .into_iter()
+        .map(|p| {
+            let reasons = pending_reasons(
+                &p,
+                plan,
+                progress.assigned + progress.running + progress.blocked_on_human,
+                throttle.as_ref(),
+                &harnesses,
+            );
+            (p, reasons)
+        })
+        .collect())
+}
+
+/// Why a pending task is not running yet. `busy` is the number of the
+/// plan's tasks holding an agent; `throttle` is set while dispatch is held
+/// back after rate limits; `harnesses` are those dispatch registers.
+fn pending_reasons(
+    pending: &PendingTask,
+    plan: &Plan,
+    busy: i64,
+    throttle: Option<&PlanThrottle>,
+    harnesses: &[&str],
+) -> Vec<String> {
+    let mut reasons: Vec<String> = pending
+        .unmet_dependencies
+        .iter()
+        .map(|dep| match dep.status {
+            TaskStatus::Escalated => {
+                format!(
+                    "dependency {} is escalated; it must be retried and pass first",
+                    dep.name
+                )
+            }
+            TaskStatus::Cancelled => {
+                format!(
+                    "dependency {} was cancelled; this task cannot run",
+                    dep.name
+                )
+            }
+            status => format!("waiting on {} ({status})", dep.name),
+        })
+        .collect();
+
+    if pending.is_ready() {
+        reasons.push(match plan.status {
+            PlanStatus::Draft => "ready, but the plan is a draft (approve it first)".to_string(),
+            PlanStatus::Approved if plan.interrupted_at.is_some() => {
+                "ready, but the plan was interrupted (resume with gator dispatch)".to_string()
+            }
+            PlanStatus::Approved => "ready, but the plan is not dispatched yet".to_string(),
+            PlanStatus::Running => match throttle {
+                Some(throttle) => format!(
+                    "ready, but dispatch is throttled after rate limits ({})",
+                    describe_throttle(throttle, Utc::now())
+                ),
+                None if busy > 0 => {
+                    format!(
+                        "ready, waiting for a free agent slot ({busy} task(s) assigned, running, or blocked on a human)"
+                    )
+                }
+                None => {
+                    "ready; starts on the orchestrator's next pass (is gator dispatch running?)"
+                        .to_string()
+                }
+            },
+            PlanStatus::Completed | PlanStatus::Failed => format!(
+                "ready, but the plan is {} (reset or resume it first)",
+                describe_plan_status(plan)
+            ),
+        });
+    }
+
+    let harness = pending
+        .task
+        .requested_harness
+        .as_deref()
+        .unwrap_or(&plan.default_harness);
+    if !harnesses.contains(&harness) {
+        reasons.push(match harnesses.first() {
+            Some(fallback) => {
+                format!("harness {harness:?} is not available; dispatch falls back to {fallback:?}")
+            }
+            None => format!("harness {harness:?} is not available"),
+        });
+    }
+    reasons
+}
+
+/// A warning when the task worktrees of `project_path`, taking `usage`
+/// bytes, exceed `cleanup.worktree_quota`.
+fn quota_warning(project_path: &str, usage: u64) -> Result<Option<String>> {
+    let Some(quota) = config::resolve_worktree_quota()? else {
+        return Ok(None);
+    };
+    Ok((usage > quota).then(|| {
+        format!(
+            "Warning: worktrees of {project_path} take {}, over the {} quota \
+             (free space with `gator cleanup --max-age <age>`)",
+            disk::format_size(usage),
+            disk::format_size(quota)
+        )
+    }))
+}
+
+/// Warn about every project of `plans` whose task worktrees exceed the
+/// quota.
+async fn warn_over_quota(pool: &SqlitePool, plans: &[Plan]) -> Result<()> {
+    let mut projects: Vec<&str> = plans.iter().map(|p| p.project_path.as_str()).collect();
+    projects.sort_unstable();
+    projects.dedup();
+    for project in projects {
+        let usage = task_db::worktree_disk_usage(pool, project).await?;
+        if let Some(warning) = quota_warning(project, usage)? {
+            eprintln!("{warning}");
+        }
+    }
+    Ok(())
+}
+
 /// List all plans with a progress summary.
-async fn run_fleet_status(pool: &SqlitePool) -> Result<()> {
+async fn run_fleet_status(pool: &SqlitePool, output: OutputFormat) -> Result<()> {
     let plans = plan_db::list_plans(pool).await?;
 
+    if output == OutputFormat::Json {
+        let mut body = Vec::with_capacity(plans.len());
+        for plan in plans {
+            let progress = task_db::get_plan_progress(pool, plan.id).await?;
+            body.push(Versioned::new(v1::PlanSummary {
+                plan: plan.into(),
+                progress: progress.into(),
+            }));
+        }
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
     if plans.is_empty() {
         println!("No plans found.");
         return Ok(());
     }
 
+    let now = Utc::now();
+    if output::is_plain() {
+        let noun = if plans.len() == 1 { "plan" } else { "plans" };
+        println!("{} {noun}.", plans.len());
+        for plan in &plans {
+            let progress = task_db::get_plan_progress(pool, plan.id).await?;
+            println!(
+                "Plan {}: {}, {}, created {}. ID {}.",
+                plan.name,
+                describe_plan_status(plan),
+                output::progress_sentence(&progress),
+                timefmt::relative(plan.created_at, now),
+                plan.id
+            );
+        }
+        return warn_over_quota(pool, &plans).await;
+    }
+
     println!(
-        "{:<38} {:<30} {:<12} {:>10}",
-        "ID", "NAME", "STATUS", "PROGRESS"
+        "{:<38} {:<30} {:<12} {:>10}  {:<10}",
+        "ID", "NAME", "STATUS", "PROGRESS", "CREATED"
     );
-    println!("{}", "-".repeat(92));
+    println!("{}", "-".repeat(104));
 
     for plan in &plans {
         let progress = task_db::get_plan_progress(pool, plan.id).await?;
@@ -107,10 +522,14 @@ async fn run_fleet_status(pool: &SqlitePool) -> Result<()> {
             plan.name.clone()
         };
         println!(
-            "{:<38} {:<30} {:<12} {:>10}",
-            plan.id, name_display, plan.status, progress_str
+            "{:<38} {:<30} {:<12} {:>10}  {:<10}",
+            plan.id,
+            name_display,
+            plan.status,
+            progress_str,
+            timefmt::relative(plan.created_at, now)
         );
     }
 
-    Ok(())
+    warn_over_quota(pool, &plans).await
 }
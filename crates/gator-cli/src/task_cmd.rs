@@ -0,0 +1,51 @@
+//! `gator task` operator commands: act on a single task of a running plan.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_core::orchestrator::cancel_task;
+use gator_db::models::TaskStatus;
+
+use crate::TaskCommands;
+
+/// Run a task command.
+pub async fn run_task(pool: &SqlitePool, command: TaskCommands) -> Result<()> {
+    match command {
+        TaskCommands::Cancel { task_id } => run_cancel(pool, &task_id).await,
+    }
+}
+
+/// Cancel a task and print what was stopped.
+async fn run_cancel(pool: &SqlitePool, task_id_str: &str) -> Result<()> {
+    let task_id =
+        Uuid::parse_str(task_id_str).with_context(|| format!("invalid task ID: {task_id_str}"))?;
+    let report = cancel_task(pool, task_id).await?;
+
+    println!("Cancelled task {} (was {}).", report.task, report.previous);
+    if matches!(
+        report.previous,
+        TaskStatus::Assigned | TaskStatus::Running | TaskStatus::BlockedOnHuman
+    ) {
+        println!("A running dispatch kills its agent and frees the slot within a second.");
+    }
+    if !report.dependents.is_empty() {
+        println!(
+            "Cancelled {} pending task(s) that depend on it:",
+            report.dependents.len()
+        );
+        for name in &report.dependents {
+            println!("  {name}");
+        }
+    }
+    if let Some(pid) = report.killed_process {
+        println!("Killed agent process {pid}.");
+    }
+    if let Some(container_id) = &report.removed_container {
+        println!("Removed agent container {container_id}.");
+    }
+    for error in &report.errors {
+        eprintln!("  warning: {error}");
+    }
+    Ok(())
+}
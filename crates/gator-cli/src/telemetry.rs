@@ -0,0 +1,115 @@
+//! Logging setup and opt-in OpenTelemetry trace export.
+//!
+//! Logs always go to stderr through `tracing_subscriber::fmt`, filtered by
+//! `RUST_LOG` (default `info`). When an OTLP endpoint is configured
+//! (`OTEL_EXPORTER_OTLP_ENDPOINT` env > `telemetry.otlp_endpoint` in the
+//! config file), spans from `gator_core` -- one trace per task lifecycle --
+//! are also exported over OTLP/gRPC, e.g. to Jaeger or Tempo.
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor;
+use tracing::Level;
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt};
+
+use crate::config::TelemetrySection;
+
+/// Service name reported when `telemetry.service_name` is unset.
+pub const DEFAULT_SERVICE_NAME: &str = "gator";
+
+/// Flushes and shuts down the exporter when dropped.
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Warning: failed to flush traces: {e}");
+            }
+        }
+    }
+}
+
+/// Resolve the OTLP endpoint: `OTEL_EXPORTER_OTLP_ENDPOINT` env > config file.
+pub fn otlp_endpoint(section: &TelemetrySection) -> Option<String> {
+    std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(|| section.otlp_endpoint.clone())
+}
+
+/// Install the global tracing subscriber, adding the OTLP layer when an
+/// endpoint is configured. Must be called from within the tokio runtime.
+pub fn init(section: &TelemetrySection) -> Result<TelemetryGuard> {
+    let fmt_layer = fmt::layer()
+        .with_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")));
+
+    let Some(endpoint) = otlp_endpoint(section) else {
+        tracing_subscriber::registry().with(fmt_layer).init();
+        return Ok(TelemetryGuard { provider: None });
+    };
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(&endpoint)
+        .build()
+        .with_context(|| format!("failed to build OTLP exporter for {endpoint}"))?;
+    let service_name = section
+        .service_name
+        .clone()
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+    let provider = SdkTracerProvider::builder()
+        .with_span_processor(
+            BatchSpanProcessor::builder(exporter, opentelemetry_sdk::runtime::Tokio).build(),
+        )
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+
+    // Only export gator's own spans; sqlx, hyper, and tonic stay local.
+    let otel_layer = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer("gator"))
+        .with_filter(Targets::new().with_target("gator_core", Level::INFO));
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    Ok(TelemetryGuard {
+        provider: Some(provider),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_endpoint_overrides_config() {
+        let _lock = crate::test_util::lock_env();
+        let section = TelemetrySection {
+            otlp_endpoint: Some("http://collector:4317".to_string()),
+            service_name: None,
+        };
+
+        unsafe { std::env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT") };
+        assert_eq!(
+            otlp_endpoint(&section).as_deref(),
+            Some("http://collector:4317")
+        );
+        assert_eq!(otlp_endpoint(&TelemetrySection::default()), None);
+
+        unsafe { std::env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", "http://env:4317") };
+        let from_env = otlp_endpoint(&section);
+        unsafe { std::env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT") };
+        assert_eq!(from_env.as_deref(), Some("http://env:4317"));
+    }
+}
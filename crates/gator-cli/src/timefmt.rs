@@ -0,0 +1,171 @@
+//! Timestamp display: renders stored UTC timestamps in the operator's
+//! timezone, plus short relative durations ("12m ago").
+//!
+//! The display zone is process-wide and set once at startup from
+//! `GATOR_TIMEZONE` > `display.timezone` in the config file > the system's
+//! local zone. Machine-readable output (JSON, CSV) bypasses this module and
+//! stays in ISO-8601 UTC.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+
+/// Where timestamps are rendered for human-readable output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayZone {
+    /// The system's local timezone.
+    #[default]
+    Local,
+    /// Coordinated Universal Time.
+    Utc,
+    /// A named IANA timezone, e.g. `Europe/Berlin`.
+    Named(Tz),
+}
+
+impl FromStr for DisplayZone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "" | "local" | "Local" => Ok(Self::Local),
+            "utc" | "UTC" => Ok(Self::Utc),
+            other => match other.parse::<Tz>() {
+                Ok(tz) => Ok(Self::Named(tz)),
+                Err(_) => bail!(
+                    "unknown timezone {other:?}; expected \"local\", \"UTC\", or an IANA name like \"America/New_York\""
+                ),
+            },
+        }
+    }
+}
+
+static DISPLAY_ZONE: OnceLock<DisplayZone> = OnceLock::new();
+
+/// Set the process-wide display zone. Later calls are ignored.
+pub fn set_display_zone(zone: DisplayZone) {
+    let _ = DISPLAY_ZONE.set(zone);
+}
+
+/// The process-wide display zone (local time if never set).
+pub fn display_zone() -> DisplayZone {
+    DISPLAY_ZONE.get().copied().unwrap_or_default()
+}
+
+/// Format `ts` with a strftime layout in the given zone.
+pub fn format_in(zone: DisplayZone, ts: DateTime<Utc>, layout: &str) -> String {
+    match zone {
+        DisplayZone::Local => ts.with_timezone(&Local).format(layout).to_string(),
+        DisplayZone::Utc => ts.format(layout).to_string(),
+        DisplayZone::Named(tz) => ts.with_timezone(&tz).format(layout).to_string(),
+    }
+}
+
+/// Format `ts` with a strftime layout in the display zone.
+pub fn format(ts: DateTime<Utc>, layout: &str) -> String {
+    format_in(display_zone(), ts, layout)
+}
+
+/// Full timestamp with a zone suffix, e.g. `2026-03-01 09:15:00 CET`.
+///
+/// Named zones use their abbreviation; the system local zone has none
+/// available, so it shows the UTC offset instead.
+pub fn timestamp_in(zone: DisplayZone, ts: DateTime<Utc>) -> String {
+    match zone {
+        DisplayZone::Local => format_in(zone, ts, "%Y-%m-%d %H:%M:%S %:z"),
+        DisplayZone::Utc => format_in(zone, ts, "%Y-%m-%d %H:%M:%S UTC"),
+        DisplayZone::Named(_) => format_in(zone, ts, "%Y-%m-%d %H:%M:%S %Z"),
+    }
+}
+
+/// Full timestamp with a zone suffix in the display zone.
+pub fn timestamp(ts: DateTime<Utc>) -> String {
+    timestamp_in(display_zone(), ts)
+}
+
+/// Full timestamp followed by the relative age, e.g.
+/// `2026-03-01 09:15:00 UTC (12m ago)`.
+pub fn timestamp_with_age(ts: DateTime<Utc>) -> String {
+    format!("{} ({})", timestamp(ts), relative(ts, Utc::now()))
+}
+
+/// Describe `ts` relative to `now` using the largest whole unit:
+/// `just now`, `45s ago`, `12m ago`, `3h ago`, `2d ago`, or `in 5m` for
+/// timestamps in the future.
+pub fn relative(ts: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - ts).num_seconds();
+    if secs.abs() < 5 {
+        return "just now".to_string();
+    }
+    let span = short_duration(secs.unsigned_abs());
+    if secs > 0 {
+        format!("{span} ago")
+    } else {
+        format!("in {span}")
+    }
+}
+
+/// Render a number of seconds as a single coarse unit (`45s`, `12m`, `3h`, `2d`).
+fn short_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, h, m, s).unwrap()
+    }
+
+    #[test]
+    fn parses_zone_names() {
+        assert_eq!("local".parse::<DisplayZone>().unwrap(), DisplayZone::Local);
+        assert_eq!("UTC".parse::<DisplayZone>().unwrap(), DisplayZone::Utc);
+        assert_eq!(
+            "Europe/Berlin".parse::<DisplayZone>().unwrap(),
+            DisplayZone::Named(chrono_tz::Europe::Berlin)
+        );
+        assert!("Mars/Olympus_Mons".parse::<DisplayZone>().is_err());
+    }
+
+    #[test]
+    fn timestamp_converts_to_named_zone() {
+        let ts = at(12, 0, 0);
+        assert_eq!(
+            timestamp_in(DisplayZone::Utc, ts),
+            "2026-01-15 12:00:00 UTC"
+        );
+        assert_eq!(
+            timestamp_in(DisplayZone::Named(chrono_tz::Europe::Berlin), ts),
+            "2026-01-15 13:00:00 CET"
+        );
+        assert_eq!(
+            format_in(
+                DisplayZone::Named(chrono_tz::America::New_York),
+                ts,
+                "%H:%M"
+            ),
+            "07:00"
+        );
+    }
+
+    #[test]
+    fn relative_picks_largest_unit() {
+        let now = at(12, 0, 0);
+        assert_eq!(relative(at(12, 0, 0), now), "just now");
+        assert_eq!(relative(at(11, 59, 15), now), "45s ago");
+        assert_eq!(relative(at(11, 48, 0), now), "12m ago");
+        assert_eq!(relative(at(9, 0, 0), now), "3h ago");
+        assert_eq!(relative(now - chrono::Duration::days(2), now), "2d ago");
+        assert_eq!(relative(at(12, 5, 0), now), "in 5m");
+    }
+}
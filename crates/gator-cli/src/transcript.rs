@@ -0,0 +1,482 @@
+//! Task transcripts for `gator log --export`: the agent event stream and
+//! gate outcomes of each attempt, rendered as Markdown or HTML to share in
+//! code review or a postmortem.
+//!
+//! A transcript is first built as a list of [`Block`]s, which each format
+//! then renders. Events were redacted when they were recorded, so nothing
+//! here needs to be.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{AgentEvent, Task};
+use gator_db::queries::agent_events;
+use gator_db::queries::gate_results::{self as gate_db, GateResultWithName};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+/// Longest tool result or gate output kept whole; longer ones keep their
+/// first and last halves of this many lines.
+const MAX_OUTPUT_LINES: usize = 200;
+
+/// How a transcript is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+}
+
+impl TranscriptFormat {
+    /// HTML for `.html` and `.htm` files, Markdown for anything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                Self::Html
+            }
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// One piece of a transcript.
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading(u8, String),
+    /// Prose, such as an agent message, kept as written.
+    Text(String),
+    /// A bold label followed by plain detail, e.g. `**Error** disk full`.
+    Labeled(String, String),
+    /// Preformatted text: tool input and output, gate output.
+    Code(String),
+    Items(Vec<String>),
+}
+
+/// Write the transcript of `task_id` -- only attempt `attempt`, if given
+/// -- to `path`, in the format its extension names.
+pub async fn export_transcript(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: Option<i32>,
+    path: &Path,
+) -> Result<()> {
+    let task = task_db::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+    let plan_name = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .map_or_else(|| task.plan_id.to_string(), |p| p.name);
+    let events = agent_events::list_all_events_for_task(pool, task_id).await?;
+    let gates = gate_db::list_gate_results_with_names(pool, task_id).await?;
+
+    let blocks = transcript_blocks(&task, &plan_name, &events, &gates, attempt);
+    let title = format!("Transcript: {}", task.name);
+    let rendered = match TranscriptFormat::from_path(path) {
+        TranscriptFormat::Markdown => render_markdown(&blocks),
+        TranscriptFormat::Html => render_html(&title, &blocks),
+    };
+    std::fs::write(path, rendered)
+        .with_context(|| format!("failed to write transcript to {}", path.display()))
+}
+
+/// The whole transcript: a header describing the task, then each attempt.
+fn transcript_blocks(
+    task: &Task,
+    plan_name: &str,
+    events: &[AgentEvent],
+    gates: &[GateResultWithName],
+    only: Option<i32>,
+) -> Vec<Block> {
+    let attempts: Vec<i32> = (0..=task.attempt)
+        .filter(|a| only.is_none_or(|only| only == *a))
+        .collect();
+    let (input, output) = token_totals(events.iter().filter(|e| attempts.contains(&e.attempt)));
+
+    let mut blocks = vec![
+        Block::Heading(1, format!("Transcript: {}", task.name)),
+        Block::Items(vec![
+            format!("Task: {}", task.id),
+            format!("Plan: {plan_name}"),
+            format!("Status: {} (attempt {})", task.status, task.attempt),
+            format!("Tokens: {input} in, {output} out"),
+        ]),
+        Block::Heading(2, "Task description".to_string()),
+        Block::Text(task.description.clone()),
+    ];
+    for attempt in attempts {
+        let events: Vec<&AgentEvent> = events.iter().filter(|e| e.attempt == attempt).collect();
+        let gates: Vec<&GateResultWithName> =
+            gates.iter().filter(|g| g.attempt == attempt).collect();
+        blocks.extend(attempt_blocks(attempt, &events, &gates));
+    }
+    blocks
+}
+
+/// One attempt: its events in order, then its gate outcomes.
+fn attempt_blocks(
+    attempt: i32,
+    events: &[&AgentEvent],
+    gates: &[&GateResultWithName],
+) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(2, format!("Attempt {attempt}"))];
+    if events.is_empty() {
+        blocks.push(Block::Text("No events recorded.".to_string()));
+    } else {
+        let (input, output) = token_totals(events.iter().copied());
+        blocks.push(Block::Items(vec![
+            format!(
+                "Started: {}",
+                crate::timefmt::timestamp(events[0].recorded_at)
+            ),
+            format!("Tokens: {input} in, {output} out"),
+        ]));
+    }
+
+    for event in events {
+        let time = crate::timefmt::format(event.recorded_at, "%H:%M:%S");
+        let payload = &event.payload;
+        match event.event_type.as_str() {
+            "message" => {
+                let role = payload["role"].as_str().unwrap_or("agent");
+                blocks.push(Block::Heading(3, format!("{} ({time})", capitalize(role))));
+                blocks.push(Block::Text(
+                    payload["content"].as_str().unwrap_or_default().to_string(),
+                ));
+            }
+            "tool_call" => {
+                let tool = payload["tool"].as_str().unwrap_or("?");
+                blocks.push(Block::Labeled(format!("Tool call: {tool}"), time));
+                blocks.push(Block::Code(elide(&value_text(&payload["input"]))));
+            }
+            "tool_result" => {
+                let tool = payload["tool"].as_str().unwrap_or("?");
+                blocks.push(Block::Labeled(format!("Result from {tool}"), time));
+                blocks.push(Block::Code(elide(&value_text(&payload["output"]))));
+            }
+            // Summed per attempt above.
+            "token_usage" => {}
+            "error" => blocks.push(Block::Labeled(
+                format!("Error ({time})"),
+                payload["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            )),
+            "question" => blocks.push(Block::Labeled(
+                format!("Question for the operator ({time})"),
+                payload["question"].as_str().unwrap_or_default().to_string(),
+            )),
+            "answer" => blocks.push(Block::Labeled(
+                format!("Operator answer ({time})"),
+                payload["answer"].as_str().unwrap_or_default().to_string(),
+            )),
+            "completed" => blocks.push(Block::Labeled("Agent finished".to_string(), time)),
+            other => {
+                blocks.push(Block::Labeled(other.to_string(), time));
+                blocks.push(Block::Code(value_text(payload)));
+            }
+        }
+    }
+
+    if !gates.is_empty() {
+        blocks.push(Block::Heading(3, "Gate".to_string()));
+        blocks.push(Block::Items(gates.iter().map(|g| gate_line(g)).collect()));
+        for gate in gates.iter().filter(|g| !g.passed) {
+            let output = [gate.stderr.as_deref(), gate.stdout.as_deref()]
+                .into_iter()
+                .flatten()
+                .filter(|s| !s.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if !output.is_empty() {
+                blocks.push(Block::Labeled(
+                    format!("{} output", gate.invariant_name),
+                    String::new(),
+                ));
+                blocks.push(Block::Code(elide(&output)));
+            }
+        }
+    }
+    blocks
+}
+
+/// `tests: failed (exit 1, 1.2s, timed out)`.
+fn gate_line(gate: &GateResultWithName) -> String {
+    let mut details = Vec::new();
+    if let Some(code) = gate.exit_code {
+        details.push(format!("exit {code}"));
+    }
+    if let Some(ms) = gate.duration_ms {
+        details.push(format!("{:.1}s", f64::from(ms) / 1000.0));
+    }
+    if gate.timed_out {
+        details.push("timed out".to_string());
+    }
+    if let Some(from) = gate.cached_from {
+        details.push(format!("cached from attempt {from}"));
+    }
+    let verdict = if gate.passed { "passed" } else { "failed" };
+    if details.is_empty() {
+        format!("{}: {verdict}", gate.invariant_name)
+    } else {
+        format!(
+            "{}: {verdict} ({})",
+            gate.invariant_name,
+            details.join(", ")
+        )
+    }
+}
+
+/// Input and output tokens of the `token_usage` events among `events`.
+fn token_totals<'a>(events: impl Iterator<Item = &'a AgentEvent>) -> (u64, u64) {
+    events
+        .filter(|e| e.event_type == "token_usage")
+        .fold((0, 0), |(input, output), e| {
+            (
+                input + e.payload["input_tokens"].as_u64().unwrap_or(0),
+                output + e.payload["output_tokens"].as_u64().unwrap_or(0),
+            )
+        })
+}
+
+/// A string as itself; anything else as pretty-printed JSON.
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+/// `text`, or its first and last [`MAX_OUTPUT_LINES`]/2 lines when longer.
+fn elide(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= MAX_OUTPUT_LINES {
+        return text.trim_end().to_string();
+    }
+    let keep = MAX_OUTPUT_LINES / 2;
+    format!(
+        "{}\n[... {} lines elided ...]\n{}",
+        lines[..keep].join("\n"),
+        lines.len() - 2 * keep,
+        lines[lines.len() - keep..].join("\n")
+    )
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "{} {text}\n", "#".repeat(usize::from(*level)));
+            }
+            Block::Text(text) => {
+                let _ = writeln!(out, "{}\n", text.trim_end());
+            }
+            Block::Labeled(label, detail) if detail.is_empty() => {
+                let _ = writeln!(out, "**{label}**\n");
+            }
+            Block::Labeled(label, detail) => {
+                let _ = writeln!(out, "**{label}** {detail}\n");
+            }
+            Block::Code(text) => {
+                // A fence longer than any backtick run inside the text.
+                let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                let _ = writeln!(out, "{fence}\n{text}\n{fence}\n");
+            }
+            Block::Items(items) => {
+                for item in items {
+                    let _ = writeln!(out, "- {item}");
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; line-height: 1.4; }}\n\
+         pre {{ background: #f4f4f4; padding: 0.6em; overflow-x: auto; white-space: pre-wrap; }}\n\
+         .text {{ white-space: pre-wrap; }}\n\
+         </style></head><body>\n",
+        escape_html(title)
+    );
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "<h{level}>{}</h{level}>", escape_html(text));
+            }
+            Block::Text(text) => {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"text\">{}</p>",
+                    escape_html(text.trim_end())
+                );
+            }
+            Block::Labeled(label, detail) => {
+                let _ = writeln!(
+                    out,
+                    "<p><strong>{}</strong> {}</p>",
+                    escape_html(label),
+                    escape_html(detail)
+                );
+            }
+            Block::Code(text) => {
+                let _ = writeln!(out, "<pre>{}</pre>", escape_html(text));
+            }
+            Block::Items(items) => {
+                out.push_str("<ul>\n");
+                for item in items {
+                    let _ = writeln!(out, "<li>{}</li>", escape_html(item));
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, payload: Value) -> AgentEvent {
+        AgentEvent {
+            id: 0,
+            task_id: Uuid::nil(),
+            attempt: 0,
+            event_type: event_type.to_string(),
+            payload,
+            recorded_at: chrono::Utc::now(),
+            run_id: None,
+            attempt_id: None,
+        }
+    }
+
+    #[test]
+    fn format_follows_the_file_extension() {
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("out/t.HTML")),
+            TranscriptFormat::Html
+        );
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("t.htm")),
+            TranscriptFormat::Html
+        );
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("t.md")),
+            TranscriptFormat::Markdown
+        );
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("transcript")),
+            TranscriptFormat::Markdown
+        );
+    }
+
+    #[test]
+    fn attempts_render_events_tokens_and_gate_failures() {
+        let events = [
+            event(
+                "message",
+                serde_json::json!({"role": "assistant", "content": "Fixing the parser."}),
+            ),
+            event(
+                "tool_call",
+                serde_json::json!({"tool": "Bash", "input": {"command": "cargo test"}}),
+            ),
+            event(
+                "tool_result",
+                serde_json::json!({"tool": "Bash", "output": "ok ```fenced```"}),
+            ),
+            event(
+                "token_usage",
+                serde_json::json!({"input_tokens": 120, "output_tokens": 30}),
+            ),
+            event("completed", serde_json::json!({})),
+        ];
+        let gate = GateResultWithName {
+            id: Uuid::nil(),
+            task_id: Uuid::nil(),
+            invariant_id: Uuid::nil(),
+            attempt: 0,
+            passed: false,
+            exit_code: Some(101),
+            stdout: Some("test parser::tokens ... FAILED".to_string()),
+            stderr: None,
+            duration_ms: Some(1500),
+            checked_at: chrono::Utc::now(),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            cached_from: None,
+            invariant_name: "tests".to_string(),
+        };
+        let events: Vec<&AgentEvent> = events.iter().collect();
+        let blocks = attempt_blocks(0, &events, &[&gate]);
+
+        let md = render_markdown(&blocks);
+        assert!(md.starts_with("## Attempt 0\n"));
+        assert!(md.contains("- Tokens: 120 in, 30 out\n"));
+        assert!(md.contains("Fixing the parser.\n"));
+        assert!(md.contains("**Tool call: Bash**"));
+        assert!(md.contains("\"command\": \"cargo test\""));
+        // The fence outgrows the backticks in the output.
+        assert!(md.contains("````\nok ```fenced```\n````"));
+        assert!(md.contains("- tests: failed (exit 101, 1.5s)\n"));
+        assert!(md.contains("test parser::tokens ... FAILED"));
+
+        let html = render_html("Transcript: <parser>", &blocks);
+        assert!(html.contains("<title>Transcript: &lt;parser&gt;</title>"));
+        assert!(html.contains("<h2>Attempt 0</h2>"));
+        assert!(html.contains("&quot;command&quot;: &quot;cargo test&quot;"));
+        assert!(html.contains("<li>tests: failed (exit 101, 1.5s)</li>"));
+    }
+
+    #[test]
+    fn long_output_keeps_its_head_and_tail() {
+        let text: String = (0..500).map(|i| format!("line {i}\n")).collect();
+        let elided = elide(&text);
+        assert!(elided.starts_with("line 0\n"));
+        assert!(elided.contains("[... 300 lines elided ...]"));
+        assert!(elided.ends_with("line 499"));
+        assert_eq!(elide("short\n"), "short");
+    }
+}
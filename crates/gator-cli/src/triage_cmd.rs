@@ -0,0 +1,367 @@
+//! `gator triage` commands: the issue-triage daemon.
+//!
+//! On every pass the daemon resumes triage plans an earlier pass left
+//! unfinished and posts finished triages back to their issues, then turns
+//! open issues carrying the queue label that it has not seen before into a
+//! new triage plan (one artifact task per issue) and runs it. Each issue is
+//! triaged once; `triage_items` remembers which.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+
+use gator_core::artifact;
+use gator_core::forge::{GithubForge, split_issue_ref};
+use gator_core::harness::PromptConfig;
+use gator_core::isolation;
+use gator_core::notify::NotificationConfig;
+use gator_core::plan::{
+    TriageOptions, create_plan_from_toml, parse_plan_toml, plan_from_triage_issues,
+    read_triage_outcome,
+};
+use gator_core::presets;
+use gator_core::token::TokenConfig;
+use gator_db::models::{TaskStatus, TriageItem};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+use gator_db::queries::triage_items;
+
+use crate::TriageCommands;
+use crate::daemon::{self, Fleet};
+
+/// Settings for `gator triage run`.
+struct RunOptions {
+    repo: String,
+    label: String,
+    interval: Duration,
+    once: bool,
+    limit: u32,
+    allowed_labels: Vec<String>,
+    invariants: Vec<String>,
+    base_branch: Option<String>,
+    max_agents: usize,
+    task_timeout: Duration,
+}
+
+/// Run a `gator triage` subcommand.
+pub async fn run_triage(
+    pool: &SqlitePool,
+    command: TriageCommands,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    match command {
+        TriageCommands::Run {
+            repo,
+            label,
+            interval,
+            once,
+            limit,
+            allowed_labels,
+            invariants,
+            base_branch,
+            max_agents,
+            timeout,
+        } => {
+            let options = RunOptions {
+                repo,
+                label,
+                interval: Duration::from_secs(interval),
+                once,
+                limit,
+                allowed_labels,
+                invariants,
+                base_branch,
+                max_agents,
+                task_timeout: Duration::from_secs(timeout),
+            };
+            cmd_run(pool, &options, token_config, notifications, prompt).await
+        }
+        TriageCommands::Status { repo } => cmd_status(pool, repo.as_deref()).await,
+    }
+}
+
+async fn cmd_run(
+    pool: &SqlitePool,
+    options: &RunOptions,
+    token_config: &TokenConfig,
+    notifications: &NotificationConfig,
+    prompt: &PromptConfig,
+) -> Result<()> {
+    let forge = GithubForge::new(&options.repo)?;
+    let cwd = std::env::current_dir().context("failed to get current directory")?;
+    let base_branch = options
+        .base_branch
+        .clone()
+        .unwrap_or_else(|| presets::detect_base_branch(&cwd));
+
+    let fleet = Fleet::new(
+        isolation::create_isolation("worktree", &cwd, None)?,
+        options.max_agents,
+        options.task_timeout,
+        token_config,
+        notifications,
+        prompt,
+    )?;
+
+    println!("Triaging {} (label {:?})", options.repo, options.label);
+    if !options.once {
+        println!("  Interval:    {}s", options.interval.as_secs());
+    }
+    println!("  Base branch: {base_branch}");
+    println!("  Max agents:  {}", options.max_agents);
+    if !options.allowed_labels.is_empty() {
+        println!("  Labels:      {}", options.allowed_labels.join(", "));
+    }
+
+    let cancel = daemon::shutdown_token();
+
+    let pass = TriagePass {
+        pool,
+        forge: &forge,
+        options,
+        base_branch: &base_branch,
+        project_path: &cwd.to_string_lossy(),
+        fleet: &fleet,
+    };
+
+    loop {
+        if let Err(e) = pass.run(cancel.clone()).await {
+            if options.once {
+                return Err(e);
+            }
+            eprintln!("Triage pass failed: {e:#}");
+        }
+        if options.once || !daemon::wait_for_next_pass(&cancel, options.interval).await {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Everything one pass of the daemon needs.
+struct TriagePass<'a> {
+    pool: &'a SqlitePool,
+    forge: &'a GithubForge,
+    options: &'a RunOptions,
+    base_branch: &'a str,
+    project_path: &'a str,
+    fleet: &'a Fleet,
+}
+
+impl TriagePass<'_> {
+    async fn run(&self, cancel: CancellationToken) -> Result<()> {
+        let repo = self.forge.repo();
+
+        // 1. Finish plans an earlier pass left behind (interrupted, or
+        //    failed tasks awaiting retry), then post what is done.
+        for (plan_id, plan_name) in self.unfinished_plans().await? {
+            if cancel.is_cancelled() {
+                return Ok(());
+            }
+            println!("Resuming triage plan {plan_name} ({plan_id})");
+            self.fleet
+                .dispatch(self.pool, plan_id, &plan_name, cancel.clone())
+                .await?;
+        }
+        self.post_results().await?;
+        if cancel.is_cancelled() {
+            return Ok(());
+        }
+
+        // 2. Queue issues not seen before.
+        let seen = triage_items::triaged_issue_numbers(self.pool, repo).await?;
+        let issues: Vec<_> = self
+            .forge
+            .list_issues(&self.options.label, self.options.limit)?
+            .into_iter()
+            .filter(|i| !seen.contains(&(i.number as i64)))
+            .collect();
+        if issues.is_empty() {
+            println!(
+                "[{}] No new issues labelled {:?} in {repo}.",
+                crate::timefmt::format(chrono::Utc::now(), "%H:%M:%S"),
+                self.options.label
+            );
+            return Ok(());
+        }
+
+        let repo_name = repo.rsplit('/').next().unwrap_or(repo);
+        let triage = TriageOptions {
+            plan_name: format!(
+                "{repo_name}-triage-{}",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            ),
+            base_branch: self.base_branch.to_string(),
+            repo: repo.to_string(),
+            allowed_labels: self.options.allowed_labels.clone(),
+            invariants: self.options.invariants.clone(),
+        };
+        let plan_toml = plan_from_triage_issues(&issues, &triage);
+        // Re-parse so bad --invariant names and the like fail before insert.
+        let content = toml::to_string(&plan_toml).context("failed to serialize triage plan")?;
+        parse_plan_toml(&content).context("generated triage plan failed validation")?;
+
+        let plan = create_plan_from_toml(self.pool, &plan_toml, self.project_path).await?;
+        let plan = plan_db::approve_plan(self.pool, plan.id).await?;
+        for task in task_db::list_tasks_for_plan(self.pool, plan.id).await? {
+            if let Some((_, number)) = task.issue.as_deref().and_then(split_issue_ref) {
+                triage_items::insert_triage_item(self.pool, repo, number as i64, plan.id, task.id)
+                    .await?;
+            }
+        }
+        println!(
+            "Queued {} issue(s) from {repo} as plan {} ({})",
+            issues.len(),
+            plan.name,
+            plan.id
+        );
+
+        // 3. Triage them.
+        self.fleet
+            .dispatch(self.pool, plan.id, &plan.name, cancel)
+            .await?;
+
+        // 4. Post what passed.
+        self.post_results().await
+    }
+
+    /// Plans with queued triages whose tasks the orchestrator can still
+    /// move forward (not passed, escalated, cancelled, or waiting on a human).
+    async fn unfinished_plans(&self) -> Result<Vec<(uuid::Uuid, String)>> {
+        let queued =
+            triage_items::list_triage_items(self.pool, Some(self.forge.repo()), Some("queued"))
+                .await?;
+        let mut plans: Vec<(uuid::Uuid, String)> = Vec::new();
+        for item in queued {
+            if plans.iter().any(|(id, _)| *id == item.plan_id) {
+                continue;
+            }
+            let Some(task) = task_db::get_task(self.pool, item.task_id).await? else {
+                continue;
+            };
+            if matches!(
+                task.status,
+                TaskStatus::Passed
+                    | TaskStatus::Escalated
+                    | TaskStatus::Cancelled
+                    | TaskStatus::BlockedOnHuman
+                    | TaskStatus::Checking
+            ) {
+                continue;
+            }
+            if let Some(plan) = plan_db::get_plan(self.pool, item.plan_id).await? {
+                plans.push((plan.id, plan.name));
+            }
+        }
+        Ok(plans)
+    }
+
+    /// Post every queued triage whose task has passed; mark escalated ones
+    /// failed. Tasks still in flight stay queued.
+    async fn post_results(&self) -> Result<()> {
+        let queued =
+            triage_items::list_triage_items(self.pool, Some(self.forge.repo()), Some("queued"))
+                .await?;
+        for item in queued {
+            let Some(task) = task_db::get_task(self.pool, item.task_id).await? else {
+                continue;
+            };
+            match task.status {
+                TaskStatus::Passed => match self.post_one(&item, &task).await {
+                    Ok(()) => {
+                        triage_items::mark_triage_posted(self.pool, item.id).await?;
+                        println!("Posted triage for {}#{}", item.repo, item.issue_number);
+                    }
+                    Err(e) => {
+                        let message = format!("{e:#}");
+                        eprintln!(
+                            "Failed to post triage for {}#{}: {message}",
+                            item.repo, item.issue_number
+                        );
+                        triage_items::mark_triage_failed(self.pool, item.id, &message).await?;
+                    }
+                },
+                TaskStatus::Escalated => {
+                    triage_items::mark_triage_failed(self.pool, item.id, "triage task escalated")
+                        .await?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Comment on and label one issue from its task's artifact bundle.
+    async fn post_one(&self, item: &TriageItem, task: &gator_db::models::Task) -> Result<()> {
+        let plan = plan_db::get_plan(self.pool, item.plan_id)
+            .await?
+            .with_context(|| format!("plan {} not found", item.plan_id))?;
+        let bundle = artifact::bundle_dir(
+            &artifact::bundle_root(Path::new(&plan.project_path)),
+            &plan.name,
+            &task.name,
+        );
+        let outcome = read_triage_outcome(&bundle, &self.options.allowed_labels)?;
+        if !outcome.rejected_labels.is_empty() {
+            eprintln!(
+                "Skipping labels not in the allowed list for {}#{}: {}",
+                item.repo,
+                item.issue_number,
+                outcome.rejected_labels.join(", ")
+            );
+        }
+
+        let number = item.issue_number as u64;
+        let body = format!(
+            "{}\n\n---\n_Triaged by gator (plan `{}`)._",
+            outcome.comment, plan.name
+        );
+        self.forge.comment_on_issue(number, &body)?;
+        self.forge.add_labels(number, &outcome.labels)?;
+        Ok(())
+    }
+}
+
+/// `gator triage status`: list queued, posted, and failed triages.
+async fn cmd_status(pool: &SqlitePool, repo: Option<&str>) -> Result<()> {
+    let items = triage_items::list_triage_items(pool, repo, None).await?;
+    if items.is_empty() {
+        println!("No triaged issues.");
+        return Ok(());
+    }
+
+    let plain = crate::output::is_plain();
+    if !plain {
+        println!("{:<32} {:<8} {:<18} ERROR", "ISSUE", "STATUS", "QUEUED");
+    }
+    for item in &items {
+        if plain {
+            let error = item
+                .error
+                .as_deref()
+                .map(|e| format!(", error: {e}"))
+                .unwrap_or_default();
+            println!(
+                "Issue {}#{}: {}, queued {}{error}.",
+                item.repo,
+                item.issue_number,
+                item.status,
+                crate::timefmt::format(item.created_at, "%Y-%m-%d %H:%M"),
+            );
+            continue;
+        }
+        println!(
+            "{:<32} {:<8} {:<18} {}",
+            format!("{}#{}", item.repo, item.issue_number),
+            item.status,
+            crate::timefmt::format(item.created_at, "%Y-%m-%d %H:%M"),
+            item.error.as_deref().unwrap_or("")
+        );
+    }
+    Ok(())
+}
@@ -1,14 +1,18 @@
 //! TUI application state and data model.
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::Result;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use gator_db::models::{Plan, Task};
+use gator_core::notify::{NotificationEvent, desktop};
+use gator_db::models::{GatePolicy, Note, Plan, PlanStatus, PlanThrottle, Task, TaskStatus};
 use gator_db::queries::agent_events;
 use gator_db::queries::gate_results::{self, GateResultWithName};
+use gator_db::queries::notes as note_db;
 use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 
@@ -27,6 +31,8 @@ pub enum View {
 pub struct PlanRow {
     pub plan: Plan,
     pub progress: task_db::PlanProgress,
+    /// Set while the plan's dispatch is held back after rate limits.
+    pub throttle: Option<PlanThrottle>,
 }
 
 /// Re-export from gator-db for the review queue.
@@ -34,37 +40,56 @@ pub use gator_db::queries::tasks::TaskWithPlanName;
 
 /// Application state for the TUI.
 pub struct App {
+    /// Primary pool, used for operator actions (approve/reject/retry).
     pub pool: SqlitePool,
+    /// Pool used for refresh queries; a read replica when one is configured.
+    pub read_pool: SqlitePool,
     pub current_view: View,
     pub plans: Vec<PlanRow>,
     pub selected_plan: usize,
     pub tasks: Vec<Task>,
     pub selected_task: usize,
+    /// Scroll offset of the description pane in the task detail view.
+    pub description_scroll: u16,
     pub gate_results: Vec<GateResultWithName>,
     pub events: Vec<gator_db::models::AgentEvent>,
+    /// Operator notes of the plan or task in the current detail view.
+    pub notes: Vec<Note>,
     pub review_tasks: Vec<TaskWithPlanName>,
     pub selected_review: usize,
     pub tick_rate: Duration,
     pub should_quit: bool,
     pub status_message: Option<String>,
+    /// Rejection reason being typed; `Some` while the reject prompt is open.
+    pub reject_input: Option<String>,
+    /// Pop desktop notifications when tasks in running plans change state.
+    pub desktop_notify: bool,
+    /// Task statuses seen at the previous refresh (for `desktop_notify`).
+    seen_statuses: HashMap<Uuid, TaskStatus>,
 }
 
 impl App {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: SqlitePool, read_pool: SqlitePool) -> Self {
         Self {
             pool,
+            read_pool,
             current_view: View::PlanList,
             plans: Vec::new(),
             selected_plan: 0,
             tasks: Vec::new(),
             selected_task: 0,
+            description_scroll: 0,
             gate_results: Vec::new(),
             events: Vec::new(),
+            notes: Vec::new(),
             review_tasks: Vec::new(),
             selected_review: 0,
             tick_rate: Duration::from_secs(1),
             should_quit: false,
             status_message: None,
+            reject_input: None,
+            desktop_notify: false,
+            seen_statuses: HashMap::new(),
         }
     }
 
@@ -76,32 +101,64 @@ impl App {
             }
             View::PlanDetail(plan_id) => {
                 let plan_id = *plan_id;
-                self.tasks = task_db::list_tasks_for_plan(&self.pool, plan_id).await?;
+                self.tasks = task_db::list_tasks_for_plan(&self.read_pool, plan_id).await?;
                 if self.selected_task >= self.tasks.len() && !self.tasks.is_empty() {
                     self.selected_task = self.tasks.len() - 1;
                 }
+                self.notes = note_db::list_notes_for_plan(&self.read_pool, plan_id).await?;
+                if let Some(row) = self.plans.iter_mut().find(|pr| pr.plan.id == plan_id) {
+                    row.throttle =
+                        crate::status_cmd::active_throttle(&self.read_pool, &row.plan).await?;
+                }
             }
             View::TaskDetail(task_id) => {
                 let task_id = *task_id;
                 self.gate_results =
-                    gate_results::get_latest_gate_results(&self.pool, task_id).await?;
+                    gate_results::get_latest_gate_results(&self.read_pool, task_id).await?;
                 self.events =
-                    agent_events::get_recent_events_for_task(&self.pool, task_id, None, 20).await?;
+                    agent_events::get_recent_events_for_task(&self.read_pool, task_id, None, 20)
+                        .await?;
+                self.notes = note_db::list_notes_for_task(&self.read_pool, task_id).await?;
             }
             View::ReviewQueue => {
                 self.refresh_review_queue().await?;
             }
             View::Help => {}
         }
+        if self.desktop_notify {
+            self.watch_task_transitions().await?;
+        }
+        Ok(())
+    }
+
+    /// Pop a desktop notification for each task in a running plan that
+    /// passed, failed, escalated, or started waiting on review since the
+    /// last refresh.
+    async fn watch_task_transitions(&mut self) -> Result<()> {
+        let plans = plan_db::list_plans(&self.read_pool).await?;
+        for plan in plans.iter().filter(|p| p.status == PlanStatus::Running) {
+            let tasks = task_db::list_tasks_for_plan(&self.read_pool, plan.id).await?;
+            for (event, task_name) in task_transitions(&mut self.seen_statuses, &tasks) {
+                let body = format!("{task_name} ({})", plan.name);
+                tokio::spawn(async move {
+                    let _ = desktop::show(desktop::desktop_title(event), &body).await;
+                });
+            }
+        }
         Ok(())
     }
 
     async fn refresh_plans(&mut self) -> Result<()> {
-        let plans = plan_db::list_plans(&self.pool).await?;
+        let plans = plan_db::list_plans(&self.read_pool).await?;
         let mut plan_rows = Vec::with_capacity(plans.len());
         for plan in plans {
-            let progress = task_db::get_plan_progress(&self.pool, plan.id).await?;
-            plan_rows.push(PlanRow { plan, progress });
+            let progress = task_db::get_plan_progress(&self.read_pool, plan.id).await?;
+            let throttle = crate::status_cmd::active_throttle(&self.read_pool, &plan).await?;
+            plan_rows.push(PlanRow {
+                plan,
+                progress,
+                throttle,
+            });
         }
         self.plans = plan_rows;
         if self.selected_plan >= self.plans.len() && !self.plans.is_empty() {
@@ -111,7 +168,7 @@ impl App {
     }
 
     async fn refresh_review_queue(&mut self) -> Result<()> {
-        self.review_tasks = task_db::list_checking_tasks(&self.pool).await?;
+        self.review_tasks = task_db::list_checking_tasks(&self.read_pool).await?;
         if self.selected_review >= self.review_tasks.len() && !self.review_tasks.is_empty() {
             self.selected_review = self.review_tasks.len() - 1;
         }
@@ -148,6 +205,7 @@ impl App {
             View::PlanDetail(_) => {
                 if let Some(task) = self.tasks.get(self.selected_task) {
                     self.current_view = View::TaskDetail(task.id);
+                    self.description_scroll = 0;
                 }
             }
             _ => {}
@@ -156,20 +214,17 @@ impl App {
 
     pub fn move_up(&mut self) {
         match &self.current_view {
-            View::PlanList => {
-                if self.selected_plan > 0 {
-                    self.selected_plan -= 1;
-                }
+            View::PlanList if self.selected_plan > 0 => {
+                self.selected_plan -= 1;
             }
-            View::PlanDetail(_) => {
-                if self.selected_task > 0 {
-                    self.selected_task -= 1;
-                }
+            View::PlanDetail(_) if self.selected_task > 0 => {
+                self.selected_task -= 1;
             }
-            View::ReviewQueue => {
-                if self.selected_review > 0 {
-                    self.selected_review -= 1;
-                }
+            View::ReviewQueue if self.selected_review > 0 => {
+                self.selected_review -= 1;
+            }
+            View::TaskDetail(_) => {
+                self.description_scroll = self.description_scroll.saturating_sub(1);
             }
             _ => {}
         }
@@ -177,22 +232,17 @@ impl App {
 
     pub fn move_down(&mut self) {
         match &self.current_view {
-            View::PlanList => {
-                if !self.plans.is_empty() && self.selected_plan < self.plans.len() - 1 {
-                    self.selected_plan += 1;
-                }
+            View::PlanList if self.selected_plan + 1 < self.plans.len() => {
+                self.selected_plan += 1;
             }
-            View::PlanDetail(_) => {
-                if !self.tasks.is_empty() && self.selected_task < self.tasks.len() - 1 {
-                    self.selected_task += 1;
-                }
+            View::PlanDetail(_) if self.selected_task + 1 < self.tasks.len() => {
+                self.selected_task += 1;
             }
-            View::ReviewQueue => {
-                if !self.review_tasks.is_empty()
-                    && self.selected_review < self.review_tasks.len() - 1
-                {
-                    self.selected_review += 1;
-                }
+            View::ReviewQueue if self.selected_review + 1 < self.review_tasks.len() => {
+                self.selected_review += 1;
+            }
+            View::TaskDetail(_) => {
+                self.description_scroll = self.description_scroll.saturating_add(1);
             }
             _ => {}
         }
@@ -222,11 +272,26 @@ impl App {
         Ok(())
     }
 
+    /// Open the reject prompt if a checking task is selected.
+    pub fn begin_reject(&mut self) {
+        if self.selected_checking_task_id().is_some() {
+            self.reject_input = Some(String::new());
+        }
+    }
+
+    /// Reject the selected task with the typed reason (if any) and close
+    /// the prompt.
     pub async fn reject_selected(&mut self) -> Result<()> {
+        let reason = self.reject_input.take().unwrap_or_default();
         let task_id = self.selected_checking_task_id();
         if let Some(id) = task_id {
-            gator_core::state::dispatch::reject_task(&self.pool, id).await?;
-            self.status_message = Some("Task rejected".to_string());
+            let reason = Some(reason.as_str()).filter(|r| !r.trim().is_empty());
+            gator_core::state::dispatch::reject_task(&self.pool, id, reason).await?;
+            self.status_message = Some(if reason.is_some() {
+                "Task rejected with feedback".to_string()
+            } else {
+                "Task rejected".to_string()
+            });
             self.refresh().await?;
         }
         Ok(())
@@ -235,13 +300,37 @@ impl App {
     pub async fn retry_selected(&mut self) -> Result<()> {
         let task_id = self.selected_actionable_task_id();
         if let Some(id) = task_id {
-            gator_core::state::dispatch::operator_retry_task(&self.pool, id, false).await?;
+            gator_core::state::dispatch::operator_retry_task(
+                &self.pool,
+                id,
+                false,
+                &Default::default(),
+                None,
+            )
+            .await?;
             self.status_message = Some("Task queued for retry".to_string());
             self.refresh().await?;
         }
         Ok(())
     }
 
+    /// Write a markdown snapshot to `gator-snapshot-<time>.md` in the
+    /// current directory: of the plan being viewed, else of the fleet.
+    pub async fn write_snapshot(&mut self) -> Result<()> {
+        let plan_id = match &self.current_view {
+            View::PlanDetail(plan_id) => Some(*plan_id),
+            View::TaskDetail(_) => self.tasks.first().map(|t| t.plan_id),
+            _ => None,
+        };
+        let path = PathBuf::from(format!(
+            "gator-snapshot-{}.md",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        crate::snapshot::write_snapshot(&self.read_pool, plan_id, &path).await?;
+        self.status_message = Some(format!("Snapshot written to {}", path.display()));
+        Ok(())
+    }
+
     /// Get the task ID of the currently selected checking task (if any).
     fn selected_checking_task_id(&self) -> Option<Uuid> {
         match &self.current_view {
@@ -280,6 +369,36 @@ impl App {
     }
 }
 
+/// Record each task's status in `seen` and return the transitions worth a
+/// notification, with the task name.
+///
+/// Tasks seen for the first time only seed `seen`, so opening the dashboard
+/// does not replay old results.
+pub fn task_transitions(
+    seen: &mut HashMap<Uuid, TaskStatus>,
+    tasks: &[Task],
+) -> Vec<(NotificationEvent, String)> {
+    let mut out = Vec::new();
+    for task in tasks {
+        let previous = seen.insert(task.id, task.status);
+        if previous.is_none() || previous == Some(task.status) {
+            continue;
+        }
+        let event = match task.status {
+            TaskStatus::Passed => NotificationEvent::TaskPassed,
+            TaskStatus::Failed => NotificationEvent::TaskFailed,
+            TaskStatus::Escalated => NotificationEvent::TaskEscalated,
+            TaskStatus::BlockedOnHuman => NotificationEvent::QuestionAsked,
+            TaskStatus::Checking if task.gate_policy != GatePolicy::Auto => {
+                NotificationEvent::HumanReviewRequired
+            }
+            _ => continue,
+        };
+        out.push((event, task.name.clone()));
+    }
+    out
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -287,6 +406,7 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gator_db::models::TaskKind;
 
     #[test]
     fn view_navigation() {
@@ -304,4 +424,79 @@ mod tests {
         let view = View::ReviewQueue;
         assert_ne!(view, View::PlanList);
     }
+
+    fn task(name: &str, gate: GatePolicy, status: TaskStatus) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            plan_id: Uuid::nil(),
+            name: name.to_string(),
+            description: String::new(),
+            scope_level: gator_db::models::ScopeLevel::Narrow,
+            gate_policy: gate,
+            retry_max: 3,
+            status,
+            assigned_harness: None,
+            requested_harness: None,
+            worktree_path: None,
+            attempt: 0,
+            created_at: chrono::Utc::now(),
+            started_at: None,
+            completed_at: None,
+            issue: None,
+            deleted_at: None,
+            services: sqlx::types::Json(Vec::new()),
+            kind: TaskKind::Code,
+            artifacts: sqlx::types::Json(Vec::new()),
+            paths: sqlx::types::Json(Vec::new()),
+            timeout_secs: None,
+            retry_env: Default::default(),
+            retry_env_attempt: None,
+            gate_script: None,
+            agent_pid: None,
+            container_id: None,
+            agent_host: None,
+            agent_started: None,
+            llm_review: false,
+            review_harness: None,
+            run_id: None,
+            attempt_id: None,
+            merged_at: None,
+            merge_commit: None,
+            setup_commands: Default::default(),
+            disk_usage_bytes: None,
+            disk_usage_at: None,
+        }
+    }
+
+    #[test]
+    fn task_transitions_skip_first_sighting_and_unchanged() {
+        let mut seen = HashMap::new();
+        let mut a = task("a", GatePolicy::Auto, TaskStatus::Running);
+        let mut b = task("b", GatePolicy::HumanReview, TaskStatus::Running);
+        let mut c = task("c", GatePolicy::Auto, TaskStatus::Running);
+
+        assert!(task_transitions(&mut seen, &[a.clone(), b.clone(), c.clone()]).is_empty());
+
+        a.status = TaskStatus::Passed;
+        b.status = TaskStatus::Checking;
+        c.status = TaskStatus::Checking;
+        let events = task_transitions(&mut seen, &[a.clone(), b.clone(), c.clone()]);
+        assert_eq!(
+            events,
+            vec![
+                (NotificationEvent::TaskPassed, "a".to_string()),
+                (NotificationEvent::HumanReviewRequired, "b".to_string()),
+            ],
+            "auto-gated tasks passing through checking are not announced"
+        );
+
+        // Nothing changed: nothing to announce.
+        assert!(task_transitions(&mut seen, &[a, b, c.clone()]).is_empty());
+
+        c.status = TaskStatus::Escalated;
+        assert_eq!(
+            task_transitions(&mut seen, &[c]),
+            vec![(NotificationEvent::TaskEscalated, "c".to_string())]
+        );
+    }
 }
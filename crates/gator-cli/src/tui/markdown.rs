@@ -0,0 +1,247 @@
+//! Minimal markdown rendering for task descriptions in the TUI.
+//!
+//! Covers what plan authors actually write in descriptions: ATX headings,
+//! bullet and numbered lists, blockquotes, fenced code blocks, horizontal
+//! rules, and inline `code`, **bold**, *italic*, and [links](url). Anything
+//! else renders as plain text.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Render markdown source into styled lines.
+pub fn render_markdown(source: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for raw in source.lines() {
+        let trimmed = raw.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(Line::from(Span::styled(
+                format!("  {raw}"),
+                Style::default().fg(Color::Green),
+            )));
+            continue;
+        }
+
+        if let Some((level, text)) = heading(trimmed) {
+            let mut style = Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD);
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::from(Span::styled(text.to_string(), style)));
+        } else if is_rule(trimmed) {
+            lines.push(Line::from(Span::styled(
+                "─".repeat(40),
+                Style::default().fg(Color::DarkGray),
+            )));
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            let mut spans = vec![Span::styled("│ ", Style::default().fg(Color::DarkGray))];
+            spans.extend(
+                inline(text.trim_start())
+                    .into_iter()
+                    .map(|s| s.patch_style(Style::default().add_modifier(Modifier::ITALIC))),
+            );
+            lines.push(Line::from(spans));
+        } else if let Some(text) = bullet(trimmed) {
+            let indent = " ".repeat(raw.len() - trimmed.len());
+            let mut spans = vec![Span::raw(format!("{indent}• "))];
+            spans.extend(inline(text));
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(inline(raw)));
+        }
+    }
+
+    lines
+}
+
+/// Match an ATX heading (`# Title` .. `###### Title`).
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        return Some((level, ""));
+    }
+    rest.strip_prefix(' ')
+        .map(|text| (level, text.trim_end_matches('#').trim()))
+}
+
+/// Match a horizontal rule: three or more `-`, `*`, or `_` (spaces allowed).
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3 && matches!(chars[0], '-' | '*' | '_') && chars.iter().all(|&c| c == chars[0])
+}
+
+/// Match an unordered list item (`- `, `* `, `+ `).
+fn bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+}
+
+/// Render inline markup within a single line.
+fn inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    let flush = |plain: &mut String, spans: &mut Vec<Span<'static>>| {
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(plain)));
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| {
+                (
+                    Span::styled(inner.to_string(), Style::default().fg(Color::Cyan)),
+                    len,
+                )
+            }),
+            '*' if rest.starts_with("**") => delimited(rest, "**").map(|(inner, len)| {
+                (
+                    Span::styled(
+                        inner.to_string(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    len,
+                )
+            }),
+            '*' => delimited(rest, "*").map(|(inner, len)| {
+                (
+                    Span::styled(
+                        inner.to_string(),
+                        Style::default().add_modifier(Modifier::ITALIC),
+                    ),
+                    len,
+                )
+            }),
+            '[' => link(rest).map(|(label, url, len)| {
+                let shown = if label == url {
+                    label.to_string()
+                } else {
+                    format!("{label} ({url})")
+                };
+                (
+                    Span::styled(
+                        shown,
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::UNDERLINED),
+                    ),
+                    len,
+                )
+            }),
+            _ => None,
+        };
+
+        match styled {
+            Some((span, len)) => {
+                flush(&mut plain, &mut spans);
+                spans.push(span);
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    flush(&mut plain, &mut spans);
+    spans
+}
+
+/// If `text` starts with `delim`, return the non-empty content up to the
+/// closing `delim` and the total length consumed.
+fn delimited<'a>(text: &'a str, delim: &str) -> Option<(&'a str, usize)> {
+    let body = text.strip_prefix(delim)?;
+    let end = body.find(delim)?;
+    if end == 0 {
+        return None;
+    }
+    Some((&body[..end], delim.len() * 2 + end))
+}
+
+/// Parse `[label](url)` at the start of `text`.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let close = text.find("](")?;
+    let label = &text[1..close];
+    let after = &text[close + 2..];
+    let end = after.find(')')?;
+    Some((label, &after[..end], close + 2 + end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn renders_block_elements() {
+        let lines = render_markdown(
+            "# Title\n\nSome text\n- one\n  * two\n> quoted\n---\n```\nlet x = **1**;\n```",
+        );
+        let texts: Vec<String> = lines.iter().map(text_of).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Title",
+                "",
+                "Some text",
+                "• one",
+                "  • two",
+                "│ quoted",
+                "─".repeat(40).as_str(),
+                "  let x = **1**;",
+            ]
+        );
+        assert!(
+            lines[0].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn renders_inline_markup() {
+        let spans = inline("run `cargo test` **now**, see [docs](https://x.dev) *soon*");
+        let contents: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "run ",
+                "cargo test",
+                " ",
+                "now",
+                ", see ",
+                "docs (https://x.dev)",
+                " ",
+                "soon",
+            ]
+        );
+        assert_eq!(spans[1].style.fg, Some(Color::Cyan));
+        assert!(spans[3].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn unmatched_delimiters_stay_literal() {
+        let spans = inline("2 * 3 = 6 and `open");
+        let text: String = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "2 * 3 = 6 and `open");
+    }
+}
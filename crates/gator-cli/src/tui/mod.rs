@@ -1,6 +1,8 @@
 //! Interactive TUI dashboard for monitoring and managing gator plans.
 
 pub mod app;
+mod markdown;
+pub mod plain;
 mod ui;
 
 use std::io;
@@ -18,7 +20,16 @@ use sqlx::SqlitePool;
 use app::App;
 
 /// Launch the interactive TUI dashboard.
-pub async fn run_dashboard(pool: SqlitePool) -> Result<()> {
+///
+/// `read_pool` serves the periodic refresh queries; `pool` is only used for
+/// operator actions. Pass the same pool twice when no replica is configured.
+/// With `desktop_notify`, task state changes in running plans pop desktop
+/// notifications.
+pub async fn run_dashboard(
+    pool: SqlitePool,
+    read_pool: SqlitePool,
+    desktop_notify: bool,
+) -> Result<()> {
     // Install panic hook to restore terminal on crash.
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
@@ -34,7 +45,8 @@ pub async fn run_dashboard(pool: SqlitePool) -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(pool);
+    let mut app = App::new(pool, read_pool);
+    app.desktop_notify = desktop_notify;
 
     // Initial data load.
     app.refresh().await?;
@@ -65,6 +77,24 @@ async fn run_event_loop(
                 // Clear status message on any keypress.
                 app.status_message = None;
 
+                // While the reject prompt is open, keys edit the reason.
+                if let Some(input) = app.reject_input.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app.reject_input = None,
+                        KeyCode::Enter => {
+                            if let Err(e) = app.reject_selected().await {
+                                app.status_message = Some(format!("Reject failed: {e}"));
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+
                 match key.code {
                     KeyCode::Char('q') | KeyCode::Esc => {
                         app.navigate_back();
@@ -92,15 +122,18 @@ async fn run_event_loop(
                         }
                     }
                     KeyCode::Char('r') => {
-                        if let Err(e) = app.reject_selected().await {
-                            app.status_message = Some(format!("Reject failed: {e}"));
-                        }
+                        app.begin_reject();
                     }
                     KeyCode::Char('R') => {
                         if let Err(e) = app.retry_selected().await {
                             app.status_message = Some(format!("Retry failed: {e}"));
                         }
                     }
+                    KeyCode::Char('s') => {
+                        if let Err(e) = app.write_snapshot().await {
+                            app.status_message = Some(format!("Snapshot failed: {e}"));
+                        }
+                    }
                     KeyCode::Char('?') => {
                         app.show_help();
                     }
@@ -0,0 +1,369 @@
+//! Plain-text dashboard (`gator dashboard --no-tui`) for screen readers.
+//!
+//! Instead of redrawing a full-screen table, it prints a short summary of
+//! running plans and the review queue once, then on each interval prints
+//! only what changed, one sentence per line. Silent intervals print nothing.
+//!
+//! With `--federate`, it reads several databases at once and shows each
+//! plan as `<database>/<plan>`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_core::notify::desktop;
+use gator_db::models::{PlanStatus, TaskStatus};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks::{self as task_db, PlanProgress};
+
+use super::app::task_transitions;
+use crate::output::progress_sentence;
+use crate::timefmt;
+
+/// One plan as seen by a refresh.
+#[derive(Debug, Clone)]
+struct PlanState {
+    id: Uuid,
+    name: String,
+    status: PlanStatus,
+    /// Present only for running plans.
+    progress: Option<PlanProgress>,
+}
+
+/// One task of a running plan as seen by a refresh.
+#[derive(Debug, Clone)]
+struct TaskState {
+    id: Uuid,
+    name: String,
+    plan_name: String,
+    status: TaskStatus,
+}
+
+/// Everything the plain dashboard reports on.
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    plans: Vec<PlanState>,
+    tasks: Vec<TaskState>,
+    /// Tasks awaiting review, as `task (plan)`.
+    review: Vec<String>,
+    /// Labels of federated databases that could not be read.
+    unavailable: Vec<String>,
+}
+
+/// A database the dashboard reads.
+pub struct Source<'a> {
+    /// Shown before each of the database's plans; `None` for the only
+    /// database of an ordinary dashboard.
+    pub label: Option<&'a str>,
+    /// `None` for a federated database that could not be opened.
+    pub pool: Option<&'a SqlitePool>,
+}
+
+/// Print plain-text dashboard updates about `sources` every `interval`
+/// until Ctrl-C.
+///
+/// With `desktop_notify`, task state changes also pop desktop
+/// notifications, as in the TUI.
+pub async fn run_plain_dashboard(
+    sources: &[Source<'_>],
+    interval: Duration,
+    desktop_notify: bool,
+) -> Result<()> {
+    println!(
+        "Dashboard started; updates every {} seconds. Press Ctrl-C to stop.",
+        interval.as_secs()
+    );
+
+    let mut ticker = tokio::time::interval(interval);
+    let mut seen_statuses = HashMap::new();
+    let mut previous: Option<Snapshot> = None;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("Dashboard stopped.");
+                return Ok(());
+            }
+        }
+
+        let (snapshot, tasks) = load_snapshot(sources).await?;
+        let lines = match &previous {
+            None => summary_lines(&snapshot),
+            Some(prev) => change_lines(prev, &snapshot),
+        };
+        if !lines.is_empty() {
+            println!();
+            println!("Update at {}.", timefmt::format(Utc::now(), "%H:%M:%S"));
+            for line in lines {
+                println!("{line}");
+            }
+        }
+
+        if desktop_notify {
+            for (event, task_name) in task_transitions(&mut seen_statuses, &tasks) {
+                tokio::spawn(async move {
+                    let _ = desktop::show(desktop::desktop_title(event), &task_name).await;
+                });
+            }
+        }
+
+        previous = Some(snapshot);
+    }
+}
+
+/// Load the current snapshot, plus the raw tasks of running plans for
+/// notification tracking. A labelled (federated) source that cannot be
+/// read is reported as unavailable; an unlabelled one fails the load.
+async fn load_snapshot(sources: &[Source<'_>]) -> Result<(Snapshot, Vec<gator_db::models::Task>)> {
+    let mut snapshot = Snapshot::default();
+    let mut all_tasks = Vec::new();
+
+    for source in sources {
+        let mut part = Snapshot::default();
+        let loaded = match source.pool {
+            Some(pool) => load_source(pool, source.label, &mut part, &mut all_tasks).await,
+            None => Err(anyhow::anyhow!("not open")),
+        };
+        match (loaded, source.label) {
+            (Ok(()), _) => {
+                snapshot.plans.extend(part.plans);
+                snapshot.tasks.extend(part.tasks);
+                snapshot.review.extend(part.review);
+            }
+            (Err(_), Some(label)) => snapshot.unavailable.push(label.to_string()),
+            (Err(e), None) => return Err(e),
+        }
+    }
+
+    Ok((snapshot, all_tasks))
+}
+
+/// Add the plans, running tasks, and review queue of one database to
+/// `snapshot`, naming plans `<label>/<plan>` when labelled.
+async fn load_source(
+    pool: &SqlitePool,
+    label: Option<&str>,
+    snapshot: &mut Snapshot,
+    all_tasks: &mut Vec<gator_db::models::Task>,
+) -> Result<()> {
+    let display = |plan_name: &str| match label {
+        Some(label) => format!("{label}/{plan_name}"),
+        None => plan_name.to_string(),
+    };
+
+    for plan in plan_db::list_plans(pool).await? {
+        let name = display(&plan.name);
+        let mut progress = None;
+        if plan.status == PlanStatus::Running {
+            progress = Some(task_db::get_plan_progress(pool, plan.id).await?);
+            let tasks = task_db::list_tasks_for_plan(pool, plan.id).await?;
+            snapshot.tasks.extend(tasks.iter().map(|t| TaskState {
+                id: t.id,
+                name: t.name.clone(),
+                plan_name: name.clone(),
+                status: t.status,
+            }));
+            all_tasks.extend(tasks);
+        }
+        snapshot.plans.push(PlanState {
+            id: plan.id,
+            name,
+            status: plan.status,
+            progress,
+        });
+    }
+
+    snapshot.review.extend(
+        task_db::list_checking_tasks(pool)
+            .await?
+            .into_iter()
+            .map(|t| format!("{} ({})", t.name, display(&t.plan_name))),
+    );
+    Ok(())
+}
+
+/// The opening summary: running plans with progress, then the review queue.
+fn summary_lines(snapshot: &Snapshot) -> Vec<String> {
+    let running: Vec<&PlanState> = snapshot
+        .plans
+        .iter()
+        .filter(|p| p.progress.is_some())
+        .collect();
+    let mut lines = Vec::new();
+    match running.len() {
+        0 => lines.push("No plans are running.".to_string()),
+        1 => lines.push("1 plan is running.".to_string()),
+        n => lines.push(format!("{n} plans are running.")),
+    }
+    lines.extend(running.iter().filter_map(|p| progress_line(p)));
+    lines.push(review_line(&snapshot.review));
+    if !snapshot.unavailable.is_empty() {
+        lines.push(format!(
+            "Unavailable databases: {}.",
+            snapshot.unavailable.join(", ")
+        ));
+    }
+    lines
+}
+
+/// Sentences describing what changed between two snapshots.
+fn change_lines(prev: &Snapshot, cur: &Snapshot) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let prev_plans: HashMap<Uuid, &PlanState> = prev.plans.iter().map(|p| (p.id, p)).collect();
+    for plan in &cur.plans {
+        match prev_plans.get(&plan.id) {
+            None => lines.push(format!("New plan {} is {}.", plan.name, plan.status)),
+            Some(old) if old.status != plan.status => {
+                lines.push(format!("Plan {} is now {}.", plan.name, plan.status));
+            }
+            _ => {}
+        }
+    }
+
+    let prev_tasks: HashMap<Uuid, TaskStatus> =
+        prev.tasks.iter().map(|t| (t.id, t.status)).collect();
+    for task in &cur.tasks {
+        if prev_tasks
+            .get(&task.id)
+            .is_some_and(|&old| old != task.status)
+        {
+            lines.push(format!(
+                "Task {} in plan {} is now {}.",
+                task.name, task.plan_name, task.status
+            ));
+        }
+    }
+
+    for plan in &cur.plans {
+        let old_line = prev_plans.get(&plan.id).and_then(|p| progress_line(p));
+        let new_line = progress_line(plan);
+        if new_line.is_some() && new_line != old_line {
+            lines.extend(new_line);
+        }
+    }
+
+    if prev.review != cur.review {
+        lines.push(review_line(&cur.review));
+    }
+
+    for label in &cur.unavailable {
+        if !prev.unavailable.contains(label) {
+            lines.push(format!("Database {label} is unavailable."));
+        }
+    }
+    for label in &prev.unavailable {
+        if !cur.unavailable.contains(label) {
+            lines.push(format!("Database {label} is available again."));
+        }
+    }
+
+    lines
+}
+
+/// `Plan <name>: <progress>.` for a running plan.
+fn progress_line(plan: &PlanState) -> Option<String> {
+    plan.progress
+        .as_ref()
+        .map(|p| format!("Plan {}: {}.", plan.name, progress_sentence(p)))
+}
+
+fn review_line(review: &[String]) -> String {
+    match review.len() {
+        0 => "No tasks await review.".to_string(),
+        1 => format!("1 task awaits review: {}.", review[0]),
+        n => format!("{n} tasks await review: {}.", review.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(id: u128, status: PlanStatus, passed: i64) -> PlanState {
+        PlanState {
+            id: Uuid::from_u128(id),
+            name: format!("plan-{id}"),
+            status,
+            progress: (status == PlanStatus::Running).then(|| PlanProgress {
+                passed,
+                pending: 2 - passed,
+                total: 2,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn task(id: u128, status: TaskStatus) -> TaskState {
+        TaskState {
+            id: Uuid::from_u128(id),
+            name: format!("task-{id}"),
+            plan_name: "plan-1".to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn summary_describes_running_plans_and_review_queue() {
+        let snapshot = Snapshot {
+            plans: vec![
+                plan(1, PlanStatus::Running, 1),
+                plan(2, PlanStatus::Completed, 0),
+            ],
+            tasks: vec![],
+            review: vec!["lint (plan-1)".to_string()],
+            unavailable: vec![],
+        };
+        assert_eq!(
+            summary_lines(&snapshot),
+            vec![
+                "1 plan is running.",
+                "Plan plan-1: 1 of 2 tasks passed; 1 pending.",
+                "1 task awaits review: lint (plan-1).",
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_report_only_what_moved() {
+        let prev = Snapshot {
+            plans: vec![plan(1, PlanStatus::Running, 0)],
+            tasks: vec![task(10, TaskStatus::Running), task(11, TaskStatus::Pending)],
+            review: vec![],
+            unavailable: vec![],
+        };
+        assert!(change_lines(&prev, &prev).is_empty());
+
+        let cur = Snapshot {
+            plans: vec![
+                plan(1, PlanStatus::Running, 1),
+                plan(2, PlanStatus::Draft, 0),
+            ],
+            tasks: vec![task(10, TaskStatus::Passed), task(11, TaskStatus::Pending)],
+            review: vec![],
+            unavailable: vec!["search".to_string()],
+        };
+        assert_eq!(
+            change_lines(&prev, &cur),
+            vec![
+                "New plan plan-2 is draft.",
+                "Task task-10 in plan plan-1 is now passed.",
+                "Plan plan-1: 1 of 2 tasks passed; 1 pending.",
+                "Database search is unavailable.",
+            ]
+        );
+        let recovered = Snapshot {
+            unavailable: vec![],
+            ..cur.clone()
+        };
+        assert_eq!(
+            change_lines(&cur, &recovered),
+            vec!["Database search is available again."]
+        );
+    }
+}
@@ -4,11 +4,12 @@ use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap};
 
 use gator_db::models::{PlanStatus, TaskStatus};
 
 use super::app::{App, View};
+use super::markdown::render_markdown;
 
 /// Render the current view.
 pub fn render(f: &mut Frame, app: &App) {
@@ -37,6 +38,7 @@ fn render_plan_list(f: &mut Frame, app: &App, area: Rect) {
         .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
     let header = Row::new(header_cells).height(1);
 
+    let now = chrono::Utc::now();
     let rows = app.plans.iter().enumerate().map(|(i, pr)| {
         let prog = &pr.progress;
         let progress_str = format!("{}/{}", prog.passed, prog.total);
@@ -45,7 +47,7 @@ fn render_plan_list(f: &mut Frame, app: &App, area: Rect) {
             .token_budget
             .map(|b| format!("{b}"))
             .unwrap_or_else(|| "-".to_string());
-        let created = pr.plan.created_at.format("%Y-%m-%d %H:%M").to_string();
+        let created = crate::timefmt::relative(pr.plan.created_at, now);
 
         let style = if i == app.selected_plan {
             Style::default()
@@ -57,7 +59,10 @@ fn render_plan_list(f: &mut Frame, app: &App, area: Rect) {
 
         Row::new(vec![
             Cell::from(pr.plan.name.clone()),
-            Cell::from(status_colored(&pr.plan.status)),
+            Cell::from(match &pr.throttle {
+                Some(_) => Span::styled("throttled", Style::default().fg(Color::Yellow)),
+                None => status_colored(&pr.plan.status),
+            }),
             Cell::from(progress_str),
             Cell::from(format!("{}", prog.total)),
             Cell::from(budget_str),
@@ -74,7 +79,7 @@ fn render_plan_list(f: &mut Frame, app: &App, area: Rect) {
             Constraint::Length(10),
             Constraint::Length(6),
             Constraint::Length(10),
-            Constraint::Length(18),
+            Constraint::Length(10),
         ],
     )
     .header(header)
@@ -93,15 +98,25 @@ fn render_plan_detail(f: &mut Frame, app: &App, plan_id: uuid::Uuid, area: Rect)
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(5)])
+        .constraints([Constraint::Length(3), Constraint::Min(5), notes_height(app)])
         .split(area);
 
     // Plan header.
     let plan_info = app.plans.iter().find(|pr| pr.plan.id == plan_id);
     let header_text = if let Some(pr) = plan_info {
         let prog = &pr.progress;
+        let throttle = pr
+            .throttle
+            .as_ref()
+            .map(|t| {
+                format!(
+                    " | Throttled: {}",
+                    crate::status_cmd::describe_throttle(t, chrono::Utc::now())
+                )
+            })
+            .unwrap_or_default();
         format!(
-            " {} | {} | {}/{} passed | Budget: {}",
+            " {} | {} | {}/{} passed | Budget: {}{}",
             plan_name,
             pr.plan.status,
             prog.passed,
@@ -110,6 +125,7 @@ fn render_plan_detail(f: &mut Frame, app: &App, plan_id: uuid::Uuid, area: Rect)
                 .token_budget
                 .map(|b| b.to_string())
                 .unwrap_or_else(|| "unlimited".to_string()),
+            throttle,
         )
     } else {
         format!(" {plan_name}")
@@ -120,11 +136,14 @@ fn render_plan_detail(f: &mut Frame, app: &App, plan_id: uuid::Uuid, area: Rect)
     f.render_widget(header, chunks[0]);
 
     // Task table.
-    let task_header_cells = ["Name", "Status", "Attempt", "Scope", "Gate", "Harness"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+    let task_header_cells = [
+        "Name", "Status", "Attempt", "Scope", "Gate", "Harness", "Updated",
+    ]
+    .iter()
+    .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
     let task_header = Row::new(task_header_cells).height(1);
 
+    let now = chrono::Utc::now();
     let task_rows = app.tasks.iter().enumerate().map(|(i, task)| {
         let style = if i == app.selected_task {
             Style::default()
@@ -139,12 +158,18 @@ fn render_plan_detail(f: &mut Frame, app: &App, plan_id: uuid::Uuid, area: Rect)
             Cell::from(task_status_colored(&task.status)),
             Cell::from(format!("{}/{}", task.attempt, task.retry_max)),
             Cell::from(task.scope_level.to_string()),
-            Cell::from(task.gate_policy.to_string()),
+            Cell::from(task.gate()),
             Cell::from(
                 task.assigned_harness
                     .clone()
                     .unwrap_or_else(|| "-".to_string()),
             ),
+            Cell::from(
+                task.completed_at
+                    .or(task.started_at)
+                    .map(|ts| crate::timefmt::relative(ts, now))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
         ])
         .style(style)
     });
@@ -158,12 +183,14 @@ fn render_plan_detail(f: &mut Frame, app: &App, plan_id: uuid::Uuid, area: Rect)
             Constraint::Length(8),
             Constraint::Length(14),
             Constraint::Length(14),
+            Constraint::Length(10),
         ],
     )
     .header(task_header)
     .block(Block::default().borders(Borders::ALL).title(" Tasks "));
 
     f.render_widget(task_table, chunks[1]);
+    render_notes(f, app, chunks[2]);
 }
 
 fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect) {
@@ -172,9 +199,11 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(5), // task info
-            Constraint::Min(5),    // gate results
-            Constraint::Min(5),    // events
+            Constraint::Length(5),      // task info
+            Constraint::Percentage(40), // description
+            Constraint::Min(5),         // gate results
+            Constraint::Min(5),         // events
+            notes_height(app),          // operator notes
         ])
         .split(area);
 
@@ -206,6 +235,20 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
     );
     f.render_widget(info, chunks[0]);
 
+    // Description (markdown, scrollable with j/k).
+    let description = task
+        .map(|t| render_markdown(&t.description))
+        .unwrap_or_default();
+    let description = Paragraph::new(description)
+        .wrap(Wrap { trim: false })
+        .scroll((app.description_scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Description (j/k to scroll) "),
+        );
+    f.render_widget(description, chunks[1]);
+
     // Gate results.
     let gate_header_cells = ["Invariant", "Passed", "Exit", "Duration"]
         .iter()
@@ -222,11 +265,13 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
         Row::new(vec![
             Cell::from(gr.invariant_name.clone()),
             Cell::from(if gr.passed { "PASS" } else { "FAIL" }).style(pass_style),
-            Cell::from(
+            Cell::from(if gr.timed_out {
+                "timeout".to_string()
+            } else {
                 gr.exit_code
                     .map(|c| c.to_string())
-                    .unwrap_or_else(|| "-".to_string()),
-            ),
+                    .unwrap_or_else(|| "-".to_string())
+            }),
             Cell::from(
                 gr.duration_ms
                     .map(|d| format!("{d}ms"))
@@ -250,7 +295,7 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
             .borders(Borders::ALL)
             .title(" Gate Results "),
     );
-    f.render_widget(gate_table, chunks[1]);
+    f.render_widget(gate_table, chunks[2]);
 
     // Recent events.
     let event_lines: Vec<Line> = app
@@ -258,7 +303,7 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
         .iter()
         .take(10)
         .map(|ev| {
-            let time = ev.recorded_at.format("%H:%M:%S").to_string();
+            let time = crate::timefmt::format(ev.recorded_at, "%H:%M:%S");
             Line::from(vec![
                 Span::styled(format!("[{time}] "), Style::default().fg(Color::DarkGray)),
                 Span::styled(
@@ -275,7 +320,33 @@ fn render_task_detail(f: &mut Frame, app: &App, task_id: uuid::Uuid, area: Rect)
             .borders(Borders::ALL)
             .title(" Recent Events "),
     );
-    f.render_widget(events, chunks[2]);
+    f.render_widget(events, chunks[3]);
+    render_notes(f, app, chunks[4]);
+}
+
+/// Most note lines shown before the oldest are cut off.
+const MAX_NOTE_LINES: usize = 6;
+
+/// Height of the notes pane: hidden when there are no notes.
+fn notes_height(app: &App) -> Constraint {
+    if app.notes.is_empty() {
+        return Constraint::Length(0);
+    }
+    let lines = crate::note_cmd::note_lines(&app.notes, &app.tasks).len();
+    Constraint::Length(lines.min(MAX_NOTE_LINES) as u16 + 2)
+}
+
+/// Operator notes, newest at the bottom.
+fn render_notes(f: &mut Frame, app: &App, area: Rect) {
+    if app.notes.is_empty() {
+        return;
+    }
+    let lines = crate::note_cmd::note_lines(&app.notes, &app.tasks);
+    let skip = lines.len().saturating_sub(MAX_NOTE_LINES);
+    let lines: Vec<Line> = lines.into_iter().skip(skip).map(Line::from).collect();
+    let notes =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Notes "));
+    f.render_widget(notes, area);
 }
 
 fn render_review_queue(f: &mut Frame, app: &App, area: Rect) {
@@ -334,6 +405,7 @@ fn render_help(f: &mut Frame, area: Rect) {
         )]),
         Line::from("    j/Down    Move down"),
         Line::from("    k/Up      Move up"),
+        Line::from("              (scrolls the description in Task Detail)"),
         Line::from("    Enter     Drill into selected"),
         Line::from("    Esc/q     Back / Quit"),
         Line::from("    Tab       Toggle Plans / Review Queue"),
@@ -346,6 +418,7 @@ fn render_help(f: &mut Frame, area: Rect) {
         )]),
         Line::from("    a         Approve selected task (if checking)"),
         Line::from("    r         Reject selected task (if checking)"),
+        Line::from("              (type a reason for the agent, Enter to reject)"),
         Line::from("    R         Retry selected task (if failed/escalated)"),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -354,6 +427,8 @@ fn render_help(f: &mut Frame, area: Rect) {
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )]),
+        Line::from("    s         Write a markdown snapshot of the plan (or fleet)"),
+        Line::from("              to gator-snapshot-<time>.md"),
         Line::from("    ?         Show this help"),
         Line::from(""),
     ];
@@ -363,6 +438,21 @@ fn render_help(f: &mut Frame, area: Rect) {
 }
 
 fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(input) = &app.reject_input {
+        let prompt = Line::from(vec![
+            Span::styled(" Reject ", Style::default().bg(Color::Red).fg(Color::White)),
+            Span::raw(" Reason: "),
+            Span::raw(input.as_str()),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            Span::styled(
+                "  Enter:reject  Esc:cancel",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        f.render_widget(Paragraph::new(prompt), area);
+        return;
+    }
+
     let view_name = match &app.current_view {
         View::PlanList => "Plans",
         View::PlanDetail(_) => "Plan Detail",
@@ -422,10 +512,12 @@ fn task_status_colored(status: &TaskStatus) -> Span<'static> {
         TaskStatus::Pending => ("pending", Color::DarkGray),
         TaskStatus::Assigned => ("assigned", Color::Cyan),
         TaskStatus::Running => ("running", Color::Blue),
+        TaskStatus::BlockedOnHuman => ("blocked_on_human", Color::LightYellow),
         TaskStatus::Checking => ("checking", Color::Yellow),
         TaskStatus::Passed => ("passed", Color::Green),
         TaskStatus::Failed => ("failed", Color::Red),
         TaskStatus::Escalated => ("escalated", Color::Magenta),
+        TaskStatus::Cancelled => ("cancelled", Color::Gray),
     };
     Span::styled(text.to_string(), Style::default().fg(color))
 }
@@ -39,6 +39,12 @@ async fn insert_test_invariant(pool: &SqlitePool, name: &str) -> gator_db::model
         threshold: None,
         scope: gator_db::models::InvariantScope::Project,
         timeout_secs: 300,
+        diff_base: false,
+        lower_is_better: false,
+        env: &Default::default(),
+        workdir: None,
+        run_after: &[],
+        artifacts: &[],
     };
     invariants::insert_invariant(pool, &new)
         .await
@@ -0,0 +1,453 @@
+//! Artifact tasks: gates on produced files and artifact bundles.
+//!
+//! A task with `kind = "artifact"` (docs, research notes, analysis) lists
+//! the paths it must produce in `artifacts`. Its gate runs built-in checks
+//! on those paths in addition to any linked invariants:
+//!
+//! - `exists`: the file is non-empty, or the directory contains a file.
+//! - `markdown`: Markdown files have balanced code fences, a space after
+//!   heading markers, and no empty link targets.
+//! - `links`: relative links in Markdown files point at existing paths.
+//!   External URLs are left to a link-checker invariant such as `lychee`.
+//!
+//! When an artifact task passes, its artifacts are copied into a bundle
+//! directory with a `manifest.json` instead of being committed for merge.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use gator_db::models::Task;
+use gator_db::queries::plans as plan_db;
+
+use crate::worktree::WorktreeManager;
+
+/// Name of the manifest file written into each bundle.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The result of one built-in check on one artifact path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactCheckResult {
+    /// Artifact path as declared (or the Markdown file inside a declared
+    /// directory).
+    pub path: String,
+    /// `exists`, `markdown`, `links`, `review`, or `scope`.
+    pub check: &'static str,
+    pub passed: bool,
+    /// What is wrong, one problem per line. Empty when passed.
+    pub message: String,
+}
+
+/// Root directory for artifact bundles of the repository at `repo_path`:
+/// `<parent>/<repo>-gator-artifacts`, next to the worktree directory.
+pub fn bundle_root(repo_path: &Path) -> PathBuf {
+    let repo_name = repo_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("repo");
+    let base_name = format!("{repo_name}-gator-artifacts");
+    repo_path
+        .parent()
+        .map(|p| p.join(&base_name))
+        .unwrap_or_else(|| PathBuf::from(base_name))
+}
+
+/// Bundle directory for one task: `<root>/<plan>/<task>`, sanitized like
+/// its branch name.
+pub fn bundle_dir(root: &Path, plan_name: &str, task_name: &str) -> PathBuf {
+    let branch = WorktreeManager::branch_name(plan_name, task_name);
+    root.join(branch.trim_start_matches("gator/"))
+}
+
+/// Run the built-in checks on every declared artifact in `worktree`.
+pub fn check_artifacts(worktree: &Path, artifacts: &[String]) -> Vec<ArtifactCheckResult> {
+    let mut results = Vec::new();
+    for artifact in artifacts {
+        let full = worktree.join(artifact);
+        let exists = check_exists(&full);
+        let present = exists.is_ok();
+        results.push(result(artifact.clone(), "exists", exists.err()));
+        if !present {
+            continue;
+        }
+
+        for md in markdown_files(&full) {
+            let display = if md == full {
+                artifact.clone()
+            } else {
+                md.strip_prefix(worktree)
+                    .unwrap_or(&md)
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            let content = match std::fs::read_to_string(&md) {
+                Ok(c) => c,
+                Err(e) => {
+                    results.push(result(
+                        display,
+                        "markdown",
+                        Some(format!("unreadable: {e}")),
+                    ));
+                    continue;
+                }
+            };
+            let lint = lint_markdown(&content);
+            results.push(result(
+                display.clone(),
+                "markdown",
+                (!lint.is_empty()).then(|| lint.join("\n")),
+            ));
+            let base = md.parent().unwrap_or(worktree);
+            let broken = broken_relative_links(&content, base);
+            results.push(result(
+                display,
+                "links",
+                (!broken.is_empty()).then(|| broken.join("\n")),
+            ));
+        }
+    }
+    results
+}
+
+fn result(path: String, check: &'static str, problem: Option<String>) -> ArtifactCheckResult {
+    ArtifactCheckResult {
+        path,
+        check,
+        passed: problem.is_none(),
+        message: problem.unwrap_or_default(),
+    }
+}
+
+fn check_exists(path: &Path) -> Result<(), String> {
+    if path.is_file() {
+        let len = path.metadata().map(|m| m.len()).unwrap_or(0);
+        if len == 0 {
+            return Err("file is empty".to_string());
+        }
+        Ok(())
+    } else if path.is_dir() {
+        if walk_files(path).is_empty() {
+            return Err("directory contains no files".to_string());
+        }
+        Ok(())
+    } else {
+        Err("not found".to_string())
+    }
+}
+
+fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "markdown")
+    )
+}
+
+fn markdown_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return if is_markdown(path) {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+    }
+    walk_files(path)
+        .into_iter()
+        .filter(|p| is_markdown(p))
+        .collect()
+}
+
+/// All files under `dir`, sorted, skipping `.git`.
+fn walk_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(d) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&d) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Structural Markdown problems, as `line N: ...` messages.
+fn lint_markdown(content: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut open_fence: Option<(usize, &str)> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let n = i + 1;
+        let trimmed = line.trim_start();
+        let fence = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f));
+        if let Some(marker) = fence {
+            match open_fence {
+                Some((_, open)) if open == marker => open_fence = None,
+                Some(_) => {}
+                None => open_fence = Some((n, marker)),
+            }
+            continue;
+        }
+        if open_fence.is_some() {
+            continue;
+        }
+
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) {
+            let rest = &line[hashes..];
+            if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+                problems.push(format!("line {n}: missing space after heading marker"));
+            }
+        }
+        if line.contains("]()") {
+            problems.push(format!("line {n}: empty link target"));
+        }
+    }
+
+    if let Some((n, _)) = open_fence {
+        problems.push(format!("line {n}: code fence is never closed"));
+    }
+    problems
+}
+
+/// Targets of inline links and images `[text](target)` outside code fences.
+fn link_targets(content: &str) -> Vec<(usize, String)> {
+    let mut targets = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(pos) = rest.find("](") {
+            let after = &rest[pos + 2..];
+            let Some(end) = after.find(')') else {
+                break;
+            };
+            // Drop an optional title: [x](path "title").
+            let target = after[..end].split_whitespace().next().unwrap_or("");
+            if !target.is_empty() {
+                targets.push((i + 1, target.to_string()));
+            }
+            rest = &after[end..];
+        }
+    }
+    targets
+}
+
+/// Relative link targets (resolved against `base`) that do not exist.
+fn broken_relative_links(content: &str, base: &Path) -> Vec<String> {
+    link_targets(content)
+        .into_iter()
+        .filter_map(|(line, target)| {
+            if target.starts_with('#') || target.contains("://") || target.starts_with("mailto:") {
+                return None;
+            }
+            let path_part = target.split(['#', '?']).next().unwrap_or("");
+            if path_part.is_empty() {
+                return None;
+            }
+            let resolved = base.join(path_part.trim_start_matches('/'));
+            (!resolved.exists()).then(|| format!("line {line}: broken link {target}"))
+        })
+        .collect()
+}
+
+/// One file in a bundle manifest.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleEntry {
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// `manifest.json` of an artifact bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleManifest {
+    pub plan: String,
+    pub task: String,
+    pub attempt: u32,
+    pub files: Vec<BundleEntry>,
+}
+
+/// Copy the declared artifacts from `worktree` into `dest` (replacing any
+/// previous bundle) and write `manifest.json` with each file's SHA-256.
+pub fn write_bundle(
+    worktree: &Path,
+    artifacts: &[String],
+    dest: &Path,
+    plan_name: &str,
+    task_name: &str,
+    attempt: u32,
+) -> Result<BundleManifest> {
+    if dest.exists() {
+        std::fs::remove_dir_all(dest)
+            .with_context(|| format!("failed to clear old bundle {}", dest.display()))?;
+    }
+    std::fs::create_dir_all(dest)
+        .with_context(|| format!("failed to create bundle directory {}", dest.display()))?;
+
+    let mut files = Vec::new();
+    for artifact in artifacts {
+        let full = worktree.join(artifact);
+        let sources = if full.is_dir() {
+            walk_files(&full)
+        } else {
+            vec![full]
+        };
+        for src in sources {
+            let rel = src.strip_prefix(worktree).unwrap_or(&src);
+            if rel
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+            {
+                continue;
+            }
+            let target = dest.join(rel);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let data = std::fs::read(&src)
+                .with_context(|| format!("failed to read artifact {}", src.display()))?;
+            std::fs::write(&target, &data)
+                .with_context(|| format!("failed to write {}", target.display()))?;
+            files.push(BundleEntry {
+                path: rel.to_string_lossy().into_owned(),
+                bytes: data.len() as u64,
+                sha256: hex::encode(Sha256::digest(&data)),
+            });
+        }
+    }
+
+    let manifest = BundleManifest {
+        plan: plan_name.to_string(),
+        task: task_name.to_string(),
+        attempt,
+        files,
+    };
+    let json = serde_json::to_string_pretty(&manifest)?;
+    std::fs::write(dest.join(MANIFEST_FILE), json)
+        .with_context(|| format!("failed to write manifest in {}", dest.display()))?;
+    Ok(manifest)
+}
+
+/// Bundle a passed artifact task's artifacts from `worktree` into its
+/// bundle directory next to the plan's repository. Returns the bundle path.
+pub async fn bundle_task(pool: &SqlitePool, task: &Task, worktree: &Path) -> Result<PathBuf> {
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let root = bundle_root(Path::new(&plan.project_path));
+    let dest = bundle_dir(&root, &plan.name, &task.name);
+    write_bundle(
+        worktree,
+        &task.artifacts,
+        &dest,
+        &plan.name,
+        &task.name,
+        task.attempt as u32,
+    )?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_flags_structural_problems() {
+        let md = "#Title\n\nSee [here]().\n\n```rust\nlet x = 1; // #notaheading\n";
+        assert_eq!(
+            lint_markdown(md),
+            vec![
+                "line 1: missing space after heading marker",
+                "line 3: empty link target",
+                "line 5: code fence is never closed",
+            ]
+        );
+        assert!(lint_markdown("# Title\n\n```\n#x\n```\n#### Sub\n").is_empty());
+    }
+
+    #[test]
+    fn checks_existence_and_relative_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/other.md"), "# Other\n").unwrap();
+        std::fs::write(
+            root.join("docs/guide.md"),
+            "# Guide\n\n[ok](other.md#top) [ext](https://example.com) [bad](missing.md)\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("empty.txt"), "").unwrap();
+
+        let results = check_artifacts(
+            root,
+            &[
+                "docs".to_string(),
+                "empty.txt".to_string(),
+                "nope.md".to_string(),
+            ],
+        );
+        let failed: Vec<(&str, &str, &str)> = results
+            .iter()
+            .filter(|r| !r.passed)
+            .map(|r| (r.path.as_str(), r.check, r.message.as_str()))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                ("docs/guide.md", "links", "line 3: broken link missing.md"),
+                ("empty.txt", "exists", "file is empty"),
+                ("nope.md", "exists", "not found"),
+            ]
+        );
+    }
+
+    #[test]
+    fn bundle_copies_artifacts_with_manifest() {
+        let work = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(work.path().join("report")).unwrap();
+        std::fs::write(work.path().join("report/summary.md"), "# Summary\n").unwrap();
+        std::fs::write(work.path().join("notes.txt"), "abc").unwrap();
+
+        let dest = bundle_dir(out.path(), "Plan", "write-report");
+        let manifest = write_bundle(
+            work.path(),
+            &["report".to_string(), "notes.txt".to_string()],
+            &dest,
+            "Plan",
+            "write-report",
+            1,
+        )
+        .unwrap();
+
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["report/summary.md", "notes.txt"]);
+        assert_eq!(
+            manifest.files[1].sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(dest, out.path().join("Plan/write-report"));
+        assert!(dest.join("report/summary.md").is_file());
+        assert!(dest.join(MANIFEST_FILE).is_file());
+    }
+}
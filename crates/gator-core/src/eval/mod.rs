@@ -0,0 +1,743 @@
+//! Evaluation harness: a golden-task regression suite for gator itself.
+//!
+//! A suite is a TOML file of small, deterministic cases. Each case is run
+//! as a one-task plan in a fresh git repository and a fresh database under
+//! the eval working directory, and its outcome (task status, files in the
+//! worktree) is compared with what the case expects:
+//!
+//! ```toml
+//! [suite]
+//! name = "golden"
+//! harness = "mock"        # or "claude-code" to run the real agent
+//!
+//! [[cases]]
+//! name = "create-file"
+//! description = "Create hello.txt containing the word hello."
+//! setup = { "README.md" = "# demo\n" }
+//! checks = ["grep -q hello hello.txt"]
+//!
+//! [cases.mock]
+//! files = { "hello.txt" = "hello\n" }
+//!
+//! [cases.expect]
+//! status = "passed"
+//! files = ["hello.txt"]
+//! contains = { "hello.txt" = "hello" }
+//! ```
+//!
+//! With the `mock` harness, [`ScriptedHarness`] writes each case's
+//! `[cases.mock]` files instead of running an agent, so the suite exercises
+//! scheduling, gating, and retries without spending tokens.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use gator_db::config::DbConfig;
+use gator_db::models::{InvariantKind, InvariantScope, TaskStatus};
+use gator_db::pool;
+use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::{plans as plan_db, tasks as task_db};
+
+use crate::harness::{AgentEvent, AgentHandle, Harness, HarnessRegistry, MaterializedTask};
+use crate::isolation::Isolation;
+use crate::isolation::worktree::WorktreeIsolation;
+use crate::notify::NotificationConfig;
+use crate::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, OrchestratorConfig, WatchdogConfig, run_orchestrator,
+};
+use crate::plan::{PlanMeta, PlanToml, TaskToml, create_plan_from_toml};
+use crate::token::TokenConfig;
+use crate::worktree::WorktreeManager;
+
+/// Name of the harness that replays `[cases.mock]` instead of running an
+/// agent.
+pub const MOCK_HARNESS: &str = "mock";
+
+/// Top-level structure of a suite file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalSuite {
+    pub suite: SuiteMeta,
+    pub cases: Vec<EvalCase>,
+}
+
+/// Suite-level settings in `[suite]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuiteMeta {
+    pub name: String,
+    /// Harness the cases run with: `"mock"` or a real harness name.
+    #[serde(default = "default_harness")]
+    pub harness: String,
+    /// Per-task timeout in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// One `[[cases]]` entry: a single task and what it should produce.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCase {
+    pub name: String,
+    /// The task description handed to the agent.
+    pub description: String,
+    /// Files committed to the repository before the task runs.
+    #[serde(default)]
+    pub setup: BTreeMap<String, String>,
+    /// Gate checks: shell commands run in the task's worktree.
+    #[serde(default)]
+    pub checks: Vec<String>,
+    #[serde(default)]
+    pub retry_max: i32,
+    /// What the mock harness does for this case.
+    #[serde(default)]
+    pub mock: MockScript,
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+/// Files the mock agent writes into its worktree, and the message it ends
+/// with.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockScript {
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// The outcome a case asserts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// Final task status, e.g. `"passed"` or `"escalated"`.
+    #[serde(default = "default_status")]
+    pub status: String,
+    /// Files that must exist in the task's worktree.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Files that must contain the given text.
+    #[serde(default)]
+    pub contains: BTreeMap<String, String>,
+}
+
+impl Default for Expectation {
+    fn default() -> Self {
+        Self {
+            status: default_status(),
+            files: Vec::new(),
+            contains: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_harness() -> String {
+    "claude-code".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+fn default_status() -> String {
+    "passed".to_string()
+}
+
+/// Parse and validate a suite file.
+pub fn parse_suite(content: &str) -> Result<EvalSuite> {
+    let suite: EvalSuite = toml::from_str(content).context("failed to parse eval suite")?;
+    if suite.cases.is_empty() {
+        bail!("eval suite {:?} has no cases", suite.suite.name);
+    }
+    let mut seen = HashSet::new();
+    for case in &suite.cases {
+        let valid_name = !case.name.is_empty()
+            && case
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            bail!(
+                "case name {:?} must be non-empty and use only letters, digits, '-' and '_'",
+                case.name
+            );
+        }
+        if !seen.insert(&case.name) {
+            bail!("duplicate case name {:?}", case.name);
+        }
+        if case.expect.status.parse::<TaskStatus>().is_err() {
+            bail!(
+                "case {:?}: unknown expected status {:?}",
+                case.name,
+                case.expect.status
+            );
+        }
+        let paths = case
+            .setup
+            .keys()
+            .chain(case.mock.files.keys())
+            .chain(&case.expect.files)
+            .chain(case.expect.contains.keys());
+        for path in paths {
+            if !is_relative_path(path) {
+                bail!(
+                    "case {:?}: {path:?} is not a relative path inside the repository",
+                    case.name
+                );
+            }
+        }
+    }
+    Ok(suite)
+}
+
+fn is_relative_path(value: &str) -> bool {
+    let path = Path::new(value);
+    !value.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+/// A harness that writes a case's scripted files instead of running an
+/// agent.
+pub struct ScriptedHarness {
+    script: MockScript,
+}
+
+impl ScriptedHarness {
+    pub fn new(script: MockScript) -> Self {
+        Self { script }
+    }
+}
+
+#[async_trait]
+impl Harness for ScriptedHarness {
+    fn name(&self) -> &str {
+        MOCK_HARNESS
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        for (path, content) in &self.script.files {
+            let dest = task.working_dir.join(path);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&dest, content)
+                .with_context(|| format!("failed to write {}", dest.display()))?;
+        }
+        Ok(AgentHandle {
+            pid: 0,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: 0,
+            harness_name: MOCK_HARNESS.to_string(),
+        })
+    }
+
+    fn events(&self, _handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        let content = self
+            .script
+            .message
+            .clone()
+            .unwrap_or_else(|| "Done.".to_string());
+        Box::pin(futures::stream::iter(vec![
+            AgentEvent::Message {
+                role: "assistant".to_string(),
+                content,
+            },
+            AgentEvent::Completed,
+        ]))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+/// Points a case's agent at the case database: agent-mode `gator`
+/// commands would otherwise write to the regular one.
+struct CaseHarness {
+    inner: Box<dyn Harness>,
+    database_path: PathBuf,
+}
+
+#[async_trait]
+impl Harness for CaseHarness {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        let mut task = task.clone();
+        task.env_vars.insert(
+            "GATOR_DATABASE_URL".to_string(),
+            self.database_path.display().to_string(),
+        );
+        self.inner.spawn(&task).await
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        self.inner.events(handle)
+    }
+
+    async fn send(&self, handle: &AgentHandle, message: &str) -> Result<()> {
+        self.inner.send(handle, message).await
+    }
+
+    async fn kill(&self, handle: &AgentHandle) -> Result<()> {
+        self.inner.kill(handle).await
+    }
+
+    async fn is_running(&self, handle: &AgentHandle) -> bool {
+        self.inner.is_running(handle).await
+    }
+
+    async fn agent_pid(&self, handle: &AgentHandle) -> u32 {
+        self.inner.agent_pid(handle).await
+    }
+}
+
+/// How one case went.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub passed: bool,
+    /// Final task status, or `"error"` if the case could not run.
+    pub status: String,
+    pub attempts: i32,
+    pub duration_secs: f64,
+    /// Why the case failed; empty when it passed.
+    pub failures: Vec<String>,
+}
+
+/// The results of a whole suite.
+#[derive(Debug, Clone, Serialize)]
+pub struct Scorecard {
+    pub suite: String,
+    pub harness: String,
+    pub cases: Vec<CaseResult>,
+}
+
+impl Scorecard {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|c| c.passed).count()
+    }
+
+    /// Plain-text table of the results, one case per row.
+    pub fn render(&self) -> String {
+        let width = self
+            .cases
+            .iter()
+            .map(|c| c.name.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let mut out = format!("Suite {} ({} harness)\n\n", self.suite, self.harness);
+        out.push_str(&format!(
+            "{:<width$}  {:<6}  {:<10}  {:>8}  {:>8}\n",
+            "CASE", "RESULT", "STATUS", "ATTEMPTS", "TIME"
+        ));
+        for case in &self.cases {
+            out.push_str(&format!(
+                "{:<width$}  {:<6}  {:<10}  {:>8}  {:>7.1}s\n",
+                case.name,
+                if case.passed { "PASS" } else { "FAIL" },
+                case.status,
+                case.attempts,
+                case.duration_secs
+            ));
+            for failure in &case.failures {
+                out.push_str(&format!("{:<width$}    - {failure}\n", ""));
+            }
+        }
+        out.push_str(&format!(
+            "\n{}/{} cases passed\n",
+            self.passed(),
+            self.cases.len()
+        ));
+        out
+    }
+}
+
+/// Run every case of `suite` in order under `workdir`.
+///
+/// `harness_for` builds the harness for a case; its name must be
+/// `suite.suite.harness`. `token_config` should be a throwaway secret: it
+/// is handed to the agents.
+pub async fn run_suite(
+    suite: &EvalSuite,
+    workdir: &Path,
+    harness_for: &dyn Fn(&EvalCase) -> Result<Box<dyn Harness>>,
+    token_config: &TokenConfig,
+    cancel: &CancellationToken,
+) -> Scorecard {
+    let mut cases = Vec::new();
+    for case in &suite.cases {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let started = Instant::now();
+        let result = match harness_for(case) {
+            Ok(harness) => {
+                run_case(
+                    &suite.suite,
+                    case,
+                    &workdir.join(&case.name),
+                    harness,
+                    token_config,
+                    cancel,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        let mut result = result.unwrap_or_else(|e| CaseResult {
+            name: case.name.clone(),
+            passed: false,
+            status: "error".to_string(),
+            attempts: 0,
+            duration_secs: 0.0,
+            failures: vec![format!("{e:#}")],
+        });
+        result.duration_secs = started.elapsed().as_secs_f64();
+        cases.push(result);
+    }
+    Scorecard {
+        suite: suite.suite.name.clone(),
+        harness: suite.suite.harness.clone(),
+        cases,
+    }
+}
+
+/// Run one case in a fresh repository and database under `case_dir`.
+async fn run_case(
+    meta: &SuiteMeta,
+    case: &EvalCase,
+    case_dir: &Path,
+    harness: Box<dyn Harness>,
+    token_config: &TokenConfig,
+    cancel: &CancellationToken,
+) -> Result<CaseResult> {
+    if case_dir.exists() {
+        std::fs::remove_dir_all(case_dir)
+            .with_context(|| format!("failed to clear {}", case_dir.display()))?;
+    }
+    let repo = case_dir.join("repo");
+    init_repo(&repo, &case.setup)?;
+
+    let database_path = case_dir.join("gator.db");
+    let db_pool = pool::create_pool(&DbConfig::new(&database_path)).await?;
+    pool::run_migrations(&db_pool).await?;
+    let mut registry = HarnessRegistry::new();
+    registry.register(CaseHarness {
+        inner: harness,
+        database_path,
+    });
+    let registry = Arc::new(registry);
+    let result = run_case_plan(meta, case, &repo, &db_pool, registry, token_config, cancel).await;
+    db_pool.close().await;
+    result
+}
+
+async fn run_case_plan(
+    meta: &SuiteMeta,
+    case: &EvalCase,
+    repo: &Path,
+    db_pool: &sqlx::SqlitePool,
+    registry: Arc<HarnessRegistry>,
+    token_config: &TokenConfig,
+    cancel: &CancellationToken,
+) -> Result<CaseResult> {
+    let mut invariant_names = Vec::new();
+    for (i, check) in case.checks.iter().enumerate() {
+        let name = format!("check_{}", i + 1);
+        invariants::insert_invariant(
+            db_pool,
+            &NewInvariant {
+                name: &name,
+                description: None,
+                kind: InvariantKind::Custom,
+                command: "sh",
+                args: &["-c".to_string(), check.clone()],
+                expected_exit_code: 0,
+                threshold: None,
+                scope: InvariantScope::Project,
+                timeout_secs: 300,
+                diff_base: false,
+                lower_is_better: false,
+                env: &Default::default(),
+                workdir: None,
+                run_after: &[],
+                artifacts: &[],
+            },
+        )
+        .await?;
+        invariant_names.push(name);
+    }
+
+    let plan_toml = PlanToml {
+        plan: PlanMeta {
+            id: None,
+            name: format!("eval-{}", case.name),
+            base_branch: "main".to_string(),
+            token_budget: None,
+            default_harness: meta.harness.clone(),
+            isolation: "worktree".to_string(),
+            container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            warmup_command: None,
+            setup_commands: Vec::new(),
+            caches: Vec::new(),
+        },
+        hooks: Default::default(),
+        tasks: vec![TaskToml {
+            name: case.name.clone(),
+            description: case.description.clone(),
+            scope: "narrow".to_string(),
+            gate: "auto".to_string(),
+            retry_max: case.retry_max,
+            depends_on: vec![],
+            ordering_only: vec![],
+            invariants: invariant_names,
+            harness: None,
+            issue: None,
+            services: vec![],
+            kind: "code".to_string(),
+            artifacts: vec![],
+            paths: vec![],
+            timeout: None,
+            setup_commands: Vec::new(),
+        }],
+    };
+    let plan = create_plan_from_toml(db_pool, &plan_toml, &repo.display().to_string()).await?;
+    plan_db::approve_plan(db_pool, plan.id).await?;
+
+    let manager = WorktreeManager::new(repo, None).map_err(|e| anyhow::anyhow!("{e}"))?;
+    let isolation: Arc<dyn Isolation> = Arc::new(WorktreeIsolation::new(manager));
+    run_orchestrator(
+        db_pool,
+        plan.id,
+        &registry,
+        &isolation,
+        token_config,
+        &OrchestratorConfig {
+            max_agents: 1,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(meta.timeout_secs),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: crate::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        cancel.child_token(),
+    )
+    .await?;
+
+    let task = task_db::list_tasks_for_plan(db_pool, plan.id)
+        .await?
+        .into_iter()
+        .next()
+        .context("eval plan has no task")?;
+    let worktree = task.worktree_path.as_ref().map(PathBuf::from);
+    let failures = check_expectation(&case.expect, task.status, worktree.as_deref());
+
+    Ok(CaseResult {
+        name: case.name.clone(),
+        passed: failures.is_empty(),
+        status: task.status.to_string(),
+        attempts: task.attempt + 1,
+        duration_secs: 0.0,
+        failures,
+    })
+}
+
+/// Compare a finished task with what the case expects.
+fn check_expectation(
+    expect: &Expectation,
+    status: TaskStatus,
+    worktree: Option<&Path>,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    if status.to_string() != expect.status {
+        failures.push(format!("expected status {}, got {status}", expect.status));
+    }
+    let wanted = !expect.files.is_empty() || !expect.contains.is_empty();
+    let Some(worktree) = worktree.filter(|_| wanted) else {
+        if wanted {
+            failures.push("task has no worktree to check files in".to_string());
+        }
+        return failures;
+    };
+    for path in &expect.files {
+        if !worktree.join(path).exists() {
+            failures.push(format!("missing file {path}"));
+        }
+    }
+    for (path, needle) in &expect.contains {
+        match std::fs::read_to_string(worktree.join(path)) {
+            Ok(content) if content.contains(needle.as_str()) => {}
+            Ok(_) => failures.push(format!("{path} does not contain {needle:?}")),
+            Err(_) => failures.push(format!("cannot read {path}")),
+        }
+    }
+    failures
+}
+
+/// Create a git repository on `main` with `files` as its first commit.
+fn init_repo(repo: &Path, files: &BTreeMap<String, String>) -> Result<()> {
+    std::fs::create_dir_all(repo)
+        .with_context(|| format!("failed to create {}", repo.display()))?;
+    git(repo, &["init", "-q", "-b", "main"])?;
+    for (path, content) in files {
+        let dest = repo.join(path);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&dest, content)
+            .with_context(|| format!("failed to write {}", dest.display()))?;
+    }
+    git(repo, &["add", "-A"])?;
+    git(
+        repo,
+        &[
+            "-c",
+            "user.name=gator eval",
+            "-c",
+            "user.email=eval@gator.invalid",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Eval setup",
+        ],
+    )
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"
+[suite]
+name = "golden"
+harness = "mock"
+
+[[cases]]
+name = "create-file"
+description = "Create hello.txt"
+checks = ["test -f hello.txt"]
+
+[cases.mock]
+files = { "hello.txt" = "hello\n" }
+
+[cases.expect]
+files = ["hello.txt"]
+contains = { "hello.txt" = "hello" }
+
+[[cases]]
+name = "nothing-done"
+description = "Create hello.txt"
+checks = ["test -f hello.txt"]
+expect = { status = "escalated" }
+"#;
+
+    #[test]
+    fn parses_suites_with_defaults() {
+        let suite = parse_suite(SUITE).unwrap();
+        assert_eq!(suite.suite.timeout_secs, 600);
+        assert_eq!(suite.cases.len(), 2);
+        assert_eq!(suite.cases[0].expect.status, "passed");
+        assert_eq!(suite.cases[0].retry_max, 0);
+        assert_eq!(suite.cases[1].expect.status, "escalated");
+    }
+
+    #[test]
+    fn rejects_invalid_suites() {
+        let dup = format!("{SUITE}\n[[cases]]\nname = \"create-file\"\ndescription = \"again\"\n");
+        assert!(parse_suite(&dup).is_err());
+
+        let bad_status = SUITE.replace("\"escalated\"", "\"done\"");
+        assert!(parse_suite(&bad_status).is_err());
+
+        let escape = SUITE.replace("files = [\"hello.txt\"]", "files = [\"../hello.txt\"]");
+        assert!(parse_suite(&escape).is_err());
+
+        assert!(parse_suite("cases = []\n[suite]\nname = \"empty\"\n").is_err());
+    }
+
+    #[test]
+    fn example_suite_is_valid() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../docs/examples/eval-golden.toml");
+        let suite = parse_suite(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(suite.suite.harness, MOCK_HARNESS);
+        assert_eq!(suite.cases.len(), 3);
+    }
+
+    #[test]
+    fn expectations_report_each_mismatch() {
+        let dir = std::env::temp_dir().join(format!("gator-eval-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("hello.txt"), "goodbye\n").unwrap();
+
+        let expect = Expectation {
+            status: "passed".to_string(),
+            files: vec!["hello.txt".to_string(), "other.txt".to_string()],
+            contains: BTreeMap::from([("hello.txt".to_string(), "hello".to_string())]),
+        };
+        let failures = check_expectation(&expect, TaskStatus::Escalated, Some(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            failures,
+            vec![
+                "expected status passed, got escalated".to_string(),
+                "missing file other.txt".to_string(),
+                "hello.txt does not contain \"hello\"".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Forge integration: read work queues from and post results back to the
+//! code host.
+//!
+//! GitHub is driven through the `gh` CLI, so authentication is whatever
+//! `gh auth` is configured with; gator stores no forge credentials.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::plan::parser::is_valid_issue_ref;
+use crate::plan::{GithubIssue, RepoSource};
+
+/// An open pull request, as emitted by `gh pr list --json
+/// number,title,body,url,headRefOid,baseRefName,isDraft`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubPullRequest {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    pub url: String,
+    /// Commit SHA at the tip of the PR branch.
+    pub head_ref_oid: String,
+    /// Branch the PR targets.
+    pub base_ref_name: String,
+    #[serde(default)]
+    pub is_draft: bool,
+}
+
+/// How a posted pull request review counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrReviewAction {
+    Comment,
+    Approve,
+    RequestChanges,
+}
+
+/// Local branches holding a fetched pull request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedPullRequest {
+    /// Branch at the PR head.
+    pub head_branch: String,
+    /// Branch at the tip of the PR's base branch.
+    pub base_branch: String,
+}
+
+/// A GitHub repository, accessed via `gh`.
+#[derive(Debug, Clone)]
+pub struct GithubForge {
+    repo: String,
+}
+
+impl GithubForge {
+    /// `repo` is `owner/name`.
+    pub fn new(repo: &str) -> Result<Self> {
+        if !is_valid_issue_ref(&format!("{repo}#1")) {
+            bail!("invalid repository {repo:?} (expected owner/name)");
+        }
+        Ok(Self {
+            repo: repo.to_string(),
+        })
+    }
+
+    /// The repository as `owner/name`.
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    /// Open issues carrying `label`, at most `limit` of them.
+    pub fn list_issues(&self, label: &str, limit: u32) -> Result<Vec<GithubIssue>> {
+        let stdout = self.gh(&[
+            "issue",
+            "list",
+            "--repo",
+            &self.repo,
+            "--label",
+            label,
+            "--state",
+            "open",
+            "--limit",
+            &limit.to_string(),
+            "--json",
+            "number,title,body,url",
+        ])?;
+        serde_json::from_slice(&stdout).context("failed to parse `gh issue list` output")
+    }
+
+    /// Post `body` as a comment on issue `number`.
+    pub fn comment_on_issue(&self, number: u64, body: &str) -> Result<()> {
+        self.gh(&[
+            "issue",
+            "comment",
+            &number.to_string(),
+            "--repo",
+            &self.repo,
+            "--body",
+            body,
+        ])?;
+        Ok(())
+    }
+
+    /// Add `labels` to issue `number`. A no-op when `labels` is empty.
+    pub fn add_labels(&self, number: u64, labels: &[String]) -> Result<()> {
+        if labels.is_empty() {
+            return Ok(());
+        }
+        self.gh(&[
+            "issue",
+            "edit",
+            &number.to_string(),
+            "--repo",
+            &self.repo,
+            "--add-label",
+            &labels.join(","),
+        ])?;
+        Ok(())
+    }
+
+    /// Open pull requests, optionally only those carrying `label`, at most
+    /// `limit` of them.
+    pub fn list_pull_requests(
+        &self,
+        label: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<GithubPullRequest>> {
+        let limit = limit.to_string();
+        let mut args = vec![
+            "pr",
+            "list",
+            "--repo",
+            &self.repo,
+            "--state",
+            "open",
+            "--limit",
+            &limit,
+            "--json",
+            "number,title,body,url,headRefOid,baseRefName,isDraft",
+        ];
+        if let Some(label) = label {
+            args.extend(["--label", label]);
+        }
+        let stdout = self.gh(&args)?;
+        serde_json::from_slice(&stdout).context("failed to parse `gh pr list` output")
+    }
+
+    /// Submit a review with `body` on pull request `number`.
+    pub fn post_pr_review(&self, number: u64, action: PrReviewAction, body: &str) -> Result<()> {
+        let flag = match action {
+            PrReviewAction::Comment => "--comment",
+            PrReviewAction::Approve => "--approve",
+            PrReviewAction::RequestChanges => "--request-changes",
+        };
+        self.gh(&[
+            "pr",
+            "review",
+            &number.to_string(),
+            "--repo",
+            &self.repo,
+            flag,
+            "--body",
+            body,
+        ])?;
+        Ok(())
+    }
+
+    /// Run `gh` with `args` and return its stdout.
+    fn gh(&self, args: &[&str]) -> Result<Vec<u8>> {
+        gh(None, args)
+    }
+}
+
+/// Clone `source` into `dest`, or fetch if `dest` is already a clone.
+/// Local checkouts are used as they are.
+pub fn clone_or_fetch(source: &RepoSource, dest: &Path) -> Result<()> {
+    if matches!(source, RepoSource::Local(_)) {
+        return Ok(());
+    }
+    if dest.join(".git").exists() {
+        git(dest, &["fetch", "--prune", "origin"])?;
+        return Ok(());
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let dest_str = dest.to_string_lossy();
+    match source {
+        RepoSource::Github(repo) => {
+            gh(None, &["repo", "clone", repo, &dest_str])?;
+        }
+        RepoSource::Url(url) => {
+            let output = Command::new("git")
+                .args(["clone", url.as_str(), &dest_str])
+                .output()
+                .context("failed to run git")?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                bail!("git clone {url} failed: {stderr}");
+            }
+        }
+        RepoSource::Local(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Force-push `branch` to `remote`.
+pub fn push_branch(repo_path: &Path, remote: &str, branch: &str) -> Result<()> {
+    git(repo_path, &["push", "--force", "-u", remote, branch])
+        .with_context(|| format!("failed to push {branch} to {remote}"))?;
+    Ok(())
+}
+
+/// Open a pull request from `head` into `base` for the checkout at
+/// `repo_path` and return its URL. If `head` already has an open PR (an
+/// earlier run pushed it), that PR's URL is returned instead.
+pub fn create_pull_request(
+    repo_path: &Path,
+    head: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+    draft: bool,
+) -> Result<String> {
+    let mut args = vec![
+        "pr", "create", "--head", head, "--base", base, "--title", title, "--body", body,
+    ];
+    if draft {
+        args.push("--draft");
+    }
+    match gh(Some(repo_path), &args) {
+        Ok(stdout) => Ok(String::from_utf8_lossy(&stdout).trim().to_string()),
+        Err(e) if e.to_string().contains("already exists") => {
+            let stdout = gh(
+                Some(repo_path),
+                &["pr", "view", head, "--json", "url", "--jq", ".url"],
+            )?;
+            Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Run `gh` with `args`, in `dir` if given, and return its stdout.
+fn gh(dir: Option<&Path>, args: &[&str]) -> Result<Vec<u8>> {
+    let mut command = Command::new("gh");
+    command.args(args);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .output()
+        .context("failed to run `gh` -- is GitHub CLI installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("gh {} {} failed: {stderr}", args[0], args[1]);
+    }
+    Ok(output.stdout)
+}
+
+/// Fetch pull request `pr` from `remote` into local branches
+/// `gator-review/pr-<N>` (head) and `gator-review/pr-<N>-base` (base),
+/// replacing earlier fetches of the same PR.
+pub fn fetch_pull_request(
+    repo_path: &Path,
+    remote: &str,
+    pr: &GithubPullRequest,
+) -> Result<FetchedPullRequest> {
+    let fetched = FetchedPullRequest {
+        head_branch: format!("gator-review/pr-{}", pr.number),
+        base_branch: format!("gator-review/pr-{}-base", pr.number),
+    };
+    let head_spec = format!(
+        "+refs/pull/{}/head:refs/heads/{}",
+        pr.number, fetched.head_branch
+    );
+    let base_spec = format!(
+        "+refs/heads/{}:refs/heads/{}",
+        pr.base_ref_name, fetched.base_branch
+    );
+    git(
+        repo_path,
+        &["fetch", "--no-tags", remote, &head_spec, &base_spec],
+    )
+    .with_context(|| format!("failed to fetch PR #{} from {remote}", pr.number))?;
+    Ok(fetched)
+}
+
+/// Files changed on `head` since it diverged from `base`.
+pub fn changed_files(repo_path: &Path, base: &str, head: &str) -> Result<Vec<String>> {
+    let range = format!("{base}...{head}");
+    let stdout = git(
+        repo_path,
+        &["diff", "--name-only", "--no-renames", "-z", &range],
+    )?;
+    Ok(stdout
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Run `git` in `repo_path` and return its stdout.
+fn git(repo_path: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed: {stderr}", args[0]);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse an issue reference (`owner/repo#N`) into its repository and number.
+pub fn split_issue_ref(value: &str) -> Option<(&str, u64)> {
+    if !is_valid_issue_ref(value) {
+        return None;
+    }
+    let (repo, number) = value.rsplit_once('#')?;
+    Some((repo, number.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_repo_and_splits_issue_refs() {
+        assert!(GithubForge::new("acme/widgets").is_ok());
+        assert!(GithubForge::new("widgets").is_err());
+        assert!(GithubForge::new("a/b/c").is_err());
+
+        assert_eq!(
+            split_issue_ref("acme/widgets#42"),
+            Some(("acme/widgets", 42))
+        );
+        assert_eq!(split_issue_ref("acme/widgets"), None);
+        assert_eq!(split_issue_ref("acme/widgets#x"), None);
+    }
+
+    #[test]
+    fn deserializes_gh_pr_json() {
+        let json = r#"[{"number": 5, "title": "Add x", "body": "", "url": "https://x/5",
+            "headRefOid": "abc123", "baseRefName": "main", "isDraft": true}]"#;
+        let prs: Vec<GithubPullRequest> = serde_json::from_str(json).unwrap();
+        assert_eq!(prs[0].head_ref_oid, "abc123");
+        assert_eq!(prs[0].base_ref_name, "main");
+        assert!(prs[0].is_draft);
+    }
+}
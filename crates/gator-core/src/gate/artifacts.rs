@@ -0,0 +1,188 @@
+//! Failure artifacts: files an invariant leaves behind when it fails.
+//!
+//! UI tests write screenshots, videos, and traces into the worktree, which
+//! is removed once the task is cleaned up. An invariant can declare
+//! `artifacts` globs, relative to the worktree root; when it fails, the
+//! files they match are copied out of the worktree and recorded against its
+//! gate result.
+//!
+//! Globs match path components with `*`, `?`, and `[...]`; a `**`
+//! component matches any number of directories. `.git` is never searched.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::plan::overlap::glob_matches;
+
+/// At most this many files are collected per failed invariant, so a glob
+/// that matches a whole build directory cannot fill the disk.
+pub const MAX_ARTIFACTS: usize = 50;
+
+/// Name of the directory, under the bundle root, holding failure artifacts.
+const FAILURES_DIR: &str = ".gate-failures";
+
+/// A file copied out of the worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectedArtifact {
+    /// Path relative to the worktree root.
+    pub path: String,
+    /// Where the copy was written.
+    pub stored_path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Directory for the failure artifacts of one task attempt:
+/// `<bundle root>/.gate-failures/<plan>/<task>/attempt-<n>`.
+pub fn failure_dir(project_path: &Path, plan_name: &str, task_name: &str, attempt: i32) -> PathBuf {
+    let root = crate::artifact::bundle_root(project_path).join(FAILURES_DIR);
+    crate::artifact::bundle_dir(&root, plan_name, task_name).join(format!("attempt-{attempt}"))
+}
+
+/// Check that an artifacts glob is relative to the worktree root and does
+/// not leave it.
+pub fn validate_pattern(pattern: &str) -> Result<()> {
+    let inside = !pattern.is_empty()
+        && Path::new(pattern)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        anyhow::bail!("artifacts glob {pattern:?} must be a relative path inside the worktree");
+    }
+    Ok(())
+}
+
+/// Copy the files in `worktree` matching any of `patterns` into `dest`,
+/// keeping their relative paths. Returns the files copied, sorted by path.
+pub fn collect(
+    worktree: &Path,
+    patterns: &[String],
+    dest: &Path,
+) -> Result<Vec<CollectedArtifact>> {
+    let mut matches = Vec::new();
+    for pattern in patterns {
+        let components: Vec<&str> = pattern
+            .split('/')
+            .filter(|c| !c.is_empty() && *c != ".")
+            .collect();
+        // Only the part of the tree below the pattern's literal prefix can
+        // match, so walk from there.
+        let literal = components
+            .iter()
+            .take_while(|c| !is_glob(c))
+            .collect::<PathBuf>();
+        for file in walk_files(&worktree.join(&literal)) {
+            let Ok(rel) = file.strip_prefix(worktree) else {
+                continue;
+            };
+            let rel_components: Option<Vec<&str>> = rel
+                .components()
+                .map(|c| match c {
+                    Component::Normal(s) => s.to_str(),
+                    _ => None,
+                })
+                .collect();
+            if rel_components.is_some_and(|rel| path_matches(&components, &rel)) {
+                matches.push(rel.to_path_buf());
+            }
+        }
+    }
+    matches.sort();
+    matches.dedup();
+    if matches.len() > MAX_ARTIFACTS {
+        tracing::warn!(
+            matched = matches.len(),
+            limit = MAX_ARTIFACTS,
+            "too many failure artifacts; collecting the first ones"
+        );
+        matches.truncate(MAX_ARTIFACTS);
+    }
+
+    let mut collected = Vec::new();
+    for rel in matches {
+        let target = dest.join(&rel);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let size_bytes = std::fs::copy(worktree.join(&rel), &target)
+            .with_context(|| format!("failed to copy artifact {}", rel.display()))?;
+        collected.push(CollectedArtifact {
+            path: rel.to_string_lossy().into_owned(),
+            stored_path: target,
+            size_bytes,
+        });
+    }
+    Ok(collected)
+}
+
+/// Whether the path `components` match the glob `pattern` components.
+pub(crate) fn path_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            path_matches(rest, path) || (!path.is_empty() && path_matches(pattern, &path[1..]))
+        }
+        (Some((p, rest)), Some((c, path_rest))) => {
+            glob_matches(p.as_bytes(), c.as_bytes()) && path_matches(rest, path_rest)
+        }
+        _ => false,
+    }
+}
+
+fn is_glob(component: &str) -> bool {
+    component.contains(['*', '?', '['])
+}
+
+/// All files under `dir` (or `dir` itself, if it is a file), skipping
+/// `.git`.
+fn walk_files(dir: &Path) -> Vec<PathBuf> {
+    if dir.is_file() {
+        return vec![dir.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(d) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&d) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        let pattern: Vec<&str> = pattern.split('/').collect();
+        let path: Vec<&str> = path.split('/').collect();
+        path_matches(&pattern, &path)
+    }
+
+    #[test]
+    fn double_star_matches_any_depth() {
+        assert!(matches("test-results/**/*.png", "test-results/a.png"));
+        assert!(matches(
+            "test-results/**/*.png",
+            "test-results/login/chromium/a.png"
+        ));
+        assert!(!matches(
+            "test-results/**/*.png",
+            "test-results/login/a.webm"
+        ));
+        assert!(matches("**/trace.zip", "e2e/out/trace.zip"));
+        assert!(!matches("screenshots/*.png", "screenshots/nested/a.png"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Compliance attestations of generated code.
+//!
+//! Organizations that must track the provenance of AI-generated code can
+//! turn on `gate.attestation`. Every code task whose invariants pass then
+//! gets an attestation: which harness (and version) produced the change, a
+//! SHA-256 of the prompt the agent was given, the commit the task branched
+//! from, a SHA-256 of the change itself (see [`crate::worktree::diff_hash`]),
+//! and when. It is recorded in the `attestations` table and written into
+//! the task's worktree as `.gator/attestations/<task>.json`, so it is
+//! committed to the branch with the work and merged with it.
+//!
+//! A later attempt of the same task overwrites the file; every attempt keeps
+//! its row. Approving only some of an attempt's files (`gator approve
+//! --files`) attests the attempt again, over the approved files alone.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{Attestation, Task};
+use gator_db::queries::attestations::{self, NewAttestation};
+use gator_db::queries::plans as plan_db;
+
+use crate::worktree::{self, WorktreeManager};
+
+/// Directory of attestation files, relative to the worktree root. Its
+/// contents are not part of the attested change.
+pub const ATTESTATION_DIR: &str = ".gator/attestations";
+
+/// Identifies the format of an attestation file.
+pub const ATTESTATION_SCHEMA: &str = "gator.attestation/v1";
+
+/// The machine-readable attestation written into the branch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestationFile {
+    /// Always [`ATTESTATION_SCHEMA`].
+    pub schema: String,
+    pub plan_id: Uuid,
+    pub plan_name: String,
+    pub task_id: Uuid,
+    pub task_name: String,
+    pub attempt: i32,
+    /// Harness the agent ran on, e.g. `claude-code`.
+    pub harness: String,
+    /// Version the harness's CLI reported, if any.
+    pub harness_version: Option<String>,
+    /// SHA-256 of the prompt the agent was given.
+    pub prompt_sha256: String,
+    /// The commit the task branched from.
+    pub base_commit: String,
+    /// SHA-256 of the change relative to `base_commit`.
+    pub diff_sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Hex SHA-256 of a prompt, as attested.
+pub fn prompt_sha256(prompt: &str) -> String {
+    hex::encode(Sha256::digest(prompt.as_bytes()))
+}
+
+/// The attestation file of `task_name`, relative to the worktree root.
+pub fn attestation_path(task_name: &str) -> String {
+    format!(
+        "{ATTESTATION_DIR}/{}.json",
+        worktree::sanitize_ref_component(task_name)
+    )
+}
+
+/// Attest the current attempt of `task`: hash its change in `worktree_path`,
+/// write the attestation file there, and record it.
+pub async fn attest(
+    pool: &SqlitePool,
+    task: &Task,
+    harness: &str,
+    harness_version: Option<&str>,
+    prompt_sha256: &str,
+    worktree_path: &Path,
+) -> Result<Attestation> {
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let manager = WorktreeManager::new(&plan.project_path, None)?;
+    let base_commit = manager
+        .merge_base(worktree_path, &plan.base_branch)?
+        .with_context(|| {
+            format!(
+                "cannot find where the task branched from {}",
+                plan.base_branch
+            )
+        })?;
+    let diff_sha256 = worktree::diff_hash(worktree_path, &base_commit, ATTESTATION_DIR)?;
+
+    let file = AttestationFile {
+        schema: ATTESTATION_SCHEMA.to_string(),
+        plan_id: plan.id,
+        plan_name: plan.name,
+        task_id: task.id,
+        task_name: task.name.clone(),
+        attempt: task.attempt,
+        harness: harness.to_string(),
+        harness_version: harness_version.map(str::to_string),
+        prompt_sha256: prompt_sha256.to_string(),
+        base_commit: base_commit.clone(),
+        diff_sha256: diff_sha256.clone(),
+        created_at: Utc::now(),
+    };
+    let path = attestation_path(&task.name);
+    let dest = worktree_path.join(&path);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut json = serde_json::to_string_pretty(&file)?;
+    json.push('\n');
+    std::fs::write(&dest, json).with_context(|| format!("failed to write {}", dest.display()))?;
+
+    attestations::insert_attestation(
+        pool,
+        &NewAttestation {
+            task_id: task.id,
+            attempt: task.attempt,
+            harness,
+            harness_version,
+            prompt_sha256,
+            base_commit: &base_commit,
+            diff_sha256: &diff_sha256,
+            path: &path,
+        },
+    )
+    .await
+}
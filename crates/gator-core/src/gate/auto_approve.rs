@@ -0,0 +1,240 @@
+//! Auto-approval rules for `human_review` gates.
+//!
+//! Operators can configure rules under `[[gate.auto_approve]]` that pass a
+//! `human_review` task without a human when its gate passes and its changes
+//! are low-risk, e.g. "under 50 changed lines, only under `tests/`". A rule
+//! matches when every condition it sets holds:
+//!
+//! - `max_diff_lines`: lines added plus removed, across all files; a
+//!   binary file has no line count, so changing one exceeds any limit;
+//! - `paths`: every changed file matches one of these patterns;
+//! - `plans`: the plan's name matches one of these globs.
+//!
+//! A rule must set `max_diff_lines` or `paths`. Changes are measured
+//! against the commit the task branched from, committed or not. A task
+//! that changes nothing, or a `human_approve` task, is never auto-approved.
+//! The matching rule is recorded in `auto_approvals`.
+
+use std::collections::HashSet;
+use std::path::{Component, Path};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::plan::overlap::glob_matches;
+use crate::worktree::FileChange;
+
+use super::artifacts::path_matches;
+
+/// One auto-approval rule (a `[[gate.auto_approve]]` config entry).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoApproveRule {
+    /// Name recorded when the rule approves a task.
+    pub name: String,
+    /// Most lines (added plus removed) the task may change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_diff_lines: Option<usize>,
+    /// Every changed file must match one of these. A pattern without glob
+    /// characters matches that file or anything under that directory;
+    /// otherwise components match with `*`, `?`, `[...]`, and `**`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Only tasks of plans whose name matches one of these globs; any plan
+    /// when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plans: Vec<String>,
+}
+
+/// The outcome of matching a task's changes against the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoApproval<'a> {
+    pub rule: &'a AutoApproveRule,
+    /// Lines added plus removed, across all files.
+    pub diff_lines: usize,
+    /// The changed files.
+    pub files: Vec<String>,
+}
+
+impl AutoApproveRule {
+    fn matches(&self, plan_name: &str, changes: &[FileChange], diff_lines: usize) -> bool {
+        let plan_ok = self.plans.is_empty()
+            || self
+                .plans
+                .iter()
+                .any(|p| glob_matches(p.as_bytes(), plan_name.as_bytes()));
+        let size_ok = self
+            .max_diff_lines
+            .is_none_or(|max| diff_lines <= max && !changes.iter().any(|c| c.binary));
+        let paths_ok = self.paths.is_empty()
+            || changes
+                .iter()
+                .all(|c| self.paths.iter().any(|p| path_allowed(p, &c.path)));
+        plan_ok && size_ok && paths_ok
+    }
+}
+
+/// Check that rules have unique names, set a condition, and use paths
+/// inside the worktree.
+pub fn validate_rules(rules: &[AutoApproveRule]) -> Result<()> {
+    let mut names = HashSet::new();
+    for rule in rules {
+        if rule.name.trim().is_empty() {
+            bail!("auto-approval rules need a name");
+        }
+        if !names.insert(rule.name.as_str()) {
+            bail!("auto-approval rule {:?} is defined twice", rule.name);
+        }
+        if rule.max_diff_lines.is_none() && rule.paths.is_empty() {
+            bail!(
+                "auto-approval rule {:?} must set max_diff_lines or paths",
+                rule.name
+            );
+        }
+        for pattern in &rule.paths {
+            let inside = Path::new(pattern)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+            if pattern.is_empty() || !inside {
+                bail!(
+                    "path {pattern:?} in auto-approval rule {:?} must be relative to the worktree root",
+                    rule.name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first rule that approves a task of `plan_name` making `changes`.
+/// `None` when no rule matches or the task changes nothing.
+pub fn find_match<'a>(
+    rules: &'a [AutoApproveRule],
+    plan_name: &str,
+    changes: &[FileChange],
+) -> Option<AutoApproval<'a>> {
+    if changes.is_empty() {
+        return None;
+    }
+    let diff_lines = changes.iter().map(|c| c.lines).sum();
+    let rule = rules
+        .iter()
+        .find(|r| r.matches(plan_name, changes, diff_lines))?;
+    Some(AutoApproval {
+        rule,
+        diff_lines,
+        files: changes.iter().map(|c| c.path.clone()).collect(),
+    })
+}
+
+/// Whether `path` is covered by the rule pattern `pattern`.
+fn path_allowed(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    if !pattern.contains(['*', '?', '[']) {
+        return path == pattern
+            || path
+                .strip_prefix(pattern)
+                .is_some_and(|rest| rest.starts_with('/'));
+    }
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let components: Option<Vec<&str>> = Path::new(path)
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect();
+    components.is_some_and(|components| path_matches(&pattern, &components))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, lines: usize) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            lines,
+            binary: false,
+        }
+    }
+
+    fn rule(max_diff_lines: Option<usize>, paths: &[&str], plans: &[&str]) -> AutoApproveRule {
+        AutoApproveRule {
+            name: "low-risk".to_string(),
+            max_diff_lines,
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            plans: plans.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn rule_needs_every_condition() {
+        let rules = [rule(Some(50), &["tests/"], &[])];
+        let small_tests = [change("tests/a.rs", 30), change("tests/unit/b.rs", 10)];
+        let approval = find_match(&rules, "p", &small_tests).unwrap();
+        assert_eq!(approval.diff_lines, 40);
+        assert_eq!(approval.files, vec!["tests/a.rs", "tests/unit/b.rs"]);
+
+        // Too big, or touching code outside tests/.
+        assert!(find_match(&rules, "p", &[change("tests/a.rs", 51)]).is_none());
+        assert!(
+            find_match(
+                &rules,
+                "p",
+                &[change("tests/a.rs", 1), change("src/lib.rs", 1)]
+            )
+            .is_none()
+        );
+        // `tests/` does not cover `tests-old/`.
+        assert!(find_match(&rules, "p", &[change("tests-old/a.rs", 1)]).is_none());
+        // Nothing changed: leave it to a human.
+        assert!(find_match(&rules, "p", &[]).is_none());
+    }
+
+    #[test]
+    fn globs_and_plan_filters() {
+        let rules = [
+            rule(None, &["docs/**/*.md", "*.md"], &["docs-*"]),
+            rule(Some(5), &[], &[]),
+        ];
+        let docs = [change("docs/guide/intro.md", 200), change("README.md", 3)];
+        assert_eq!(
+            find_match(&rules, "docs-refresh", &docs)
+                .unwrap()
+                .rule
+                .paths
+                .len(),
+            2
+        );
+        // Other plans only get the size rule.
+        assert!(find_match(&rules, "backend", &docs).is_none());
+        assert_eq!(
+            find_match(&rules, "backend", &[change("src/lib.rs", 4)])
+                .unwrap()
+                .rule
+                .max_diff_lines,
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn binary_changes_exceed_any_line_limit() {
+        let image = FileChange {
+            path: "assets/logo.png".to_string(),
+            lines: 0,
+            binary: true,
+        };
+        let size_only = [rule(Some(50), &[], &[])];
+        assert!(find_match(&size_only, "p", std::slice::from_ref(&image)).is_none());
+        assert!(find_match(&size_only, "p", &[image.clone(), change("a.rs", 1)]).is_none());
+        // A path rule can still approve binaries it covers.
+        let assets = [rule(None, &["assets/"], &[])];
+        assert!(find_match(&assets, "p", &[image]).is_some());
+    }
+
+    #[test]
+    fn validate_rejects_unconditional_and_duplicate_rules() {
+        assert!(validate_rules(&[rule(Some(10), &[], &[])]).is_ok());
+        assert!(validate_rules(&[rule(None, &[], &["*"])]).is_err());
+        assert!(validate_rules(&[rule(Some(1), &[], &[]), rule(Some(2), &[], &[])]).is_err());
+        assert!(validate_rules(&[rule(None, &["../outside"], &[])]).is_err());
+    }
+}
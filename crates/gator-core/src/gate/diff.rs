@@ -0,0 +1,258 @@
+//! Differential invariants: compare a task branch against its base.
+//!
+//! An invariant with `diff_base` set is run twice, in the task's worktree
+//! and in a detached checkout of the commit the task branched from. Each
+//! run's *value* is the last number its command prints on stdout (a test
+//! count, a coverage percentage, a binary size). The invariant fails only
+//! when the task's value is worse than the base's by more than the
+//! invariant's `threshold`, so a suite that was already failing on the base
+//! branch does not block every task.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use gator_db::models::Invariant;
+
+use crate::invariant::runner::InvariantResult;
+use crate::worktree::WorktreeManager;
+
+/// The last number in `output`, e.g. `87.5` in `coverage: 87.5%` or `412`
+/// in `test result: ok. 412 passed`. Thousands separators are not
+/// understood: `1,024` reads as `024`.
+pub fn parse_metric(output: &str) -> Option<f64> {
+    let bytes = output.as_bytes();
+    let mut end = bytes.len();
+    while end > 0 {
+        // Find the last digit, then the start of the number around it.
+        let last_digit = bytes[..end].iter().rposition(u8::is_ascii_digit)?;
+        let mut start = last_digit;
+        while start > 0 && (bytes[start - 1].is_ascii_digit() || bytes[start - 1] == b'.') {
+            start -= 1;
+        }
+        if start > 0 && bytes[start - 1] == b'-' {
+            start -= 1;
+        }
+        let text = output[start..=last_digit].trim_start_matches('.');
+        if let Ok(value) = text.parse::<f64>() {
+            return Some(value);
+        }
+        end = start;
+    }
+    None
+}
+
+/// The verdict of a differential invariant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOutcome {
+    pub passed: bool,
+    pub base_value: Option<f64>,
+    pub task_value: Option<f64>,
+    /// Why the invariant failed, or how it was judged when the base had no
+    /// value to compare against.
+    pub message: Option<String>,
+}
+
+/// Judge a differential invariant from its task and base runs. `base` is
+/// `None` when there was no base commit to run on.
+///
+/// - A task run that timed out or printed no number fails.
+/// - Without a base value, the task run's exit code decides, as for an
+///   ordinary invariant.
+/// - Otherwise the task's value may be worse than the base's by at most
+///   `threshold` (default 0); exit codes are not considered.
+pub fn compare(
+    invariant: &Invariant,
+    task: &InvariantResult,
+    base: Option<&InvariantResult>,
+) -> DiffOutcome {
+    let task_value = (!task.timed_out)
+        .then(|| parse_metric(&task.stdout))
+        .flatten();
+    let base_value = base
+        .filter(|b| !b.timed_out)
+        .and_then(|b| parse_metric(&b.stdout));
+    let outcome = |passed, message: Option<String>| DiffOutcome {
+        passed,
+        base_value,
+        task_value,
+        message,
+    };
+
+    if task.timed_out {
+        return outcome(false, None);
+    }
+    let Some(task_value) = task_value else {
+        return outcome(
+            false,
+            Some("printed no number to compare with the base branch".to_string()),
+        );
+    };
+    let Some(base_value) = base_value else {
+        return outcome(
+            task.passed,
+            Some("the base branch gave no number to compare with; judged by exit code".to_string()),
+        );
+    };
+
+    let tolerance = f64::from(invariant.threshold.unwrap_or(0.0)).abs();
+    let regressed = if invariant.lower_is_better {
+        task_value > base_value + tolerance
+    } else {
+        task_value < base_value - tolerance
+    };
+    if regressed {
+        let allowed = if tolerance > 0.0 {
+            format!(" (allowed regression: {tolerance})")
+        } else {
+            String::new()
+        };
+        outcome(
+            false,
+            Some(format!(
+                "regressed from {base_value} on the base branch to {task_value}{allowed}"
+            )),
+        )
+    } else {
+        outcome(true, None)
+    }
+}
+
+/// A detached checkout of the commit a task branched from, next to the
+/// task's worktree. Removed when dropped.
+pub(crate) struct BaseCheckout {
+    manager: WorktreeManager,
+    path: PathBuf,
+}
+
+impl BaseCheckout {
+    /// Check out the merge base of `worktree`'s `HEAD` and `base_branch`.
+    /// Returns `None` when `base_branch` does not exist or shares no
+    /// history with the task branch.
+    pub(crate) fn create(
+        repo_path: &str,
+        base_branch: &str,
+        worktree: &Path,
+    ) -> Result<Option<Self>> {
+        let manager = WorktreeManager::new(repo_path, None)?;
+        let Some(commit) = manager.merge_base(worktree, base_branch)? else {
+            return Ok(None);
+        };
+        let mut name = worktree.file_name().unwrap_or_default().to_os_string();
+        name.push(".base");
+        let path = worktree.with_file_name(name);
+        // A checkout left behind by an interrupted gate may be at another
+        // commit.
+        manager.remove_worktree(&path)?;
+        manager
+            .add_detached(&path, &commit)
+            .with_context(|| format!("failed to check out base commit {commit}"))?;
+        Ok(Some(Self { manager, path }))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for BaseCheckout {
+    fn drop(&mut self) {
+        if let Err(e) = self.manager.remove_worktree(&self.path) {
+            tracing::warn!(path = %self.path.display(), error = %e, "failed to remove base checkout");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use gator_db::models::{InvariantKind, InvariantScope};
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn invariant(threshold: Option<f32>, lower_is_better: bool) -> Invariant {
+        Invariant {
+            id: Uuid::new_v4(),
+            name: "tests".to_owned(),
+            description: None,
+            kind: InvariantKind::TestSuite,
+            command: "true".to_owned(),
+            args: sqlx::types::Json(Vec::new()),
+            expected_exit_code: 0,
+            threshold,
+            scope: InvariantScope::Project,
+            timeout_secs: 300,
+            created_at: Utc::now(),
+            deleted_at: None,
+            diff_base: true,
+            lower_is_better,
+            disabled_at: None,
+            env: Default::default(),
+            workdir: None,
+            run_after: Default::default(),
+            artifacts: Default::default(),
+        }
+    }
+
+    fn run(stdout: &str, passed: bool) -> InvariantResult {
+        InvariantResult {
+            passed,
+            exit_code: Some(if passed { 0 } else { 1 }),
+            timed_out: false,
+            stdout: stdout.to_owned(),
+            stderr: String::new(),
+            duration_ms: 10,
+        }
+    }
+
+    #[test]
+    fn parse_metric_takes_the_last_number() {
+        assert_eq!(parse_metric("test result: ok. 412 passed\n"), Some(412.0));
+        assert_eq!(parse_metric("lines: 10\ncoverage: 87.5%\n"), Some(87.5));
+        assert_eq!(parse_metric("delta -3 bytes"), Some(-3.0));
+        assert_eq!(parse_metric("version 1.2.\n"), Some(1.2));
+        assert_eq!(parse_metric("no numbers here"), None);
+    }
+
+    #[test]
+    fn higher_is_better_fails_only_on_a_drop() {
+        let inv = invariant(None, false);
+        let base = run("400 passed", false);
+
+        let same = compare(&inv, &run("400 passed", false), Some(&base));
+        assert!(
+            same.passed,
+            "an unchanged failing suite is not a regression"
+        );
+        assert_eq!(
+            (same.base_value, same.task_value),
+            (Some(400.0), Some(400.0))
+        );
+
+        let dropped = compare(&inv, &run("399 passed", true), Some(&base));
+        assert!(!dropped.passed);
+        assert!(dropped.message.unwrap().contains("from 400"));
+    }
+
+    #[test]
+    fn lower_is_better_allows_growth_within_threshold() {
+        let inv = invariant(Some(1024.0), true);
+        let base = run("5000000", true);
+        assert!(compare(&inv, &run("5001024", true), Some(&base)).passed);
+        assert!(!compare(&inv, &run("5001025", true), Some(&base)).passed);
+        assert!(compare(&inv, &run("4000000", true), Some(&base)).passed);
+    }
+
+    #[test]
+    fn missing_values_fall_back_or_fail() {
+        let inv = invariant(None, false);
+        let no_number = compare(&inv, &run("done", true), Some(&run("12", true)));
+        assert!(!no_number.passed);
+
+        let no_base = compare(&inv, &run("12", true), None);
+        assert!(no_base.passed);
+        assert_eq!(no_base.base_value, None);
+        assert!(!compare(&inv, &run("12", false), Some(&run("?", true))).passed);
+    }
+}
@@ -1,16 +1,25 @@
 //! Gate verdict evaluator: translates a [`GateVerdict`] into a concrete
 //! [`GateAction`] based on the task's gate policy and retry eligibility.
 
+use std::path::Path;
+
 use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use gator_db::models::GatePolicy;
+use gator_db::models::{GatePolicy, Task};
+use gator_db::queries::auto_approvals;
+use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 
+use crate::harness::Harness;
 use crate::state::dispatch;
+use crate::worktree::{self, WorktreeManager};
 
 use super::GateVerdict;
+use super::auto_approve::{AutoApproveRule, find_match};
+use super::llm_review::{ReviewVerdict, run_review};
+use super::script::{ScriptDecision, run_gate_script, script_input, script_path};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -43,17 +52,64 @@ pub enum GateAction {
 /// - **`auto`**: Automatically transition the task to `passed` or `failed`
 ///   based on the verdict. When failing, checks retry eligibility.
 /// - **`human_review`** / **`human_approve`**: Leave the task in `checking`
-///   state and return [`GateAction::HumanRequired`].
+///   state and return [`GateAction::HumanRequired`]. A passing
+///   `human_review` task whose changes match one of `auto_approve` passes
+///   instead (see [`super::auto_approve`]); the match is recorded.
+/// - **`script:<path>`** (stored as `auto` with a `gate_script`): the gate
+///   script decides between the two (see [`super::script`]). A script that
+///   fails or prints no decision leaves the task for a human.
+/// - **`llm_review`** (stored as `auto` with `llm_review` set): failing
+///   invariants fail the task; passing ones go to `reviewer` (see
+///   [`super::llm_review`]), whose change requests fail it with their comments
+///   recorded as review feedback. A review that cannot be had leaves the
+///   task for a human.
 pub async fn evaluate_verdict(
     pool: &SqlitePool,
     task_id: Uuid,
     verdict: &GateVerdict,
+    reviewer: Option<&dyn Harness>,
+    auto_approve: &[AutoApproveRule],
 ) -> Result<GateAction> {
     let task = task_db::get_task(pool, task_id)
         .await?
         .with_context(|| format!("task {} not found", task_id))?;
 
     match task.gate_policy {
+        GatePolicy::Auto if task.gate_script.is_some() => {
+            match run_script(pool, &task, verdict).await {
+                Ok(ScriptDecision::Pass) => {
+                    dispatch::pass_task(pool, task_id).await?;
+                    Ok(GateAction::AutoPassed)
+                }
+                Ok(ScriptDecision::Fail) => {
+                    dispatch::fail_task(pool, task_id).await?;
+                    let can_retry = task.attempt < task.retry_max;
+                    Ok(GateAction::AutoFailed { can_retry })
+                }
+                Ok(ScriptDecision::Human) => Ok(GateAction::HumanRequired),
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %format!("{e:#}"), "gate script failed; leaving task for human review");
+                    Ok(GateAction::HumanRequired)
+                }
+            }
+        }
+        GatePolicy::Auto if task.llm_review && matches!(verdict, GateVerdict::Passed) => {
+            match review(pool, &task, reviewer).await {
+                Ok(ReviewVerdict::Approve) => {
+                    dispatch::pass_task(pool, task_id).await?;
+                    Ok(GateAction::AutoPassed)
+                }
+                Ok(ReviewVerdict::RequestChanges { comments }) => {
+                    dispatch::reject_task(pool, task_id, Some(&comments)).await?;
+                    let can_retry = task.attempt < task.retry_max;
+                    Ok(GateAction::AutoFailed { can_retry })
+                }
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %format!("{e:#}"), "LLM review failed; leaving task for human review");
+                    Ok(GateAction::HumanRequired)
+                }
+            }
+        }
         GatePolicy::Auto => match verdict {
             GateVerdict::Passed => {
                 dispatch::pass_task(pool, task_id).await?;
@@ -65,9 +121,110 @@ pub async fn evaluate_verdict(
                 Ok(GateAction::AutoFailed { can_retry })
             }
         },
+        GatePolicy::HumanReview
+            if !auto_approve.is_empty() && matches!(verdict, GateVerdict::Passed) =>
+        {
+            match try_auto_approve(pool, &task, auto_approve).await {
+                Ok(true) => Ok(GateAction::AutoPassed),
+                Ok(false) => Ok(GateAction::HumanRequired),
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %format!("{e:#}"), "could not check auto-approval rules; leaving task for human review");
+                    Ok(GateAction::HumanRequired)
+                }
+            }
+        }
         GatePolicy::HumanReview | GatePolicy::HumanApprove => {
             // Leave the task in checking state for human decision.
             Ok(GateAction::HumanRequired)
         }
     }
 }
+
+/// Pass the task if its changes since it branched match an auto-approval
+/// rule, and record the rule. Returns whether it passed.
+async fn try_auto_approve(
+    pool: &SqlitePool,
+    task: &Task,
+    rules: &[AutoApproveRule],
+) -> Result<bool> {
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let worktree = task
+        .worktree_path
+        .as_deref()
+        .map(Path::new)
+        .with_context(|| format!("task {} has no worktree_path set", task.id))?;
+    let manager = WorktreeManager::new(&plan.project_path, None)?;
+    let base = manager
+        .merge_base(worktree, &plan.base_branch)?
+        .with_context(|| {
+            format!(
+                "cannot find where the task branched from {}",
+                plan.base_branch
+            )
+        })?;
+    let changes = worktree::diff_stat(worktree, &base)?;
+
+    let Some(approval) = find_match(rules, &plan.name, &changes) else {
+        return Ok(false);
+    };
+    dispatch::pass_task(pool, task.id).await?;
+    auto_approvals::insert_auto_approval(
+        pool,
+        task.id,
+        task.attempt,
+        &approval.rule.name,
+        i64::try_from(approval.diff_lines).unwrap_or(i64::MAX),
+        &approval.files,
+    )
+    .await?;
+    tracing::info!(
+        task_id = %task.id,
+        rule = %approval.rule.name,
+        diff_lines = approval.diff_lines,
+        files = approval.files.len(),
+        "auto-approved human_review task"
+    );
+    Ok(true)
+}
+
+/// Run the task's gate script on the current attempt's results.
+async fn run_script(
+    pool: &SqlitePool,
+    task: &Task,
+    verdict: &GateVerdict,
+) -> Result<ScriptDecision> {
+    let script = task
+        .gate_script
+        .as_deref()
+        .context("task has no gate script")?;
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let worktree = task
+        .worktree_path
+        .as_deref()
+        .with_context(|| format!("task {} has no worktree_path set", task.id))?;
+    let input = script_input(pool, task, verdict).await?;
+    run_gate_script(
+        &script_path(script, &plan.project_path),
+        Path::new(worktree),
+        &input,
+    )
+    .await
+}
+
+/// Have the reviewer review the task's worktree.
+async fn review(
+    pool: &SqlitePool,
+    task: &Task,
+    reviewer: Option<&dyn Harness>,
+) -> Result<ReviewVerdict> {
+    let reviewer = reviewer.context("no reviewer harness available")?;
+    let worktree = task
+        .worktree_path
+        .as_deref()
+        .with_context(|| format!("task {} has no worktree_path set", task.id))?;
+    run_review(pool, reviewer, task, Path::new(worktree)).await
+}
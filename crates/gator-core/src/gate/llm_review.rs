@@ -0,0 +1,226 @@
+//! LLM review gates: a reviewer agent has the last word on a change.
+//!
+//! A task with `gate = "llm_review"` runs its invariants like an `auto`
+//! gate. Once they pass, a reviewer agent gets the task description and the
+//! worktree's diff and ends its reply with a JSON verdict:
+//!
+//! ```json
+//! {"verdict": "request_changes", "comments": "The retry loop never backs off."}
+//! ```
+//!
+//! An approval passes the task; a change request fails it and records the
+//! comments as review feedback, which the next attempt's prompt quotes.
+//!
+//! The reviewer runs in an empty scratch directory with no agent token, so
+//! it can neither edit the change it judges nor report on the task.
+//! `llm_review:<harness>` picks a different harness (and so model) for it.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use gator_db::models::Task;
+use gator_db::queries::agent_events::{self, NewAgentEvent};
+
+use crate::harness::Harness;
+use crate::harness::types::{AgentEvent, MaterializedTask};
+use crate::plan::materialize::truncate_feedback_snippet;
+use crate::worktree;
+
+/// How long a reviewer may take before it is killed.
+pub const REVIEW_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Diffs longer than this are truncated in the review prompt.
+pub const MAX_REVIEW_DIFF_BYTES: usize = 100_000;
+
+/// What a reviewer decided.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    Approve,
+    RequestChanges { comments: String },
+}
+
+/// Build the reviewer's prompt from the task and its diff.
+pub fn review_prompt(task: &Task, diff: &str) -> String {
+    let mut out = format!("# Review: {}\n\n", task.name);
+    out.push_str(
+        "Another agent made the change below for the task that follows. It \
+         already passes the task's automated checks. Review it: does it do what \
+         the task asks, correctly and completely? Do not modify any files.\n\n",
+    );
+    out.push_str("## Task\n\n");
+    out.push_str(task.description.trim());
+    out.push_str("\n\n## Diff\n\n```diff\n");
+    out.push_str(&truncate_feedback_snippet(diff, MAX_REVIEW_DIFF_BYTES));
+    out.push_str("\n```\n\n## Verdict\n\n");
+    out.push_str(
+        "End your reply with a JSON object on its own line, either\n\n\
+         {\"verdict\": \"approve\"}\n\nor\n\n\
+         {\"verdict\": \"request_changes\", \"comments\": \"<what must change>\"}\n\n\
+         Comments go to the author as-is, so make them specific enough to act on.\n",
+    );
+    out
+}
+
+/// Have `reviewer` review the uncommitted changes in `worktree_path`.
+///
+/// Token usage is recorded on the task's current attempt, so reviews count
+/// toward the plan's budget.
+///
+/// # Errors
+///
+/// Fails if the diff cannot be read, the reviewer cannot be spawned, runs
+/// past [`REVIEW_TIMEOUT`], or does not end its reply with a verdict.
+pub async fn run_review(
+    pool: &SqlitePool,
+    reviewer: &dyn Harness,
+    task: &Task,
+    worktree_path: &Path,
+) -> Result<ReviewVerdict> {
+    let diff = worktree::working_diff(worktree_path)
+        .with_context(|| format!("failed to diff worktree {}", worktree_path.display()))?;
+    let scratch = tempfile::tempdir().context("failed to create reviewer directory")?;
+    let materialized = MaterializedTask {
+        task_id: task.id,
+        name: format!("{}-review", task.name),
+        description: review_prompt(task, &diff),
+        invariant_commands: Vec::new(),
+        working_dir: scratch.path().to_path_buf(),
+        env_vars: HashMap::new(),
+    };
+
+    let mut handle = reviewer
+        .spawn(&materialized)
+        .await
+        .with_context(|| format!("failed to spawn reviewer for task {}", task.name))?;
+    if let Some(mut stdin) = handle.stdin.take() {
+        use tokio::io::AsyncWriteExt;
+        if let Err(e) = stdin.write_all(materialized.description.as_bytes()).await {
+            tracing::warn!(task_id = %task.id, error = %e, "failed to write prompt to reviewer stdin");
+        }
+    }
+
+    let mut events = reviewer.events(&handle);
+    let mut reply = String::new();
+    let collect = async {
+        while let Some(event) = events.next().await {
+            match event {
+                AgentEvent::Message { role, content } if role == "assistant" => {
+                    reply.push_str(&content);
+                    reply.push('\n');
+                }
+                AgentEvent::TokenUsage {
+                    input_tokens,
+                    output_tokens,
+                } => {
+                    let usage = NewAgentEvent {
+                        task_id: task.id,
+                        attempt: task.attempt,
+                        event_type: "token_usage".to_string(),
+                        payload: serde_json::json!({
+                            "input_tokens": input_tokens,
+                            "output_tokens": output_tokens,
+                        }),
+                    };
+                    if let Err(e) = agent_events::insert_agent_event(pool, &usage).await {
+                        tracing::warn!(task_id = %task.id, error = %e, "failed to record reviewer token usage");
+                    }
+                }
+                AgentEvent::Completed => break,
+                _ => {}
+            }
+        }
+    };
+    if tokio::time::timeout(REVIEW_TIMEOUT, collect).await.is_err() {
+        if let Err(e) = reviewer.kill(&handle).await {
+            tracing::warn!(task_id = %task.id, error = %e, "failed to kill timed-out reviewer");
+        }
+        anyhow::bail!("reviewer timed out after {}s", REVIEW_TIMEOUT.as_secs());
+    }
+
+    parse_verdict(&reply).context("reviewer did not end its reply with a verdict")
+}
+
+/// The verdict in the last JSON object of a reviewer's reply that has one.
+fn parse_verdict(reply: &str) -> Option<ReviewVerdict> {
+    reply
+        .rmatch_indices('{')
+        .find_map(|(start, _)| {
+            let value = serde_json::Deserializer::from_str(&reply[start..])
+                .into_iter::<Value>()
+                .next()?
+                .ok()?;
+            let verdict = value.get("verdict")?.as_str()?.to_ascii_lowercase();
+            Some((verdict, value))
+        })
+        .and_then(
+            |(verdict, value)| match verdict.replace('-', "_").as_str() {
+                "approve" => Some(ReviewVerdict::Approve),
+                "request_changes" => Some(ReviewVerdict::RequestChanges {
+                    comments: comments(&value),
+                }),
+                _ => None,
+            },
+        )
+}
+
+/// A verdict's comments, given as a string or a list of strings.
+fn comments(value: &Value) -> String {
+    let comments = match value.get("comments") {
+        Some(Value::String(s)) => s.trim().to_string(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|c| format!("- {}", c.trim()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+    if comments.is_empty() {
+        "The reviewer requested changes without saying which.".to_string()
+    } else {
+        comments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdict_is_the_last_json_object_with_one() {
+        let reply = "Looks good overall.\n\n{\"verdict\": \"approve\"}\n";
+        assert_eq!(parse_verdict(reply), Some(ReviewVerdict::Approve));
+
+        let reply = "I first thought {\"verdict\": \"approve\"}, but the tests skip \
+                     the error path.\n{\"verdict\": \"request-changes\", \
+                     \"comments\": [\"Test the error path\", \"Drop the debug print\"]}";
+        assert_eq!(
+            parse_verdict(reply),
+            Some(ReviewVerdict::RequestChanges {
+                comments: "- Test the error path\n- Drop the debug print".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn missing_or_unknown_verdict_is_none() {
+        assert_eq!(parse_verdict("LGTM"), None);
+        assert_eq!(parse_verdict("{\"verdict\": \"maybe\"}"), None);
+        assert_eq!(parse_verdict("{\"comments\": \"x\"}"), None);
+    }
+
+    #[test]
+    fn change_request_without_comments_says_so() {
+        let verdict = parse_verdict("{\"verdict\": \"request_changes\"}");
+        let Some(ReviewVerdict::RequestChanges { comments }) = verdict else {
+            panic!("expected a change request, got {verdict:?}");
+        };
+        assert!(comments.contains("without saying"));
+    }
+}
@@ -4,22 +4,74 @@
 //! The gate runner transitions a task into the `checking` state, executes
 //! every linked invariant in the task's worktree directory, records each
 //! result in the `gate_results` table, and returns a [`GateVerdict`].
+//!
+//! Tasks that declare `paths` also get a built-in `scope` check: it fails
+//! if anything outside those patterns was modified. Disabled invariants are
+//! skipped.
+//!
+//! Invariants run concurrently, up to the runner's parallelism limit. An
+//! invariant whose `run_after` names others in the same gate starts only
+//! once those have finished (whether they passed or not), so e.g. a test
+//! suite can wait for the build instead of racing it for the build
+//! directory.
+//!
+//! Invariants with `diff_base` set are also run on the commit the task
+//! branched from, and fail only when the task regresses the number they
+//! print (see [`diff`]).
+//!
+//! When an invariant that declares `artifacts` fails, the files its globs
+//! match (screenshots, videos) are copied out of the worktree and attached
+//! to its gate result (see [`artifacts`]).
+//!
+//! A passing `human_review` gate can be approved without a human by an
+//! operator-configured rule (see [`auto_approve`]).
+//!
+//! With attestation on, a code task whose invariants pass gets a compliance
+//! attestation of its change (see [`attestation`]).
+//!
+//! Each result is stored with a hash of the worktree contents (see
+//! [`crate::worktree::content_hash`]), the invariant's definition, and the
+//! gate environment. When a retry leaves the tree identical to an earlier
+//! attempt, the earlier result is reused instead of running the invariant
+//! again, unless caching is turned off. Scope and artifact checks always
+//! run.
 
+pub mod artifacts;
+pub mod attestation;
+pub mod auto_approve;
+pub mod diff;
 pub mod evaluator;
+pub mod llm_review;
+pub mod script;
 
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
+use tracing::Instrument;
 use uuid::Uuid;
 
-use gator_db::models::Invariant;
+use gator_db::models::{Invariant, Task, TaskKind};
+use gator_db::queries::artifact_checks::{self, NewArtifactCheck};
+use gator_db::queries::gate_artifacts::{self, NewGateArtifact};
 use gator_db::queries::gate_results::{self, NewGateResult};
 use gator_db::queries::invariants as inv_db;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::pr_reviews;
 use gator_db::queries::tasks as task_db;
 
-use crate::invariant::runner::{InvariantResult, run_invariant};
+use crate::artifact::{self, ArtifactCheckResult};
+use crate::invariant::runner::{InvariantResult, run_invariant_with_env};
+use crate::review;
 use crate::state::dispatch;
+use crate::worktree::{self, WorktreeManager};
+
+/// How many invariants a gate runs at once unless configured otherwise.
+pub const DEFAULT_GATE_PARALLELISM: usize = 4;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -44,6 +96,8 @@ pub struct GateFailure {
     pub invariant_name: String,
     /// The process exit code, or `None` if killed by signal.
     pub exit_code: Option<i32>,
+    /// The invariant ran past its timeout and was killed.
+    pub timed_out: bool,
     /// A truncated snippet of stderr output (up to 1024 bytes).
     pub stderr_snippet: String,
 }
@@ -56,20 +110,52 @@ pub struct GateFailure {
 /// recording the results.
 pub struct GateRunner<'a> {
     pool: &'a SqlitePool,
+    env: HashMap<String, String>,
+    parallelism: usize,
+    cache: bool,
 }
 
 impl<'a> GateRunner<'a> {
     /// Create a new `GateRunner` backed by the given connection pool.
     pub fn new(pool: &'a SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            env: HashMap::new(),
+            parallelism: DEFAULT_GATE_PARALLELISM,
+            cache: true,
+        }
+    }
+
+    /// Set extra environment variables for every invariant command.
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Run at most `parallelism` invariants at once (at least one).
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Reuse an earlier attempt's result for an invariant when the worktree
+    /// is identical (on by default). Results are recorded with their hash
+    /// either way.
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
     }
 
     /// Run all gate checks for a task.
     ///
     /// 1. Transitions the task from `running` to `checking`.
     /// 2. Looks up all invariants linked to the task.
-    /// 3. Runs each invariant in the task's worktree directory.
-    /// 4. Records every result in the `gate_results` table.
+    /// 3. Runs the invariants in the task's worktree directory, concurrently
+    ///    up to the parallelism limit and in `run_after` order.
+    /// 4. Records every result in the `gate_results` table. Artifact tasks
+    ///    also run the built-in artifact checks (plus the review check for PR
+    ///    reviews), and tasks with `paths` the scope check, all recorded in
+    ///    `artifact_checks`.
     /// 5. Returns [`GateVerdict::Passed`] if all invariants passed,
     ///    or [`GateVerdict::Failed`] with details for each failure.
     pub async fn run_gate(&self, task_id: Uuid) -> Result<GateVerdict> {
@@ -91,27 +177,171 @@ impl<'a> GateRunner<'a> {
         // 3. Look up linked invariants.
         let invariants = inv_db::get_invariants_for_task(self.pool, task_id).await?;
 
-        if invariants.is_empty() {
+        // Artifact tasks are gated on their artifacts even without invariants.
+        if invariants.is_empty() && task.kind != TaskKind::Artifact {
             bail!("task {} has no linked invariants; cannot run gate", task_id);
         }
 
-        // 4. Run each invariant and collect results.
-        let mut failures = Vec::new();
+        // Disabled invariants stay linked but are not run.
+        let (disabled, invariants): (Vec<Invariant>, Vec<Invariant>) = invariants
+            .into_iter()
+            .partition(|i| i.disabled_at.is_some());
+        for invariant in &disabled {
+            tracing::info!(task_id = %task_id, invariant = %invariant.name, "skipping disabled invariant");
+        }
 
-        for invariant in &invariants {
-            let inv_result = self
-                .run_and_record(task_id, task.attempt, invariant, working_dir)
-                .await?;
+        let plan = if invariants
+            .iter()
+            .any(|i| i.diff_base || !i.artifacts.is_empty())
+            || !task.paths.is_empty()
+        {
+            Some(
+                plan_db::get_plan(self.pool, task.plan_id)
+                    .await?
+                    .with_context(|| format!("plan {} not found", task.plan_id))?,
+            )
+        } else {
+            None
+        };
 
+        // Differential invariants also run on the task's base commit.
+        let base_checkout = match &plan {
+            Some(plan) if invariants.iter().any(|i| i.diff_base) => {
+                diff::BaseCheckout::create(&plan.project_path, &plan.base_branch, working_dir)?
+            }
+            _ => None,
+        };
+        let base_dir = base_checkout.as_ref().map(diff::BaseCheckout::path);
+
+        // Where files of failing invariants that declare artifacts go.
+        let failure_dir = plan.as_ref().map(|plan| {
+            artifacts::failure_dir(
+                Path::new(&plan.project_path),
+                &plan.name,
+                &task.name,
+                task.attempt,
+            )
+        });
+        let failure_dir = failure_dir.as_deref();
+
+        // Without a hash, results are recorded but never reused.
+        let tree_hash = match tree_hash(working_dir, base_dir) {
+            Ok(hash) => Some(hash),
+            Err(e) => {
+                tracing::warn!(task_id = %task_id, error = %e, "could not hash worktree; gate results will not be cached");
+                None
+            }
+        };
+
+        // 4. Run the invariants and collect results, in definition order.
+        let task = &task;
+        let prerequisites = prerequisites(&invariants);
+        let mut results: Vec<Option<InvariantResult>> = vec![None; invariants.len()];
+        let mut waiting: Vec<usize> = (0..invariants.len()).collect();
+        let mut running = FuturesUnordered::new();
+        loop {
+            while running.len() < self.parallelism {
+                let ready = waiting
+                    .iter()
+                    .position(|&i| prerequisites[i].iter().all(|&p| results[p].is_some()));
+                let next = match ready {
+                    Some(pos) => waiting.remove(pos),
+                    // Only a `run_after` cycle leaves nothing ready with
+                    // nothing running; break it rather than stall.
+                    None if running.is_empty() && !waiting.is_empty() => {
+                        tracing::warn!(
+                            task_id = %task_id,
+                            invariant = %invariants[waiting[0]].name,
+                            "run_after cycle; running invariant without waiting"
+                        );
+                        waiting.remove(0)
+                    }
+                    None => break,
+                };
+                let invariant = &invariants[next];
+                let content_hash = tree_hash.as_deref().map(|h| self.cache_key(h, invariant));
+                running.push(async move {
+                    let result = self
+                        .run_and_record(
+                            task,
+                            invariant,
+                            working_dir,
+                            base_dir,
+                            failure_dir,
+                            content_hash,
+                        )
+                        .await;
+                    (next, result)
+                });
+            }
+            let Some((i, result)) = running.next().await else {
+                break;
+            };
+            results[i] = Some(result?);
+        }
+
+        let mut failures = Vec::new();
+        for (invariant, inv_result) in invariants.iter().zip(results.into_iter().flatten()) {
             if !inv_result.passed {
                 failures.push(GateFailure {
                     invariant_name: invariant.name.clone(),
                     exit_code: inv_result.exit_code,
+                    timed_out: inv_result.timed_out,
                     stderr_snippet: truncate_snippet(&inv_result.stderr, 1024),
                 });
             }
         }
 
+        // 4b. Check declared artifacts of artifact tasks.
+        let mut checks = Vec::new();
+        if task.kind == TaskKind::Artifact {
+            checks = artifact::check_artifacts(working_dir, &task.artifacts);
+            // PR reviews must also cover every file the PR changes.
+            if let Some(pr_review) = pr_reviews::get_pr_review_for_task(self.pool, task_id).await? {
+                if working_dir.join(review::REVIEW_FILE).is_file() {
+                    checks.push(review::check_review(working_dir, &pr_review.changed_files));
+                }
+            }
+        }
+
+        // 4c. Tasks that declare paths may not modify anything else.
+        if let Some(plan) = plan.as_ref().filter(|_| !task.paths.is_empty()) {
+            checks.push(check_scope(
+                &plan.project_path,
+                &plan.base_branch,
+                working_dir,
+                &task.paths,
+            )?);
+        }
+
+        for check in checks {
+            artifact_checks::insert_artifact_check(
+                self.pool,
+                &NewArtifactCheck {
+                    task_id,
+                    attempt: task.attempt,
+                    path: check.path.clone(),
+                    check_name: check.check.to_string(),
+                    passed: check.passed,
+                    message: (!check.passed).then(|| check.message.clone()),
+                },
+            )
+            .await?;
+            if !check.passed {
+                let invariant_name = if check.check == "scope" {
+                    format!("scope ({})", check.path)
+                } else {
+                    format!("artifact {} ({})", check.path, check.check)
+                };
+                failures.push(GateFailure {
+                    invariant_name,
+                    exit_code: None,
+                    timed_out: false,
+                    stderr_snippet: truncate_snippet(&check.message, 1024),
+                });
+            }
+        }
+
         // 5. Return verdict.
         if failures.is_empty() {
             Ok(GateVerdict::Passed)
@@ -120,15 +350,139 @@ impl<'a> GateRunner<'a> {
         }
     }
 
+    /// The hash a result of `invariant` is stored under: the tree hash plus
+    /// everything about the invariant and its environment that can change
+    /// the outcome.
+    fn cache_key(&self, tree_hash: &str, invariant: &Invariant) -> String {
+        let definition = serde_json::json!({
+            "command": invariant.command,
+            "args": invariant.args,
+            "expected_exit_code": invariant.expected_exit_code,
+            "threshold": invariant.threshold,
+            "timeout_secs": invariant.timeout_secs,
+            "diff_base": invariant.diff_base,
+            "lower_is_better": invariant.lower_is_better,
+            "env": invariant.env,
+            "workdir": invariant.workdir,
+            "gate_env": self.env.iter().collect::<BTreeMap<_, _>>(),
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(tree_hash);
+        hasher.update([0]);
+        hasher.update(definition.to_string());
+        hex::encode(hasher.finalize())
+    }
+
     /// Run a single invariant and record its result in the DB.
+    ///
+    /// A `diff_base` invariant is also run in `base_dir`, when there is one,
+    /// and its verdict comes from [`diff::compare`]. With caching on, an
+    /// earlier attempt's result stored under the same `content_hash` is
+    /// recorded again instead, with its artifacts.
+    ///
+    /// When the invariant fails and declares `artifacts`, the matching
+    /// files are copied into a subdirectory of `failure_dir` named after
+    /// it. A failure to collect them is logged, not returned.
     async fn run_and_record(
         &self,
-        task_id: Uuid,
-        attempt: i32,
+        task: &Task,
         invariant: &Invariant,
         working_dir: &Path,
+        base_dir: Option<&Path>,
+        failure_dir: Option<&Path>,
+        content_hash: Option<String>,
     ) -> Result<InvariantResult> {
-        let result = run_invariant(invariant, working_dir).await?;
+        let (task_id, attempt) = (task.id, task.attempt);
+        let cached = match &content_hash {
+            Some(hash) if self.cache => {
+                gate_results::find_cached_gate_result(
+                    self.pool,
+                    task_id,
+                    invariant.id,
+                    attempt,
+                    hash,
+                )
+                .await?
+            }
+            _ => None,
+        };
+        if let Some(cached) = cached {
+            let cached_from = cached.cached_from.unwrap_or(cached.attempt);
+            tracing::info!(
+                task_id = %task_id,
+                invariant = %invariant.name,
+                cached_from,
+                "worktree unchanged; reusing gate result"
+            );
+            let new_result = NewGateResult {
+                task_id,
+                invariant_id: invariant.id,
+                attempt,
+                passed: cached.passed,
+                exit_code: cached.exit_code,
+                stdout: cached.stdout.clone(),
+                stderr: cached.stderr.clone(),
+                duration_ms: cached.duration_ms,
+                timed_out: cached.timed_out,
+                base_value: cached.base_value,
+                task_value: cached.task_value,
+                content_hash,
+                cached_from: Some(cached_from),
+            };
+            let recorded = gate_results::insert_gate_result(self.pool, &new_result)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to record gate result for invariant {:?}",
+                        invariant.name
+                    )
+                })?;
+            gate_artifacts::copy_gate_artifacts(self.pool, cached.id, recorded.id).await?;
+            return Ok(InvariantResult {
+                passed: cached.passed,
+                exit_code: cached.exit_code,
+                timed_out: cached.timed_out,
+                stdout: cached.stdout.unwrap_or_default(),
+                stderr: cached.stderr.unwrap_or_default(),
+                duration_ms: cached.duration_ms.map_or(0, i64::from),
+            });
+        }
+
+        let span = tracing::info_span!(
+            "invariant",
+            invariant = %invariant.name,
+            command = %invariant.command,
+            passed = tracing::field::Empty,
+            exit_code = tracing::field::Empty,
+        );
+        let mut result = run_invariant_with_env(invariant, working_dir, &self.env)
+            .instrument(span.clone())
+            .await?;
+
+        let mut values = (None, None);
+        if invariant.diff_base {
+            let base = match base_dir {
+                Some(dir) => Some(
+                    run_invariant_with_env(invariant, dir, &self.env)
+                        .instrument(span.clone())
+                        .await?,
+                ),
+                None => None,
+            };
+            let outcome = diff::compare(invariant, &result, base.as_ref());
+            result.passed = outcome.passed;
+            if let Some(message) = outcome.message {
+                if !result.stderr.is_empty() && !result.stderr.ends_with('\n') {
+                    result.stderr.push('\n');
+                }
+                result.stderr.push_str(&format!("gator: {message}\n"));
+            }
+            values = (outcome.base_value, outcome.task_value);
+        }
+        span.record("passed", result.passed);
+        if let Some(code) = result.exit_code {
+            span.record("exit_code", code);
+        }
 
         let duration_ms = i32::try_from(result.duration_ms).unwrap_or(i32::MAX);
 
@@ -141,9 +495,14 @@ impl<'a> GateRunner<'a> {
             stdout: Some(result.stdout.clone()),
             stderr: Some(result.stderr.clone()),
             duration_ms: Some(duration_ms),
+            timed_out: result.timed_out,
+            base_value: values.0,
+            task_value: values.1,
+            content_hash,
+            cached_from: None,
         };
 
-        gate_results::insert_gate_result(self.pool, &new_result)
+        let recorded = gate_results::insert_gate_result(self.pool, &new_result)
             .await
             .with_context(|| {
                 format!(
@@ -152,10 +511,104 @@ impl<'a> GateRunner<'a> {
                 )
             })?;
 
+        let failure_dir = failure_dir.filter(|_| !result.passed && !invariant.artifacts.is_empty());
+        if let Some(dir) = failure_dir {
+            let dest = dir.join(artifact_dir_name(&invariant.name));
+            match artifacts::collect(working_dir, &invariant.artifacts, &dest) {
+                Ok(collected) => {
+                    for artifact in collected {
+                        gate_artifacts::insert_gate_artifact(
+                            self.pool,
+                            &NewGateArtifact {
+                                gate_result_id: recorded.id,
+                                path: artifact.path,
+                                stored_path: artifact.stored_path.to_string_lossy().into_owned(),
+                                size_bytes: i64::try_from(artifact.size_bytes).unwrap_or(i64::MAX),
+                            },
+                        )
+                        .await?;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        task_id = %task_id,
+                        invariant = %invariant.name,
+                        error = %e,
+                        "could not collect failure artifacts"
+                    );
+                }
+            }
+        }
+
         Ok(result)
     }
 }
 
+/// Directory name for an invariant's failure artifacts: its name with
+/// anything but letters, digits, `-`, and `_` replaced by `-`.
+fn artifact_dir_name(name: &str) -> PathBuf {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .into()
+}
+
+/// Hash of the worktree, plus the base checkout that `diff_base`
+/// invariants compare against, when there is one.
+fn tree_hash(working_dir: &Path, base_dir: Option<&Path>) -> Result<String> {
+    let mut hash = worktree::content_hash(working_dir)?;
+    if let Some(base) = base_dir {
+        hash.push(':');
+        hash.push_str(&worktree::content_hash(base)?);
+    }
+    Ok(hash)
+}
+
+/// For each invariant, the indices of the invariants in the same gate that
+/// its `run_after` names. Names not in the gate are ignored.
+fn prerequisites(invariants: &[Invariant]) -> Vec<Vec<usize>> {
+    invariants
+        .iter()
+        .map(|invariant| {
+            invariant
+                .run_after
+                .iter()
+                .filter_map(|name| invariants.iter().position(|i| &i.name == name))
+                .collect()
+        })
+        .collect()
+}
+
+/// The `scope` check: every file changed in `worktree` (uncommitted, or
+/// committed since the branch left `base_branch`) must match one of
+/// `paths`. The check's path is the patterns; its message lists the files
+/// outside them.
+fn check_scope(
+    repo_path: &str,
+    base_branch: &str,
+    worktree: &Path,
+    paths: &[String],
+) -> Result<ArtifactCheckResult> {
+    let manager = WorktreeManager::new(repo_path, None)?;
+    let outside = manager.changed_paths_outside(worktree, base_branch, paths)?;
+    Ok(ArtifactCheckResult {
+        path: paths.join(", "),
+        check: "scope",
+        passed: outside.is_empty(),
+        message: outside
+            .iter()
+            .map(|p| format!("modified outside the allowed paths: {p}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    })
+}
+
 /// Truncate a string to at most `max_bytes` bytes, appending "..." if
 /// truncated.
 fn truncate_snippet(s: &str, max_bytes: usize) -> String {
@@ -193,4 +646,41 @@ mod tests {
     fn truncate_empty_string() {
         assert_eq!(truncate_snippet("", 10), "");
     }
+
+    fn invariant(name: &str, run_after: &[&str]) -> Invariant {
+        Invariant {
+            id: Uuid::new_v4(),
+            name: name.to_owned(),
+            description: None,
+            kind: gator_db::models::InvariantKind::Custom,
+            command: "true".to_owned(),
+            args: sqlx::types::Json(vec![]),
+            expected_exit_code: 0,
+            threshold: None,
+            scope: gator_db::models::InvariantScope::Project,
+            timeout_secs: 300,
+            created_at: chrono::Utc::now(),
+            deleted_at: None,
+            diff_base: false,
+            lower_is_better: false,
+            disabled_at: None,
+            env: Default::default(),
+            workdir: None,
+            run_after: sqlx::types::Json(run_after.iter().map(|s| (*s).to_owned()).collect()),
+            artifacts: Default::default(),
+        }
+    }
+
+    #[test]
+    fn prerequisites_resolve_names_within_the_gate() {
+        let invariants = [
+            invariant("test", &["build", "not_in_gate"]),
+            invariant("build", &[]),
+            invariant("lint", &["build", "test"]),
+        ];
+        assert_eq!(
+            prerequisites(&invariants),
+            vec![vec![1], vec![], vec![1, 0]]
+        );
+    }
 }
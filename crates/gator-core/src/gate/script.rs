@@ -0,0 +1,199 @@
+//! Gate scripts: operator-provided programs that decide a gate's verdict.
+//!
+//! A task with `gate = "script:<path>"` runs its invariants like an `auto`
+//! gate, then hands the results to the script as JSON on stdin. The script
+//! runs in the task's worktree and prints `pass`, `fail`, or `human` as the
+//! last line of its stdout. This lets an organization encode its own policy,
+//! e.g. "pass only if two `lint` invariants passed".
+//!
+//! A relative path is resolved against the plan's project, not the
+//! worktree, so the agent cannot rewrite the policy that judges it.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use gator_db::models::Task;
+use gator_db::queries::artifact_checks;
+use gator_db::queries::gate_results;
+use gator_db::queries::invariants as inv_db;
+
+use super::GateVerdict;
+
+/// How long a gate script may run before it is killed.
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a gate script decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptDecision {
+    Pass,
+    Fail,
+    Human,
+}
+
+/// Resolve a task's gate script against the plan's project directory.
+pub fn script_path(script: &str, project_path: &str) -> PathBuf {
+    let path = Path::new(script);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(project_path).join(path)
+    }
+}
+
+/// Build the JSON a gate script receives: the task, the invariant verdict,
+/// and every invariant result and artifact check of the current attempt.
+pub async fn script_input(pool: &SqlitePool, task: &Task, verdict: &GateVerdict) -> Result<Value> {
+    let invariants = inv_db::get_invariants_for_task(pool, task.id).await?;
+    let results = gate_results::get_latest_gate_results(pool, task.id).await?;
+    let checks = artifact_checks::get_artifact_checks(pool, task.id, task.attempt).await?;
+
+    let results: Vec<Value> = results
+        .iter()
+        .map(|r| {
+            let kind = invariants
+                .iter()
+                .find(|i| i.id == r.invariant_id)
+                .map(|i| i.kind.to_string());
+            json!({
+                "name": r.invariant_name,
+                "kind": kind,
+                "passed": r.passed,
+                "exit_code": r.exit_code,
+                "timed_out": r.timed_out,
+                "duration_ms": r.duration_ms,
+                "base_value": r.base_value,
+                "task_value": r.task_value,
+                "cached_from": r.cached_from,
+                "stdout": r.stdout,
+                "stderr": r.stderr,
+            })
+        })
+        .collect();
+    let checks: Vec<Value> = checks
+        .iter()
+        .map(|c| {
+            json!({
+                "path": c.path,
+                "check": c.check_name,
+                "passed": c.passed,
+                "message": c.message,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "task": {
+            "id": task.id,
+            "name": task.name,
+            "kind": task.kind,
+            "attempt": task.attempt,
+            "retry_max": task.retry_max,
+        },
+        "verdict": match verdict {
+            GateVerdict::Passed => "passed",
+            GateVerdict::Failed { .. } => "failed",
+        },
+        "invariants": results,
+        "artifact_checks": checks,
+    }))
+}
+
+/// Run a gate script in `working_dir` with `input` on stdin and parse its
+/// decision.
+///
+/// # Errors
+///
+/// Fails if the script cannot be started, exits non-zero, runs past
+/// [`SCRIPT_TIMEOUT`], or does not end its output with a decision.
+pub async fn run_gate_script(
+    script: &Path,
+    working_dir: &Path,
+    input: &Value,
+) -> Result<ScriptDecision> {
+    let mut child = Command::new(script)
+        .current_dir(working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run gate script {}", script.display()))?;
+
+    // A script that ignores its input may exit before reading it all.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.to_string().as_bytes()).await;
+    }
+
+    let output = tokio::time::timeout(SCRIPT_TIMEOUT, child.wait_with_output())
+        .await
+        .with_context(|| {
+            format!(
+                "gate script {} timed out after {}s",
+                script.display(),
+                SCRIPT_TIMEOUT.as_secs()
+            )
+        })?
+        .with_context(|| format!("failed to wait on gate script {}", script.display()))?;
+
+    if !output.status.success() {
+        bail!(
+            "gate script {} failed ({}): {}",
+            script.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_decision(&stdout).with_context(|| {
+        format!(
+            "gate script {} did not print pass, fail, or human",
+            script.display()
+        )
+    })
+}
+
+/// The decision on the last non-empty line of a script's output.
+fn parse_decision(stdout: &str) -> Option<ScriptDecision> {
+    let last = stdout.lines().rev().find(|l| !l.trim().is_empty())?;
+    match last.trim().to_ascii_lowercase().as_str() {
+        "pass" => Some(ScriptDecision::Pass),
+        "fail" => Some(ScriptDecision::Fail),
+        "human" => Some(ScriptDecision::Human),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decision_is_the_last_non_empty_line() {
+        assert_eq!(
+            parse_decision("2 lint invariants passed\nPASS\n\n"),
+            Some(ScriptDecision::Pass)
+        );
+        assert_eq!(parse_decision("human"), Some(ScriptDecision::Human));
+        assert_eq!(parse_decision("fail\nmaybe"), None);
+        assert_eq!(parse_decision(""), None);
+    }
+
+    #[test]
+    fn relative_scripts_resolve_against_the_project() {
+        assert_eq!(
+            script_path("ci/gate.sh", "/repo"),
+            PathBuf::from("/repo/ci/gate.sh")
+        );
+        assert_eq!(
+            script_path("/opt/gate.sh", "/repo"),
+            PathBuf::from("/opt/gate.sh")
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Prompt size budgeting.
+//!
+//! A materialized task grows with every memory, dependency, and round of
+//! retry feedback, and past a harness's context limit the agent silently
+//! does worse. A [`PromptBudget`] estimates the size of the materialized
+//! task and drops whole `##` sections, lowest priority first, until it
+//! fits. The description and the sections the gate enforces (artifacts and
+//! allowed paths) are never dropped.
+
+use anyhow::{Result, bail};
+
+/// Rough number of characters per token for English prose and code.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Sections that may be trimmed: the key used in `trim_order`, and the
+/// heading `materialize_task` gives the section.
+pub const TRIMMABLE_SECTIONS: &[(&str, &str)] = &[
+    ("memories", "Relevant Past Experience"),
+    ("dependencies", "Dependencies"),
+    ("invariants", "Invariants"),
+    ("feedback", "Previous Attempt Feedback"),
+];
+
+/// Headings of the sections that are always kept.
+const KEPT_SECTIONS: &[&str] = &["Description", "Artifacts", "Allowed Paths"];
+
+/// Trim order used when none is configured: past experience goes first,
+/// feedback on the previous attempt last.
+pub const DEFAULT_TRIM_ORDER: &[&str] = &["memories", "dependencies", "invariants", "feedback"];
+
+/// Estimate the number of tokens `text` takes up.
+///
+/// This is a character-count heuristic, not a tokenizer: it is meant to
+/// catch prompts that are far over a limit, not to land on it exactly.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// An estimated token limit for a materialized task, and the order in which
+/// sections are dropped to meet it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptBudget {
+    pub max_tokens: usize,
+    /// Section keys from [`TRIMMABLE_SECTIONS`], first dropped first.
+    pub trim_order: Vec<String>,
+}
+
+/// A section dropped from a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrimmedSection {
+    /// Section key, e.g. `memories`.
+    pub section: String,
+    pub estimated_tokens: usize,
+}
+
+/// A prompt after fitting it to a [`PromptBudget`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FittedPrompt {
+    pub text: String,
+    /// Estimated size of `text`; still above the budget when every
+    /// trimmable section was dropped and that was not enough.
+    pub estimated_tokens: usize,
+    /// Dropped sections, in the order they were dropped.
+    pub trimmed: Vec<TrimmedSection>,
+}
+
+impl PromptBudget {
+    /// Create a budget, rejecting unknown or repeated section keys.
+    pub fn new(max_tokens: usize, trim_order: Vec<String>) -> Result<Self> {
+        if max_tokens == 0 {
+            bail!("prompt.max_tokens must be greater than zero");
+        }
+        for (i, key) in trim_order.iter().enumerate() {
+            if !TRIMMABLE_SECTIONS.iter().any(|(k, _)| k == key) {
+                let known: Vec<_> = TRIMMABLE_SECTIONS.iter().map(|(k, _)| *k).collect();
+                bail!(
+                    "unknown prompt section {key:?} in prompt.trim_order (expected one of: {})",
+                    known.join(", ")
+                );
+            }
+            if trim_order[..i].contains(key) {
+                bail!("prompt section {key:?} appears twice in prompt.trim_order");
+            }
+        }
+        Ok(Self {
+            max_tokens,
+            trim_order,
+        })
+    }
+
+    /// Drop sections of `prompt` in trim order until its estimated size is
+    /// within the budget. A note naming the dropped sections is appended so
+    /// the agent knows context is missing.
+    pub fn fit(&self, prompt: &str) -> FittedPrompt {
+        let mut sections = split_sections(prompt);
+        let mut trimmed = Vec::new();
+        let mut estimated = estimate_tokens(prompt);
+
+        for key in &self.trim_order {
+            if estimated <= self.max_tokens {
+                break;
+            }
+            let before = sections.len();
+            let mut dropped = 0;
+            sections.retain(|(k, text)| {
+                if k.is_some_and(|k| k == key) {
+                    dropped += estimate_tokens(text);
+                    false
+                } else {
+                    true
+                }
+            });
+            if sections.len() < before {
+                estimated = estimated.saturating_sub(dropped);
+                trimmed.push(TrimmedSection {
+                    section: key.clone(),
+                    estimated_tokens: dropped,
+                });
+            }
+        }
+
+        let mut text: String = sections.into_iter().map(|(_, text)| text).collect();
+        if !trimmed.is_empty() {
+            let headings: Vec<_> = trimmed
+                .iter()
+                .filter_map(|t| heading_of(&t.section))
+                .collect();
+            text.push_str(&format!(
+                "_Left out to fit the prompt size limit: {}._\n",
+                headings.join(", ")
+            ));
+        }
+        FittedPrompt {
+            estimated_tokens: estimate_tokens(&text),
+            text,
+            trimmed,
+        }
+    }
+}
+
+fn heading_of(key: &str) -> Option<&'static str> {
+    TRIMMABLE_SECTIONS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, heading)| *heading)
+}
+
+/// Split a materialized task into its preamble and `##` sections, each
+/// tagged with its trimmable key. Only the headings `materialize_task`
+/// writes start a section, and never inside a code fence, so headings in
+/// descriptions, memories, or feedback snippets stay with their section.
+fn split_sections(prompt: &str) -> Vec<(Option<&'static str>, String)> {
+    let mut sections: Vec<(Option<&'static str>, String)> = vec![(None, String::new())];
+    let mut in_fence = false;
+    for line in prompt.split_inclusive('\n') {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if let Some(heading) = line.strip_prefix("## ").filter(|_| !in_fence) {
+            let heading = heading.trim_end();
+            if let Some((key, _)) = TRIMMABLE_SECTIONS.iter().find(|(_, h)| *h == heading) {
+                sections.push((Some(key), String::new()));
+            } else if KEPT_SECTIONS.contains(&heading) {
+                sections.push((None, String::new()));
+            }
+        }
+        if let Some((_, text)) = sections.last_mut() {
+            text.push_str(line);
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPT: &str = "# Task: t\n\n\
+        ## Description\n\nDo the thing.\n\n\
+        ## Dependencies\n\n- **a**: passed\n\n\
+        ## Relevant Past Experience\n\n### old (passed)\n\n\
+        ```\n## Dependencies\n```\n\n\
+        ## Previous Attempt Feedback\n\nAttempt 0 failed.\n\n";
+
+    fn budget(max_tokens: usize) -> PromptBudget {
+        let order = DEFAULT_TRIM_ORDER.iter().map(|s| s.to_string()).collect();
+        PromptBudget::new(max_tokens, order).unwrap()
+    }
+
+    #[test]
+    fn prompt_within_budget_is_unchanged() {
+        let fitted = budget(10_000).fit(PROMPT);
+        assert_eq!(fitted.text, PROMPT);
+        assert!(fitted.trimmed.is_empty());
+        assert_eq!(fitted.estimated_tokens, estimate_tokens(PROMPT));
+    }
+
+    #[test]
+    fn drops_sections_in_trim_order_until_it_fits() {
+        let without_memories = estimate_tokens(PROMPT)
+            - estimate_tokens(
+                "## Relevant Past Experience\n\n### old (passed)\n\n```\n## Dependencies\n```\n\n",
+            );
+        let fitted = budget(without_memories - 1).fit(PROMPT);
+
+        let dropped: Vec<_> = fitted.trimmed.iter().map(|t| t.section.as_str()).collect();
+        assert_eq!(dropped, ["memories", "dependencies"]);
+        assert!(fitted.text.contains("## Description"));
+        assert!(fitted.text.contains("## Previous Attempt Feedback"));
+        assert!(!fitted.text.contains("### old"));
+        assert!(!fitted.text.contains("**a**"));
+        assert!(fitted.text.ends_with(
+            "_Left out to fit the prompt size limit: Relevant Past Experience, Dependencies._\n"
+        ));
+    }
+
+    #[test]
+    fn description_is_kept_even_when_over_budget() {
+        let fitted = budget(1).fit(PROMPT);
+        assert_eq!(fitted.trimmed.len(), 3);
+        assert!(fitted.text.contains("Do the thing."));
+        assert!(fitted.estimated_tokens > 1);
+    }
+
+    #[test]
+    fn rejects_unknown_and_repeated_sections() {
+        let err = PromptBudget::new(100, vec!["description".into()]).unwrap_err();
+        assert!(err.to_string().contains("description"), "got: {err}");
+        assert!(PromptBudget::new(100, vec!["feedback".into(), "feedback".into()]).is_err());
+        assert!(PromptBudget::new(0, vec![]).is_err());
+    }
+}
@@ -2,28 +2,39 @@
 //!
 //! Spawns `claude -p --output-format stream-json` as a subprocess and
 //! parses its JSONL output into [`AgentEvent`] variants.
+//!
+//! Each `claude -p` process runs a single turn. [`Harness::send`] continues
+//! the conversation in a new process started with `--resume` and the
+//! session ID the previous one reported; it is tracked under the handle's
+//! original pid.
 
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use futures::Stream;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdout, Command};
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
+use super::scaffold::PromptScaffold;
 use super::trait_def::Harness;
 use super::types::{AgentEvent, AgentHandle, MaterializedTask};
 
 /// Internal state kept per spawned process.
 struct ProcessState {
-    /// The child process handle (for kill / is_running).
+    /// The child process handle (for kill / is_running). Replaced when the
+    /// session is resumed.
     child: Child,
     /// Stdout reader; `Option` so it can be `.take()`-ed once for streaming.
     stdout: Option<ChildStdout>,
+    /// The task the agent works on, to build the resume command.
+    task: MaterializedTask,
+    /// Claude Code session ID, once the output has reported it.
+    session_id: Option<String>,
 }
 
 /// Harness adapter for [Claude Code](https://docs.anthropic.com/en/docs/claude-code).
@@ -34,6 +45,8 @@ struct ProcessState {
 pub struct ClaudeCodeAdapter {
     /// Path to the `claude` binary. Defaults to `"claude"` (found via `$PATH`).
     claude_binary_path: String,
+    /// Instructions wrapped around each task (localized).
+    scaffold: PromptScaffold,
     /// Per-process bookkeeping, keyed by OS pid.
     processes: Arc<Mutex<HashMap<u32, ProcessState>>>,
 }
@@ -42,6 +55,7 @@ impl std::fmt::Debug for ClaudeCodeAdapter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ClaudeCodeAdapter")
             .field("claude_binary_path", &self.claude_binary_path)
+            .field("scaffold", &self.scaffold)
             .finish()
     }
 }
@@ -51,6 +65,7 @@ impl ClaudeCodeAdapter {
     pub fn new() -> Self {
         Self {
             claude_binary_path: "claude".to_string(),
+            scaffold: PromptScaffold::default(),
             processes: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -62,9 +77,16 @@ impl ClaudeCodeAdapter {
     pub fn with_binary(path: impl Into<String>) -> Self {
         Self {
             claude_binary_path: path.into(),
+            scaffold: PromptScaffold::default(),
             processes: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Use a different (e.g. localized) prompt scaffold.
+    pub fn with_scaffold(mut self, scaffold: PromptScaffold) -> Self {
+        self.scaffold = scaffold;
+        self
+    }
 }
 
 impl Default for ClaudeCodeAdapter {
@@ -73,10 +95,71 @@ impl Default for ClaudeCodeAdapter {
     }
 }
 
+impl ClaudeCodeAdapter {
+    /// Build the `claude` command for `task`, resuming `session_id` if set.
+    fn command(&self, task: &MaterializedTask, session_id: Option<&str>) -> Command {
+        // Build the system prompt / task instructions that will be appended.
+        let system_instructions = self.scaffold.system_instructions(task);
+
+        // If a container ID is provided, prefix the command with `docker exec -i`.
+        let container_id = task.env_vars.get("GATOR_CONTAINER_ID");
+
+        let mut cmd = if let Some(cid) = container_id {
+            let mut c = Command::new("docker");
+            c.arg("exec")
+                .arg("-i")
+                .arg("-w")
+                .arg(task.working_dir.to_string_lossy().as_ref())
+                .arg(cid)
+                .arg(&self.claude_binary_path);
+            c
+        } else {
+            Command::new(&self.claude_binary_path)
+        };
+
+        cmd.arg("-p")
+            .arg("--output-format")
+            .arg("stream-json")
+            .arg("--verbose")
+            .arg("--dangerously-skip-permissions")
+            .arg("--allowedTools")
+            .arg("Bash,Read,Edit,Write,Glob,Grep")
+            .arg("--disable-slash-commands")
+            .arg("--append-system-prompt")
+            .arg(&system_instructions);
+        if let Some(session_id) = session_id {
+            cmd.arg("--resume").arg(session_id);
+        }
+
+        // Working directory (for non-container mode).
+        if container_id.is_none() {
+            cmd.current_dir(&task.working_dir);
+        }
+
+        // Environment variables (merge, don't replace the entire env).
+        for (key, value) in &task.env_vars {
+            cmd.env(key, value);
+        }
+
+        // We need stdin, stdout, and stderr pipes.
+        cmd.stdin(std::process::Stdio::piped());
+        cmd.stdout(std::process::Stdio::piped());
+        cmd.stderr(std::process::Stdio::null());
+        cmd
+    }
+}
+
 // ---------------------------------------------------------------------------
 // JSONL parsing helpers
 // ---------------------------------------------------------------------------
 
+/// The session ID carried by a stream-json line (the `system` init line and
+/// the final `result` have one), if any.
+fn parse_session_id(line: &str) -> Option<String> {
+    let v: serde_json::Value = serde_json::from_str(line).ok()?;
+    v.get("session_id")?.as_str().map(str::to_string)
+}
+
 /// Parse a single JSONL line from Claude Code's stream-json output into
 /// zero or more `AgentEvent` values.
 ///
@@ -258,63 +341,7 @@ impl Harness for ClaudeCodeAdapter {
     }
 
     async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
-        // Build the system prompt / task instructions that will be appended.
-        let system_instructions = format!(
-            "You are working on task: {name}\n\n{description}\n\n\
-             Available invariant commands:\n{invariants}\n\n\
-             When you are done, run: gator done",
-            name = task.name,
-            description = task.description,
-            invariants = task
-                .invariant_commands
-                .iter()
-                .map(|c| format!("  - {c}"))
-                .collect::<Vec<_>>()
-                .join("\n"),
-        );
-
-        // If a container ID is provided, prefix the command with `docker exec -i`.
-        let container_id = task.env_vars.get("GATOR_CONTAINER_ID");
-
-        let mut cmd = if let Some(cid) = container_id {
-            let mut c = Command::new("docker");
-            c.arg("exec")
-                .arg("-i")
-                .arg("-w")
-                .arg(task.working_dir.to_string_lossy().as_ref())
-                .arg(cid)
-                .arg(&self.claude_binary_path);
-            c
-        } else {
-            Command::new(&self.claude_binary_path)
-        };
-
-        cmd.arg("-p")
-            .arg("--output-format")
-            .arg("stream-json")
-            .arg("--verbose")
-            .arg("--dangerously-skip-permissions")
-            .arg("--allowedTools")
-            .arg("Bash,Read,Edit,Write,Glob,Grep")
-            .arg("--disable-slash-commands")
-            .arg("--append-system-prompt")
-            .arg(&system_instructions);
-
-        // Working directory (for non-container mode).
-        if container_id.is_none() {
-            cmd.current_dir(&task.working_dir);
-        }
-
-        // Environment variables (merge, don't replace the entire env).
-        for (key, value) in &task.env_vars {
-            cmd.env(key, value);
-        }
-
-        // We need stdin, stdout, and stderr pipes.
-        cmd.stdin(std::process::Stdio::piped());
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::null());
-
+        let mut cmd = self.command(task, None);
         let mut child = cmd.spawn().with_context(|| {
             format!(
                 "failed to spawn claude binary at '{}' -- is it installed and on PATH?",
@@ -333,7 +360,15 @@ impl Harness for ClaudeCodeAdapter {
         // is_running().
         {
             let mut processes = self.processes.lock().await;
-            processes.insert(pid, ProcessState { child, stdout });
+            processes.insert(
+                pid,
+                ProcessState {
+                    child,
+                    stdout,
+                    task: task.clone(),
+                    session_id: None,
+                },
+            );
         }
 
         Ok(AgentHandle {
@@ -372,6 +407,7 @@ impl Harness for ClaudeCodeAdapter {
 
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
+            let mut session_known = false;
 
             loop {
                 match lines.next_line().await {
@@ -380,6 +416,18 @@ impl Harness for ClaudeCodeAdapter {
                         if trimmed.is_empty() {
                             continue;
                         }
+                        // Remember the session so `send` can resume it.
+                        let session_id = if session_known {
+                            None
+                        } else {
+                            parse_session_id(trimmed)
+                        };
+                        if let Some(session_id) = session_id {
+                            if let Some(state) = processes.lock().await.get_mut(&pid) {
+                                state.session_id = Some(session_id);
+                            }
+                            session_known = true;
+                        }
                         match parse_stream_json_line(trimmed) {
                             Ok(events) => {
                                 for event in events {
@@ -412,30 +460,34 @@ impl Harness for ClaudeCodeAdapter {
     }
 
     async fn send(&self, handle: &AgentHandle, message: &str) -> Result<()> {
-        // AgentHandle.stdin is Option<ChildStdin>, but we only have &handle,
-        // not &mut handle. For real usage the caller must hold &mut handle
-        // and take() the stdin. However, the trait signature uses &AgentHandle.
-        //
-        // We fall back to a stored reference, but ideally the caller should
-        // have taken stdin from the handle and written directly.
-        //
-        // For now: bail with a descriptive error since we cannot mutate the
-        // handle through a shared reference. A production implementation
-        // would use interior mutability on the handle or store stdin in the
-        // adapter's process map. Let's store a channel instead -- but to keep
-        // it simple, we note that `send` is primarily for --resume flows
-        // and stdin is typically written before events() consumes stdout.
-        //
-        // Practical approach: we don't have the stdin here, but we can still
-        // write to /proc/<pid>/fd/0 on Linux. That won't work portably.
-        // Instead, let's just log a warning for now. This will be refined
-        // when conversation continuation is implemented in T013.
-        let _ = handle;
-        let _ = message;
-        bail!(
-            "send() is not yet supported for ClaudeCodeAdapter -- \
-             conversation continuation via --resume will be added in T013"
-        )
+        let mut processes = self.processes.lock().await;
+        let state = processes
+            .get_mut(&handle.pid)
+            .with_context(|| format!("no agent process {} to continue", handle.pid))?;
+        let session_id = state.session_id.clone().with_context(|| {
+            format!("agent process {} reported no session to resume", handle.pid)
+        })?;
+
+        let mut child = self
+            .command(&state.task, Some(&session_id))
+            .spawn()
+            .with_context(|| {
+                format!(
+                    "failed to spawn claude binary at '{}' to resume session {session_id}",
+                    self.claude_binary_path
+                )
+            })?;
+        // The message is the new turn's prompt; closing stdin starts it.
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(message.as_bytes())
+                .await
+                .context("failed to write message to agent stdin")?;
+        }
+        debug!(pid = handle.pid, new_pid = ?child.id(), session_id, "resumed agent session");
+        state.stdout = child.stdout.take();
+        state.child = child;
+        Ok(())
     }
 
     async fn kill(&self, handle: &AgentHandle) -> Result<()> {
@@ -444,6 +496,8 @@ impl Harness for ClaudeCodeAdapter {
         let mut processes = self.processes.lock().await;
 
         if let Some(state) = processes.get_mut(&pid) {
+            // The current process: after `send`, no longer `pid` itself.
+            let current_pid = state.child.id().unwrap_or(pid);
             // First attempt: SIGTERM via Child::kill() which on Unix sends
             // SIGKILL. We want SIGTERM first, so use nix/libc if available.
             // Since we target macOS/Linux, use libc::kill directly.
@@ -452,7 +506,7 @@ impl Harness for ClaudeCodeAdapter {
             #[cfg(unix)]
             {
                 // SAFETY: pid is a valid u32 from a child we spawned.
-                let ret = unsafe { libc::kill(pid as i32, libc::SIGTERM) };
+                let ret = unsafe { libc::kill(current_pid as i32, libc::SIGTERM) };
                 if ret != 0 {
                     warn!(pid, "SIGTERM failed, proceeding to SIGKILL");
                 }
@@ -490,11 +544,8 @@ impl Harness for ClaudeCodeAdapter {
             // try_wait returns Ok(Some(status)) if exited, Ok(None) if still
             // running, Err on failure.
             match state.child.try_wait() {
-                Ok(Some(_status)) => {
-                    // Exited -- clean up.
-                    processes.remove(&pid);
-                    false
-                }
+                // Exited. The state stays so `send` can resume the session.
+                Ok(Some(_status)) => false,
                 Ok(None) => true,
                 Err(e) => {
                     warn!(pid, error = %e, "error checking process status");
@@ -505,6 +556,37 @@ impl Harness for ClaudeCodeAdapter {
             false
         }
     }
+
+    async fn agent_pid(&self, handle: &AgentHandle) -> u32 {
+        let processes = self.processes.lock().await;
+        processes
+            .get(&handle.pid)
+            .and_then(|state| state.child.id())
+            .unwrap_or(handle.pid)
+    }
+
+    async fn version(&self) -> Option<String> {
+        let output = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            Command::new(&self.claude_binary_path)
+                .arg("--version")
+                .stdin(std::process::Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .ok()?
+        .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .map(str::to_string)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -834,6 +916,28 @@ mod tests {
         assert!(!adapter.is_running(&handle).await);
     }
 
+    #[tokio::test]
+    async fn version_reports_the_first_line_of_the_binary_output() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script_path = tmp.path().join("versioned_claude.sh");
+        std::fs::write(&script_path, "#!/bin/sh\necho '1.2.3 (Claude Code)'\n").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let adapter = ClaudeCodeAdapter::with_binary(script_path.to_str().unwrap());
+        assert_eq!(
+            adapter.version().await.as_deref(),
+            Some("1.2.3 (Claude Code)")
+        );
+
+        let missing = ClaudeCodeAdapter::with_binary(tmp.path().join("missing").to_str().unwrap());
+        assert_eq!(missing.version().await, None);
+    }
+
     #[tokio::test]
     async fn is_running_returns_false_after_exit() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1020,6 +1124,72 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn send_resumes_the_reported_session_in_a_new_process() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script_path = tmp.path().join("session_claude.sh");
+        // The first run reports a session; a resumed run echoes its prompt.
+        std::fs::write(
+            &script_path,
+            "#!/bin/sh\n\
+             case \"$*\" in\n\
+               *'--resume sess-1'*) echo \"{\\\"type\\\":\\\"result\\\",\\\"result\\\":\\\"resumed: $(cat)\\\"}\" ;;\n\
+               *) echo '{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"sess-1\"}' ;;\n\
+             esac\n",
+        )
+        .unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let adapter = ClaudeCodeAdapter::with_binary(script_path.to_str().unwrap());
+        let task = test_task(tmp.path());
+
+        let handle = adapter.spawn(&task).await.unwrap();
+        let events: Vec<AgentEvent> = adapter.events(&handle).collect().await;
+        assert_eq!(events, vec![AgentEvent::Completed]);
+
+        adapter.send(&handle, "use port 8080").await.unwrap();
+        assert_ne!(adapter.agent_pid(&handle).await, handle.pid);
+        let events: Vec<AgentEvent> = adapter.events(&handle).collect().await;
+        assert_eq!(
+            events,
+            vec![
+                AgentEvent::Message {
+                    role: "assistant".to_string(),
+                    content: "resumed: use port 8080".to_string(),
+                },
+                AgentEvent::Completed,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_without_a_session_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script_path = tmp.path().join("quiet_claude.sh");
+        std::fs::write(&script_path, "#!/bin/sh\nexit 0\n").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let adapter = ClaudeCodeAdapter::with_binary(script_path.to_str().unwrap());
+        let handle = adapter.spawn(&test_task(tmp.path())).await.unwrap();
+        let _: Vec<AgentEvent> = adapter.events(&handle).collect().await;
+
+        let err = adapter.send(&handle, "hello").await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("no session to resume"),
+            "{err:#}"
+        );
+    }
+
     #[tokio::test]
     async fn process_exit_emits_completed() {
         let tmp = tempfile::tempdir().unwrap();
@@ -23,13 +23,18 @@
 //!     |   is_running(handle)
 //! ```
 
+pub mod budget;
 pub mod claude_code;
+pub mod rate_limit;
 pub mod registry;
+pub mod scaffold;
 pub mod trait_def;
 pub mod types;
 
 // Re-export the primary public API at the module level.
+pub use budget::{FittedPrompt, PromptBudget};
 pub use claude_code::ClaudeCodeAdapter;
 pub use registry::HarnessRegistry;
+pub use scaffold::{PromptConfig, PromptScaffold};
 pub use trait_def::Harness;
 pub use types::{AgentEvent, AgentHandle, MaterializedTask};
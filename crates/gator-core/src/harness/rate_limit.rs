@@ -0,0 +1,92 @@
+//! Recognizing provider rate limits in agent errors.
+//!
+//! Harnesses report the provider's errors as [`AgentEvent::Error`]
+//! messages in whatever words the provider uses: `429 Too Many Requests`,
+//! `rate_limit_error: ...`, `Overloaded`. [`detect`] reads such a message
+//! and, when it is a rate limit, how long the provider asked to wait.
+//!
+//! [`AgentEvent::Error`]: super::AgentEvent::Error
+
+use std::time::Duration;
+
+/// Phrases that mark a rate-limit or overload error, lowercase.
+const PHRASES: &[&str] = &[
+    "rate limit",
+    "rate_limit",
+    "ratelimit",
+    "rate-limit",
+    "too many requests",
+    "overloaded",
+];
+
+/// A rate-limit error reported by an agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How long the provider asked to wait, when it said.
+    pub retry_after: Option<Duration>,
+}
+
+/// Whether `message` reports a rate limit, and if so the wait it asks for.
+pub fn detect(message: &str) -> Option<RateLimit> {
+    let lower = message.to_lowercase();
+    let limited = PHRASES.iter().any(|p| lower.contains(p)) || has_status_429(&lower);
+    limited.then(|| RateLimit {
+        retry_after: retry_after(&lower),
+    })
+}
+
+/// Whether `429` appears as a number of its own, not inside a longer one.
+fn has_status_429(message: &str) -> bool {
+    let bytes = message.as_bytes();
+    message.match_indices("429").any(|(i, _)| {
+        let before = i.checked_sub(1).map(|j| bytes[j]);
+        let after = bytes.get(i + 3).copied();
+        !before.is_some_and(|b| b.is_ascii_digit() || b == b'.')
+            && !after.is_some_and(|b| b.is_ascii_digit() || b == b'.')
+    })
+}
+
+/// The seconds after `retry after`, `retry-after:`, `retry_after=`, or
+/// `try again in`, e.g. 30 in `retry-after: 30` or `try again in 30s`.
+fn retry_after(message: &str) -> Option<Duration> {
+    ["retry after", "retry-after", "retry_after", "try again in"]
+        .iter()
+        .find_map(|key| {
+            let rest = &message[message.find(key)? + key.len()..];
+            let rest = rest.trim_start_matches([':', '=', ' ']);
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok().map(Duration::from_secs)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_rate_limit_messages() {
+        assert!(detect("rate limit exceeded").is_some());
+        assert!(detect("API Error: 429 {\"type\":\"error\"}").is_some());
+        assert!(detect("overloaded_error: Overloaded").is_some());
+        assert!(detect("HTTP 429 Too Many Requests").is_some());
+        assert!(detect("compile error at line 4291").is_none());
+        assert!(detect("stdout read error: broken pipe").is_none());
+    }
+
+    #[test]
+    fn reads_the_requested_wait() {
+        assert_eq!(
+            detect("429 Too Many Requests; retry-after: 30").unwrap(),
+            RateLimit {
+                retry_after: Some(Duration::from_secs(30))
+            }
+        );
+        assert_eq!(
+            detect("Rate limited. Please try again in 12s")
+                .unwrap()
+                .retry_after,
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(detect("rate_limit_error").unwrap().retry_after, None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Localized prompt scaffold: the fixed instructions a harness wraps around
+//! each task ("You are working on task: ...").
+//!
+//! A [`PromptConfig`] picks one of the built-in translations by locale and
+//! can point at a TOML override file whose fields replace individual
+//! strings. Task names, descriptions, and invariant commands are inserted
+//! verbatim; only the surrounding text is translated.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::budget::{DEFAULT_TRIM_ORDER, PromptBudget};
+use super::types::MaterializedTask;
+
+/// Locale used when none is configured.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Locales with a built-in scaffold.
+pub const BUILTIN_LOCALES: &[&str] = &["en", "de", "es", "fr", "ja", "pt"];
+
+/// Prompt settings (the `[prompt]` config section).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptConfig {
+    /// Locale of the built-in scaffold, e.g. `de` or `pt-BR` (the region is
+    /// ignored when no regional translation exists).
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// TOML file whose fields override the selected scaffold's strings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaffold_file: Option<PathBuf>,
+    /// Estimated token limit for a materialized task; unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    /// Sections dropped, first to last, from a task over `max_tokens`.
+    #[serde(default = "default_trim_order")]
+    pub trim_order: Vec<String>,
+}
+
+fn default_locale() -> String {
+    DEFAULT_LOCALE.to_string()
+}
+
+fn default_trim_order() -> Vec<String> {
+    DEFAULT_TRIM_ORDER.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            locale: default_locale(),
+            scaffold_file: None,
+            max_tokens: None,
+            trim_order: default_trim_order(),
+        }
+    }
+}
+
+impl PromptConfig {
+    /// The prompt budget, if `max_tokens` is set.
+    pub fn budget(&self) -> Result<Option<PromptBudget>> {
+        self.max_tokens
+            .map(|max| PromptBudget::new(max, self.trim_order.clone()))
+            .transpose()
+    }
+}
+
+/// The translatable strings around a task prompt.
+///
+/// `intro` may contain `{name}`, which is replaced with the task name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptScaffold {
+    /// Opening line naming the task.
+    pub intro: String,
+    /// Heading above the list of invariant commands.
+    pub invariants_heading: String,
+    /// Closing instruction telling the agent how to finish.
+    pub done_instruction: String,
+}
+
+/// Partial scaffold read from an override file; unset fields keep the
+/// locale's built-in text.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScaffoldOverride {
+    intro: Option<String>,
+    invariants_heading: Option<String>,
+    done_instruction: Option<String>,
+}
+
+impl Default for PromptScaffold {
+    fn default() -> Self {
+        Self::builtin(DEFAULT_LOCALE).expect("default locale is built in")
+    }
+}
+
+impl PromptScaffold {
+    /// Return the built-in scaffold for `locale`, or `None` if there is no
+    /// translation. Matching ignores case and any region suffix
+    /// (`pt-BR`, `de_AT`).
+    pub fn builtin(locale: &str) -> Option<Self> {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (intro, invariants_heading, done_instruction) = match language.as_str() {
+            "en" => (
+                "You are working on task: {name}",
+                "Available invariant commands:",
+                "When you are done, run: gator done",
+            ),
+            "de" => (
+                "Du arbeitest an der Aufgabe: {name}",
+                "Verfügbare Invarianten-Befehle:",
+                "Wenn du fertig bist, führe aus: gator done",
+            ),
+            "es" => (
+                "Estás trabajando en la tarea: {name}",
+                "Comandos de invariantes disponibles:",
+                "Cuando termines, ejecuta: gator done",
+            ),
+            "fr" => (
+                "Vous travaillez sur la tâche : {name}",
+                "Commandes d'invariants disponibles :",
+                "Lorsque vous avez terminé, exécutez : gator done",
+            ),
+            "ja" => (
+                "あなたが担当するタスク: {name}",
+                "利用可能な不変条件コマンド:",
+                "完了したら次を実行してください: gator done",
+            ),
+            "pt" => (
+                "Você está trabalhando na tarefa: {name}",
+                "Comandos de invariantes disponíveis:",
+                "Quando terminar, execute: gator done",
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            intro: intro.to_string(),
+            invariants_heading: invariants_heading.to_string(),
+            done_instruction: done_instruction.to_string(),
+        })
+    }
+
+    /// Resolve the scaffold for a config: the locale's built-in text with
+    /// any override file applied on top.
+    ///
+    /// A locale without a built-in translation is accepted only together
+    /// with an override file, whose fields are laid over the English text.
+    pub fn resolve(config: &PromptConfig) -> Result<Self> {
+        let mut scaffold = match Self::builtin(&config.locale) {
+            Some(scaffold) => scaffold,
+            None if config.scaffold_file.is_some() => Self::default(),
+            None => bail!(
+                "no prompt scaffold for locale {:?} (built in: {}); \
+                 set prompt.scaffold_file to supply one",
+                config.locale,
+                BUILTIN_LOCALES.join(", ")
+            ),
+        };
+        if let Some(path) = &config.scaffold_file {
+            scaffold.apply_override_file(path)?;
+        }
+        Ok(scaffold)
+    }
+
+    /// Replace fields with those set in a TOML override file.
+    fn apply_override_file(&mut self, path: &Path) -> Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read prompt scaffold {}", path.display()))?;
+        let overrides: ScaffoldOverride = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse prompt scaffold {}", path.display()))?;
+        if let Some(intro) = overrides.intro {
+            self.intro = intro;
+        }
+        if let Some(heading) = overrides.invariants_heading {
+            self.invariants_heading = heading;
+        }
+        if let Some(done) = overrides.done_instruction {
+            self.done_instruction = done;
+        }
+        Ok(())
+    }
+
+    /// Assemble the instructions handed to the agent alongside its task.
+    pub fn system_instructions(&self, task: &MaterializedTask) -> String {
+        let invariants = task
+            .invariant_commands
+            .iter()
+            .map(|c| format!("  - {c}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "{intro}\n\n{description}\n\n{heading}\n{invariants}\n\n{done}",
+            intro = self.intro.replace("{name}", &task.name),
+            description = task.description,
+            heading = self.invariants_heading,
+            done = self.done_instruction,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn task() -> MaterializedTask {
+        MaterializedTask {
+            task_id: uuid::Uuid::nil(),
+            name: "add-login".to_string(),
+            description: "Implement the login form.".to_string(),
+            invariant_commands: vec!["cargo test".to_string()],
+            working_dir: PathBuf::from("/tmp"),
+            env_vars: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn english_scaffold_matches_original_wording() {
+        let text = PromptScaffold::default().system_instructions(&task());
+        assert_eq!(
+            text,
+            "You are working on task: add-login\n\n\
+             Implement the login form.\n\n\
+             Available invariant commands:\n  - cargo test\n\n\
+             When you are done, run: gator done"
+        );
+    }
+
+    #[test]
+    fn every_builtin_locale_resolves() {
+        for locale in BUILTIN_LOCALES {
+            let scaffold = PromptScaffold::builtin(locale).expect("built-in locale");
+            assert!(scaffold.intro.contains("{name}"), "{locale}");
+            assert!(scaffold.done_instruction.contains("gator done"), "{locale}");
+        }
+        assert_eq!(
+            PromptScaffold::builtin("pt-BR"),
+            PromptScaffold::builtin("pt")
+        );
+        assert!(PromptScaffold::builtin("xx").is_none());
+    }
+
+    #[test]
+    fn unknown_locale_is_an_error() {
+        let config = PromptConfig {
+            locale: "tlh".to_string(),
+            ..PromptConfig::default()
+        };
+        let err = PromptScaffold::resolve(&config).unwrap_err().to_string();
+        assert!(err.contains("tlh"), "got: {err}");
+    }
+
+    #[test]
+    fn override_file_replaces_selected_fields() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "intro = \"Taak: {name}\"\n").unwrap();
+        let config = PromptConfig {
+            locale: "de".to_string(),
+            scaffold_file: Some(tmp.path().to_path_buf()),
+            ..PromptConfig::default()
+        };
+        let scaffold = PromptScaffold::resolve(&config).unwrap();
+        assert_eq!(scaffold.intro, "Taak: {name}");
+        assert_eq!(
+            scaffold.invariants_heading,
+            "Verfügbare Invarianten-Befehle:"
+        );
+
+        let custom = PromptConfig {
+            locale: "nl".to_string(),
+            scaffold_file: Some(tmp.path().to_path_buf()),
+            ..PromptConfig::default()
+        };
+        let scaffold = PromptScaffold::resolve(&custom).unwrap();
+        assert_eq!(scaffold.intro, "Taak: {name}");
+        assert_eq!(
+            scaffold.done_instruction,
+            "When you are done, run: gator done"
+        );
+
+        std::fs::write(tmp.path(), "intro_typo = \"x\"\n").unwrap();
+        assert!(PromptScaffold::resolve(&config).is_err());
+    }
+}
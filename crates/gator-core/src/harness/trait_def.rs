@@ -44,7 +44,10 @@ pub trait Harness: Send + Sync {
 
     /// Send a text message to the agent's stdin.
     ///
-    /// Used for conversation continuation (e.g. via `--resume`).
+    /// Used for conversation continuation (e.g. via `--resume`): after the
+    /// agent's events end, the lifecycle sends the operator's answer to a
+    /// `gator ask` question and reads [`Self::events`] for the same handle
+    /// again.
     async fn send(&self, handle: &AgentHandle, message: &str) -> Result<()>;
 
     /// Terminate the agent process.
@@ -55,6 +58,22 @@ pub trait Harness: Send + Sync {
 
     /// Check whether the agent process is still alive.
     async fn is_running(&self, handle: &AgentHandle) -> bool;
+
+    /// OS process ID of the agent's current process.
+    ///
+    /// Differs from `handle.pid` once [`Self::send`] has continued the
+    /// conversation in a new process; the default assumes it never does.
+    async fn agent_pid(&self, handle: &AgentHandle) -> u32 {
+        handle.pid
+    }
+
+    /// Version of the underlying agent CLI, if it can be determined.
+    ///
+    /// Only used for diagnostics such as the startup banner, so the default
+    /// reports nothing.
+    async fn version(&self) -> Option<String> {
+        None
+    }
 }
 
 // Compile-time assertion: Harness must be object-safe.
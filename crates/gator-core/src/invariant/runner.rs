@@ -1,12 +1,53 @@
-use std::path::Path;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Component, Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
 
 use gator_db::models::Invariant;
 
+/// Most bytes of stdout (and, separately, stderr) kept from one invariant
+/// run. The first and last halves are kept; the middle is replaced with a
+/// marker saying how much was dropped.
+pub const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// How long to wait for the output pipes to close after a timed-out
+/// invariant's process group was killed. A grandchild that left the group
+/// can hold them open indefinitely.
+const DRAIN_AFTER_KILL: Duration = Duration::from_secs(5);
+
+/// Whether `command` can be spawned: a bare name is looked up on `PATH`, an
+/// absolute path must be an executable file. `None` for a relative path
+/// such as `./scripts/check.sh`, which depends on the worktree it runs in.
+pub fn command_available(command: &str) -> Option<bool> {
+    let path = Path::new(command);
+    if path.is_absolute() {
+        return Some(is_executable(path));
+    }
+    if path.components().count() > 1 {
+        return None;
+    }
+    let dirs = std::env::var_os("PATH").unwrap_or_default();
+    Some(std::env::split_paths(&dirs).any(|dir| is_executable(&dir.join(command))))
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
 /// The result of executing a single invariant check.
 #[derive(Debug, Clone)]
 pub struct InvariantResult {
@@ -15,106 +56,203 @@ pub struct InvariantResult {
     /// The actual exit code returned by the process, or `None` if the
     /// process was terminated by a signal.
     pub exit_code: Option<i32>,
-    /// Captured standard output.
+    /// Whether the invariant ran past its `timeout_secs` and was killed.
+    pub timed_out: bool,
+    /// Captured standard output, capped at [`MAX_OUTPUT_BYTES`].
     pub stdout: String,
-    /// Captured standard error.
+    /// Captured standard error, capped at [`MAX_OUTPUT_BYTES`].
     pub stderr: String,
     /// Wall-clock duration in milliseconds.
     pub duration_ms: i64,
 }
 
+/// Check an invariant's `workdir`: a relative path that stays inside the
+/// worktree, such as `packages/web` or `./frontend/`.
+pub fn validate_workdir(workdir: &str) -> Result<()> {
+    let path = Path::new(workdir);
+    let inside = !workdir.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        anyhow::bail!("workdir {workdir:?} must be a relative path inside the worktree");
+    }
+    Ok(())
+}
+
+/// The directory an invariant runs in: `working_dir`, or its `workdir`
+/// subdirectory when one is set.
+pub fn invariant_dir(invariant: &Invariant, working_dir: &Path) -> PathBuf {
+    match &invariant.workdir {
+        Some(workdir) => working_dir.join(workdir),
+        None => working_dir.to_path_buf(),
+    }
+}
+
 /// Run an invariant's command in the given working directory and return
 /// the result.
 ///
-/// The command is spawned as a child process with `stdout` and `stderr`
+/// The command runs in the invariant's `workdir` under `working_dir` when
+/// one is set, with the invariant's own `env` added to the inherited
+/// environment. The command is spawned as a child process with `stdout` and `stderr`
 /// captured.  The exit code is compared against
 /// [`Invariant::expected_exit_code`] to determine pass/fail.
 pub async fn run_invariant(invariant: &Invariant, working_dir: &Path) -> Result<InvariantResult> {
-    let start = Instant::now();
-    let timeout = Duration::from_secs(invariant.timeout_secs.max(1) as u64);
+    run_invariant_with_env(invariant, working_dir, &HashMap::new()).await
+}
 
-    let mut child = Command::new(&invariant.command)
+/// Like [`run_invariant`], with extra environment variables set on the
+/// child process (e.g. an ephemeral `DATABASE_URL`). These win over the
+/// invariant's own `env` on a clash.
+///
+/// The command runs in its own process group. When it outlives the
+/// invariant's `timeout_secs`, the whole group is killed and the result is
+/// marked `timed_out`, keeping whatever output was produced until then.
+pub async fn run_invariant_with_env(
+    invariant: &Invariant,
+    working_dir: &Path,
+    env: &HashMap<String, String>,
+) -> Result<InvariantResult> {
+    let mut command = Command::new(&invariant.command);
+    command
         .args(invariant.args.as_slice())
-        .current_dir(working_dir)
+        .current_dir(invariant_dir(invariant, working_dir))
+        .envs(invariant.env.iter())
+        .envs(env);
+
+    let mut result = run_captured(
+        command,
+        &format!("invariant {:?}", invariant.name),
+        &format!("{} {}", invariant.command, invariant.args.join(" ")),
+        Duration::from_secs(invariant.timeout_secs.max(1) as u64),
+    )
+    .await?;
+    result.passed = !result.timed_out && result.exit_code == Some(invariant.expected_exit_code);
+    Ok(result)
+}
+
+/// Run a prepared command the way invariants run: in its own process
+/// group, with output captured and capped, killed with everything it
+/// started once it outlives `timeout`. `label` names it in errors and the
+/// timeout message, `shown` is the command line quoted in errors. The
+/// result passes when the command exits 0 in time.
+pub(crate) async fn run_captured(
+    mut command: Command,
+    label: &str,
+    shown: &str,
+    timeout: Duration,
+) -> Result<InvariantResult> {
+    let start = Instant::now();
+
+    command
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = command
         .spawn()
-        .with_context(|| {
-            format!(
-                "failed to execute invariant {:?} (command: {} {})",
-                invariant.name,
-                invariant.command,
-                invariant.args.join(" "),
-            )
-        })?;
-
-    // Take stdout/stderr handles so we can read them concurrently with
-    // waiting for the process. This avoids deadlocks if the child fills the
-    // pipe buffer.
-    let mut stdout_pipe = child.stdout.take();
-    let mut stderr_pipe = child.stderr.take();
-
-    let read_stdout = async {
-        let mut buf = Vec::new();
-        if let Some(ref mut pipe) = stdout_pipe {
-            pipe.read_to_end(&mut buf).await.ok();
+        .with_context(|| format!("failed to execute {label} (command: {shown})"))?;
+
+    // Read stdout/stderr in their own tasks, concurrently with waiting for
+    // the process, so a child that fills a pipe buffer cannot deadlock and
+    // the output read so far survives a timeout.
+    let read_stdout = tokio::spawn(read_capped(child.stdout.take()));
+    let read_stderr = tokio::spawn(read_capped(child.stderr.take()));
+
+    let (exit_code, timed_out) = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) => (status.code(), false),
+        Ok(Err(e)) => {
+            return Err(e).with_context(|| format!("failed to wait on {label} (command: {shown})"));
+        }
+        Err(_) => {
+            kill_process_group(&mut child).await;
+            (None, true)
         }
-        String::from_utf8_lossy(&buf).into_owned()
     };
 
-    let read_stderr = async {
-        let mut buf = Vec::new();
-        if let Some(ref mut pipe) = stderr_pipe {
-            pipe.read_to_end(&mut buf).await.ok();
-        }
-        String::from_utf8_lossy(&buf).into_owned()
+    let drain = async {
+        let stdout = read_stdout.await.unwrap_or_default();
+        let stderr = read_stderr.await.unwrap_or_default();
+        (stdout, stderr)
+    };
+    let (stdout, mut stderr) = if timed_out {
+        tokio::time::timeout(DRAIN_AFTER_KILL, drain)
+            .await
+            .unwrap_or_default()
+    } else {
+        drain.await
     };
+    if timed_out {
+        if !stderr.is_empty() && !stderr.ends_with('\n') {
+            stderr.push('\n');
+        }
+        stderr.push_str(&format!("{label} timed out after {}s", timeout.as_secs()));
+    }
 
-    // Wait for exit + read output concurrently, with a timeout.
-    match tokio::time::timeout(timeout, async {
-        let (wait_result, stdout, stderr) = tokio::join!(child.wait(), read_stdout, read_stderr);
-        (wait_result, stdout, stderr)
+    let duration_ms = i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX);
+    Ok(InvariantResult {
+        passed: !timed_out && exit_code == Some(0),
+        exit_code,
+        timed_out,
+        stdout,
+        stderr,
+        duration_ms,
     })
-    .await
-    {
-        Ok((Ok(status), stdout, stderr)) => {
-            let duration_ms = i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX);
-            let exit_code = status.code();
-            let passed = exit_code == Some(invariant.expected_exit_code);
-
-            Ok(InvariantResult {
-                passed,
-                exit_code,
-                stdout,
-                stderr,
-                duration_ms,
-            })
+}
+
+/// Kill a timed-out invariant together with everything it started.
+async fn kill_process_group(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: kill(2) with a negative pid signals the process group the
+        // child leads (it was spawned with `process_group(0)`).
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
         }
-        Ok((Err(e), _, _)) => Err(e).with_context(|| {
-            format!(
-                "failed to wait on invariant {:?} (command: {} {})",
-                invariant.name,
-                invariant.command,
-                invariant.args.join(" "),
-            )
-        }),
-        Err(_) => {
-            // Timeout: kill the child process.
-            let _ = child.kill().await;
-            let duration_ms = i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX);
-
-            Ok(InvariantResult {
-                passed: false,
-                exit_code: None,
-                stdout: String::new(),
-                stderr: format!(
-                    "invariant {:?} timed out after {}s",
-                    invariant.name, invariant.timeout_secs
-                ),
-                duration_ms,
-            })
+    }
+    let _ = child.kill().await;
+}
+
+/// Read a pipe to the end, keeping the first and last halves of
+/// [`MAX_OUTPUT_BYTES`] and counting what falls in between.
+async fn read_capped<R: AsyncRead + Unpin>(pipe: Option<R>) -> String {
+    let Some(mut pipe) = pipe else {
+        return String::new();
+    };
+    let half = MAX_OUTPUT_BYTES / 2;
+    let mut head = Vec::new();
+    let mut tail = VecDeque::new();
+    let mut dropped = 0usize;
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = match pipe.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        let mut bytes = &chunk[..n];
+        if head.len() < half {
+            let take = bytes.len().min(half - head.len());
+            head.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
         }
+        tail.extend(bytes);
+        if tail.len() > half {
+            let excess = tail.len() - half;
+            tail.drain(..excess);
+            dropped += excess;
+        }
+    }
+
+    let mut out = String::from_utf8_lossy(&head).into_owned();
+    if dropped > 0 {
+        out.push_str(&format!(
+            "\n[... {dropped} bytes of output truncated ...]\n"
+        ));
     }
+    out.push_str(&String::from_utf8_lossy(tail.make_contiguous()));
+    out
 }
 
 #[cfg(test)]
@@ -138,9 +276,61 @@ mod tests {
             scope: InvariantScope::Project,
             timeout_secs: 300,
             created_at: Utc::now(),
+            deleted_at: None,
+            diff_base: false,
+            lower_is_better: false,
+            disabled_at: None,
+            env: Default::default(),
+            workdir: None,
+            run_after: Default::default(),
+            artifacts: Default::default(),
         }
     }
 
+    #[test]
+    fn command_available_looks_up_path() {
+        assert_eq!(command_available("sh"), Some(true));
+        assert_eq!(command_available("/bin/sh"), Some(true));
+        assert_eq!(command_available("gator-no-such-command"), Some(false));
+        assert_eq!(command_available("/nonexistent/check"), Some(false));
+        assert_eq!(command_available("./scripts/check.sh"), None);
+    }
+
+    #[test]
+    fn validate_workdir_rejects_paths_outside_the_worktree() {
+        assert!(validate_workdir("packages/web").is_ok());
+        assert!(validate_workdir("./frontend/").is_ok());
+        assert!(validate_workdir("").is_err());
+        assert!(validate_workdir("/abs").is_err());
+        assert!(validate_workdir("../sibling").is_err());
+        assert!(validate_workdir("a/../../b").is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_in_workdir_with_invariant_env() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("sub")).expect("mkdir");
+        let mut inv = test_invariant("sh", &["-c", "pwd; echo \"$FOO $BAR\""], 0);
+        inv.workdir = Some("sub".to_owned());
+        inv.env = sqlx::types::Json(
+            [("FOO", "from-invariant"), ("BAR", "shadowed")]
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+        );
+        let caller_env = HashMap::from([("BAR".to_owned(), "from-caller".to_owned())]);
+
+        let result = run_invariant_with_env(&inv, dir.path(), &caller_env)
+            .await
+            .expect("should succeed");
+
+        assert!(result.passed);
+        let mut lines = result.stdout.lines();
+        let pwd = lines.next().expect("pwd line");
+        assert!(pwd.ends_with("/sub"), "should run in workdir, got {pwd:?}");
+        assert_eq!(lines.next(), Some("from-invariant from-caller"));
+    }
+
     #[tokio::test]
     async fn run_true_command_passes() {
         let inv = test_invariant("true", &[], 0);
@@ -235,6 +425,46 @@ mod tests {
             "stderr should mention timeout, got: {:?}",
             result.stderr
         );
+        assert!(result.timed_out);
+    }
+
+    #[tokio::test]
+    async fn timeout_kills_the_process_group_and_keeps_output() {
+        // The shell's background child keeps the pipes open unless the
+        // whole group is killed.
+        let mut inv = test_invariant("sh", &["-c", "echo started; sleep 60 & wait"], 0);
+        inv.timeout_secs = 1;
+        let start = Instant::now();
+        let result = run_invariant(&inv, Path::new("/tmp"))
+            .await
+            .expect("should succeed even on timeout");
+
+        assert!(result.timed_out);
+        assert!(!result.passed);
+        assert_eq!(result.stdout, "started\n");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn output_is_capped_with_a_truncation_marker() {
+        let script = format!(
+            "echo first; head -c {} /dev/zero | tr '\\0' x; echo; echo last",
+            MAX_OUTPUT_BYTES * 4
+        );
+        let inv = test_invariant("sh", &["-c", &script], 0);
+        let result = run_invariant(&inv, Path::new("/tmp"))
+            .await
+            .expect("should succeed");
+
+        assert!(result.passed);
+        assert!(!result.timed_out);
+        assert!(result.stdout.len() < MAX_OUTPUT_BYTES + 100);
+        assert!(result.stdout.starts_with("first\n"));
+        assert!(result.stdout.ends_with("\nlast\n"));
+        assert!(
+            result.stdout.contains("bytes of output truncated"),
+            "missing marker"
+        );
     }
 
     #[tokio::test]
@@ -0,0 +1,174 @@
+//! Shared dependency caches.
+//!
+//! A plan's `caches` (e.g. `["cargo", "pnpm"]`) give every task workspace
+//! the same package caches, so parallel agents download each dependency
+//! once. The caches live in `.gator-cache/<kind>` under the worktree base,
+//! on the same filesystem as the worktrees, so pnpm can hard-link from its
+//! store. Worktree workspaces get environment variables pointing at the
+//! host directories; containers get each directory bind-mounted under
+//! [`CONTAINER_CACHE_ROOT`] and variables pointing there.
+//!
+//! Each tool already guards its cache against concurrent use: cargo takes
+//! a file lock on its package cache, npm's and pnpm's stores are
+//! content-addressed with atomic writes, pip writes by atomic rename, and
+//! Go locks its module cache. Nothing else writes to the directories, so
+//! parallel agents can share them safely.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use gator_db::models::CacheKind;
+
+/// Directory under the worktree base holding the shared caches.
+pub const CACHE_DIR: &str = ".gator-cache";
+
+/// Where the caches are mounted inside a container.
+pub const CONTAINER_CACHE_ROOT: &str = "/gator-cache";
+
+/// The caches a project of `project_type` (as detected by
+/// [`crate::presets::detect_project_type`]) usually wants.
+pub fn caches_for_project_type(project_type: &str) -> &'static [CacheKind] {
+    match project_type {
+        "rust" => &[CacheKind::Cargo],
+        "node" => &[CacheKind::Npm, CacheKind::Pnpm],
+        "python" => &[CacheKind::Pip],
+        "go" => &[CacheKind::Go],
+        _ => &[],
+    }
+}
+
+/// The environment that points `kind`'s tool at the cache in `dir`.
+fn cache_env(kind: CacheKind, dir: &Path) -> Vec<(&'static str, PathBuf)> {
+    match kind {
+        CacheKind::Cargo => vec![("CARGO_HOME", dir.to_path_buf())],
+        CacheKind::Npm => vec![("npm_config_cache", dir.to_path_buf())],
+        CacheKind::Pnpm => vec![("npm_config_store_dir", dir.to_path_buf())],
+        CacheKind::Pip => vec![("PIP_CACHE_DIR", dir.to_path_buf())],
+        CacheKind::Go => vec![
+            ("GOMODCACHE", dir.join("mod")),
+            ("GOCACHE", dir.join("build")),
+        ],
+    }
+}
+
+/// Shared caches for the workspaces under one worktree base.
+#[derive(Debug, Clone)]
+pub struct SharedCaches {
+    root: PathBuf,
+    kinds: Vec<CacheKind>,
+}
+
+impl SharedCaches {
+    /// The caches `kinds` under `worktree_base`.
+    pub fn new(worktree_base: &Path, kinds: &[CacheKind]) -> Self {
+        let mut unique = Vec::new();
+        for kind in kinds {
+            if !unique.contains(kind) {
+                unique.push(*kind);
+            }
+        }
+        Self {
+            root: worktree_base.join(CACHE_DIR),
+            kinds: unique,
+        }
+    }
+
+    /// Host directory of `kind`'s cache.
+    pub fn host_dir(&self, kind: CacheKind) -> PathBuf {
+        self.root.join(kind.to_string())
+    }
+
+    /// Create the cache directories. A container mount of a missing
+    /// directory would create it owned by root.
+    pub fn prepare(&self) -> Result<()> {
+        for kind in &self.kinds {
+            let dir = self.host_dir(*kind);
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("failed to create cache directory {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Environment for a workspace on the host.
+    pub fn host_env(&self) -> HashMap<String, String> {
+        self.env(|kind| self.host_dir(kind))
+    }
+
+    /// Environment for a workspace in a container with
+    /// [`SharedCaches::container_mounts`].
+    pub fn container_env(&self) -> HashMap<String, String> {
+        self.env(|kind| Path::new(CONTAINER_CACHE_ROOT).join(kind.to_string()))
+    }
+
+    /// `docker create` flags that bind-mount each cache into the container.
+    pub fn container_mounts(&self) -> Vec<String> {
+        self.kinds
+            .iter()
+            .flat_map(|kind| {
+                [
+                    "-v".to_string(),
+                    format!(
+                        "{}:{CONTAINER_CACHE_ROOT}/{kind}",
+                        self.host_dir(*kind).display()
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    fn env(&self, dir: impl Fn(CacheKind) -> PathBuf) -> HashMap<String, String> {
+        self.kinds
+            .iter()
+            .flat_map(|kind| cache_env(*kind, &dir(*kind)))
+            .map(|(key, path)| (key.to_string(), path.display().to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_and_container_env_point_at_the_same_caches() {
+        let caches = SharedCaches::new(
+            Path::new("/work/repo-gator-worktrees"),
+            &[CacheKind::Cargo, CacheKind::Go, CacheKind::Cargo],
+        );
+        let host = caches.host_env();
+        assert_eq!(host.len(), 3);
+        assert_eq!(
+            host["CARGO_HOME"],
+            "/work/repo-gator-worktrees/.gator-cache/cargo"
+        );
+        assert_eq!(
+            host["GOMODCACHE"],
+            "/work/repo-gator-worktrees/.gator-cache/go/mod"
+        );
+
+        let container = caches.container_env();
+        assert_eq!(container["CARGO_HOME"], "/gator-cache/cargo");
+        assert_eq!(container["GOCACHE"], "/gator-cache/go/build");
+        assert_eq!(
+            caches.container_mounts(),
+            vec![
+                "-v",
+                "/work/repo-gator-worktrees/.gator-cache/cargo:/gator-cache/cargo",
+                "-v",
+                "/work/repo-gator-worktrees/.gator-cache/go:/gator-cache/go",
+            ]
+        );
+    }
+
+    #[test]
+    fn project_types_have_default_caches() {
+        assert_eq!(caches_for_project_type("rust"), &[CacheKind::Cargo]);
+        assert_eq!(
+            caches_for_project_type("node"),
+            &[CacheKind::Npm, CacheKind::Pnpm]
+        );
+        assert!(caches_for_project_type("docs").is_empty());
+    }
+}
@@ -11,9 +11,20 @@ use anyhow::{Context, Result, bail};
 use async_trait::async_trait;
 use tokio::process::Command;
 
+use gator_db::models::CacheKind;
+
+use super::cache::SharedCaches;
 use super::{Isolation, WorkspaceInfo};
 use crate::worktree::WorktreeManager;
 
+/// Image used when a plan names none.
+pub const DEFAULT_IMAGE: &str = "ubuntu:24.04";
+
+/// Container labels naming the plan and task a workspace container belongs
+/// to, so `gator gc` can find the ones no task is using.
+pub const PLAN_LABEL: &str = "gator.plan";
+pub const TASK_LABEL: &str = "gator.task";
+
 /// Configuration for the container isolation backend.
 #[derive(Debug, Clone)]
 pub struct ContainerConfig {
@@ -26,12 +37,14 @@ pub struct ContainerConfig {
 /// Isolation backend that runs tasks inside sandboxed Docker containers.
 ///
 /// The agent writes to a container-local `/workspace` directory. No host
-/// paths are bind-mounted read-write. Results are extracted via `docker cp`
-/// after the agent completes.
+/// paths are bind-mounted read-write, except the plan's shared dependency
+/// caches. Results are extracted via `docker cp` after the agent completes.
 #[derive(Debug)]
 pub struct ContainerIsolation {
     config: ContainerConfig,
     worktree_manager: WorktreeManager,
+    /// Dependency caches mounted into every container, if any.
+    caches: Option<SharedCaches>,
 }
 
 impl ContainerIsolation {
@@ -40,9 +53,18 @@ impl ContainerIsolation {
         Self {
             config,
             worktree_manager,
+            caches: None,
         }
     }
 
+    /// Mount the dependency caches `kinds`, kept under the worktree base,
+    /// into every container.
+    pub fn with_caches(mut self, kinds: &[CacheKind]) -> Self {
+        self.caches = (!kinds.is_empty())
+            .then(|| SharedCaches::new(self.worktree_manager.worktree_base(), kinds));
+        self
+    }
+
     /// Build the container name for a plan/task pair.
     fn container_name(plan_name: &str, task_name: &str) -> String {
         // Sanitize names for Docker container naming (alphanumeric + hyphens).
@@ -143,7 +165,12 @@ impl Isolation for ContainerIsolation {
         "container"
     }
 
-    async fn create_workspace(&self, plan_name: &str, task_name: &str) -> Result<WorkspaceInfo> {
+    async fn create_workspace(
+        &self,
+        plan_name: &str,
+        task_name: &str,
+        paths: &[String],
+    ) -> Result<WorkspaceInfo> {
         let container_name = Self::container_name(plan_name, task_name);
         let branch_name = Self::branch_name(plan_name, task_name);
 
@@ -156,11 +183,26 @@ impl Isolation for ContainerIsolation {
 
         let host_worktree_path = wt_info.path.clone();
 
-        // 2. docker create WITHOUT volume mount.
+        // Only the checked-out files are copied into the container.
+        if !paths.is_empty() {
+            self.worktree_manager
+                .sparse_checkout(&host_worktree_path, paths)
+                .map_err(|e| anyhow::anyhow!("{e}"))
+                .with_context(|| {
+                    format!("failed to restrict worktree for {plan_name}/{task_name}")
+                })?;
+        }
+
+        // 2. docker create WITHOUT mounting the worktree; only the shared
+        //    caches are mounted.
         let mut args = vec![
             "create".to_string(),
             "--name".to_string(),
             container_name.clone(),
+            "--label".to_string(),
+            format!("{PLAN_LABEL}={plan_name}"),
+            "--label".to_string(),
+            format!("{TASK_LABEL}={task_name}"),
             "-w".to_string(),
             "/workspace".to_string(),
         ];
@@ -168,6 +210,10 @@ impl Isolation for ContainerIsolation {
         for flag in &self.config.extra_flags {
             args.push(flag.clone());
         }
+        if let Some(caches) = &self.caches {
+            caches.prepare()?;
+            args.extend(caches.container_mounts());
+        }
 
         args.push(self.config.image.clone());
         args.push("sleep".to_string());
@@ -218,6 +264,11 @@ impl Isolation for ContainerIsolation {
             host_path: Some(host_worktree_path),
             branch: wt_info.branch,
             container_id: Some(container_id),
+            env: self
+                .caches
+                .as_ref()
+                .map(SharedCaches::container_env)
+                .unwrap_or_default(),
         })
     }
 
@@ -3,9 +3,11 @@
 //! Decouples workspace creation from `WorktreeManager` so that different
 //! backends (git worktrees, Docker containers) can be used interchangeably.
 
+pub mod cache;
 pub mod container;
 pub mod worktree;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -30,6 +32,9 @@ pub struct WorkspaceInfo {
     pub branch: Option<String>,
     /// Docker container ID, if applicable.
     pub container_id: Option<String>,
+    /// Environment every command in the workspace gets, e.g. the locations
+    /// of the plan's shared caches.
+    pub env: HashMap<String, String>,
 }
 
 /// Trait for workspace isolation backends.
@@ -39,7 +44,15 @@ pub trait Isolation: Send + Sync {
     fn name(&self) -> &str;
 
     /// Create an isolated workspace for a task.
-    async fn create_workspace(&self, plan_name: &str, task_name: &str) -> Result<WorkspaceInfo>;
+    ///
+    /// When `paths` is non-empty the host worktree is a sparse checkout of
+    /// those patterns, so the agent only sees the files it may change.
+    async fn create_workspace(
+        &self,
+        plan_name: &str,
+        task_name: &str,
+        paths: &[String],
+    ) -> Result<WorkspaceInfo>;
 
     /// Extract results from the workspace back to the host filesystem.
     ///
@@ -52,6 +65,50 @@ pub trait Isolation: Send + Sync {
     async fn remove_workspace(&self, info: &WorkspaceInfo) -> Result<()>;
 }
 
+/// Create the isolation backend `plan` is configured for, cloning task
+/// worktrees from a template worktree when the plan sets `template_setup`
+/// and sharing the plan's `caches` between workspaces. Templates are only
+/// used with worktree isolation.
+pub fn create_plan_isolation(plan: &gator_db::models::Plan) -> Result<Arc<dyn Isolation>> {
+    let repo_path = Path::new(&plan.project_path);
+    let caches = &plan.caches.0;
+    match (plan.isolation.as_str(), plan.template_setup.as_deref()) {
+        ("worktree", setup) => {
+            let mgr = crate::worktree::WorktreeManager::new(repo_path, None)
+                .map_err(|e| anyhow::anyhow!("{e}"))?;
+            let mut isolation = worktree::WorktreeIsolation::new(mgr).with_caches(caches);
+            if let Some(setup) = setup {
+                isolation = isolation
+                    .with_template(setup)
+                    .with_template_hardlinks(plan.template_hardlinks);
+            }
+            Ok(Arc::new(isolation))
+        }
+        ("container", setup) => {
+            if setup.is_some() {
+                tracing::warn!(
+                    plan = %plan.name,
+                    "template_setup is ignored with container isolation"
+                );
+            }
+            let mgr = crate::worktree::WorktreeManager::new(repo_path, None)
+                .map_err(|e| anyhow::anyhow!("{e}"))?;
+            let config = container::ContainerConfig {
+                image: plan
+                    .container_image
+                    .as_deref()
+                    .unwrap_or(container::DEFAULT_IMAGE)
+                    .to_string(),
+                extra_flags: vec![],
+            };
+            Ok(Arc::new(
+                container::ContainerIsolation::new(config, mgr).with_caches(caches),
+            ))
+        }
+        (mode, _) => create_isolation(mode, repo_path, plan.container_image.as_deref()),
+    }
+}
+
 /// Factory function: create an isolation backend from a mode string.
 ///
 /// `container_image` is only used when `mode` is `"container"`. It defaults
@@ -68,7 +125,9 @@ pub fn create_isolation(
             Ok(Arc::new(worktree::WorktreeIsolation::new(mgr)))
         }
         "container" => {
-            let image = container_image.unwrap_or("ubuntu:24.04").to_string();
+            let image = container_image
+                .unwrap_or(container::DEFAULT_IMAGE)
+                .to_string();
             let mgr = crate::worktree::WorktreeManager::new(repo_path, None)
                 .map_err(|e| anyhow::anyhow!("{e}"))?;
             let config = container::ContainerConfig {
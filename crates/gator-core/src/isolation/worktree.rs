@@ -2,9 +2,15 @@
 //!
 //! Wraps [`WorktreeManager`] behind the [`Isolation`] trait.
 
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use gator_db::models::CacheKind;
 
+use super::cache::SharedCaches;
 use super::{Isolation, WorkspaceInfo};
 use crate::worktree::WorktreeManager;
 
@@ -12,18 +18,69 @@ use crate::worktree::WorktreeManager;
 #[derive(Debug)]
 pub struct WorktreeIsolation {
     manager: WorktreeManager,
+    /// Setup command of the template worktree task worktrees are cloned
+    /// from, if the plan uses one.
+    template_setup: Option<String>,
+    /// Hard-link the template's build outputs into worktrees that cannot be
+    /// cloned from it.
+    template_hardlinks: bool,
+    /// Held while a template is prepared, so concurrent tasks wait for one
+    /// setup run instead of starting their own.
+    template_lock: Mutex<()>,
+    /// Dependency caches shared by every workspace, if any.
+    caches: Option<SharedCaches>,
 }
 
 impl WorktreeIsolation {
     /// Create a new `WorktreeIsolation` from an existing `WorktreeManager`.
     pub fn new(manager: WorktreeManager) -> Self {
-        Self { manager }
+        Self {
+            manager,
+            template_setup: None,
+            template_hardlinks: false,
+            template_lock: Mutex::new(()),
+            caches: None,
+        }
+    }
+
+    /// Clone task worktrees from a per-plan template worktree, prepared on
+    /// first use by running `setup` in it.
+    pub fn with_template(mut self, setup: impl Into<String>) -> Self {
+        self.template_setup = Some(setup.into());
+        self
+    }
+
+    /// When a task worktree cannot be cloned from the template, hard-link
+    /// the template's build outputs into its checkout instead.
+    pub fn with_template_hardlinks(mut self, enabled: bool) -> Self {
+        self.template_hardlinks = enabled;
+        self
+    }
+
+    /// Share the dependency caches `kinds` between workspaces, under the
+    /// worktree base.
+    pub fn with_caches(mut self, kinds: &[CacheKind]) -> Self {
+        self.caches =
+            (!kinds.is_empty()).then(|| SharedCaches::new(self.manager.worktree_base(), kinds));
+        self
     }
 
     /// Access the underlying `WorktreeManager`.
     pub fn manager(&self) -> &WorktreeManager {
         &self.manager
     }
+
+    /// Prepare (or reuse) the template worktree of `plan_name`. The setup
+    /// command can take minutes, so it runs on the blocking pool.
+    async fn template(&self, plan_name: &str, setup: &str) -> Result<PathBuf> {
+        let _guard = self.template_lock.lock().await;
+        let manager = self.manager.clone();
+        let (plan, setup) = (plan_name.to_string(), setup.to_string());
+        tokio::task::spawn_blocking(move || manager.prepare_template(&plan, &setup))
+            .await
+            .context("template setup task panicked")?
+            .with_context(|| format!("failed to prepare the template worktree for {plan_name}"))
+    }
 }
 
 #[async_trait]
@@ -32,18 +89,47 @@ impl Isolation for WorktreeIsolation {
         "worktree"
     }
 
-    async fn create_workspace(&self, plan_name: &str, task_name: &str) -> Result<WorkspaceInfo> {
+    async fn create_workspace(
+        &self,
+        plan_name: &str,
+        task_name: &str,
+        paths: &[String],
+    ) -> Result<WorkspaceInfo> {
         let branch_name = WorktreeManager::branch_name(plan_name, task_name);
-        let wt_info = self
-            .manager
-            .create_worktree(&branch_name)
-            .with_context(|| format!("failed to create worktree for {plan_name}/{task_name}"))?;
+        let wt_info = match self.template_setup {
+            Some(ref setup) => {
+                let template = self.template(plan_name, setup).await?;
+                self.manager.create_worktree_from_template(
+                    &branch_name,
+                    &template,
+                    self.template_hardlinks,
+                )
+            }
+            None => self.manager.create_worktree(&branch_name),
+        }
+        .with_context(|| format!("failed to create worktree for {plan_name}/{task_name}"))?;
+        if !paths.is_empty() {
+            self.manager
+                .sparse_checkout(&wt_info.path, paths)
+                .with_context(|| {
+                    format!("failed to restrict worktree for {plan_name}/{task_name}")
+                })?;
+        }
+
+        let env = match &self.caches {
+            Some(caches) => {
+                caches.prepare()?;
+                caches.host_env()
+            }
+            None => Default::default(),
+        };
 
         Ok(WorkspaceInfo {
             path: wt_info.path,
             host_path: None,
             branch: wt_info.branch,
             container_id: None,
+            env,
         })
     }
 
@@ -100,7 +186,7 @@ mod tests {
         assert_eq!(isolation.name(), "worktree");
 
         let info = isolation
-            .create_workspace("test-plan", "test-task")
+            .create_workspace("test-plan", "test-task", &[])
             .await
             .expect("create_workspace failed");
 
@@ -116,4 +202,24 @@ mod tests {
 
         assert!(!info.path.exists());
     }
+
+    #[tokio::test]
+    async fn workspaces_share_caches_under_the_worktree_base() {
+        let (_dir, repo_path) = create_temp_repo();
+        let wt_base = TempDir::new().unwrap();
+        let mgr = WorktreeManager::new(&repo_path, Some(wt_base.path().to_path_buf())).unwrap();
+        let isolation = WorktreeIsolation::new(mgr).with_caches(&[CacheKind::Pip]);
+
+        let a = isolation.create_workspace("p", "a", &[]).await.unwrap();
+        let b = isolation.create_workspace("p", "b", &[]).await.unwrap();
+
+        let cache = wt_base.path().join(".gator-cache").join("pip");
+        assert!(cache.is_dir());
+        assert_eq!(a.env["PIP_CACHE_DIR"], cache.display().to_string());
+        assert_eq!(a.env, b.env);
+
+        // Removing a workspace keeps the cache.
+        isolation.remove_workspace(&a).await.unwrap();
+        assert!(cache.is_dir());
+    }
 }
@@ -1,11 +1,20 @@
+pub mod artifact;
+pub mod ephemeral_db;
+pub mod eval;
+pub mod forge;
 pub mod gate;
 pub mod harness;
 pub mod invariant;
 pub mod isolation;
 pub mod lifecycle;
+pub mod memory;
+pub mod notify;
 pub mod orchestrator;
 pub mod plan;
 pub mod presets;
+pub mod review;
+pub mod schema;
+pub mod services;
 pub mod state;
 pub mod task;
 pub mod token;
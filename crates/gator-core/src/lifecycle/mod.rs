@@ -4,29 +4,73 @@
 //! The lifecycle function manages the full sequence: create worktree, generate
 //! token, materialize task, spawn agent, collect events, run gate, evaluate
 //! verdict.
+//!
+//! When the agent asks the operator a question (`gator ask`), the lifecycle
+//! waits for `gator answer` and resumes the agent's session with the answer;
+//! the wait does not count against the task timeout.
+//!
+//! Each run is a root `task_lifecycle` tracing span (one trace per task
+//! attempt when OTLP export is enabled) with child spans for workspace
+//! creation, the agent run, the gate (one `invariant` span per check), and
+//! verdict evaluation. The root span carries the dispatch's `run_id` and a
+//! fresh `attempt_id`, which are also stored on the task so the attempt's
+//! events and gate results can be found by them (`gator log --run`).
+//!
+//! When the plan enables `ephemeral_db`, a fresh Postgres container is
+//! provisioned after the workspace and removed when the lifecycle returns;
+//! likewise for the task's compose `services`. The plan's and then the
+//! task's `setup_commands` run next, each recorded in `setup_results`; the
+//! first that fails fails the attempt before the agent starts. A plan's
+//! `warmup_command` then runs in the workspace, and its result is recorded
+//! in `warmup_results`.
+//!
+//! The wall time of each lifecycle phase (workspace, services, setup, warmup,
+//! materialize, agent, extract, gate, verdict) is recorded per attempt in the
+//! `attempt_phases` table for `gator report`.
 
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use futures::StreamExt;
 use sqlx::SqlitePool;
-use tracing;
+use tracing::{self, Instrument};
 use uuid::Uuid;
 
-use gator_db::models::Task;
+use gator_db::models::{LifecyclePhase, Task, TaskKind, TaskStatus};
 use gator_db::queries::agent_events::{self, NewAgentEvent};
+use gator_db::queries::attempt_phases;
 use gator_db::queries::invariants as inv_db;
+use gator_db::queries::prompt_trims;
+use gator_db::queries::setup_results::{self, NewSetupResult};
+use gator_db::queries::tasks as task_db;
+use gator_db::queries::warmup_results::{self, NewWarmupResult};
 
-use crate::gate::GateRunner;
+use crate::artifact;
+use crate::ephemeral_db::EphemeralDb;
+use crate::gate::attestation;
+use crate::gate::auto_approve::AutoApproveRule;
 use crate::gate::evaluator::{GateAction, evaluate_verdict};
-use crate::harness::Harness;
+use crate::gate::{GateRunner, GateVerdict};
 use crate::harness::types::{AgentEvent, MaterializedTask};
-use crate::isolation::Isolation;
+use crate::harness::{Harness, PromptBudget};
+use crate::invariant::runner::{self, InvariantResult};
+use crate::isolation::{Isolation, WorkspaceInfo};
+use crate::memory;
+use crate::orchestrator;
 use crate::plan::materialize_task;
+use crate::services::ComposeServices;
 use crate::state::dispatch;
 use crate::token::{self, TokenConfig};
 
+/// How often an agent waiting on `gator answer` checks for it.
+const ANSWER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a running agent's task is checked for a cancellation or a
+/// stall.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Result of running an agent through its full lifecycle.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LifecycleResult {
@@ -40,6 +84,25 @@ pub enum LifecycleResult {
     HumanRequired,
     /// The agent timed out.
     TimedOut,
+    /// The agent stalled: no events or progress for the stall timeout.
+    Stalled,
+    /// The task was cancelled while its agent ran; the agent was killed.
+    Cancelled,
+}
+
+impl LifecycleResult {
+    /// The result as a snake_case word, e.g. for hook environments.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Passed => "passed",
+            Self::FailedCanRetry => "failed_can_retry",
+            Self::FailedNoRetry => "failed_no_retry",
+            Self::HumanRequired => "human_required",
+            Self::TimedOut => "timed_out",
+            Self::Stalled => "stalled",
+            Self::Cancelled => "cancelled",
+        }
+    }
 }
 
 /// Configuration for the agent lifecycle.
@@ -47,12 +110,41 @@ pub enum LifecycleResult {
 pub struct LifecycleConfig {
     /// Maximum wall time for the agent to complete.
     pub timeout: Duration,
+    /// How long the agent may go without emitting events or reporting
+    /// progress before it is killed and the attempt failed; `None` disables
+    /// stall detection.
+    pub stall_timeout: Option<Duration>,
+    /// Postgres image for a per-attempt ephemeral database, if enabled.
+    pub ephemeral_db: Option<String>,
+    /// The plan's setup commands, run in the workspace before the task's
+    /// own; a failing one fails the attempt.
+    pub setup_commands: Vec<String>,
+    /// Command run in the workspace before the agent starts, if any.
+    pub warmup_command: Option<String>,
+    /// Size limit for the materialized task; sections are trimmed to fit.
+    pub prompt_budget: Option<PromptBudget>,
+    /// How many invariants the gate runs at once.
+    pub gate_parallelism: usize,
+    /// Whether the gate reuses results from earlier attempts with an
+    /// identical worktree.
+    pub gate_cache: bool,
+    /// Rules that may approve a passing `human_review` task without a human.
+    pub auto_approve: Vec<AutoApproveRule>,
+    /// Whether code tasks whose invariants pass get a compliance
+    /// attestation (see [`crate::gate::attestation`]).
+    pub attestation: bool,
+    /// The `gator dispatch` run this lifecycle belongs to, if any.
+    pub run_id: Option<Uuid>,
 }
 
 /// Run the full lifecycle for a single agent task.
 ///
 /// Steps:
-/// 1. Create workspace (worktree on host; container w/ copy-in for sandboxed mode)
+/// 1. Create workspace (worktree on host; container w/ copy-in for sandboxed mode),
+///    sparse-checked-out to the task's `paths` if it declares any, plus an
+///    ephemeral database and compose services if configured, then the
+///    plan's and task's setup commands (a failure fails the attempt here)
+///    and the plan's warm-up command
 /// 2. Generate scoped token
 /// 3. Materialize task (includes retry feedback if attempt > 0)
 /// 4. Build MaterializedTask with env vars
@@ -62,12 +154,71 @@ pub struct LifecycleConfig {
 /// 8. Collect events with timeout
 /// 9. Extract results from container to host worktree (no-op for worktree mode)
 /// 10. Run gate on host worktree
-/// 11. Evaluate verdict -> return LifecycleResult
+/// 11. Evaluate verdict -> return LifecycleResult (on auto-pass, commit code
+///     tasks to their branch; bundle artifact tasks' artifacts). An
+///     `llm_review` gate is reviewed by `reviewer`, or by `harness` if none.
+///     With `attestation` on, a code task whose invariants passed gets a
+///     compliance attestation first.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_agent_lifecycle(
     pool: &SqlitePool,
     task: &Task,
     plan_name: &str,
     harness: &dyn Harness,
+    reviewer: Option<&dyn Harness>,
+    isolation: &dyn Isolation,
+    token_config: &TokenConfig,
+    config: &LifecycleConfig,
+) -> Result<LifecycleResult> {
+    // A root span: each lifecycle is its own trace rather than a child of
+    // whatever the orchestrator loop happens to be doing. The run ID ties
+    // it back to the dispatch.
+    let attempt_id = Uuid::new_v4();
+    let span = tracing::info_span!(
+        parent: None,
+        "task_lifecycle",
+        run_id = tracing::field::Empty,
+        attempt_id = %attempt_id,
+        task_id = %task.id,
+        task_name = %task.name,
+        plan_id = %task.plan_id,
+        plan_name = %plan_name,
+        attempt = task.attempt,
+        harness = %harness.name(),
+        result = tracing::field::Empty,
+    );
+    if let Some(run_id) = config.run_id {
+        span.record("run_id", tracing::field::display(run_id));
+    }
+    let result = run_lifecycle_steps(
+        pool,
+        task,
+        attempt_id,
+        plan_name,
+        harness,
+        reviewer,
+        isolation,
+        token_config,
+        config,
+    )
+    .instrument(span.clone())
+    .await;
+    match &result {
+        Ok(r) => span.record("result", tracing::field::debug(r)),
+        Err(_) => span.record("result", "error"),
+    };
+    result
+}
+
+/// The lifecycle steps, run inside the `task_lifecycle` span.
+#[allow(clippy::too_many_arguments)]
+async fn run_lifecycle_steps(
+    pool: &SqlitePool,
+    task: &Task,
+    attempt_id: Uuid,
+    plan_name: &str,
+    harness: &dyn Harness,
+    reviewer: Option<&dyn Harness>,
     isolation: &dyn Isolation,
     token_config: &TokenConfig,
     config: &LifecycleConfig,
@@ -82,9 +233,23 @@ pub async fn run_agent_lifecycle(
         "starting agent lifecycle"
     );
 
+    // Everything recorded for this attempt from here on carries its IDs.
+    task_db::set_correlation(pool, task_id, config.run_id, attempt_id).await?;
+
+    // Plans stored before validation rejected the combination.
+    if config.ephemeral_db.is_some() && isolation.name() == "container" {
+        bail!(
+            "task {}: ephemeral_db cannot be used with container isolation, whose agent \
+             cannot reach the database on the host's loopback",
+            task.name
+        );
+    }
+
     // 1. Create workspace via isolation backend.
+    let mut phase_start = Instant::now();
     let workspace = isolation
-        .create_workspace(plan_name, &task.name)
+        .create_workspace(plan_name, &task.name, &task.paths)
+        .instrument(tracing::info_span!("create_workspace"))
         .await
         .with_context(|| format!("failed to create workspace for task {}", task.name))?;
 
@@ -96,16 +261,122 @@ pub async fn run_agent_lifecycle(
         .clone()
         .unwrap_or_else(|| workspace.path.clone());
 
-    // 2. Generate scoped token.
+    record_phase(pool, task, LifecyclePhase::Workspace, &mut phase_start).await;
+
+    // 1b. Provision the attempt's ephemeral database. It is torn down once
+    //     the gate has run; dropping it on any other return path removes
+    //     the container too.
+    let ephemeral_db = match &config.ephemeral_db {
+        Some(image) => Some(
+            EphemeralDb::provision(image, &format!("{task_id}-{attempt}"))
+                .instrument(tracing::info_span!("provision_ephemeral_db"))
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to provision ephemeral database for task {}",
+                        task.name
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    // 1c. Bring up the task's compose services in the host worktree.
+    //     Dropping them on any return path runs `compose down`.
+    let services = if task.services.is_empty() {
+        None
+    } else {
+        let project = format!("gator-{}-{attempt}", task_id.simple());
+        Some(
+            ComposeServices::up(&project, &task.services, &host_worktree_path)
+                .instrument(tracing::info_span!("services_up"))
+                .await
+                .with_context(|| format!("failed to start services for task {}", task.name))?,
+        )
+    };
+
+    if ephemeral_db.is_some() || services.is_some() {
+        record_phase(pool, task, LifecyclePhase::Services, &mut phase_start).await;
+    }
+
+    // The workspace's own environment (shared cache locations) comes first,
+    // so setup, the agent, and the gate all use the same caches.
+    let mut service_env = workspace.env.clone();
+    if let Some(ref db) = ephemeral_db {
+        service_env.extend(db.env_vars());
+    }
+    if let Some(ref services) = services {
+        service_env.extend(services.env_vars());
+    }
+    // Overrides from `gator retry --env` reach the agent and the gate. They
+    // win over service variables, but not over gator's own below.
+    if let Some(overrides) = task.attempt_env() {
+        tracing::info!(task_id = %task_id, vars = ?overrides.keys().collect::<Vec<_>>(), "attempt env overrides");
+        service_env.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    // 1d. Set the workspace up (e.g. `npm ci`): the plan's setup commands,
+    //     then the task's. An agent in a workspace that could not be set up
+    //     would only waste its attempt, so a failure fails the attempt.
+    let setup: Vec<&str> = config
+        .setup_commands
+        .iter()
+        .chain(task.setup_commands.iter())
+        .map(String::as_str)
+        .collect();
+    if !setup.is_empty() {
+        let failure = run_setup(pool, task, &setup, &workspace, &service_env, config.timeout)
+            .instrument(tracing::info_span!("setup"))
+            .await?;
+        record_phase(pool, task, LifecyclePhase::Setup, &mut phase_start).await;
+        if let Some(message) = failure {
+            tracing::warn!(task_id = %task_id, %message, "setup failed; not starting the agent");
+            dispatch::assign_task(pool, task_id, harness.name(), &host_worktree_path)
+                .await
+                .with_context(|| format!("failed to assign task {}", task.name))?;
+            dispatch::fail_setup(pool, task_id, task.attempt, &message).await?;
+            return Ok(if task.attempt < task.retry_max {
+                LifecycleResult::FailedCanRetry
+            } else {
+                LifecycleResult::FailedNoRetry
+            });
+        }
+    }
+
+    // 1e. Warm the workspace up (e.g. a first build) so the agent does not
+    //     spend its time on it. A failed warm-up is recorded and the agent
+    //     starts anyway: fixing the build may be its job.
+    if let Some(ref command) = config.warmup_command {
+        run_warmup(
+            pool,
+            task,
+            command,
+            &workspace,
+            &service_env,
+            config.timeout,
+        )
+        .instrument(tracing::info_span!("warmup"))
+        .await?;
+        record_phase(pool, task, LifecyclePhase::Warmup, &mut phase_start).await;
+    }
+
+    // 2. Generate scoped token, with the plan's own key when per-plan
+    // secrets are enabled.
+    let token_config = &token_config.for_plan(task.plan_id);
     let agent_token = token::generate_token(token_config, task_id, attempt);
 
     // 3. Materialize task description.
     let task_description = materialize_task(pool, task_id)
         .await
         .with_context(|| format!("failed to materialize task {}", task.name))?;
+    let task_description = match config.prompt_budget {
+        Some(ref budget) => fit_prompt(pool, task, budget, &task_description).await,
+        None => task_description,
+    };
+    let prompt_sha256 = attestation::prompt_sha256(&task_description);
 
     // 4. Build MaterializedTask.
-    let invariants = inv_db::get_invariants_for_task(pool, task_id).await?;
+    let invariants = inv_db::get_enabled_invariants_for_task(pool, task_id).await?;
     let invariant_commands: Vec<String> = invariants
         .iter()
         .map(|inv| {
@@ -117,17 +388,25 @@ pub async fn run_agent_lifecycle(
         })
         .collect();
 
-    let mut env_vars = HashMap::new();
+    let mut env_vars = service_env.clone();
     env_vars.insert("GATOR_AGENT_TOKEN".to_string(), agent_token);
     // Forward database URL if available.
     if let Ok(db_url) = std::env::var("GATOR_DATABASE_URL") {
         env_vars.insert("GATOR_DATABASE_URL".to_string(), db_url);
     }
-    // Always forward the token secret from the resolved config.
+    // Always forward the signing secret (per-plan when enabled). Previous
+    // secrets stay with the orchestrator.
     env_vars.insert(
         "GATOR_TOKEN_SECRET".to_string(),
         hex::encode(&token_config.secret),
     );
+    env_vars.insert(
+        "GATOR_TOKEN_KEY_ID".to_string(),
+        token_config.key_id.clone(),
+    );
+    if !token_config.accept_v1 {
+        env_vars.insert("GATOR_TOKEN_ACCEPT_V1".to_string(), "0".to_string());
+    }
     // If running in a container, expose the container ID and sandbox flag.
     if let Some(ref cid) = workspace.container_id {
         env_vars.insert("GATOR_CONTAINER_ID".to_string(), cid.clone());
@@ -143,17 +422,31 @@ pub async fn run_agent_lifecycle(
         env_vars,
     };
 
+    record_phase(pool, task, LifecyclePhase::Materialize, &mut phase_start).await;
+
     // 5. Assign task (pending -> assigned).
     // Store the host-side path so the gate runner can find the worktree.
     dispatch::assign_task(pool, task_id, harness.name(), &host_worktree_path)
         .await
         .with_context(|| format!("failed to assign task {}", task.name))?;
 
-    // 6. Spawn agent.
+    // 6. Spawn agent. The `agent_run` span covers spawn through the end of
+    //    event collection.
+    let agent_span = tracing::info_span!(
+        "agent_run",
+        pid = tracing::field::Empty,
+        timed_out = false,
+        stalled = false
+    );
     let mut handle = harness
         .spawn(&materialized)
+        .instrument(agent_span.clone())
         .await
         .with_context(|| format!("failed to spawn agent for task {}", task.name))?;
+    agent_span.record("pid", handle.pid);
+
+    // Record where the agent runs so `gator admin stop-all` can kill it.
+    record_agent_process(pool, task_id, handle.pid, workspace.container_id.as_deref()).await;
 
     // 6b. Write the task prompt to stdin and close it.
     //     Claude Code in `-p` mode reads the user prompt from stdin.
@@ -166,59 +459,175 @@ pub async fn run_agent_lifecycle(
         drop(stdin); // Close stdin so the agent starts processing.
     }
 
-    // 7. Start task (assigned -> running).
-    dispatch::start_task(pool, task_id)
-        .await
-        .with_context(|| format!("failed to start task {}", task.name))?;
+    // 7. Start task (assigned -> running). If the task was stopped while
+    //    the agent spawned, kill the agent rather than leave it running.
+    if let Err(e) = dispatch::start_task(pool, task_id).await {
+        if let Err(kill_err) = harness.kill(&handle).await {
+            tracing::warn!(task_id = %task_id, error = %kill_err, "failed to kill agent");
+        }
+        return Err(e).with_context(|| format!("failed to start task {}", task.name));
+    }
 
-    // 8. Collect events with timeout.
-    let event_stream = harness.events(&handle);
-    let collect_result = tokio::time::timeout(
-        config.timeout,
-        collect_events(pool, task_id, task.attempt, event_stream),
-    )
-    .await;
+    // 8. Collect events with timeout. An agent that asked the operator a
+    //    question (`gator ask`) ends its turn; once `gator answer` arrives,
+    //    its session is resumed with the answer and collection goes on.
+    //    Time spent waiting on the operator does not count against the
+    //    timeout. An agent that goes quiet for the stall timeout is killed
+    //    like one that times out, and one whose task is cancelled is killed
+    //    at once.
+    let mut remaining = config.timeout;
+    let mut last_answer = None;
+    loop {
+        let turn_start = Instant::now();
+        let turn_started_at = chrono::Utc::now();
+        let collect = collect_events(pool, task_id, task.attempt, harness.events(&handle));
+        let collect_result = tokio::time::timeout(remaining, async {
+            tokio::select! {
+                result = collect => Ok(result),
+                interruption = watch_agent(pool, task, config.stall_timeout, turn_started_at) => {
+                    Err(interruption)
+                }
+            }
+        })
+        .instrument(agent_span.clone())
+        .await;
+        remaining = remaining.saturating_sub(turn_start.elapsed());
 
-    match collect_result {
-        Ok(Ok(())) => {
-            tracing::info!(task_id = %task_id, "agent completed normally");
-        }
-        Ok(Err(e)) => {
-            tracing::warn!(task_id = %task_id, error = %e, "error collecting events");
-            // Continue to gate check anyway.
-        }
-        Err(_elapsed) => {
-            tracing::warn!(task_id = %task_id, "agent timed out");
-            // Kill the agent.
-            if let Err(e) = harness.kill(&handle).await {
-                tracing::warn!(task_id = %task_id, error = %e, "failed to kill timed-out agent");
+        match collect_result {
+            Ok(Ok(Ok(()))) => {
+                tracing::info!(task_id = %task_id, "agent completed normally");
+            }
+            Ok(Ok(Err(e))) => {
+                tracing::warn!(task_id = %task_id, error = %e, "error collecting events");
+                // Continue to gate check anyway.
+            }
+            Ok(Err(Interruption::Cancelled)) => {
+                tracing::info!(task_id = %task_id, "task cancelled, killing its agent");
+                if let Err(e) = harness.kill(&handle).await {
+                    tracing::warn!(task_id = %task_id, error = %e, "failed to kill cancelled agent");
+                }
+                record_phase(pool, task, LifecyclePhase::Agent, &mut phase_start).await;
+                return Ok(LifecycleResult::Cancelled);
+            }
+            Ok(Err(Interruption::Stalled(idle))) => {
+                agent_span.record("stalled", true);
+                tracing::warn!(task_id = %task_id, idle_secs = idle.as_secs(), "agent stalled");
+                if let Err(e) = harness.kill(&handle).await {
+                    tracing::warn!(task_id = %task_id, error = %e, "failed to kill stalled agent");
+                }
+                record_phase(pool, task, LifecyclePhase::Agent, &mut phase_start).await;
+                dispatch::stall_task(pool, task_id, task.attempt, idle).await?;
+                return Ok(LifecycleResult::Stalled);
+            }
+            Err(_elapsed) => {
+                agent_span.record("timed_out", true);
+                tracing::warn!(task_id = %task_id, "agent timed out");
+                // Kill the agent.
+                if let Err(e) = harness.kill(&handle).await {
+                    tracing::warn!(task_id = %task_id, error = %e, "failed to kill timed-out agent");
+                }
+                record_phase(pool, task, LifecyclePhase::Agent, &mut phase_start).await;
+                // Transition running -> checking -> failed in one transaction.
+                dispatch::time_out_task(pool, task_id, task.attempt, config.timeout).await?;
+                return Ok(LifecycleResult::TimedOut);
             }
-            // Transition running -> checking -> failed.
-            dispatch::begin_checking(pool, task_id).await?;
-            dispatch::fail_task(pool, task_id).await?;
-            return Ok(LifecycleResult::TimedOut);
         }
+
+        let Some(answer) = wait_for_answer(pool, task, &mut last_answer).await? else {
+            break;
+        };
+        let message = format!("The operator answered your question:\n\n{answer}");
+        harness
+            .send(&handle, &message)
+            .instrument(agent_span.clone())
+            .await
+            .with_context(|| format!("failed to resume agent for task {}", task.name))?;
+        let pid = harness.agent_pid(&handle).await;
+        record_agent_process(pool, task_id, pid, workspace.container_id.as_deref()).await;
+        tracing::info!(task_id = %task_id, pid, "resumed agent with the operator's answer");
     }
 
+    record_phase(pool, task, LifecyclePhase::Agent, &mut phase_start).await;
+
     // 9. Extract results from container (no-op for worktree isolation).
     isolation
         .extract_results(&workspace)
+        .instrument(tracing::info_span!("extract_results"))
         .await
         .with_context(|| format!("failed to extract results for task {}", task.name))?;
 
+    record_phase(pool, task, LifecyclePhase::Extract, &mut phase_start).await;
+
     // 10. Run gate on host worktree.
-    let gate_runner = GateRunner::new(pool);
+    let gate_runner = GateRunner::new(pool)
+        .with_env(service_env)
+        .with_parallelism(config.gate_parallelism)
+        .with_cache(config.gate_cache);
     let verdict = gate_runner
         .run_gate(task_id)
+        .instrument(tracing::info_span!("gate"))
         .await
         .with_context(|| format!("gate check failed for task {}", task.name))?;
 
+    // 10b. Remember how it went for later tasks of the project.
+    if let Err(e) = memory::record_gate_outcome(pool, task, &verdict).await {
+        tracing::warn!(task_id = %task_id, error = %e, "failed to record memory (non-fatal)");
+    }
+
+    record_phase(pool, task, LifecyclePhase::Gate, &mut phase_start).await;
+
     // 11. Evaluate verdict.
-    let action = evaluate_verdict(pool, task_id, &verdict)
+    let action = evaluate_verdict(
+        pool,
+        task_id,
+        &verdict,
+        Some(reviewer.unwrap_or(harness)),
+        &config.auto_approve,
+    )
+    .instrument(tracing::info_span!("evaluate_verdict"))
+    .await
+    .with_context(|| format!("failed to evaluate verdict for task {}", task.name))?;
+
+    // 11b. Attest the change of a code task whose invariants passed, before
+    //      an auto-pass commits it to the branch.
+    if config.attestation
+        && task.kind == TaskKind::Code
+        && matches!(action, GateAction::AutoPassed | GateAction::HumanRequired)
+        && matches!(verdict, GateVerdict::Passed)
+    {
+        let version = harness.version().await;
+        match attestation::attest(
+            pool,
+            task,
+            harness.name(),
+            version.as_deref(),
+            &prompt_sha256,
+            &host_worktree_path,
+        )
         .await
-        .with_context(|| format!("failed to evaluate verdict for task {}", task.name))?;
+        {
+            Ok(attested) => {
+                tracing::info!(task_id = %task_id, path = %attested.path, diff_sha256 = %attested.diff_sha256, "wrote compliance attestation");
+            }
+            Err(e) => {
+                tracing::warn!(task_id = %task_id, error = %format!("{e:#}"), "failed to write compliance attestation (non-fatal)");
+            }
+        }
+    }
 
     let result = match action {
+        GateAction::AutoPassed if task.kind == TaskKind::Artifact => {
+            // Artifact tasks produce a bundle instead of a branch to merge.
+            match artifact::bundle_task(pool, task, &host_worktree_path).await {
+                Ok(dest) => {
+                    tracing::info!(task_id = %task_id, bundle = %dest.display(), "wrote artifact bundle");
+                }
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %e, "failed to write artifact bundle (non-fatal)");
+                }
+            }
+            LifecycleResult::Passed
+        }
         GateAction::AutoPassed => {
             // Commit all agent work to the worktree branch so `gator merge` can find it.
             match commit_agent_work(&host_worktree_path, &task.name, attempt) {
@@ -238,6 +647,16 @@ pub async fn run_agent_lifecycle(
         GateAction::AutoFailed { can_retry: false } => LifecycleResult::FailedNoRetry,
         GateAction::HumanRequired => LifecycleResult::HumanRequired,
     };
+    record_phase(pool, task, LifecyclePhase::Verdict, &mut phase_start).await;
+
+    // 11c. The gate has run: remove the attempt's database. Early returns
+    //      leave that to its `Drop`.
+    if let Some(db) = ephemeral_db {
+        db.teardown().await;
+    }
+
+    // 12. Measure the worktree for the quota in `gator status`.
+    record_disk_usage(pool, task_id, host_worktree_path).await;
 
     tracing::info!(
         task_id = %task_id,
@@ -249,6 +668,329 @@ pub async fn run_agent_lifecycle(
     Ok(result)
 }
 
+/// Once the agent's turn has ended, return the operator's answer to a
+/// question it asked with `gator ask`, waiting while the task is
+/// `blocked_on_human`; `None` when there is no new answer. `last_answer` is
+/// the ID of the answer event delivered last.
+///
+/// Fails when the task leaves `blocked_on_human` other than by an answer
+/// (it was cancelled or stopped).
+async fn wait_for_answer(
+    pool: &SqlitePool,
+    task: &Task,
+    last_answer: &mut Option<i64>,
+) -> Result<Option<String>> {
+    let mut waiting = false;
+    loop {
+        let current = task_db::get_task(pool, task.id)
+            .await?
+            .with_context(|| format!("task {} not found", task.id))?;
+        match current.status {
+            TaskStatus::BlockedOnHuman => {
+                if !waiting {
+                    tracing::info!(task_id = %task.id, "agent is waiting for an answer");
+                    waiting = true;
+                }
+                tokio::time::sleep(ANSWER_POLL_INTERVAL).await;
+            }
+            TaskStatus::Running => {
+                let answer =
+                    agent_events::get_latest_event_of_type(pool, task.id, task.attempt, "answer")
+                        .await?
+                        .filter(|event| Some(event.id) != *last_answer);
+                let Some(answer) = answer else {
+                    return Ok(None);
+                };
+                *last_answer = Some(answer.id);
+                let text = answer.payload["answer"].as_str().unwrap_or_default();
+                return Ok(Some(text.to_string()));
+            }
+            other => bail!(
+                "task {} became {} while its agent waited for an answer",
+                task.name,
+                other
+            ),
+        }
+    }
+}
+
+/// Why [`watch_agent`] cut an agent's turn short.
+enum Interruption {
+    /// No activity for the stall timeout.
+    Stalled(Duration),
+    /// The operator cancelled the task (`gator cancel`).
+    Cancelled,
+}
+
+/// Watch the agent of `task` during a turn. Returns once the task has been
+/// cancelled, or once the agent has been quiet for `stall_timeout`: no event
+/// recorded for its attempt (output or `gator progress` calls) since then,
+/// counting from `turn_started_at` at the earliest.
+async fn watch_agent(
+    pool: &SqlitePool,
+    task: &Task,
+    stall_timeout: Option<Duration>,
+    turn_started_at: chrono::DateTime<chrono::Utc>,
+) -> Interruption {
+    let poll = stall_timeout.map_or(WATCH_POLL_INTERVAL, |idle| {
+        WATCH_POLL_INTERVAL.min(idle / 2)
+    });
+    loop {
+        tokio::time::sleep(poll).await;
+        match task_db::get_task(pool, task.id).await {
+            Ok(Some(current)) if current.status == TaskStatus::Cancelled => {
+                return Interruption::Cancelled;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(task_id = %task.id, error = %e, "failed to check task status");
+            }
+        }
+        let Some(idle) = stall_timeout else {
+            continue;
+        };
+        let last_event = match agent_events::last_event_at(pool, task.id, task.attempt).await {
+            Ok(at) => at,
+            Err(e) => {
+                tracing::warn!(task_id = %task.id, error = %e, "failed to check agent activity");
+                continue;
+            }
+        };
+        let last_activity = last_event.map_or(turn_started_at, |at| at.max(turn_started_at));
+        let quiet = (chrono::Utc::now() - last_activity)
+            .to_std()
+            .unwrap_or_default();
+        if quiet >= idle {
+            return Interruption::Stalled(idle);
+        }
+    }
+}
+
+/// A shell command that runs `command_line` in the attempt's workspace
+/// (inside the container under container isolation) with `env`.
+fn workspace_command(
+    command_line: &str,
+    workspace: &WorkspaceInfo,
+    env: &HashMap<String, String>,
+) -> tokio::process::Command {
+    match &workspace.container_id {
+        Some(container_id) => {
+            let mut command = tokio::process::Command::new("docker");
+            command.arg("exec").arg("-w").arg(&workspace.path);
+            for (key, value) in env {
+                command.arg("-e").arg(format!("{key}={value}"));
+            }
+            command.args([container_id.as_str(), "sh", "-c", command_line]);
+            command
+        }
+        None => {
+            let mut command = tokio::process::Command::new("sh");
+            command
+                .args(["-c", command_line])
+                .current_dir(&workspace.path)
+                .envs(env);
+            command
+        }
+    }
+}
+
+/// Run `command_line` in the workspace; a command that cannot be started
+/// counts as failed, with the reason as its stderr.
+async fn run_in_workspace(
+    label: &str,
+    command_line: &str,
+    workspace: &WorkspaceInfo,
+    env: &HashMap<String, String>,
+    timeout: Duration,
+) -> InvariantResult {
+    let command = workspace_command(command_line, workspace, env);
+    runner::run_captured(command, label, command_line, timeout)
+        .await
+        .unwrap_or_else(|e| InvariantResult {
+            passed: false,
+            exit_code: None,
+            timed_out: false,
+            stdout: String::new(),
+            stderr: format!("{e:#}"),
+            duration_ms: 0,
+        })
+}
+
+/// Run the setup commands in order in the attempt's workspace, recording
+/// each result, and stop at the first that fails. Returns why it failed,
+/// or `None` when every command passed. Only failing to record a result
+/// is an error.
+async fn run_setup(
+    pool: &SqlitePool,
+    task: &Task,
+    commands: &[&str],
+    workspace: &WorkspaceInfo,
+    env: &HashMap<String, String>,
+    timeout: Duration,
+) -> Result<Option<String>> {
+    for (position, command_line) in commands.iter().enumerate() {
+        let result = run_in_workspace("setup", command_line, workspace, env, timeout).await;
+        setup_results::insert_setup_result(
+            pool,
+            &NewSetupResult {
+                task_id: task.id,
+                attempt: task.attempt,
+                position: position as i32,
+                command: command_line.to_string(),
+                passed: result.passed,
+                exit_code: result.exit_code,
+                timed_out: result.timed_out,
+                stdout: Some(result.stdout),
+                stderr: Some(result.stderr.clone()),
+                duration_ms: result.duration_ms,
+            },
+        )
+        .await?;
+        if !result.passed {
+            let how = match result.exit_code {
+                _ if result.timed_out => "timed out".to_string(),
+                Some(code) => format!("exited with {code}"),
+                None => "could not run".to_string(),
+            };
+            let mut message = format!("setup command `{command_line}` {how}");
+            if let Some(last) = result.stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+                message.push_str(&format!(": {}", last.trim()));
+            }
+            return Ok(Some(message));
+        }
+        tracing::info!(task_id = %task.id, command = %command_line, duration_ms = result.duration_ms, "setup command finished");
+    }
+    Ok(None)
+}
+
+/// Run the plan's warm-up command in the attempt's workspace (inside the
+/// container under container isolation) with the attempt's environment,
+/// and record the result. Only failing to record it is an error.
+async fn run_warmup(
+    pool: &SqlitePool,
+    task: &Task,
+    command_line: &str,
+    workspace: &WorkspaceInfo,
+    env: &HashMap<String, String>,
+    timeout: Duration,
+) -> Result<()> {
+    let result = run_in_workspace("warm-up", command_line, workspace, env, timeout).await;
+    if result.passed {
+        tracing::info!(task_id = %task.id, duration_ms = result.duration_ms, "warm-up finished");
+    } else {
+        tracing::warn!(
+            task_id = %task.id,
+            exit_code = ?result.exit_code,
+            timed_out = result.timed_out,
+            "warm-up failed; starting the agent anyway"
+        );
+    }
+
+    warmup_results::insert_warmup_result(
+        pool,
+        &NewWarmupResult {
+            task_id: task.id,
+            attempt: task.attempt,
+            command: command_line.to_string(),
+            passed: result.passed,
+            exit_code: result.exit_code,
+            timed_out: result.timed_out,
+            stdout: Some(result.stdout),
+            stderr: Some(result.stderr),
+            duration_ms: result.duration_ms,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Record the agent's host, process and container on its task, so it can
+/// be killed from another process or by a restarted dispatch. Failing to
+/// record it never fails the lifecycle.
+async fn record_agent_process(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    pid: u32,
+    container_id: Option<&str>,
+) {
+    let host = orchestrator::host();
+    let started = orchestrator::process_start_time(pid);
+    let process = task_db::AgentProcess {
+        pid: Some(pid),
+        started: started.as_deref(),
+        host: Some(&host),
+        container_id,
+    };
+    if let Err(e) = task_db::set_agent_process(pool, task_id, &process).await {
+        tracing::warn!(task_id = %task_id, error = %e, "failed to record agent process");
+    }
+}
+
+/// Record the time since `phase_start` as `phase` of this attempt and restart
+/// the clock. Failing to record a timing never fails the lifecycle.
+async fn record_phase(
+    pool: &SqlitePool,
+    task: &Task,
+    phase: LifecyclePhase,
+    phase_start: &mut Instant,
+) {
+    let elapsed = phase_start.elapsed();
+    *phase_start = Instant::now();
+    if let Err(e) = attempt_phases::record_phase(pool, task.id, task.attempt, phase, elapsed).await
+    {
+        tracing::warn!(task_id = %task.id, %phase, error = %e, "failed to record phase timing (non-fatal)");
+    }
+}
+
+/// Store how much disk the worktree at `path` takes on the task. The walk
+/// can take a while over large build outputs, so it runs on the blocking
+/// pool. Failing to measure never fails the lifecycle.
+async fn record_disk_usage(pool: &SqlitePool, task_id: Uuid, path: PathBuf) {
+    let measured = tokio::task::spawn_blocking(move || crate::worktree::disk::disk_usage(&path))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r.map_err(anyhow::Error::from));
+    let result = match measured {
+        Ok(bytes) => task_db::set_disk_usage(pool, task_id, bytes).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!(task_id = %task_id, error = %e, "failed to record worktree disk usage (non-fatal)");
+    }
+}
+
+/// Trim a materialized task to the prompt budget, recording each dropped
+/// section on the attempt. Recording failures are logged, not fatal.
+async fn fit_prompt(pool: &SqlitePool, task: &Task, budget: &PromptBudget, prompt: &str) -> String {
+    let fitted = budget.fit(prompt);
+    for trim in &fitted.trimmed {
+        let tokens = i64::try_from(trim.estimated_tokens).unwrap_or(i64::MAX);
+        if let Err(e) =
+            prompt_trims::record_trim(pool, task.id, task.attempt, &trim.section, tokens).await
+        {
+            tracing::warn!(task_id = %task.id, section = %trim.section, error = %e, "failed to record prompt trim (non-fatal)");
+        }
+    }
+    if !fitted.trimmed.is_empty() {
+        tracing::info!(
+            task = %task.name,
+            estimated_tokens = fitted.estimated_tokens,
+            max_tokens = budget.max_tokens,
+            trimmed = ?fitted.trimmed.iter().map(|t| &t.section).collect::<Vec<_>>(),
+            "trimmed task prompt to fit the budget"
+        );
+    }
+    if fitted.estimated_tokens > budget.max_tokens {
+        tracing::warn!(
+            task = %task.name,
+            estimated_tokens = fitted.estimated_tokens,
+            max_tokens = budget.max_tokens,
+            "task prompt is over budget after trimming every trimmable section"
+        );
+    }
+    fitted.text
+}
+
 /// Commit all agent work in a worktree (git add -A + git commit).
 ///
 /// Returns `Ok(true)` if a commit was created, `Ok(false)` if there was
@@ -0,0 +1,247 @@
+//! Long-term memory: past task outcomes of a project, retrieved into new
+//! tasks.
+//!
+//! Every gate run records a memory in the project's store -- what the task
+//! was asked to do and either the agent's closing summary (gate passed) or
+//! the failing checks (gate failed). When a task is materialized, the most
+//! relevant memories of other tasks in the same project are included, so
+//! agents see how similar work went before.
+//!
+//! Relevance ranking is pluggable through [`Retriever`]; the default is
+//! [`TfIdfRetriever`], a plain TF-IDF cosine similarity.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+use gator_db::models::{Memory, Task};
+use gator_db::queries::agent_events;
+use gator_db::queries::memories::{self, NewMemory};
+use gator_db::queries::plans as plan_db;
+
+use crate::gate::GateVerdict;
+use crate::plan::materialize::truncate_feedback_snippet;
+
+/// How many memories are included in a materialized task.
+pub const RECALL_TOP_K: usize = 3;
+
+/// Only the newest this-many memories of a project are ranked.
+const RECALL_CANDIDATES: i64 = 2000;
+
+/// Matches scoring below this are not worth the prompt space.
+const MIN_SCORE: f64 = 0.1;
+
+/// Ranks stored memories by relevance to a query.
+pub trait Retriever: Send + Sync {
+    /// The best matches for `query`, as `(index into documents, score)`,
+    /// best first, at most `k` of them. Scores are in `0.0..=1.0`.
+    fn rank(&self, query: &str, documents: &[&str], k: usize) -> Vec<(usize, f64)>;
+}
+
+/// TF-IDF weighted cosine similarity over word tokens.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TfIdfRetriever;
+
+impl Retriever for TfIdfRetriever {
+    fn rank(&self, query: &str, documents: &[&str], k: usize) -> Vec<(usize, f64)> {
+        let docs: Vec<HashMap<String, f64>> = documents.iter().map(|d| term_counts(d)).collect();
+        let mut df: HashMap<&str, usize> = HashMap::new();
+        for doc in &docs {
+            for term in doc.keys() {
+                *df.entry(term.as_str()).or_default() += 1;
+            }
+        }
+        let n = docs.len() as f64;
+        let idf =
+            |term: &str| ((n + 1.0) / (df.get(term).copied().unwrap_or(0) as f64 + 1.0)).ln() + 1.0;
+        let weigh = |counts: &HashMap<String, f64>| -> HashMap<String, f64> {
+            counts
+                .iter()
+                .map(|(term, tf)| (term.clone(), tf * idf(term)))
+                .collect()
+        };
+
+        let query = weigh(&term_counts(query));
+        let query_norm = norm(&query);
+        if query_norm == 0.0 {
+            return Vec::new();
+        }
+        let mut scored: Vec<(usize, f64)> = docs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, doc)| {
+                let doc = weigh(doc);
+                let doc_norm = norm(&doc);
+                if doc_norm == 0.0 {
+                    return None;
+                }
+                let dot: f64 = query
+                    .iter()
+                    .filter_map(|(term, w)| doc.get(term).map(|d| w * d))
+                    .sum();
+                (dot > 0.0).then_some((i, dot / (query_norm * doc_norm)))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored.truncate(k);
+        scored
+    }
+}
+
+fn norm(vector: &HashMap<String, f64>) -> f64 {
+    vector.values().map(|w| w * w).sum::<f64>().sqrt()
+}
+
+/// Lowercased word counts, skipping short words, numbers, and stopwords.
+fn term_counts(text: &str) -> HashMap<String, f64> {
+    const STOPWORDS: &[&str] = &[
+        "the", "and", "for", "that", "this", "with", "from", "into", "are", "was", "were", "not",
+        "but", "you", "your", "all", "any", "can", "should", "must", "will", "have", "has", "its",
+        "use", "when", "then", "than", "them", "they", "also", "only", "each", "other",
+    ];
+    let mut counts = HashMap::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(str::to_lowercase)
+        .filter(|w| w.len() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+    {
+        *counts.entry(word).or_insert(0.0) += 1.0;
+    }
+    counts
+}
+
+/// Record the outcome of a gate run for `task` as a memory of its project.
+pub async fn record_gate_outcome(
+    pool: &SqlitePool,
+    task: &Task,
+    verdict: &GateVerdict,
+) -> Result<Memory> {
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+
+    let mut content = truncate_feedback_snippet(task.description.trim(), 1000);
+    let kind = match verdict {
+        GateVerdict::Passed => {
+            content.push_str(&format!("\n\nPassed the gate on attempt {}.", task.attempt));
+            if let Some(summary) = final_agent_message(pool, task).await? {
+                content.push_str("\nAgent summary: ");
+                content.push_str(&truncate_feedback_snippet(summary.trim(), 1000));
+            }
+            "success"
+        }
+        GateVerdict::Failed { failures } => {
+            content.push_str(&format!("\n\nFailed the gate on attempt {}:", task.attempt));
+            for failure in failures {
+                let exit = if failure.timed_out {
+                    "timed out".to_string()
+                } else {
+                    failure
+                        .exit_code
+                        .map_or_else(|| "signal".to_string(), |c| format!("exit {c}"))
+                };
+                content.push_str(&format!("\n- {} ({exit})", failure.invariant_name));
+                let stderr = failure.stderr_snippet.trim();
+                if !stderr.is_empty() {
+                    content.push_str(": ");
+                    content.push_str(&truncate_feedback_snippet(stderr, 300));
+                }
+            }
+            "failure"
+        }
+    };
+
+    memories::insert_memory(
+        pool,
+        &NewMemory {
+            project_path: &plan.project_path,
+            task_id: task.id,
+            attempt: task.attempt,
+            kind,
+            title: &format!("{}/{}", plan.name, task.name),
+            content: &content,
+        },
+    )
+    .await
+}
+
+/// The last assistant message of the task's current attempt.
+async fn final_agent_message(pool: &SqlitePool, task: &Task) -> Result<Option<String>> {
+    let events = agent_events::list_events_for_task(pool, task.id, task.attempt).await?;
+    Ok(events
+        .iter()
+        .rev()
+        .filter(|e| e.event_type == "message" && e.payload["role"] == "assistant")
+        .find_map(|e| e.payload["content"].as_str().map(str::to_string))
+        .filter(|s| !s.trim().is_empty()))
+}
+
+/// The `k` memories of `task`'s project most relevant to it, best first,
+/// excluding the task's own (its retries already get gate feedback).
+pub async fn recall(
+    pool: &SqlitePool,
+    task: &Task,
+    retriever: &dyn Retriever,
+    k: usize,
+) -> Result<Vec<Memory>> {
+    let plan = plan_db::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let candidates: Vec<Memory> =
+        memories::list_memories_for_project(pool, &plan.project_path, RECALL_CANDIDATES)
+            .await?
+            .into_iter()
+            .filter(|m| m.task_id != Some(task.id))
+            .collect();
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let documents: Vec<String> = candidates
+        .iter()
+        .map(|m| format!("{}\n{}", m.title, m.content))
+        .collect();
+    let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+    let query = format!("{}\n{}", task.name, task.description);
+
+    // Several memories of one task (say, two failed attempts) say much the
+    // same thing; keep only the best-scoring one per title.
+    let mut seen = HashSet::new();
+    Ok(retriever
+        .rank(&query, &documents, candidates.len())
+        .into_iter()
+        .filter(|(_, score)| *score >= MIN_SCORE)
+        .filter(|(i, _)| seen.insert(candidates[*i].title.clone()))
+        .take(k)
+        .map(|(i, _)| candidates[i].clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tfidf_ranks_topical_matches_first() {
+        let docs = [
+            "Add a database migration for the users table; sqlx migrate failed on the index",
+            "Write the README section about installation",
+            "Fix flaky websocket reconnect test in the client",
+        ];
+        let ranked = TfIdfRetriever.rank("Add a migration that indexes the orders table", &docs, 3);
+
+        assert_eq!(ranked[0].0, 0);
+        assert!(ranked[0].1 > 0.0 && ranked[0].1 <= 1.0);
+        assert!(ranked.iter().all(|(i, _)| *i != 2), "unrelated doc scored");
+    }
+
+    #[test]
+    fn tfidf_ignores_stopwords_and_empty_queries() {
+        let docs = ["the and for with", "parser error recovery"];
+        assert!(TfIdfRetriever.rank("the and for", &docs, 5).is_empty());
+        assert!(TfIdfRetriever.rank("", &docs, 5).is_empty());
+        assert_eq!(TfIdfRetriever.rank("Parser recovery", &docs, 1)[0].0, 1);
+    }
+}
@@ -0,0 +1,260 @@
+//! Slack and Discord messages for tasks that need a human.
+//!
+//! Chat webhooks receive a short, human-readable message instead of the raw
+//! [`Notification`] JSON: the task name, a summary of its latest gate
+//! results, and the CLI commands to review, approve, reject, or retry it.
+//! A question from an agent is quoted with the command to answer it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use gator_db::queries::gate_results::GateResultWithName;
+
+use super::{Notification, NotificationEvent};
+
+/// Discord rejects messages longer than this many characters.
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+/// A chat service that accepts incoming-webhook messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatService {
+    Slack,
+    Discord,
+}
+
+impl ChatService {
+    /// Lowercase service name, used in logs and dead letters.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Slack => "slack",
+            Self::Discord => "discord",
+        }
+    }
+
+    /// Wrap `text` in bold using the service's markup.
+    fn bold(self, text: &str) -> String {
+        match self {
+            Self::Slack => format!("*{text}*"),
+            Self::Discord => format!("**{text}**"),
+        }
+    }
+}
+
+/// A chat incoming-webhook (`[notifications.slack]` / `[notifications.discord]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatConfig {
+    /// Incoming-webhook URL issued by the chat service.
+    pub webhook_url: String,
+}
+
+/// Whether chat services are notified about `event`.
+///
+/// Chat is for events a human has to act on; plan-level events go to
+/// generic webhooks only.
+pub fn is_chat_event(event: NotificationEvent) -> bool {
+    matches!(
+        event,
+        NotificationEvent::HumanReviewRequired
+            | NotificationEvent::TaskEscalated
+            | NotificationEvent::QuestionAsked
+    )
+}
+
+/// Format the chat message for a task notification.
+///
+/// `gate_results` are the task's latest gate results; an empty slice
+/// renders as "no gate results recorded".
+pub fn format_chat_message(
+    service: ChatService,
+    notification: &Notification,
+    gate_results: &[GateResultWithName],
+) -> String {
+    let task_name = notification.task_name.as_deref().unwrap_or("(unknown)");
+    let task_id = notification
+        .task_id
+        .map(|id| id.to_string())
+        .unwrap_or_default();
+
+    let headline = match notification.event {
+        NotificationEvent::TaskPassed => "Task passed",
+        NotificationEvent::TaskFailed => "Task failed",
+        NotificationEvent::HumanReviewRequired => "Human review required",
+        NotificationEvent::QuestionAsked => "Question from the agent",
+        NotificationEvent::TaskEscalated => "Task escalated",
+        NotificationEvent::PlanCompleted => "Plan completed",
+        NotificationEvent::PlanFailed => "Plan failed",
+    };
+
+    let mut out = format!(
+        "{}: task `{task_name}` in plan `{}`\n",
+        service.bold(headline),
+        notification.plan_name
+    );
+
+    if notification.event == NotificationEvent::QuestionAsked {
+        for line in notification.message.lines() {
+            out.push_str(&format!("> {line}\n"));
+        }
+        out.push_str(&format!("Answer:  `gator answer {task_id} \"...\"`\n"));
+        return out;
+    }
+
+    if gate_results.is_empty() {
+        out.push_str("Gate: no gate results recorded\n");
+    } else {
+        let passed = gate_results.iter().filter(|r| r.passed).count();
+        out.push_str(&format!(
+            "Gate (attempt {}): {passed}/{} invariants passed\n",
+            gate_results[0].attempt,
+            gate_results.len()
+        ));
+        for r in gate_results {
+            let status = if r.passed { "PASS" } else { "FAIL" };
+            match (r.passed, r.exit_code) {
+                (false, _) if r.timed_out => {
+                    out.push_str(&format!("  {status} {} (timed out)\n", r.invariant_name))
+                }
+                (false, Some(code)) => {
+                    out.push_str(&format!("  {status} {} (exit {code})\n", r.invariant_name))
+                }
+                _ => out.push_str(&format!("  {status} {}\n", r.invariant_name)),
+            }
+        }
+    }
+
+    out.push_str(&format!("Review:  `gator gate {task_id}`\n"));
+    match notification.event {
+        NotificationEvent::TaskEscalated => {
+            out.push_str(&format!("Retry:   `gator retry {task_id}`\n"));
+        }
+        _ => {
+            out.push_str(&format!("Approve: `gator approve {task_id}`\n"));
+            out.push_str(&format!("Reject:  `gator reject {task_id}`\n"));
+        }
+    }
+
+    out
+}
+
+/// Build the webhook body for `service` carrying `text`.
+pub fn chat_payload(service: ChatService, text: &str) -> Value {
+    match service {
+        ChatService::Slack => json!({ "text": text }),
+        ChatService::Discord => {
+            let content: String = text.chars().take(DISCORD_MAX_CONTENT).collect();
+            json!({ "content": content })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn notification(event: NotificationEvent) -> Notification {
+        Notification {
+            event,
+            plan_id: Uuid::nil(),
+            plan_name: "auth-refactor".to_string(),
+            task_id: Some(Uuid::nil()),
+            task_name: Some("add-login".to_string()),
+            message: String::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn gate_result(name: &str, passed: bool, exit_code: i32) -> GateResultWithName {
+        GateResultWithName {
+            id: Uuid::new_v4(),
+            task_id: Uuid::nil(),
+            invariant_id: Uuid::new_v4(),
+            attempt: 1,
+            passed,
+            exit_code: Some(exit_code),
+            stdout: None,
+            stderr: None,
+            duration_ms: None,
+            checked_at: Utc::now(),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            cached_from: None,
+            invariant_name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn review_message_has_summary_and_hints() {
+        let results = vec![
+            gate_result("rust_test", true, 0),
+            gate_result("clippy", false, 101),
+        ];
+        let msg = format_chat_message(
+            ChatService::Slack,
+            &notification(NotificationEvent::HumanReviewRequired),
+            &results,
+        );
+
+        assert!(msg.starts_with("*Human review required*: task `add-login`"));
+        assert!(msg.contains("Gate (attempt 1): 1/2 invariants passed"));
+        assert!(msg.contains("PASS rust_test"));
+        assert!(msg.contains("FAIL clippy (exit 101)"));
+        assert!(msg.contains(&format!("gator approve {}", Uuid::nil())));
+        assert!(msg.contains(&format!("gator reject {}", Uuid::nil())));
+        assert!(!msg.contains("gator retry"));
+    }
+
+    #[test]
+    fn escalation_message_suggests_retry() {
+        let msg = format_chat_message(
+            ChatService::Discord,
+            &notification(NotificationEvent::TaskEscalated),
+            &[],
+        );
+        assert!(msg.starts_with("**Task escalated**"));
+        assert!(msg.contains("no gate results recorded"));
+        assert!(msg.contains("gator retry"));
+        assert!(!msg.contains("gator approve"));
+    }
+
+    #[test]
+    fn question_message_quotes_question_and_suggests_answer() {
+        let mut n = notification(NotificationEvent::QuestionAsked);
+        n.message = "Which port?\n8080 or 9090".to_string();
+        let msg = format_chat_message(ChatService::Slack, &n, &[]);
+        assert!(msg.starts_with("*Question from the agent*: task `add-login`"));
+        assert!(msg.contains("> Which port?\n> 8080 or 9090\n"));
+        assert!(msg.contains(&format!("gator answer {}", Uuid::nil())));
+        assert!(!msg.contains("Gate"));
+    }
+
+    #[test]
+    fn payload_shape_per_service() {
+        assert_eq!(
+            chat_payload(ChatService::Slack, "hi"),
+            json!({"text": "hi"})
+        );
+        assert_eq!(
+            chat_payload(ChatService::Discord, "hi"),
+            json!({"content": "hi"})
+        );
+        let long = "x".repeat(DISCORD_MAX_CONTENT + 10);
+        let payload = chat_payload(ChatService::Discord, &long);
+        assert_eq!(
+            payload["content"].as_str().unwrap().len(),
+            DISCORD_MAX_CONTENT
+        );
+    }
+
+    #[test]
+    fn only_task_events_go_to_chat() {
+        assert!(is_chat_event(NotificationEvent::HumanReviewRequired));
+        assert!(is_chat_event(NotificationEvent::TaskEscalated));
+        assert!(is_chat_event(NotificationEvent::QuestionAsked));
+        assert!(!is_chat_event(NotificationEvent::PlanCompleted));
+        assert!(!is_chat_event(NotificationEvent::PlanFailed));
+    }
+}
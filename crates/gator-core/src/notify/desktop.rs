@@ -0,0 +1,32 @@
+//! Native desktop notifications.
+//!
+//! Uses `notify-rust`: the freedesktop notification service over D-Bus on
+//! Linux and the BSDs, Notification Center on macOS. Showing a notification
+//! blocks, so it runs on the blocking pool.
+
+use anyhow::{Context, Result};
+
+use super::NotificationEvent;
+
+/// Popup title for an event.
+pub fn desktop_title(event: NotificationEvent) -> &'static str {
+    match event {
+        NotificationEvent::TaskPassed => "gator: task passed",
+        NotificationEvent::TaskFailed => "gator: task failed",
+        NotificationEvent::TaskEscalated => "gator: task escalated",
+        NotificationEvent::HumanReviewRequired => "gator: review required",
+        NotificationEvent::QuestionAsked => "gator: question from an agent",
+        NotificationEvent::PlanCompleted => "gator: plan completed",
+        NotificationEvent::PlanFailed => "gator: plan failed",
+    }
+}
+
+/// Show a desktop notification.
+pub async fn show(title: &str, body: &str) -> Result<()> {
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("gator").summary(title).body(body);
+    tokio::task::spawn_blocking(move || notification.show().map(drop))
+        .await
+        .context("desktop notification task panicked")?
+        .context("desktop notification failed")
+}
@@ -0,0 +1,549 @@
+//! Outbound notifications for task and plan state changes.
+//!
+//! The orchestrator reports notable events (a task escalated, a task waiting
+//! on human review or on an answer to its agent's question, a plan
+//! finishing) to a [`Notifier`], which POSTs a JSON [`Notification`] to
+//! every configured webhook that subscribes to the event. Tasks that need a
+//! human (escalated, waiting on a review gate, or asking a question) are
+//! also posted to Slack and Discord, when configured, as a readable message
+//! with a gate summary or the question, and CLI hints (see [`chat`]).
+//!
+//! With `desktop` enabled, every event also pops a native desktop
+//! notification (see [`desktop`]).
+//!
+//! Delivery is retried with exponential backoff; payloads that still cannot
+//! be delivered are written to the `notification_dead_letters` table so no
+//! event is silently lost.
+//!
+//! Delivery runs on a background task, in the order notifications were
+//! queued, so a slow or unreachable endpoint never holds up the
+//! orchestrator: [`Notifier::notify`] only queues. [`Notifier::flush`] waits
+//! for what is queued, as a dispatch does before it exits.
+//!
+//! Notification failures never fail the plan: every error is logged and
+//! dead-lettered, and [`Notifier::notify`] itself is infallible.
+
+pub mod chat;
+pub mod desktop;
+pub mod webhook;
+
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use gator_db::models::Plan;
+use gator_db::queries::gate_results;
+use gator_db::queries::notifications::{self as notify_db, NewDeadLetter};
+
+use crate::schema::Versioned;
+
+pub use chat::{ChatConfig, ChatService};
+pub use webhook::{CurlTransport, WebhookTransport};
+
+/// Default number of delivery attempts per webhook.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Default per-request timeout, in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Delay before the first retry; doubled for each subsequent retry.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Notifications queued for delivery before [`Notifier::notify`] waits for
+/// room.
+const QUEUE_CAPACITY: usize = 256;
+
+// ---------------------------------------------------------------------------
+// Events and payloads
+// ---------------------------------------------------------------------------
+
+/// The kinds of event a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// A task's agent finished and its gate passed.
+    TaskPassed,
+    /// A task's attempt failed its gate or timed out.
+    TaskFailed,
+    /// A task exhausted its retries and was escalated.
+    TaskEscalated,
+    /// A task passed its invariants but is waiting on a human gate.
+    HumanReviewRequired,
+    /// A task's agent asked the operator a question (`gator ask`).
+    QuestionAsked,
+    /// Every task in a plan passed.
+    PlanCompleted,
+    /// A plan stopped with escalated tasks or an exceeded budget.
+    PlanFailed,
+}
+
+impl NotificationEvent {
+    /// The wire name of the event, as used in config and payloads.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TaskPassed => "task_passed",
+            Self::TaskFailed => "task_failed",
+            Self::TaskEscalated => "task_escalated",
+            Self::HumanReviewRequired => "human_review_required",
+            Self::QuestionAsked => "question_asked",
+            Self::PlanCompleted => "plan_completed",
+            Self::PlanFailed => "plan_failed",
+        }
+    }
+}
+
+impl fmt::Display for NotificationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The JSON body POSTed to webhooks, stamped with `schema_version` (see
+/// [`crate::schema`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub event: NotificationEvent,
+    pub plan_id: Uuid,
+    pub plan_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_name: Option<String>,
+    /// Human-readable summary of what happened.
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Build a plan-level notification.
+    pub fn for_plan(event: NotificationEvent, plan: &Plan, message: impl Into<String>) -> Self {
+        Self {
+            event,
+            plan_id: plan.id,
+            plan_name: plan.name.clone(),
+            task_id: None,
+            task_name: None,
+            message: message.into(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Build a notification about a single task within a plan.
+    pub fn for_task(
+        event: NotificationEvent,
+        plan: &Plan,
+        task_id: Uuid,
+        task_name: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            task_id: Some(task_id),
+            task_name: Some(task_name.to_string()),
+            ..Self::for_plan(event, plan, message)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// The `[notifications]` config section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Webhook endpoints to notify.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Delivery attempts per webhook before dead-lettering.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Per-request timeout, in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Slack incoming webhook for tasks that need a human.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slack: Option<ChatConfig>,
+    /// Discord webhook for tasks that need a human.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord: Option<ChatConfig>,
+    /// Pop native desktop notifications (`gator dispatch --notify`).
+    #[serde(default)]
+    pub desktop: bool,
+}
+
+fn default_max_attempts() -> u32 {
+    DEFAULT_MAX_ATTEMPTS
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            slack: None,
+            discord: None,
+            desktop: false,
+        }
+    }
+}
+
+/// A single webhook endpoint (`[[notifications.webhooks]]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// URL to POST to.
+    pub url: String,
+    /// Events this webhook receives. Empty means all events.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<NotificationEvent>,
+}
+
+impl WebhookConfig {
+    /// Whether this webhook subscribes to `event`.
+    pub fn accepts(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Notifier
+// ---------------------------------------------------------------------------
+
+/// Delivers notifications to configured webhooks, in the background.
+pub struct Notifier {
+    delivery: Delivery,
+    /// Feeds the delivery task, started by the first notification.
+    queue: OnceLock<mpsc::Sender<Job>>,
+}
+
+/// Work for the delivery task.
+enum Job {
+    Deliver(Notification),
+    /// Reply once every earlier job is done.
+    Flush(oneshot::Sender<()>),
+}
+
+/// What the delivery task needs: where to send, and how.
+#[derive(Clone)]
+struct Delivery {
+    pool: SqlitePool,
+    config: NotificationConfig,
+    transport: Arc<dyn WebhookTransport>,
+    backoff: Duration,
+    /// Whether a failed desktop notification has been warned about.
+    desktop_warned: bool,
+}
+
+impl Notifier {
+    /// Create a notifier that delivers with `curl`.
+    pub fn new(pool: SqlitePool, config: NotificationConfig) -> Self {
+        let transport = CurlTransport::new(Duration::from_secs(config.timeout_secs));
+        Self {
+            delivery: Delivery {
+                pool,
+                config,
+                transport: Arc::new(transport),
+                backoff: DEFAULT_BACKOFF,
+                desktop_warned: false,
+            },
+            queue: OnceLock::new(),
+        }
+    }
+
+    /// Replace the transport (used by tests).
+    pub fn with_transport(mut self, transport: Arc<dyn WebhookTransport>) -> Self {
+        self.delivery.transport = transport;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.delivery.backoff = backoff;
+        self
+    }
+
+    /// Whether any webhooks, chat services, or desktop popups are configured.
+    pub fn is_enabled(&self) -> bool {
+        let config = &self.delivery.config;
+        !config.webhooks.is_empty() || !self.delivery.chat_targets().is_empty() || config.desktop
+    }
+
+    /// Queue `notification` for every webhook subscribed to its event, and
+    /// for the configured chat services if a human needs to act on it.
+    ///
+    /// Returns once it is queued; it only waits when the queue is full.
+    /// Each target gets up to `max_attempts` tries. A target that never
+    /// succeeds has the payload recorded as a dead letter.
+    pub async fn notify(&self, notification: &Notification) {
+        if !self.is_enabled() {
+            return;
+        }
+        let queue = self.queue.get_or_init(|| {
+            let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+            tokio::spawn(self.delivery.clone().run(rx));
+            tx
+        });
+        if queue
+            .send(Job::Deliver(notification.clone()))
+            .await
+            .is_err()
+        {
+            tracing::error!(event = %notification.event, "notification delivery task stopped; notification dropped");
+        }
+    }
+
+    /// Wait, at most `timeout`, until every queued notification has been
+    /// delivered or dead-lettered. Returns whether the queue drained.
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        let Some(queue) = self.queue.get() else {
+            return true;
+        };
+        let (done, drained) = oneshot::channel();
+        if queue.send(Job::Flush(done)).await.is_err() {
+            return true;
+        }
+        tokio::time::timeout(timeout, drained).await.is_ok()
+    }
+}
+
+impl Delivery {
+    /// Deliver queued jobs in order until the [`Notifier`] is dropped.
+    async fn run(mut self, mut queue: mpsc::Receiver<Job>) {
+        while let Some(job) = queue.recv().await {
+            match job {
+                Job::Deliver(notification) => self.notify(&notification).await,
+                Job::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    /// Configured chat services and their webhook URLs.
+    fn chat_targets(&self) -> Vec<(ChatService, &str)> {
+        let mut targets = Vec::new();
+        if let Some(slack) = &self.config.slack {
+            targets.push((ChatService::Slack, slack.webhook_url.as_str()));
+        }
+        if let Some(discord) = &self.config.discord {
+            targets.push((ChatService::Discord, discord.webhook_url.as_str()));
+        }
+        targets
+    }
+
+    /// Send `notification` to every target that takes it.
+    async fn notify(&mut self, notification: &Notification) {
+        match serde_json::to_value(Versioned::new(notification)) {
+            Ok(payload) => {
+                for webhook in &self.config.webhooks {
+                    if webhook.accepts(notification.event) {
+                        self.deliver(&webhook.url, notification.event, &payload)
+                            .await;
+                    }
+                }
+            }
+            Err(e) => tracing::error!(error = %e, "failed to serialize notification"),
+        }
+
+        if chat::is_chat_event(notification.event) {
+            self.notify_chat(notification).await;
+        }
+
+        if self.config.desktop {
+            let title = desktop::desktop_title(notification.event);
+            if let Err(e) = desktop::show(title, &notification.message).await {
+                // Without a notification service every popup fails; say so
+                // once rather than on every event.
+                if self.desktop_warned {
+                    tracing::debug!(error = %e, "desktop notification not shown");
+                } else {
+                    self.desktop_warned = true;
+                    tracing::warn!(error = %e, "desktop notifications are enabled but could not be shown");
+                }
+            }
+        }
+    }
+
+    /// Post a human-readable message about a task to each chat service.
+    async fn notify_chat(&self, notification: &Notification) {
+        let targets = self.chat_targets();
+        if targets.is_empty() {
+            return;
+        }
+
+        let results = match notification.task_id {
+            Some(_) if notification.event == NotificationEvent::QuestionAsked => Vec::new(),
+            Some(task_id) => gate_results::get_latest_gate_results(&self.pool, task_id)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(task_id = %task_id, error = %e, "failed to load gate results for chat message");
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+
+        for (service, url) in targets {
+            let text = chat::format_chat_message(service, notification, &results);
+            let payload = chat::chat_payload(service, &text);
+            self.deliver(url, notification.event, &payload).await;
+        }
+    }
+
+    /// Deliver one payload to one URL, retrying and dead-lettering on failure.
+    async fn deliver(&self, url: &str, event: NotificationEvent, payload: &serde_json::Value) {
+        let body = payload.to_string();
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut delay = self.backoff;
+        let mut last_error = String::new();
+
+        for attempt in 1..=max_attempts {
+            match self.transport.post(url, &body).await {
+                Ok(()) => {
+                    tracing::debug!(url = %url, event = %event, attempt, "notification delivered");
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        url = %url,
+                        event = %event,
+                        attempt,
+                        max_attempts,
+                        error = %e,
+                        "notification delivery failed"
+                    );
+                    last_error = format!("{e:#}");
+                }
+            }
+            if attempt < max_attempts {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+
+        let dead_letter = NewDeadLetter {
+            event: event.to_string(),
+            url: url.to_string(),
+            payload: payload.clone(),
+            error: last_error,
+            attempts: max_attempts as i32,
+        };
+        if let Err(e) = notify_db::insert_dead_letter(&self.pool, &dead_letter).await {
+            tracing::error!(url = %url, event = %event, error = %e, "failed to record dead letter");
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults_when_section_is_empty() {
+        let config: NotificationConfig = toml::from_str("").unwrap();
+        assert_eq!(config, NotificationConfig::default());
+        assert!(config.webhooks.is_empty());
+    }
+
+    #[test]
+    fn config_parses_webhooks_with_event_filters() {
+        let config: NotificationConfig = toml::from_str(
+            r#"
+            max_attempts = 5
+
+            [[webhooks]]
+            url = "https://example.com/all"
+
+            [[webhooks]]
+            url = "https://example.com/escalations"
+            events = ["task_escalated", "plan_failed"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.max_attempts, 5);
+        assert_eq!(config.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert!(config.webhooks[0].accepts(NotificationEvent::PlanCompleted));
+        assert!(config.webhooks[1].accepts(NotificationEvent::TaskEscalated));
+        assert!(!config.webhooks[1].accepts(NotificationEvent::PlanCompleted));
+    }
+
+    #[test]
+    fn config_parses_chat_sections() {
+        let config: NotificationConfig = toml::from_str(
+            r#"
+            [slack]
+            webhook_url = "https://hooks.slack.com/services/T/B/X"
+
+            [discord]
+            webhook_url = "https://discord.com/api/webhooks/1/abc"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.webhooks.is_empty());
+        assert_eq!(
+            config.slack.unwrap().webhook_url,
+            "https://hooks.slack.com/services/T/B/X"
+        );
+        assert!(config.discord.is_some());
+    }
+
+    #[test]
+    fn config_rejects_unknown_event() {
+        let result: Result<NotificationConfig, _> = toml::from_str(
+            r#"
+            [[webhooks]]
+            url = "https://example.com"
+            events = ["task_exploded"]
+            "#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn event_names_match_serde() {
+        for event in [
+            NotificationEvent::TaskPassed,
+            NotificationEvent::TaskFailed,
+            NotificationEvent::TaskEscalated,
+            NotificationEvent::HumanReviewRequired,
+            NotificationEvent::QuestionAsked,
+            NotificationEvent::PlanCompleted,
+            NotificationEvent::PlanFailed,
+        ] {
+            let json = serde_json::to_value(event).unwrap();
+            assert_eq!(json, serde_json::json!(event.as_str()));
+        }
+    }
+
+    #[test]
+    fn plan_payload_omits_task_fields() {
+        let n = Notification {
+            event: NotificationEvent::PlanCompleted,
+            plan_id: Uuid::nil(),
+            plan_name: "p".to_string(),
+            task_id: None,
+            task_name: None,
+            message: "done".to_string(),
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&n).unwrap();
+        assert_eq!(json["event"], "plan_completed");
+        assert!(json.get("task_id").is_none());
+        assert!(json.get("task_name").is_none());
+    }
+}
@@ -0,0 +1,89 @@
+//! Webhook transport: delivers a JSON body to a URL with an HTTP POST.
+//!
+//! The default transport shells out to `curl`, the same way gator drives
+//! `git`, `gh`, and `docker`, so HTTPS endpoints work without linking a TLS
+//! stack into the binary.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Something that can POST a JSON body to a URL.
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    /// POST `body` (already serialized JSON) to `url`.
+    ///
+    /// Returns an error for transport failures and non-2xx responses.
+    async fn post(&self, url: &str, body: &str) -> Result<()>;
+}
+
+/// Transport that runs `curl` for each request.
+#[derive(Debug, Clone)]
+pub struct CurlTransport {
+    /// Maximum time allowed for a single request.
+    pub timeout: Duration,
+}
+
+impl CurlTransport {
+    /// Create a transport with the given per-request timeout.
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for CurlTransport {
+    async fn post(&self, url: &str, body: &str) -> Result<()> {
+        let mut child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--request",
+                "POST",
+                "--header",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+                "--max-time",
+                &self.timeout.as_secs().max(1).to_string(),
+                "--output",
+                "/dev/null",
+                "--write-out",
+                "%{http_code}",
+                url,
+            ])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .context("failed to run curl (is it installed?)")?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(body.as_bytes())
+                .await
+                .context("failed to write webhook body to curl")?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .context("failed to wait for curl")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("curl failed: {}", stderr.trim());
+        }
+
+        let status = String::from_utf8_lossy(&output.stdout);
+        let status = status.trim();
+        if !status.starts_with('2') {
+            bail!("webhook returned HTTP {status}");
+        }
+
+        Ok(())
+    }
+}
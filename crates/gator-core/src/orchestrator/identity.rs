@@ -0,0 +1,91 @@
+//! Who is running an orchestrator: recorded on each plan it dispatches so
+//! `gator status` can tell which machine owns a run when several share one
+//! database.
+
+use super::reconcile::process_start_time;
+
+/// Host, process, and user of the current process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatcherIdentity {
+    pub host: String,
+    pub pid: u32,
+    pub user: String,
+}
+
+impl DispatcherIdentity {
+    /// Identify this process. Parts that cannot be determined are
+    /// `unknown`.
+    pub fn current() -> Self {
+        Self {
+            host: host(),
+            pid: std::process::id(),
+            user: ["USER", "USERNAME", "LOGNAME"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+                .unwrap_or_else(|| "unknown".to_string()),
+        }
+    }
+}
+
+/// Whether the orchestrator recorded on a plan may still be running. On
+/// this host that is whether process `pid` exists; one on another host --
+/// or one recorded before dispatchers were -- cannot be checked, so it is
+/// taken for alive while the plan is `running`.
+pub fn dispatcher_alive(host: Option<&str>, pid: Option<i64>, plan_running: bool) -> bool {
+    match (host, pid) {
+        (Some(recorded), Some(pid)) if recorded == self::host() => u32::try_from(pid)
+            .ok()
+            .and_then(process_start_time)
+            .is_some(),
+        _ => plan_running,
+    }
+}
+
+/// Name of this host, `unknown` if it cannot be determined.
+pub(crate) fn host() -> String {
+    hostname().unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: `buf` is valid for `buf.len()` bytes; gethostname writes at
+    // most that many and we stop at the first NUL below.
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if ret != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]).into_owned();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_identity_is_this_process() {
+        let identity = DispatcherIdentity::current();
+        assert_eq!(identity.pid, std::process::id());
+        assert!(!identity.host.is_empty());
+        assert!(!identity.user.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dispatcher_liveness_is_checked_on_this_host_only() {
+        let identity = DispatcherIdentity::current();
+        let pid = Some(i64::from(identity.pid));
+        assert!(dispatcher_alive(Some(&identity.host), pid, false));
+        assert!(!dispatcher_alive(Some(&identity.host), Some(-1), true));
+        assert!(dispatcher_alive(Some("elsewhere"), pid, true));
+        assert!(!dispatcher_alive(Some("elsewhere"), pid, false));
+        assert!(dispatcher_alive(None, None, true));
+    }
+}
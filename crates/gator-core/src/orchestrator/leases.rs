@@ -0,0 +1,121 @@
+//! Machine-wide agent cap (`dispatch.max_total_agents`).
+//!
+//! `--max-agents` limits one dispatch. When several plans dispatch on the
+//! same host -- `gator daemon`, or two terminals -- the total is bounded
+//! through the `agent_leases` table instead: each agent lifecycle holds a
+//! lease from spawn until it ends, and no more than `max_total_agents`
+//! leases exist per host. Leases left by a dispatch that died are reclaimed
+//! once its process is gone.
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::queries::agent_leases::{self, LeaseHolder};
+
+use super::identity;
+use super::reconcile::process_start_time;
+
+/// This process's claim on the host's agent slots.
+#[derive(Debug, Clone)]
+pub(crate) struct AgentLeases {
+    host: String,
+    holder: LeaseHolder,
+    max: usize,
+}
+
+impl AgentLeases {
+    /// Leases for this process, out of `max` per host.
+    pub(crate) fn new(max: usize) -> Self {
+        let pid = std::process::id();
+        Self {
+            host: identity::host(),
+            holder: LeaseHolder {
+                pid: i64::from(pid),
+                process_started: process_start_time(pid),
+            },
+            max,
+        }
+    }
+
+    /// Take a slot for `task_id`. Returns `false` when every slot on the
+    /// host is held by a live dispatch.
+    pub(crate) async fn try_acquire(&self, pool: &SqlitePool, task_id: Uuid) -> Result<bool> {
+        if self.acquire(pool, task_id).await? {
+            return Ok(true);
+        }
+        if self.reclaim_dead(pool).await? == 0 {
+            return Ok(false);
+        }
+        self.acquire(pool, task_id).await
+    }
+
+    /// Give back the slot held for `task_id`.
+    pub(crate) async fn release(&self, pool: &SqlitePool, task_id: Uuid) -> Result<()> {
+        agent_leases::release_lease(pool, task_id).await
+    }
+
+    async fn acquire(&self, pool: &SqlitePool, task_id: Uuid) -> Result<bool> {
+        agent_leases::try_acquire_lease(
+            pool,
+            task_id,
+            &self.host,
+            self.holder.pid,
+            self.holder.process_started.as_deref(),
+            self.max,
+        )
+        .await
+    }
+
+    /// Drop the leases of dispatches on this host that are no longer
+    /// running. A holder without a recorded start time is only taken for
+    /// dead once no process with its ID exists.
+    async fn reclaim_dead(&self, pool: &SqlitePool) -> Result<u64> {
+        let mut reclaimed = 0;
+        for holder in agent_leases::list_lease_holders(pool, &self.host).await? {
+            if holder == self.holder || is_alive(&holder) {
+                continue;
+            }
+            let dropped = agent_leases::release_leases_of(pool, &self.host, &holder).await?;
+            tracing::warn!(
+                pid = holder.pid,
+                leases = dropped,
+                "reclaimed agent slots of a dispatch that is no longer running"
+            );
+            reclaimed += dropped;
+        }
+        Ok(reclaimed)
+    }
+}
+
+/// Whether the process holding a lease is still running.
+fn is_alive(holder: &LeaseHolder) -> bool {
+    let Ok(pid) = u32::try_from(holder.pid) else {
+        return false;
+    };
+    match (process_start_time(pid), &holder.process_started) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(now), Some(started)) => &now == started,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn holder_liveness_follows_the_process() {
+        let this = AgentLeases::new(1).holder;
+        assert!(is_alive(&this));
+        assert!(is_alive(&LeaseHolder {
+            process_started: None,
+            ..this.clone()
+        }));
+        assert!(!is_alive(&LeaseHolder {
+            process_started: Some("not-a-start-time".to_string()),
+            ..this
+        }));
+    }
+}
@@ -1,34 +1,120 @@
 //! DAG-aware orchestrator: runs a plan to completion by spawning agents in
 //! topological order, enforcing concurrency limits, and handling retries.
+//! Tasks whose declared `paths` overlap never run at the same time.
+//! When an agent asks the operator a question (`gator ask`), subscribers
+//! are notified with it while the task waits on `gator answer`. A run can
+//! be limited to a slice of the DAG (see [`TaskSelection`]). Besides the
+//! per-dispatch limit, a machine-wide cap can bound the agents of every
+//! dispatch on the host (see [`OrchestratorConfig::max_total_agents`]).
+//! When agents report provider rate limits, the orchestrator runs fewer of
+//! them and pauses before starting more (see [`throttle`]).
+//! The plan's `pre_dispatch`, `post_task`, and `post_plan` hooks run around
+//! the dispatch (see [`crate::plan::hooks`]).
 
-use std::collections::HashSet;
+mod identity;
+mod leases;
+mod preview;
+mod reconcile;
+mod selection;
+mod stop;
+pub mod throttle;
+mod watchdog;
+
+pub use identity::{DispatcherIdentity, dispatcher_alive};
+pub use preview::{DispatchPreview, PreviewTask, preview_dispatch};
+pub use reconcile::{ReconcileReport, process_start_time, reconcile_in_flight};
+pub use selection::{TaskSelection, resolve_selection};
+pub use stop::{CancelReport, StopReport, cancel_task, stop_all};
+pub use watchdog::{DEFAULT_STALL_AFTER, StallSnapshot, WatchdogConfig};
+
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use sqlx::SqlitePool;
 use tokio::sync::{Semaphore, mpsc};
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
-use gator_db::models::{PlanStatus, TaskStatus};
+use gator_db::models::{Plan, PlanFailureReason, PlanStatus, Task, TaskStatus};
 use gator_db::queries::agent_events;
 use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 
-use crate::harness::HarnessRegistry;
+use crate::gate::auto_approve::AutoApproveRule;
+use crate::harness::{HarnessRegistry, PromptBudget};
 use crate::isolation::Isolation;
 use crate::lifecycle::{LifecycleConfig, LifecycleResult, run_agent_lifecycle};
+use crate::notify::{Notification, NotificationConfig, NotificationEvent, Notifier};
+use crate::plan::hooks::{self, Hook};
+use crate::plan::overlap;
 use crate::state::dispatch;
 use crate::token::TokenConfig;
+use crate::worktree::WorktreeManager;
+
+pub(crate) use identity::host;
+use leases::AgentLeases;
+use throttle::Throttle;
+use watchdog::Watchdog;
+
+/// How long a cancelled orchestrator waits for in-flight agents by default.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a plan whose only runnable work waits on other plans re-checks
+/// their tasks.
+const CROSS_PLAN_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the orchestrator looks for questions agents asked while it
+/// waits on in-flight tasks.
+const QUESTION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often a running orchestrator checks whether its plan was stopped
+/// from outside (see [`stop_all`]).
+const STOP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a plan with ready tasks but no free machine-wide agent slot
+/// tries again.
+const SLOT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a finished run waits for its queued notifications.
+const NOTIFY_FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Configuration for the orchestrator.
 #[derive(Debug, Clone)]
 pub struct OrchestratorConfig {
     /// Maximum number of concurrent agents.
     pub max_agents: usize,
+    /// Maximum number of agents across every dispatch on this host sharing
+    /// the database; no machine-wide limit when `None`.
+    pub max_total_agents: Option<usize>,
     /// Wall time limit per task.
     pub task_timeout: Duration,
+    /// Webhooks to notify on escalations, human review, and plan completion.
+    pub notifications: NotificationConfig,
+    /// Stall detection and recovery for the orchestration loop.
+    pub watchdog: WatchdogConfig,
+    /// On cancellation, how long to wait for in-flight agents to finish
+    /// before giving up on them.
+    pub drain_timeout: Duration,
+    /// Size limit for materialized task prompts; unlimited when `None`.
+    pub prompt_budget: Option<PromptBudget>,
+    /// How many invariants each task's gate runs at once.
+    pub gate_parallelism: usize,
+    /// Whether gates reuse results from earlier attempts with an identical
+    /// worktree.
+    pub gate_cache: bool,
+    /// The tasks to run, from [`resolve_selection`]; every task of the plan
+    /// when `None`.
+    pub selection: Option<HashSet<Uuid>>,
+    /// Rules that may approve a passing `human_review` task without a
+    /// human (see [`crate::gate::auto_approve`]).
+    pub auto_approve: Vec<AutoApproveRule>,
+    /// Whether code tasks whose invariants pass get a compliance
+    /// attestation (see [`crate::gate::attestation`]).
+    pub attestation: bool,
 }
 
 /// Result of running the orchestrator to completion.
@@ -42,8 +128,42 @@ pub enum OrchestratorResult {
     HumanRequired { tasks_awaiting_review: Vec<String> },
     /// Token budget exceeded.
     BudgetExceeded { used: i64, budget: i64 },
-    /// Orchestrator was interrupted by a cancellation signal.
+    /// Orchestrator was interrupted by a cancellation signal. The plan is
+    /// left `approved` (marked interrupted) so the next dispatch resumes it.
     Interrupted,
+    /// Every selected task passed, but `remaining` tasks outside the
+    /// selection have not. The plan is left `approved`.
+    SelectionCompleted { remaining: usize },
+}
+
+/// Poll the plan's status and, once it is no longer `running` (another
+/// process stopped it), set `stopped` and cancel the orchestrator. The
+/// watcher ends when the returned guard is dropped.
+fn watch_for_stop(
+    pool: SqlitePool,
+    plan_id: Uuid,
+    cancel: CancellationToken,
+    stopped: Arc<AtomicBool>,
+) -> tokio_util::sync::DropGuard {
+    let done = CancellationToken::new();
+    let guard = done.clone().drop_guard();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = done.cancelled() => return,
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(STOP_POLL_INTERVAL) => {}
+            }
+            if let Ok(Some(plan)) = plan_db::get_plan(&pool, plan_id).await {
+                if plan.status != PlanStatus::Running {
+                    stopped.store(true, Ordering::SeqCst);
+                    cancel.cancel();
+                    return;
+                }
+            }
+        }
+    });
+    guard
 }
 
 /// Message sent from spawned lifecycle tasks back to the orchestrator loop.
@@ -90,11 +210,38 @@ async fn orchestrator_retry(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
     Ok(())
 }
 
+/// Warn once each time the DB pool becomes saturated, and note recovery.
+///
+/// A saturated pool means every connection is checked out; lifecycles
+/// writing events will queue on acquire and may hit the acquire timeout.
+fn check_pool_pressure(pool: &SqlitePool, plan_id: Uuid, in_flight: usize, saturated: &mut bool) {
+    let stats = gator_db::pool::pool_stats(pool);
+    if stats.is_saturated() && !*saturated {
+        tracing::warn!(
+            plan_id = %plan_id,
+            in_flight = in_flight,
+            max_connections = stats.max_connections,
+            "database pool saturated; consider raising database.max_connections"
+        );
+    } else if !stats.is_saturated() && *saturated {
+        tracing::info!(
+            plan_id = %plan_id,
+            in_use = stats.in_use(),
+            max_connections = stats.max_connections,
+            "database pool pressure relieved"
+        );
+    }
+    *saturated = stats.is_saturated();
+}
+
 /// Run the orchestrator for a plan.
 ///
 /// Spawns agents in DAG order (tasks whose dependencies are all passed),
 /// enforces a concurrency limit via a semaphore, retries failures when
 /// eligible, and escalates when retries are exhausted.
+///
+/// Each call is one run with a fresh `run_id`, carried by an `orchestrator`
+/// tracing span and passed to every task lifecycle it starts.
 pub async fn run_orchestrator(
     pool: &SqlitePool,
     plan_id: Uuid,
@@ -104,92 +251,183 @@ pub async fn run_orchestrator(
     config: &OrchestratorConfig,
     cancel: CancellationToken,
 ) -> Result<OrchestratorResult> {
+    let run_id = Uuid::new_v4();
+    let span = tracing::info_span!("orchestrator", plan_id = %plan_id, run_id = %run_id);
+    let mut started = false;
+    let notifier = Notifier::new(pool.clone(), config.notifications.clone());
+    let result = run_plan(
+        pool,
+        plan_id,
+        run_id,
+        &mut started,
+        &notifier,
+        registry,
+        isolation,
+        token_config,
+        config,
+        cancel,
+    )
+    .instrument(span.clone())
+    .await;
+
+    // Notifications are delivered in the background; give the last ones
+    // (the plan's outcome) a chance to go out before the run returns.
+    if !notifier.flush(NOTIFY_FLUSH_TIMEOUT).await {
+        tracing::warn!(
+            plan_id = %plan_id,
+            "notifications still undelivered after {}s; giving up on them",
+            NOTIFY_FLUSH_TIMEOUT.as_secs()
+        );
+    }
+
+    // The post_plan hook sees how the run ended and the plan as it was
+    // left. A run that never started (bad status, failed pre_dispatch hook)
+    // has nothing to report.
+    let plan = if started {
+        plan_db::get_plan(pool, plan_id).await.ok().flatten()
+    } else {
+        None
+    };
+    if let Some(plan) = plan.filter(|p| Hook::PostPlan.command(&p.hooks).is_some()) {
+        let env = [
+            ("GATOR_RUN_ID", run_id.to_string()),
+            ("GATOR_PLAN_RESULT", result_name(&result).to_string()),
+        ];
+        hooks::run_hook_logged(&plan, Hook::PostPlan, &env)
+            .instrument(span)
+            .await;
+    }
+    result
+}
+
+/// How a run ended, as a snake_case word for the `post_plan` hook.
+fn result_name(result: &Result<OrchestratorResult>) -> &'static str {
+    match result {
+        Ok(OrchestratorResult::Completed) => "completed",
+        Ok(OrchestratorResult::Failed { .. }) => "failed",
+        Ok(OrchestratorResult::HumanRequired { .. }) => "human_required",
+        Ok(OrchestratorResult::BudgetExceeded { .. }) => "budget_exceeded",
+        Ok(OrchestratorResult::Interrupted) => "interrupted",
+        Ok(OrchestratorResult::SelectionCompleted { .. }) => "selection_completed",
+        Err(_) => "error",
+    }
+}
+
+/// The orchestrator proper, run inside the `orchestrator` span.
+#[allow(clippy::too_many_arguments)]
+async fn run_plan(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    run_id: Uuid,
+    started: &mut bool,
+    notifier: &Notifier,
+    registry: &Arc<HarnessRegistry>,
+    isolation: &Arc<dyn Isolation>,
+    token_config: &TokenConfig,
+    config: &OrchestratorConfig,
+    cancel: CancellationToken,
+) -> Result<OrchestratorResult> {
+    tracing::info!(plan_id = %plan_id, run_id = %run_id, "starting dispatch run");
+
     // Look up the plan.
     let plan = plan_db::get_plan(pool, plan_id)
         .await?
         .with_context(|| format!("plan {} not found", plan_id))?;
 
     let plan_name = plan.name.clone();
+    let selection = config.selection.as_ref();
     let default_harness = plan.default_harness.clone();
 
-    // 1. Restart recovery: reset orphaned tasks.
-    let orphaned = task_db::reset_orphaned_tasks(pool, plan_id).await?;
-    for orphan in &orphaned {
-        tracing::warn!(
-            task_id = %orphan.id,
-            task_name = %orphan.name,
-            "reset orphaned task to failed"
-        );
+    // 1. Restart recovery: requeue tasks a dead dispatch left in flight
+    //    and kill their surviving agents.
+    let recovered = reconcile_in_flight(pool, plan_id).await?;
+    for remote in &recovered.remote {
+        tracing::warn!(task = %remote, "agent of requeued task ran on another host; stop it there");
     }
-
-    // Handle orphaned tasks: retry if eligible, escalate otherwise.
-    for orphan in &orphaned {
-        if orphan.attempt < orphan.retry_max {
-            orchestrator_retry(pool, orphan.id).await?;
-            tracing::info!(
-                task_id = %orphan.id,
-                task_name = %orphan.name,
-                "retrying orphaned task"
-            );
-        } else {
-            dispatch::escalate_task(pool, orphan.id).await?;
-            tracing::warn!(
-                task_id = %orphan.id,
-                task_name = %orphan.name,
-                "escalating orphaned task (no retries left)"
-            );
-        }
+    for error in &recovered.errors {
+        tracing::warn!(error = %error, "failed to stop surviving agent");
     }
 
-    // 2. Plan status: approved -> running (skip if already running).
-    if plan.status == PlanStatus::Approved {
-        plan_db::update_plan_status(pool, plan_id, PlanStatus::Running).await?;
-    } else if plan.status != PlanStatus::Running {
+    // 2. Plan status: approved -> running (skip if already running), after
+    //    the plan's pre_dispatch hook. A failing hook stops the dispatch
+    //    before the plan starts running.
+    if plan.status != PlanStatus::Approved && plan.status != PlanStatus::Running {
         bail!(
             "plan {} has status {}, expected approved or running",
             plan_id,
             plan.status
         );
     }
+    hooks::run_hook(
+        &plan,
+        Hook::PreDispatch,
+        &[("GATOR_RUN_ID", run_id.to_string())],
+    )
+    .await
+    .context("pre_dispatch hook failed; not dispatching")?;
+    *started = true;
+    if plan.status == PlanStatus::Approved {
+        plan_db::update_plan_status(pool, plan_id, PlanStatus::Running).await?;
+    }
+    let identity = DispatcherIdentity::current();
+    plan_db::record_dispatcher(pool, plan_id, &identity.host, identity.pid, &identity.user).await?;
 
     // 3. Main orchestration loop.
     let semaphore = Arc::new(Semaphore::new(config.max_agents));
+    let leases = config.max_total_agents.map(AgentLeases::new);
+    let mut waiting_for_slot = false;
+    let mut throttle = Throttle::start(pool, plan_id, config.max_agents).await?;
     let (tx, mut rx) = mpsc::channel::<LifecycleDone>(config.max_agents * 2);
-    let mut in_flight: usize = 0;
-    let mut in_flight_tasks: HashSet<Uuid> = HashSet::new();
+    // Tasks with a live lifecycle, by id. The number in flight is always
+    // derived from this map so a self-heal can never make it underflow.
+    let mut in_flight_tasks: HashMap<Uuid, String> = HashMap::new();
+    let mut pool_saturated = false;
+    let mut waiting_on_other_plans = false;
+    // Tasks blocked on a question the operator has been notified about.
+    let mut asked: HashSet<Uuid> = HashSet::new();
+    let mut watchdog = Watchdog::start(pool, plan_id, &config.watchdog).await?;
+    let stopped = Arc::new(AtomicBool::new(false));
+    let cancel = cancel.child_token();
+    let _stop_watch = watch_for_stop(pool.clone(), plan_id, cancel.clone(), stopped.clone());
 
     loop {
-        // 3-pre. Check cancellation.
+        // 3-pre. Check cancellation. A plan stopped from outside has already
+        // been interrupted and had its tasks reset; just get out of the way.
+        if stopped.load(Ordering::SeqCst) {
+            tracing::info!(plan_id = %plan_id, "plan stopped externally");
+            return Ok(OrchestratorResult::Interrupted);
+        }
         if cancel.is_cancelled() {
             tracing::info!(plan_id = %plan_id, "orchestrator cancelled, draining in-flight tasks");
-            let drain_deadline = tokio::time::Instant::now() + Duration::from_secs(10);
-            while in_flight > 0 {
+            // Agents that finish while draining are recorded as usual; any
+            // still running afterwards are reset when the plan resumes.
+            let drain_deadline = tokio::time::Instant::now() + config.drain_timeout;
+            while !in_flight_tasks.is_empty() {
                 match tokio::time::timeout_at(drain_deadline, rx.recv()).await {
                     Ok(Some(done)) => {
-                        in_flight -= 1;
                         in_flight_tasks.remove(&done.task_id);
-                        let _ = handle_lifecycle_result(pool, &done).await;
+                        let _ = handle_lifecycle_result(pool, notifier, &plan, &done).await;
                     }
                     _ => break,
                 }
             }
-            if in_flight > 0 {
+            if !in_flight_tasks.is_empty() {
                 tracing::warn!(
                     plan_id = %plan_id,
-                    remaining = in_flight,
+                    remaining = in_flight_tasks.len(),
                     "drain timeout expired, {} tasks still in flight",
-                    in_flight
+                    in_flight_tasks.len()
                 );
             }
-            plan_db::update_plan_status(pool, plan_id, PlanStatus::Failed).await?;
+            plan_db::interrupt_plan(pool, plan_id).await?;
             return Ok(OrchestratorResult::Interrupted);
         }
 
         // 3a. Drain completed results (non-blocking).
         while let Ok(done) = rx.try_recv() {
-            in_flight -= 1;
             in_flight_tasks.remove(&done.task_id);
-            handle_lifecycle_result(pool, &done).await?;
+            watchdog.progress();
+            handle_lifecycle_result(pool, notifier, &plan, &done).await?;
         }
 
         // 3a-bis. Budget check.
@@ -203,7 +441,15 @@ pub async fn run_orchestrator(
                     budget = budget,
                     "token budget exceeded, stopping plan"
                 );
-                plan_db::update_plan_status(pool, plan_id, PlanStatus::Failed).await?;
+                plan_db::fail_plan(pool, plan_id, PlanFailureReason::BudgetExceeded).await?;
+                let message = format!("token budget exceeded ({total}/{budget} tokens used)");
+                notifier
+                    .notify(&Notification::for_plan(
+                        NotificationEvent::PlanFailed,
+                        &plan,
+                        message,
+                    ))
+                    .await;
                 return Ok(OrchestratorResult::BudgetExceeded {
                     used: total,
                     budget,
@@ -212,13 +458,52 @@ pub async fn run_orchestrator(
         }
 
         // 3b. Check termination conditions.
-        let is_complete = task_db::is_plan_complete(pool, plan_id).await?;
+        let is_complete = match selection {
+            None => task_db::is_plan_complete(pool, plan_id).await?,
+            Some(_) => scoped_tasks(pool, plan_id, selection)
+                .await?
+                .iter()
+                .all(|t| matches!(t.status, TaskStatus::Passed | TaskStatus::Cancelled)),
+        };
+        if is_complete && selection.is_some() && !task_db::is_plan_complete(pool, plan_id).await? {
+            // The rest of the plan is left for a later dispatch.
+            let remaining = task_db::list_tasks_for_plan(pool, plan_id)
+                .await?
+                .iter()
+                .filter(|t| !matches!(t.status, TaskStatus::Passed | TaskStatus::Cancelled))
+                .count();
+            plan_db::update_plan_status(pool, plan_id, PlanStatus::Approved).await?;
+            return Ok(OrchestratorResult::SelectionCompleted { remaining });
+        }
         if is_complete {
             plan_db::update_plan_status(pool, plan_id, PlanStatus::Completed).await?;
+            let cancelled = task_db::get_plan_progress(pool, plan_id).await?.cancelled;
+            let message = if cancelled > 0 {
+                format!("all tasks passed except {cancelled} cancelled")
+            } else {
+                "all tasks passed".to_string()
+            };
+            notifier
+                .notify(&Notification::for_plan(
+                    NotificationEvent::PlanCompleted,
+                    &plan,
+                    message,
+                ))
+                .await;
             return Ok(OrchestratorResult::Completed);
         }
 
-        let progress = task_db::get_plan_progress(pool, plan_id).await?;
+        let progress = scoped_progress(pool, plan_id, selection).await?;
+        let in_flight = in_flight_tasks.len();
+
+        // 3b-bis. Pass on questions agents asked. Waiting on the operator's
+        // answer is not a stall.
+        if progress.blocked_on_human > 0 || !asked.is_empty() {
+            notify_questions(pool, notifier, &plan, &mut asked).await?;
+        }
+        if progress.blocked_on_human > 0 {
+            watchdog.progress();
+        }
 
         // All non-passed tasks are either escalated or checking (human review).
         if progress.pending == 0
@@ -227,7 +512,7 @@ pub async fn run_orchestrator(
             && progress.failed == 0
             && in_flight == 0
         {
-            let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+            let tasks = scoped_tasks(pool, plan_id, selection).await?;
             let escalated: Vec<String> = tasks
                 .iter()
                 .filter(|t| t.status == TaskStatus::Escalated)
@@ -247,7 +532,8 @@ pub async fn run_orchestrator(
                 });
             }
             if !escalated.is_empty() {
-                plan_db::update_plan_status(pool, plan_id, PlanStatus::Failed).await?;
+                plan_db::fail_plan(pool, plan_id, PlanFailureReason::TasksEscalated).await?;
+                notify_plan_failed(notifier, &plan, &escalated).await;
                 return Ok(OrchestratorResult::Failed {
                     failed_tasks: escalated,
                 });
@@ -256,31 +542,74 @@ pub async fn run_orchestrator(
 
         // 3c. Handle any failed tasks (retry or escalate).
         if progress.failed > 0 && in_flight == 0 {
-            let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+            let tasks = scoped_tasks(pool, plan_id, selection).await?;
             for task in &tasks {
                 if task.status == TaskStatus::Failed {
                     if task.attempt < task.retry_max {
                         orchestrator_retry(pool, task.id).await?;
                     } else {
-                        dispatch::escalate_task(pool, task.id).await?;
+                        escalate_and_notify(pool, notifier, &plan, task.id, &task.name).await?;
                     }
                 }
             }
+            watchdog.progress();
             // Continue to spawn ready tasks in the next iteration.
             continue;
         }
 
+        // 3c-bis. Back off while agents are being rate-limited.
+        throttle.observe(pool).await?;
+
         // 3d. Spawn ready tasks (skip any already in flight).
         let ready = task_db::get_ready_tasks(pool, plan_id).await?;
         let ready: Vec<_> = ready
             .into_iter()
-            .filter(|t| !in_flight_tasks.contains(&t.id))
+            .filter(|t| {
+                !in_flight_tasks.contains_key(&t.id) && selection.is_none_or(|s| s.contains(&t.id))
+            })
             .collect();
-        let spawned_any = !ready.is_empty();
+        let has_ready = !ready.is_empty();
+        let mut spawned_any = false;
+        let mut out_of_slots = false;
+        let mut throttled = false;
+
+        // A task whose declared paths overlap those of a running task waits
+        // for it, so the two never produce conflicting branches.
+        let mut claimed: Vec<Vec<String>> = if ready.iter().any(|t| !t.paths.is_empty()) {
+            task_db::list_tasks_for_plan(pool, plan_id)
+                .await?
+                .into_iter()
+                .filter(|t| in_flight_tasks.contains_key(&t.id))
+                .map(|t| t.paths.0)
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         for task in ready {
-            // Acquire semaphore permit.
-            let permit = semaphore.clone().acquire_owned().await?;
+            if claimed
+                .iter()
+                .any(|paths| !overlap::paths_overlap(&task.paths, paths).is_empty())
+            {
+                tracing::debug!(
+                    task_name = %task.name,
+                    "paths overlap a running task, deferring"
+                );
+                continue;
+            }
+
+            if !throttle.allows(in_flight_tasks.len(), chrono::Utc::now()) {
+                throttled = true;
+                break;
+            }
+
+            // Take a free agent slot without waiting. Tasks that don't get
+            // one stay ready and are spawned on a later pass, once a running
+            // agent has finished -- waiting here would stop the loop from
+            // handling results and cancellation.
+            let Ok(permit) = semaphore.clone().try_acquire_owned() else {
+                break;
+            };
 
             let pool_clone = pool.clone();
             let plan_name_clone = plan_name.clone();
@@ -288,11 +617,24 @@ pub async fn run_orchestrator(
             let isolation_clone = Arc::clone(isolation);
             let token_cfg = token_config.clone();
             let lifecycle_config = LifecycleConfig {
-                timeout: config.task_timeout,
+                timeout: task
+                    .timeout_secs
+                    .map_or(config.task_timeout, |secs| Duration::from_secs(secs as u64)),
+                stall_timeout: config.watchdog.agent_stall_after,
+                ephemeral_db: plan.ephemeral_db.clone(),
+                setup_commands: plan.setup_commands.0.clone(),
+                warmup_command: plan.warmup_command.clone(),
+                prompt_budget: config.prompt_budget.clone(),
+                gate_parallelism: config.gate_parallelism,
+                gate_cache: config.gate_cache,
+                auto_approve: config.auto_approve.clone(),
+                attestation: config.attestation,
+                run_id: Some(run_id),
             };
             let tx_clone = tx.clone();
             let task_name = task.name.clone();
             let task_id = task.id;
+            let post_task = Hook::PostTask.command(&plan.hooks).is_some();
 
             // Choose harness: per-task > plan default > first registered.
             let preferred = task
@@ -318,77 +660,211 @@ pub async fn run_orchestrator(
                 continue;
             };
 
-            in_flight += 1;
-            in_flight_tasks.insert(task_id);
-
-            tokio::spawn(async move {
-                let Some(harness) = registry_clone.get(&harness_name) else {
-                    tracing::error!(
-                        task_id = %task_id,
-                        harness = %harness_name,
-                        "harness disappeared from registry after validation"
+            // Then a machine-wide slot. Without one the task stays ready,
+            // as when no local slot is free.
+            let slot_free = match &leases {
+                Some(leases) => leases.try_acquire(pool, task_id).await?,
+                None => true,
+            };
+            if !slot_free {
+                if !waiting_for_slot {
+                    tracing::info!(
+                        plan_id = %plan_id,
+                        max_total_agents = config.max_total_agents,
+                        "all machine-wide agent slots are taken, waiting"
                     );
+                }
+                out_of_slots = true;
+                break;
+            }
+
+            in_flight_tasks.insert(task_id, task_name.clone());
+            claimed.push(task.paths.0.clone());
+            spawned_any = true;
+            watchdog.progress();
+            let task_leases = leases.clone();
+
+            tokio::spawn(
+                async move {
+                    let Some(harness) = registry_clone.get(&harness_name) else {
+                        tracing::error!(
+                            task_id = %task_id,
+                            harness = %harness_name,
+                            "harness disappeared from registry after validation"
+                        );
+                        drop(permit);
+                        release_lease(task_leases.as_ref(), &pool_clone, task_id).await;
+                        let _ = tx_clone
+                            .send(LifecycleDone {
+                                task_id,
+                                task_name,
+                                result: Err(anyhow::anyhow!(
+                                    "harness '{}' not found in registry",
+                                    harness_name
+                                )),
+                            })
+                            .await;
+                        return;
+                    };
+
+                    // An `llm_review:<harness>` reviewer; otherwise the task's
+                    // own harness reviews.
+                    let reviewer = task.review_harness.as_deref().and_then(|name| {
+                        let reviewer = registry_clone.get(name);
+                        if reviewer.is_none() {
+                            tracing::warn!(
+                                task_id = %task_id,
+                                reviewer = %name,
+                                "review harness not found, reviewing with the task's harness"
+                            );
+                        }
+                        reviewer
+                    });
+
+                    let result = run_agent_lifecycle(
+                        &pool_clone,
+                        &task,
+                        &plan_name_clone,
+                        harness,
+                        reviewer,
+                        isolation_clone.as_ref(),
+                        &token_cfg,
+                        &lifecycle_config,
+                    )
+                    .await;
+
+                    // Release the agent slots.
                     drop(permit);
+                    release_lease(task_leases.as_ref(), &pool_clone, task_id).await;
+
+                    // The post_task hook runs before the orchestrator
+                    // handles the result, without holding a slot.
+                    if post_task {
+                        run_post_task_hook(&pool_clone, task_id, run_id, &result).await;
+                    }
+
+                    // Send result back.
                     let _ = tx_clone
                         .send(LifecycleDone {
                             task_id,
                             task_name,
-                            result: Err(anyhow::anyhow!(
-                                "harness '{}' not found in registry",
-                                harness_name
-                            )),
+                            result,
                         })
                         .await;
-                    return;
-                };
-
-                let result = run_agent_lifecycle(
-                    &pool_clone,
-                    &task,
-                    &plan_name_clone,
-                    harness,
-                    isolation_clone.as_ref(),
-                    &token_cfg,
-                    &lifecycle_config,
-                )
-                .await;
+                }
+                .in_current_span(),
+            );
+        }
 
-                // Release semaphore permit.
-                drop(permit);
+        waiting_for_slot = out_of_slots;
 
-                // Send result back.
-                let _ = tx_clone
-                    .send(LifecycleDone {
-                        task_id,
-                        task_name,
-                        result,
-                    })
-                    .await;
-            });
+        // 3d-bis. Surface DB pool pressure before blocking.
+        check_pool_pressure(pool, plan_id, in_flight_tasks.len(), &mut pool_saturated);
+
+        // 3d-ter. Watchdog: no progress for too long while tasks remain.
+        if let Some(stalled_for) = watchdog.check(pool, plan_id).await? {
+            let mut in_flight_names: Vec<String> = in_flight_tasks.values().cloned().collect();
+            in_flight_names.sort();
+            let snapshot = StallSnapshot {
+                stalled_for,
+                in_flight: in_flight_names,
+                progress: task_db::get_plan_progress(pool, plan_id).await?,
+                queued_results: rx.len(),
+                free_permits: semaphore.available_permits(),
+                max_agents: config.max_agents,
+            };
+            tracing::error!(plan_id = %plan_id, %snapshot, "orchestrator stalled");
+            if config.watchdog.self_heal {
+                self_heal(
+                    pool,
+                    plan_id,
+                    &mut in_flight_tasks,
+                    &semaphore,
+                    config.max_agents,
+                )
+                .await?;
+            }
+            // Re-arm so a persistent stall is reported once per period.
+            watchdog.progress();
+            continue;
         }
 
-        // 3e. If tasks are in flight but nothing is ready, wait for a result
-        // or cancellation.
-        if in_flight > 0 {
+        // 3e. If tasks are in flight but nothing is ready, wait for a result,
+        // cancellation, or the watchdog.
+        if !in_flight_tasks.is_empty() {
             tokio::select! {
                 done = rx.recv() => {
                     if let Some(done) = done {
-                        in_flight -= 1;
                         in_flight_tasks.remove(&done.task_id);
-                        handle_lifecycle_result(pool, &done).await?;
+                        watchdog.progress();
+                        handle_lifecycle_result(pool, notifier, &plan, &done).await?;
                     }
                 }
+                _ = watchdog.expired() => {
+                    // Checked at the next pass through 3d-ter.
+                    continue;
+                }
+                _ = tokio::time::sleep(QUESTION_POLL_INTERVAL) => {
+                    // Look for new questions at the next pass through 3b-bis.
+                    continue;
+                }
                 _ = cancel.cancelled() => {
                     // Will be handled at top of next loop iteration.
                     continue;
                 }
             }
         } else if !spawned_any {
-            // Nothing in flight, nothing spawned. If there are still pending
-            // tasks they must be blocked by escalated dependencies and can
-            // never become ready -- the plan is deadlocked.
-            if progress.pending > 0 {
-                let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+            // Nothing in flight, nothing spawned. Ready tasks waiting on a
+            // machine-wide slot get one once another dispatch's agent ends,
+            // and throttled ones once the pause is over; that is not a
+            // stall of this plan.
+            if out_of_slots || throttled {
+                watchdog.progress();
+                tokio::select! {
+                    _ = tokio::time::sleep(SLOT_POLL_INTERVAL) => {}
+                    _ = cancel.cancelled() => {}
+                }
+                continue;
+            }
+
+            // Pending tasks waiting on a
+            // task of another plan that can still pass become ready once it
+            // does; poll until then.
+            if progress.pending > 0 && !has_ready {
+                let mut waiting = task_db::list_open_cross_plan_dependencies(pool, plan_id).await?;
+                if selection.is_some() {
+                    let selected: HashSet<String> = scoped_tasks(pool, plan_id, selection)
+                        .await?
+                        .into_iter()
+                        .map(|t| t.name)
+                        .collect();
+                    waiting.retain(|(task, _)| selected.contains(task));
+                }
+                if !waiting.is_empty() {
+                    if !waiting_on_other_plans {
+                        tracing::info!(
+                            plan_id = %plan_id,
+                            ?waiting,
+                            "waiting for tasks of other plans to pass"
+                        );
+                        waiting_on_other_plans = true;
+                    }
+                    // Waiting on another plan is not a stall of this one.
+                    watchdog.progress();
+                    tokio::select! {
+                        _ = tokio::time::sleep(CROSS_PLAN_POLL_INTERVAL) => {}
+                        _ = cancel.cancelled() => {}
+                    }
+                    continue;
+                }
+            }
+            waiting_on_other_plans = false;
+
+            // Otherwise, if there are still pending tasks but none is ready,
+            // they must be blocked by escalated dependencies and can never
+            // become ready -- the plan is deadlocked.
+            if progress.pending > 0 && !has_ready {
+                let tasks = scoped_tasks(pool, plan_id, selection).await?;
                 let mut blocked: Vec<String> = Vec::new();
                 for task in &tasks {
                     if task.status == TaskStatus::Pending {
@@ -408,7 +884,8 @@ pub async fn run_orchestrator(
                 );
                 let mut all_failed = escalated;
                 all_failed.extend(blocked);
-                plan_db::update_plan_status(pool, plan_id, PlanStatus::Failed).await?;
+                plan_db::fail_plan(pool, plan_id, PlanFailureReason::TasksEscalated).await?;
+                notify_plan_failed(notifier, &plan, &all_failed).await;
                 return Ok(OrchestratorResult::Failed {
                     failed_tasks: all_failed,
                 });
@@ -425,8 +902,276 @@ pub async fn run_orchestrator(
     }
 }
 
+/// Run the plan's `post_task` hook for a finished lifecycle of `task_id`,
+/// with the task and the lifecycle's result in its environment. Failures
+/// are logged.
+async fn run_post_task_hook(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    run_id: Uuid,
+    result: &Result<LifecycleResult>,
+) {
+    let task = match task_db::get_task(pool, task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(task_id = %task_id, error = %e, "failed to load task for post_task hook");
+            return;
+        }
+    };
+    let plan = match plan_db::get_plan(pool, task.plan_id).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(task_id = %task_id, error = %e, "failed to load plan for post_task hook");
+            return;
+        }
+    };
+    let env = [
+        ("GATOR_RUN_ID", run_id.to_string()),
+        ("GATOR_TASK_ID", task.id.to_string()),
+        ("GATOR_TASK_NAME", task.name.clone()),
+        ("GATOR_TASK_STATUS", task.status.to_string()),
+        ("GATOR_TASK_ATTEMPT", task.attempt.to_string()),
+        (
+            "GATOR_TASK_RESULT",
+            result
+                .as_ref()
+                .map_or("error", LifecycleResult::as_str)
+                .to_string(),
+        ),
+        (
+            "GATOR_TASK_BRANCH",
+            WorktreeManager::branch_name(&plan.name, &task.name),
+        ),
+        (
+            "GATOR_WORKTREE_PATH",
+            task.worktree_path.clone().unwrap_or_default(),
+        ),
+    ];
+    hooks::run_hook_logged(&plan, Hook::PostTask, &env).await;
+}
+
+/// Give back the machine-wide slot of a finished lifecycle. A lease that
+/// cannot be released is reclaimed once this dispatch exits.
+async fn release_lease(leases: Option<&AgentLeases>, pool: &SqlitePool, task_id: Uuid) {
+    let Some(leases) = leases else {
+        return;
+    };
+    if let Err(e) = leases.release(pool, task_id).await {
+        tracing::warn!(task_id = %task_id, error = %e, "failed to release agent slot");
+    }
+}
+
+/// The plan's tasks the run is limited to: all of them without a selection.
+async fn scoped_tasks(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    selection: Option<&HashSet<Uuid>>,
+) -> Result<Vec<Task>> {
+    let mut tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+    if let Some(selected) = selection {
+        tasks.retain(|t| selected.contains(&t.id));
+    }
+    Ok(tasks)
+}
+
+/// Status counts of the tasks the run is limited to.
+async fn scoped_progress(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    selection: Option<&HashSet<Uuid>>,
+) -> Result<task_db::PlanProgress> {
+    if selection.is_none() {
+        return task_db::get_plan_progress(pool, plan_id).await;
+    }
+    let mut progress = task_db::PlanProgress::default();
+    for task in scoped_tasks(pool, plan_id, selection).await? {
+        let count = match task.status {
+            TaskStatus::Pending => &mut progress.pending,
+            TaskStatus::Assigned => &mut progress.assigned,
+            TaskStatus::Running => &mut progress.running,
+            TaskStatus::BlockedOnHuman => &mut progress.blocked_on_human,
+            TaskStatus::Checking => &mut progress.checking,
+            TaskStatus::Passed => &mut progress.passed,
+            TaskStatus::Failed => &mut progress.failed,
+            TaskStatus::Escalated => &mut progress.escalated,
+            TaskStatus::Cancelled => &mut progress.cancelled,
+        };
+        *count += 1;
+        progress.total += 1;
+    }
+    Ok(progress)
+}
+
+/// Re-scan a stalled plan and repair the loop's bookkeeping.
+///
+/// - In-flight entries whose task already reached a terminal status are
+///   dropped (the lifecycle finished but its result never arrived).
+/// - Agent slots that are neither free nor held by an in-flight task are
+///   returned to the semaphore.
+/// - Tasks marked `assigned` or `running` with no lifecycle behind them are
+///   failed, so the normal retry/escalation path picks them up.
+async fn self_heal(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    in_flight_tasks: &mut HashMap<Uuid, String>,
+    semaphore: &Semaphore,
+    max_agents: usize,
+) -> Result<()> {
+    let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+
+    for task in &tasks {
+        let terminal = matches!(
+            task.status,
+            TaskStatus::Passed | TaskStatus::Failed | TaskStatus::Escalated | TaskStatus::Cancelled
+        );
+        if terminal && in_flight_tasks.remove(&task.id).is_some() {
+            tracing::warn!(
+                task_id = %task.id,
+                task_name = %task.name,
+                status = %task.status,
+                "self-heal: dropped in-flight task that already finished"
+            );
+        }
+    }
+
+    let held = in_flight_tasks.len();
+    let leaked = max_agents
+        .saturating_sub(semaphore.available_permits())
+        .saturating_sub(held);
+    if leaked > 0 {
+        semaphore.add_permits(leaked);
+        tracing::warn!(
+            plan_id = %plan_id,
+            restored = leaked,
+            "self-heal: restored leaked agent slots"
+        );
+    }
+
+    for task in &tasks {
+        if in_flight_tasks.contains_key(&task.id)
+            || !matches!(task.status, TaskStatus::Assigned | TaskStatus::Running)
+        {
+            continue;
+        }
+        let rows = task_db::transition_task_status(
+            pool,
+            task.id,
+            task.status,
+            TaskStatus::Failed,
+            None,
+            Some(chrono::Utc::now()),
+        )
+        .await?;
+        if rows > 0 {
+            tracing::warn!(
+                task_id = %task.id,
+                task_name = %task.name,
+                was = %task.status,
+                "self-heal: failed active task with no agent"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Escalate a task that has no retries left and notify subscribers.
+async fn escalate_and_notify(
+    pool: &SqlitePool,
+    notifier: &Notifier,
+    plan: &Plan,
+    task_id: Uuid,
+    task_name: &str,
+) -> Result<()> {
+    dispatch::escalate_task(pool, task_id).await?;
+    notifier
+        .notify(&Notification::for_task(
+            NotificationEvent::TaskEscalated,
+            plan,
+            task_id,
+            task_name,
+            format!("task {task_name} failed with no retries left and was escalated"),
+        ))
+        .await;
+    Ok(())
+}
+
+/// Notify subscribers that a plan stopped with failed tasks.
+async fn notify_plan_failed(notifier: &Notifier, plan: &Plan, failed_tasks: &[String]) {
+    let message = format!("plan failed: {}", failed_tasks.join(", "));
+    notifier
+        .notify(&Notification::for_plan(
+            NotificationEvent::PlanFailed,
+            plan,
+            message,
+        ))
+        .await;
+}
+
+/// Notify subscribers, once per question, about tasks that are
+/// `blocked_on_human`, with the question the agent asked. `asked` holds the
+/// tasks already announced; tasks that were answered leave it.
+async fn notify_questions(
+    pool: &SqlitePool,
+    notifier: &Notifier,
+    plan: &Plan,
+    asked: &mut HashSet<Uuid>,
+) -> Result<()> {
+    let blocked: Vec<_> = task_db::list_tasks_for_plan(pool, plan.id)
+        .await?
+        .into_iter()
+        .filter(|t| t.status == TaskStatus::BlockedOnHuman)
+        .collect();
+    asked.retain(|id| blocked.iter().any(|t| t.id == *id));
+    for task in &blocked {
+        if !asked.insert(task.id) {
+            continue;
+        }
+        let question = dispatch::latest_question(pool, task.id, task.attempt)
+            .await?
+            .unwrap_or_default();
+        tracing::info!(task_id = %task.id, task_name = %task.name, %question, "agent asked a question");
+        notifier
+            .notify(&Notification::for_task(
+                NotificationEvent::QuestionAsked,
+                plan,
+                task.id,
+                &task.name,
+                question,
+            ))
+            .await;
+    }
+    Ok(())
+}
+
+/// Notify subscribers about a finished lifecycle.
+async fn notify_task(
+    notifier: &Notifier,
+    plan: &Plan,
+    done: &LifecycleDone,
+    event: NotificationEvent,
+    message: String,
+) {
+    notifier
+        .notify(&Notification::for_task(
+            event,
+            plan,
+            done.task_id,
+            &done.task_name,
+            message,
+        ))
+        .await;
+}
+
 /// Handle the result of a completed lifecycle.
-async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Result<()> {
+async fn handle_lifecycle_result(
+    pool: &SqlitePool,
+    notifier: &Notifier,
+    plan: &Plan,
+    done: &LifecycleDone,
+) -> Result<()> {
     match &done.result {
         Ok(LifecycleResult::Passed) => {
             tracing::info!(
@@ -434,6 +1179,14 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 task_name = %done.task_name,
                 "task passed"
             );
+            notify_task(
+                notifier,
+                plan,
+                done,
+                NotificationEvent::TaskPassed,
+                format!("task {} passed", done.task_name),
+            )
+            .await;
         }
         Ok(LifecycleResult::FailedCanRetry) => {
             tracing::info!(
@@ -441,6 +1194,14 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 task_name = %done.task_name,
                 "task failed, will retry on next loop iteration"
             );
+            notify_task(
+                notifier,
+                plan,
+                done,
+                NotificationEvent::TaskFailed,
+                format!("task {} failed its gate; retrying", done.task_name),
+            )
+            .await;
             // Task is in `failed` state. The main loop will handle retry via
             // orchestrator_retry (which resets to pending for DAG scheduling).
         }
@@ -450,7 +1211,7 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 task_name = %done.task_name,
                 "task failed, no retries left, escalating"
             );
-            dispatch::escalate_task(pool, done.task_id).await?;
+            escalate_and_notify(pool, notifier, plan, done.task_id, &done.task_name).await?;
         }
         Ok(LifecycleResult::TimedOut) => {
             tracing::warn!(
@@ -458,9 +1219,41 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 task_name = %done.task_name,
                 "task timed out"
             );
+            notify_task(
+                notifier,
+                plan,
+                done,
+                NotificationEvent::TaskFailed,
+                format!("task {} timed out", done.task_name),
+            )
+            .await;
             // Task is already in `failed` state from lifecycle timeout handler.
             // The main loop will handle retry or escalation.
         }
+        Ok(LifecycleResult::Stalled) => {
+            tracing::warn!(
+                task_id = %done.task_id,
+                task_name = %done.task_name,
+                "task stalled"
+            );
+            notify_task(
+                notifier,
+                plan,
+                done,
+                NotificationEvent::TaskFailed,
+                format!("task {} stalled with no agent activity", done.task_name),
+            )
+            .await;
+            // Failed like a timeout; the main loop retries or escalates.
+        }
+        Ok(LifecycleResult::Cancelled) => {
+            tracing::info!(
+                task_id = %done.task_id,
+                task_name = %done.task_name,
+                "task cancelled, its agent was killed"
+            );
+            // The task is already `cancelled`; its slot is free again.
+        }
         Ok(LifecycleResult::HumanRequired) => {
             tracing::info!(
                 task_id = %done.task_id,
@@ -468,8 +1261,30 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 "task requires human review"
             );
             // Task stays in checking state.
+            notify_task(
+                notifier,
+                plan,
+                done,
+                NotificationEvent::HumanReviewRequired,
+                format!("task {} is waiting for human review", done.task_name),
+            )
+            .await;
         }
         Err(e) => {
+            let task = task_db::get_task(pool, done.task_id).await?;
+            // A cancelled task's lifecycle fails its next transition; that
+            // is the cancellation taking effect, not an error.
+            if task
+                .as_ref()
+                .is_some_and(|t| t.status == TaskStatus::Cancelled)
+            {
+                tracing::info!(
+                    task_id = %done.task_id,
+                    task_name = %done.task_name,
+                    "lifecycle of cancelled task ended"
+                );
+                return Ok(());
+            }
             tracing::error!(
                 task_id = %done.task_id,
                 task_name = %done.task_name,
@@ -477,7 +1292,6 @@ async fn handle_lifecycle_result(pool: &SqlitePool, done: &LifecycleDone) -> Res
                 "lifecycle failed with error"
             );
             // Try to transition the task to failed for cleanup.
-            let task = task_db::get_task(pool, done.task_id).await?;
             if let Some(task) = task {
                 match task.status {
                     TaskStatus::Running => {
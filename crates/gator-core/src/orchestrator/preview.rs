@@ -0,0 +1,387 @@
+//! `gator dispatch --dry-run`: what a dispatch would do, without doing it.
+//!
+//! The preview replays the orchestrator's scheduling on the plan as it is
+//! stored: every task that can still run is placed in a wave, each wave
+//! holding the tasks that would start together once the previous one has
+//! finished. Like the orchestrator, a wave holds at most `max_agents`
+//! tasks and never two whose declared `paths` overlap. It assumes every
+//! task passes on its first attempt and that tasks take equally long, so
+//! it shows the order and width of the run, not its duration.
+//!
+//! Token estimates come from the size of each materialized task prompt
+//! and the average usage of earlier attempts in the same project.
+//! Nothing is written: no worktrees, no agents, no status changes.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{Plan, PlanStatus, TaskStatus};
+use gator_db::queries::agent_events;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use crate::harness::budget::estimate_tokens;
+use crate::plan::{materialize_task, overlap};
+
+/// A task as the preview schedules it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewTask {
+    pub name: String,
+    /// Declared `paths`; empty when the task declares none.
+    pub paths: Vec<String>,
+    /// Names of the plan's own tasks it depends on.
+    pub depends_on: Vec<String>,
+    /// Estimated size of its materialized prompt, in tokens.
+    pub prompt_tokens: usize,
+}
+
+/// What [`preview_dispatch`] found.
+#[derive(Debug, Clone)]
+pub struct DispatchPreview {
+    pub plan: Plan,
+    pub max_agents: usize,
+    /// Number of tasks that already passed.
+    pub passed: usize,
+    /// Tasks in the order they would start, one list per wave.
+    pub waves: Vec<Vec<PreviewTask>>,
+    /// Earlier attempts in the project that reported token usage.
+    pub past_attempts: i64,
+    /// Their average usage, input and output combined.
+    pub average_attempt_tokens: i64,
+    /// Tokens the plan has used so far.
+    pub used_tokens: i64,
+    /// Problems that would stop or slow the dispatch.
+    pub issues: Vec<String>,
+}
+
+impl DispatchPreview {
+    /// Number of tasks the dispatch would run.
+    pub fn task_count(&self) -> usize {
+        self.waves.iter().map(Vec::len).sum()
+    }
+
+    /// Estimated size of every prompt the dispatch would send first.
+    pub fn prompt_tokens(&self) -> usize {
+        self.waves.iter().flatten().map(|t| t.prompt_tokens).sum()
+    }
+
+    /// Estimated usage of the dispatch, from the project's past attempts;
+    /// `None` without any.
+    pub fn estimated_tokens(&self) -> Option<i64> {
+        (self.past_attempts > 0).then(|| self.average_attempt_tokens * self.task_count() as i64)
+    }
+}
+
+/// Preview a dispatch of `plan_id` with `max_agents` agents. `harnesses`
+/// are the harnesses the dispatch registers.
+pub async fn preview_dispatch(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    max_agents: usize,
+    harnesses: &[&str],
+) -> Result<DispatchPreview> {
+    let plan = plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+    let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+    let mut issues = Vec::new();
+
+    match plan.status {
+        PlanStatus::Approved | PlanStatus::Running => {}
+        PlanStatus::Draft => {
+            issues.push("plan is a draft; approve it before dispatching".to_string());
+        }
+        status => issues.push(format!(
+            "plan is {status}; dispatch only runs approved or running plans"
+        )),
+    }
+
+    // Tasks that cannot run, and why; everything behind them waits too.
+    let mut stuck: HashMap<String, String> = HashMap::new();
+    let mut to_run = Vec::new();
+    let mut passed = 0;
+    for task in &tasks {
+        match task.status {
+            TaskStatus::Passed => passed += 1,
+            TaskStatus::Escalated => {
+                stuck.insert(
+                    task.name.clone(),
+                    "is escalated (retry it with gator retry)".to_string(),
+                );
+            }
+            TaskStatus::Cancelled => {
+                stuck.insert(task.name.clone(), "was cancelled".to_string());
+            }
+            status => {
+                if matches!(
+                    status,
+                    TaskStatus::Assigned
+                        | TaskStatus::Running
+                        | TaskStatus::BlockedOnHuman
+                        | TaskStatus::Checking
+                ) {
+                    issues.push(format!(
+                        "{} is {status} from an earlier dispatch; it will be requeued \
+                         and a surviving agent killed",
+                        task.name
+                    ));
+                }
+                to_run.push(task);
+            }
+        }
+    }
+
+    let names: HashSet<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+    let mut preview_tasks = Vec::new();
+    for task in to_run {
+        let harness = task
+            .requested_harness
+            .as_deref()
+            .unwrap_or(&plan.default_harness);
+        if !harnesses.contains(&harness) {
+            issues.push(format!(
+                "{} uses harness {harness:?}, which dispatch does not provide",
+                task.name
+            ));
+        }
+
+        let mut depends_on = Vec::new();
+        for (dep, status) in task_db::get_task_dependency_statuses(pool, task.id).await? {
+            if names.contains(dep.as_str()) {
+                depends_on.push(dep);
+                continue;
+            }
+            // A task of another plan.
+            match status {
+                TaskStatus::Passed => {}
+                TaskStatus::Escalated | TaskStatus::Cancelled => {
+                    stuck.insert(
+                        task.name.clone(),
+                        format!("depends on {dep}, which is {status}"),
+                    );
+                }
+                status => {
+                    stuck.insert(
+                        task.name.clone(),
+                        format!("waits on {dep} ({status}) of another plan"),
+                    );
+                }
+            }
+        }
+
+        let prompt = materialize_task(pool, task.id)
+            .await
+            .with_context(|| format!("failed to materialize task {}", task.name))?;
+        preview_tasks.push(PreviewTask {
+            name: task.name.clone(),
+            paths: task.paths.0.clone(),
+            depends_on,
+            prompt_tokens: estimate_tokens(&prompt),
+        });
+    }
+
+    let (waves, unscheduled) = plan_waves(preview_tasks, max_agents, &stuck);
+    issues.extend(unscheduled);
+    for (a, b) in serialized_pairs(&waves) {
+        issues.push(format!(
+            "{a} and {b} declare overlapping paths; they run one after the other"
+        ));
+    }
+
+    let (past_attempts, average_attempt_tokens) =
+        agent_events::average_tokens_per_attempt(pool, &plan.project_path).await?;
+    let (used_in, used_out) = agent_events::get_token_usage_for_plan(pool, plan_id).await?;
+    let used_tokens = used_in + used_out;
+
+    let mut preview = DispatchPreview {
+        plan,
+        max_agents,
+        passed,
+        waves,
+        past_attempts,
+        average_attempt_tokens,
+        used_tokens,
+        issues,
+    };
+    if let (Some(budget), Some(estimate)) = (preview.plan.token_budget, preview.estimated_tokens())
+    {
+        if used_tokens + estimate > budget {
+            preview.issues.push(format!(
+                "estimated usage of ~{estimate} tokens exceeds the {} left of the plan's budget",
+                (budget - used_tokens).max(0)
+            ));
+        }
+    }
+    Ok(preview)
+}
+
+/// Place tasks in waves: a task joins the first wave after all its
+/// dependencies', if that wave has a free agent and no task whose paths
+/// overlap its own. Tasks in `stuck` never run, nor does anything that
+/// depends on one; they are returned as issues instead.
+pub fn plan_waves(
+    tasks: Vec<PreviewTask>,
+    max_agents: usize,
+    stuck: &HashMap<String, String>,
+) -> (Vec<Vec<PreviewTask>>, Vec<String>) {
+    let max_agents = max_agents.max(1);
+    let mut waves: Vec<Vec<PreviewTask>> = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+    let mut remaining = tasks;
+
+    loop {
+        let mut wave: Vec<PreviewTask> = Vec::new();
+        let mut rest = Vec::new();
+        for task in remaining {
+            let ready =
+                !stuck.contains_key(&task.name) && task.depends_on.iter().all(|d| done.contains(d));
+            let fits = wave.len() < max_agents
+                && wave
+                    .iter()
+                    .all(|t| overlap::paths_overlap(&t.paths, &task.paths).is_empty());
+            if ready && fits {
+                wave.push(task);
+            } else {
+                rest.push(task);
+            }
+        }
+        remaining = rest;
+        if wave.is_empty() {
+            break;
+        }
+        done.extend(wave.iter().map(|t| t.name.clone()));
+        waves.push(wave);
+    }
+
+    // What is left waits, directly or not, on a task that cannot run.
+    let mut issues: Vec<String> = remaining
+        .iter()
+        .map(|task| match stuck.get(&task.name) {
+            Some(reason) => format!("{} {reason}; it will not run", task.name),
+            None => {
+                let blocker = task
+                    .depends_on
+                    .iter()
+                    .find(|d| !done.contains(*d))
+                    .map(String::as_str)
+                    .unwrap_or("?");
+                format!(
+                    "{} will not run: dependency {blocker} cannot pass",
+                    task.name
+                )
+            }
+        })
+        .collect();
+    // Tasks stuck on their own are reported even when nothing runs after
+    // them.
+    let mut own: Vec<_> = stuck
+        .iter()
+        .filter(|(name, _)| !remaining.iter().any(|t| &t.name == *name))
+        .map(|(name, reason)| format!("{name} {reason}"))
+        .collect();
+    own.sort();
+    issues.extend(own);
+    (waves, issues)
+}
+
+/// Pairs of tasks in different waves that would have shared one had their
+/// paths not overlapped: both were ready by the earlier wave's start.
+fn serialized_pairs(waves: &[Vec<PreviewTask>]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut done: HashSet<&str> = HashSet::new();
+    for (i, wave) in waves.iter().enumerate() {
+        for later in waves.iter().skip(i + 1).flatten() {
+            if !later.depends_on.iter().all(|d| done.contains(d.as_str())) {
+                continue;
+            }
+            for task in wave {
+                if !overlap::paths_overlap(&task.paths, &later.paths).is_empty()
+                    && !pairs
+                        .iter()
+                        .any(|(_, b): &(String, String)| b == &later.name)
+                {
+                    pairs.push((task.name.clone(), later.name.clone()));
+                }
+            }
+        }
+        done.extend(wave.iter().map(|t| t.name.as_str()));
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, depends_on: &[&str], paths: &[&str]) -> PreviewTask {
+        PreviewTask {
+            name: name.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            prompt_tokens: 0,
+        }
+    }
+
+    fn names(waves: &[Vec<PreviewTask>]) -> Vec<Vec<&str>> {
+        waves
+            .iter()
+            .map(|w| w.iter().map(|t| t.name.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn waves_follow_dependencies_agents_and_paths() {
+        let tasks = vec![
+            task("a", &[], &["src/db/**"]),
+            task("b", &[], &["src/db/schema.rs"]),
+            task("c", &[], &[]),
+            task("d", &[], &[]),
+            task("e", &["a", "c"], &[]),
+        ];
+        let (waves, issues) = plan_waves(tasks, 2, &HashMap::new());
+
+        // b overlaps a, d finds the wave full; e waits for a and c.
+        assert_eq!(
+            names(&waves),
+            vec![vec!["a", "c"], vec!["b", "d"], vec!["e"]]
+        );
+        assert!(issues.is_empty());
+        assert_eq!(
+            serialized_pairs(&waves),
+            vec![("a".to_string(), "b".to_string())]
+        );
+    }
+
+    #[test]
+    fn stuck_tasks_hold_back_their_dependents() {
+        let tasks = vec![
+            task("a", &[], &[]),
+            task("b", &["x"], &[]),
+            task("c", &["b"], &[]),
+        ];
+        let stuck = HashMap::from([
+            (
+                "b".to_string(),
+                "waits on other:x (running) of another plan".to_string(),
+            ),
+            (
+                "x".to_string(),
+                "is escalated (retry it with gator retry)".to_string(),
+            ),
+        ]);
+        let (waves, issues) = plan_waves(tasks, 4, &stuck);
+
+        assert_eq!(names(&waves), vec![vec!["a"]]);
+        assert_eq!(
+            issues,
+            vec![
+                "b waits on other:x (running) of another plan; it will not run",
+                "c will not run: dependency b cannot pass",
+                "x is escalated (retry it with gator retry)",
+            ]
+        );
+    }
+}
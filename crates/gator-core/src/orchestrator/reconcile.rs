@@ -0,0 +1,183 @@
+//! Restart recovery: reconcile the tasks a dead dispatch left in flight.
+//!
+//! Every agent is recorded on its task as it spawns: host, process ID, the
+//! process's start time, and its container under container isolation. When
+//! `gator dispatch` starts, each in-flight task of the plan is requeued
+//! and any agent of it still alive on this host is killed. The start time
+//! tells the agent apart from a later process that reuses its ID, so
+//! nothing else is ever killed; a process without a recorded start time is
+//! left alone.
+//!
+//! A surviving agent cannot be re-attached: its output went to pipes held
+//! by the dispatch that died, so its events and completion are lost. It is
+//! killed and the task starts a new attempt. The interrupted attempt is not
+//! charged to the retry budget. Agents on other hosts cannot be checked and
+//! are reported instead.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::TaskStatus;
+use gator_db::queries::tasks as task_db;
+
+use super::identity;
+use super::stop::{kill_process, remove_container};
+
+/// How long to wait for a killed agent to exit.
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// Pause between checks that a killed agent has exited.
+const KILL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What [`reconcile_in_flight`] did.
+#[derive(Debug, Default)]
+pub struct ReconcileReport {
+    /// Names of the tasks requeued as `pending`.
+    pub requeued: Vec<String>,
+    /// Surviving agent processes that were killed.
+    pub killed_processes: Vec<u32>,
+    /// Agent containers that were removed.
+    pub removed_containers: Vec<String>,
+    /// Tasks whose agent ran on another host, as `task (host)`.
+    pub remote: Vec<String>,
+    /// Processes or containers that could not be stopped.
+    pub errors: Vec<String>,
+}
+
+/// Requeue the in-flight tasks of a plan whose dispatch died, killing the
+/// agents of theirs that are still alive on this host.
+pub async fn reconcile_in_flight(pool: &SqlitePool, plan_id: Uuid) -> Result<ReconcileReport> {
+    let mut report = ReconcileReport::default();
+    let host = identity::host();
+
+    for task in task_db::list_tasks_for_plan(pool, plan_id).await? {
+        if !matches!(
+            task.status,
+            TaskStatus::Assigned
+                | TaskStatus::Running
+                | TaskStatus::BlockedOnHuman
+                | TaskStatus::Checking
+        ) {
+            continue;
+        }
+        // As in `stop_all`, requeue before killing so the agent's death is
+        // never taken for a finished attempt.
+        if task_db::requeue_interrupted_task(pool, task.id, task.attempt).await? == 0 {
+            continue;
+        }
+        tracing::warn!(
+            task_id = %task.id,
+            task_name = %task.name,
+            attempt = task.attempt,
+            "requeued task interrupted by a previous dispatch"
+        );
+        report.requeued.push(task.name.clone());
+
+        // Agents recorded before hosts were recorded ran here.
+        if let Some(agent_host) = task.agent_host.as_deref().filter(|&h| h != host) {
+            report.remote.push(format!("{} ({agent_host})", task.name));
+            continue;
+        }
+        if let (Some(pid), Some(started)) = (task.agent_pid, task.agent_started.as_deref()) {
+            if is_agent_alive(pid, started) {
+                match kill_agent(pid, started).await {
+                    Ok(pid) => {
+                        tracing::info!(task_id = %task.id, pid, "killed surviving agent");
+                        report.killed_processes.push(pid);
+                    }
+                    Err(e) => report.errors.push(format!("{}: {e}", task.name)),
+                }
+            }
+        }
+        if let Some(container_id) = task.container_id {
+            match remove_container(&container_id).await {
+                Ok(()) => report.removed_containers.push(container_id),
+                Err(e) => report.errors.push(format!("{}: {e}", task.name)),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Whether `pid` is still the process that started at `started`.
+fn is_agent_alive(pid: i64, started: &str) -> bool {
+    u32::try_from(pid)
+        .ok()
+        .and_then(process_start_time)
+        .is_some_and(|now| now == started)
+}
+
+/// SIGKILL a surviving agent and wait for it to exit.
+async fn kill_agent(pid: i64, started: &str) -> Result<u32> {
+    let killed = kill_process(pid)?;
+    let deadline = tokio::time::Instant::now() + KILL_WAIT;
+    while is_agent_alive(pid, started) {
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!(
+                "process {pid} still running {}s after SIGKILL",
+                KILL_WAIT.as_secs()
+            );
+        }
+        tokio::time::sleep(KILL_POLL_INTERVAL).await;
+    }
+    Ok(killed)
+}
+
+/// Start time of a live process, in the OS's own units: clock ticks since
+/// boot on Linux, `ps`'s start date elsewhere. `None` when the process does
+/// not exist or has exited.
+#[cfg(target_os = "linux")]
+pub fn process_start_time(pid: u32) -> Option<String> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name is parenthesised and may contain anything; the
+    // fields after it are state (3rd) onwards, start time being the 22nd.
+    let (_, fields) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    if fields
+        .first()
+        .is_some_and(|state| *state == "Z" || *state == "X")
+    {
+        return None;
+    }
+    fields.get(19).map(|s| s.to_string())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn process_start_time(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "lstart=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !started.is_empty()).then_some(started)
+}
+
+#[cfg(not(unix))]
+pub fn process_start_time(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn start_time_identifies_a_live_process() {
+        let started = process_start_time(std::process::id());
+        assert!(started.is_some());
+        assert_eq!(process_start_time(std::process::id()), started);
+        assert!(is_agent_alive(
+            i64::from(std::process::id()),
+            started.as_deref().unwrap()
+        ));
+        assert!(!is_agent_alive(
+            i64::from(std::process::id()),
+            "not-a-start-time"
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! `gator dispatch --only/--from/--until`: run a slice of a plan's DAG.
+//!
+//! `--only` names the tasks to run. `--from` runs a task and everything
+//! that depends on it, directly or transitively; `--until` runs a task and
+//! everything it depends on. Given both, the slice is the tasks between
+//! them. Tasks are named by name or ID.
+//!
+//! A slice must be closed under its dependencies: every dependency of a
+//! selected task is either selected too or has already passed. Otherwise
+//! the selected task could never become ready, and the slice is rejected
+//! before anything runs.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{Result, bail};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::TaskStatus;
+use gator_db::queries::tasks as task_db;
+
+/// Which tasks of a plan a dispatch runs. Empty selects every task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskSelection {
+    /// Run exactly these tasks.
+    pub only: Vec<String>,
+    /// Run this task and its transitive dependents.
+    pub from: Option<String>,
+    /// Run this task and its transitive dependencies.
+    pub until: Option<String>,
+}
+
+impl TaskSelection {
+    /// Whether the selection leaves the plan whole.
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.from.is_none() && self.until.is_none()
+    }
+}
+
+/// Resolve `selection` against the tasks of `plan_id` to the IDs of the
+/// tasks to run, checking that the slice is closed under its dependencies.
+pub async fn resolve_selection(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    selection: &TaskSelection,
+) -> Result<HashSet<Uuid>> {
+    let tasks = task_db::list_tasks_for_plan(pool, plan_id).await?;
+    let mut dependencies = task_db::list_plan_dependency_names(pool, plan_id).await?;
+    let names: HashSet<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+
+    // The plan's own DAG, by name. Tasks of other plans are waited on as
+    // usual and play no part in the slice.
+    let graph: BTreeMap<&str, Vec<String>> = tasks
+        .iter()
+        .map(|task| {
+            let deps = dependencies
+                .remove(&task.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, _)| name)
+                .filter(|name| names.contains(name.as_str()))
+                .collect();
+            (task.name.as_str(), deps)
+        })
+        .collect();
+
+    let find = |reference: &str| -> Result<&str> {
+        tasks
+            .iter()
+            .find(|t| t.name == reference || t.id.to_string() == reference)
+            .map(|t| t.name.as_str())
+            .ok_or_else(|| anyhow::anyhow!("no task {reference:?} in the plan"))
+    };
+    let only = selection
+        .only
+        .iter()
+        .map(|r| find(r))
+        .collect::<Result<Vec<_>>>()?;
+    let from = selection.from.as_deref().map(find).transpose()?;
+    let until = selection.until.as_deref().map(find).transpose()?;
+
+    let selected = select_tasks(&graph, &only, from, until)?;
+
+    let passed: HashSet<&str> = tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Passed)
+        .map(|t| t.name.as_str())
+        .collect();
+    let mut missing = Vec::new();
+    for name in &selected {
+        for dep in &graph[name] {
+            if !selected.contains(dep.as_str()) && !passed.contains(dep.as_str()) {
+                missing.push(format!("{name} depends on {dep}"));
+            }
+        }
+    }
+    if !missing.is_empty() {
+        bail!(
+            "the selected tasks depend on tasks that are neither selected nor passed:\n  {}\n\
+             Select those too, or use --until to take in a task's dependencies.",
+            missing.join("\n  ")
+        );
+    }
+
+    Ok(tasks
+        .iter()
+        .filter(|t| selected.contains(t.name.as_str()))
+        .map(|t| t.id)
+        .collect())
+}
+
+/// The names of the tasks in the slice of `graph` (task to its
+/// dependencies) given by `only`, or by `from` and `until`.
+fn select_tasks<'a>(
+    graph: &BTreeMap<&'a str, Vec<String>>,
+    only: &[&'a str],
+    from: Option<&'a str>,
+    until: Option<&'a str>,
+) -> Result<BTreeSet<&'a str>> {
+    if !only.is_empty() {
+        return Ok(only.iter().copied().collect());
+    }
+
+    let dependents = |root: &'a str| {
+        reachable(root, |name| {
+            graph
+                .iter()
+                .filter(|(_, deps)| deps.iter().any(|d| d == name))
+                .map(|(task, _)| *task)
+                .collect()
+        })
+    };
+    let dependencies = |root: &'a str| {
+        reachable(root, |name| {
+            graph[name]
+                .iter()
+                .filter_map(|d| graph.get_key_value(d.as_str()).map(|(k, _)| *k))
+                .collect()
+        })
+    };
+
+    match (from, until) {
+        (Some(from), Some(until)) => {
+            let slice: BTreeSet<_> = dependents(from)
+                .intersection(&dependencies(until))
+                .copied()
+                .collect();
+            if slice.is_empty() {
+                bail!("{until} does not depend on {from}; --from and --until select nothing");
+            }
+            Ok(slice)
+        }
+        (Some(from), None) => Ok(dependents(from)),
+        (None, Some(until)) => Ok(dependencies(until)),
+        (None, None) => Ok(graph.keys().copied().collect()),
+    }
+}
+
+/// `root` and every task reachable from it through `next`.
+fn reachable<'a>(root: &'a str, next: impl Fn(&str) -> Vec<&'a str>) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::from([root]);
+    let mut stack = vec![root];
+    while let Some(name) = stack.pop() {
+        for task in next(name) {
+            if seen.insert(task) {
+                stack.push(task);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a -> b -> d, a -> c -> d, e on its own.
+    fn graph() -> BTreeMap<&'static str, Vec<String>> {
+        BTreeMap::from([
+            ("a", vec![]),
+            ("b", vec!["a".to_string()]),
+            ("c", vec!["a".to_string()]),
+            ("d", vec!["b".to_string(), "c".to_string()]),
+            ("e", vec![]),
+        ])
+    }
+
+    fn names(set: BTreeSet<&str>) -> Vec<&str> {
+        set.into_iter().collect()
+    }
+
+    #[test]
+    fn from_and_until_follow_the_dag() {
+        let graph = graph();
+        assert_eq!(
+            names(select_tasks(&graph, &[], Some("b"), None).unwrap()),
+            ["b", "d"]
+        );
+        assert_eq!(
+            names(select_tasks(&graph, &[], None, Some("b")).unwrap()),
+            ["a", "b"]
+        );
+        assert_eq!(
+            names(select_tasks(&graph, &[], Some("a"), Some("d")).unwrap()),
+            ["a", "b", "c", "d"]
+        );
+        assert_eq!(
+            names(select_tasks(&graph, &["e", "c"], None, None).unwrap()),
+            ["c", "e"]
+        );
+    }
+
+    #[test]
+    fn disjoint_from_and_until_is_an_error() {
+        let err = select_tasks(&graph(), &[], Some("b"), Some("c")).unwrap_err();
+        assert!(err.to_string().contains("c does not depend on b"));
+    }
+}
@@ -0,0 +1,220 @@
+//! The global kill switch behind `gator admin stop-all`.
+//!
+//! Stopping works through the database so it reaches orchestrators in other
+//! processes: every running plan is interrupted (each orchestrator notices
+//! within a second and stops scheduling), every in-flight task is reset to
+//! `pending` with a fresh attempt, and then the agents those tasks recorded
+//! are killed. A lifecycle whose agent dies, or whose task was reset under
+//! it, fails its next state transition and cleans up its workspace.
+//!
+//! Resetting before killing matters: an agent killed first could be
+//! mistaken for one that finished and have its work gated.
+//!
+//! `gator cancel` (or `gator task cancel`) is the same for one task, except
+//! that the task ends `cancelled` rather than going back to `pending`, and
+//! its plan keeps running: the orchestrator treats a cancelled task as
+//! finished.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use sqlx::SqlitePool;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use gator_db::models::{PlanStatus, TaskStatus};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use crate::state::TaskStateMachine;
+
+/// How many times in-flight tasks are swept. Orchestrators may assign a
+/// task between the plan being interrupted and noticing it, so later sweeps
+/// catch what the first one missed.
+const STOP_SWEEPS: usize = 3;
+
+/// Pause between sweeps; longer than an orchestrator's stop poll.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What [`stop_all`] did.
+#[derive(Debug, Default)]
+pub struct StopReport {
+    /// Names of the plans that were interrupted.
+    pub plans: Vec<String>,
+    /// Names of the tasks reset to `pending`.
+    pub tasks: Vec<String>,
+    /// Agent processes that were killed.
+    pub killed_processes: Vec<u32>,
+    /// Agent containers that were removed.
+    pub removed_containers: Vec<String>,
+    /// Processes or containers that could not be stopped.
+    pub errors: Vec<String>,
+}
+
+/// Stop everything: interrupt all running plans, reset their in-flight
+/// tasks to `pending`, and kill the agents' processes and containers.
+///
+/// Every plan is left `approved` (marked interrupted), so `gator dispatch`
+/// resumes it with the stopped tasks starting a new attempt.
+pub async fn stop_all(pool: &SqlitePool) -> Result<StopReport> {
+    let mut report = StopReport::default();
+
+    for plan in plan_db::list_plans(pool).await? {
+        if plan.status != PlanStatus::Running {
+            continue;
+        }
+        // A plan that finished in the meantime is no longer running.
+        if plan_db::interrupt_plan(pool, plan.id).await.is_ok() {
+            tracing::info!(plan_id = %plan.id, "interrupted plan");
+            report.plans.push(plan.name);
+        }
+    }
+
+    let sweeps = if report.plans.is_empty() {
+        1
+    } else {
+        STOP_SWEEPS
+    };
+    for sweep in 0..sweeps {
+        if sweep > 0 {
+            tokio::time::sleep(SWEEP_INTERVAL).await;
+        }
+        for task in task_db::list_in_flight_tasks(pool).await? {
+            if task_db::stop_task(pool, task.id).await? == 0 {
+                continue;
+            }
+            tracing::info!(task_id = %task.id, task_name = %task.name, "stopped task");
+            report.tasks.push(task.name.clone());
+
+            // A checking task's agent has exited; its process ID may
+            // already belong to something else.
+            let agent_running = matches!(task.status, TaskStatus::Assigned | TaskStatus::Running);
+            if let Some(pid) = task.agent_pid.filter(|_| agent_running) {
+                match kill_process(pid) {
+                    Ok(pid) => report.killed_processes.push(pid),
+                    Err(e) => report.errors.push(format!("{}: {e}", task.name)),
+                }
+            }
+            if let Some(container_id) = task.container_id {
+                match remove_container(&container_id).await {
+                    Ok(()) => report.removed_containers.push(container_id),
+                    Err(e) => report.errors.push(format!("{}: {e}", task.name)),
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// What [`cancel_task`] did.
+#[derive(Debug)]
+pub struct CancelReport {
+    /// Name of the cancelled task.
+    pub task: String,
+    /// The task's status before it was cancelled.
+    pub previous: TaskStatus,
+    /// Names of the pending tasks that depended on it, cancelled with it.
+    pub dependents: Vec<String>,
+    /// The agent process that was killed, if one was running.
+    pub killed_process: Option<u32>,
+    /// The agent container that was removed, if there was one.
+    pub removed_container: Option<String>,
+    /// Processes or containers that could not be stopped.
+    pub errors: Vec<String>,
+}
+
+/// Cancel one task: mark it `cancelled` along with the pending tasks that
+/// depend on it, then kill its agent's process and container.
+///
+/// Any task that has not passed can be cancelled. The status is the signal
+/// to a dispatch running the task: its lifecycle notices within a second,
+/// kills the agent through its harness, and frees the agent slot (a
+/// lifecycle past its agent fails its next state transition instead). The
+/// plan carries on with the remaining tasks. Killing the process here as
+/// well covers a dispatch that is no longer running.
+pub async fn cancel_task(pool: &SqlitePool, task_id: Uuid) -> Result<CancelReport> {
+    let task = task_db::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+    if matches!(task.status, TaskStatus::Passed | TaskStatus::Cancelled) {
+        bail!("task {} is {}, cannot cancel it", task.name, task.status);
+    }
+
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, task.status, TaskStatus::Cancelled).await?;
+    let dependents = task_db::cancel_pending_dependents(&mut *tx, task_id).await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit cancellation of task {task_id}"))?;
+    tracing::info!(task_id = %task.id, task_name = %task.name, "cancelled task");
+
+    let mut report = CancelReport {
+        task: task.name.clone(),
+        previous: task.status,
+        dependents: dependents.into_iter().map(|t| t.name).collect(),
+        killed_process: None,
+        removed_container: None,
+        errors: Vec::new(),
+    };
+    let in_flight = matches!(
+        task.status,
+        TaskStatus::Assigned | TaskStatus::Running | TaskStatus::Checking
+    );
+    // As in `stop_all`, only an assigned or running task's process ID is
+    // known to be its agent.
+    let agent_running = matches!(task.status, TaskStatus::Assigned | TaskStatus::Running);
+    if let Some(pid) = task.agent_pid.filter(|_| agent_running) {
+        match kill_process(pid) {
+            Ok(pid) => report.killed_process = Some(pid),
+            Err(e) => report.errors.push(e.to_string()),
+        }
+    }
+    if let Some(container_id) = task.container_id.filter(|_| in_flight) {
+        match remove_container(&container_id).await {
+            Ok(()) => report.removed_container = Some(container_id),
+            Err(e) => report.errors.push(e.to_string()),
+        }
+    }
+
+    Ok(report)
+}
+
+/// SIGKILL an agent process. A process that already exited is not an
+/// error.
+#[cfg(unix)]
+pub(super) fn kill_process(pid: i64) -> Result<u32> {
+    // kill(2) treats 0 and negative IDs as process groups; never send those.
+    let pid = i32::try_from(pid)
+        .ok()
+        .filter(|pid| *pid > 0)
+        .with_context(|| format!("invalid agent process ID {pid}"))?;
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    let ret = unsafe { libc::kill(pid, libc::SIGKILL) };
+    if ret != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ESRCH) {
+            anyhow::bail!("failed to kill process {pid}: {err}");
+        }
+    }
+    Ok(pid as u32)
+}
+
+#[cfg(not(unix))]
+pub(super) fn kill_process(pid: i64) -> Result<u32> {
+    anyhow::bail!("cannot kill process {pid} on this platform")
+}
+
+/// Force-remove an agent container. A container that is already gone is
+/// not an error.
+pub(super) async fn remove_container(container_id: &str) -> Result<()> {
+    let output = Command::new("docker")
+        .args(["rm", "-f", container_id])
+        .output()
+        .await?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && !stderr.contains("No such container") {
+        anyhow::bail!("docker rm -f {container_id} failed: {}", stderr.trim());
+    }
+    Ok(())
+}
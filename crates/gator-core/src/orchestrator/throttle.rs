@@ -0,0 +1,240 @@
+//! Adaptive throttling after provider rate limits.
+//!
+//! Agents that hit a rate limit report it as an error event (see
+//! [`crate::harness::rate_limit`]). Starting more agents then only earns
+//! more rate limits, so the orchestrator backs off for the whole plan: it
+//! halves the number of agents it runs at once and starts none until a
+//! pause is over. The pause is what the provider asked for, or else a
+//! backoff that doubles with each new rate limit, up to [`MAX_BACKOFF`].
+//! Once the pause is over, the limit grows back by one agent every
+//! [`RECOVERY_STEP`] without a rate limit, and the throttle is lifted when
+//! it reaches `max_agents` again.
+//!
+//! The state is kept in `plan_throttles` for `gator status` and the
+//! dashboard.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::queries::agent_events;
+use gator_db::queries::plan_throttles;
+
+use crate::harness::rate_limit;
+
+/// Pause after the first rate limit that does not say how long to wait.
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Longest pause the backoff grows to.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// How long the limit must go without a rate limit to grow by one agent.
+pub const RECOVERY_STEP: Duration = Duration::from_secs(60);
+
+/// The orchestrator's throttle for one plan.
+#[derive(Debug, Clone)]
+pub(crate) struct Throttle {
+    plan_id: Uuid,
+    max_agents: usize,
+    limit: usize,
+    paused_until: Option<DateTime<Utc>>,
+    backoff: Duration,
+    /// When the limit last shrank or grew.
+    changed_at: Option<DateTime<Utc>>,
+    rate_limits: i64,
+    last_error: Option<String>,
+    /// Error events up to this ID have been read.
+    seen_event_id: i64,
+}
+
+impl Throttle {
+    /// An open throttle for `plan_id`. Errors recorded before now, and any
+    /// throttle state left by an earlier run, are ignored.
+    pub(crate) async fn start(pool: &SqlitePool, plan_id: Uuid, max_agents: usize) -> Result<Self> {
+        plan_throttles::clear_throttle(pool, plan_id).await?;
+        Ok(Self {
+            plan_id,
+            max_agents,
+            limit: max_agents,
+            paused_until: None,
+            backoff: INITIAL_BACKOFF,
+            changed_at: None,
+            rate_limits: 0,
+            last_error: None,
+            seen_event_id: agent_events::max_event_id(pool).await?,
+        })
+    }
+
+    /// Whether another agent may start while `in_flight` are running.
+    pub(crate) fn allows(&self, in_flight: usize, now: DateTime<Utc>) -> bool {
+        in_flight < self.limit && self.paused_until.is_none_or(|until| until <= now)
+    }
+
+    /// The end of the current pause, if one is under way.
+    pub(crate) fn paused_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.paused_until.filter(|until| *until > now)
+    }
+
+    /// Read the plan's new error events, throttle on rate limits, and let
+    /// the limit recover; record the state when it changes.
+    pub(crate) async fn observe(&mut self, pool: &SqlitePool) -> Result<()> {
+        let now = Utc::now();
+        let mut changed = false;
+        for event in
+            agent_events::list_errors_for_plan_after(pool, self.plan_id, self.seen_event_id).await?
+        {
+            self.seen_event_id = self.seen_event_id.max(event.id);
+            let Some(message) = event.payload.get("message").and_then(|m| m.as_str()) else {
+                continue;
+            };
+            if let Some(limit) = rate_limit::detect(message) {
+                self.rate_limited(now, message, limit.retry_after);
+                changed = true;
+            }
+        }
+        changed |= self.recover(now);
+
+        if !changed {
+            return Ok(());
+        }
+        if self.is_throttled() {
+            plan_throttles::set_throttle(
+                pool,
+                self.plan_id,
+                self.limit,
+                self.max_agents,
+                self.paused_until,
+                self.rate_limits,
+                self.last_error.as_deref(),
+            )
+            .await
+        } else {
+            plan_throttles::clear_throttle(pool, self.plan_id).await
+        }
+    }
+
+    fn is_throttled(&self) -> bool {
+        self.limit < self.max_agents || self.paused_until.is_some()
+    }
+
+    /// Back off after a rate limit reported at `now`.
+    fn rate_limited(&mut self, now: DateTime<Utc>, message: &str, retry_after: Option<Duration>) {
+        self.rate_limits += 1;
+        self.last_error = Some(message.to_string());
+        let wait = retry_after.unwrap_or(self.backoff);
+        let until = now + chrono::Duration::from_std(wait).unwrap_or(chrono::Duration::MAX);
+
+        // Agents that were already running hit the same limit; they extend
+        // the pause but do not shrink the limit again.
+        if let Some(current) = self.paused_until(now) {
+            self.paused_until = Some(current.max(until));
+            return;
+        }
+
+        self.limit = (self.limit / 2).max(1);
+        self.paused_until = Some(until);
+        self.changed_at = Some(now);
+        if retry_after.is_none() {
+            self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        }
+        tracing::warn!(
+            plan_id = %self.plan_id,
+            agent_limit = self.limit,
+            max_agents = self.max_agents,
+            pause_secs = wait.as_secs(),
+            error = %message,
+            "agents are being rate-limited; throttling dispatch"
+        );
+    }
+
+    /// Grow the limit by one agent once the pause is over and a recovery
+    /// step has passed without a rate limit. Returns whether anything
+    /// changed.
+    fn recover(&mut self, now: DateTime<Utc>) -> bool {
+        if !self.is_throttled() || self.paused_until(now).is_some() {
+            return false;
+        }
+        let step = chrono::Duration::from_std(RECOVERY_STEP).unwrap_or(chrono::Duration::MAX);
+        let since = self.paused_until.into_iter().chain(self.changed_at).max();
+        if since.is_some_and(|since| now - since < step) && self.limit < self.max_agents {
+            return false;
+        }
+
+        self.paused_until = None;
+        self.limit = (self.limit + 1).min(self.max_agents);
+        self.changed_at = Some(now);
+        if self.limit == self.max_agents {
+            tracing::info!(plan_id = %self.plan_id, "rate limits have cleared; throttle lifted");
+            self.backoff = INITIAL_BACKOFF;
+            self.rate_limits = 0;
+            self.last_error = None;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(max_agents: usize) -> Throttle {
+        Throttle {
+            plan_id: Uuid::new_v4(),
+            max_agents,
+            limit: max_agents,
+            paused_until: None,
+            backoff: INITIAL_BACKOFF,
+            changed_at: None,
+            rate_limits: 0,
+            last_error: None,
+            seen_event_id: 0,
+        }
+    }
+
+    fn secs(n: i64) -> chrono::Duration {
+        chrono::Duration::seconds(n)
+    }
+
+    #[test]
+    fn rate_limit_halves_the_limit_and_pauses() {
+        let now = Utc::now();
+        let mut t = throttle(8);
+        t.rate_limited(now, "429", None);
+        assert_eq!(t.limit, 4);
+        assert!(!t.allows(0, now + secs(29)));
+        assert!(t.allows(3, now + secs(30)));
+        assert!(!t.allows(4, now + secs(30)));
+
+        // Others hitting the same limit during the pause only extend it.
+        t.rate_limited(now + secs(1), "429", Some(Duration::from_secs(45)));
+        assert_eq!(t.limit, 4);
+        assert_eq!(t.paused_until(now), Some(now + secs(46)));
+
+        // A new rate limit after the pause backs off twice as long.
+        t.rate_limited(now + secs(50), "429", None);
+        assert_eq!(t.limit, 2);
+        assert_eq!(t.paused_until(now), Some(now + secs(110)));
+    }
+
+    #[test]
+    fn limit_recovers_one_agent_per_step() {
+        let now = Utc::now();
+        let mut t = throttle(3);
+        t.rate_limited(now, "rate limit", Some(Duration::from_secs(10)));
+        assert_eq!(t.limit, 1);
+
+        assert!(!t.recover(now + secs(5)));
+        assert!(!t.recover(now + secs(30)));
+        assert!(t.recover(now + secs(70)));
+        assert_eq!(t.limit, 2);
+        assert!(!t.recover(now + secs(100)));
+        assert!(t.recover(now + secs(131)));
+        assert_eq!(t.limit, 3);
+        assert!(!t.is_throttled());
+        assert_eq!(t.backoff, INITIAL_BACKOFF);
+        assert!(!t.recover(now + secs(500)));
+    }
+}
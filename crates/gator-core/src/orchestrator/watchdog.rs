@@ -0,0 +1,211 @@
+//! Stall detection for the orchestrator loop.
+//!
+//! While a plan has non-terminal tasks the orchestrator should keep moving:
+//! spawning agents, receiving their results, retrying failures. A leaked
+//! concurrency permit, a lifecycle that never reports back, or a task left
+//! `running` with no agent behind it all look the same from the outside --
+//! a loop that waits forever. The watchdog notices when nothing has happened
+//! for [`WatchdogConfig::stall_after`] and the orchestrator logs a
+//! [`StallSnapshot`] (and, if enabled, re-scans state to recover).
+//!
+//! A single agent can stall too, alive but silent. With
+//! [`WatchdogConfig::agent_stall_after`] set, its lifecycle kills an agent
+//! that records no events and no `gator progress` for that long, and the
+//! attempt fails and is retried like a timed-out one.
+
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use gator_db::queries::agent_events;
+use gator_db::queries::tasks::PlanProgress;
+
+/// Default time without progress before the orchestrator counts as stalled.
+pub const DEFAULT_STALL_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Configuration for the orchestrator's stall watchdog.
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    /// How long the orchestrator may go without scheduling progress (or new
+    /// agent events) before it is considered stalled. `None` disables the
+    /// watchdog.
+    pub stall_after: Option<Duration>,
+    /// On a stall, re-scan state: forget in-flight tasks that already
+    /// finished, restore leaked agent slots, and fail active tasks that
+    /// have no agent so the normal retry/escalation path picks them up.
+    pub self_heal: bool,
+    /// How long one agent may go without events or progress reports before
+    /// it is killed and its attempt failed, separately from the per-task
+    /// wall timeout. `None` disables per-agent stall detection.
+    pub agent_stall_after: Option<Duration>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stall_after: Some(DEFAULT_STALL_AFTER),
+            self_heal: false,
+            agent_stall_after: None,
+        }
+    }
+}
+
+/// Tracks when the orchestrator last made progress.
+pub(super) struct Watchdog {
+    stall_after: Option<Duration>,
+    last_progress: Instant,
+    last_event_count: i64,
+}
+
+impl Watchdog {
+    pub(super) async fn start(
+        pool: &SqlitePool,
+        plan_id: Uuid,
+        config: &WatchdogConfig,
+    ) -> Result<Self> {
+        let last_event_count = match config.stall_after {
+            Some(_) => agent_events::count_events_for_plan(pool, plan_id).await?,
+            None => 0,
+        };
+        Ok(Self {
+            stall_after: config.stall_after,
+            last_progress: Instant::now(),
+            last_event_count,
+        })
+    }
+
+    /// Record scheduling progress, re-arming the watchdog.
+    pub(super) fn progress(&mut self) {
+        self.last_progress = Instant::now();
+    }
+
+    /// Resolves once the stall deadline passes; never, if disabled.
+    pub(super) async fn expired(&self) {
+        match self.stall_after {
+            Some(after) => tokio::time::sleep_until(self.last_progress + after).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// How long the orchestrator has been stalled, if it has.
+    ///
+    /// Agent events written since the last check count as progress: an
+    /// agent that is still working on a long task is not a stall.
+    pub(super) async fn check(
+        &mut self,
+        pool: &SqlitePool,
+        plan_id: Uuid,
+    ) -> Result<Option<Duration>> {
+        let Some(after) = self.stall_after else {
+            return Ok(None);
+        };
+        let stalled_for = self.last_progress.elapsed();
+        if stalled_for < after {
+            return Ok(None);
+        }
+        let events = agent_events::count_events_for_plan(pool, plan_id).await?;
+        if events != self.last_event_count {
+            self.last_event_count = events;
+            self.progress();
+            return Ok(None);
+        }
+        Ok(Some(stalled_for))
+    }
+}
+
+/// What the orchestrator looked like when the watchdog fired.
+#[derive(Debug, Clone)]
+pub struct StallSnapshot {
+    pub stalled_for: Duration,
+    /// Names of the tasks the orchestrator believes have a live agent.
+    pub in_flight: Vec<String>,
+    /// Task counts by status, from the database.
+    pub progress: PlanProgress,
+    /// Lifecycle results sent but not yet received.
+    pub queued_results: usize,
+    /// Free agent slots on the concurrency semaphore.
+    pub free_permits: usize,
+    pub max_agents: usize,
+}
+
+impl StallSnapshot {
+    /// Agent slots neither free nor held by an in-flight task.
+    pub fn leaked_permits(&self) -> usize {
+        self.max_agents
+            .saturating_sub(self.free_permits)
+            .saturating_sub(self.in_flight.len())
+    }
+}
+
+impl fmt::Display for StallSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = &self.progress;
+        write!(
+            f,
+            "no progress for {}s; in flight: [{}]; tasks: {} pending, {} assigned, {} running, \
+             {} checking, {} passed, {} failed, {} escalated; queued results: {}; \
+             free agent slots: {}/{}",
+            self.stalled_for.as_secs(),
+            self.in_flight.join(", "),
+            p.pending,
+            p.assigned,
+            p.running,
+            p.checking,
+            p.passed,
+            p.failed,
+            p.escalated,
+            self.queued_results,
+            self.free_permits,
+            self.max_agents,
+        )?;
+        let leaked = self.leaked_permits();
+        if leaked > 0 {
+            write!(f, " ({leaked} unaccounted for)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(in_flight: &[&str], free_permits: usize) -> StallSnapshot {
+        StallSnapshot {
+            stalled_for: Duration::from_secs(600),
+            in_flight: in_flight.iter().map(|s| s.to_string()).collect(),
+            progress: PlanProgress {
+                pending: 1,
+                running: 1,
+                passed: 2,
+                total: 4,
+                ..Default::default()
+            },
+            queued_results: 0,
+            free_permits,
+            max_agents: 4,
+        }
+    }
+
+    #[test]
+    fn snapshot_renders_counts() {
+        let rendered = snapshot(&["build"], 3).to_string();
+        assert!(rendered.starts_with("no progress for 600s; in flight: [build];"));
+        assert!(rendered.contains("1 pending, 0 assigned, 1 running"));
+        assert!(rendered.ends_with("free agent slots: 3/4"));
+    }
+
+    #[test]
+    fn snapshot_reports_leaked_permits() {
+        let snap = snapshot(&["build"], 1);
+        assert_eq!(snap.leaked_permits(), 2);
+        assert!(
+            snap.to_string()
+                .ends_with("free agent slots: 1/4 (2 unaccounted for)")
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! Editing the tasks of a draft plan in place.
+//!
+//! Each edit is applied to the plan's TOML form, re-validated with the same
+//! rules as `gator plan create` (including the dependency cycle check), and
+//! only then written back, in one transaction. Plans that have been
+//! approved are not editable.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use gator_db::models::{Plan, PlanStatus, Task, split_gate};
+use gator_db::queries::{plans as plan_queries, tasks as task_queries};
+
+use super::materialize::{materialize_plan, parse_materialized};
+use super::parser::validate;
+use super::service::resolve_dependency;
+use super::toml_format::{PlanToml, TaskToml};
+
+/// Changes to one task of a draft plan. `None` and empty fields are left
+/// as they are.
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    /// New task name; dependents keep pointing at the task.
+    pub rename: Option<String>,
+    pub description: Option<String>,
+    pub scope: Option<String>,
+    pub gate: Option<String>,
+    pub retry_max: Option<i32>,
+    pub harness: Option<String>,
+    pub issue: Option<String>,
+    /// Dependencies to add, or to turn from `ordering_only` into
+    /// `needs_output` edges.
+    pub add_depends_on: Vec<String>,
+    /// Dependencies to add, or to turn into, `ordering_only` edges.
+    pub add_ordering_only: Vec<String>,
+    pub remove_depends_on: Vec<String>,
+    pub add_invariants: Vec<String>,
+    pub remove_invariants: Vec<String>,
+}
+
+/// A draft plan loaded for editing.
+struct DraftPlan {
+    plan: Plan,
+    toml: PlanToml,
+    task_ids: HashMap<String, Uuid>,
+}
+
+async fn load_draft(pool: &SqlitePool, plan_id: Uuid) -> Result<DraftPlan> {
+    let plan = plan_queries::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+    if plan.status != PlanStatus::Draft {
+        bail!(
+            "plan {:?} is {}; only draft plans can be edited",
+            plan.name,
+            plan.status
+        );
+    }
+    let toml = parse_materialized(&materialize_plan(pool, plan_id).await?)?;
+    let task_ids = task_queries::list_tasks_for_plan(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|t| (t.name, t.id))
+        .collect();
+    Ok(DraftPlan {
+        plan,
+        toml,
+        task_ids,
+    })
+}
+
+/// Add a task to a draft plan.
+pub async fn add_task(pool: &SqlitePool, plan_id: Uuid, task: TaskToml) -> Result<Task> {
+    let mut draft = load_draft(pool, plan_id).await?;
+    draft.toml.tasks.push(task.clone());
+    validate(&draft.toml).with_context(|| format!("cannot add task {:?}", task.name))?;
+
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+    let row = write_task(&mut tx, &draft.plan, None, &task, &draft.task_ids).await?;
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(row)
+}
+
+/// Remove a task from a draft plan. Refuses while other tasks depend on it.
+pub async fn remove_task(pool: &SqlitePool, plan_id: Uuid, name: &str) -> Result<()> {
+    let mut draft = load_draft(pool, plan_id).await?;
+    let task_id = *draft
+        .task_ids
+        .get(name)
+        .with_context(|| format!("no task {name:?} in plan {:?}", draft.plan.name))?;
+
+    let dependents: Vec<&str> = draft
+        .toml
+        .tasks
+        .iter()
+        .filter(|t| t.depends_on.iter().any(|d| d == name))
+        .map(|t| t.name.as_str())
+        .collect();
+    if !dependents.is_empty() {
+        bail!(
+            "cannot remove task {name:?}: {} depend(s) on it",
+            dependents.join(", ")
+        );
+    }
+    draft.toml.tasks.retain(|t| t.name != name);
+    validate(&draft.toml).with_context(|| format!("cannot remove task {name:?}"))?;
+
+    sqlx::query("DELETE FROM tasks WHERE id = $1")
+        .bind(task_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to delete task {name:?}"))?;
+    Ok(())
+}
+
+/// Apply `changes` to a task of a draft plan.
+pub async fn update_task(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    name: &str,
+    changes: &TaskChanges,
+) -> Result<Task> {
+    let mut draft = load_draft(pool, plan_id).await?;
+    let task_id = *draft
+        .task_ids
+        .get(name)
+        .with_context(|| format!("no task {name:?} in plan {:?}", draft.plan.name))?;
+
+    let new_name = changes.rename.as_deref().unwrap_or(name);
+    for other in &mut draft.toml.tasks {
+        if other.name != name {
+            for dep in other.depends_on.iter_mut().chain(&mut other.ordering_only) {
+                if dep == name {
+                    *dep = new_name.to_string();
+                }
+            }
+        }
+    }
+
+    let task = draft
+        .toml
+        .tasks
+        .iter_mut()
+        .find(|t| t.name == name)
+        .expect("materialized plan contains every task");
+    apply_changes(task, changes)?;
+    let task = task.clone();
+
+    validate(&draft.toml).with_context(|| format!("cannot update task {name:?}"))?;
+
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+    let row = write_task(&mut tx, &draft.plan, Some(task_id), &task, &draft.task_ids).await?;
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(row)
+}
+
+fn apply_changes(task: &mut TaskToml, changes: &TaskChanges) -> Result<()> {
+    if let Some(ref name) = changes.rename {
+        task.name = name.clone();
+    }
+    if let Some(ref description) = changes.description {
+        task.description = description.clone();
+    }
+    if let Some(ref scope) = changes.scope {
+        task.scope = scope.clone();
+    }
+    if let Some(ref gate) = changes.gate {
+        task.gate = gate.clone();
+    }
+    if let Some(retry_max) = changes.retry_max {
+        task.retry_max = retry_max;
+    }
+    if let Some(ref harness) = changes.harness {
+        task.harness = Some(harness.clone());
+    }
+    if let Some(ref issue) = changes.issue {
+        task.issue = Some(issue.clone());
+    }
+
+    for dep in &changes.remove_depends_on {
+        if !task.depends_on.contains(dep) {
+            bail!("task {:?} does not depend on {dep:?}", task.name);
+        }
+        task.depends_on.retain(|d| d != dep);
+        task.ordering_only.retain(|d| d != dep);
+    }
+    for (deps, ordering_only) in [
+        (&changes.add_depends_on, false),
+        (&changes.add_ordering_only, true),
+    ] {
+        for dep in deps {
+            if dep == &task.name {
+                bail!("task {:?} cannot depend on itself", task.name);
+            }
+            if !task.depends_on.contains(dep) {
+                task.depends_on.push(dep.clone());
+            }
+            task.ordering_only.retain(|d| d != dep);
+            if ordering_only {
+                task.ordering_only.push(dep.clone());
+            }
+        }
+    }
+
+    for inv in &changes.remove_invariants {
+        if !task.invariants.contains(inv) {
+            bail!("invariant {inv:?} is not linked to task {:?}", task.name);
+        }
+        task.invariants.retain(|i| i != inv);
+    }
+    for inv in &changes.add_invariants {
+        if !task.invariants.contains(inv) {
+            task.invariants.push(inv.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Insert (`task_id` is `None`) or overwrite a task row, then replace its
+/// dependency edges and invariant links.
+pub(super) async fn write_task(
+    conn: &mut SqliteConnection,
+    plan: &Plan,
+    task_id: Option<Uuid>,
+    task: &TaskToml,
+    task_ids: &HashMap<String, Uuid>,
+) -> Result<Task> {
+    let gate = split_gate(&task.gate);
+    let row = match task_id {
+        None => sqlx::query_as::<_, Task>(
+            "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy, retry_max, requested_harness, issue, services, kind, artifacts, paths, timeout_secs, gate_script, llm_review, review_harness, setup_commands) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18) \
+             RETURNING *",
+        )
+        .bind(Uuid::new_v4())
+        .bind(plan.id),
+        Some(id) => sqlx::query_as::<_, Task>(
+            "UPDATE tasks SET name = $3, description = $4, scope_level = $5, gate_policy = $6, \
+             retry_max = $7, requested_harness = $8, issue = $9, services = $10, kind = $11, artifacts = $12, \
+             paths = $13, timeout_secs = $14, gate_script = $15, llm_review = $16, review_harness = $17, \
+             setup_commands = $18 \
+             WHERE id = $1 AND plan_id = $2 \
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(plan.id),
+    }
+    .bind(&task.name)
+    .bind(&task.description)
+    .bind(&task.scope)
+    .bind(gate.policy)
+    .bind(task.retry_max)
+    .bind(&task.harness)
+    .bind(&task.issue)
+    .bind(sqlx::types::Json(&task.services))
+    .bind(&task.kind)
+    .bind(sqlx::types::Json(&task.artifacts))
+    .bind(sqlx::types::Json(&task.paths))
+    .bind(task.timeout)
+    .bind(gate.script)
+    .bind(gate.llm_review)
+    .bind(gate.review_harness)
+    .bind(sqlx::types::Json(&task.setup_commands))
+    .fetch_one(&mut *conn)
+    .await
+    .with_context(|| format!("failed to write task {:?}", task.name))?;
+
+    sqlx::query("DELETE FROM task_dependencies WHERE task_id = $1")
+        .bind(row.id)
+        .execute(&mut *conn)
+        .await
+        .context("failed to clear task dependencies")?;
+    for dep_name in &task.depends_on {
+        // Validation guarantees local names exist; a renamed task keeps its id.
+        let dep_id = resolve_dependency(conn, plan.id, row.id, task_ids, dep_name).await?;
+        sqlx::query(
+            "INSERT INTO task_dependencies (task_id, depends_on, kind) VALUES ($1, $2, $3)",
+        )
+        .bind(row.id)
+        .bind(dep_id)
+        .bind(task.dependency_kind(dep_name))
+        .execute(&mut *conn)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to insert dependency: {:?} -> {:?}",
+                task.name, dep_name
+            )
+        })?;
+    }
+
+    sqlx::query("DELETE FROM task_invariants WHERE task_id = $1")
+        .bind(row.id)
+        .execute(&mut *conn)
+        .await
+        .context("failed to clear task invariants")?;
+    for inv_name in &task.invariants {
+        let (inv_id,): (Uuid,) = sqlx::query_as("SELECT id FROM invariants WHERE name = $1")
+            .bind(inv_name)
+            .fetch_optional(&mut *conn)
+            .await
+            .with_context(|| format!("failed to look up invariant {inv_name:?}"))?
+            .with_context(|| format!("invariant {inv_name:?} does not exist in the database"))?;
+        sqlx::query("INSERT INTO task_invariants (task_id, invariant_id) VALUES ($1, $2)")
+            .bind(row.id)
+            .bind(inv_id)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to link task {:?} to invariant {:?}",
+                    task.name, inv_name
+                )
+            })?;
+    }
+
+    Ok(row)
+}
@@ -0,0 +1,192 @@
+//! Fan-out: one templated plan instantiated across many repositories.
+//!
+//! A repository list names one repository per line -- `owner/name` on
+//! GitHub, a git URL, or a local path starting with `/` or `.` -- and the
+//! plan template is an ordinary plan TOML that may use these placeholders:
+//!
+//! - `{{repo}}`: the repository as written in the list
+//! - `{{repo_name}}`: its last path component, without `.git`
+//! - `{{base_branch}}`: the repository's detected default branch
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use super::parser::{is_valid_issue_ref, parse_plan_toml};
+use super::toml_format::PlanToml;
+
+/// Where a fan-out repository comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoSource {
+    /// A GitHub repository, `owner/name`, cloned with `gh`.
+    Github(String),
+    /// Any git URL, cloned with `git`.
+    Url(String),
+    /// An existing local checkout, used in place.
+    Local(PathBuf),
+}
+
+/// One line of a repository list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanoutRepo {
+    /// The line as written.
+    pub spec: String,
+    /// Short name: the last path component, without `.git`.
+    pub name: String,
+    pub source: RepoSource,
+}
+
+impl FanoutRepo {
+    /// Where a clone of this repository lives under `workdir`; local
+    /// checkouts are used where they are.
+    pub fn checkout_path(&self, workdir: &Path) -> PathBuf {
+        match &self.source {
+            RepoSource::Github(repo) => workdir.join(repo),
+            RepoSource::Url(_) => workdir.join(&self.name),
+            RepoSource::Local(path) => path.clone(),
+        }
+    }
+}
+
+/// Parse a repository list: one repository per line, blank lines and `#`
+/// comments skipped. Repositories must have distinct checkout locations.
+pub fn parse_repo_list(text: &str) -> Result<Vec<FanoutRepo>> {
+    let mut repos: Vec<FanoutRepo> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let spec = line.trim();
+        if spec.is_empty() || spec.starts_with('#') {
+            continue;
+        }
+        let repo = parse_repo_spec(spec).with_context(|| format!("line {}", i + 1))?;
+        let clash = repos.iter().find(|r| match (&r.source, &repo.source) {
+            (RepoSource::Github(a), RepoSource::Github(b)) => a == b,
+            (RepoSource::Local(a), RepoSource::Local(b)) => a == b,
+            (RepoSource::Url(_), RepoSource::Url(_)) => r.name == repo.name,
+            _ => false,
+        });
+        if let Some(other) = clash {
+            bail!(
+                "line {}: {spec} would share a checkout with {}",
+                i + 1,
+                other.spec
+            );
+        }
+        repos.push(repo);
+    }
+    if repos.is_empty() {
+        bail!("repository list is empty");
+    }
+    Ok(repos)
+}
+
+fn parse_repo_spec(spec: &str) -> Result<FanoutRepo> {
+    let source = if spec.contains("://") || spec.starts_with("git@") {
+        RepoSource::Url(spec.to_string())
+    } else if spec.starts_with('/') || spec.starts_with('.') {
+        RepoSource::Local(PathBuf::from(spec))
+    } else if is_valid_issue_ref(&format!("{spec}#1")) {
+        RepoSource::Github(spec.to_string())
+    } else {
+        bail!("{spec:?} is not owner/name, a git URL, or a path");
+    };
+    let name = spec
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(spec)
+        .trim_end_matches(".git")
+        .to_string();
+    if name.is_empty() {
+        bail!("cannot tell the repository name of {spec:?}");
+    }
+    Ok(FanoutRepo {
+        spec: spec.to_string(),
+        name,
+        source,
+    })
+}
+
+/// Fill the template's placeholders for `repo` and parse the result.
+pub fn instantiate_template(
+    template: &str,
+    repo: &FanoutRepo,
+    base_branch: &str,
+) -> Result<PlanToml> {
+    let content = template
+        .replace("{{repo}}", &repo.spec)
+        .replace("{{repo_name}}", &repo.name)
+        .replace("{{base_branch}}", base_branch);
+    if let Some(start) = content.find("{{") {
+        let end = content[start..]
+            .find("}}")
+            .map_or(content.len(), |e| start + e + 2);
+        bail!(
+            "unknown placeholder {} in plan template",
+            &content[start..end]
+        );
+    }
+    parse_plan_toml(&content).with_context(|| format!("plan template is invalid for {}", repo.spec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"
+[plan]
+name = "bump-serde-{{repo_name}}"
+base_branch = "{{base_branch}}"
+
+[[tasks]]
+name = "bump"
+description = "Bump serde in {{repo}}."
+scope = "narrow"
+gate = "auto"
+"#;
+
+    #[test]
+    fn parses_repo_lists() {
+        let repos = parse_repo_list(
+            "# services\nacme/api\n\nhttps://git.example.com/tools/cli.git\n\
+             git@github.com:acme/web.git\n./vendor/lib\n",
+        )
+        .unwrap();
+
+        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "cli", "web", "lib"]);
+        assert_eq!(repos[0].source, RepoSource::Github("acme/api".to_string()));
+        assert!(matches!(repos[1].source, RepoSource::Url(_)));
+        assert!(matches!(repos[2].source, RepoSource::Url(_)));
+        assert_eq!(
+            repos[3].source,
+            RepoSource::Local(PathBuf::from("./vendor/lib"))
+        );
+
+        let workdir = Path::new("/work");
+        assert_eq!(repos[0].checkout_path(workdir), Path::new("/work/acme/api"));
+        assert_eq!(repos[1].checkout_path(workdir), Path::new("/work/cli"));
+    }
+
+    #[test]
+    fn rejects_bad_and_clashing_entries() {
+        let err = parse_repo_list("acme/api\nnot a repo\n").unwrap_err();
+        assert!(format!("{err:#}").starts_with("line 2: "));
+
+        assert!(parse_repo_list("acme/api\nacme/api\n").is_err());
+        assert!(parse_repo_list("https://a/x/cli.git\nhttps://b/y/cli\n").is_err());
+        assert!(parse_repo_list("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn instantiates_templates_per_repo() {
+        let repo = parse_repo_list("acme/api").unwrap().remove(0);
+        let plan = instantiate_template(TEMPLATE, &repo, "trunk").unwrap();
+        assert_eq!(plan.plan.name, "bump-serde-api");
+        assert_eq!(plan.plan.base_branch, "trunk");
+        assert_eq!(plan.tasks[0].description, "Bump serde in acme/api.");
+
+        let err = instantiate_template(&format!("{TEMPLATE}# {{{{owner}}}}\n"), &repo, "main")
+            .unwrap_err();
+        assert!(err.to_string().contains("{{owner}}"));
+    }
+}
@@ -69,6 +69,11 @@ pub enum GenerateValidationError {
         path: String,
         source: PlanParseError,
     },
+    /// The plan's dependency graph has problems; all of them are listed.
+    Dependencies {
+        path: String,
+        problems: Vec<PlanParseError>,
+    },
 }
 
 impl std::fmt::Display for GenerateValidationError {
@@ -81,6 +86,17 @@ impl std::fmt::Display for GenerateValidationError {
             Self::Invalid { path, source } => {
                 write!(f, "plan file at {path:?} is invalid: {source}")
             }
+            Self::Dependencies { path, problems } => {
+                write!(
+                    f,
+                    "plan file at {path:?} has {} dependency problem(s):",
+                    problems.len()
+                )?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -91,6 +107,7 @@ impl std::error::Error for GenerateValidationError {
             Self::FileNotFound { source, .. } => Some(source),
             Self::EmptyFile { .. } => None,
             Self::Invalid { source, .. } => Some(source),
+            Self::Dependencies { .. } => None,
         }
     }
 }
@@ -145,18 +162,37 @@ base_branch = "string"    # REQUIRED. Git branch to base task branches on.
 # token_budget = 500000   # Optional. Total token budget (input + output).
 # isolation = "worktree"  # Optional. "worktree" (default) or "container".
 # container_image = "img" # Optional. Docker image for container isolation.
+# ephemeral_db = "img"    # Optional. Postgres image; each attempt gets its own DATABASE_URL.
+# template_setup = "cmd"  # Optional. Setup run once in a template checkout task worktrees are cloned from.
+# template_hardlinks = true # Optional. Hard-link template build outputs when a worktree cannot be cloned.
+# setup_commands = ["cmd"] # Optional. Must pass in each task's workspace before its agent starts.
+# warmup_command = "cmd"  # Optional. Run in each task's workspace before its agent starts.
+# caches = ["cargo"]     # Optional. Dependency caches shared by all tasks: cargo, npm, pnpm, pip, go.
+
+# [plan.defaults]         # Optional. Values for tasks that omit scope, gate,
+# scope = "narrow"        # retry_max, harness, or timeout. A task's own value wins.
+# gate = "auto"
+
+# [hooks]                 # Optional. Shell commands run in the project directory:
+# pre_dispatch = "cmd"    # before a dispatch starts (failure stops it),
+# post_task = "cmd"       # after each task attempt,
+# post_plan = "cmd"       # when a dispatch ends,
+# post_merge = "cmd"      # after `gator merge`.
 
 [[tasks]]
 name = "string"           # REQUIRED. Unique task identifier (kebab-case).
 description = """          # REQUIRED. Multi-line description for the agent.
 Detailed instructions...
 """
-scope = "narrow"           # REQUIRED. "narrow", "medium", or "broad".
-gate = "auto"              # REQUIRED. "auto", "human_review", or "human_approve".
+scope = "narrow"           # REQUIRED unless in [plan.defaults]. "narrow", "medium", or "broad".
+gate = "auto"              # REQUIRED unless in [plan.defaults]. "auto", "human_review", or "human_approve".
 # retry_max = 3            # Optional. Max retries before escalation (default: 3).
+# timeout = 1800           # Optional. Seconds per attempt (default: the dispatch timeout).
 # depends_on = ["other"]   # Optional. Task names this depends on.
+# ordering_only = ["other"] # Optional. depends_on entries whose changes this task does not need.
 invariants = ["name"]      # REQUIRED (should not be empty). Invariant names to check.
 # harness = "claude-code"  # Optional. Override the default harness.
+# setup_commands = ["cmd"] # Optional. Run after the plan's setup_commands.
 ```
 
 ### Scope levels
@@ -183,7 +219,7 @@ const DECOMPOSITION_GUIDELINES: &str = r#"## Decomposition Guidelines
    - Edge cases to handle
    - What NOT to change
 6. **Every task needs invariants.** Tasks without invariants cannot be auto-gated. Always include at least the build and test invariants.
-7. **Use `depends_on` for data dependencies.** If task B reads a file that task A creates, B must depend on A.
+7. **Use `depends_on` for data dependencies.** If task B reads a file that task A creates, B must depend on A. When B only has to run after A but does not use its changes, also list A in `ordering_only`.
 8. **Keep task names kebab-case.** They become git branch suffixes.
 "#;
 
@@ -278,7 +314,14 @@ pub fn build_meta_plan(system_prompt: &str, base_branch: &str, gate: &str) -> Pl
             default_harness: "claude-code".to_string(),
             isolation: "worktree".to_string(),
             container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            warmup_command: None,
+            setup_commands: Vec::new(),
+            caches: Vec::new(),
         },
+        hooks: Default::default(),
         tasks: vec![TaskToml {
             name: "write-plan".to_string(),
             description: system_prompt.to_string(),
@@ -286,11 +329,19 @@ pub fn build_meta_plan(system_prompt: &str, base_branch: &str, gate: &str) -> Pl
             gate: gate.to_string(),
             retry_max: 2,
             depends_on: vec![],
+            ordering_only: vec![],
             invariants: vec![
                 "_gator_plan_file_exists".to_string(),
                 "_gator_plan_validates".to_string(),
             ],
             harness: None,
+            issue: None,
+            services: Vec::new(),
+            kind: "code".to_string(),
+            artifacts: Vec::new(),
+            paths: Vec::new(),
+            timeout: None,
+            setup_commands: Vec::new(),
         }],
     }
 }
@@ -302,6 +353,8 @@ pub fn build_meta_plan(system_prompt: &str, base_branch: &str, gate: &str) -> Pl
 /// Read and validate a generated plan TOML file.
 ///
 /// Returns the parsed `PlanToml` on success, or a descriptive error.
+/// Dependency problems -- duplicate task names, unknown `depends_on`
+/// entries, cycles, and tasks that can never run -- are reported together.
 pub fn validate_generated_plan(path: &str) -> Result<PlanToml, GenerateValidationError> {
     let content =
         std::fs::read_to_string(path).map_err(|e| GenerateValidationError::FileNotFound {
@@ -315,10 +368,23 @@ pub fn validate_generated_plan(path: &str) -> Result<PlanToml, GenerateValidatio
         });
     }
 
-    crate::plan::parse_plan_toml(&content).map_err(|e| GenerateValidationError::Invalid {
+    let invalid = |source: PlanParseError| GenerateValidationError::Invalid {
         path: path.to_string(),
-        source: e,
-    })
+        source,
+    };
+    let plan: PlanToml = crate::plan::parser::deserialize_plan(&content).map_err(invalid)?;
+
+    // Report every dependency problem at once rather than just the first.
+    let problems = crate::plan::parser::dependency_problems(&plan);
+    if !problems.is_empty() {
+        return Err(GenerateValidationError::Dependencies {
+            path: path.to_string(),
+            problems,
+        });
+    }
+
+    crate::plan::parser::validate(&plan).map_err(invalid)?;
+    Ok(plan)
 }
 
 // ---------------------------------------------------------------------------
@@ -546,6 +612,51 @@ invariants = ["check"]
         assert_eq!(plan.tasks.len(), 1);
     }
 
+    #[test]
+    fn validate_reports_every_dependency_problem() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tangled.toml");
+        std::fs::write(
+            &path,
+            r#"
+[plan]
+name = "Tangled"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "A"
+scope = "narrow"
+gate = "auto"
+depends_on = ["b"]
+
+[[tasks]]
+name = "b"
+description = "B"
+scope = "narrow"
+gate = "auto"
+depends_on = ["a"]
+
+[[tasks]]
+name = "c"
+description = "C"
+scope = "narrow"
+gate = "auto"
+depends_on = ["ghost"]
+"#,
+        )
+        .unwrap();
+        let err = validate_generated_plan(path.to_str().unwrap()).unwrap_err();
+        let GenerateValidationError::Dependencies { ref problems, .. } = err else {
+            panic!("expected Dependencies, got: {err}");
+        };
+        assert_eq!(problems.len(), 2);
+        let rendered = err.to_string();
+        assert!(rendered.contains("2 dependency problem(s)"), "{rendered}");
+        assert!(rendered.contains("\"ghost\""), "{rendered}");
+        assert!(rendered.contains("a -> b -> a"), "{rendered}");
+    }
+
     // -- InvariantInfo From<InvariantPreset> --
 
     #[test]
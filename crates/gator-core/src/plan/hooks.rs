@@ -0,0 +1,135 @@
+//! Plan-level hooks: shell commands from a plan's `[hooks]` table.
+//!
+//! Hooks let a project provision its environment or report on a run
+//! without forking gator:
+//!
+//! - `pre_dispatch` runs before a dispatch starts any task. A hook that
+//!   fails stops the dispatch, and the plan stays `approved`.
+//! - `post_task` runs after each task attempt's lifecycle ends, with the
+//!   attempt's result.
+//! - `post_plan` runs when a dispatch of the plan that got past
+//!   `pre_dispatch` ends, however it ends.
+//! - `post_merge` runs after `gator merge` has merged the plan's branches.
+//!
+//! Each runs with `sh -c` in the plan's project directory, so
+//! `./scripts/setup.sh` means the project's script, and gets the plan (and
+//! task) in `GATOR_*` environment variables. Output is captured and logged.
+//! Only `pre_dispatch` can stop anything; the other hooks' failures are
+//! logged.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use tokio::process::Command;
+
+use gator_db::models::{Plan, PlanHooks};
+
+use crate::invariant::runner;
+
+/// How long a hook may run before it is killed.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The points of a plan's life a hook can run at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreDispatch,
+    PostTask,
+    PostPlan,
+    PostMerge,
+}
+
+impl Hook {
+    /// Every hook, in the order they run.
+    pub const ALL: [Hook; 4] = [
+        Hook::PreDispatch,
+        Hook::PostTask,
+        Hook::PostPlan,
+        Hook::PostMerge,
+    ];
+
+    /// The hook's command in `hooks`, if set.
+    pub fn command(self, hooks: &PlanHooks) -> Option<&str> {
+        match self {
+            Hook::PreDispatch => hooks.pre_dispatch.as_deref(),
+            Hook::PostTask => hooks.post_task.as_deref(),
+            Hook::PostPlan => hooks.post_plan.as_deref(),
+            Hook::PostMerge => hooks.post_merge.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Hook::PreDispatch => "pre_dispatch",
+            Hook::PostTask => "post_task",
+            Hook::PostPlan => "post_plan",
+            Hook::PostMerge => "post_merge",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The environment every hook of `plan` gets: `GATOR_HOOK`, and the plan's
+/// `GATOR_PLAN_ID`, `GATOR_PLAN_NAME`, `GATOR_PLAN_STATUS`,
+/// `GATOR_BASE_BRANCH`, and `GATOR_PROJECT_PATH`.
+pub fn hook_env(plan: &Plan, hook: Hook) -> BTreeMap<String, String> {
+    [
+        ("GATOR_HOOK", hook.to_string()),
+        ("GATOR_PLAN_ID", plan.id.to_string()),
+        ("GATOR_PLAN_NAME", plan.name.clone()),
+        ("GATOR_PLAN_STATUS", plan.status.to_string()),
+        ("GATOR_BASE_BRANCH", plan.base_branch.clone()),
+        ("GATOR_PROJECT_PATH", plan.project_path.clone()),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect()
+}
+
+/// Run `plan`'s `hook`, if it has one, with [`hook_env`] plus `extra_env`.
+///
+/// # Errors
+///
+/// Fails if the hook cannot be started, exits non-zero, or runs past
+/// [`HOOK_TIMEOUT`].
+pub async fn run_hook(plan: &Plan, hook: Hook, extra_env: &[(&str, String)]) -> Result<()> {
+    let Some(command_line) = hook.command(&plan.hooks) else {
+        return Ok(());
+    };
+    let mut env = hook_env(plan, hook);
+    env.extend(extra_env.iter().map(|(k, v)| (k.to_string(), v.clone())));
+
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", command_line])
+        .current_dir(Path::new(&plan.project_path))
+        .envs(&env);
+    let label = format!("{hook} hook");
+    let result = runner::run_captured(command, &label, command_line, HOOK_TIMEOUT).await?;
+
+    for line in result.stdout.lines() {
+        tracing::info!(plan = %plan.name, %hook, "{line}");
+    }
+    if !result.passed {
+        let stderr = result.stderr.trim();
+        match result.exit_code {
+            // The runner's timeout message ends stderr.
+            _ if result.timed_out => bail!("{stderr}"),
+            Some(code) => bail!("{label} exited with {code}: {stderr}"),
+            None => bail!("{label} was killed: {stderr}"),
+        }
+    }
+    tracing::info!(plan = %plan.name, %hook, duration_ms = result.duration_ms, "hook finished");
+    Ok(())
+}
+
+/// Run a hook whose failure must not stop anything, logging the failure.
+pub async fn run_hook_logged(plan: &Plan, hook: Hook, extra_env: &[(&str, String)]) {
+    if let Err(e) = run_hook(plan, hook, extra_env).await {
+        tracing::warn!(plan = %plan.name, %hook, error = %format!("{e:#}"), "hook failed");
+    }
+}
@@ -0,0 +1,210 @@
+//! GitHub issue import: turn open issues into plan tasks.
+//!
+//! Converts issue data (as returned by `gh issue list --json
+//! number,title,body,url`) into a [`PlanToml`] with one task per issue.
+//! Each task keeps an `issue` reference (`owner/repo#N`) so the plan's PR
+//! can close the issues it resolves.
+
+use serde::Deserialize;
+
+use super::toml_format::{PlanMeta, PlanToml, TaskToml};
+
+/// Maximum length of the title slug used in generated task names.
+const MAX_SLUG_LEN: usize = 40;
+
+/// A GitHub issue, as emitted by `gh issue list --json number,title,body,url`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct GithubIssue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    pub url: String,
+}
+
+/// Settings applied to every task generated by [`plan_from_issues`].
+#[derive(Debug, Clone)]
+pub struct IssueImportOptions {
+    /// Plan name.
+    pub plan_name: String,
+    /// Base branch for task branches.
+    pub base_branch: String,
+    /// Repository the issues came from, as `owner/name`.
+    pub repo: String,
+    /// Scope level for every task.
+    pub scope: String,
+    /// Gate policy for every task.
+    pub gate: String,
+    /// Invariant names linked to every task.
+    pub invariants: Vec<String>,
+}
+
+/// Build a plan with one independent task per issue, ordered by issue number.
+///
+/// Task names are derived from the issue number and title (see
+/// [`issue_task_name`]); descriptions are the issue title and body followed
+/// by a link back to the issue.
+pub fn plan_from_issues(issues: &[GithubIssue], options: &IssueImportOptions) -> PlanToml {
+    let mut sorted: Vec<&GithubIssue> = issues.iter().collect();
+    sorted.sort_by_key(|i| i.number);
+
+    let tasks = sorted
+        .into_iter()
+        .map(|issue| TaskToml {
+            name: issue_task_name(issue.number, &issue.title),
+            description: issue_description(issue),
+            scope: options.scope.clone(),
+            gate: options.gate.clone(),
+            retry_max: 3,
+            depends_on: vec![],
+            ordering_only: vec![],
+            invariants: options.invariants.clone(),
+            harness: None,
+            issue: Some(format!("{}#{}", options.repo, issue.number)),
+            services: Vec::new(),
+            kind: "code".to_string(),
+            artifacts: Vec::new(),
+            paths: Vec::new(),
+            timeout: None,
+            setup_commands: Vec::new(),
+        })
+        .collect();
+
+    PlanToml {
+        plan: PlanMeta {
+            id: None,
+            name: options.plan_name.clone(),
+            base_branch: options.base_branch.clone(),
+            token_budget: None,
+            default_harness: "claude-code".to_string(),
+            isolation: "worktree".to_string(),
+            container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            warmup_command: None,
+            setup_commands: Vec::new(),
+            caches: Vec::new(),
+        },
+        hooks: Default::default(),
+        tasks,
+    }
+}
+
+/// Derive a task name from an issue: `issue-<number>-<title-slug>`.
+///
+/// The slug keeps ASCII alphanumerics, lowercased, with runs of anything
+/// else collapsed to a single `-`, truncated to a readable length. The
+/// issue number keeps names unique even when titles collide.
+pub fn issue_task_name(number: u64, title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        format!("issue-{number}")
+    } else {
+        format!("issue-{number}-{slug}")
+    }
+}
+
+/// Build the task description from an issue's title, body, and URL.
+fn issue_description(issue: &GithubIssue) -> String {
+    let mut out = String::new();
+    out.push_str(issue.title.trim());
+    out.push_str("\n\n");
+    let body = issue.body.trim();
+    if !body.is_empty() {
+        out.push_str(body);
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("Source: {}\n", issue.url));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::parse_plan_toml;
+
+    fn issue(number: u64, title: &str, body: &str) -> GithubIssue {
+        GithubIssue {
+            number,
+            title: title.to_string(),
+            body: body.to_string(),
+            url: format!("https://github.com/acme/widgets/issues/{number}"),
+        }
+    }
+
+    fn options() -> IssueImportOptions {
+        IssueImportOptions {
+            plan_name: "widget fixes".to_string(),
+            base_branch: "main".to_string(),
+            repo: "acme/widgets".to_string(),
+            scope: "medium".to_string(),
+            gate: "auto".to_string(),
+            invariants: vec!["rust_test".to_string()],
+        }
+    }
+
+    #[test]
+    fn task_name_slugifies_title() {
+        assert_eq!(
+            issue_task_name(12, "Fix: login redirect loops!"),
+            "issue-12-fix-login-redirect-loops"
+        );
+        assert_eq!(issue_task_name(3, "???"), "issue-3");
+    }
+
+    #[test]
+    fn task_name_truncates_long_titles() {
+        let name = issue_task_name(1, &"word ".repeat(30));
+        assert!(name.len() <= "issue-1-".len() + MAX_SLUG_LEN);
+        assert!(!name.ends_with('-'));
+    }
+
+    #[test]
+    fn plan_has_one_task_per_issue_sorted_by_number() {
+        let issues = vec![
+            issue(20, "Second", "later"),
+            issue(4, "First", "Body text\nwith lines"),
+        ];
+        let plan = plan_from_issues(&issues, &options());
+
+        assert_eq!(plan.tasks.len(), 2);
+        assert_eq!(plan.tasks[0].name, "issue-4-first");
+        assert_eq!(plan.tasks[0].issue.as_deref(), Some("acme/widgets#4"));
+        assert!(plan.tasks[0].description.contains("Body text\nwith lines"));
+        assert!(
+            plan.tasks[0]
+                .description
+                .contains("https://github.com/acme/widgets/issues/4")
+        );
+        assert_eq!(plan.tasks[1].invariants, vec!["rust_test".to_string()]);
+        assert!(plan.tasks.iter().all(|t| t.depends_on.is_empty()));
+    }
+
+    #[test]
+    fn generated_plan_roundtrips_through_parser() {
+        let issues = vec![issue(7, "Quote \"this\"", "Has 'quotes' and \\ slashes")];
+        let plan = plan_from_issues(&issues, &options());
+        let text = toml::to_string(&plan).unwrap();
+        let parsed = parse_plan_toml(&text).expect("generated plan should validate");
+        assert_eq!(parsed, plan);
+    }
+
+    #[test]
+    fn deserializes_gh_json() {
+        let json = r#"[{"number": 9, "title": "T", "body": "", "url": "https://x/9"}]"#;
+        let issues: Vec<GithubIssue> = serde_json::from_str(json).unwrap();
+        assert_eq!(issues[0].number, 9);
+        assert_eq!(issues[0].title, "T");
+    }
+}
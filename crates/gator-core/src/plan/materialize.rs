@@ -9,11 +9,14 @@ use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use gator_db::models::{DependencyKind, TaskKind};
 use gator_db::queries::{
-    gate_results, invariants as inv_queries, plans as plan_queries, tasks as task_queries,
+    artifact_checks, gate_artifacts, gate_results, invariants as inv_queries,
+    plans as plan_queries, review_feedback, tasks as task_queries,
 };
 
 use super::toml_format::PlanToml;
+use crate::memory::{self, TfIdfRetriever};
 
 /// Materialize a plan from the database back to `plan.toml` content.
 ///
@@ -26,6 +29,8 @@ pub async fn materialize_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<String
         .with_context(|| format!("plan {plan_id} not found"))?;
 
     let tasks = task_queries::list_tasks_for_plan(pool, plan_id).await?;
+    let mut dependencies = task_queries::list_plan_dependency_names(pool, plan_id).await?;
+    let mut invariants = inv_queries::list_invariants_for_plan(pool, plan_id).await?;
 
     // Build a structured representation, then serialize manually so we can
     // include the status field and preserve multiline descriptions properly.
@@ -57,30 +62,58 @@ pub async fn materialize_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<String
             "scope = {}\n",
             toml_quote(&task.scope_level.to_string())
         ));
-        out.push_str(&format!(
-            "gate = {}\n",
-            toml_quote(&task.gate_policy.to_string())
-        ));
+        out.push_str(&format!("gate = {}\n", toml_quote(&task.gate())));
         out.push_str(&format!("retry_max = {}\n", task.retry_max));
         if let Some(ref harness) = task.requested_harness {
             out.push_str(&format!("harness = {}\n", toml_quote(harness)));
         }
+        if let Some(ref issue) = task.issue {
+            out.push_str(&format!("issue = {}\n", toml_quote(issue)));
+        }
+        if !task.services.is_empty() {
+            let strs: Vec<String> = task.services.iter().map(|s| toml_quote(s)).collect();
+            out.push_str(&format!("services = [{}]\n", strs.join(", ")));
+        }
+        if task.kind == TaskKind::Artifact {
+            out.push_str("kind = \"artifact\"\n");
+            let strs: Vec<String> = task.artifacts.iter().map(|s| toml_quote(s)).collect();
+            out.push_str(&format!("artifacts = [{}]\n", strs.join(", ")));
+        }
+        if !task.paths.is_empty() {
+            let strs: Vec<String> = task.paths.iter().map(|s| toml_quote(s)).collect();
+            out.push_str(&format!("paths = [{}]\n", strs.join(", ")));
+        }
+        if let Some(timeout) = task.timeout_secs {
+            out.push_str(&format!("timeout = {timeout}\n"));
+        }
+        if !task.setup_commands.is_empty() {
+            let strs: Vec<String> = task.setup_commands.iter().map(|s| toml_quote(s)).collect();
+            out.push_str(&format!("setup_commands = [{}]\n", strs.join(", ")));
+        }
         out.push_str(&format!(
             "status = {}\n",
             toml_quote(&task.status.to_string())
         ));
 
         // Dependencies.
-        let dep_names = task_queries::get_task_dependency_names(pool, task.id).await?;
-        if !dep_names.is_empty() {
-            let dep_strs: Vec<String> = dep_names.iter().map(|n| toml_quote(n)).collect();
+        let deps = dependencies.remove(&task.id).unwrap_or_default();
+        if !deps.is_empty() {
+            let dep_strs: Vec<String> = deps.iter().map(|(n, _)| toml_quote(n)).collect();
             out.push_str(&format!("depends_on = [{}]\n", dep_strs.join(", ")));
         } else {
             out.push_str("depends_on = []\n");
         }
+        let ordering_only: Vec<String> = deps
+            .iter()
+            .filter(|(_, kind)| *kind == DependencyKind::OrderingOnly)
+            .map(|(n, _)| toml_quote(n))
+            .collect();
+        if !ordering_only.is_empty() {
+            out.push_str(&format!("ordering_only = [{}]\n", ordering_only.join(", ")));
+        }
 
         // Invariants.
-        let invariants = inv_queries::get_invariants_for_task(pool, task.id).await?;
+        let invariants = invariants.remove(&task.id).unwrap_or_default();
         if !invariants.is_empty() {
             let inv_strs: Vec<String> = invariants.iter().map(|i| toml_quote(&i.name)).collect();
             out.push_str(&format!("invariants = [{}]\n", inv_strs.join(", ")));
@@ -98,7 +131,8 @@ pub async fn materialize_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<String
 /// - Task name and description
 /// - Invariant commands (so the agent can run `gator check`)
 /// - Scope and gate policy
-/// - Dependencies and their current statuses
+/// - Dependencies, their current statuses, and which are ordering-only
+/// - The most relevant memories of earlier tasks in the same project
 ///
 /// It does NOT include plan-level context, other tasks' details, or database
 /// identifiers.
@@ -117,7 +151,11 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
 
     // Scope and gate
     out.push_str(&format!("**Scope:** {}  \n", task.scope_level));
-    out.push_str(&format!("**Gate policy:** {}\n\n", task.gate_policy));
+    out.push_str(&format!("**Gate policy:** {}\n\n", task.gate()));
+
+    if let Some(ref issue) = task.issue {
+        out.push_str(&format!("**Issue:** {}\n\n", issue));
+    }
 
     // Description
     out.push_str("## Description\n\n");
@@ -125,20 +163,49 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
     out.push_str("\n\n");
 
     // Dependencies
-    let dep_names = task_queries::get_task_dependency_names(pool, task.id).await?;
-    if !dep_names.is_empty() {
+    let deps = task_queries::get_task_dependency_statuses(pool, task.id).await?;
+    if !deps.is_empty() {
+        let ordering_only = task_queries::get_ordering_only_dependency_names(pool, task.id).await?;
         out.push_str("## Dependencies\n\n");
+        for (dep_name, dep_status) in &deps {
+            out.push_str(&format!("- **{}**: {}", dep_name, dep_status));
+            if ordering_only.contains(dep_name) {
+                out.push_str(" (ordering only; this task does not build on its changes)");
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
 
-        // For each dependency, look up its status.
-        for dep_name in &dep_names {
-            let dep_status = get_dependency_status_by_name(pool, task.plan_id, dep_name).await?;
-            out.push_str(&format!("- **{}**: {}\n", dep_name, dep_status));
+    // Artifacts
+    if task.kind == TaskKind::Artifact {
+        out.push_str("## Artifacts\n\n");
+        out.push_str(
+            "This task produces documents, not code. Create these paths; the gate \
+             checks that they exist and are non-empty, that Markdown is well formed, \
+             and that relative links resolve:\n\n",
+        );
+        for path in task.artifacts.iter() {
+            out.push_str(&format!("- `{}`\n", path));
+        }
+        out.push('\n');
+    }
+
+    // Allowed paths
+    if !task.paths.is_empty() {
+        out.push_str("## Allowed Paths\n\n");
+        out.push_str(
+            "Only files matching these patterns are checked out, and the gate \
+             fails if anything outside them is modified:\n\n",
+        );
+        for path in task.paths.iter() {
+            out.push_str(&format!("- `{}`\n", path));
         }
         out.push('\n');
     }
 
     // Invariants
-    let invariants = inv_queries::get_invariants_for_task(pool, task.id).await?;
+    let invariants = inv_queries::get_enabled_invariants_for_task(pool, task.id).await?;
     if !invariants.is_empty() {
         out.push_str("## Invariants\n\n");
         out.push_str("Run `gator check` to verify all invariants pass.\n\n");
@@ -160,18 +227,68 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
         out.push('\n');
     }
 
+    // Relevant Past Experience (long-term memory)
+    let recalled = memory::recall(pool, &task, &TfIdfRetriever, memory::RECALL_TOP_K).await?;
+    if !recalled.is_empty() {
+        out.push_str(
+            "## Relevant Past Experience
+
+",
+        );
+        out.push_str(
+            "Earlier tasks in this project that look similar. Use what applies; \
+             ignore the rest.\n\n",
+        );
+        for memory in &recalled {
+            let outcome = if memory.kind == "success" {
+                "passed"
+            } else {
+                "failed"
+            };
+            out.push_str(&format!("### {} ({})\n\n", memory.title, outcome));
+            out.push_str(memory.content.trim());
+            out.push_str("\n\n");
+        }
+    }
+
     // Previous Attempt Feedback (retry context)
     if task.attempt > 0 {
         let prev_attempt = task.attempt - 1;
         let prev_results = gate_results::get_gate_results(pool, task.id, prev_attempt).await?;
         let failures: Vec<_> = prev_results.iter().filter(|r| !r.passed).collect();
-
-        if !failures.is_empty() {
+        let artifact_failures: Vec<_> =
+            artifact_checks::get_artifact_checks(pool, task.id, prev_attempt)
+                .await?
+                .into_iter()
+                .filter(|c| !c.passed)
+                .collect();
+        let reviews = review_feedback::get_review_feedback(pool, task.id, prev_attempt).await?;
+        let artifacts = gate_artifacts::list_gate_artifacts(pool, task.id, prev_attempt).await?;
+
+        if !reviews.is_empty() {
             out.push_str("## Previous Attempt Feedback\n\n");
             out.push_str(&format!(
-                "Attempt {} failed. The following invariants did not pass:\n\n",
+                "Attempt {} was rejected by a reviewer. Address this feedback:\n\n",
                 prev_attempt
             ));
+            for review in &reviews {
+                for line in review.reason.lines() {
+                    out.push_str(&format!("> {}\n", line));
+                }
+                out.push('\n');
+            }
+        }
+
+        if !failures.is_empty() || !artifact_failures.is_empty() {
+            if reviews.is_empty() {
+                out.push_str("## Previous Attempt Feedback\n\n");
+                out.push_str(&format!(
+                    "Attempt {} failed. The following checks did not pass:\n\n",
+                    prev_attempt
+                ));
+            } else {
+                out.push_str("The following checks also did not pass:\n\n");
+            }
 
             for failure in &failures {
                 let inv_name = match inv_queries::get_invariant(pool, failure.invariant_id).await? {
@@ -179,10 +296,14 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
                     None => format!("unknown ({})", failure.invariant_id),
                 };
 
-                let exit_code = failure
-                    .exit_code
-                    .map(|c| c.to_string())
-                    .unwrap_or_else(|| "signal".to_string());
+                let exit_code = if failure.timed_out {
+                    "none (timed out)".to_string()
+                } else {
+                    failure
+                        .exit_code
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "signal".to_string())
+                };
 
                 let stderr_snippet = failure.stderr.as_deref().unwrap_or("").to_string();
                 let stderr_truncated = truncate_feedback_snippet(&stderr_snippet, 2048);
@@ -194,6 +315,36 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
                     out.push_str(&stderr_truncated);
                     out.push_str("\n```\n");
                 }
+                let collected: Vec<_> = artifacts
+                    .iter()
+                    .filter(|a| a.gate_result_id == failure.id)
+                    .collect();
+                if !collected.is_empty() {
+                    out.push_str("- **Artifacts:**\n");
+                    for artifact in collected {
+                        out.push_str(&format!(
+                            "  - `{}` (saved as `{}`)\n",
+                            artifact.path, artifact.stored_path
+                        ));
+                    }
+                }
+                out.push('\n');
+            }
+
+            for check in &artifact_failures {
+                if check.check_name == "scope" {
+                    out.push_str("### Files outside the allowed paths\n\n");
+                } else {
+                    out.push_str(&format!(
+                        "### Artifact `{}` ({})\n\n",
+                        check.path, check.check_name
+                    ));
+                }
+                if let Some(ref message) = check.message {
+                    out.push_str("```\n");
+                    out.push_str(&truncate_feedback_snippet(message, 2048));
+                    out.push_str("\n```\n");
+                }
                 out.push('\n');
             }
         }
@@ -202,34 +353,9 @@ pub async fn materialize_task(pool: &SqlitePool, task_id: Uuid) -> Result<String
     Ok(out)
 }
 
-/// Look up a task's status by name within a plan.
-async fn get_dependency_status_by_name(
-    pool: &SqlitePool,
-    plan_id: Uuid,
-    task_name: &str,
-) -> Result<String> {
-    let row: Option<(String,)> =
-        sqlx::query_as("SELECT status FROM tasks WHERE plan_id = $1 AND name = $2")
-            .bind(plan_id)
-            .bind(task_name)
-            .fetch_optional(pool)
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to look up dependency status for task {:?}",
-                    task_name
-                )
-            })?;
-
-    match row {
-        Some((status,)) => Ok(status),
-        None => Ok("unknown".to_string()),
-    }
-}
-
 /// Truncate a string to at most `max_bytes` bytes for feedback snippets,
 /// appending "..." if truncated.
-fn truncate_feedback_snippet(s: &str, max_bytes: usize) -> String {
+pub(crate) fn truncate_feedback_snippet(s: &str, max_bytes: usize) -> String {
     if s.len() <= max_bytes {
         return s.to_owned();
     }
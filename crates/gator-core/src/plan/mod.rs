@@ -1,16 +1,44 @@
-//! Plan management: TOML parsing, service layer, materialization, generation.
+//! Plan management: TOML parsing, service layer, draft editing,
+//! materialization, generation, issue import, issue triage, fan-out
+//! across repositories, conflict prediction between tasks, risk scoring at
+//! approval time, syncing edited plan files into created plans, and the
+//! plan's `[hooks]`.
 
+pub mod edit;
+pub mod fanout;
 pub mod generate;
+pub mod hooks;
+pub mod issues;
 pub mod materialize;
+pub mod overlap;
 pub mod parser;
+pub mod risk;
+pub mod schedule;
 pub mod service;
+pub mod sync;
 pub mod toml_format;
+pub mod triage;
 
+pub use edit::{TaskChanges, add_task, remove_task, update_task};
+pub use fanout::{FanoutRepo, RepoSource, instantiate_template, parse_repo_list};
 pub use generate::{
     GenerateContext, GenerateValidationError, InvariantInfo, build_meta_plan, build_system_prompt,
     detect_context, invariants_from_presets, validate_generated_plan,
 };
+pub use hooks::{Hook, run_hook, run_hook_logged};
+pub use issues::{GithubIssue, IssueImportOptions, plan_from_issues};
 pub use materialize::{materialize_plan, materialize_task};
-pub use parser::{PlanParseError, parse_plan_toml};
-pub use service::{create_plan_from_toml, get_plan_with_tasks};
-pub use toml_format::{PlanMeta, PlanToml, TaskToml};
+pub use overlap::{
+    InferredDependency, OverlapReport, OverlapTask, analyze_overlaps, analyze_plan_overlaps,
+    infer_dependencies,
+};
+pub use parser::{
+    DESCRIPTION_WARN_BYTES, PlanParseError, dependency_problems, description_size_warnings,
+    parse_plan_toml,
+};
+pub use risk::{PlanRisk, RiskFactor, RiskLevel, TaskRisk, assess_plan_risk};
+pub use schedule::{Cron, run_plan_name, start_scheduled_run};
+pub use service::{clone_plan, create_plan_from_toml, get_plan_with_tasks};
+pub use sync::{Change, PlanChange, PlanDiff, diff_plan, sync_plan};
+pub use toml_format::{PlanDefaults, PlanMeta, PlanToml, TaskToml};
+pub use triage::{TriageOptions, TriageOutcome, plan_from_triage_issues, read_triage_outcome};
@@ -0,0 +1,486 @@
+//! File-level conflict prediction between tasks of a plan.
+//!
+//! Each task's footprint is its declared `paths`, or, failing that, the
+//! file paths its description mentions. Two tasks whose footprints overlap
+//! and that can run at the same time (neither depends on the other) are
+//! likely to produce conflicting branches. [`analyze_overlaps`] builds the
+//! full matrix for `gator plan analyze`; the orchestrator uses
+//! [`paths_overlap`] to keep tasks with overlapping declared paths from
+//! running in parallel, and [`infer_dependencies`] turns those overlaps into
+//! explicit `depends_on` edges when a plan is created.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use super::toml_format::TaskToml;
+
+/// The input to the analysis: what a task may touch and what it waits for.
+#[derive(Debug, Clone)]
+pub struct OverlapTask {
+    pub name: String,
+    pub description: String,
+    pub paths: Vec<String>,
+    /// Names of tasks in the same plan this task depends on. Other entries
+    /// (such as cross-plan references) are ignored.
+    pub depends_on: Vec<String>,
+}
+
+impl From<&TaskToml> for OverlapTask {
+    fn from(task: &TaskToml) -> Self {
+        Self {
+            name: task.name.clone(),
+            description: task.description.clone(),
+            paths: task.paths.clone(),
+            depends_on: task.depends_on.clone(),
+        }
+    }
+}
+
+/// Where a task's footprint came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FootprintSource {
+    /// The task's `paths`, enforced by the gate.
+    Declared,
+    /// Paths mentioned in the task description.
+    Hinted,
+    /// Neither: the task could touch anything.
+    Unknown,
+}
+
+/// The files a task is expected to touch.
+#[derive(Debug, Clone)]
+pub struct Footprint {
+    pub task: String,
+    pub source: FootprintSource,
+    pub patterns: Vec<String>,
+}
+
+/// How two tasks relate in the overlap matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overlap {
+    /// The diagonal.
+    SameTask,
+    /// One depends (transitively) on the other, so they never run together.
+    Ordered,
+    /// At least one footprint is unknown.
+    Unknown,
+    /// The footprints share no files.
+    Disjoint,
+    /// The footprints overlap; each pair is one pattern from each task.
+    Overlapping(Vec<(String, String)>),
+}
+
+/// The overlap matrix of a plan: `cells[i][j]` relates `footprints[i]` and
+/// `footprints[j]`.
+#[derive(Debug, Clone)]
+pub struct OverlapReport {
+    pub footprints: Vec<Footprint>,
+    pub cells: Vec<Vec<Overlap>>,
+}
+
+impl OverlapReport {
+    /// Pairs of tasks (by index, `i < j`) that can run in parallel and
+    /// overlap.
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let mut out = Vec::new();
+        for (i, row) in self.cells.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate().skip(i + 1) {
+                if matches!(cell, Overlap::Overlapping(_)) {
+                    out.push((i, j));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Build the overlap matrix for a plan's tasks.
+pub fn analyze_overlaps(tasks: &[OverlapTask]) -> OverlapReport {
+    let footprints: Vec<Footprint> = tasks.iter().map(footprint).collect();
+    let reach = dependency_closure(tasks);
+
+    let mut cells = vec![vec![Overlap::Unknown; tasks.len()]; tasks.len()];
+    for i in 0..tasks.len() {
+        for j in 0..tasks.len() {
+            cells[i][j] = if i == j {
+                Overlap::SameTask
+            } else if reach[i].contains(&j) || reach[j].contains(&i) {
+                Overlap::Ordered
+            } else if footprints[i].source == FootprintSource::Unknown
+                || footprints[j].source == FootprintSource::Unknown
+            {
+                Overlap::Unknown
+            } else {
+                let pairs = paths_overlap(&footprints[i].patterns, &footprints[j].patterns);
+                if pairs.is_empty() {
+                    Overlap::Disjoint
+                } else {
+                    Overlap::Overlapping(pairs)
+                }
+            };
+        }
+    }
+
+    OverlapReport { footprints, cells }
+}
+
+/// A `depends_on` edge added by [`infer_dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredDependency {
+    /// The task that now waits.
+    pub task: String,
+    pub depends_on: String,
+    /// The overlapping patterns, one from each task.
+    pub pairs: Vec<(String, String)>,
+}
+
+/// Add `depends_on` edges between tasks whose declared `paths` overlap and
+/// that could otherwise run in parallel, so plan authors need not maintain
+/// those edges by hand. The later task in plan order waits for the earlier
+/// one, and a pair that is already ordered (directly, transitively, or
+/// through an edge added here) gets no edge. The new edges follow plan
+/// order between tasks that were unordered, so they never form a cycle.
+///
+/// Only declared paths count; description hints are too loose to
+/// serialize a plan on.
+pub fn infer_dependencies(tasks: &mut [TaskToml]) -> Vec<InferredDependency> {
+    let mut overlap_tasks: Vec<OverlapTask> = tasks.iter().map(OverlapTask::from).collect();
+    let mut inferred = Vec::new();
+
+    for j in 0..tasks.len() {
+        // Nearest earlier task first, so a run of overlapping tasks becomes
+        // a chain rather than every task depending on all earlier ones.
+        for i in (0..j).rev() {
+            if tasks[i].paths.is_empty() || tasks[j].paths.is_empty() {
+                continue;
+            }
+            let pairs = paths_overlap(&tasks[i].paths, &tasks[j].paths);
+            if pairs.is_empty() {
+                continue;
+            }
+            let reach = dependency_closure(&overlap_tasks);
+            if reach[i].contains(&j) || reach[j].contains(&i) {
+                continue;
+            }
+            let name = tasks[i].name.clone();
+            tasks[j].depends_on.push(name.clone());
+            overlap_tasks[j].depends_on.push(name.clone());
+            inferred.push(InferredDependency {
+                task: tasks[j].name.clone(),
+                depends_on: name,
+                pairs,
+            });
+        }
+    }
+
+    inferred
+}
+
+/// Build the overlap matrix for a plan in the database.
+pub async fn analyze_plan_overlaps(pool: &SqlitePool, plan_id: Uuid) -> Result<OverlapReport> {
+    plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+
+    let mut dependencies = task_db::list_plan_dependency_names(pool, plan_id).await?;
+    let tasks: Vec<OverlapTask> = task_db::list_tasks_for_plan(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|task| OverlapTask {
+            depends_on: dependencies
+                .remove(&task.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect(),
+            name: task.name,
+            description: task.description,
+            paths: task.paths.0,
+        })
+        .collect();
+    Ok(analyze_overlaps(&tasks))
+}
+
+/// The task's declared paths, else the paths its description mentions.
+fn footprint(task: &OverlapTask) -> Footprint {
+    let (source, patterns) = if !task.paths.is_empty() {
+        (FootprintSource::Declared, task.paths.clone())
+    } else {
+        let hints = description_hints(&task.description);
+        if hints.is_empty() {
+            (FootprintSource::Unknown, Vec::new())
+        } else {
+            (FootprintSource::Hinted, hints)
+        }
+    };
+    Footprint {
+        task: task.name.clone(),
+        source,
+        patterns,
+    }
+}
+
+/// For each task, the indices of every task it depends on, directly or not.
+fn dependency_closure(tasks: &[OverlapTask]) -> Vec<HashSet<usize>> {
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.name.as_str(), i))
+        .collect();
+    tasks
+        .iter()
+        .map(|task| {
+            let mut seen = HashSet::new();
+            let mut stack: Vec<&str> = task.depends_on.iter().map(String::as_str).collect();
+            while let Some(dep) = stack.pop() {
+                if let Some(&i) = index.get(dep) {
+                    if seen.insert(i) {
+                        stack.extend(tasks[i].depends_on.iter().map(String::as_str));
+                    }
+                }
+            }
+            seen
+        })
+        .collect()
+}
+
+/// File extensions that make a bare word (without a `/`) look like a file.
+const FILE_EXTENSIONS: &[&str] = &[
+    "rs", "toml", "md", "py", "ts", "tsx", "js", "jsx", "go", "json", "yaml", "yml", "sql", "sh",
+    "c", "h", "cpp", "java", "rb", "html", "css", "proto",
+];
+
+/// Paths mentioned in a task description, as patterns: words containing a
+/// `/` that end in a file name, a glob, or a trailing `/` (a directory,
+/// returned as `dir/**`), and bare file names with a known source
+/// extension. URLs are skipped.
+pub fn description_hints(description: &str) -> Vec<String> {
+    let mut hints = Vec::new();
+    for word in description.split_whitespace() {
+        let word = word.trim_matches(|c: char| "`'\"()[]{},;:!?<>".contains(c));
+        let word = word.strip_suffix('.').unwrap_or(word);
+        let word = word.strip_prefix("./").unwrap_or(word);
+        if word.is_empty()
+            || word.contains("://")
+            || word.starts_with(['/', '.', '-'])
+            || !word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-./*".contains(c))
+        {
+            continue;
+        }
+
+        let hint = if let Some(dir) = word.strip_suffix('/') {
+            format!("{dir}/**")
+        } else if word.contains('/') {
+            let last = word.rsplit('/').next().unwrap_or_default();
+            if !(last.contains('.') || last.contains('*')) {
+                continue;
+            }
+            word.to_string()
+        } else {
+            match word.rsplit_once('.') {
+                Some((stem, ext)) if !stem.is_empty() && FILE_EXTENSIONS.contains(&ext) => {
+                    word.to_string()
+                }
+                _ => continue,
+            }
+        };
+        if !hints.contains(&hint) {
+            hints.push(hint);
+        }
+    }
+    hints
+}
+
+/// Every pair of patterns, one from each list, that can match a common
+/// path.
+pub fn paths_overlap(a: &[String], b: &[String]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for pa in a {
+        for pb in b {
+            if patterns_overlap(pa, pb) {
+                pairs.push((pa.clone(), pb.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+/// Whether two repo-relative path patterns can match a common path.
+///
+/// Patterns are compared component by component from the root. A pattern
+/// that names a directory covers everything below it, and `**` matches any
+/// number of components. The answer errs towards overlap: two wildcard
+/// components are assumed to match each other.
+pub fn patterns_overlap(a: &str, b: &str) -> bool {
+    let a: Vec<&str> = a.split('/').filter(|c| !c.is_empty()).collect();
+    let b: Vec<&str> = b.split('/').filter(|c| !c.is_empty()).collect();
+    components_overlap(&a, &b)
+}
+
+fn components_overlap(a: &[&str], b: &[&str]) -> bool {
+    match (a.split_first(), b.split_first()) {
+        // One pattern is a directory prefix of the other.
+        (None, _) | (_, None) => true,
+        (Some((&"**", _)), _) | (_, Some((&"**", _))) => true,
+        (Some((ca, rest_a)), Some((cb, rest_b))) => {
+            component_overlap(ca, cb) && components_overlap(rest_a, rest_b)
+        }
+    }
+}
+
+fn component_overlap(a: &str, b: &str) -> bool {
+    match (is_glob(a), is_glob(b)) {
+        (false, false) => a == b,
+        (true, false) => glob_matches(a.as_bytes(), b.as_bytes()),
+        (false, true) => glob_matches(b.as_bytes(), a.as_bytes()),
+        (true, true) => true,
+    }
+}
+
+fn is_glob(component: &str) -> bool {
+    component.contains(['*', '?', '['])
+}
+
+/// Match one path component against a glob with `*` and `?`. A character
+/// class matches any single character.
+pub(crate) fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| glob_matches(rest, &text[skip..])),
+        Some((b'[', rest)) => match rest.iter().position(|&c| c == b']') {
+            Some(end) => !text.is_empty() && glob_matches(&rest[end + 1..], &text[1..]),
+            None => text.first() == Some(&b'[') && glob_matches(rest, &text[1..]),
+        },
+        Some((b'?', rest)) => !text.is_empty() && glob_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, description: &str, paths: &[&str], deps: &[&str]) -> OverlapTask {
+        OverlapTask {
+            name: name.to_string(),
+            description: description.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn pattern_overlap() {
+        assert!(patterns_overlap("crates/foo/**", "crates/foo/src/lib.rs"));
+        assert!(patterns_overlap("crates/foo", "crates/foo/src/lib.rs"));
+        assert!(patterns_overlap("crates/*/Cargo.toml", "crates/foo/**"));
+        assert!(patterns_overlap("docs/*.md", "docs/guide.md"));
+        assert!(patterns_overlap("**/*.rs", "README.md"));
+        assert!(!patterns_overlap("crates/foo/**", "crates/bar/**"));
+        assert!(!patterns_overlap("docs/*.md", "docs/logo.png"));
+        assert!(!patterns_overlap(
+            "crates/foo/src/a.rs",
+            "crates/foo/src/b.rs"
+        ));
+    }
+
+    #[test]
+    fn hints_from_description() {
+        let hints = description_hints(
+            "Update `src/auth/login.rs` and the docs/ folder (see https://x.io/a.md), \
+             then bump Cargo.toml. Use and/or e.g. whatever.",
+        );
+        assert_eq!(hints, vec!["src/auth/login.rs", "docs/**", "Cargo.toml"]);
+    }
+
+    #[test]
+    fn matrix_marks_parallel_overlaps_only() {
+        let report = analyze_overlaps(&[
+            task("a", "", &["crates/foo/**"], &[]),
+            task("b", "Fix crates/foo/src/lib.rs", &[], &[]),
+            task("c", "", &["crates/foo/**"], &["a"]),
+            task("d", "", &["crates/bar/**"], &[]),
+            task("e", "Tidy things up", &[], &[]),
+        ]);
+
+        assert_eq!(report.footprints[1].source, FootprintSource::Hinted);
+        assert_eq!(report.footprints[4].source, FootprintSource::Unknown);
+        assert_eq!(report.cells[0][0], Overlap::SameTask);
+        assert_eq!(report.cells[0][2], Overlap::Ordered);
+        assert_eq!(report.cells[0][3], Overlap::Disjoint);
+        assert_eq!(report.cells[0][4], Overlap::Unknown);
+        assert!(matches!(report.cells[1][0], Overlap::Overlapping(_)));
+
+        assert_eq!(report.conflicts(), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn infers_chained_dependencies_between_overlapping_tasks() {
+        let toml = r#"
+[plan]
+name = "p"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "x"
+scope = "narrow"
+gate = "auto"
+paths = ["crates/foo/**"]
+
+[[tasks]]
+name = "b"
+description = "x"
+scope = "narrow"
+gate = "auto"
+paths = ["crates/foo/src/lib.rs"]
+
+[[tasks]]
+name = "c"
+description = "x"
+scope = "narrow"
+gate = "auto"
+paths = ["crates/foo/src/*.rs", "crates/bar/**"]
+
+[[tasks]]
+name = "d"
+description = "x"
+scope = "narrow"
+gate = "auto"
+paths = ["crates/bar/**"]
+depends_on = ["c"]
+
+[[tasks]]
+name = "e"
+description = "Edit crates/foo/src/lib.rs"
+scope = "narrow"
+gate = "auto"
+"#;
+        let mut plan = crate::plan::parse_plan_toml(toml).unwrap();
+        let inferred = infer_dependencies(&mut plan.tasks);
+
+        let edges: Vec<_> = inferred
+            .iter()
+            .map(|d| (d.task.as_str(), d.depends_on.as_str()))
+            .collect();
+        // c reaches a through b; d already waits for c; e declares no paths.
+        assert_eq!(edges, [("b", "a"), ("c", "b")]);
+        assert_eq!(
+            inferred[0].pairs,
+            [(
+                "crates/foo/**".to_string(),
+                "crates/foo/src/lib.rs".to_string()
+            )]
+        );
+        assert_eq!(plan.tasks[2].depends_on, ["b"]);
+        assert!(infer_dependencies(&mut plan.tasks).is_empty());
+    }
+}
@@ -1,17 +1,30 @@
 //! Plan TOML parser with validation.
 //!
-//! Parses a `plan.toml` string into a [`PlanToml`] and validates:
+//! Parses a `plan.toml` string into a [`PlanToml`], filling fields a task
+//! omits from `[plan.defaults]` (see [`PlanDefaults`]), and validates:
 //! - Scope and gate values are valid enum variants.
 //! - Task names are unique.
-//! - `depends_on` references point to existing task names.
-//! - The dependency graph is acyclic (topological sort).
+//! - `depends_on` references point to existing task names, or name a task
+//!   of another plan as `plan-name:task-name` (resolved when the plan is
+//!   created; see [`cross_plan_dependency`]).
+//! - `ordering_only` entries are also listed in `depends_on`.
+//! - `services` and `artifacts` entries are relative paths inside the
+//!   repository; artifact tasks list at least one artifact.
+//! - `paths` entries are plain patterns inside the repository.
+//! - `timeout` is a positive number of seconds.
+//! - `[hooks]` commands and `setup_commands` are not empty.
+//! - The dependency graph is acyclic.
+//!
+//! [`dependency_problems`] reports every problem with the dependency graph
+//! at once, for `gator plan validate`.
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
-use gator_db::models::{GatePolicy, ScopeLevel};
+use gator_db::models::{GatePolicy, ScopeLevel, TaskKind, split_gate};
 use thiserror::Error;
 
-use super::toml_format::PlanToml;
+use super::hooks::Hook;
+use super::toml_format::{PlanDefaults, PlanToml};
 
 /// Errors that can occur during plan parsing and validation.
 #[derive(Debug, Error)]
@@ -25,35 +38,170 @@ pub enum PlanParseError {
     #[error("task {task:?} depends on unknown task {dependency:?}")]
     UnknownDependency { task: String, dependency: String },
 
+    #[error("task {task:?} lists {dependency:?} in ordering_only but not in depends_on")]
+    OrderingOnlyNotDependency { task: String, dependency: String },
+
     #[error("invalid scope {value:?} on task {task:?} (expected narrow, medium, or broad)")]
     InvalidScope { task: String, value: String },
 
     #[error(
-        "invalid gate {value:?} on task {task:?} (expected auto, human_review, or human_approve)"
+        "invalid gate {value:?} on task {task:?} (expected auto, human_review, human_approve, script:<path>, or llm_review[:<harness>])"
     )]
     InvalidGate { task: String, value: String },
 
-    #[error("dependency cycle detected involving tasks: {0}")]
-    CycleDetected(String),
+    #[error("invalid issue {value:?} on task {task:?} (expected owner/repo#number)")]
+    InvalidIssue { task: String, value: String },
+
+    #[error(
+        "invalid service file {value:?} on task {task:?} (expected a relative path inside the repo)"
+    )]
+    InvalidService { task: String, value: String },
+
+    #[error("invalid kind {value:?} on task {task:?} (expected code or artifact)")]
+    InvalidKind { task: String, value: String },
+
+    #[error(
+        "invalid artifact path {value:?} on task {task:?} (expected a relative path inside the repo)"
+    )]
+    InvalidArtifact { task: String, value: String },
+
+    #[error(
+        "invalid path pattern {value:?} on task {task:?} (expected a pattern inside the repo, e.g. \"crates/foo/**\")"
+    )]
+    InvalidPathPattern { task: String, value: String },
+
+    #[error("invalid timeout {value} on task {task:?} (expected a positive number of seconds)")]
+    InvalidTimeout { task: String, value: i64 },
+
+    #[error("hook {0} has an empty command")]
+    EmptyHook(String),
+
+    #[error("{0} has an empty setup command")]
+    EmptySetupCommand(String),
+
+    #[error(
+        "ephemeral_db cannot be used with isolation = \"container\": the database is published on the host's loopback, which the agent's container cannot reach"
+    )]
+    EphemeralDbInContainer,
+
+    #[error("invalid [plan.defaults]: {0}")]
+    InvalidDefaults(String),
+
+    #[error("artifact task {0:?} must list at least one path in `artifacts`")]
+    MissingArtifacts(String),
+
+    #[error("dependency cycle {cycle}: task {task:?} depending on {dependency:?} closes it")]
+    CycleDetected {
+        /// The cycle as `a -> b -> a`, where `->` reads "depends on".
+        cycle: String,
+        task: String,
+        dependency: String,
+    },
+
+    #[error(
+        "task {task:?} can never run: it depends on {blocker:?} (via {path}), which {}",
+        blocked_reason(.in_cycle)
+    )]
+    UnreachableTask {
+        task: String,
+        /// The chain of dependencies from `task` to `blocker`.
+        path: String,
+        blocker: String,
+        /// Whether `blocker` is on a cycle (otherwise it has a missing
+        /// dependency).
+        in_cycle: bool,
+    },
 
     #[error("plan must contain at least one task")]
     NoTasks,
 }
 
+fn blocked_reason(in_cycle: &bool) -> &'static str {
+    if *in_cycle {
+        "is part of a dependency cycle"
+    } else {
+        "depends on a task that does not exist"
+    }
+}
+
 /// Parse and validate a `plan.toml` string.
 ///
 /// Returns a validated [`PlanToml`] or a descriptive error.
 pub fn parse_plan_toml(content: &str) -> Result<PlanToml, PlanParseError> {
-    let plan: PlanToml = toml::from_str(content)?;
+    let plan = deserialize_plan(content)?;
     validate(&plan)?;
     Ok(plan)
 }
 
+/// Deserialize a `plan.toml` string without validating it, applying
+/// `[plan.defaults]` to every task that omits a defaulted field.
+pub(crate) fn deserialize_plan(content: &str) -> Result<PlanToml, PlanParseError> {
+    let mut doc: toml::Table = content.parse()?;
+    let defaults = doc
+        .get_mut("plan")
+        .and_then(toml::Value::as_table_mut)
+        .and_then(|plan| plan.remove("defaults"));
+    let Some(defaults) = defaults else {
+        // Deserializing the text itself keeps line numbers in errors.
+        return Ok(toml::from_str(content)?);
+    };
+    let defaults: PlanDefaults = defaults
+        .try_into()
+        .map_err(|e: toml::de::Error| PlanParseError::InvalidDefaults(e.message().to_owned()))?;
+
+    if let Some(tasks) = doc.get_mut("tasks").and_then(toml::Value::as_array_mut) {
+        for task in tasks.iter_mut().filter_map(toml::Value::as_table_mut) {
+            apply_defaults(task, &defaults);
+        }
+    }
+    Ok(toml::Value::Table(doc).try_into()?)
+}
+
+/// Fill the fields `task` does not set from `defaults`.
+fn apply_defaults(task: &mut toml::Table, defaults: &PlanDefaults) {
+    let fields: [(&str, Option<toml::Value>); 5] = [
+        ("scope", defaults.scope.clone().map(Into::into)),
+        ("gate", defaults.gate.clone().map(Into::into)),
+        ("retry_max", defaults.retry_max.map(Into::into)),
+        ("harness", defaults.harness.clone().map(Into::into)),
+        ("timeout", defaults.timeout.map(Into::into)),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            task.entry(key).or_insert(value);
+        }
+    }
+}
+
 /// Validate the parsed plan structure.
-fn validate(plan: &PlanToml) -> Result<(), PlanParseError> {
+pub(crate) fn validate(plan: &PlanToml) -> Result<(), PlanParseError> {
     if plan.tasks.is_empty() {
         return Err(PlanParseError::NoTasks);
     }
+    for hook in Hook::ALL {
+        if hook
+            .command(&plan.hooks)
+            .is_some_and(|c| c.trim().is_empty())
+        {
+            return Err(PlanParseError::EmptyHook(hook.to_string()));
+        }
+    }
+    if plan.plan.setup_commands.iter().any(|c| c.trim().is_empty()) {
+        return Err(PlanParseError::EmptySetupCommand("the plan".to_string()));
+    }
+    if plan.plan.ephemeral_db.is_some() && plan.plan.isolation == "container" {
+        return Err(PlanParseError::EphemeralDbInContainer);
+    }
+    if let Some(task) = plan
+        .tasks
+        .iter()
+        .find(|t| t.setup_commands.iter().any(|c| c.trim().is_empty()))
+    {
+        return Err(PlanParseError::EmptySetupCommand(format!(
+            "task {:?}",
+            task.name
+        )));
+    }
 
     // Collect task names and check for duplicates.
     let mut seen = HashSet::new();
@@ -74,87 +222,370 @@ fn validate(plan: &PlanToml) -> Result<(), PlanParseError> {
         }
 
         // Validate gate.
-        if task.gate.parse::<GatePolicy>().is_err() {
+        let gate = split_gate(&task.gate);
+        let valid_gate = match (gate.script, gate.review_harness) {
+            (Some(script), _) => !script.trim().is_empty(),
+            (None, Some(harness)) => !harness.trim().is_empty(),
+            (None, None) => gate.policy.parse::<GatePolicy>().is_ok(),
+        };
+        if !valid_gate {
             return Err(PlanParseError::InvalidGate {
                 task: task.name.clone(),
                 value: task.gate.clone(),
             });
         }
 
+        // Validate issue reference.
+        if let Some(ref issue) = task.issue {
+            if !is_valid_issue_ref(issue) {
+                return Err(PlanParseError::InvalidIssue {
+                    task: task.name.clone(),
+                    value: issue.clone(),
+                });
+            }
+        }
+
+        // Validate kind and artifact paths.
+        let kind = task
+            .kind
+            .parse::<TaskKind>()
+            .map_err(|_| PlanParseError::InvalidKind {
+                task: task.name.clone(),
+                value: task.kind.clone(),
+            })?;
+        if kind == TaskKind::Artifact && task.artifacts.is_empty() {
+            return Err(PlanParseError::MissingArtifacts(task.name.clone()));
+        }
+        for path in &task.artifacts {
+            if !is_repo_relative_path(path) {
+                return Err(PlanParseError::InvalidArtifact {
+                    task: task.name.clone(),
+                    value: path.clone(),
+                });
+            }
+        }
+
+        // Compose files are resolved inside the task's worktree.
+        for file in &task.services {
+            if !is_repo_relative_path(file) {
+                return Err(PlanParseError::InvalidService {
+                    task: task.name.clone(),
+                    value: file.clone(),
+                });
+            }
+        }
+
+        // Path patterns become sparse-checkout patterns and git pathspecs.
+        for pattern in &task.paths {
+            if !is_valid_path_pattern(pattern) {
+                return Err(PlanParseError::InvalidPathPattern {
+                    task: task.name.clone(),
+                    value: pattern.clone(),
+                });
+            }
+        }
+
+        if let Some(timeout) = task.timeout.filter(|&t| t <= 0) {
+            return Err(PlanParseError::InvalidTimeout {
+                task: task.name.clone(),
+                value: timeout,
+            });
+        }
+
         // Check dependency references.
         for dep in &task.depends_on {
-            if !seen.contains(dep) {
+            if !seen.contains(dep) && cross_plan_dependency(dep).is_none() {
                 return Err(PlanParseError::UnknownDependency {
                     task: task.name.clone(),
                     dependency: dep.clone(),
                 });
             }
         }
+        for dep in &task.ordering_only {
+            if !task.depends_on.contains(dep) {
+                return Err(PlanParseError::OrderingOnlyNotDependency {
+                    task: task.name.clone(),
+                    dependency: dep.clone(),
+                });
+            }
+        }
     }
 
-    // Check for cycles using Kahn's algorithm (topological sort).
-    check_for_cycles(plan)?;
+    if let Some(cycle) = DependencyGraph::new(plan).cycles().into_iter().next() {
+        return Err(cycle);
+    }
 
     Ok(())
 }
 
-/// Detect dependency cycles using Kahn's algorithm for topological sort.
+/// Every problem with the plan's dependency graph: duplicate task names,
+/// unknown `depends_on` entries, each cycle (with the edge that closes it),
+/// and tasks that can never run because they depend on a task that is in a
+/// cycle or has a missing dependency.
 ///
-/// Returns `Ok(())` if the graph is a DAG, or `Err` with details of the cycle.
-fn check_for_cycles(plan: &PlanToml) -> Result<(), PlanParseError> {
-    // Build adjacency list and in-degree map.
-    let task_names: Vec<&str> = plan.tasks.iter().map(|t| t.name.as_str()).collect();
-    let name_to_idx: HashMap<&str, usize> = task_names
-        .iter()
-        .enumerate()
-        .map(|(i, name)| (*name, i))
-        .collect();
-
-    let n = task_names.len();
-    let mut in_degree = vec![0usize; n];
-    let mut adj: Vec<Vec<usize>> = vec![vec![]; n];
+/// [`parse_plan_toml`] stops at the first problem; this collects them all.
+pub fn dependency_problems(plan: &PlanToml) -> Vec<PlanParseError> {
+    let mut problems = Vec::new();
 
+    let mut seen = HashSet::new();
+    for task in &plan.tasks {
+        if !seen.insert(&task.name) {
+            problems.push(PlanParseError::DuplicateTaskName(task.name.clone()));
+        }
+    }
     for task in &plan.tasks {
-        let task_idx = name_to_idx[task.name.as_str()];
-        for dep_name in &task.depends_on {
-            let dep_idx = name_to_idx[dep_name.as_str()];
-            // Edge: dep -> task (dep must complete before task).
-            adj[dep_idx].push(task_idx);
-            in_degree[task_idx] += 1;
+        for dep in &task.depends_on {
+            if !seen.contains(dep) && cross_plan_dependency(dep).is_none() {
+                problems.push(PlanParseError::UnknownDependency {
+                    task: task.name.clone(),
+                    dependency: dep.clone(),
+                });
+            }
         }
     }
 
-    // Kahn's algorithm.
-    let mut queue: VecDeque<usize> = VecDeque::new();
-    for (i, deg) in in_degree.iter().enumerate() {
-        if *deg == 0 {
-            queue.push_back(i);
+    let graph = DependencyGraph::new(plan);
+    problems.extend(graph.cycles());
+    problems.extend(graph.unreachable());
+    problems
+}
+
+/// Split a `depends_on` entry of the form `plan-name:task-name` into the
+/// plan and task names. Entries naming a task of the same plan take
+/// precedence, so only call this for names the plan does not have.
+pub fn cross_plan_dependency(dep: &str) -> Option<(&str, &str)> {
+    let (plan, task) = dep.rsplit_once(':')?;
+    (!plan.is_empty() && !task.is_empty()).then_some((plan, task))
+}
+
+/// Service files and artifacts must be non-empty relative paths that stay
+/// inside the repository (no `..` components).
+fn is_repo_relative_path(value: &str) -> bool {
+    let path = std::path::Path::new(value);
+    !value.is_empty()
+        && path.is_relative()
+        && path.components().all(|c| {
+            matches!(
+                c,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        })
+}
+
+/// A `paths` pattern must be a repo-relative path written from the root
+/// (no `.` or `..` components) and must not start with a character that
+/// git reads as negation, a comment, or pathspec magic.
+fn is_valid_path_pattern(value: &str) -> bool {
+    is_repo_relative_path(value)
+        && !value.starts_with(['!', '#', ':'])
+        && std::path::Path::new(value)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+/// Task descriptions above this size (in bytes) draw a warning.
+///
+/// Large descriptions are stored in full, but they are injected into every
+/// agent prompt, including each retry, so they cost tokens on every attempt.
+pub const DESCRIPTION_WARN_BYTES: usize = 16 * 1024;
+
+/// Return a warning for each task whose description exceeds
+/// [`DESCRIPTION_WARN_BYTES`]. Oversized descriptions are not an error.
+pub fn description_size_warnings(plan: &PlanToml) -> Vec<String> {
+    plan.tasks
+        .iter()
+        .filter(|t| t.description.len() > DESCRIPTION_WARN_BYTES)
+        .map(|t| {
+            format!(
+                "task {:?} has a {} KiB description (over {} KiB); it is sent with every agent attempt",
+                t.name,
+                t.description.len().div_ceil(1024),
+                DESCRIPTION_WARN_BYTES / 1024
+            )
+        })
+        .collect()
+}
+
+/// Check that an issue reference has the form `owner/repo#number`.
+pub fn is_valid_issue_ref(value: &str) -> bool {
+    let Some((repo, number)) = value.rsplit_once('#') else {
+        return false;
+    };
+    let Some((owner, name)) = repo.split_once('/') else {
+        return false;
+    };
+    !owner.is_empty()
+        && !name.is_empty()
+        && !name.contains('/')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Task dependencies by index. Duplicate names resolve to the first task
+/// of that name; unknown dependencies are kept aside. Dependencies on other
+/// plans are left out: they cannot close a cycle within this plan.
+struct DependencyGraph<'a> {
+    names: Vec<&'a str>,
+    deps: Vec<Vec<usize>>,
+    has_unknown_dep: Vec<bool>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    fn new(plan: &'a PlanToml) -> Self {
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (i, task) in plan.tasks.iter().enumerate() {
+            index.entry(task.name.as_str()).or_insert(i);
+        }
+        let mut deps = Vec::with_capacity(plan.tasks.len());
+        let mut has_unknown_dep = Vec::with_capacity(plan.tasks.len());
+        for task in &plan.tasks {
+            let known: Vec<usize> = task
+                .depends_on
+                .iter()
+                .filter_map(|d| index.get(d.as_str()).copied())
+                .collect();
+            has_unknown_dep.push(
+                task.depends_on
+                    .iter()
+                    .any(|d| !index.contains_key(d.as_str()) && cross_plan_dependency(d).is_none()),
+            );
+            deps.push(known);
+        }
+        Self {
+            names: plan.tasks.iter().map(|t| t.name.as_str()).collect(),
+            deps,
+            has_unknown_dep,
         }
     }
 
-    let mut sorted_count = 0usize;
-    while let Some(node) = queue.pop_front() {
-        sorted_count += 1;
-        for &neighbor in &adj[node] {
-            in_degree[neighbor] -= 1;
-            if in_degree[neighbor] == 0 {
-                queue.push_back(neighbor);
+    fn path(&self, nodes: &[usize]) -> String {
+        nodes
+            .iter()
+            .map(|&n| self.names[n])
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    /// One error per dependency edge that closes a cycle, found by
+    /// depth-first search in task order.
+    fn cycles(&self) -> Vec<PlanParseError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            OnStack,
+            Done,
+        }
+
+        fn visit(
+            graph: &DependencyGraph<'_>,
+            node: usize,
+            marks: &mut [Mark],
+            stack: &mut Vec<usize>,
+            found: &mut Vec<PlanParseError>,
+        ) {
+            marks[node] = Mark::OnStack;
+            stack.push(node);
+            for &dep in &graph.deps[node] {
+                match marks[dep] {
+                    Mark::New => visit(graph, dep, marks, stack, found),
+                    Mark::OnStack => {
+                        let start = stack.iter().position(|&n| n == dep).unwrap_or(0);
+                        let mut cycle = stack[start..].to_vec();
+                        cycle.push(dep);
+                        found.push(PlanParseError::CycleDetected {
+                            cycle: graph.path(&cycle),
+                            task: graph.names[node].to_string(),
+                            dependency: graph.names[dep].to_string(),
+                        });
+                    }
+                    Mark::Done => {}
+                }
             }
+            stack.pop();
+            marks[node] = Mark::Done;
         }
+
+        let mut marks = vec![Mark::New; self.names.len()];
+        let mut found = Vec::new();
+        for node in 0..self.names.len() {
+            if marks[node] == Mark::New {
+                visit(self, node, &mut marks, &mut Vec::new(), &mut found);
+            }
+        }
+        found
     }
 
-    if sorted_count != n {
-        // Collect the names of tasks that are part of the cycle.
-        let cycle_tasks: Vec<&str> = in_degree
-            .iter()
-            .enumerate()
-            .filter(|(_, deg)| **deg > 0)
-            .map(|(i, _)| task_names[i])
-            .collect();
-        return Err(PlanParseError::CycleDetected(cycle_tasks.join(", ")));
+    /// Tasks that can never become ready although they have no problem of
+    /// their own: somewhere down their dependencies is a cycle or a missing
+    /// task.
+    fn unreachable(&self) -> Vec<PlanParseError> {
+        let n = self.names.len();
+
+        // A task can run once all its dependencies can; iterate to a fixpoint.
+        let mut runnable = vec![false; n];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in 0..n {
+                if !runnable[node]
+                    && !self.has_unknown_dep[node]
+                    && self.deps[node].iter().all(|&d| runnable[d])
+                {
+                    runnable[node] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        // Nodes on a cycle are reported as cycles, not as unreachable.
+        let on_cycle: HashSet<usize> = (0..n).filter(|&node| self.reaches(node, node)).collect();
+
+        let mut found = Vec::new();
+        for node in 0..n {
+            if runnable[node] || self.has_unknown_dep[node] || on_cycle.contains(&node) {
+                continue;
+            }
+            // Follow blocked dependencies until one that is blocked on its
+            // own account.
+            let mut path = vec![node];
+            let mut current = node;
+            loop {
+                let next = self.deps[current]
+                    .iter()
+                    .copied()
+                    .find(|&d| !runnable[d])
+                    .expect("a blocked task has a blocked dependency");
+                path.push(next);
+                if self.has_unknown_dep[next] || on_cycle.contains(&next) {
+                    found.push(PlanParseError::UnreachableTask {
+                        task: self.names[node].to_string(),
+                        path: self.path(&path),
+                        blocker: self.names[next].to_string(),
+                        in_cycle: on_cycle.contains(&next),
+                    });
+                    break;
+                }
+                current = next;
+            }
+        }
+        found
     }
 
-    Ok(())
+    /// Whether `to` can be reached from `from` by following at least one
+    /// dependency edge.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut seen = vec![false; self.names.len()];
+        let mut todo: Vec<usize> = self.deps[from].clone();
+        while let Some(node) = todo.pop() {
+            if node == to {
+                return true;
+            }
+            if !std::mem::replace(&mut seen[node], true) {
+                todo.extend(&self.deps[node]);
+            }
+        }
+        false
+    }
 }
 
 #[cfg(test)]
@@ -265,6 +696,69 @@ depends_on = ["nonexistent"]
         );
     }
 
+    #[test]
+    fn ordering_only_must_be_a_dependency() {
+        let toml_str = r#"
+[plan]
+name = "Ordering"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "b"
+description = "Task B"
+scope = "narrow"
+gate = "auto"
+ordering_only = ["a"]
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::OrderingOnlyNotDependency { .. }),
+            "expected OrderingOnlyNotDependency, got: {err}"
+        );
+
+        let plan = parse_plan_toml(&toml_str.replace(
+            "ordering_only = [\"a\"]",
+            "depends_on = [\"a\"]\nordering_only = [\"a\"]",
+        ))
+        .expect("ordering_only entry is a dependency");
+        assert_eq!(
+            plan.tasks[1].dependency_kind("a"),
+            gator_db::models::DependencyKind::OrderingOnly
+        );
+    }
+
+    #[test]
+    fn accepts_cross_plan_dependency() {
+        let toml_str = r#"
+[plan]
+name = "Downstream"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+scope = "narrow"
+gate = "auto"
+depends_on = ["upstream:api"]
+"#;
+        let plan = parse_plan_toml(toml_str).expect("cross-plan reference is valid");
+        assert!(dependency_problems(&plan).is_empty());
+
+        assert_eq!(
+            cross_plan_dependency("upstream:api"),
+            Some(("upstream", "api"))
+        );
+        assert_eq!(cross_plan_dependency("upstream:"), None);
+        assert_eq!(cross_plan_dependency(":api"), None);
+        assert_eq!(cross_plan_dependency("api"), None);
+    }
+
     #[test]
     fn rejects_invalid_scope() {
         let toml_str = r#"
@@ -305,6 +799,157 @@ gate = "robot"
         );
     }
 
+    #[test]
+    fn accepts_script_gate_but_not_an_empty_path() {
+        let plan = |gate: &str| {
+            format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\n\n[[tasks]]\n\
+                 name = \"a\"\ndescription = \"A\"\nscope = \"narrow\"\ngate = {gate:?}\n"
+            )
+        };
+        assert!(parse_plan_toml(&plan("script:ci/gate.sh")).is_ok());
+        let err = parse_plan_toml(&plan("script:")).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidGate { .. }),
+            "expected InvalidGate, got: {err}"
+        );
+    }
+
+    #[test]
+    fn accepts_llm_review_gate_with_an_optional_harness() {
+        let plan = |gate: &str| {
+            format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\n\n[[tasks]]\n\
+                 name = \"a\"\ndescription = \"A\"\nscope = \"narrow\"\ngate = {gate:?}\n"
+            )
+        };
+        assert!(parse_plan_toml(&plan("llm_review")).is_ok());
+        assert!(parse_plan_toml(&plan("llm_review:codex")).is_ok());
+        for gate in ["llm_review:", "llm_reviewer"] {
+            let err = parse_plan_toml(&plan(gate)).unwrap_err();
+            assert!(
+                matches!(err, PlanParseError::InvalidGate { .. }),
+                "expected InvalidGate for {gate:?}, got: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_issue() {
+        let toml_str = r#"
+[plan]
+name = "Bad issue"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+scope = "narrow"
+gate = "auto"
+issue = "https://example.com/12"
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidIssue { .. }),
+            "expected InvalidIssue, got: {err}"
+        );
+    }
+
+    #[test]
+    fn rejects_service_paths_outside_repo() {
+        for bad in ["../compose.yml", "/etc/compose.yml", ""] {
+            let toml_str = format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\n\n[[tasks]]\nname = \"a\"\n\
+                 description = \"A\"\nscope = \"narrow\"\ngate = \"auto\"\nservices = [{bad:?}]\n"
+            );
+            let err = parse_plan_toml(&toml_str).unwrap_err();
+            assert!(
+                matches!(err, PlanParseError::InvalidService { .. }),
+                "expected InvalidService for {bad:?}, got: {err}"
+            );
+        }
+        assert!(is_repo_relative_path("docker-compose.test.yml"));
+        assert!(is_repo_relative_path("./ci/compose.yml"));
+    }
+
+    #[test]
+    fn rejects_ephemeral_db_in_container_workspaces() {
+        let plan = |isolation: &str| {
+            format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\nisolation = \"{isolation}\"\n\
+                 ephemeral_db = \"postgres:16\"\n\n[[tasks]]\nname = \"a\"\n\
+                 description = \"A\"\nscope = \"narrow\"\ngate = \"auto\"\n"
+            )
+        };
+        assert!(matches!(
+            parse_plan_toml(&plan("container")).unwrap_err(),
+            PlanParseError::EphemeralDbInContainer
+        ));
+        assert!(parse_plan_toml(&plan("worktree")).is_ok());
+    }
+
+    #[test]
+    fn validates_path_patterns() {
+        for bad in [
+            "../x/**",
+            "/crates/**",
+            "./crates/**",
+            "!crates/foo",
+            ":(glob)x",
+            "",
+        ] {
+            let toml_str = format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\n\n[[tasks]]\nname = \"a\"\n\
+                 description = \"A\"\nscope = \"narrow\"\ngate = \"auto\"\npaths = [{bad:?}]\n"
+            );
+            let err = parse_plan_toml(&toml_str).unwrap_err();
+            assert!(
+                matches!(err, PlanParseError::InvalidPathPattern { .. }),
+                "expected InvalidPathPattern for {bad:?}, got: {err}"
+            );
+        }
+        assert!(is_valid_path_pattern("crates/foo/**"));
+        assert!(is_valid_path_pattern("docs/*.md"));
+        assert!(is_valid_path_pattern("Cargo.toml"));
+    }
+
+    #[test]
+    fn validates_artifact_tasks() {
+        let plan = |extra: &str| {
+            format!(
+                "[plan]\nname = \"p\"\nbase_branch = \"main\"\n\n[[tasks]]\nname = \"docs\"\n\
+                 description = \"Write docs\"\nscope = \"narrow\"\ngate = \"auto\"\n{extra}"
+            )
+        };
+        let ok = parse_plan_toml(&plan(
+            "kind = \"artifact\"\nartifacts = [\"docs/guide.md\"]\n",
+        ))
+        .unwrap();
+        assert_eq!(ok.tasks[0].kind, "artifact");
+
+        let err = parse_plan_toml(&plan("kind = \"artifact\"\n")).unwrap_err();
+        assert!(matches!(err, PlanParseError::MissingArtifacts(_)), "{err}");
+        let err = parse_plan_toml(&plan("kind = \"essay\"\n")).unwrap_err();
+        assert!(matches!(err, PlanParseError::InvalidKind { .. }), "{err}");
+        let err =
+            parse_plan_toml(&plan("kind = \"artifact\"\nartifacts = [\"../x.md\"]\n")).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidArtifact { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn issue_ref_format() {
+        assert!(is_valid_issue_ref("acme/widgets#12"));
+        assert!(!is_valid_issue_ref("acme/widgets"));
+        assert!(!is_valid_issue_ref("widgets#12"));
+        assert!(!is_valid_issue_ref("acme/widgets#"));
+        assert!(!is_valid_issue_ref("acme/widgets#12a"));
+        assert!(!is_valid_issue_ref("/widgets#12"));
+        assert!(!is_valid_issue_ref("a/b/c#1"));
+    }
+
     #[test]
     fn rejects_direct_cycle() {
         let toml_str = r#"
@@ -327,10 +972,17 @@ gate = "auto"
 depends_on = ["a"]
 "#;
         let err = parse_plan_toml(toml_str).unwrap_err();
-        assert!(
-            matches!(err, PlanParseError::CycleDetected(_)),
-            "expected CycleDetected, got: {err}"
-        );
+        match err {
+            PlanParseError::CycleDetected {
+                ref cycle,
+                ref task,
+                ref dependency,
+            } => {
+                assert_eq!(cycle, "a -> b -> a");
+                assert_eq!((task.as_str(), dependency.as_str()), ("b", "a"));
+            }
+            _ => panic!("expected CycleDetected, got: {err}"),
+        }
     }
 
     #[test]
@@ -363,7 +1015,7 @@ depends_on = ["b"]
 "#;
         let err = parse_plan_toml(toml_str).unwrap_err();
         assert!(
-            matches!(err, PlanParseError::CycleDetected(_)),
+            matches!(err, PlanParseError::CycleDetected { .. }),
             "expected CycleDetected, got: {err}"
         );
     }
@@ -407,6 +1059,73 @@ depends_on = ["b", "c"]
         assert_eq!(plan.tasks.len(), 4);
     }
 
+    #[test]
+    fn parses_hooks() {
+        let plan = |hooks: &str| {
+            format!(
+                r#"
+[plan]
+name = "p"
+base_branch = "main"
+
+[hooks]
+{hooks}
+
+[[tasks]]
+name = "a"
+description = "A"
+scope = "narrow"
+gate = "auto"
+"#
+            )
+        };
+        let parsed = parse_plan_toml(&plan(
+            "pre_dispatch = \"./scripts/setup.sh\"\npost_merge = \"make report\"",
+        ))
+        .unwrap();
+        assert_eq!(
+            parsed.hooks.pre_dispatch.as_deref(),
+            Some("./scripts/setup.sh")
+        );
+        assert_eq!(parsed.hooks.post_merge.as_deref(), Some("make report"));
+        assert_eq!(parsed.hooks.post_task, None);
+
+        let err = parse_plan_toml(&plan("post_task = \" \"")).unwrap_err();
+        assert!(matches!(err, PlanParseError::EmptyHook(_)), "{err}");
+        let err = parse_plan_toml(&plan("pre_merge = \"x\"")).unwrap_err();
+        assert!(matches!(err, PlanParseError::TomlError(_)), "{err}");
+    }
+
+    #[test]
+    fn parses_setup_commands() {
+        let plan = |task_setup: &str| {
+            format!(
+                r#"
+[plan]
+name = "p"
+base_branch = "main"
+setup_commands = ["npm ci"]
+
+[[tasks]]
+name = "a"
+description = "A"
+scope = "narrow"
+gate = "auto"
+setup_commands = [{task_setup}]
+"#
+            )
+        };
+        let parsed = parse_plan_toml(&plan("\"cargo fetch\"")).unwrap();
+        assert_eq!(parsed.plan.setup_commands, vec!["npm ci"]);
+        assert_eq!(parsed.tasks[0].setup_commands, vec!["cargo fetch"]);
+
+        let err = parse_plan_toml(&plan("\"\"")).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::EmptySetupCommand(ref what) if what == "task \"a\""),
+            "{err}"
+        );
+    }
+
     #[test]
     fn rejects_malformed_toml() {
         let err = parse_plan_toml("this is not valid toml {{{").unwrap_err();
@@ -468,6 +1187,34 @@ gate = "{gate}"
             .to_path_buf()
     }
 
+    #[test]
+    fn warns_on_oversized_descriptions() {
+        let big = "x".repeat(DESCRIPTION_WARN_BYTES + 1);
+        let toml_str = format!(
+            r#"
+[plan]
+name = "Big"
+base_branch = "main"
+
+[[tasks]]
+name = "small"
+description = "short"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "large"
+description = "{big}"
+scope = "narrow"
+gate = "auto"
+"#
+        );
+        let plan = parse_plan_toml(&toml_str).expect("large descriptions are valid");
+        let warnings = description_size_warnings(&plan);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"large\""), "got: {}", warnings[0]);
+    }
+
     #[test]
     fn validate_example_minimal_toml() {
         let path = workspace_root().join("docs/examples/minimal.toml");
@@ -486,4 +1233,191 @@ gate = "{gate}"
             .unwrap_or_else(|e| panic!("docs/examples/rust-project.toml failed validation: {e}"));
         assert_eq!(plan.tasks.len(), 4, "rust-project.toml should have 4 tasks");
     }
+
+    #[test]
+    fn dependency_problems_lists_cycles_missing_and_blocked_tasks() {
+        let toml_str = r#"
+[plan]
+name = "Tangled"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+scope = "narrow"
+gate = "auto"
+depends_on = ["a"]
+
+[[tasks]]
+name = "b"
+description = "Task B"
+scope = "narrow"
+gate = "auto"
+depends_on = ["missing"]
+
+[[tasks]]
+name = "c"
+description = "Waits on the self-cycle"
+scope = "narrow"
+gate = "auto"
+depends_on = ["a"]
+
+[[tasks]]
+name = "d"
+description = "Waits on c"
+scope = "narrow"
+gate = "auto"
+depends_on = ["c", "e"]
+
+[[tasks]]
+name = "e"
+description = "Fine on its own"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "e"
+description = "Duplicate"
+scope = "narrow"
+gate = "auto"
+"#;
+        let plan: PlanToml = toml::from_str(toml_str).unwrap();
+        let problems: Vec<String> = dependency_problems(&plan)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(problems.len(), 5, "{problems:#?}");
+        assert_eq!(problems[0], "duplicate task name: \"e\"");
+        assert_eq!(
+            problems[1],
+            "task \"b\" depends on unknown task \"missing\""
+        );
+        assert!(problems[2].starts_with("dependency cycle a -> a:"));
+        assert!(problems[3].contains("task \"c\" can never run"));
+        assert!(problems[4].contains("(via d -> c -> a), which is part of a dependency cycle"));
+    }
+
+    #[test]
+    fn plan_defaults_fill_omitted_task_fields() {
+        let toml_str = r#"
+[plan]
+name = "Defaults"
+base_branch = "main"
+
+[plan.defaults]
+scope = "narrow"
+gate = "human_review"
+retry_max = 1
+harness = "codex-cli"
+timeout = 900
+
+[[tasks]]
+name = "a"
+description = "Uses every default"
+
+[[tasks]]
+name = "b"
+description = "Overrides some"
+scope = "broad"
+retry_max = 5
+timeout = 60
+"#;
+        let plan = parse_plan_toml(toml_str).expect("should parse");
+        let a = &plan.tasks[0];
+        assert_eq!(a.scope, "narrow");
+        assert_eq!(a.gate, "human_review");
+        assert_eq!(a.retry_max, 1);
+        assert_eq!(a.harness.as_deref(), Some("codex-cli"));
+        assert_eq!(a.timeout, Some(900));
+
+        let b = &plan.tasks[1];
+        assert_eq!(b.scope, "broad");
+        assert_eq!(b.gate, "human_review");
+        assert_eq!(b.retry_max, 5);
+        assert_eq!(b.timeout, Some(60));
+    }
+
+    #[test]
+    fn task_without_scope_needs_a_default() {
+        let toml_str = r#"
+[plan]
+name = "No defaults"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Missing scope"
+gate = "auto"
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(err.to_string().contains("scope"), "got: {err}");
+    }
+
+    #[test]
+    fn rejects_unknown_plan_defaults() {
+        let toml_str = r#"
+[plan]
+name = "Bad defaults"
+base_branch = "main"
+
+[plan.defaults]
+scope = "narrow"
+gate = "auto"
+paths = ["src/**"]
+
+[[tasks]]
+name = "a"
+description = "Task A"
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidDefaults(_)),
+            "expected InvalidDefaults, got: {err}"
+        );
+        assert!(err.to_string().contains("paths"), "got: {err}");
+    }
+
+    #[test]
+    fn invalid_default_is_reported_on_the_task() {
+        let toml_str = r#"
+[plan]
+name = "Bad gate"
+base_branch = "main"
+
+[plan.defaults]
+scope = "narrow"
+gate = "sometimes"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidGate { ref task, .. } if task == "a"),
+            "expected InvalidGate on a, got: {err}"
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_timeout() {
+        let toml_str = r#"
+[plan]
+name = "Zero timeout"
+base_branch = "main"
+
+[[tasks]]
+name = "a"
+description = "Task A"
+scope = "narrow"
+gate = "auto"
+timeout = 0
+"#;
+        let err = parse_plan_toml(toml_str).unwrap_err();
+        assert!(
+            matches!(err, PlanParseError::InvalidTimeout { value: 0, .. }),
+            "expected InvalidTimeout, got: {err}"
+        );
+    }
 }
@@ -0,0 +1,409 @@
+//! Risk scoring of a plan, shown when it is approved.
+//!
+//! Each task collects points for the things that make an unattended run
+//! go wrong: a broad scope, an `auto` gate on a task that touches sensitive
+//! paths, no `test_suite` invariant, and a history of failures among similar
+//! tasks of the same project. The plan's score is the sum over its tasks.
+//! Risky tasks that are still gated `auto` are suggested for
+//! `human_review`.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{GatePolicy, InvariantKind, ScopeLevel, TaskStatus};
+use gator_db::queries::invariants as inv_db;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+
+use super::overlap::{description_hints, glob_matches};
+use crate::memory::{Retriever, TfIdfRetriever};
+
+/// Path components where an unreviewed change does outsized damage: CI,
+/// build and dependency manifests, migrations, deployment, and
+/// credentials. A task touches them when any component of its footprint
+/// matches one of these (`*` and `?` allowed).
+pub const SENSITIVE_PATHS: &[&str] = &[
+    ".github",
+    "Cargo.toml",
+    "Cargo.lock",
+    "package.json",
+    "migrations",
+    "*.sql",
+    "Dockerfile",
+    "deploy",
+    "infra",
+    ".env*",
+    "*secret*",
+    "auth",
+];
+
+/// Points for a task with `scope = "broad"`.
+const BROAD_SCOPE_POINTS: u32 = 2;
+/// Points for an `auto`-gated task touching [`SENSITIVE_PATHS`].
+const SENSITIVE_AUTO_POINTS: u32 = 3;
+/// Points for a task without a `test_suite` invariant.
+const NO_TEST_POINTS: u32 = 1;
+/// Points for a task whose similar predecessors mostly failed.
+const FAILURE_HISTORY_POINTS: u32 = 2;
+
+/// Finished tasks scoring at least this similar count as "similar".
+const SIMILARITY_THRESHOLD: f64 = 0.2;
+/// At most this many similar tasks are considered per task.
+const SIMILAR_TOP_K: usize = 10;
+/// Fewer similar tasks than this say nothing about the failure rate.
+const MIN_SIMILAR: usize = 2;
+/// Failure rates at or above this count against a task.
+const FAILURE_RATE_THRESHOLD: f64 = 0.5;
+
+/// A task at or above this score should not be gated `auto`.
+pub const REVIEW_SUGGESTED_SCORE: u32 = 3;
+
+/// What a task is scored on.
+#[derive(Debug, Clone)]
+pub struct RiskTask {
+    pub name: String,
+    pub description: String,
+    pub scope: ScopeLevel,
+    pub gate: GatePolicy,
+    pub paths: Vec<String>,
+    pub invariant_kinds: Vec<InvariantKind>,
+}
+
+/// A finished task of an earlier plan, for the failure history.
+#[derive(Debug, Clone)]
+pub struct PastTask {
+    pub name: String,
+    pub description: String,
+    pub failed: bool,
+}
+
+/// One reason a task scored points.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskFactor {
+    /// The task has `scope = "broad"`.
+    BroadScope,
+    /// The task is gated `auto` and its footprint matches these entries of
+    /// [`SENSITIVE_PATHS`].
+    SensitiveAutoGate(Vec<String>),
+    /// No `test_suite` invariant is linked to the task.
+    NoTestInvariant,
+    /// `failed` of `similar` finished tasks like this one did not pass.
+    FailureHistory { similar: usize, failed: usize },
+}
+
+impl RiskFactor {
+    /// The points this factor adds to a task's score.
+    pub fn points(&self) -> u32 {
+        match self {
+            Self::BroadScope => BROAD_SCOPE_POINTS,
+            Self::SensitiveAutoGate(_) => SENSITIVE_AUTO_POINTS,
+            Self::NoTestInvariant => NO_TEST_POINTS,
+            Self::FailureHistory { .. } => FAILURE_HISTORY_POINTS,
+        }
+    }
+
+    /// A one-line explanation for operators.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::BroadScope => "broad scope".to_string(),
+            Self::SensitiveAutoGate(patterns) => {
+                format!("auto gate on sensitive paths ({})", patterns.join(", "))
+            }
+            Self::NoTestInvariant => "no test_suite invariant".to_string(),
+            Self::FailureHistory { similar, failed } => {
+                format!("{failed} of {similar} similar past tasks failed")
+            }
+        }
+    }
+}
+
+/// The risk of one task.
+#[derive(Debug, Clone)]
+pub struct TaskRisk {
+    pub task: String,
+    pub gate: GatePolicy,
+    pub factors: Vec<RiskFactor>,
+}
+
+impl TaskRisk {
+    pub fn score(&self) -> u32 {
+        self.factors.iter().map(RiskFactor::points).sum()
+    }
+
+    /// Whether the task is gated `auto` but risky enough for a human to
+    /// look at its result.
+    pub fn suggests_review(&self) -> bool {
+        self.gate == GatePolicy::Auto && self.score() >= REVIEW_SUGGESTED_SCORE
+    }
+}
+
+/// Coarse rating of a plan's score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        })
+    }
+}
+
+/// The risk of a plan: every task, in plan order.
+#[derive(Debug, Clone)]
+pub struct PlanRisk {
+    pub tasks: Vec<TaskRisk>,
+}
+
+impl PlanRisk {
+    pub fn score(&self) -> u32 {
+        self.tasks.iter().map(TaskRisk::score).sum()
+    }
+
+    /// `high` when any task needs review, `medium` when any task scored,
+    /// else `low`.
+    pub fn level(&self) -> RiskLevel {
+        if self.tasks.iter().any(TaskRisk::suggests_review) {
+            RiskLevel::High
+        } else if self.score() > 0 {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        }
+    }
+}
+
+/// Score tasks against the finished tasks of earlier plans.
+pub fn assess_risk(tasks: &[RiskTask], history: &[PastTask]) -> PlanRisk {
+    let documents: Vec<String> = history
+        .iter()
+        .map(|t| format!("{} {}", t.name, t.description))
+        .collect();
+    let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+
+    let tasks = tasks
+        .iter()
+        .map(|task| {
+            let mut factors = Vec::new();
+            if task.scope == ScopeLevel::Broad {
+                factors.push(RiskFactor::BroadScope);
+            }
+            if task.gate == GatePolicy::Auto {
+                let sensitive = sensitive_patterns(task);
+                if !sensitive.is_empty() {
+                    factors.push(RiskFactor::SensitiveAutoGate(sensitive));
+                }
+            }
+            if !task.invariant_kinds.contains(&InvariantKind::TestSuite) {
+                factors.push(RiskFactor::NoTestInvariant);
+            }
+
+            let query = format!("{} {}", task.name, task.description);
+            let similar: Vec<&PastTask> = TfIdfRetriever
+                .rank(&query, &documents, SIMILAR_TOP_K)
+                .into_iter()
+                .filter(|&(_, score)| score >= SIMILARITY_THRESHOLD)
+                .map(|(i, _)| &history[i])
+                .collect();
+            let failed = similar.iter().filter(|t| t.failed).count();
+            if similar.len() >= MIN_SIMILAR
+                && failed as f64 / similar.len() as f64 >= FAILURE_RATE_THRESHOLD
+            {
+                factors.push(RiskFactor::FailureHistory {
+                    similar: similar.len(),
+                    failed,
+                });
+            }
+
+            TaskRisk {
+                task: task.name.clone(),
+                gate: task.gate,
+                factors,
+            }
+        })
+        .collect();
+
+    PlanRisk { tasks }
+}
+
+/// Score a plan in the database against the finished tasks of the other
+/// plans of its project.
+pub async fn assess_plan_risk(pool: &SqlitePool, plan_id: Uuid) -> Result<PlanRisk> {
+    let plan = plan_db::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+
+    let mut invariants = inv_db::list_invariants_for_plan(pool, plan_id).await?;
+    let tasks: Vec<RiskTask> = task_db::list_tasks_for_plan(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|task| RiskTask {
+            invariant_kinds: invariants
+                .remove(&task.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|inv| inv.kind)
+                .collect(),
+            name: task.name,
+            description: task.description,
+            scope: task.scope_level,
+            gate: task.gate_policy,
+            paths: task.paths.0,
+        })
+        .collect();
+
+    let history: Vec<PastTask> =
+        task_db::list_finished_tasks_for_project(pool, &plan.project_path, plan_id)
+            .await?
+            .into_iter()
+            .map(|t| PastTask {
+                failed: t.status != TaskStatus::Passed,
+                name: t.name,
+                description: t.description,
+            })
+            .collect();
+
+    Ok(assess_risk(&tasks, &history))
+}
+
+/// The entries of [`SENSITIVE_PATHS`] that a component of the task's
+/// declared paths, or failing that the paths its description mentions,
+/// matches. A wildcard component only matches an identical entry.
+fn sensitive_patterns(task: &RiskTask) -> Vec<String> {
+    let footprint = if task.paths.is_empty() {
+        description_hints(&task.description)
+    } else {
+        task.paths.clone()
+    };
+    let mut hits = Vec::new();
+    for component in footprint.iter().flat_map(|p| p.split('/')) {
+        for &sensitive in SENSITIVE_PATHS {
+            let matched = if component.contains(['*', '?', '[']) {
+                component == sensitive
+            } else {
+                glob_matches(sensitive.as_bytes(), component.as_bytes())
+            };
+            if matched && !hits.iter().any(|h| h == sensitive) {
+                hits.push(sensitive.to_string());
+            }
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, scope: ScopeLevel, gate: GatePolicy, paths: &[&str]) -> RiskTask {
+        RiskTask {
+            name: name.to_string(),
+            description: format!("Implement {name}"),
+            scope,
+            gate,
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            invariant_kinds: vec![InvariantKind::TestSuite],
+        }
+    }
+
+    fn past(name: &str, description: &str, failed: bool) -> PastTask {
+        PastTask {
+            name: name.to_string(),
+            description: description.to_string(),
+            failed,
+        }
+    }
+
+    #[test]
+    fn low_risk_plan_scores_zero() {
+        let risk = assess_risk(
+            &[task(
+                "parser",
+                ScopeLevel::Narrow,
+                GatePolicy::Auto,
+                &["src/parser.rs"],
+            )],
+            &[],
+        );
+        assert_eq!(risk.score(), 0);
+        assert_eq!(risk.level(), RiskLevel::Low);
+    }
+
+    #[test]
+    fn auto_gate_on_sensitive_paths_suggests_review() {
+        let mut migration = task(
+            "schema",
+            ScopeLevel::Narrow,
+            GatePolicy::Auto,
+            &["crates/db/migrations/**"],
+        );
+        migration.invariant_kinds = vec![InvariantKind::Lint];
+        let reviewed = task(
+            "ci",
+            ScopeLevel::Broad,
+            GatePolicy::HumanReview,
+            &[".github/workflows/ci.yml"],
+        );
+
+        let risk = assess_risk(&[migration, reviewed], &[]);
+
+        assert_eq!(
+            risk.tasks[0].factors,
+            [
+                RiskFactor::SensitiveAutoGate(vec!["migrations".to_string()]),
+                RiskFactor::NoTestInvariant,
+            ]
+        );
+        assert!(risk.tasks[0].suggests_review());
+        // Already reviewed by a human: broad scope only.
+        assert_eq!(risk.tasks[1].factors, [RiskFactor::BroadScope]);
+        assert!(!risk.tasks[1].suggests_review());
+        assert_eq!(risk.level(), RiskLevel::High);
+    }
+
+    #[test]
+    fn sensitive_paths_from_description_hints() {
+        let mut bump = task("bump", ScopeLevel::Narrow, GatePolicy::Auto, &[]);
+        bump.description = "Bump serde in Cargo.toml".to_string();
+        let risk = assess_risk(&[bump], &[]);
+        assert_eq!(
+            risk.tasks[0].factors,
+            [RiskFactor::SensitiveAutoGate(vec![
+                "Cargo.toml".to_string()
+            ])]
+        );
+    }
+
+    #[test]
+    fn failure_history_of_similar_tasks() {
+        let history = [
+            past("oauth-login", "Implement oauth login flow", true),
+            past("oauth-refresh", "Implement oauth token refresh", true),
+            past("oauth-logout", "Implement oauth logout", false),
+            past("readme", "Document the release process", false),
+        ];
+        let mut oauth = task("oauth-scopes", ScopeLevel::Narrow, GatePolicy::Auto, &[]);
+        oauth.description = "Implement oauth scopes".to_string();
+        let mut docs = task("changelog", ScopeLevel::Narrow, GatePolicy::Auto, &[]);
+        docs.description = "Write the changelog".to_string();
+
+        let risk = assess_risk(&[oauth, docs], &history);
+
+        assert_eq!(
+            risk.tasks[0].factors,
+            [RiskFactor::FailureHistory {
+                similar: 3,
+                failed: 2
+            }]
+        );
+        assert!(risk.tasks[1].factors.is_empty());
+        assert_eq!(risk.level(), RiskLevel::Medium);
+    }
+}
@@ -0,0 +1,336 @@
+//! Scheduled dispatch: cloning a plan on a cron schedule.
+//!
+//! A schedule treats a plan as a template. Each time the schedule comes
+//! due, the plan is cloned with fresh attempt counters, the clone is
+//! approved, and `gator schedule run` dispatches it, e.g. a nightly
+//! dependency-bump plan. Schedules use five-field cron expressions
+//! (`minute hour day-of-month month day-of-week`), evaluated in UTC.
+//!
+//! A schedule that came due while nothing was running it runs once, not
+//! once per missed time, and is then next due at its first time after now.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+use sqlx::SqlitePool;
+
+use gator_db::models::{Plan, Schedule};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::schedules;
+
+use super::clone_plan;
+
+/// How far ahead [`Cron::next_after`] looks. `0 0 29 2 1` (Feb 29 or a
+/// Monday in February) always matches within a few years; an expression
+/// like `0 0 31 2 *` never does.
+const SEARCH_DAYS: u32 = 366 * 8;
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed five-field cron expression.
+///
+/// Fields take `*`, numbers, ranges (`1-5`), lists (`1,15`), and steps
+/// (`*/15`, `0-30/10`). Months and weekdays also take three-letter names;
+/// weekday 0 and 7 are both Sunday. As in Vixie cron, when both the
+/// day-of-month and day-of-week are restricted, a day matching either one
+/// matches. `@hourly`, `@daily`, `@weekly`, `@monthly`, and `@yearly` are
+/// shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day-of-month / day-of-week field starts with `*`.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// The first time after `after` the expression matches, to the minute.
+    /// `None` if it matches no time in the next few years.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(chrono::Duration::minutes(1))?;
+        let mut date = start.date_naive();
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_day(date) {
+                let (first_hour, first_minute) = if date == start.date_naive() {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for hour in (first_hour..24).filter(|h| bit(self.hours, *h)) {
+                    let from = if hour == first_hour { first_minute } else { 0 };
+                    if let Some(minute) = (from..60).find(|m| bit(self.minutes, *m)) {
+                        return Some(date.and_hms_opt(hour, minute, 0)?.and_utc());
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !bit(self.months, date.month()) {
+            return false;
+        }
+        let day = bit(self.days, date.day());
+        let weekday = bit(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+}
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let expr = s.trim();
+        let expanded = match expr {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "invalid cron expression {expr:?}: expected 5 fields \
+                 (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            );
+        };
+        let field = |value: &str, name: &str, min: u32, max: u32, names: &[&str]| {
+            parse_field(value, min, max, names).with_context(|| {
+                format!("invalid {name} field {value:?} in cron expression {expr:?}")
+            })
+        };
+        let mut weekdays = field(weekday, "day-of-week", 0, 7, WEEKDAY_NAMES)?;
+        if bit(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expr: expr.to_string(),
+            minutes: field(minute, "minute", 0, 59, &[])?,
+            hours: field(hour, "hour", 0, 23, &[])?,
+            days: field(day, "day-of-month", 1, 31, &[])?,
+            months: field(month, "month", 1, 12, MONTH_NAMES)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+fn bit(set: u64, n: u32) -> bool {
+    set & (1 << n) != 0
+}
+
+/// The values a cron field allows, as a bit set. `names` spell the values
+/// from `min` up, e.g. `jan` for month 1.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let n = match names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            Some(i) => i as u32 + min,
+            None => s
+                .parse()
+                .with_context(|| format!("{s:?} is not a number"))?,
+        };
+        if !(min..=max).contains(&n) {
+            bail!("{n} is out of range {min}-{max}");
+        }
+        Ok(n)
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .with_context(|| format!("{step:?} is not a number"))?;
+                if step == 0 {
+                    bail!("step must be at least 1");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (value(lo)?, value(hi)?),
+                // `5/15` means from 5 to the end, every 15.
+                None if part.contains('/') => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if lo > hi {
+            bail!("range {lo}-{hi} is backwards");
+        }
+        for n in (lo..=hi).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+/// Name of the plan a schedule's run at `at` creates: the template's name
+/// and the time, so each run gets its own branches.
+pub fn run_plan_name(plan_name: &str, at: DateTime<Utc>) -> String {
+    format!("{plan_name}-{}", at.format("%Y%m%d-%H%M"))
+}
+
+/// Start a due schedule's run: clone its plan, approve the clone, and move
+/// the schedule on to its next time after `now`. The run is recorded on the
+/// schedule whether or not it could be started.
+pub async fn start_scheduled_run(
+    pool: &SqlitePool,
+    schedule: &Schedule,
+    now: DateTime<Utc>,
+) -> Result<Plan> {
+    let cron: Cron = schedule.cron.parse()?;
+    // A schedule that can never run again is left due; `gator schedule
+    // list` shows the error.
+    let next_run_at = cron.next_after(now).unwrap_or(schedule.next_run_at);
+
+    let started = async {
+        let template = plan_db::get_plan(pool, schedule.plan_id)
+            .await?
+            .with_context(|| format!("plan {} not found", schedule.plan_id))?;
+        if template.deleted_at.is_some() {
+            bail!("plan {} has been deleted", template.id);
+        }
+        let clone = clone_plan(
+            pool,
+            template.id,
+            Some(&run_plan_name(&template.name, now)),
+            true,
+        )
+        .await?;
+        plan_db::approve_plan(pool, clone.id).await
+    }
+    .await;
+
+    match &started {
+        Ok(plan) => {
+            schedules::record_schedule_run(pool, schedule.id, next_run_at, Some(plan.id), None)
+                .await?
+        }
+        Err(e) => {
+            schedules::record_schedule_run(
+                pool,
+                schedule.id,
+                next_run_at,
+                None,
+                Some(&format!("{e:#}")),
+            )
+            .await?
+        }
+    }
+    started
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> DateTime<Utc> {
+        expr.parse::<Cron>().unwrap().next_after(at(after)).unwrap()
+    }
+
+    #[test]
+    fn next_after_finds_the_following_match() {
+        assert_eq!(
+            next("0 2 * * *", "2026-03-01T01:59:30Z"),
+            at("2026-03-01T02:00:00Z")
+        );
+        assert_eq!(
+            next("0 2 * * *", "2026-03-01T02:00:00Z"),
+            at("2026-03-02T02:00:00Z")
+        );
+        assert_eq!(
+            next("*/15 9-17 * * mon-fri", "2026-03-06T17:50:00Z"),
+            at("2026-03-09T09:00:00Z")
+        );
+        assert_eq!(
+            next("30 4 1 jan,jul *", "2026-03-01T00:00:00Z"),
+            at("2026-07-01T04:30:00Z")
+        );
+        assert_eq!(
+            next("@weekly", "2026-03-04T12:00:00Z"),
+            at("2026-03-08T00:00:00Z")
+        );
+        // Sunday is 0 or 7.
+        assert_eq!(
+            next("0 0 * * 7", "2026-03-04T12:00:00Z"),
+            at("2026-03-08T00:00:00Z")
+        );
+        // Day-of-month and day-of-week both restricted: either matches.
+        assert_eq!(
+            next("0 0 13 * fri", "2026-03-01T00:00:00Z"),
+            at("2026-03-06T00:00:00Z")
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01T00:00:00Z"),
+            at("2028-02-29T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn impossible_dates_never_match() {
+        let cron: Cron = "0 0 31 2 *".parse().unwrap();
+        assert_eq!(cron.next_after(at("2026-01-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expr in [
+            "0 2 * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+            "@sometimes",
+        ] {
+            assert!(expr.parse::<Cron>().is_err(), "{expr} should not parse");
+        }
+        assert_eq!(
+            next("@daily", "2026-03-01T12:00:00Z"),
+            next("0 0 * * *", "2026-03-01T12:00:00Z")
+        );
+    }
+}
@@ -7,18 +7,21 @@
 use std::collections::HashMap;
 
 use anyhow::{Context, Result, bail};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
-use gator_db::models::{Plan, Task};
+use gator_db::models::{DependencyKind, Plan, Task, split_gate};
+use gator_db::queries::tasks::NewTask;
 use gator_db::queries::{plans as plan_queries, tasks as task_queries};
 
+use super::parser::cross_plan_dependency;
 use super::toml_format::PlanToml;
 
 /// Create a plan and all its tasks from a parsed and validated [`PlanToml`].
 ///
 /// Inserts the plan row, all task rows, dependency edges, and invariant links
-/// inside a single database transaction. If any step fails, the entire
+/// inside a single database transaction, using multi-row inserts so large
+/// generated plans are created quickly. If any step fails, the entire
 /// operation is rolled back.
 ///
 /// `project_path` is the filesystem path of the project this plan belongs to.
@@ -36,8 +39,8 @@ pub async fn create_plan_from_toml(
     // 1. Insert the plan row.
     let plan_id = Uuid::new_v4();
     let plan = sqlx::query_as::<_, Plan>(
-        "INSERT INTO plans (id, name, project_path, base_branch, token_budget, default_harness, isolation, container_image) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+        "INSERT INTO plans (id, name, project_path, base_branch, token_budget, default_harness, isolation, container_image, ephemeral_db, template_setup, template_hardlinks, warmup_command, hooks, setup_commands, caches) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) \
          RETURNING *",
     )
     .bind(plan_id)
@@ -48,87 +51,81 @@ pub async fn create_plan_from_toml(
     .bind(&plan_toml.plan.default_harness)
     .bind(&plan_toml.plan.isolation)
     .bind(&plan_toml.plan.container_image)
+    .bind(&plan_toml.plan.ephemeral_db)
+    .bind(&plan_toml.plan.template_setup)
+    .bind(plan_toml.plan.template_hardlinks)
+    .bind(&plan_toml.plan.warmup_command)
+    .bind(sqlx::types::Json(&plan_toml.hooks))
+    .bind(sqlx::types::Json(&plan_toml.plan.setup_commands))
+    .bind(sqlx::types::Json(&plan_toml.plan.caches))
     .fetch_one(&mut *tx)
     .await
     .context("failed to insert plan")?;
 
-    // 2. Insert all tasks and build a name -> UUID map.
-    let mut task_name_to_id: HashMap<String, Uuid> = HashMap::new();
-
-    for task_toml in &plan_toml.tasks {
-        let task_id = Uuid::new_v4();
-        let task = sqlx::query_as::<_, Task>(
-            "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy, retry_max, requested_harness) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
-             RETURNING *",
-        )
-        .bind(task_id)
-        .bind(plan.id)
-        .bind(&task_toml.name)
-        .bind(&task_toml.description)
-        .bind(&task_toml.scope)
-        .bind(&task_toml.gate)
-        .bind(task_toml.retry_max)
-        .bind(&task_toml.harness)
-        .fetch_one(&mut *tx)
-        .await
-        .with_context(|| format!("failed to insert task {:?}", task_toml.name))?;
-
-        task_name_to_id.insert(task_toml.name.clone(), task.id);
-    }
+    // 2. Insert all tasks. Ids are assigned up front so the edges and links
+    //    below can refer to them.
+    let task_name_to_id: HashMap<String, Uuid> = plan_toml
+        .tasks
+        .iter()
+        .map(|t| (t.name.clone(), Uuid::new_v4()))
+        .collect();
+    let new_tasks: Vec<NewTask<'_>> = plan_toml
+        .tasks
+        .iter()
+        .map(|task_toml| {
+            let gate = split_gate(&task_toml.gate);
+            NewTask {
+                id: task_name_to_id[&task_toml.name],
+                plan_id: plan.id,
+                name: &task_toml.name,
+                description: &task_toml.description,
+                scope_level: &task_toml.scope,
+                gate_policy: gate.policy,
+                retry_max: task_toml.retry_max,
+                requested_harness: task_toml.harness.as_deref(),
+                issue: task_toml.issue.as_deref(),
+                services: &task_toml.services,
+                kind: &task_toml.kind,
+                artifacts: &task_toml.artifacts,
+                paths: &task_toml.paths,
+                timeout_secs: task_toml.timeout,
+                gate_script: gate.script,
+                llm_review: gate.llm_review,
+                review_harness: gate.review_harness,
+                setup_commands: &task_toml.setup_commands,
+            }
+        })
+        .collect();
+    task_queries::insert_tasks(&mut tx, &new_tasks).await?;
 
     // 3. Insert dependency edges.
+    let mut edges = Vec::new();
     for task_toml in &plan_toml.tasks {
         let task_id = task_name_to_id[&task_toml.name];
         for dep_name in &task_toml.depends_on {
-            let dep_id = task_name_to_id[dep_name];
-            sqlx::query(
-                "INSERT INTO task_dependencies (task_id, depends_on) VALUES ($1, $2) \
-                 ON CONFLICT DO NOTHING",
-            )
-            .bind(task_id)
-            .bind(dep_id)
-            .execute(&mut *tx)
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to insert dependency: {:?} -> {:?}",
-                    task_toml.name, dep_name
-                )
-            })?;
+            let dep_id =
+                resolve_dependency(&mut tx, plan.id, task_id, &task_name_to_id, dep_name).await?;
+            edges.push((task_id, dep_id, task_toml.dependency_kind(dep_name)));
         }
     }
+    task_queries::insert_task_dependencies(&mut tx, &edges).await?;
 
     // 4. Link invariants by name (look up each name in the invariants table).
+    let invariant_ids: HashMap<String, Uuid> =
+        sqlx::query_as::<_, (String, Uuid)>("SELECT name, id FROM invariants")
+            .fetch_all(&mut *tx)
+            .await
+            .context("failed to look up invariants")?
+            .into_iter()
+            .collect();
+    let mut links = Vec::new();
     let mut missing: Vec<String> = Vec::new();
 
     for task_toml in &plan_toml.tasks {
         let task_id = task_name_to_id[&task_toml.name];
         for inv_name in &task_toml.invariants {
-            let inv_row: Option<(Uuid,)> =
-                sqlx::query_as("SELECT id FROM invariants WHERE name = $1")
-                    .bind(inv_name)
-                    .fetch_optional(&mut *tx)
-                    .await
-                    .with_context(|| format!("failed to look up invariant {:?}", inv_name))?;
-
-            match inv_row {
-                Some((inv_id,)) => {
-                    sqlx::query(
-                        "INSERT INTO task_invariants (task_id, invariant_id) VALUES ($1, $2) \
-                         ON CONFLICT DO NOTHING",
-                    )
-                    .bind(task_id)
-                    .bind(inv_id)
-                    .execute(&mut *tx)
-                    .await
-                    .with_context(|| {
-                        format!(
-                            "failed to link task {:?} to invariant {:?}",
-                            task_toml.name, inv_name
-                        )
-                    })?;
-                }
+            match invariant_ids.get(inv_name) {
+                Some(&inv_id) => links.push((task_id, inv_id)),
                 None => {
                     missing.push(format!(
                         "invariant {:?} referenced by task {:?} does not exist in the database",
@@ -146,6 +143,183 @@ pub async fn create_plan_from_toml(
             missing.join("\n  ")
         );
     }
+    task_queries::link_task_invariants(&mut tx, &links).await?;
+
+    tx.commit().await.context("failed to commit transaction")?;
+
+    Ok(plan)
+}
+
+/// Resolve a `depends_on` entry of task `task_id` in plan `plan_id` to a
+/// task id: a task of the same plan (from `local`), or, for
+/// `plan-name:task-name`, a task of another plan. The other plan must exist
+/// (and not be deleted) under a unique name, and must not itself depend on
+/// `task_id`.
+pub(crate) async fn resolve_dependency(
+    conn: &mut SqliteConnection,
+    plan_id: Uuid,
+    task_id: Uuid,
+    local: &HashMap<String, Uuid>,
+    dep: &str,
+) -> Result<Uuid> {
+    if let Some(&id) = local.get(dep) {
+        return Ok(id);
+    }
+    let Some((plan_name, task_name)) = cross_plan_dependency(dep) else {
+        bail!("unknown dependency {dep:?}");
+    };
+
+    let plans: Vec<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM plans WHERE name = $1 AND id != $2 AND deleted_at IS NULL")
+            .bind(plan_name)
+            .bind(plan_id)
+            .fetch_all(&mut *conn)
+            .await
+            .with_context(|| format!("failed to look up plan {plan_name:?}"))?;
+    let other_plan = match plans.as_slice() {
+        [] => bail!("dependency {dep:?}: no plan named {plan_name:?}"),
+        [(id,)] => *id,
+        _ => bail!(
+            "dependency {dep:?}: {} plans are named {plan_name:?}; rename one to disambiguate",
+            plans.len()
+        ),
+    };
+
+    let (dep_id,): (Uuid,) =
+        sqlx::query_as("SELECT id FROM tasks WHERE plan_id = $1 AND name = $2")
+            .bind(other_plan)
+            .bind(task_name)
+            .fetch_optional(&mut *conn)
+            .await
+            .with_context(|| format!("failed to look up task {dep:?}"))?
+            .with_context(|| {
+                format!("dependency {dep:?}: plan {plan_name:?} has no task {task_name:?}")
+            })?;
+
+    // Within a plan the parser rules out cycles; across plans, check that
+    // the dependency does not (transitively) wait on this task.
+    let (closes_cycle,): (bool,) = sqlx::query_as(
+        "WITH RECURSIVE upstream(id) AS ( \
+             SELECT $1 \
+             UNION SELECT td.depends_on FROM task_dependencies td \
+             JOIN upstream u ON td.task_id = u.id \
+         ) \
+         SELECT EXISTS (SELECT 1 FROM upstream WHERE id = $2)",
+    )
+    .bind(dep_id)
+    .bind(task_id)
+    .fetch_one(&mut *conn)
+    .await
+    .context("failed to check for a cross-plan dependency cycle")?;
+    if closes_cycle {
+        bail!("dependency {dep:?} would create a cycle across plans");
+    }
+
+    Ok(dep_id)
+}
+
+/// Duplicate a plan as a new draft: the plan row, its tasks, dependency
+/// edges, and invariant links, all in one transaction.
+///
+/// Cloned tasks start out `pending`. They keep their attempt counters (and
+/// so their remaining retry budget) unless `reset_attempts` is set. `name`
+/// overrides the clone's name; by default it keeps the original's.
+pub async fn clone_plan(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    name: Option<&str>,
+    reset_attempts: bool,
+) -> Result<Plan> {
+    let (source, tasks) = get_plan_with_tasks(pool, plan_id).await?;
+
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let plan = sqlx::query_as::<_, Plan>(
+        "INSERT INTO plans (id, name, project_path, base_branch, token_budget, default_harness, isolation, container_image, ephemeral_db, template_setup, template_hardlinks, warmup_command, hooks, setup_commands, caches) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(name.unwrap_or(&source.name))
+    .bind(&source.project_path)
+    .bind(&source.base_branch)
+    .bind(source.token_budget)
+    .bind(&source.default_harness)
+    .bind(&source.isolation)
+    .bind(&source.container_image)
+    .bind(&source.ephemeral_db)
+    .bind(&source.template_setup)
+    .bind(source.template_hardlinks)
+    .bind(&source.warmup_command)
+    .bind(&source.hooks)
+    .bind(&source.setup_commands)
+    .bind(&source.caches)
+    .fetch_one(&mut *tx)
+    .await
+    .context("failed to insert cloned plan")?;
+
+    let mut new_ids: HashMap<Uuid, Uuid> = HashMap::new();
+    for task in &tasks {
+        let task_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy, retry_max, requested_harness, issue, services, kind, artifacts, paths, timeout_secs, attempt, gate_script, llm_review, review_harness, setup_commands) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)",
+        )
+        .bind(task_id)
+        .bind(plan.id)
+        .bind(&task.name)
+        .bind(&task.description)
+        .bind(task.scope_level)
+        .bind(task.gate_policy)
+        .bind(task.retry_max)
+        .bind(&task.requested_harness)
+        .bind(&task.issue)
+        .bind(&task.services)
+        .bind(task.kind)
+        .bind(&task.artifacts)
+        .bind(&task.paths)
+        .bind(task.timeout_secs)
+        .bind(if reset_attempts { 0 } else { task.attempt })
+        .bind(&task.gate_script)
+        .bind(task.llm_review)
+        .bind(&task.review_harness)
+        .bind(&task.setup_commands)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("failed to clone task {:?}", task.name))?;
+        new_ids.insert(task.id, task_id);
+    }
+
+    for task in &tasks {
+        let deps: Vec<(Uuid, DependencyKind)> =
+            sqlx::query_as("SELECT depends_on, kind FROM task_dependencies WHERE task_id = $1")
+                .bind(task.id)
+                .fetch_all(&mut *tx)
+                .await
+                .with_context(|| format!("failed to read dependencies of {:?}", task.name))?;
+        for (dep, kind) in deps {
+            // Dependencies on other plans' tasks are kept as they are.
+            sqlx::query(
+                "INSERT INTO task_dependencies (task_id, depends_on, kind) VALUES ($1, $2, $3)",
+            )
+            .bind(new_ids[&task.id])
+            .bind(new_ids.get(&dep).copied().unwrap_or(dep))
+            .bind(kind)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to clone dependencies of {:?}", task.name))?;
+        }
+
+        sqlx::query(
+            "INSERT INTO task_invariants (task_id, invariant_id) \
+             SELECT $1, invariant_id FROM task_invariants WHERE task_id = $2",
+        )
+        .bind(new_ids[&task.id])
+        .bind(task.id)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("failed to clone invariant links of {:?}", task.name))?;
+    }
 
     tx.commit().await.context("failed to commit transaction")?;
 
@@ -0,0 +1,406 @@
+//! Comparing a plan file with the plan stored in the database, and
+//! applying the safe part of the difference.
+//!
+//! Once `gator plan create` has written the plan's id into the file, the
+//! file and the database are separate copies. [`diff_plan`] lists where they
+//! differ; [`sync_plan`] applies the changes that cannot disturb work already
+//! done -- new description text for tasks that have not started, and new
+//! tasks, which start out pending -- and reports the rest.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{Plan, PlanStatus, Task, TaskStatus};
+use gator_db::queries::{invariants as inv_queries, plans as plan_queries, tasks as task_queries};
+
+use super::edit::write_task;
+use super::materialize::{materialize_plan, parse_materialized};
+use super::toml_format::{PlanToml, TaskToml};
+
+/// One difference between a plan file and the stored plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A `[plan]` setting differs.
+    PlanField {
+        field: &'static str,
+        stored: String,
+        file: String,
+    },
+    /// The file has a task the plan does not.
+    TaskAdded { name: String },
+    /// The plan has a task the file no longer lists.
+    TaskRemoved { name: String },
+    /// A task's description differs.
+    DescriptionChanged { name: String },
+    /// Other fields of a task differ.
+    TaskChanged {
+        name: String,
+        fields: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PlanField {
+                field,
+                stored,
+                file,
+            } => write!(f, "~ plan {field}: {stored} -> {file}"),
+            Self::TaskAdded { name } => write!(f, "+ task {name}"),
+            Self::TaskRemoved { name } => write!(f, "- task {name}"),
+            Self::DescriptionChanged { name } => write!(f, "~ task {name}: description"),
+            Self::TaskChanged { name, fields } => {
+                write!(f, "~ task {name}: {}", fields.join(", "))
+            }
+        }
+    }
+}
+
+/// A [`Change`] and whether `gator plan sync` applies it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanChange {
+    pub change: Change,
+    /// Why sync leaves the change alone; `None` when it is applied.
+    pub blocked: Option<String>,
+}
+
+/// Every difference between a plan file and the stored plan, in file order.
+#[derive(Debug, Clone, Default)]
+pub struct PlanDiff {
+    pub changes: Vec<PlanChange>,
+}
+
+impl PlanDiff {
+    /// The changes `gator plan sync` applies.
+    pub fn safe(&self) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(|c| c.blocked.is_none())
+            .map(|c| &c.change)
+    }
+}
+
+/// Compare the parsed plan file `file` with the stored plan `plan_id`.
+pub async fn diff_plan(pool: &SqlitePool, plan_id: Uuid, file: &PlanToml) -> Result<PlanDiff> {
+    let plan = plan_queries::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+    let stored = parse_materialized(&materialize_plan(pool, plan_id).await?)?;
+    let rows: HashMap<String, Task> = task_queries::list_tasks_for_plan(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|t| (t.name.clone(), t))
+        .collect();
+
+    let mut changes = Vec::new();
+    let blocked = |reason: &str| Some(reason.to_string());
+
+    for (field, stored, file) in plan_field_changes(&plan, file) {
+        changes.push(PlanChange {
+            change: Change::PlanField {
+                field,
+                stored,
+                file,
+            },
+            blocked: blocked("plan settings are not synced"),
+        });
+    }
+
+    let mut unaddable = BTreeSet::new();
+    for task in &file.tasks {
+        let Some(current) = stored.tasks.iter().find(|t| t.name == task.name) else {
+            let mut reason = None;
+            for inv in &task.invariants {
+                if inv_queries::get_invariant_by_name(pool, inv)
+                    .await?
+                    .is_none()
+                {
+                    reason = Some(format!("invariant {inv:?} does not exist"));
+                    break;
+                }
+            }
+            if reason.is_some() {
+                unaddable.insert(task.name.clone());
+            }
+            changes.push(PlanChange {
+                change: Change::TaskAdded {
+                    name: task.name.clone(),
+                },
+                blocked: reason,
+            });
+            continue;
+        };
+
+        let status = rows[&task.name].status;
+        if task.description != current.description {
+            changes.push(PlanChange {
+                change: Change::DescriptionChanged {
+                    name: task.name.clone(),
+                },
+                blocked: (status != TaskStatus::Pending).then(|| format!("task is {status}")),
+            });
+        }
+        let fields = task_field_changes(current, task);
+        if !fields.is_empty() {
+            changes.push(PlanChange {
+                change: Change::TaskChanged {
+                    name: task.name.clone(),
+                    fields,
+                },
+                blocked: blocked("only descriptions and new tasks are synced"),
+            });
+        }
+    }
+
+    // A new task can only be added along with the new tasks it depends on.
+    loop {
+        let before = unaddable.len();
+        for change in &mut changes {
+            if change.blocked.is_some() {
+                continue;
+            }
+            if let Change::TaskAdded { name } = &change.change {
+                let task = file
+                    .tasks
+                    .iter()
+                    .find(|t| &t.name == name)
+                    .expect("file task");
+                if let Some(dep) = task.depends_on.iter().find(|d| unaddable.contains(*d)) {
+                    change.blocked = Some(format!("depends on {dep:?}, which is not added"));
+                    unaddable.insert(name.clone());
+                }
+            }
+        }
+        if unaddable.len() == before {
+            break;
+        }
+    }
+
+    for task in &stored.tasks {
+        if !file.tasks.iter().any(|t| t.name == task.name) {
+            changes.push(PlanChange {
+                change: Change::TaskRemoved {
+                    name: task.name.clone(),
+                },
+                blocked: blocked("tasks are not removed by sync"),
+            });
+        }
+    }
+
+    Ok(PlanDiff { changes })
+}
+
+/// Apply the safe changes between `file` and the stored plan `plan_id` in
+/// one transaction, and return the full diff. Refuses while the plan is
+/// running, and once it has completed.
+pub async fn sync_plan(pool: &SqlitePool, plan_id: Uuid, file: &PlanToml) -> Result<PlanDiff> {
+    let plan = plan_queries::get_plan(pool, plan_id)
+        .await?
+        .with_context(|| format!("plan {plan_id} not found"))?;
+    match plan.status {
+        PlanStatus::Running => bail!(
+            "plan {:?} is running; sync it once the dispatch has stopped",
+            plan.name
+        ),
+        PlanStatus::Completed => bail!(
+            "plan {:?} is completed; clone it to run more tasks",
+            plan.name
+        ),
+        PlanStatus::Draft | PlanStatus::Approved | PlanStatus::Failed => {}
+    }
+
+    let diff = diff_plan(pool, plan_id, file).await?;
+    let mut task_ids: HashMap<String, Uuid> = task_queries::list_tasks_for_plan(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|t| (t.name, t.id))
+        .collect();
+
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+    let mut added: Vec<&TaskToml> = Vec::new();
+    for change in diff.safe() {
+        match change {
+            Change::DescriptionChanged { name } => {
+                let task = file
+                    .tasks
+                    .iter()
+                    .find(|t| &t.name == name)
+                    .expect("file task");
+                sqlx::query("UPDATE tasks SET description = $1 WHERE id = $2")
+                    .bind(&task.description)
+                    .bind(task_ids[name])
+                    .execute(&mut *tx)
+                    .await
+                    .with_context(|| format!("failed to update task {name:?}"))?;
+            }
+            Change::TaskAdded { name } => {
+                added.push(
+                    file.tasks
+                        .iter()
+                        .find(|t| &t.name == name)
+                        .expect("file task"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    // Insert new tasks after the new tasks they depend on. The file was
+    // validated, so its dependencies are acyclic.
+    while !added.is_empty() {
+        let ready = added
+            .iter()
+            .position(|t| {
+                t.depends_on
+                    .iter()
+                    .all(|d| task_ids.contains_key(d) || !added.iter().any(|a| &a.name == d))
+            })
+            .expect("plan file dependencies are acyclic");
+        let task = added.remove(ready);
+        let row = write_task(&mut tx, &plan, None, task, &task_ids).await?;
+        task_ids.insert(row.name, row.id);
+    }
+
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(diff)
+}
+
+/// `[plan]` settings that differ, as (field, stored, file).
+fn plan_field_changes(plan: &Plan, file: &PlanToml) -> Vec<(&'static str, String, String)> {
+    let opt = |v: Option<String>| v.unwrap_or_else(|| "none".to_string());
+    let meta = &file.plan;
+    [
+        ("name", plan.name.clone(), meta.name.clone()),
+        (
+            "base_branch",
+            plan.base_branch.clone(),
+            meta.base_branch.clone(),
+        ),
+        (
+            "token_budget",
+            opt(plan.token_budget.map(|b| b.to_string())),
+            opt(meta.token_budget.map(|b| b.to_string())),
+        ),
+        (
+            "default_harness",
+            plan.default_harness.clone(),
+            meta.default_harness.clone(),
+        ),
+        ("isolation", plan.isolation.clone(), meta.isolation.clone()),
+        (
+            "container_image",
+            opt(plan.container_image.clone()),
+            opt(meta.container_image.clone()),
+        ),
+        (
+            "ephemeral_db",
+            opt(plan.ephemeral_db.clone()),
+            opt(meta.ephemeral_db.clone()),
+        ),
+        (
+            "template_setup",
+            opt(plan.template_setup.clone()),
+            opt(meta.template_setup.clone()),
+        ),
+        (
+            "template_hardlinks",
+            plan.template_hardlinks.to_string(),
+            meta.template_hardlinks.to_string(),
+        ),
+        (
+            "warmup_command",
+            opt(plan.warmup_command.clone()),
+            opt(meta.warmup_command.clone()),
+        ),
+        (
+            "hooks",
+            serde_json::to_string(&plan.hooks.0).unwrap_or_default(),
+            serde_json::to_string(&file.hooks).unwrap_or_default(),
+        ),
+        (
+            "setup_commands",
+            serde_json::to_string(&plan.setup_commands.0).unwrap_or_default(),
+            serde_json::to_string(&meta.setup_commands).unwrap_or_default(),
+        ),
+        (
+            "caches",
+            serde_json::to_string(&plan.caches.0).unwrap_or_default(),
+            serde_json::to_string(&meta.caches).unwrap_or_default(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, stored, file)| stored != file)
+    .collect()
+}
+
+/// Names of the task fields other than the description that differ.
+/// List fields are compared as sets.
+fn task_field_changes(stored: &TaskToml, file: &TaskToml) -> Vec<&'static str> {
+    let set = |v: &[String]| v.iter().cloned().collect::<BTreeSet<_>>();
+    [
+        ("scope", stored.scope == file.scope),
+        ("gate", stored.gate == file.gate),
+        ("retry_max", stored.retry_max == file.retry_max),
+        (
+            "depends_on",
+            set(&stored.depends_on) == set(&file.depends_on),
+        ),
+        (
+            "ordering_only",
+            set(&stored.ordering_only) == set(&file.ordering_only),
+        ),
+        (
+            "invariants",
+            set(&stored.invariants) == set(&file.invariants),
+        ),
+        ("harness", stored.harness == file.harness),
+        ("issue", stored.issue == file.issue),
+        ("services", stored.services == file.services),
+        ("kind", stored.kind == file.kind),
+        ("artifacts", stored.artifacts == file.artifacts),
+        ("paths", stored.paths == file.paths),
+        ("timeout", stored.timeout == file.timeout),
+        (
+            "setup_commands",
+            stored.setup_commands == file.setup_commands,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, same)| !same)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str) -> TaskToml {
+        toml::from_str(&format!(
+            "name = {name:?}\ndescription = \"d\"\nscope = \"narrow\"\ngate = \"auto\""
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn task_field_changes_compare_lists_as_sets() {
+        let mut stored = task("a");
+        stored.depends_on = vec!["x".into(), "y".into()];
+        let mut file = stored.clone();
+        file.depends_on = vec!["y".into(), "x".into()];
+        file.description = "changed".into();
+        assert!(task_field_changes(&stored, &file).is_empty());
+
+        file.gate = "human_review".into();
+        file.invariants = vec!["lint".into()];
+        assert_eq!(
+            task_field_changes(&stored, &file),
+            vec!["gate", "invariants"]
+        );
+    }
+}
@@ -3,6 +3,7 @@
 //! These types map directly to the `plan.toml` on-disk format and are
 //! deserialized via `serde` + the `toml` crate.
 
+use gator_db::models::{CacheKind, DependencyKind, PlanHooks};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,6 +12,9 @@ use uuid::Uuid;
 pub struct PlanToml {
     /// Plan metadata.
     pub plan: PlanMeta,
+    /// Shell commands run at points of a dispatch or merge (`[hooks]`).
+    #[serde(default, skip_serializing_if = "PlanHooks::is_empty")]
+    pub hooks: PlanHooks,
     /// Tasks within the plan.
     #[serde(default)]
     pub tasks: Vec<TaskToml>,
@@ -40,6 +44,34 @@ pub struct PlanMeta {
     /// Only used when `isolation = "container"`. Falls back to "ubuntu:24.04".
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub container_image: Option<String>,
+    /// Postgres image (e.g. "postgres:16-alpine") for a throwaway database
+    /// per task attempt, exposed to the agent and gate as `DATABASE_URL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_db: Option<String>,
+    /// Setup command (e.g. "cargo build") run once in a template checkout
+    /// that task worktrees are cloned from copy-on-write. Worktree isolation
+    /// only; task worktrees are checked out normally when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_setup: Option<String>,
+    /// When a task worktree cannot be cloned from the template (the
+    /// filesystem has no reflinks, or a retry reuses the branch), hard-link
+    /// the template's untracked and ignored files into its checkout.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub template_hardlinks: bool,
+    /// Warm-up command (e.g. "cargo build" or "npm ci") run in each task's
+    /// workspace before its agent starts. Its output and duration are
+    /// recorded per attempt, like a gate result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_command: Option<String>,
+    /// Commands (e.g. "npm ci") run in each task's workspace before its
+    /// agent starts, ahead of the task's own. Unlike the warm-up, a failing
+    /// one fails the attempt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup_commands: Vec<String>,
+    /// Dependency caches (`cargo`, `npm`, `pnpm`, `pip`, `go`) shared by
+    /// every task workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub caches: Vec<CacheKind>,
 }
 
 /// A single `[[tasks]]` entry in the plan TOML.
@@ -59,12 +91,74 @@ pub struct TaskToml {
     /// Names of tasks this task depends on (must complete first).
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// Entries of `depends_on` that only order this task after another,
+    /// without needing its changes. The rest are `needs_output` edges.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ordering_only: Vec<String>,
     /// Names of invariants to link to this task.
     #[serde(default)]
     pub invariants: Vec<String>,
     /// Override harness for this task (uses plan default_harness if not set).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub harness: Option<String>,
+    /// Source issue reference, e.g. `"owner/repo#12"`. The plan's PR closes it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Compose files (relative to the repo root) whose services are brought
+    /// up before the agent and torn down after the gate.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    /// Task kind: "code" (default) or "artifact" for docs and research
+    /// tasks gated on the files they produce.
+    #[serde(default = "default_task_kind", skip_serializing_if = "is_code_kind")]
+    pub kind: String,
+    /// Paths (relative to the repo root) an artifact task must produce.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
+    /// Paths the task may modify, as gitignore-style patterns relative to
+    /// the repo root (e.g. `"crates/foo/**"`). The worktree is a sparse
+    /// checkout of them and the gate fails on changes outside them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Wall-clock limit in seconds for each attempt, overriding the
+    /// dispatch `--timeout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<i64>,
+    /// Setup commands run after the plan's, before the agent starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup_commands: Vec<String>,
+}
+
+impl TaskToml {
+    /// The kind of the edge to `dependency`, an entry of `depends_on`.
+    pub fn dependency_kind(&self, dependency: &str) -> DependencyKind {
+        if self.ordering_only.iter().any(|d| d == dependency) {
+            DependencyKind::OrderingOnly
+        } else {
+            DependencyKind::NeedsOutput
+        }
+    }
+}
+
+/// Task field defaults in `[plan.defaults]`.
+///
+/// The parser copies each set field into every task that omits it before
+/// deserializing the tasks, so a field set on the task always wins. The
+/// defaults are not kept on [`PlanToml`]: once applied, every task carries
+/// its own values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_max: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub harness: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<i64>,
 }
 
 fn default_retry_max() -> i32 {
@@ -75,6 +169,14 @@ fn default_harness_name() -> String {
     "claude-code".to_string()
 }
 
+fn default_task_kind() -> String {
+    "code".to_string()
+}
+
+fn is_code_kind(kind: &str) -> bool {
+    kind == "code"
+}
+
 fn default_isolation() -> String {
     "worktree".to_string()
 }
@@ -188,7 +290,14 @@ gate = "auto"
                 default_harness: "claude-code".to_owned(),
                 isolation: "worktree".to_owned(),
                 container_image: None,
+                ephemeral_db: None,
+                template_setup: None,
+                template_hardlinks: false,
+                warmup_command: None,
+                setup_commands: Vec::new(),
+                caches: Vec::new(),
             },
+            hooks: Default::default(),
             tasks: vec![TaskToml {
                 name: "t1".to_owned(),
                 description: "First task".to_owned(),
@@ -196,8 +305,16 @@ gate = "auto"
                 gate: "auto".to_owned(),
                 retry_max: 2,
                 depends_on: vec![],
+                ordering_only: vec![],
                 invariants: vec!["check".to_owned()],
                 harness: None,
+                issue: Some("acme/widgets#7".to_owned()),
+                services: Vec::new(),
+                kind: "code".to_string(),
+                artifacts: Vec::new(),
+                paths: Vec::new(),
+                timeout: None,
+                setup_commands: Vec::new(),
             }],
         };
 
@@ -292,6 +409,7 @@ gate = "auto"
 "#;
         let plan: PlanToml = toml::from_str(toml_str).expect("should parse");
         assert_eq!(plan.plan.container_image, None);
+        assert_eq!(plan.plan.ephemeral_db, None);
     }
 
     /// Helper to resolve a path relative to the workspace root.
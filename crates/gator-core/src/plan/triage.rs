@@ -0,0 +1,242 @@
+//! Issue triage: recurring plans generated from an issue queue.
+//!
+//! Each triage plan has one artifact task per new issue. The agent reads
+//! the issue and the codebase and writes a triage comment and labels into
+//! the worktree; when the task passes, the bundled artifacts are posted
+//! back to the issue (see [`crate::forge`]). Nothing is committed.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::issues::{GithubIssue, issue_task_name};
+use super::toml_format::{PlanMeta, PlanToml, TaskToml};
+
+/// Artifact path of the triage comment (Markdown).
+pub const TRIAGE_COMMENT: &str = ".gator-triage/comment.md";
+/// Artifact path of the label list (one label per line).
+pub const TRIAGE_LABELS: &str = ".gator-triage/labels.txt";
+
+/// Settings for the plans built by [`plan_from_triage_issues`].
+#[derive(Debug, Clone)]
+pub struct TriageOptions {
+    /// Plan name.
+    pub plan_name: String,
+    /// Base branch the worktrees are created from.
+    pub base_branch: String,
+    /// Repository the issues came from, as `owner/name`.
+    pub repo: String,
+    /// Labels the agent may apply. Empty means any label.
+    pub allowed_labels: Vec<String>,
+    /// Invariant names linked to every task, on top of the artifact checks.
+    pub invariants: Vec<String>,
+}
+
+/// Build a triage plan with one independent artifact task per issue,
+/// ordered by issue number.
+pub fn plan_from_triage_issues(issues: &[GithubIssue], options: &TriageOptions) -> PlanToml {
+    let mut sorted: Vec<&GithubIssue> = issues.iter().collect();
+    sorted.sort_by_key(|i| i.number);
+
+    let tasks = sorted
+        .into_iter()
+        .map(|issue| TaskToml {
+            name: issue_task_name(issue.number, &issue.title),
+            description: triage_description(issue, &options.allowed_labels),
+            scope: "narrow".to_string(),
+            gate: "auto".to_string(),
+            retry_max: 2,
+            depends_on: vec![],
+            ordering_only: vec![],
+            invariants: options.invariants.clone(),
+            harness: None,
+            issue: Some(format!("{}#{}", options.repo, issue.number)),
+            services: Vec::new(),
+            kind: "artifact".to_string(),
+            artifacts: vec![TRIAGE_COMMENT.to_string(), TRIAGE_LABELS.to_string()],
+            paths: Vec::new(),
+            timeout: None,
+            setup_commands: Vec::new(),
+        })
+        .collect();
+
+    PlanToml {
+        plan: PlanMeta {
+            id: None,
+            name: options.plan_name.clone(),
+            base_branch: options.base_branch.clone(),
+            token_budget: None,
+            default_harness: "claude-code".to_string(),
+            isolation: "worktree".to_string(),
+            container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            warmup_command: None,
+            setup_commands: Vec::new(),
+            caches: Vec::new(),
+        },
+        hooks: Default::default(),
+        tasks,
+    }
+}
+
+/// The agent instructions for triaging one issue.
+fn triage_description(issue: &GithubIssue, allowed_labels: &[String]) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "Triage issue #{}: {}\n\n",
+        issue.number,
+        issue.title.trim()
+    ));
+    let body = issue.body.trim();
+    if !body.is_empty() {
+        out.push_str(body);
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("Source: {}\n\n", issue.url));
+    out.push_str(
+        "Do not change any code. Investigate the issue against this codebase and write:\n\n",
+    );
+    out.push_str(&format!(
+        "- `{TRIAGE_COMMENT}`: a triage comment for the issue in Markdown -- a short \
+         summary, the code areas involved, whether you could confirm it, and a \
+         suggested next step.\n"
+    ));
+    out.push_str(&format!(
+        "- `{TRIAGE_LABELS}`: the labels to apply, one per line.\n"
+    ));
+    if !allowed_labels.is_empty() {
+        out.push_str(&format!(
+            "\nChoose labels only from: {}.\n",
+            allowed_labels.join(", ")
+        ));
+    }
+    out
+}
+
+/// A finished triage, read back from a task's artifact bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriageOutcome {
+    /// Comment body to post.
+    pub comment: String,
+    /// Labels to apply.
+    pub labels: Vec<String>,
+    /// Labels the agent chose that are not in the allowed list.
+    pub rejected_labels: Vec<String>,
+}
+
+/// Read the triage comment and labels from a bundle directory.
+pub fn read_triage_outcome(bundle: &Path, allowed_labels: &[String]) -> Result<TriageOutcome> {
+    let comment_path = bundle.join(TRIAGE_COMMENT);
+    let comment = std::fs::read_to_string(&comment_path)
+        .with_context(|| format!("failed to read {}", comment_path.display()))?;
+    let labels_path = bundle.join(TRIAGE_LABELS);
+    let labels = std::fs::read_to_string(&labels_path)
+        .with_context(|| format!("failed to read {}", labels_path.display()))?;
+    let (labels, rejected_labels) = parse_labels(&labels, allowed_labels);
+    Ok(TriageOutcome {
+        comment: comment.trim().to_string(),
+        labels,
+        rejected_labels,
+    })
+}
+
+/// Split a label file into (accepted, rejected) labels. Blank lines and
+/// `#` comments are skipped, duplicates dropped. With an empty
+/// `allowed_labels`, every label is accepted.
+fn parse_labels(text: &str, allowed_labels: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    for line in text.lines() {
+        let label = line.trim().trim_start_matches("- ").trim();
+        if label.is_empty() || label.starts_with('#') {
+            continue;
+        }
+        let label = label.to_string();
+        if accepted.contains(&label) || rejected.contains(&label) {
+            continue;
+        }
+        if allowed_labels.is_empty() || allowed_labels.contains(&label) {
+            accepted.push(label);
+        } else {
+            rejected.push(label);
+        }
+    }
+    (accepted, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::parse_plan_toml;
+
+    fn options(allowed: &[&str]) -> TriageOptions {
+        TriageOptions {
+            plan_name: "widgets-triage".to_string(),
+            base_branch: "main".to_string(),
+            repo: "acme/widgets".to_string(),
+            allowed_labels: allowed.iter().map(|s| s.to_string()).collect(),
+            invariants: vec![],
+        }
+    }
+
+    #[test]
+    fn builds_artifact_tasks_that_validate() {
+        let issues = vec![
+            GithubIssue {
+                number: 8,
+                title: "Crash on start".to_string(),
+                body: "Stack trace...".to_string(),
+                url: "https://github.com/acme/widgets/issues/8".to_string(),
+            },
+            GithubIssue {
+                number: 3,
+                title: "Docs typo".to_string(),
+                body: String::new(),
+                url: "https://github.com/acme/widgets/issues/3".to_string(),
+            },
+        ];
+        let plan = plan_from_triage_issues(&issues, &options(&["bug", "docs"]));
+
+        assert_eq!(plan.tasks.len(), 2);
+        let task = &plan.tasks[0];
+        assert_eq!(task.name, "issue-3-docs-typo");
+        assert_eq!(task.kind, "artifact");
+        assert_eq!(task.artifacts, vec![TRIAGE_COMMENT, TRIAGE_LABELS]);
+        assert_eq!(task.issue.as_deref(), Some("acme/widgets#3"));
+        assert!(
+            task.description
+                .contains("Choose labels only from: bug, docs.")
+        );
+
+        let text = toml::to_string(&plan).unwrap();
+        assert_eq!(parse_plan_toml(&text).unwrap(), plan);
+    }
+
+    #[test]
+    fn reads_outcome_and_filters_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gator-triage")).unwrap();
+        std::fs::write(
+            dir.path().join(TRIAGE_COMMENT),
+            "\n## Triage\n\nLooks real.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join(TRIAGE_LABELS),
+            "# labels\nbug\n\n- needs-repro\nbug\nwontfix\n",
+        )
+        .unwrap();
+
+        let allowed = vec!["bug".to_string(), "needs-repro".to_string()];
+        let outcome = read_triage_outcome(dir.path(), &allowed).unwrap();
+        assert_eq!(outcome.comment, "## Triage\n\nLooks real.");
+        assert_eq!(outcome.labels, vec!["bug", "needs-repro"]);
+        assert_eq!(outcome.rejected_labels, vec!["wontfix"]);
+
+        let open = read_triage_outcome(dir.path(), &[]).unwrap();
+        assert_eq!(open.labels, vec!["bug", "needs-repro", "wontfix"]);
+        assert!(read_triage_outcome(&dir.path().join("missing"), &[]).is_err());
+    }
+}
@@ -1,14 +1,17 @@
 //! Invariant preset library and project detection.
 //!
 //! Provides a built-in library of standard invariant definitions for common
-//! project types (Rust, Node, Python, Go). The presets are defined in
-//! `invariants.toml` and embedded in the binary at compile time.
+//! project types (Rust, Node, Python, Go) and for docs (artifact tasks). The
+//! presets are defined in `invariants.toml` and embedded in the binary at
+//! compile time.
 
 use std::path::Path;
 use std::process::Command;
 
 use serde::Deserialize;
 
+use gator_db::models::Invariant;
+
 /// A single invariant preset from the embedded library.
 #[derive(Debug, Clone, Deserialize)]
 pub struct InvariantPreset {
@@ -55,6 +58,26 @@ pub fn presets_for_project_type(project_type: &str) -> Vec<InvariantPreset> {
         .collect()
 }
 
+/// The fields in which an installed invariant differs from `preset`, e.g.
+/// `["command", "args"]`; empty when it matches. Only the fields a preset
+/// defines are compared, so a changed timeout or threshold is not drift.
+pub fn preset_drift(preset: &InvariantPreset, invariant: &Invariant) -> Vec<&'static str> {
+    let mut drift = Vec::new();
+    if invariant.description.as_deref() != Some(preset.description.as_str()) {
+        drift.push("description");
+    }
+    if invariant.kind.to_string() != preset.kind {
+        drift.push("kind");
+    }
+    if invariant.command != preset.command {
+        drift.push("command");
+    }
+    if invariant.args.0 != preset.args {
+        drift.push("args");
+    }
+    drift
+}
+
 /// Return the list of distinct project types defined in the preset library.
 pub fn available_project_types() -> Vec<String> {
     let presets = load_presets();
@@ -132,6 +155,40 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn preset_drift_compares_preset_fields_only() {
+        let preset = presets_for_project_type("rust")
+            .into_iter()
+            .find(|p| p.name == "rust_build")
+            .unwrap();
+        let mut invariant = Invariant {
+            id: uuid::Uuid::new_v4(),
+            name: preset.name.clone(),
+            description: Some(preset.description.clone()),
+            kind: preset.kind.parse().unwrap(),
+            command: preset.command.clone(),
+            args: sqlx::types::Json(preset.args.clone()),
+            expected_exit_code: 0,
+            threshold: None,
+            scope: gator_db::models::InvariantScope::Project,
+            timeout_secs: 900,
+            created_at: chrono::Utc::now(),
+            deleted_at: None,
+            diff_base: false,
+            lower_is_better: false,
+            disabled_at: None,
+            env: Default::default(),
+            workdir: None,
+            run_after: Default::default(),
+            artifacts: Default::default(),
+        };
+        assert!(preset_drift(&preset, &invariant).is_empty());
+
+        invariant.command = "cargo-nightly".to_string();
+        invariant.args.0.push("--locked".to_string());
+        assert_eq!(preset_drift(&preset, &invariant), ["command", "args"]);
+    }
+
     #[test]
     fn load_presets_returns_nonempty() {
         let presets = load_presets();
@@ -186,6 +243,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn presets_for_docs() {
+        let presets = presets_for_project_type("docs");
+        let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["docs_markdownlint", "docs_links"]);
+    }
+
     #[test]
     fn presets_for_nonexistent_returns_empty() {
         let presets = presets_for_project_type("nonexistent");
@@ -0,0 +1,362 @@
+//! Review-bot mode: agents review open pull requests.
+//!
+//! Each PR head is fetched into a local branch and becomes a one-task plan
+//! whose worktree starts at that head. The agent writes a structured review
+//! to [`REVIEW_FILE`]; the gate's `review` artifact check validates its
+//! format and that it covers every file the PR changes, and the passed
+//! review is posted back to the PR (see [`crate::forge`]).
+//!
+//! ```json
+//! {
+//!   "verdict": "comment",
+//!   "summary": "Overall assessment, in Markdown.",
+//!   "files": [
+//!     { "path": "src/lib.rs", "notes": "What changed and whether it is right.",
+//!       "comments": [{ "line": 42, "body": "Off by one." }] }
+//!   ]
+//! }
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::artifact::ArtifactCheckResult;
+use crate::forge::{GithubPullRequest, PrReviewAction};
+use crate::plan::{PlanMeta, PlanToml, TaskToml};
+
+/// Artifact path of the structured review.
+pub const REVIEW_FILE: &str = ".gator-review/review.json";
+
+/// Overall outcome the agent recommends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    Comment,
+    RequestChanges,
+}
+
+impl ReviewVerdict {
+    /// The review action to post. Unless `submit_verdict` is set, every
+    /// review is posted as a plain comment.
+    pub fn action(self, submit_verdict: bool) -> PrReviewAction {
+        match self {
+            _ if !submit_verdict => PrReviewAction::Comment,
+            Self::Approve => PrReviewAction::Approve,
+            Self::Comment => PrReviewAction::Comment,
+            Self::RequestChanges => PrReviewAction::RequestChanges,
+        }
+    }
+}
+
+/// A structured review, as written by the agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Review {
+    pub verdict: ReviewVerdict,
+    pub summary: String,
+    pub files: Vec<FileReview>,
+}
+
+/// The review of one changed file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileReview {
+    pub path: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub comments: Vec<LineComment>,
+}
+
+/// A comment on one line of a changed file (line numbers in the new file).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LineComment {
+    pub line: u32,
+    pub body: String,
+}
+
+/// Problems with `review`, one per entry: empty summary, changed files it
+/// does not cover, files it reviews that the PR does not change, and empty
+/// or unnumbered comments.
+pub fn validate_review(review: &Review, changed_files: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if review.summary.trim().is_empty() {
+        problems.push("summary is empty".to_string());
+    }
+    for path in changed_files {
+        if !review.files.iter().any(|f| &f.path == path) {
+            problems.push(format!("changed file {path} is not reviewed"));
+        }
+    }
+    for (i, file) in review.files.iter().enumerate() {
+        if !changed_files.contains(&file.path) {
+            problems.push(format!("{} is not changed by this PR", file.path));
+        }
+        if review.files[..i].iter().any(|f| f.path == file.path) {
+            problems.push(format!("{} is reviewed more than once", file.path));
+        }
+        if file.notes.trim().is_empty() && file.comments.is_empty() {
+            problems.push(format!("{} has neither notes nor comments", file.path));
+        }
+        for comment in &file.comments {
+            if comment.line == 0 {
+                problems.push(format!("{}: comment on line 0", file.path));
+            }
+            if comment.body.trim().is_empty() {
+                problems.push(format!("{}:{}: empty comment", file.path, comment.line));
+            }
+        }
+    }
+    problems
+}
+
+/// Gate check for [`REVIEW_FILE`] in `worktree`: it must parse and pass
+/// [`validate_review`].
+pub fn check_review(worktree: &Path, changed_files: &[String]) -> ArtifactCheckResult {
+    let problems = match std::fs::read_to_string(worktree.join(REVIEW_FILE)) {
+        Err(e) => vec![format!("unreadable: {e}")],
+        Ok(text) => match serde_json::from_str::<Review>(&text) {
+            Err(e) => vec![format!("invalid review JSON: {e}")],
+            Ok(review) => validate_review(&review, changed_files),
+        },
+    };
+    ArtifactCheckResult {
+        path: REVIEW_FILE.to_string(),
+        check: "review",
+        passed: problems.is_empty(),
+        message: problems.join("\n"),
+    }
+}
+
+/// Render a review as the Markdown body of a PR review.
+pub fn render_review(review: &Review) -> String {
+    let verdict = match review.verdict {
+        ReviewVerdict::Approve => "approve",
+        ReviewVerdict::Comment => "comment",
+        ReviewVerdict::RequestChanges => "request changes",
+    };
+    let mut out = String::new();
+    out.push_str(review.summary.trim());
+    out.push_str(&format!("\n\n**Recommendation:** {verdict}\n"));
+    for file in &review.files {
+        out.push_str(&format!("\n### `{}`\n\n", file.path));
+        if !file.notes.trim().is_empty() {
+            out.push_str(file.notes.trim());
+            out.push_str("\n\n");
+        }
+        for comment in &file.comments {
+            out.push_str(&format!(
+                "- **L{}:** {}\n",
+                comment.line,
+                comment.body.trim()
+            ));
+        }
+    }
+    out
+}
+
+/// Settings for [`plan_for_pull_request`].
+#[derive(Debug, Clone)]
+pub struct ReviewPlanOptions {
+    /// Plan name.
+    pub plan_name: String,
+    /// Local branch at the PR head; the worktree starts here.
+    pub head_branch: String,
+    /// Local branch at the PR's base, to diff against.
+    pub base_branch: String,
+    /// Invariant names linked to the task, on top of the review check.
+    pub invariants: Vec<String>,
+}
+
+/// Build a one-task plan reviewing `pr`, whose diff touches `changed_files`.
+pub fn plan_for_pull_request(
+    pr: &GithubPullRequest,
+    changed_files: &[String],
+    options: &ReviewPlanOptions,
+) -> PlanToml {
+    PlanToml {
+        plan: PlanMeta {
+            id: None,
+            name: options.plan_name.clone(),
+            base_branch: options.head_branch.clone(),
+            token_budget: None,
+            default_harness: "claude-code".to_string(),
+            isolation: "worktree".to_string(),
+            container_image: None,
+            ephemeral_db: None,
+            template_setup: None,
+            template_hardlinks: false,
+            warmup_command: None,
+            setup_commands: Vec::new(),
+            caches: Vec::new(),
+        },
+        hooks: Default::default(),
+        tasks: vec![TaskToml {
+            name: format!("review-pr-{}", pr.number),
+            description: review_description(pr, changed_files, &options.base_branch),
+            scope: "narrow".to_string(),
+            gate: "auto".to_string(),
+            retry_max: 2,
+            depends_on: vec![],
+            ordering_only: vec![],
+            invariants: options.invariants.clone(),
+            harness: None,
+            issue: None,
+            services: Vec::new(),
+            kind: "artifact".to_string(),
+            artifacts: vec![REVIEW_FILE.to_string()],
+            paths: Vec::new(),
+            timeout: None,
+            setup_commands: Vec::new(),
+        }],
+    }
+}
+
+/// The agent instructions for reviewing one PR.
+fn review_description(pr: &GithubPullRequest, changed_files: &[String], base: &str) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "Review pull request #{}: {}\n\n",
+        pr.number,
+        pr.title.trim()
+    ));
+    let body = pr.body.trim();
+    if !body.is_empty() {
+        out.push_str(body);
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("Source: {}\n\n", pr.url));
+    out.push_str(&format!(
+        "The worktree is checked out at the PR head. See the change with \
+         `git diff {base}...HEAD`. Do not change any code.\n\n"
+    ));
+    out.push_str("Changed files:\n\n");
+    for path in changed_files {
+        out.push_str(&format!("- `{path}`\n"));
+    }
+    out.push_str(&format!(
+        "\nWrite your review to `{REVIEW_FILE}` as JSON:\n\n\
+         ```json\n\
+         {{\n  \"verdict\": \"approve | comment | request_changes\",\n  \
+         \"summary\": \"overall assessment (Markdown)\",\n  \
+         \"files\": [{{\"path\": \"...\", \"notes\": \"...\", \
+         \"comments\": [{{\"line\": 1, \"body\": \"...\"}}]}}]\n}}\n\
+         ```\n\n\
+         Review every changed file listed above, and only those. Line numbers \
+         refer to the new version of the file.\n"
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::parse_plan_toml;
+
+    fn changed() -> Vec<String> {
+        vec!["src/a.rs".to_string(), "src/b.rs".to_string()]
+    }
+
+    fn review(files: &[&str]) -> Review {
+        Review {
+            verdict: ReviewVerdict::RequestChanges,
+            summary: "Mostly fine.".to_string(),
+            files: files
+                .iter()
+                .map(|p| FileReview {
+                    path: p.to_string(),
+                    notes: "Looks right.".to_string(),
+                    comments: vec![LineComment {
+                        line: 3,
+                        body: "Rename this.".to_string(),
+                    }],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn validates_coverage_of_changed_files() {
+        assert!(validate_review(&review(&["src/a.rs", "src/b.rs"]), &changed()).is_empty());
+
+        let problems = validate_review(&review(&["src/a.rs", "src/c.rs", "src/a.rs"]), &changed());
+        assert_eq!(
+            problems,
+            vec![
+                "changed file src/b.rs is not reviewed",
+                "src/c.rs is not changed by this PR",
+                "src/a.rs is reviewed more than once",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_reports_bad_json_and_passes_good_reviews() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gator-review")).unwrap();
+        let path = dir.path().join(REVIEW_FILE);
+
+        std::fs::write(&path, r#"{"verdict": "lgtm", "summary": "", "files": []}"#).unwrap();
+        let check = check_review(dir.path(), &changed());
+        assert!(!check.passed);
+        assert!(check.message.starts_with("invalid review JSON"));
+
+        let good = serde_json::to_string(&review(&["src/b.rs", "src/a.rs"])).unwrap();
+        std::fs::write(&path, good).unwrap();
+        let check = check_review(dir.path(), &changed());
+        assert!(check.passed, "{}", check.message);
+        assert_eq!(check.check, "review");
+    }
+
+    #[test]
+    fn renders_and_maps_verdicts() {
+        let body = render_review(&review(&["src/a.rs"]));
+        assert!(body.starts_with("Mostly fine.\n\n**Recommendation:** request changes\n"));
+        assert!(body.contains("### `src/a.rs`\n\nLooks right.\n\n- **L3:** Rename this.\n"));
+
+        assert_eq!(
+            ReviewVerdict::RequestChanges.action(false),
+            PrReviewAction::Comment
+        );
+        assert_eq!(
+            ReviewVerdict::RequestChanges.action(true),
+            PrReviewAction::RequestChanges
+        );
+    }
+
+    #[test]
+    fn builds_a_review_plan_on_the_pr_head() {
+        let pr = GithubPullRequest {
+            number: 12,
+            title: "Add widgets".to_string(),
+            body: "Adds them.".to_string(),
+            url: "https://github.com/acme/widgets/pull/12".to_string(),
+            head_ref_oid: "abc".to_string(),
+            base_ref_name: "main".to_string(),
+            is_draft: false,
+        };
+        let options = ReviewPlanOptions {
+            plan_name: "widgets-review-pr-12".to_string(),
+            head_branch: "gator-review/pr-12".to_string(),
+            base_branch: "gator-review/pr-12-base".to_string(),
+            invariants: vec![],
+        };
+        let plan = plan_for_pull_request(&pr, &changed(), &options);
+
+        assert_eq!(plan.plan.base_branch, "gator-review/pr-12");
+        let task = &plan.tasks[0];
+        assert_eq!(task.name, "review-pr-12");
+        assert_eq!(task.artifacts, vec![REVIEW_FILE]);
+        assert!(
+            task.description
+                .contains("git diff gator-review/pr-12-base...HEAD")
+        );
+        assert!(task.description.contains("- `src/b.rs`"));
+
+        let text = toml::to_string(&plan).unwrap();
+        assert_eq!(parse_plan_toml(&text).unwrap(), plan);
+    }
+}
@@ -0,0 +1,42 @@
+//! The public JSON schema: what `gator status --output json`, the `gator
+//! serve` API, and webhooks emit.
+//!
+//! Payloads are built from the types in [`v1`], not from the database
+//! models, so internal structs can change without changing what external
+//! consumers see. Every top-level object, and every element of a top-level
+//! array, carries `schema_version` (see [`Versioned`]). The schema itself
+//! is published as JSON Schema in `docs/schema/v1.schema.json`.
+//!
+//! Within a version:
+//! - fields are only ever added, never removed, renamed, or retyped;
+//! - enumerated values (statuses, kinds, event types) are strings, and new
+//!   values may appear, so consumers must accept ones they do not know;
+//! - a field that may be `null` is always present unless documented as
+//!   omitted when empty.
+//!
+//! Anything else is a breaking change and bumps [`SCHEMA_VERSION`].
+
+pub mod v1;
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the public JSON schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A payload stamped with the schema version it follows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> Versioned<T> {
+    /// Stamp `body` with the current [`SCHEMA_VERSION`].
+    pub fn new(body: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            body,
+        }
+    }
+}
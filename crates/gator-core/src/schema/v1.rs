@@ -0,0 +1,504 @@
+//! Version 1 of the public JSON schema.
+//!
+//! Each type converts from the database model it describes. A new field on
+//! a model stays internal until it is added here, and adding it here is
+//! the only way it reaches consumers.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use gator_db::models;
+use gator_db::queries::gate_results::GateResultWithName;
+use gator_db::queries::tasks::PlanProgress;
+
+pub use crate::notify::{Notification, NotificationEvent};
+
+/// A plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub id: Uuid,
+    pub name: String,
+    pub project_path: String,
+    pub base_branch: String,
+    /// `draft`, `approved`, `running`, `completed`, or `failed`.
+    pub status: String,
+    pub token_budget: Option<i64>,
+    pub default_harness: String,
+    /// `worktree` or `container`.
+    pub isolation: String,
+    pub container_image: Option<String>,
+    pub ephemeral_db: Option<String>,
+    pub template_setup: Option<String>,
+    pub setup_commands: Vec<String>,
+    pub warmup_command: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub interrupted_at: Option<DateTime<Utc>>,
+    /// `budget_exceeded` or `tasks_escalated`, while the plan is `failed`.
+    pub failure_reason: Option<String>,
+    pub dispatched_host: Option<String>,
+    pub dispatched_pid: Option<i64>,
+    pub dispatched_user: Option<String>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+impl From<models::Plan> for Plan {
+    fn from(plan: models::Plan) -> Self {
+        Self {
+            id: plan.id,
+            name: plan.name,
+            project_path: plan.project_path,
+            base_branch: plan.base_branch,
+            status: plan.status.to_string(),
+            token_budget: plan.token_budget,
+            default_harness: plan.default_harness,
+            isolation: plan.isolation,
+            container_image: plan.container_image,
+            ephemeral_db: plan.ephemeral_db,
+            template_setup: plan.template_setup,
+            setup_commands: plan.setup_commands.0,
+            warmup_command: plan.warmup_command,
+            created_at: plan.created_at,
+            approved_at: plan.approved_at,
+            completed_at: plan.completed_at,
+            deleted_at: plan.deleted_at,
+            archived_at: plan.archived_at,
+            interrupted_at: plan.interrupted_at,
+            failure_reason: plan.failure_reason.map(|r| r.to_string()),
+            dispatched_host: plan.dispatched_host,
+            dispatched_pid: plan.dispatched_pid,
+            dispatched_user: plan.dispatched_user,
+            dispatched_at: plan.dispatched_at,
+        }
+    }
+}
+
+/// A task of a plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub name: String,
+    pub description: String,
+    /// `narrow`, `medium`, or `broad`.
+    pub scope_level: String,
+    /// `auto`, `human_review`, or `human_approve`.
+    pub gate_policy: String,
+    /// `code` or `artifact`.
+    pub kind: String,
+    pub retry_max: i32,
+    /// `pending`, `assigned`, `running`, `blocked_on_human`, `checking`,
+    /// `passed`, `failed`, `escalated`, or `cancelled`.
+    pub status: String,
+    pub attempt: i32,
+    pub assigned_harness: Option<String>,
+    pub requested_harness: Option<String>,
+    pub worktree_path: Option<String>,
+    /// Bytes the worktree took on disk when last measured.
+    pub disk_usage_bytes: Option<i64>,
+    pub issue: Option<String>,
+    pub services: Vec<String>,
+    pub artifacts: Vec<String>,
+    pub paths: Vec<String>,
+    pub setup_commands: Vec<String>,
+    pub timeout_secs: Option<i64>,
+    pub retry_env: BTreeMap<String, String>,
+    pub retry_env_attempt: Option<i32>,
+    pub gate_script: Option<String>,
+    pub llm_review: bool,
+    pub review_harness: Option<String>,
+    pub agent_pid: Option<i64>,
+    pub agent_host: Option<String>,
+    pub container_id: Option<String>,
+    pub run_id: Option<Uuid>,
+    pub attempt_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub merge_commit: Option<String>,
+}
+
+impl From<models::Task> for Task {
+    fn from(task: models::Task) -> Self {
+        Self {
+            id: task.id,
+            plan_id: task.plan_id,
+            name: task.name,
+            description: task.description,
+            scope_level: task.scope_level.to_string(),
+            gate_policy: task.gate_policy.to_string(),
+            kind: task.kind.to_string(),
+            retry_max: task.retry_max,
+            status: task.status.to_string(),
+            attempt: task.attempt,
+            assigned_harness: task.assigned_harness,
+            requested_harness: task.requested_harness,
+            worktree_path: task.worktree_path,
+            disk_usage_bytes: task.disk_usage_bytes,
+            issue: task.issue,
+            services: task.services.0,
+            artifacts: task.artifacts.0,
+            paths: task.paths.0,
+            setup_commands: task.setup_commands.0,
+            timeout_secs: task.timeout_secs,
+            retry_env: task.retry_env.0,
+            retry_env_attempt: task.retry_env_attempt,
+            gate_script: task.gate_script,
+            llm_review: task.llm_review,
+            review_harness: task.review_harness,
+            agent_pid: task.agent_pid,
+            agent_host: task.agent_host,
+            container_id: task.container_id,
+            run_id: task.run_id,
+            attempt_id: task.attempt_id,
+            created_at: task.created_at,
+            started_at: task.started_at,
+            completed_at: task.completed_at,
+            deleted_at: task.deleted_at,
+            merged_at: task.merged_at,
+            merge_commit: task.merge_commit,
+        }
+    }
+}
+
+/// An invariant definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Invariant {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// `test_suite`, `typecheck`, `lint`, `coverage`, or `custom`.
+    pub kind: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub expected_exit_code: i32,
+    pub threshold: Option<f32>,
+    /// `global` or `project`.
+    pub scope: String,
+    pub timeout_secs: i32,
+    pub diff_base: bool,
+    pub lower_is_better: bool,
+    pub env: BTreeMap<String, String>,
+    pub workdir: Option<String>,
+    pub run_after: Vec<String>,
+    /// Globs of files collected from the worktree when the invariant fails.
+    pub artifacts: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+impl From<models::Invariant> for Invariant {
+    fn from(invariant: models::Invariant) -> Self {
+        Self {
+            id: invariant.id,
+            name: invariant.name,
+            description: invariant.description,
+            kind: invariant.kind.to_string(),
+            command: invariant.command,
+            args: invariant.args.0,
+            expected_exit_code: invariant.expected_exit_code,
+            threshold: invariant.threshold,
+            scope: invariant.scope.to_string(),
+            timeout_secs: invariant.timeout_secs,
+            diff_base: invariant.diff_base,
+            lower_is_better: invariant.lower_is_better,
+            env: invariant.env.0,
+            workdir: invariant.workdir,
+            run_after: invariant.run_after.0,
+            artifacts: invariant.artifacts.0,
+            created_at: invariant.created_at,
+            deleted_at: invariant.deleted_at,
+            disabled_at: invariant.disabled_at,
+        }
+    }
+}
+
+/// An event recorded from an agent's stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentEvent {
+    pub id: i64,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// `message`, `tool_call`, `tool_result`, `token_usage`, `error`, or
+    /// `completed` for events from the agent; gator records others.
+    pub event_type: String,
+    /// The event as the harness reported it; its `type` matches
+    /// `event_type`.
+    pub payload: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
+    pub run_id: Option<Uuid>,
+    pub attempt_id: Option<Uuid>,
+}
+
+impl From<models::AgentEvent> for AgentEvent {
+    fn from(event: models::AgentEvent) -> Self {
+        Self {
+            id: event.id,
+            task_id: event.task_id,
+            attempt: event.attempt,
+            event_type: event.event_type,
+            payload: event.payload,
+            recorded_at: event.recorded_at,
+            run_id: event.run_id,
+            attempt_id: event.attempt_id,
+        }
+    }
+}
+
+/// The result of one invariant in a task's gate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GateResult {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub invariant_id: Uuid,
+    pub invariant_name: String,
+    pub attempt: i32,
+    pub passed: bool,
+    pub timed_out: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub duration_ms: Option<i32>,
+    pub base_value: Option<f64>,
+    pub task_value: Option<f64>,
+    /// The earlier attempt whose result was reused, if any.
+    pub cached_from: Option<i32>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl From<GateResultWithName> for GateResult {
+    fn from(result: GateResultWithName) -> Self {
+        Self {
+            id: result.id,
+            task_id: result.task_id,
+            invariant_id: result.invariant_id,
+            invariant_name: result.invariant_name,
+            attempt: result.attempt,
+            passed: result.passed,
+            timed_out: result.timed_out,
+            exit_code: result.exit_code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            duration_ms: result.duration_ms,
+            base_value: result.base_value,
+            task_value: result.task_value,
+            cached_from: result.cached_from,
+            checked_at: result.checked_at,
+        }
+    }
+}
+
+/// An operator note on a plan, or on one of its tasks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub task_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<models::Note> for Note {
+    fn from(note: models::Note) -> Self {
+        Self {
+            id: note.id,
+            plan_id: note.plan_id,
+            task_id: note.task_id,
+            body: note.body,
+            created_at: note.created_at,
+        }
+    }
+}
+
+/// Task counts of a plan by status.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub pending: i64,
+    pub assigned: i64,
+    pub running: i64,
+    pub blocked_on_human: i64,
+    pub checking: i64,
+    pub passed: i64,
+    pub failed: i64,
+    pub escalated: i64,
+    pub cancelled: i64,
+    pub total: i64,
+}
+
+impl From<PlanProgress> for Progress {
+    fn from(p: PlanProgress) -> Self {
+        Self {
+            pending: p.pending,
+            assigned: p.assigned,
+            running: p.running,
+            blocked_on_human: p.blocked_on_human,
+            checking: p.checking,
+            passed: p.passed,
+            failed: p.failed,
+            escalated: p.escalated,
+            cancelled: p.cancelled,
+            total: p.total,
+        }
+    }
+}
+
+/// Tokens reported by agents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub total_tokens: i64,
+}
+
+impl TokenUsage {
+    pub fn new(input_tokens: i64, output_tokens: i64) -> Self {
+        Self {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+        }
+    }
+}
+
+/// A plan with its progress: `GET /api/plans` and `gator status --output
+/// json` without a plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanSummary {
+    #[serde(flatten)]
+    pub plan: Plan,
+    pub progress: Progress,
+}
+
+/// A plan with its tasks: `GET /api/plans/{id}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanDetail {
+    #[serde(flatten)]
+    pub plan: Plan,
+    pub progress: Progress,
+    pub token_usage: TokenUsage,
+    pub tasks: Vec<Task>,
+}
+
+/// A task with its events and latest gate: `GET /api/tasks/{id}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDetail {
+    #[serde(flatten)]
+    pub task: Task,
+    /// IDs of the tasks it depends on.
+    pub dependencies: Vec<Uuid>,
+    pub invariants: Vec<Invariant>,
+    pub events: Vec<AgentEvent>,
+    pub gate_results: Vec<GateResult>,
+    pub token_usage: TokenUsage,
+}
+
+/// `gator status <plan> --output json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStatus {
+    #[serde(flatten)]
+    pub plan: Plan,
+    pub progress: Progress,
+    pub tasks: Vec<Task>,
+    /// Operator notes, oldest first; omitted when there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    /// With `--why`: why each pending task is not running yet; omitted
+    /// otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub why: Option<Vec<PendingTask>>,
+    /// How dispatch is holding back after rate limits; omitted when the
+    /// plan is not throttled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle: Option<Throttle>,
+}
+
+/// How a running plan's dispatch is holding back after provider rate
+/// limits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Throttle {
+    /// Agents run at once for now.
+    pub agent_limit: i64,
+    /// Agents run at once when not throttled.
+    pub max_agents: i64,
+    /// No new agents start before this time.
+    pub paused_until: Option<DateTime<Utc>>,
+    /// Rate-limit errors seen since throttling began.
+    pub rate_limits: i64,
+    pub last_error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<models::PlanThrottle> for Throttle {
+    fn from(throttle: models::PlanThrottle) -> Self {
+        Self {
+            agent_limit: throttle.agent_limit,
+            max_agents: throttle.max_agents,
+            paused_until: throttle.paused_until,
+            rate_limits: throttle.rate_limits,
+            last_error: throttle.last_error,
+            updated_at: throttle.updated_at,
+        }
+    }
+}
+
+/// Why a pending task is not running yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingTask {
+    pub task: String,
+    pub ready: bool,
+    pub reasons: Vec<String>,
+}
+
+/// `gator report <plan> --format json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanReport {
+    #[serde(flatten)]
+    pub plan: Plan,
+    pub progress: Progress,
+    pub token_usage: TokenUsage,
+    /// Tokens priced with `--usd-per-mtok`; null without it.
+    pub cost_usd: Option<f64>,
+    /// Seconds from approval to completion; null until the plan completes.
+    pub duration_secs: Option<i64>,
+    pub tasks: Vec<TaskReport>,
+}
+
+/// One task of a plan report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskReport {
+    pub id: Uuid,
+    pub name: String,
+    /// As in [`Task::status`].
+    pub status: String,
+    /// Attempts started so far.
+    pub attempts: i32,
+    /// Seconds from the last start to completion; null while it has not
+    /// run or is still running.
+    pub wall_time_secs: Option<i64>,
+    pub token_usage: TokenUsage,
+    /// Tokens priced with `--usd-per-mtok`; null without it.
+    pub cost_usd: Option<f64>,
+    /// Invariant checks run, across all attempts.
+    pub gate_checks: i64,
+    pub gate_checks_passed: i64,
+    /// `gate_checks_passed / gate_checks`; null when no gate ran.
+    pub gate_pass_rate: Option<f64>,
+    /// Why each attempt that was followed by another did not pass.
+    pub retries: Vec<RetryReason>,
+}
+
+/// Why an attempt was retried.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryReason {
+    pub attempt: i32,
+    pub reason: String,
+}
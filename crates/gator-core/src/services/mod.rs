@@ -0,0 +1,269 @@
+//! Per-task service dependencies via Docker Compose.
+//!
+//! A task with `services = ["docker-compose.test.yml"]` gets its own
+//! Compose project for each attempt: the lifecycle runs `docker compose up
+//! --wait` (which blocks until every service with a healthcheck reports
+//! healthy) before the agent starts, and `docker compose down -v` when the
+//! attempt ends. Compose files are read from the task's worktree.
+//!
+//! Published ports are injected into the agent and gate environments as
+//! `GATOR_SERVICE_<SERVICE>_PORT_<CONTAINER_PORT>`, plus
+//! `GATOR_SERVICE_<SERVICE>_PORT` for the service's first published port,
+//! so compose files can publish on ephemeral host ports and parallel tasks
+//! never collide.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use tokio::process::Command;
+
+/// How long `docker compose up --wait` may take before the attempt fails.
+const UP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A running Compose project. Dropping it tears the project down.
+#[derive(Debug)]
+pub struct ComposeServices {
+    project: String,
+    files: Vec<PathBuf>,
+    working_dir: PathBuf,
+    env: HashMap<String, String>,
+}
+
+impl ComposeServices {
+    /// Bring up the services in `files` (relative to `working_dir`) as
+    /// Compose project `project`, wait for them to become healthy, and
+    /// collect their published ports.
+    pub async fn up(project: &str, files: &[String], working_dir: &Path) -> Result<Self> {
+        let files: Vec<PathBuf> = files.iter().map(|f| working_dir.join(f)).collect();
+        for file in &files {
+            if !file.is_file() {
+                bail!("compose file {} not found", file.display());
+            }
+        }
+
+        // From here on, dropping `services` runs `compose down`, which also
+        // cleans up a partially started project.
+        let mut services = Self {
+            project: project_name(project),
+            files,
+            working_dir: working_dir.to_path_buf(),
+            env: HashMap::new(),
+        };
+
+        let up = services.compose().args(["up", "-d", "--wait"]).output();
+        let output = tokio::time::timeout(UP_TIMEOUT, up)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "services not healthy after {}s (project {})",
+                    UP_TIMEOUT.as_secs(),
+                    services.project
+                )
+            })?
+            .context("failed to run docker compose up")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("docker compose up failed: {stderr}");
+        }
+
+        let output = services
+            .compose()
+            .args(["ps", "--format", "json"])
+            .output()
+            .await
+            .context("failed to run docker compose ps")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("docker compose ps failed: {stderr}");
+        }
+        let containers = parse_ps_output(&String::from_utf8_lossy(&output.stdout))
+            .context("failed to parse docker compose ps output")?;
+        services.env = port_env_vars(&containers);
+
+        tracing::info!(
+            project = %services.project,
+            ports = services.env.len(),
+            "task services ready"
+        );
+        Ok(services)
+    }
+
+    /// Environment variables to inject into the agent and gate.
+    pub fn env_vars(&self) -> HashMap<String, String> {
+        self.env.clone()
+    }
+
+    /// A `docker compose` command for this project.
+    fn compose(&self) -> Command {
+        let mut cmd = Command::new("docker");
+        cmd.arg("compose").args(["-p", &self.project]);
+        for file in &self.files {
+            cmd.arg("-f").arg(file);
+        }
+        cmd.current_dir(&self.working_dir);
+        cmd
+    }
+}
+
+impl Drop for ComposeServices {
+    fn drop(&mut self) {
+        // Synchronous so it also runs on error and timeout paths.
+        let mut cmd = std::process::Command::new("docker");
+        cmd.arg("compose").args(["-p", &self.project]);
+        for file in &self.files {
+            cmd.arg("-f").arg(file);
+        }
+        let result = cmd
+            .args(["down", "-v", "--remove-orphans"])
+            .current_dir(&self.working_dir)
+            .output();
+        match result {
+            Ok(out) if out.status.success() => {}
+            Ok(out) => tracing::warn!(
+                project = %self.project,
+                stderr = %String::from_utf8_lossy(&out.stderr),
+                "failed to tear down task services"
+            ),
+            Err(e) => tracing::warn!(
+                project = %self.project,
+                error = %e,
+                "failed to run docker compose down"
+            ),
+        }
+    }
+}
+
+/// One container from `docker compose ps --format json`.
+#[derive(Debug, Deserialize)]
+struct PsEntry {
+    #[serde(rename = "Service")]
+    service: String,
+    #[serde(rename = "Publishers", default)]
+    publishers: Option<Vec<Publisher>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Publisher {
+    #[serde(rename = "TargetPort")]
+    target_port: u16,
+    #[serde(rename = "PublishedPort")]
+    published_port: u16,
+}
+
+/// Compose project names allow lowercase letters, digits, `-` and `_`.
+fn project_name(raw: &str) -> String {
+    raw.chars()
+        .map(|c| {
+            let c = c.to_ascii_lowercase();
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Parse `docker compose ps --format json`: a JSON array on older Compose
+/// releases, one object per line on newer ones.
+fn parse_ps_output(output: &str) -> Result<Vec<PsEntry>> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    if trimmed.starts_with('[') {
+        return Ok(serde_json::from_str(trimmed)?);
+    }
+    trimmed
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Ok(serde_json::from_str(l)?))
+        .collect()
+}
+
+/// Build `GATOR_SERVICE_*` port variables. Unpublished ports (published
+/// port 0) are skipped.
+fn port_env_vars(containers: &[PsEntry]) -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    for entry in containers {
+        let prefix = format!("GATOR_SERVICE_{}", env_name(&entry.service));
+        let published = entry
+            .publishers
+            .iter()
+            .flatten()
+            .filter(|p| p.published_port != 0);
+        for publisher in published {
+            env.entry(format!("{prefix}_PORT"))
+                .or_insert_with(|| publisher.published_port.to_string());
+            env.insert(
+                format!("{prefix}_PORT_{}", publisher.target_port),
+                publisher.published_port.to_string(),
+            );
+        }
+    }
+    env
+}
+
+/// `my-db.v2` -> `MY_DB_V2`.
+fn env_name(service: &str) -> String {
+    service
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ps_output_in_both_formats() {
+        let line = r#"{"Service":"db","Publishers":[{"URL":"0.0.0.0","TargetPort":5432,"PublishedPort":55001,"Protocol":"tcp"}]}"#;
+        let ndjson = format!("{line}\n{}\n", r#"{"Service":"worker","Publishers":null}"#);
+        let entries = parse_ps_output(&ndjson).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].service, "db");
+
+        let array = format!("[{line}]");
+        assert_eq!(parse_ps_output(&array).unwrap().len(), 1);
+        assert!(parse_ps_output("").unwrap().is_empty());
+        assert!(parse_ps_output("not json").is_err());
+    }
+
+    #[test]
+    fn builds_port_env_vars() {
+        let entries = parse_ps_output(
+            r#"[
+              {"Service":"redis-cache","Publishers":[
+                {"TargetPort":6379,"PublishedPort":49200},
+                {"TargetPort":6380,"PublishedPort":0}
+              ]},
+              {"Service":"db","Publishers":[
+                {"TargetPort":5432,"PublishedPort":55001},
+                {"TargetPort":5432,"PublishedPort":55001}
+              ]}
+            ]"#,
+        )
+        .unwrap();
+        let env = port_env_vars(&entries);
+        assert_eq!(env["GATOR_SERVICE_REDIS_CACHE_PORT"], "49200");
+        assert_eq!(env["GATOR_SERVICE_REDIS_CACHE_PORT_6379"], "49200");
+        assert!(!env.contains_key("GATOR_SERVICE_REDIS_CACHE_PORT_6380"));
+        assert_eq!(env["GATOR_SERVICE_DB_PORT_5432"], "55001");
+        assert_eq!(env.len(), 4);
+    }
+
+    #[test]
+    fn project_names_are_sanitized() {
+        assert_eq!(project_name("gator-AB12_x.y"), "gator-ab12_x-y");
+    }
+}
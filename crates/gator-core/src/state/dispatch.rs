@@ -1,15 +1,25 @@
 //! Convenience dispatch helpers that wrap [`super::TaskStateMachine`]
 //! transitions with semantic names.
+//!
+//! Helpers that make more than one write (a lifecycle milestone) run them in
+//! a single transaction: either every write lands or none does, so a crash
+//! mid-milestone leaves the task in its previous state for restart recovery.
 
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use gator_db::models::TaskStatus;
+use gator_db::models::{TaskKind, TaskStatus};
+use gator_db::queries::agent_events::{self, NewAgentEvent};
+use gator_db::queries::{attestations, review_feedback};
 
 use super::TaskStateMachine;
+use crate::gate::attestation::{self, ATTESTATION_DIR};
+use crate::worktree::WorktreeManager;
 
 /// Assign a task to a harness and worktree.
 ///
@@ -50,6 +60,180 @@ pub async fn fail_task(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
     TaskStateMachine::transition(pool, task_id, TaskStatus::Checking, TaskStatus::Failed).await
 }
 
+/// Record an agent timeout: transition `running -> checking -> failed` and
+/// log an `error` event, atomically.
+///
+/// The agent produced no verdict, so the gate is skipped and the task goes
+/// straight to `failed` (retry eligibility is decided by the orchestrator).
+pub async fn time_out_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    timeout: Duration,
+) -> Result<()> {
+    let message = format!("agent timed out after {}s", timeout.as_secs());
+    fail_running_task(pool, task_id, attempt, &message).await
+}
+
+/// Record a stalled agent, one that went `idle` without emitting events or
+/// reporting progress: like [`time_out_task`], the task goes straight from
+/// `running` to `failed` with an `error` event.
+pub async fn stall_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    idle: Duration,
+) -> Result<()> {
+    let message = format!("agent stalled: no activity for {}s", idle.as_secs());
+    fail_running_task(pool, task_id, attempt, &message).await
+}
+
+/// Record a failed setup command: the agent never started, so the task goes
+/// from `assigned` through `running` and `checking` to `failed`, with an
+/// `error` event, atomically. Retry eligibility is decided by the
+/// orchestrator, as for a failed gate.
+pub async fn fail_setup(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    message: &str,
+) -> Result<()> {
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Assigned, TaskStatus::Running)
+        .await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Running, TaskStatus::Checking)
+        .await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Checking, TaskStatus::Failed)
+        .await?;
+    agent_events::insert_agent_event(
+        &mut *tx,
+        &NewAgentEvent {
+            task_id,
+            attempt,
+            event_type: "error".to_string(),
+            payload: serde_json::json!({ "message": message }),
+        },
+    )
+    .await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to record setup failure of task {task_id}"))
+}
+
+async fn fail_running_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    message: &str,
+) -> Result<()> {
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Running, TaskStatus::Checking)
+        .await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Checking, TaskStatus::Failed)
+        .await?;
+    agent_events::insert_agent_event(
+        &mut *tx,
+        &NewAgentEvent {
+            task_id,
+            attempt,
+            event_type: "error".to_string(),
+            payload: serde_json::json!({ "message": message }),
+        },
+    )
+    .await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit failure of task {task_id}"))
+}
+
+/// An agent asks the operator a question: transition `running ->
+/// blocked_on_human` and record a `question` event, atomically.
+pub async fn ask_question(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    question: &str,
+) -> Result<()> {
+    let question = question.trim();
+    if question.is_empty() {
+        bail!("the question is empty");
+    }
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    TaskStateMachine::transition_on(
+        &mut tx,
+        task_id,
+        TaskStatus::Running,
+        TaskStatus::BlockedOnHuman,
+    )
+    .await?;
+    agent_events::insert_agent_event(
+        &mut *tx,
+        &NewAgentEvent {
+            task_id,
+            attempt,
+            event_type: "question".to_string(),
+            payload: serde_json::json!({ "question": question }),
+        },
+    )
+    .await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit question of task {task_id}"))
+}
+
+/// Operator answer: record an `answer` event for a `blocked_on_human` task
+/// and transition it back to `running`, atomically. The lifecycle then
+/// resumes the agent's session with the answer.
+pub async fn answer_question(pool: &SqlitePool, task_id: Uuid, answer: &str) -> Result<()> {
+    let task = gator_db::queries::tasks::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+
+    if task.status != TaskStatus::BlockedOnHuman {
+        bail!(
+            "task {} is {}, must be blocked_on_human to answer",
+            task_id,
+            task.status
+        );
+    }
+    let answer = answer.trim();
+    if answer.is_empty() {
+        bail!("the answer is empty");
+    }
+
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    agent_events::insert_agent_event(
+        &mut *tx,
+        &NewAgentEvent {
+            task_id,
+            attempt: task.attempt,
+            event_type: "answer".to_string(),
+            payload: serde_json::json!({ "answer": answer }),
+        },
+    )
+    .await?;
+    TaskStateMachine::transition_on(
+        &mut tx,
+        task_id,
+        TaskStatus::BlockedOnHuman,
+        TaskStatus::Running,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit answer for task {task_id}"))
+}
+
+/// The question a task's attempt asked last, if any.
+pub async fn latest_question(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Option<String>> {
+    let event = agent_events::get_latest_event_of_type(pool, task_id, attempt, "question").await?;
+    Ok(event.and_then(|e| e.payload["question"].as_str().map(str::to_string)))
+}
+
 /// Retry a failed task: transition `failed -> assigned`.
 ///
 /// Increments the attempt counter. Fails if `attempt >= retry_max`.
@@ -84,10 +268,139 @@ pub async fn approve_task(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
     TaskStateMachine::transition(pool, task_id, TaskStatus::Checking, TaskStatus::Passed).await
 }
 
+/// Outcome of [`approve_task_paths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialApproval {
+    /// Changed paths that were committed.
+    pub committed: Vec<String>,
+    /// Changed paths that were reverted.
+    pub discarded: Vec<String>,
+}
+
+/// Operator partial approval: commit only the changes under `paths` in a
+/// `checking` task's worktree, discard the rest, and transition the task to
+/// `passed`.
+///
+/// Each entry of `paths` must be a changed file or a directory containing
+/// one, relative to the worktree root; nothing is touched otherwise.
+///
+/// If the attempt was attested, it is attested again over the approved
+/// change alone, and the new attestation file is committed with it.
+pub async fn approve_task_paths(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    paths: &[String],
+) -> Result<PartialApproval> {
+    let task = gator_db::queries::tasks::get_task(pool, task_id)
+        .await?
+        .with_context(|| format!("task {task_id} not found"))?;
+
+    if task.status != TaskStatus::Checking {
+        bail!(
+            "task {} is {}, must be checking to approve",
+            task_id,
+            task.status
+        );
+    }
+    if task.kind == TaskKind::Artifact {
+        bail!("task {task_id} is an artifact task; approve it without --files");
+    }
+    let worktree = task
+        .worktree_path
+        .as_deref()
+        .map(Path::new)
+        .with_context(|| format!("task {task_id} has no worktree"))?;
+    let plan = gator_db::queries::plans::get_plan(pool, task.plan_id)
+        .await?
+        .with_context(|| format!("plan {} not found", task.plan_id))?;
+    let manager = WorktreeManager::new(&plan.project_path, None)?;
+
+    let attestation_files = format!("{ATTESTATION_DIR}/");
+    let mut changed = manager.changed_paths(worktree)?;
+    changed.retain(|path| !path.starts_with(&attestation_files));
+    let approval = split_approved_paths(&changed, paths)?;
+
+    let mut committed = approval.committed.clone();
+    let attested = attestations::list_attestations_for_task(pool, task_id, Some(task.attempt))
+        .await?
+        .pop();
+    if let Some(attested) = attested {
+        // Reduce the worktree to the approved change before hashing it.
+        manager.stage_paths(worktree, &approval.committed)?;
+        manager.discard_unstaged(worktree)?;
+        attestation::attest(
+            pool,
+            &task,
+            &attested.harness,
+            attested.harness_version.as_deref(),
+            &attested.prompt_sha256,
+            worktree,
+        )
+        .await
+        .with_context(|| format!("failed to attest the approved change of task {task_id}"))?;
+        committed.push(ATTESTATION_DIR.to_string());
+    }
+
+    let message = format!(
+        "gator: {} (attempt {}, partially approved)",
+        task.name, task.attempt
+    );
+    manager.commit_paths(worktree, &committed, &message)?;
+    manager.discard_changes(worktree)?;
+
+    TaskStateMachine::transition(pool, task_id, TaskStatus::Checking, TaskStatus::Passed).await?;
+    Ok(approval)
+}
+
+/// Split `changed` into approved and discarded paths. Fails if `paths` is
+/// empty or an entry matches no changed path.
+fn split_approved_paths(changed: &[String], paths: &[String]) -> Result<PartialApproval> {
+    if paths.is_empty() {
+        bail!("no paths to approve");
+    }
+    let approved: Vec<&str> = paths
+        .iter()
+        .map(|p| p.trim().trim_start_matches("./").trim_end_matches('/'))
+        .collect();
+    let covers = |pattern: &str, path: &str| {
+        path == pattern
+            || path
+                .strip_prefix(pattern)
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+
+    for pattern in &approved {
+        if pattern.is_empty() || pattern.starts_with('/') || pattern.split('/').any(|c| c == "..") {
+            bail!("{pattern:?} is not a path inside the worktree");
+        }
+        if !changed.iter().any(|c| covers(pattern, c)) {
+            bail!(
+                "{pattern} has no changes in the worktree; changed: {}",
+                if changed.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    changed.join(", ")
+                }
+            );
+        }
+    }
+
+    let (committed, discarded) = changed
+        .iter()
+        .cloned()
+        .partition(|c| approved.iter().any(|p| covers(p, c)));
+    Ok(PartialApproval {
+        committed,
+        discarded,
+    })
+}
+
 /// Operator rejection: transition a `checking` task to `failed`.
 ///
-/// The task can then be retried or escalated.
-pub async fn reject_task(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
+/// The task can then be retried or escalated. A `reason` is recorded as
+/// review feedback for the current attempt (in the same transaction) and
+/// quoted in the next attempt's prompt.
+pub async fn reject_task(pool: &SqlitePool, task_id: Uuid, reason: Option<&str>) -> Result<()> {
     let task = gator_db::queries::tasks::get_task(pool, task_id)
         .await?
         .with_context(|| format!("task {task_id} not found"))?;
@@ -100,18 +413,57 @@ pub async fn reject_task(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
         );
     }
 
-    TaskStateMachine::transition(pool, task_id, TaskStatus::Checking, TaskStatus::Failed).await
+    let Some(reason) = reason.map(str::trim).filter(|r| !r.is_empty()) else {
+        return TaskStateMachine::transition(
+            pool,
+            task_id,
+            TaskStatus::Checking,
+            TaskStatus::Failed,
+        )
+        .await;
+    };
+
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    TaskStateMachine::transition_on(&mut tx, task_id, TaskStatus::Checking, TaskStatus::Failed)
+        .await?;
+    review_feedback::insert_review_feedback(&mut *tx, task_id, task.attempt, reason).await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit rejection of task {task_id}"))
 }
 
 /// Operator retry: reset a failed or escalated task back to pending.
 ///
 /// For `failed` tasks: respects retry_max unless `force` is true.
 /// For `escalated` tasks: always allowed (operator override).
-pub async fn operator_retry_task(pool: &SqlitePool, task_id: Uuid, force: bool) -> Result<()> {
+///
+/// A non-empty `env` is set for the agent and gate of the attempt the retry
+/// starts, and of no later one. A `description` different from the task's
+/// replaces it from that attempt on, keeping the old one as a revision.
+pub async fn operator_retry_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    force: bool,
+    env: &BTreeMap<String, String>,
+    description: Option<&str>,
+) -> Result<()> {
     let task = gator_db::queries::tasks::get_task(pool, task_id)
         .await?
         .with_context(|| format!("task {task_id} not found"))?;
 
+    let mut tx = gator_db::pool::begin_immediate(pool).await?;
+    if !env.is_empty() {
+        gator_db::queries::tasks::set_retry_env(&mut *tx, task_id, task.attempt, env).await?;
+    }
+    if let Some(description) = description.filter(|d| *d != task.description) {
+        gator_db::queries::task_revisions::revise_task_description(
+            &mut tx,
+            task_id,
+            task.attempt + 1,
+            description,
+        )
+        .await?;
+    }
     match task.status {
         TaskStatus::Failed => {
             if !force && task.attempt >= task.retry_max {
@@ -122,16 +474,20 @@ pub async fn operator_retry_task(pool: &SqlitePool, task_id: Uuid, force: bool)
                     task.retry_max
                 );
             }
-            let rows = gator_db::queries::tasks::retry_task_to_pending(pool, task_id, task.attempt)
-                .await?;
+            let rows =
+                gator_db::queries::tasks::retry_task_to_pending(&mut *tx, task_id, task.attempt)
+                    .await?;
             if rows == 0 {
                 bail!("optimistic lock failed on retry for task {}", task_id);
             }
         }
         TaskStatus::Escalated => {
-            let rows =
-                gator_db::queries::tasks::retry_escalated_to_pending(pool, task_id, task.attempt)
-                    .await?;
+            let rows = gator_db::queries::tasks::retry_escalated_to_pending(
+                &mut *tx,
+                task_id,
+                task.attempt,
+            )
+            .await?;
             if rows == 0 {
                 bail!(
                     "optimistic lock failed on retry-from-escalated for task {}",
@@ -147,6 +503,30 @@ pub async fn operator_retry_task(pool: &SqlitePool, task_id: Uuid, force: bool)
             );
         }
     }
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to commit retry of task {task_id}"))
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_approved_paths_by_file_and_directory() {
+        let changed = paths(&["src/a.rs", "src/b.rs", "src/bin/x.rs", "README.md"]);
+
+        let approval = split_approved_paths(&changed, &paths(&["./src/a.rs", "src/bin/"])).unwrap();
+        assert_eq!(approval.committed, paths(&["src/a.rs", "src/bin/x.rs"]));
+        assert_eq!(approval.discarded, paths(&["src/b.rs", "README.md"]));
+
+        // `src/b` is not a directory prefix of `src/b.rs`.
+        assert!(split_approved_paths(&changed, &paths(&["src/b"])).is_err());
+        assert!(split_approved_paths(&changed, &paths(&["../etc/passwd"])).is_err());
+        assert!(split_approved_paths(&changed, &[]).is_err());
+    }
 }
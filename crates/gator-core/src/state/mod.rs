@@ -3,6 +3,10 @@
 //! Validates and executes state transitions for tasks, enforcing the
 //! allowed transition graph, optimistic locking, timestamp management,
 //! and retry limits.
+//!
+//! Every transition has a `*_on` variant that runs on a caller-supplied
+//! connection, so multi-step milestones in [`dispatch`] can compose them
+//! inside a single transaction.
 
 pub mod dispatch;
 pub mod queries;
@@ -11,7 +15,7 @@ use std::path::Path;
 
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
 use gator_db::models::TaskStatus;
@@ -25,11 +29,14 @@ use gator_db::queries::tasks as db;
 /// pending   -> assigned
 /// assigned  -> running
 /// running   -> checking
+/// running   -> blocked_on_human  (agent asks a question)
+/// blocked_on_human -> running    (operator answers)
 /// checking  -> passed
 /// checking  -> failed
 /// failed    -> assigned  (retry)
 /// failed    -> escalated
 /// escalated -> pending   (operator retry override)
+/// any but passed -> cancelled  (operator cancel)
 /// ```
 pub struct TaskStateMachine;
 
@@ -42,11 +49,23 @@ impl TaskStateMachine {
             (TaskStatus::Pending, TaskStatus::Assigned)
                 | (TaskStatus::Assigned, TaskStatus::Running)
                 | (TaskStatus::Running, TaskStatus::Checking)
+                | (TaskStatus::Running, TaskStatus::BlockedOnHuman)
+                | (TaskStatus::BlockedOnHuman, TaskStatus::Running)
                 | (TaskStatus::Checking, TaskStatus::Passed)
                 | (TaskStatus::Checking, TaskStatus::Failed)
                 | (TaskStatus::Failed, TaskStatus::Assigned)
                 | (TaskStatus::Failed, TaskStatus::Escalated)
                 | (TaskStatus::Escalated, TaskStatus::Pending)
+                | (
+                    TaskStatus::Pending
+                        | TaskStatus::Assigned
+                        | TaskStatus::Running
+                        | TaskStatus::BlockedOnHuman
+                        | TaskStatus::Checking
+                        | TaskStatus::Failed
+                        | TaskStatus::Escalated,
+                    TaskStatus::Cancelled
+                )
         )
     }
 
@@ -55,7 +74,7 @@ impl TaskStateMachine {
     /// - Validates the transition is legal.
     /// - Sets `started_at` when transitioning `assigned -> running`.
     /// - Sets `completed_at` when transitioning to `passed`, `failed`,
-    ///   or `escalated`.
+    ///   `escalated`, or `cancelled`.
     /// - For `failed -> assigned` (retry), delegates to
     ///   [`Self::retry_transition`] which also increments the attempt
     ///   counter.
@@ -70,6 +89,20 @@ impl TaskStateMachine {
         task_id: Uuid,
         from: TaskStatus,
         to: TaskStatus,
+    ) -> Result<()> {
+        let mut conn = pool
+            .acquire()
+            .await
+            .context("failed to acquire database connection")?;
+        Self::transition_on(&mut conn, task_id, from, to).await
+    }
+
+    /// [`Self::transition`] on an existing connection or transaction.
+    pub async fn transition_on(
+        conn: &mut SqliteConnection,
+        task_id: Uuid,
+        from: TaskStatus,
+        to: TaskStatus,
     ) -> Result<()> {
         if !Self::is_valid_transition(from, to) {
             bail!(
@@ -82,7 +115,7 @@ impl TaskStateMachine {
 
         // Retry is special: it increments the attempt counter.
         if from == TaskStatus::Failed && to == TaskStatus::Assigned {
-            return Self::retry_transition(pool, task_id).await;
+            return Self::retry_transition(conn, task_id).await;
         }
 
         let started_at = if from == TaskStatus::Assigned && to == TaskStatus::Running {
@@ -92,22 +125,26 @@ impl TaskStateMachine {
         };
 
         let completed_at = match to {
-            TaskStatus::Passed | TaskStatus::Failed | TaskStatus::Escalated => Some(Utc::now()),
+            TaskStatus::Passed
+            | TaskStatus::Failed
+            | TaskStatus::Escalated
+            | TaskStatus::Cancelled => Some(Utc::now()),
             _ => None,
         };
 
-        let rows = db::transition_task_status(pool, task_id, from, to, started_at, completed_at)
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to transition task {} from {} to {}",
-                    task_id, from, to
-                )
-            })?;
+        let rows =
+            db::transition_task_status(&mut *conn, task_id, from, to, started_at, completed_at)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to transition task {} from {} to {}",
+                        task_id, from, to
+                    )
+                })?;
 
         if rows == 0 {
             // Either the task does not exist or the status did not match.
-            let task = db::get_task(pool, task_id).await?;
+            let task = db::get_task(&mut *conn, task_id).await?;
             match task {
                 None => bail!("task {} not found", task_id),
                 Some(t) => bail!(
@@ -126,8 +163,8 @@ impl TaskStateMachine {
     ///
     /// Fetches the task to check the attempt counter against `retry_max`,
     /// then atomically increments the attempt and resets the status.
-    async fn retry_transition(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
-        let task = db::get_task(pool, task_id)
+    async fn retry_transition(conn: &mut SqliteConnection, task_id: Uuid) -> Result<()> {
+        let task = db::get_task(&mut *conn, task_id)
             .await?
             .with_context(|| format!("task {} not found", task_id))?;
 
@@ -148,7 +185,7 @@ impl TaskStateMachine {
             );
         }
 
-        let rows = db::transition_task_retry(pool, task_id, task.attempt).await?;
+        let rows = db::transition_task_retry(&mut *conn, task_id, task.attempt).await?;
 
         if rows == 0 {
             bail!(
@@ -163,10 +200,19 @@ impl TaskStateMachine {
 
     /// Validate that all dependencies of a task are in `passed` status.
     pub async fn check_dependencies(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
-        let dep_ids = db::get_task_dependencies(pool, task_id).await?;
+        let mut conn = pool
+            .acquire()
+            .await
+            .context("failed to acquire database connection")?;
+        Self::check_dependencies_on(&mut conn, task_id).await
+    }
+
+    /// [`Self::check_dependencies`] on an existing connection or transaction.
+    pub async fn check_dependencies_on(conn: &mut SqliteConnection, task_id: Uuid) -> Result<()> {
+        let dep_ids = db::get_task_dependencies(&mut *conn, task_id).await?;
 
         for dep_id in dep_ids {
-            let dep = db::get_task(pool, dep_id)
+            let dep = db::get_task(&mut *conn, dep_id)
                 .await?
                 .with_context(|| format!("dependency task {} not found", dep_id))?;
 
@@ -186,14 +232,22 @@ impl TaskStateMachine {
 
     /// Assign a task: validate dependencies, set harness/worktree metadata,
     /// and transition `pending -> assigned`.
+    ///
+    /// All three steps run in one transaction, so a failed transition (or a
+    /// crash) never leaves metadata written on a task that was not assigned.
     pub async fn assign_task(
         pool: &SqlitePool,
         task_id: Uuid,
         harness: &str,
         worktree_path: &Path,
     ) -> Result<()> {
-        Self::check_dependencies(pool, task_id).await?;
-        db::assign_task_metadata(pool, task_id, harness, &worktree_path.to_string_lossy()).await?;
-        Self::transition(pool, task_id, TaskStatus::Pending, TaskStatus::Assigned).await
+        let mut tx = gator_db::pool::begin_immediate(pool).await?;
+        Self::check_dependencies_on(&mut tx, task_id).await?;
+        db::assign_task_metadata(&mut *tx, task_id, harness, &worktree_path.to_string_lossy())
+            .await?;
+        Self::transition_on(&mut tx, task_id, TaskStatus::Pending, TaskStatus::Assigned).await?;
+        tx.commit()
+            .await
+            .with_context(|| format!("failed to commit assignment of task {}", task_id))
     }
 }
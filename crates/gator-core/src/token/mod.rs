@@ -1,19 +1,64 @@
 //! Scoped token generation and validation for agent-mode authentication.
 //!
 //! Tokens are HMAC-SHA256 based, scoped to a (task_id, attempt) pair.
-//! Format: `gator_at_<task_id>_<attempt>_<hmac_hex>`
+//! Two formats exist:
+//!
+//! - v2 (issued): `gator_at2_<base64url(task_id || attempt || mac)>`, where
+//!   `task_id` is the 16 raw UUID bytes, `attempt` is 4 bytes big-endian,
+//!   and `mac` is the first 16 bytes of HMAC-SHA256 over
+//!   `"gator-token-v2:" || task_id || attempt`. 58 characters in total.
+//! - v1 (legacy): `gator_at_<task_id>_<attempt>_<hmac_hex>`, about 120
+//!   characters. Still accepted by default so agents started before an
+//!   upgrade keep working; see [`TokenConfig::accept_v1`].
+//!
+//! The v2 MAC is truncated to 128 bits. Forging a token means guessing
+//! the MAC for a chosen (task, attempt): a 2^-128 chance per try, with every
+//! try an online request against the secret. NIST SP 800-107 permits
+//! HMAC truncation down to 64 bits for this kind of use; 128 bits leaves a
+//! wide margin while halving the token length.
+//!
+//! A [`TokenConfig`] holds one active secret, used to sign, plus any
+//! previous secrets kept after a rotation; validation accepts a token
+//! signed by any of them. With per-plan secrets enabled, each plan's tokens
+//! are signed by a key derived from the configured one, and only that
+//! derived key is handed to the plan's agents.
 
 pub mod guard;
 
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use uuid::Uuid;
 
 type HmacSha256 = Hmac<Sha256>;
 
-/// Token prefix used to identify gator agent tokens.
+/// Token prefix used to identify v1 gator agent tokens.
 const TOKEN_PREFIX: &str = "gator_at_";
 
+/// Token prefix used to identify v2 gator agent tokens.
+const TOKEN_V2_PREFIX: &str = "gator_at2_";
+
+/// Domain separator for v2 MACs, so a v2 MAC is never valid for a v1
+/// message or vice versa.
+const V2_MAC_CONTEXT: &[u8] = b"gator-token-v2:";
+
+/// Bytes of HMAC-SHA256 kept in a v2 token.
+const V2_MAC_LEN: usize = 16;
+
+/// Decoded v2 payload length: UUID, attempt, truncated MAC.
+const V2_PAYLOAD_LEN: usize = 16 + 4 + V2_MAC_LEN;
+
+/// Base64url length of the v2 payload (36 bytes, no padding needed).
+const V2_ENCODED_LEN: usize = V2_PAYLOAD_LEN / 3 * 4;
+
+/// Longest token accepted: prefix, UUID, `u32::MAX`, two separators, and a
+/// 64-char HMAC come to 120 bytes; anything much longer is not a token.
+const MAX_TOKEN_LEN: usize = 128;
+
+/// Length of the hex-encoded HMAC-SHA256.
+const HMAC_HEX_LEN: usize = 64;
+
 /// Errors that can occur during token operations.
 #[derive(Debug, thiserror::Error)]
 pub enum TokenError {
@@ -29,35 +74,158 @@ pub enum TokenError {
     #[error("token HMAC verification failed")]
     HmacMismatch,
 
+    #[error("v1 tokens are no longer accepted (GATOR_TOKEN_ACCEPT_V1=0)")]
+    LegacyRejected,
+
     #[error("missing token secret")]
     MissingSecret,
 }
 
+/// ID of the active secret when none is configured.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Domain separator for per-plan key derivation.
+const PLAN_KEY_CONTEXT: &[u8] = b"gator-plan-secret:";
+
+/// A secret that is no longer used for signing but still validates tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenKey {
+    /// Identifier shown by `gator secret list`; never part of the token.
+    pub id: String,
+    /// The HMAC secret key bytes.
+    pub secret: Vec<u8>,
+}
+
 /// Configuration for token generation and validation.
 #[derive(Debug, Clone)]
 pub struct TokenConfig {
-    /// The HMAC secret key bytes.
+    /// The HMAC secret key bytes of the active (signing) secret.
     pub secret: Vec<u8>,
+    /// Identifier of the active secret.
+    pub key_id: String,
+    /// Secrets retired by rotation that still validate tokens, newest first.
+    pub previous: Vec<TokenKey>,
+    /// Sign each plan's tokens with a key derived from the secret and the
+    /// plan ID (see [`TokenConfig::for_plan`]).
+    pub per_plan: bool,
+    /// Whether [`validate_token`] still accepts v1 tokens. On by default for
+    /// the migration window; turn off once no pre-v2 attempts are running.
+    pub accept_v1: bool,
 }
 
 impl TokenConfig {
-    /// Create a new TokenConfig with the given secret.
+    /// Create a new TokenConfig with the given secret, accepting v1 tokens.
     pub fn new(secret: Vec<u8>) -> Self {
-        Self { secret }
+        Self {
+            secret,
+            key_id: DEFAULT_KEY_ID.to_string(),
+            previous: Vec::new(),
+            per_plan: false,
+            accept_v1: true,
+        }
+    }
+
+    /// Set whether v1 tokens are accepted.
+    pub fn with_accept_v1(mut self, accept_v1: bool) -> Self {
+        self.accept_v1 = accept_v1;
+        self
+    }
+
+    /// Set the identifier of the active secret.
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// Set the previous secrets that still validate tokens.
+    pub fn with_previous(mut self, previous: Vec<TokenKey>) -> Self {
+        self.previous = previous;
+        self
+    }
+
+    /// Set whether tokens are signed with per-plan derived keys.
+    pub fn with_per_plan(mut self, per_plan: bool) -> Self {
+        self.per_plan = per_plan;
+        self
+    }
+
+    /// The config to sign and validate a plan's tokens with.
+    ///
+    /// Without per-plan secrets this is a copy of `self`. With them, every
+    /// secret (active and previous) is replaced by
+    /// `HMAC-SHA256(secret, "gator-plan-secret:" || plan_id)`, so an agent
+    /// holding its plan's key cannot mint tokens for another plan.
+    pub fn for_plan(&self, plan_id: Uuid) -> TokenConfig {
+        if !self.per_plan {
+            return self.clone();
+        }
+        let derive =
+            |secret: &[u8]| compute_hmac(secret, &[PLAN_KEY_CONTEXT, plan_id.as_bytes()].concat());
+        TokenConfig {
+            secret: derive(&self.secret),
+            key_id: self.key_id.clone(),
+            previous: self
+                .previous
+                .iter()
+                .map(|k| TokenKey {
+                    id: k.id.clone(),
+                    secret: derive(&k.secret),
+                })
+                .collect(),
+            per_plan: false,
+            accept_v1: self.accept_v1,
+        }
+    }
+
+    /// All secrets that validate tokens: the active one first.
+    fn validation_secrets(&self) -> impl Iterator<Item = &[u8]> {
+        std::iter::once(self.secret.as_slice())
+            .chain(self.previous.iter().map(|k| k.secret.as_slice()))
     }
 
     /// Create a TokenConfig from the `GATOR_TOKEN_SECRET` environment variable.
     ///
     /// The value must be a hex-encoded string (as written by `gator init`
     /// and forwarded by the orchestrator). Returns an error if the variable
-    /// is missing or contains invalid hex.
+    /// is missing or contains invalid hex. `GATOR_TOKEN_ACCEPT_V1=0`
+    /// disables v1 tokens.
     pub fn from_env() -> Result<Self, TokenError> {
         let secret_hex =
             std::env::var("GATOR_TOKEN_SECRET").map_err(|_| TokenError::MissingSecret)?;
         let secret = hex::decode(&secret_hex).map_err(|e| {
             TokenError::InvalidFormat(format!("GATOR_TOKEN_SECRET is not valid hex: {e}"))
         })?;
-        Ok(Self::new(secret))
+        let mut config = Self::new(secret).with_accept_v1(accept_v1_from_env().unwrap_or(true));
+        if let Ok(key_id) = std::env::var("GATOR_TOKEN_KEY_ID") {
+            config.key_id = key_id;
+        }
+        Ok(config)
+    }
+}
+
+/// Read `GATOR_TOKEN_ACCEPT_V1`, if set: `0`/`false`/`no` disable v1 tokens,
+/// anything else enables them.
+pub fn accept_v1_from_env() -> Option<bool> {
+    std::env::var("GATOR_TOKEN_ACCEPT_V1")
+        .ok()
+        .map(|v| !matches!(v.trim(), "0" | "false" | "no"))
+}
+
+/// Token format version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenVersion {
+    /// Legacy `gator_at_<uuid>_<attempt>_<hex>` tokens.
+    V1,
+    /// Compact base64url tokens with a truncated MAC.
+    V2,
+}
+
+impl std::fmt::Display for TokenVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        })
     }
 }
 
@@ -68,13 +236,28 @@ pub struct TokenClaims {
     pub task_id: Uuid,
     /// The attempt number this token is scoped to.
     pub attempt: u32,
+    /// Format the token was presented in.
+    pub version: TokenVersion,
 }
 
 /// Generate a scoped agent token for a given task and attempt.
 ///
-/// The token format is: `gator_at_<task_id>_<attempt>_<hmac_hex>`
-/// where the HMAC-SHA256 is computed over `<task_id>:<attempt>`.
+/// Tokens are issued in the v2 format (see the module docs).
 pub fn generate_token(config: &TokenConfig, task_id: Uuid, attempt: u32) -> String {
+    let mut payload = Vec::with_capacity(V2_PAYLOAD_LEN);
+    payload.extend_from_slice(task_id.as_bytes());
+    payload.extend_from_slice(&attempt.to_be_bytes());
+    let mac = compute_hmac(&config.secret, &v2_mac_message(&payload));
+    payload.extend_from_slice(&mac[..V2_MAC_LEN]);
+    format!("{TOKEN_V2_PREFIX}{}", URL_SAFE_NO_PAD.encode(payload))
+}
+
+/// Generate a token in the legacy v1 format.
+///
+/// The format is: `gator_at_<task_id>_<attempt>_<hmac_hex>`
+/// where the HMAC-SHA256 is computed over `<task_id>:<attempt>`.
+/// Only needed to exercise v1 validation during the migration window.
+pub fn generate_token_v1(config: &TokenConfig, task_id: Uuid, attempt: u32) -> String {
     let message = format!("{task_id}:{attempt}");
     let mac = compute_hmac(&config.secret, message.as_bytes());
     let hmac_hex = hex::encode(mac);
@@ -84,11 +267,75 @@ pub fn generate_token(config: &TokenConfig, task_id: Uuid, attempt: u32) -> Stri
 /// Validate a scoped agent token and extract its claims.
 ///
 /// This function:
-/// 1. Parses the token format
+/// 1. Detects the format from the prefix and parses it
 /// 2. Recomputes the HMAC
 /// 3. Uses constant-time comparison to verify the HMAC
 /// 4. Returns the extracted claims on success
+///
+/// v1 tokens are accepted only while [`TokenConfig::accept_v1`] is set.
+/// Only the exact encoding produced by [`generate_token`] or
+/// [`generate_token_v1`] is accepted. Tokens arrive from agent
+/// environments, so oversized or otherwise malformed input is rejected
+/// before parsing.
 pub fn validate_token(config: &TokenConfig, token: &str) -> Result<TokenClaims, TokenError> {
+    if token.len() > MAX_TOKEN_LEN {
+        return Err(TokenError::InvalidFormat(format!(
+            "token longer than {MAX_TOKEN_LEN} bytes"
+        )));
+    }
+
+    if let Some(rest) = token.strip_prefix(TOKEN_V2_PREFIX) {
+        return validate_v2(config, rest);
+    }
+    if token.starts_with(TOKEN_PREFIX) {
+        if !config.accept_v1 {
+            return Err(TokenError::LegacyRejected);
+        }
+        return validate_v1(config, token);
+    }
+    Err(TokenError::InvalidFormat(
+        "token must start with 'gator_at2_' or 'gator_at_'".to_string(),
+    ))
+}
+
+/// Validate the base64url body of a v2 token.
+fn validate_v2(config: &TokenConfig, encoded: &str) -> Result<TokenClaims, TokenError> {
+    if encoded.len() != V2_ENCODED_LEN {
+        return Err(TokenError::InvalidFormat(format!(
+            "v2 token body must be {V2_ENCODED_LEN} characters"
+        )));
+    }
+    let payload = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| TokenError::InvalidFormat(format!("invalid base64url: {e}")))?;
+    let (claims, provided_mac) = payload.split_at(V2_PAYLOAD_LEN - V2_MAC_LEN);
+
+    let message = v2_mac_message(claims);
+    let verified = config.validation_secrets().any(|secret| {
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
+        mac.update(&message);
+        mac.verify_truncated_left(provided_mac).is_ok()
+    });
+    if !verified {
+        return Err(TokenError::HmacMismatch);
+    }
+
+    let (id_bytes, attempt_bytes) = claims.split_at(16);
+    Ok(TokenClaims {
+        task_id: Uuid::from_slice(id_bytes).expect("16 bytes"),
+        attempt: u32::from_be_bytes(attempt_bytes.try_into().expect("4 bytes")),
+        version: TokenVersion::V2,
+    })
+}
+
+/// The message a v2 MAC covers: the context string, then the encoded
+/// task ID and attempt.
+fn v2_mac_message(claims: &[u8]) -> Vec<u8> {
+    [V2_MAC_CONTEXT, claims].concat()
+}
+
+/// Validate a v1 token (`gator_at_<task_id>_<attempt>_<hmac_hex>`).
+fn validate_v1(config: &TokenConfig, token: &str) -> Result<TokenClaims, TokenError> {
     // Strip prefix
     let rest = token.strip_prefix(TOKEN_PREFIX).ok_or_else(|| {
         TokenError::InvalidFormat("token must start with 'gator_at_'".to_string())
@@ -101,6 +348,11 @@ pub fn validate_token(config: &TokenConfig, token: &str) -> Result<TokenClaims,
 
     let task_id =
         Uuid::parse_str(task_id_str).map_err(|e| TokenError::InvalidTaskId(e.to_string()))?;
+    if task_id.hyphenated().to_string() != task_id_str {
+        return Err(TokenError::InvalidTaskId(
+            "task ID must be a lowercase hyphenated UUID".to_string(),
+        ));
+    }
 
     // after_task_id should start with '_'
     let after_underscore = after_task_id.strip_prefix('_').ok_or_else(|| {
@@ -112,9 +364,17 @@ pub fn validate_token(config: &TokenConfig, token: &str) -> Result<TokenClaims,
         TokenError::InvalidFormat("expected underscore between attempt and hmac".to_string())
     })?;
 
-    let attempt: u32 = attempt_str
-        .parse()
-        .map_err(|e: std::num::ParseIntError| TokenError::InvalidAttempt(e.to_string()))?;
+    let attempt = parse_attempt(attempt_str)?;
+
+    if hmac_hex.len() != HMAC_HEX_LEN
+        || !hmac_hex
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return Err(TokenError::InvalidFormat(format!(
+            "hmac must be {HMAC_HEX_LEN} lowercase hex digits"
+        )));
+    }
 
     // Decode the provided HMAC
     let provided_mac = hex::decode(hmac_hex)
@@ -122,9 +382,18 @@ pub fn validate_token(config: &TokenConfig, token: &str) -> Result<TokenClaims,
 
     // Recompute and verify HMAC using constant-time comparison
     let message = format!("{task_id}:{attempt}");
-    verify_hmac_constant_time(&config.secret, message.as_bytes(), &provided_mac)?;
-
-    Ok(TokenClaims { task_id, attempt })
+    config
+        .validation_secrets()
+        .find_map(|secret| {
+            verify_hmac_constant_time(secret, message.as_bytes(), &provided_mac).ok()
+        })
+        .ok_or(TokenError::HmacMismatch)?;
+
+    Ok(TokenClaims {
+        task_id,
+        attempt,
+        version: TokenVersion::V1,
+    })
 }
 
 /// Parse a UUID from the beginning of a string.
@@ -136,7 +405,29 @@ fn parse_uuid_prefix(s: &str) -> Result<(&str, &str), TokenError> {
             "token too short to contain a valid UUID".to_string(),
         ));
     }
-    Ok(s.split_at(36))
+    // Byte 36 may fall inside a multi-byte character in hostile input.
+    s.split_at_checked(36).ok_or_else(|| {
+        TokenError::InvalidTaskId("task ID contains non-ASCII characters".to_string())
+    })
+}
+
+/// Parse the attempt number in its canonical decimal form.
+///
+/// `str::parse` also accepts `+1` and `01`; those would verify against the
+/// HMAC for `1`, letting one token have several spellings.
+fn parse_attempt(s: &str) -> Result<u32, TokenError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(TokenError::InvalidAttempt(format!(
+            "{s:?} is not a decimal number"
+        )));
+    }
+    if s.len() > 1 && s.starts_with('0') {
+        return Err(TokenError::InvalidAttempt(format!(
+            "{s:?} has leading zeros"
+        )));
+    }
+    s.parse()
+        .map_err(|e: std::num::ParseIntError| TokenError::InvalidAttempt(e.to_string()))
 }
 
 /// Compute HMAC-SHA256 over the given message with the given key.
@@ -170,12 +461,12 @@ mod tests {
     }
 
     #[test]
-    fn generate_token_has_correct_format() {
+    fn generate_token_v1_has_correct_format() {
         let config = test_config();
         let task_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
         let attempt = 1;
 
-        let token = generate_token(&config, task_id, attempt);
+        let token = generate_token_v1(&config, task_id, attempt);
 
         assert!(
             token.starts_with("gator_at_"),
@@ -257,7 +548,7 @@ mod tests {
     fn reject_tampered_task_id() {
         let config = test_config();
         let task_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
-        let token = generate_token(&config, task_id, 1);
+        let token = generate_token_v1(&config, task_id, 1);
 
         // Replace task_id in the token with a different one
         let other_id = Uuid::parse_str("660e8400-e29b-41d4-a716-446655440000").unwrap();
@@ -274,7 +565,7 @@ mod tests {
     fn reject_tampered_attempt() {
         let config = test_config();
         let task_id = Uuid::new_v4();
-        let token = generate_token(&config, task_id, 1);
+        let token = generate_token_v1(&config, task_id, 1);
 
         // Replace _1_ with _2_ in the token (after the UUID)
         let prefix_and_uuid = &token[..TOKEN_PREFIX.len() + 36];
@@ -399,7 +690,7 @@ mod tests {
         // verify_slice for constant-time comparison).
         let config = test_config();
         let task_id = Uuid::new_v4();
-        let token = generate_token(&config, task_id, 1);
+        let token = generate_token_v1(&config, task_id, 1);
 
         // Valid token should succeed
         assert!(validate_token(&config, &token).is_ok());
@@ -424,6 +715,115 @@ mod tests {
         assert!(matches!(result.unwrap_err(), TokenError::HmacMismatch));
     }
 
+    #[test]
+    fn generate_token_v2_is_compact() {
+        let config = test_config();
+        let task_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+
+        let token = generate_token(&config, task_id, u32::MAX);
+
+        assert!(token.starts_with("gator_at2_"));
+        assert_eq!(token.len(), TOKEN_V2_PREFIX.len() + V2_ENCODED_LEN);
+        assert_eq!(token.len(), 58);
+        assert!(
+            token[TOKEN_V2_PREFIX.len()..]
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        );
+        let claims = validate_token(&config, &token).unwrap();
+        assert_eq!(claims.version, TokenVersion::V2);
+        assert_eq!(claims.attempt, u32::MAX);
+    }
+
+    #[test]
+    fn v1_tokens_accepted_during_migration_window() {
+        let config = test_config();
+        let task_id = Uuid::new_v4();
+        let legacy = generate_token_v1(&config, task_id, 2);
+
+        let claims = validate_token(&config, &legacy).unwrap();
+        assert_eq!(claims.version, TokenVersion::V1);
+        assert_eq!(claims.task_id, task_id);
+
+        let strict = test_config().with_accept_v1(false);
+        assert!(matches!(
+            validate_token(&strict, &legacy),
+            Err(TokenError::LegacyRejected)
+        ));
+        assert!(validate_token(&strict, &generate_token(&strict, task_id, 2)).is_ok());
+    }
+
+    #[test]
+    fn v2_rejects_wrong_length_and_foreign_mac() {
+        let config = test_config();
+        let task_id = Uuid::new_v4();
+        let token = generate_token(&config, task_id, 1);
+
+        let short = &token[..token.len() - 4];
+        assert!(matches!(
+            validate_token(&config, short),
+            Err(TokenError::InvalidFormat(_))
+        ));
+
+        // A v2 body carrying the v1 MAC (truncated) must not verify: the
+        // MACs cover different messages.
+        let v1_mac = compute_hmac(&config.secret, format!("{task_id}:1").as_bytes());
+        let mut payload = task_id.as_bytes().to_vec();
+        payload.extend_from_slice(&1u32.to_be_bytes());
+        payload.extend_from_slice(&v1_mac[..V2_MAC_LEN]);
+        let forged = format!("{TOKEN_V2_PREFIX}{}", URL_SAFE_NO_PAD.encode(payload));
+        assert!(matches!(
+            validate_token(&config, &forged),
+            Err(TokenError::HmacMismatch)
+        ));
+    }
+
+    #[test]
+    fn previous_secrets_still_validate() {
+        let old = test_config();
+        let task_id = Uuid::new_v4();
+        let old_v2 = generate_token(&old, task_id, 1);
+        let old_v1 = generate_token_v1(&old, task_id, 1);
+
+        let rotated = TokenConfig::new(b"rotated-secret".to_vec())
+            .with_key_id("k2")
+            .with_previous(vec![TokenKey {
+                id: "k1".to_string(),
+                secret: old.secret.clone(),
+            }]);
+        assert!(validate_token(&rotated, &old_v2).is_ok());
+        assert!(validate_token(&rotated, &old_v1).is_ok());
+        assert!(validate_token(&rotated, &generate_token(&rotated, task_id, 1)).is_ok());
+
+        // Once retired, the old secret's tokens stop validating.
+        let retired = rotated.with_previous(Vec::new());
+        assert!(matches!(
+            validate_token(&retired, &old_v2),
+            Err(TokenError::HmacMismatch)
+        ));
+    }
+
+    #[test]
+    fn per_plan_keys_are_isolated() {
+        let config = test_config().with_per_plan(true);
+        let (plan_a, plan_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let task_id = Uuid::new_v4();
+
+        let a = config.for_plan(plan_a);
+        let b = config.for_plan(plan_b);
+        assert_ne!(a.secret, config.secret);
+        assert_ne!(a.secret, b.secret);
+        assert_eq!(a.secret, config.for_plan(plan_a).secret);
+
+        let token = generate_token(&a, task_id, 0);
+        assert!(validate_token(&a, &token).is_ok());
+        assert!(validate_token(&b, &token).is_err());
+        assert!(validate_token(&config, &token).is_err());
+
+        // Without per-plan secrets the plan ID changes nothing.
+        assert_eq!(test_config().for_plan(plan_a).secret, config.secret);
+    }
+
     #[test]
     fn token_config_from_env_missing() {
         // Test that missing env var produces MissingSecret error
@@ -434,3 +834,84 @@ mod tests {
         assert!(matches!(result.unwrap_err(), TokenError::MissingSecret));
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn config() -> TokenConfig {
+        TokenConfig::new(b"proptest-secret".to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrips_any_task_and_attempt(id in any::<u128>(), attempt in any::<u32>()) {
+            let task_id = Uuid::from_u128(id);
+            let token = generate_token(&config(), task_id, attempt);
+            prop_assert!(token.len() <= MAX_TOKEN_LEN);
+            let claims = validate_token(&config(), &token).unwrap();
+            prop_assert_eq!(claims, TokenClaims { task_id, attempt, version: TokenVersion::V2 });
+
+            let legacy = generate_token_v1(&config(), task_id, attempt);
+            let claims = validate_token(&config(), &legacy).unwrap();
+            prop_assert_eq!(claims, TokenClaims { task_id, attempt, version: TokenVersion::V1 });
+        }
+
+        #[test]
+        fn arbitrary_input_never_panics(input in ".{0,200}") {
+            let _ = validate_token(&config(), &input);
+        }
+
+        #[test]
+        fn accepted_tokens_are_canonical(
+            id in any::<u128>(),
+            attempt in any::<u32>(),
+            pos in any::<prop::sample::Index>(),
+            replacement in any::<char>(),
+            v1 in any::<bool>(),
+        ) {
+            let generate = if v1 { generate_token_v1 } else { generate_token };
+            let token = generate(&config(), Uuid::from_u128(id), attempt);
+            let mut chars: Vec<char> = token.chars().collect();
+            let i = pos.index(chars.len());
+            prop_assume!(chars[i] != replacement);
+            chars[i] = replacement;
+            let mutated: String = chars.into_iter().collect();
+            prop_assert!(validate_token(&config(), &mutated).is_err());
+        }
+
+        #[test]
+        fn rejects_non_canonical_attempts(id in any::<u128>(), attempt in any::<u32>()) {
+            let task_id = Uuid::from_u128(id);
+            let token = generate_token_v1(&config(), task_id, attempt);
+            let padded = token.replacen(
+                &format!("{task_id}_{attempt}_"),
+                &format!("{task_id}_0{attempt}_"),
+                1,
+            );
+            prop_assert!(matches!(
+                validate_token(&config(), &padded),
+                Err(TokenError::InvalidAttempt(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_and_non_ascii_input() {
+        let huge = format!("gator_at_{}", "9".repeat(10_000));
+        assert!(matches!(
+            validate_token(&config(), &huge),
+            Err(TokenError::InvalidFormat(_))
+        ));
+
+        // Byte 36 after the prefix lands inside the 'é'.
+        let split = format!("gator_at_{}é_1_{}", "a".repeat(35), "0".repeat(64));
+        assert!(validate_token(&config(), &split).is_err());
+
+        let upper = generate_token_v1(&config(), Uuid::from_u128(0xABCDEF), 1).to_uppercase();
+        let upper = upper.replacen("GATOR_AT_", "gator_at_", 1);
+        assert!(validate_token(&config(), &upper).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Disk usage of worktrees: measuring them, the free space left on their
+//! filesystem, and human-readable sizes for quotas and cleanup policies.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+/// Bytes allocated on disk for everything under `path`, including `.git`
+/// files but not the repository objects they point to. On unix, a file
+/// reachable through several hard links is counted once; elsewhere file
+/// lengths stand in for allocated bytes. Symlinks are not followed.
+pub fn disk_usage(path: &Path) -> io::Result<u64> {
+    let mut seen = HashSet::new();
+    usage_of(path, &mut seen)
+}
+
+fn usage_of(path: &Path, seen: &mut HashSet<(u64, u64)>) -> io::Result<u64> {
+    let meta = path.symlink_metadata()?;
+    let mut total = allocated(&meta, seen);
+    if meta.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            match usage_of(&entry?.path(), seen) {
+                Ok(bytes) => total += bytes,
+                // Removed while we walked (e.g. a build cleaning up).
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Bytes allocated for one file, or 0 if another hard link to it was
+/// already counted.
+#[cfg(unix)]
+fn allocated(meta: &std::fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    if meta.nlink() > 1 && !seen.insert((meta.dev(), meta.ino())) {
+        return 0;
+    }
+    meta.blocks() * 512
+}
+
+/// Length of one file; hard links are not detected.
+#[cfg(not(unix))]
+fn allocated(meta: &std::fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> u64 {
+    meta.len()
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a
+    // properly sized, writable statvfs struct.
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Bytes available on the filesystem holding `path`.
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "free space is only known on unix",
+    ))
+}
+
+/// Parse a size such as `20G`, `512M`, `1.5T`, or `4096`. Units are powers
+/// of 1024 and may be spelled `G`, `GB`, or `GiB`; a bare number is bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size {s:?}: expected e.g. 20G or 512M"))?;
+    let shift = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return Err(format!("invalid size unit in {s:?}: use K, M, G, or T")),
+    };
+    Ok((number * (1u64 << shift) as f64) as u64)
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1, e.g.
+/// `1.5 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_parse_in_binary_units() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("20G"), Ok(20 << 30));
+        assert_eq!(parse_size("512mb"), Ok(512 << 20));
+        assert_eq!(parse_size("1.5 TiB"), Ok(3 << 39));
+        assert!(parse_size("G").is_err());
+        assert!(parse_size("10X").is_err());
+    }
+
+    #[test]
+    fn sizes_format_in_the_largest_unit() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(20 << 30), "20.0 GiB");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = vec![7u8; 64 * 1024];
+        std::fs::write(dir.path().join("a"), &data).unwrap();
+        let one = disk_usage(dir.path()).unwrap();
+        assert!(one >= data.len() as u64);
+
+        std::fs::hard_link(dir.path().join("a"), dir.path().join("b")).unwrap();
+        assert_eq!(disk_usage(dir.path()).unwrap(), one);
+
+        std::fs::write(dir.path().join("c"), &data).unwrap();
+        assert!(disk_usage(dir.path()).unwrap() >= one + data.len() as u64);
+        assert!(free_space(dir.path()).unwrap() > 0);
+    }
+}
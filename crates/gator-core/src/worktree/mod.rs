@@ -5,10 +5,13 @@
 //! share the object store of the main repository but have independent
 //! working directories and index files.
 
+pub mod disk;
+
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
 
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 /// Errors that can occur during worktree operations.
@@ -39,6 +42,14 @@ pub enum WorktreeError {
     #[error("worktree path exists but has unexpected branch: expected {expected}, found {found}")]
     BranchMismatch { expected: String, found: String },
 
+    /// The setup command of a template worktree failed.
+    #[error("template setup `{command}` failed (exit {code}): {stderr}")]
+    TemplateSetup {
+        command: String,
+        code: i32,
+        stderr: String,
+    },
+
     /// Failed to parse porcelain output from `git worktree list`.
     #[error("failed to parse worktree list output: {0}")]
     ParseError(String),
@@ -236,6 +247,258 @@ impl WorktreeManager {
         Ok(true)
     }
 
+    /// List the paths with uncommitted changes (tracked and untracked) in a
+    /// worktree, relative to its root. Untracked directories are expanded
+    /// to their files; a rename lists both the old and new path.
+    pub fn changed_paths(&self, worktree_path: &Path) -> Result<Vec<String>, WorktreeError> {
+        let output = Command::new("git")
+            .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git status".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "status".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+
+        parse_status_z(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Stage and commit only `paths` in a worktree, leaving every other
+    /// change uncommitted. Returns `Ok(false)` if the paths had nothing to
+    /// commit.
+    pub fn commit_paths(
+        &self,
+        worktree_path: &Path,
+        paths: &[String],
+        message: &str,
+    ) -> Result<bool, WorktreeError> {
+        self.stage_paths(worktree_path, paths)?;
+
+        // `diff --cached --quiet` exits 1 when something is staged.
+        let staged = Command::new("git")
+            .args(["diff", "--cached", "--quiet"])
+            .current_dir(worktree_path)
+            .status()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git diff --cached".into(),
+                source: e,
+            })?;
+        if staged.success() {
+            return Ok(false);
+        }
+
+        let output = Command::new("git")
+            .args(["commit", "-m", message])
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git commit".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "commit".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Stage every change under `paths` in a worktree, including deletions.
+    pub fn stage_paths(&self, worktree_path: &Path, paths: &[String]) -> Result<(), WorktreeError> {
+        let output = Command::new("git")
+            .args(["add", "-A", "--"])
+            .args(paths)
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git add".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "add".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+        Ok(())
+    }
+
+    /// Discard the unstaged changes in a worktree: restore tracked files
+    /// from the index and delete untracked files, so the worktree matches
+    /// what is staged. Ignored files are kept.
+    pub fn discard_unstaged(&self, worktree_path: &Path) -> Result<(), WorktreeError> {
+        for args in [&["checkout", "--", "."][..], &["clean", "-fd"][..]] {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(worktree_path)
+                .output()
+                .map_err(|e| WorktreeError::GitCommand {
+                    message: format!("failed to run git {}", args[0]),
+                    source: e,
+                })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                return Err(WorktreeError::GitExit {
+                    command: args.join(" "),
+                    code: output.status.code().unwrap_or(-1),
+                    stderr,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Discard every uncommitted change in a worktree: reset tracked files
+    /// to `HEAD` and delete untracked files. Ignored files are kept.
+    pub fn discard_changes(&self, worktree_path: &Path) -> Result<(), WorktreeError> {
+        for args in [&["reset", "--hard", "HEAD"][..], &["clean", "-fd"][..]] {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(worktree_path)
+                .output()
+                .map_err(|e| WorktreeError::GitCommand {
+                    message: format!("failed to run git {}", args[0]),
+                    source: e,
+                })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                return Err(WorktreeError::GitExit {
+                    command: args.join(" "),
+                    code: output.status.code().unwrap_or(-1),
+                    stderr,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Restrict a worktree to the files matching `patterns` (gitignore-style,
+    /// relative to the repo root) with a non-cone sparse checkout. The
+    /// setting is stored per worktree and does not affect the main checkout.
+    pub fn sparse_checkout(
+        &self,
+        worktree_path: &Path,
+        patterns: &[String],
+    ) -> Result<(), WorktreeError> {
+        // Enabling per-worktree config touches the shared repository config.
+        let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let output = Command::new("git")
+            .args(["sparse-checkout", "set", "--no-cone"])
+            .args(patterns)
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git sparse-checkout".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "sparse-checkout set".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+        Ok(())
+    }
+
+    /// List the paths changed in a worktree that match none of `patterns`:
+    /// uncommitted changes (tracked and untracked), plus files changed by
+    /// commits on the worktree's branch since it forked from `base_branch`.
+    /// Commits are not considered when `base_branch` does not exist.
+    pub fn changed_paths_outside(
+        &self,
+        worktree_path: &Path,
+        base_branch: &str,
+        patterns: &[String],
+    ) -> Result<Vec<String>, WorktreeError> {
+        let mut pathspecs = vec![".".to_string()];
+        pathspecs.extend(patterns.iter().map(|p| format!(":(exclude,glob){p}")));
+
+        let output = Command::new("git")
+            .args(["status", "--porcelain", "-z", "--untracked-files=all", "--"])
+            .args(&pathspecs)
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git status".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "status".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+        let mut paths = parse_status_z(&String::from_utf8_lossy(&output.stdout))?;
+
+        let base_exists = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{base_branch}^{{commit}}"))
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git rev-parse".into(),
+                source: e,
+            })?
+            .status
+            .success();
+        if base_exists {
+            let output = Command::new("git")
+                .args(["diff", "--name-only", "-z"])
+                .arg(format!("{base_branch}...HEAD"))
+                .arg("--")
+                .args(&pathspecs)
+                .current_dir(worktree_path)
+                .output()
+                .map_err(|e| WorktreeError::GitCommand {
+                    message: "failed to run git diff".into(),
+                    source: e,
+                })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                return Err(WorktreeError::GitExit {
+                    command: "diff".into(),
+                    code: output.status.code().unwrap_or(-1),
+                    stderr,
+                });
+            }
+            for path in String::from_utf8_lossy(&output.stdout).split('\0') {
+                if !path.is_empty() && !paths.iter().any(|p| p == path) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
     /// Create a new worktree with the given branch name.
     ///
     /// The worktree directory is placed under `worktree_base/<dir_name>`
@@ -337,6 +600,296 @@ impl WorktreeManager {
         self.find_worktree_by_path(&worktree_path)
     }
 
+    /// Path of the template worktree for `plan_name`. Task names never
+    /// start with `.`, so it cannot collide with a task worktree.
+    pub fn template_path(&self, plan_name: &str) -> PathBuf {
+        let plan = sanitize_ref_component(plan_name);
+        self.worktree_base.join(format!("gator--{plan}--.template"))
+    }
+
+    /// Prepare the template worktree for `plan_name`: a detached checkout of
+    /// the main repository's `HEAD` in which `setup` is run once with
+    /// `sh -c`. Later calls with the same `setup` return the existing
+    /// template; a template whose setup failed or changed is set up again.
+    pub fn prepare_template(&self, plan_name: &str, setup: &str) -> Result<PathBuf, WorktreeError> {
+        let path = self.template_path(plan_name);
+        let marker = template_marker(&path);
+        if path.exists() && std::fs::read_to_string(&marker).is_ok_and(|s| s == setup) {
+            return Ok(path);
+        }
+        let _ = std::fs::remove_file(&marker);
+
+        self.add_detached(&path, "HEAD")?;
+
+        tracing::info!(path = %path.display(), setup, "running template worktree setup");
+        let output = Command::new("sh")
+            .args(["-c", setup])
+            .current_dir(&path)
+            .output()
+            .map_err(|e| WorktreeError::TemplateSetup {
+                command: setup.to_string(),
+                code: -1,
+                stderr: e.to_string(),
+            })?;
+        if !output.status.success() {
+            return Err(WorktreeError::TemplateSetup {
+                command: setup.to_string(),
+                code: output.status.code().unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+
+        std::fs::write(&marker, setup).map_err(|e| WorktreeError::GitCommand {
+            message: format!("failed to write template marker: {}", marker.display()),
+            source: e,
+        })?;
+        Ok(path)
+    }
+
+    /// Check out `rev` detached at `path`, unless a worktree is already
+    /// registered there.
+    pub fn add_detached(&self, path: &Path, rev: &str) -> Result<(), WorktreeError> {
+        let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
+        if self.find_worktree_by_path(path).is_ok() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| WorktreeError::GitCommand {
+                message: format!("failed to create directory: {}", parent.display()),
+                source: e,
+            })?;
+        }
+        let output = Command::new("git")
+            .args(["worktree", "add", "--detach"])
+            .arg(path)
+            .arg(rev)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git worktree add --detach".into(),
+                source: e,
+            })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            self.cleanup_partial(path);
+            return Err(WorktreeError::GitExit {
+                command: "worktree add".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+        Ok(())
+    }
+
+    /// The commit `worktree_path`'s `HEAD` forked from `base_branch`, or
+    /// `None` when `base_branch` does not exist or shares no history.
+    pub fn merge_base(
+        &self,
+        worktree_path: &Path,
+        base_branch: &str,
+    ) -> Result<Option<String>, WorktreeError> {
+        let output = Command::new("git")
+            .args(["merge-base", "HEAD", base_branch])
+            .current_dir(worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git merge-base".into(),
+                source: e,
+            })?;
+        // Exit 1: no common ancestor; 128: unknown revision.
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!commit.is_empty()).then_some(commit))
+    }
+
+    /// Remove the template worktree of `plan_name`, if there is one.
+    pub fn remove_template(&self, plan_name: &str) -> Result<(), WorktreeError> {
+        let path = self.template_path(plan_name);
+        let _ = std::fs::remove_file(template_marker(&path));
+        self.remove_worktree(&path)
+    }
+
+    /// Create a worktree for `branch_name` as a copy-on-write clone of the
+    /// `template` worktree (reflink on Linux, `clonefile` on macOS), so it
+    /// starts with the template's build outputs at almost no disk or time
+    /// cost. The new branch starts at the template's `HEAD`.
+    ///
+    /// Falls back to [`create_worktree`](Self::create_worktree) when the
+    /// filesystem cannot clone, and when the worktree or branch already
+    /// exists (a retry keeps the branch's earlier commits). With
+    /// `link_outputs`, the fallback then hard-links the template's untracked
+    /// and ignored files (build outputs such as `target/` or
+    /// `node_modules/`) into the checkout; see
+    /// [`link_template_outputs`](Self::link_template_outputs).
+    pub fn create_worktree_from_template(
+        &self,
+        branch_name: &str,
+        template: &Path,
+        link_outputs: bool,
+    ) -> Result<WorktreeInfo, WorktreeError> {
+        self.create_worktree_with_copier(branch_name, template, clone_entries, link_outputs)
+    }
+
+    fn create_worktree_with_copier(
+        &self,
+        branch_name: &str,
+        template: &Path,
+        copy: fn(&[PathBuf], &Path) -> bool,
+        link_outputs: bool,
+    ) -> Result<WorktreeInfo, WorktreeError> {
+        if let Some(info) = self.clone_template(branch_name, template, copy)? {
+            return Ok(info);
+        }
+        let info = self.create_worktree(branch_name)?;
+        if link_outputs {
+            if let Err(e) = self.link_template_outputs(template, &info.path) {
+                // The checkout is still usable; the agent just builds from scratch.
+                tracing::warn!(
+                    template = %template.display(),
+                    worktree = %info.path.display(),
+                    error = %e,
+                    "failed to hard-link template build outputs"
+                );
+            }
+        }
+        Ok(info)
+    }
+
+    /// Hard-link the untracked and ignored files of `template` into the
+    /// worktree at `dest`, recreating directories and symlinks. Paths that
+    /// already exist in `dest` are left alone, so tracked files stay real
+    /// checkouts and a retry keeps its own outputs.
+    ///
+    /// Unlike a clone, a hard link shares the file with the template: a tool
+    /// that rewrites an output in place (instead of replacing it) changes it
+    /// for every worktree. Compilers and package managers write new files,
+    /// so this suits `target/` and `node_modules/`. The template and the
+    /// worktree must be on the same filesystem.
+    pub fn link_template_outputs(&self, template: &Path, dest: &Path) -> Result<(), WorktreeError> {
+        let stdout = git_stdout(template, &["ls-files", "-z", "--others", "--directory"])?;
+        for entry in String::from_utf8_lossy(&stdout)
+            .split('\0')
+            .filter(|e| !e.is_empty())
+        {
+            let entry = entry.trim_end_matches('/');
+            link_tree(&template.join(entry), &dest.join(entry)).map_err(|e| {
+                WorktreeError::GitCommand {
+                    message: format!("failed to hard-link {entry} from the template worktree"),
+                    source: e,
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Register a `--no-checkout` worktree at the template's `HEAD` and copy
+    /// the template's files into it. Returns `Ok(None)` when the caller
+    /// should create a normal worktree instead.
+    fn clone_template(
+        &self,
+        branch_name: &str,
+        template: &Path,
+        copy: fn(&[PathBuf], &Path) -> bool,
+    ) -> Result<Option<WorktreeInfo>, WorktreeError> {
+        let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let worktree_path = self.worktree_base.join(branch_name.replace('/', "--"));
+        if worktree_path.exists() || self.branch_exists(branch_name)? {
+            return Ok(None);
+        }
+
+        let output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(template)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git rev-parse".into(),
+                source: e,
+            })?;
+        if !output.status.success() {
+            return Err(WorktreeError::GitExit {
+                command: "rev-parse HEAD".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+        let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let entries = std::fs::read_dir(template)
+            .and_then(|dir| {
+                dir.map(|entry| entry.map(|e| e.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| WorktreeError::GitCommand {
+                message: format!("failed to read template worktree: {}", template.display()),
+                source: e,
+            })?
+            .into_iter()
+            .filter(|p| p.file_name().is_some_and(|n| n != ".git"))
+            .collect::<Vec<_>>();
+
+        let output = Command::new("git")
+            .args(["worktree", "add", "--no-checkout", "-b"])
+            .arg(branch_name)
+            .arg(&worktree_path)
+            .arg(&head)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git worktree add --no-checkout".into(),
+                source: e,
+            })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            self.cleanup_partial(&worktree_path);
+            return Err(WorktreeError::GitExit {
+                command: "worktree add".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+
+        if !copy(&entries, &worktree_path) {
+            tracing::info!(
+                template = %template.display(),
+                "filesystem cannot clone the template worktree, checking out instead"
+            );
+            // Unregister the half-made worktree and its branch.
+            let _ = Command::new("git")
+                .args(["worktree", "remove", "--force"])
+                .arg(&worktree_path)
+                .current_dir(&self.repo_path)
+                .output();
+            let _ = Command::new("git")
+                .args(["branch", "-D", branch_name])
+                .current_dir(&self.repo_path)
+                .output();
+            self.cleanup_partial(&worktree_path);
+            return Ok(None);
+        }
+
+        // Fill the index from HEAD; the files are already in place.
+        let output = Command::new("git")
+            .args(["reset", "-q"])
+            .current_dir(&worktree_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git reset".into(),
+                source: e,
+            })?;
+        if !output.status.success() {
+            return Err(WorktreeError::GitExit {
+                command: "reset".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+
+        self.find_worktree_by_path(&worktree_path).map(Some)
+    }
+
     /// Remove a worktree by its path.
     ///
     /// This removes the worktree directory and unregisters it from git.
@@ -386,7 +939,7 @@ impl WorktreeManager {
     /// List all worktrees associated with the main repository.
     pub fn list_worktrees(&self) -> Result<Vec<WorktreeInfo>, WorktreeError> {
         let output = Command::new("git")
-            .args(["worktree", "list", "--porcelain"])
+            .args(["worktree", "list", "--porcelain", "-z"])
             .current_dir(&self.repo_path)
             .output()
             .map_err(|e| WorktreeError::GitCommand {
@@ -412,8 +965,19 @@ impl WorktreeManager {
     /// Runs `git worktree prune` to clean up references to worktrees
     /// whose directories have been removed externally.
     pub fn cleanup_stale(&self) -> Result<(), WorktreeError> {
+        self.prune_stale(false).map(|_| ())
+    }
+
+    /// Prune stale worktree entries, or with `dry_run` only find them.
+    ///
+    /// Returns the pruned entries' names under `.git/worktrees`.
+    pub fn prune_stale(&self, dry_run: bool) -> Result<Vec<String>, WorktreeError> {
+        let mut args = vec!["worktree", "prune", "--verbose"];
+        if dry_run {
+            args.push("--dry-run");
+        }
         let output = Command::new("git")
-            .args(["worktree", "prune"])
+            .args(&args)
             .current_dir(&self.repo_path)
             .output()
             .map_err(|e| WorktreeError::GitCommand {
@@ -421,8 +985,8 @@ impl WorktreeManager {
                 source: e,
             })?;
 
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             return Err(WorktreeError::GitExit {
                 command: "worktree prune".into(),
                 code: output.status.code().unwrap_or(-1),
@@ -430,7 +994,27 @@ impl WorktreeManager {
             });
         }
 
-        Ok(())
+        // git reports each entry on stderr as
+        // "Removing worktrees/<name>: <reason>".
+        Ok(stderr
+            .lines()
+            .filter_map(|line| line.strip_prefix("Removing worktrees/"))
+            .map(|rest| rest.split(':').next().unwrap_or(rest).to_string())
+            .collect())
+    }
+
+    /// Every local branch under `gator/`, as created by
+    /// [`WorktreeManager::branch_name`].
+    pub fn gator_branches(&self) -> Result<Vec<String>, WorktreeError> {
+        let out = git_stdout(
+            &self.repo_path,
+            &["for-each-ref", "--format=%(refname)", "refs/heads/gator/"],
+        )?;
+        Ok(String::from_utf8_lossy(&out)
+            .lines()
+            .filter_map(|line| line.strip_prefix("refs/heads/"))
+            .map(str::to_string)
+            .collect())
     }
 
     /// Merge a branch into the current branch of the main repo using `--no-ff`.
@@ -512,6 +1096,108 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// Path of the worktree in which a conflicted `gator merge` of
+    /// `plan_name` is resolved.
+    pub fn merge_path(&self, plan_name: &str) -> PathBuf {
+        let plan = sanitize_ref_component(plan_name);
+        self.worktree_base.join(format!("gator--{plan}--.merge"))
+    }
+
+    /// Redo the merge of `branch_name` into `base_branch` in a detached
+    /// worktree at `path`, leaving its conflicts in place for the operator.
+    /// Returns the conflicted paths; empty when the merge went through.
+    pub fn open_conflicted_merge(
+        &self,
+        path: &Path,
+        base_branch: &str,
+        branch_name: &str,
+    ) -> Result<Vec<String>, WorktreeError> {
+        self.add_detached(path, base_branch)?;
+
+        let output = Command::new("git")
+            .args(["merge", "--no-ff", "-m"])
+            .arg(format!("Merge branch '{branch_name}' into {base_branch}"))
+            .arg(branch_name)
+            .current_dir(path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git merge".into(),
+                source: e,
+            })?;
+        if output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let conflicted = unmerged_paths(path)?;
+        if conflicted.is_empty() {
+            return Err(WorktreeError::GitExit {
+                command: "merge".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+        Ok(conflicted)
+    }
+
+    /// Commit the merge in progress in `path` with its prepared message, if
+    /// the operator has not committed it already, and return the `HEAD`
+    /// commit.
+    pub fn commit_merge(&self, path: &Path) -> Result<String, WorktreeError> {
+        let in_progress = Command::new("git")
+            .args(["rev-parse", "-q", "--verify", "MERGE_HEAD"])
+            .current_dir(path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git rev-parse".into(),
+                source: e,
+            })?
+            .status
+            .success();
+        if in_progress {
+            git_stdout(path, &["commit", "--no-edit"])?;
+        }
+        let head = git_stdout(path, &["rev-parse", "HEAD"])?;
+        Ok(String::from_utf8_lossy(&head).trim().to_string())
+    }
+
+    /// Fast-forward the branch checked out in the main repository to `rev`.
+    pub fn fast_forward(&self, rev: &str) -> Result<(), WorktreeError> {
+        let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
+        git_stdout(&self.repo_path, &["merge", "--ff-only", rev]).map(|_| ())
+    }
+
+    /// Whether `branch_name` is already contained in `into`.
+    pub fn is_merged(&self, branch_name: &str, into: &str) -> Result<bool, WorktreeError> {
+        let output = Command::new("git")
+            .args(["merge-base", "--is-ancestor", branch_name, into])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git merge-base --is-ancestor".into(),
+                source: e,
+            })?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            code => Err(WorktreeError::GitExit {
+                command: "merge-base".into(),
+                code: code.unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }),
+        }
+    }
+
+    /// The commit checked out in the main repository.
+    pub fn head_commit(&self) -> Result<String, WorktreeError> {
+        self.rev_commit("HEAD")
+    }
+
+    /// The commit `rev` (a branch, tag, or hash) resolves to.
+    pub fn rev_commit(&self, rev: &str) -> Result<String, WorktreeError> {
+        let commit = git_stdout(&self.repo_path, &["rev-parse", "--verify", rev])?;
+        Ok(String::from_utf8_lossy(&commit).trim().to_string())
+    }
+
     /// Checkout a branch in the main repository.
     pub fn checkout(&self, branch_name: &str) -> Result<(), WorktreeError> {
         let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
@@ -537,6 +1223,36 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// Create (or reset) `branch_name` at `start_point` and check it out in
+    /// the main repository.
+    pub fn checkout_new_branch(
+        &self,
+        branch_name: &str,
+        start_point: &str,
+    ) -> Result<(), WorktreeError> {
+        let _lock = self.git_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let output = Command::new("git")
+            .args(["checkout", "-B", branch_name, start_point])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                message: "failed to run git checkout -B".into(),
+                source: e,
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(WorktreeError::GitExit {
+                command: "checkout -B".into(),
+                code: output.status.code().unwrap_or(-1),
+                stderr,
+            });
+        }
+
+        Ok(())
+    }
+
     /// Check whether a branch exists in the repository.
     pub fn branch_exists(&self, branch_name: &str) -> Result<bool, WorktreeError> {
         let output = Command::new("git")
@@ -558,31 +1274,287 @@ impl WorktreeManager {
         // Canonicalize for comparison where possible.
         let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
-        for wt in worktrees {
-            let wt_canonical = wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone());
-            if wt_canonical == canonical {
-                return Ok(wt);
+        for wt in worktrees {
+            let wt_canonical = wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone());
+            if wt_canonical == canonical {
+                return Ok(wt);
+            }
+        }
+
+        Err(WorktreeError::ParseError(format!(
+            "worktree not found at path: {}",
+            path.display()
+        )))
+    }
+
+    /// Best-effort cleanup of a partially created worktree directory.
+    fn cleanup_partial(&self, path: &Path) {
+        if path.exists() {
+            tracing::warn!(
+                path = %path.display(),
+                "cleaning up partial worktree directory"
+            );
+            let _ = std::fs::remove_dir_all(path);
+        }
+        // Also try to prune stale entries.
+        let _ = self.cleanup_stale();
+    }
+}
+
+/// Marker written next to a template worktree once its setup succeeded. It
+/// holds the setup command, so a changed command sets the template up again.
+fn template_marker(template: &Path) -> PathBuf {
+    let mut marker = template.as_os_str().to_owned();
+    marker.push(".ready");
+    PathBuf::from(marker)
+}
+
+/// Copy `entries` into `dest` as copy-on-write clones. Returns `false` when
+/// the filesystem cannot clone them.
+fn clone_entries(entries: &[PathBuf], dest: &Path) -> bool {
+    if entries.is_empty() {
+        return true;
+    }
+    let flags: &[&str] = if cfg!(target_os = "macos") {
+        &["-c", "-R", "-p"]
+    } else {
+        &["-a", "--reflink=always"]
+    };
+    Command::new("cp")
+        .args(flags)
+        .args(entries)
+        .arg(dest)
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Recreate `src` at `dest` with files hard-linked, directories created,
+/// and symlinks copied (on unix; elsewhere they are left out). Paths that
+/// already exist at `dest` are skipped.
+fn link_tree(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if dest.symlink_metadata().is_ok() {
+        return Ok(());
+    }
+    let file_type = src.symlink_metadata()?.file_type();
+    if file_type.is_dir() {
+        std::fs::create_dir_all(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            link_tree(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if file_type.is_symlink() {
+            copy_symlink(src, dest)
+        } else {
+            std::fs::hard_link(src, dest)
+        }
+    }
+}
+
+/// Create a symlink at `dest` pointing where the symlink `src` points.
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+}
+
+/// Symlinks need extra privileges to create elsewhere; skip them.
+#[cfg(not(unix))]
+fn copy_symlink(_src: &Path, _dest: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Run git in `worktree_path` and return its stdout, failing on a non-zero
+/// exit.
+fn git_stdout(worktree_path: &Path, args: &[&str]) -> Result<Vec<u8>, WorktreeError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(worktree_path)
+        .output()
+        .map_err(|e| WorktreeError::GitCommand {
+            message: format!("failed to run git {}", args[0]),
+            source: e,
+        })?;
+    if !output.status.success() {
+        return Err(WorktreeError::GitExit {
+            command: args[0].into(),
+            code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// Paths of `worktree_path` with unresolved merge conflicts.
+pub fn unmerged_paths(worktree_path: &Path) -> Result<Vec<String>, WorktreeError> {
+    let out = git_stdout(worktree_path, &["diff", "--name-only", "--diff-filter=U"])?;
+    Ok(String::from_utf8_lossy(&out)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Untracked, non-ignored files of a worktree with their contents.
+fn untracked_files(worktree_path: &Path) -> Result<Vec<(String, Vec<u8>)>, WorktreeError> {
+    let untracked = git_stdout(
+        worktree_path,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    untracked
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|path| {
+            let name = String::from_utf8_lossy(path).into_owned();
+            let data = std::fs::read(worktree_path.join(&name)).map_err(|e| {
+                WorktreeError::GitCommand {
+                    message: format!("failed to read untracked file {name}"),
+                    source: e,
+                }
+            })?;
+            Ok((name, data))
+        })
+        .collect()
+}
+
+/// Hash the contents of a worktree: its HEAD commit, the uncommitted diff
+/// against it, and every untracked file that is not ignored. Two trees with
+/// the same hash have the same files, whatever happened in between.
+pub fn content_hash(worktree_path: &Path) -> Result<String, WorktreeError> {
+    let mut hasher = Sha256::new();
+    hasher.update(git_stdout(worktree_path, &["rev-parse", "HEAD"])?);
+    hasher.update(git_stdout(worktree_path, &["diff", "HEAD", "--binary"])?);
+    for (name, data) in untracked_files(worktree_path)? {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash the changes a worktree makes relative to the commit `base`: the
+/// path and current contents of every file changed since `base` (committed,
+/// uncommitted, or untracked and not ignored), or that it was deleted.
+/// Committing a change does not change its hash. Files under the directory
+/// `exclude` are left out.
+pub fn diff_hash(worktree_path: &Path, base: &str, exclude: &str) -> Result<String, WorktreeError> {
+    let exclude = exclude.trim_end_matches('/');
+    let excluded = |name: &str| {
+        name.strip_prefix(exclude)
+            .is_some_and(|rest| rest.starts_with('/'))
+    };
+    let changed = git_stdout(
+        worktree_path,
+        &["diff", "--name-only", "-z", "--no-renames", base],
+    )?;
+    let mut names: Vec<String> = changed
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| String::from_utf8_lossy(p).into_owned())
+        .collect();
+    names.extend(
+        untracked_files(worktree_path)?
+            .into_iter()
+            .map(|(name, _)| name),
+    );
+    names.retain(|name| !excluded(name));
+    names.sort();
+    names.dedup();
+
+    let mut hasher = Sha256::new();
+    for name in &names {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        match std::fs::read(worktree_path.join(name)) {
+            Ok(data) => {
+                hasher.update((data.len() as u64).to_le_bytes());
+                hasher.update(&data);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                hasher.update(u64::MAX.to_le_bytes());
+            }
+            Err(e) => {
+                return Err(WorktreeError::GitCommand {
+                    message: format!("failed to read changed file {name}"),
+                    source: e,
+                });
             }
         }
-
-        Err(WorktreeError::ParseError(format!(
-            "worktree not found at path: {}",
-            path.display()
-        )))
     }
+    Ok(hex::encode(hasher.finalize()))
+}
 
-    /// Best-effort cleanup of a partially created worktree directory.
-    fn cleanup_partial(&self, path: &Path) {
-        if path.exists() {
-            tracing::warn!(
-                path = %path.display(),
-                "cleaning up partial worktree directory"
-            );
-            let _ = std::fs::remove_dir_all(path);
+/// The uncommitted changes of a worktree as text: the diff against HEAD,
+/// then each untracked file in full (binary files by name only).
+pub fn working_diff(worktree_path: &Path) -> Result<String, WorktreeError> {
+    let mut diff =
+        String::from_utf8_lossy(&git_stdout(worktree_path, &["diff", "HEAD"])?).into_owned();
+    for (name, data) in untracked_files(worktree_path)? {
+        match String::from_utf8(data) {
+            Ok(text) => {
+                diff.push_str(&format!("new file {name}:\n{text}"));
+                if !text.ends_with('\n') {
+                    diff.push('\n');
+                }
+            }
+            Err(_) => diff.push_str(&format!("new binary file {name}\n")),
         }
-        // Also try to prune stale entries.
-        let _ = self.cleanup_stale();
     }
+    Ok(diff)
+}
+
+/// A file a worktree changes, and how many lines changed in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path relative to the worktree root.
+    pub path: String,
+    /// Lines added plus lines removed; 0 for binary files.
+    pub lines: usize,
+    /// Whether git (or, for an untracked file, its content) marks the file
+    /// as binary, so `lines` says nothing about the size of the change.
+    pub binary: bool,
+}
+
+/// The files a worktree changes relative to the commit `base`, whether
+/// committed, uncommitted, or untracked, sorted by path. Every line of an
+/// untracked file counts as added; a rename is a removal and an addition.
+/// An untracked file that is not UTF-8 text, or contains a NUL byte, is
+/// binary.
+pub fn diff_stat(worktree_path: &Path, base: &str) -> Result<Vec<FileChange>, WorktreeError> {
+    let numstat = git_stdout(
+        worktree_path,
+        &["diff", "--numstat", "-z", "--no-renames", base],
+    )?;
+    let mut changes: Vec<FileChange> = String::from_utf8_lossy(&numstat)
+        .split('\0')
+        .filter_map(|entry| {
+            let mut fields = entry.splitn(3, '\t');
+            // Binary files are listed as `-\t-\t<path>`.
+            let added = fields.next()?;
+            let removed = fields.next()?;
+            Some(FileChange {
+                path: fields.next()?.to_string(),
+                lines: added.parse::<usize>().unwrap_or(0) + removed.parse::<usize>().unwrap_or(0),
+                binary: added == "-" && removed == "-",
+            })
+        })
+        .collect();
+    for (path, data) in untracked_files(worktree_path)? {
+        let (lines, binary) = match std::str::from_utf8(&data) {
+            Ok(text) if !text.contains('\0') => (text.lines().count(), false),
+            _ => (0, true),
+        };
+        changes.push(FileChange {
+            path,
+            lines,
+            binary,
+        });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
 }
 
 /// Sanitize a string for use as a git ref component (between slashes).
@@ -645,7 +1617,29 @@ pub fn sanitize_ref_component(s: &str) -> String {
     }
 }
 
-/// Parse the porcelain output of `git worktree list --porcelain`.
+/// Parse `git status --porcelain -z` output into changed paths.
+///
+/// Records are `XY <path>` separated by NUL; renames and copies are followed
+/// by an extra record holding the original path, which is listed too.
+fn parse_status_z(output: &str) -> Result<Vec<String>, WorktreeError> {
+    let mut paths = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let (status, path) = record
+            .split_at_checked(3)
+            .ok_or_else(|| WorktreeError::ParseError(format!("bad status record {record:?}")))?;
+        paths.push(path.to_string());
+        if status.starts_with('R') || status.starts_with('C') {
+            let orig = records.next().ok_or_else(|| {
+                WorktreeError::ParseError(format!("rename without source: {record:?}"))
+            })?;
+            paths.push(orig.to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// Parse the porcelain output of `git worktree list --porcelain [-z]`.
 ///
 /// The format consists of blocks separated by blank lines. Each block has:
 ///
@@ -657,13 +1651,25 @@ pub fn sanitize_ref_component(s: &str) -> String {
 ///
 /// The main worktree may show `bare` instead of `branch`, and detached
 /// worktrees show `detached` instead of `branch`.
-fn parse_porcelain_output(output: &str) -> Result<Vec<WorktreeInfo>, WorktreeError> {
+///
+/// With `-z`, each line ends in NUL instead of newline, so paths may contain
+/// newlines. Without it, git C-quotes unusual paths (`"a\nb"`), and those are
+/// unquoted here. A HEAD that is not hex or a branch containing control
+/// characters is a [`WorktreeError::ParseError`] rather than a guess.
+pub fn parse_porcelain_output(output: &str) -> Result<Vec<WorktreeInfo>, WorktreeError> {
+    let nul_separated = output.contains('\0');
+    let records: Box<dyn Iterator<Item = &str>> = if nul_separated {
+        Box::new(output.split('\0'))
+    } else {
+        Box::new(output.lines())
+    };
+
     let mut worktrees = Vec::new();
     let mut current_path: Option<PathBuf> = None;
     let mut current_head: Option<String> = None;
     let mut current_branch: Option<String> = None;
 
-    for line in output.lines() {
+    for line in records {
         if line.is_empty() {
             // End of a block -- commit the current entry if we have one.
             if let (Some(path), Some(head)) = (current_path.take(), current_head.take()) {
@@ -681,10 +1687,25 @@ fn parse_porcelain_output(output: &str) -> Result<Vec<WorktreeInfo>, WorktreeErr
         }
 
         if let Some(rest) = line.strip_prefix("worktree ") {
-            current_path = Some(PathBuf::from(rest));
+            let path = if !nul_separated && rest.starts_with('"') {
+                unquote_c_style(rest)?
+            } else {
+                rest.to_string()
+            };
+            current_path = Some(PathBuf::from(path));
         } else if let Some(rest) = line.strip_prefix("HEAD ") {
+            if rest.is_empty() || rest.len() > 64 || !rest.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(WorktreeError::ParseError(format!("invalid HEAD {rest:?}")));
+            }
             current_head = Some(rest.to_string());
         } else if let Some(rest) = line.strip_prefix("branch ") {
+            // Git refuses control characters in ref names, so one here
+            // means the output was not what we think it is.
+            if rest.chars().any(char::is_control) {
+                return Err(WorktreeError::ParseError(format!(
+                    "invalid branch {rest:?}"
+                )));
+            }
             // Strip the refs/heads/ prefix to get the short branch name.
             let branch = rest.strip_prefix("refs/heads/").unwrap_or(rest).to_string();
             current_branch = Some(branch);
@@ -704,6 +1725,46 @@ fn parse_porcelain_output(output: &str) -> Result<Vec<WorktreeInfo>, WorktreeErr
     Ok(worktrees)
 }
 
+/// Undo git's C-style path quoting: `"a\tb\303\251"` becomes `a<TAB>bé`.
+fn unquote_c_style(quoted: &str) -> Result<String, WorktreeError> {
+    let malformed = || WorktreeError::ParseError(format!("malformed quoted path {quoted:?}"));
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(malformed)?;
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut iter = inner.bytes();
+    while let Some(b) = iter.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let byte = match iter.next().ok_or_else(malformed)? {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0b,
+            b'f' => 0x0c,
+            b'r' => b'\r',
+            b'"' => b'"',
+            b'\\' => b'\\',
+            high @ b'0'..=b'3' => {
+                let mid = iter.next().filter(|d| (b'0'..=b'7').contains(d));
+                let low = iter.next().filter(|d| (b'0'..=b'7').contains(d));
+                match (mid, low) {
+                    (Some(mid), Some(low)) => (high - b'0') * 64 + (mid - b'0') * 8 + (low - b'0'),
+                    _ => return Err(malformed()),
+                }
+            }
+            _ => return Err(malformed()),
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8(bytes).map_err(|_| malformed())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -766,6 +1827,125 @@ mod tests {
         assert_eq!(mgr.repo_path(), repo_path);
     }
 
+    #[test]
+    fn content_hash_tracks_tree_contents() {
+        let (_dir, repo_path) = create_temp_repo();
+        let clean = content_hash(&repo_path).unwrap();
+        assert_eq!(content_hash(&repo_path).unwrap(), clean);
+
+        std::fs::write(repo_path.join("README.md"), "# Changed\n").unwrap();
+        let dirty = content_hash(&repo_path).unwrap();
+        assert_ne!(dirty, clean);
+
+        std::fs::write(repo_path.join("new.txt"), "a").unwrap();
+        let untracked = content_hash(&repo_path).unwrap();
+        assert_ne!(untracked, dirty);
+        std::fs::write(repo_path.join("new.txt"), "b").unwrap();
+        assert_ne!(content_hash(&repo_path).unwrap(), untracked);
+
+        std::fs::remove_file(repo_path.join("new.txt")).unwrap();
+        std::fs::write(repo_path.join("README.md"), "# Test repo\n").unwrap();
+        assert_eq!(content_hash(&repo_path).unwrap(), clean);
+    }
+
+    #[test]
+    fn working_diff_includes_untracked_files() {
+        let (_dir, repo_path) = create_temp_repo();
+        assert_eq!(working_diff(&repo_path).unwrap(), "");
+
+        std::fs::write(repo_path.join("README.md"), "# Changed\n").unwrap();
+        std::fs::write(repo_path.join("new.txt"), "hello").unwrap();
+        let diff = working_diff(&repo_path).unwrap();
+        assert!(diff.contains("+# Changed"), "{diff}");
+        assert!(diff.ends_with("new file new.txt:\nhello\n"), "{diff}");
+    }
+
+    #[test]
+    fn diff_stat_counts_committed_uncommitted_and_untracked_lines() {
+        let (_dir, repo_path) = create_temp_repo();
+        let base = String::from_utf8(git_stdout(&repo_path, &["rev-parse", "HEAD"]).unwrap())
+            .unwrap()
+            .trim()
+            .to_string();
+        assert_eq!(diff_stat(&repo_path, &base).unwrap(), vec![]);
+
+        std::fs::create_dir(repo_path.join("tests")).unwrap();
+        std::fs::write(repo_path.join("tests/a.rs"), "one\ntwo\n").unwrap();
+        git_stdout(&repo_path, &["add", "."]).unwrap();
+        git_stdout(&repo_path, &["commit", "-m", "add test"]).unwrap();
+        std::fs::write(repo_path.join("README.md"), "# Changed\n").unwrap();
+        std::fs::write(repo_path.join("tests/b.rs"), "x\ny\nz").unwrap();
+
+        assert_eq!(
+            diff_stat(&repo_path, &base).unwrap(),
+            vec![
+                FileChange {
+                    path: "README.md".into(),
+                    lines: 2,
+                    binary: false
+                },
+                FileChange {
+                    path: "tests/a.rs".into(),
+                    lines: 2,
+                    binary: false
+                },
+                FileChange {
+                    path: "tests/b.rs".into(),
+                    lines: 3,
+                    binary: false
+                },
+            ]
+        );
+
+        // Binary files, committed or untracked, are marked as such.
+        std::fs::write(repo_path.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 1]).unwrap();
+        std::fs::write(repo_path.join("tests/data.bin"), [0xff, 0xfe, 0]).unwrap();
+        git_stdout(&repo_path, &["add", "logo.png"]).unwrap();
+        git_stdout(&repo_path, &["commit", "-m", "add logo"]).unwrap();
+        let binary: Vec<(String, bool)> = diff_stat(&repo_path, &base)
+            .unwrap()
+            .into_iter()
+            .filter(|c| c.binary)
+            .map(|c| (c.path, c.lines == 0))
+            .collect();
+        assert_eq!(
+            binary,
+            vec![
+                ("logo.png".to_string(), true),
+                ("tests/data.bin".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_hash_covers_the_change_but_not_the_excluded_dir() {
+        let (_dir, repo_path) = create_temp_repo();
+        let base = String::from_utf8(git_stdout(&repo_path, &["rev-parse", "HEAD"]).unwrap())
+            .unwrap()
+            .trim()
+            .to_string();
+        let empty = diff_hash(&repo_path, &base, ".gator/attestations").unwrap();
+
+        std::fs::write(repo_path.join("new.txt"), "a").unwrap();
+        let uncommitted = diff_hash(&repo_path, &base, ".gator/attestations").unwrap();
+        assert_ne!(uncommitted, empty);
+
+        // Committing the same change does not change the hash.
+        git_stdout(&repo_path, &["add", "."]).unwrap();
+        git_stdout(&repo_path, &["commit", "-m", "add new.txt"]).unwrap();
+        assert_eq!(
+            diff_hash(&repo_path, &base, ".gator/attestations").unwrap(),
+            uncommitted
+        );
+
+        std::fs::create_dir_all(repo_path.join(".gator/attestations")).unwrap();
+        std::fs::write(repo_path.join(".gator/attestations/t.json"), "{}").unwrap();
+        assert_eq!(
+            diff_hash(&repo_path, &base, ".gator/attestations").unwrap(),
+            uncommitted
+        );
+    }
+
     #[test]
     fn test_new_with_invalid_repo() {
         let dir = TempDir::new().expect("failed to create temp dir");
@@ -985,6 +2165,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_prune_stale_dry_run_and_gator_branches() {
+        let (_dir, repo_path) = create_temp_repo();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+
+        let stale = WorktreeManager::branch_name("plan", "stale-task");
+        let info = mgr.create_worktree(&stale).expect("create failed");
+        let live = WorktreeManager::branch_name("plan", "live-task");
+        let live_info = mgr.create_worktree(&live).expect("create failed");
+        std::fs::remove_dir_all(&info.path).expect("manual remove failed");
+
+        let mut branches = mgr.gator_branches().expect("list branches failed");
+        branches.sort();
+        assert_eq!(branches, vec![live.clone(), stale.clone()]);
+
+        // A branch without commits of its own is contained in HEAD; one
+        // with work on it is not.
+        std::fs::write(live_info.path.join("work.txt"), "work\n").unwrap();
+        assert!(mgr.commit_worktree(&live_info.path, "work").unwrap());
+        assert!(mgr.is_merged(&stale, "HEAD").unwrap());
+        assert!(!mgr.is_merged(&live, "HEAD").unwrap());
+        assert!(mgr.is_merged("no-such-branch", "HEAD").is_err());
+
+        // A dry run reports the stale entry without pruning it.
+        let found = mgr.prune_stale(true).expect("dry run failed");
+        assert_eq!(found.len(), 1);
+        assert_eq!(mgr.prune_stale(true).unwrap(), found);
+
+        assert_eq!(mgr.prune_stale(false).unwrap(), found);
+        assert!(mgr.prune_stale(true).unwrap().is_empty());
+    }
+
     #[test]
     fn test_create_multiple_worktrees() {
         let (_dir, repo_path) = create_temp_repo();
@@ -1188,6 +2402,63 @@ branch refs/heads/main";
         );
     }
 
+    #[test]
+    fn conflicted_merge_is_resolved_in_its_own_worktree() {
+        let (_dir, repo_path) = create_temp_repo();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+        let run = |args: &[&str], dir: &Path| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        let base = run(&["rev-parse", "--abbrev-ref", "HEAD"], &repo_path);
+
+        // The task branch and the base branch both change the README.
+        let branch = WorktreeManager::branch_name("plan", "conflict-task");
+        let info = mgr.create_worktree(&branch).expect("create failed");
+        std::fs::write(info.path.join("README.md"), "# From the task\n").unwrap();
+        run(&["commit", "-am", "Task change"], &info.path);
+        mgr.remove_worktree(&info.path).expect("remove failed");
+        std::fs::write(repo_path.join("README.md"), "# From the base\n").unwrap();
+        run(&["commit", "-am", "Base change"], &repo_path);
+
+        assert!(matches!(
+            mgr.merge_branch(&branch).unwrap(),
+            MergeResult::Conflict { .. }
+        ));
+        assert!(!mgr.is_merged(&branch, &base).unwrap());
+
+        let path = mgr.merge_path("plan");
+        let conflicted = mgr.open_conflicted_merge(&path, &base, &branch).unwrap();
+        assert_eq!(conflicted, vec!["README.md".to_string()]);
+
+        std::fs::write(path.join("README.md"), "# Resolved\n").unwrap();
+        run(&["add", "README.md"], &path);
+        assert!(unmerged_paths(&path).unwrap().is_empty());
+        let head = mgr.commit_merge(&path).unwrap();
+        mgr.remove_worktree(&path).unwrap();
+
+        mgr.fast_forward(&head).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo_path.join("README.md")).unwrap(),
+            "# Resolved\n"
+        );
+        assert_eq!(mgr.head_commit().unwrap(), head);
+        assert!(mgr.is_merged(&branch, &base).unwrap());
+        assert_ne!(mgr.rev_commit(&branch).unwrap(), head);
+    }
+
     #[test]
     fn test_delete_branch() {
         let (_dir, repo_path) = create_temp_repo();
@@ -1264,6 +2535,224 @@ branch refs/heads/main";
         assert!(!committed, "should not have committed when no changes");
     }
 
+    #[test]
+    fn test_commit_paths_then_discard_rest() {
+        let (_dir, repo_path) = create_temp_repo();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+
+        let branch = WorktreeManager::branch_name("plan", "partial");
+        let info = mgr.create_worktree(&branch).expect("create failed");
+
+        std::fs::create_dir_all(info.path.join("src")).unwrap();
+        std::fs::write(info.path.join("src/keep.rs"), "fn keep() {}\n").unwrap();
+        std::fs::write(info.path.join("src/drop.rs"), "fn drop() {}\n").unwrap();
+        std::fs::write(info.path.join("README.md"), "# Changed\n").unwrap();
+
+        let mut changed = mgr.changed_paths(&info.path).unwrap();
+        changed.sort();
+        assert_eq!(changed, vec!["README.md", "src/drop.rs", "src/keep.rs"]);
+
+        let committed = mgr
+            .commit_paths(&info.path, &["src/keep.rs".to_string()], "partial")
+            .expect("commit failed");
+        assert!(committed);
+        mgr.discard_changes(&info.path).expect("discard failed");
+
+        assert!(info.path.join("src/keep.rs").exists());
+        assert!(!info.path.join("src/drop.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(info.path.join("README.md")).unwrap(),
+            "# Test repo\n"
+        );
+        assert!(mgr.changed_paths(&info.path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sparse_checkout_and_changes_outside_patterns() {
+        let (_dir, repo_path) = create_temp_repo();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+
+        let branch = WorktreeManager::branch_name("plan", "scoped");
+        let info = mgr.create_worktree(&branch).expect("create failed");
+        let patterns = vec!["src/**".to_string()];
+        mgr.sparse_checkout(&info.path, &patterns)
+            .expect("sparse checkout failed");
+
+        // Files outside the patterns are not checked out.
+        assert!(!info.path.join("README.md").exists());
+
+        std::fs::create_dir_all(info.path.join("src")).unwrap();
+        std::fs::write(info.path.join("src/lib.rs"), "fn ok() {}\n").unwrap();
+        assert!(
+            mgr.changed_paths_outside(&info.path, "no-such-branch", &patterns)
+                .unwrap()
+                .is_empty()
+        );
+
+        // An agent commit outside the patterns (plain `git add` refuses
+        // paths outside the sparse checkout).
+        std::fs::write(info.path.join("notes.md"), "stray\n").unwrap();
+        for args in [
+            &["add", "--sparse", "-A"][..],
+            &["commit", "-m", "agent"][..],
+        ] {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(&info.path)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        }
+        std::fs::create_dir_all(info.path.join("docs")).unwrap();
+        std::fs::write(info.path.join("docs/x.md"), "more\n").unwrap();
+
+        let output = Command::new("git")
+            .args(["branch", "--show-current"])
+            .current_dir(&repo_path)
+            .output()
+            .expect("failed to get current branch");
+        let base = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        assert_eq!(
+            mgr.changed_paths_outside(&info.path, &base, &patterns)
+                .unwrap(),
+            vec!["docs/x.md", "notes.md"]
+        );
+        // Without a base branch only uncommitted changes are seen.
+        assert_eq!(
+            mgr.changed_paths_outside(&info.path, "no-such-branch", &patterns)
+                .unwrap(),
+            vec!["docs/x.md"]
+        );
+
+        // The main checkout is untouched.
+        assert!(repo_path.join("README.md").exists());
+    }
+
+    #[test]
+    fn test_template_setup_runs_once_and_clones_into_task_worktrees() {
+        let (_dir, repo_path) = create_temp_repo();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+
+        let setup = "echo built >> build.log";
+        let template = mgr.prepare_template("plan", setup).expect("prepare failed");
+        assert_eq!(template, mgr.template_path("plan"));
+        assert_eq!(mgr.prepare_template("plan", setup).unwrap(), template);
+        assert_eq!(
+            std::fs::read_to_string(template.join("build.log")).unwrap(),
+            "built\n"
+        );
+
+        // A plain copy stands in for a reflink clone, which the test
+        // filesystem may not support.
+        fn copy(entries: &[PathBuf], dest: &Path) -> bool {
+            Command::new("cp")
+                .arg("-a")
+                .args(entries)
+                .arg(dest)
+                .status()
+                .is_ok_and(|s| s.success())
+        }
+        let branch = WorktreeManager::branch_name("plan", "cloned");
+        let info = mgr
+            .create_worktree_with_copier(&branch, &template, copy, false)
+            .expect("clone failed");
+        assert_eq!(info.branch.as_deref(), Some(branch.as_str()));
+        assert!(info.path.join("build.log").exists());
+        assert_eq!(mgr.changed_paths(&info.path).unwrap(), vec!["build.log"]);
+
+        // Without clone support the worktree is checked out normally.
+        let branch = WorktreeManager::branch_name("plan", "checked-out");
+        let info = mgr
+            .create_worktree_with_copier(&branch, &template, |_, _| false, false)
+            .expect("fallback failed");
+        assert_eq!(info.branch.as_deref(), Some(branch.as_str()));
+        assert!(info.path.join("README.md").exists());
+        assert!(!info.path.join("build.log").exists());
+
+        mgr.remove_template("plan").expect("remove failed");
+        assert!(!template.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_template_outputs_are_hard_linked_when_cloning_is_unavailable() {
+        use std::os::unix::fs::MetadataExt;
+
+        let (_dir, repo_path) = create_temp_repo();
+        std::fs::write(repo_path.join(".gitignore"), "target/\n").unwrap();
+        Command::new("git")
+            .args(["add", ".gitignore"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "ignore target"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        let worktree_base = TempDir::new().expect("failed to create worktree base");
+        let mgr =
+            WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf())).unwrap();
+
+        let setup = "mkdir -p target/debug && echo bin > target/debug/app \
+                     && ln -s debug target/latest && echo gen > generated.rs";
+        let template = mgr.prepare_template("plan", setup).expect("prepare failed");
+
+        let branch = WorktreeManager::branch_name("plan", "linked");
+        let info = mgr
+            .create_worktree_with_copier(&branch, &template, |_, _| false, true)
+            .expect("fallback failed");
+        let inode = |p: &Path| std::fs::symlink_metadata(p).unwrap().ino();
+
+        // Build outputs are shared with the template...
+        let app = Path::new("target/debug/app");
+        assert_eq!(inode(&info.path.join(app)), inode(&template.join(app)));
+        assert_eq!(
+            inode(&info.path.join("generated.rs")),
+            inode(&template.join("generated.rs"))
+        );
+        assert_eq!(
+            std::fs::read_link(info.path.join("target/latest")).unwrap(),
+            Path::new("debug")
+        );
+        // ...while tracked files are the worktree's own.
+        assert_ne!(
+            inode(&info.path.join("README.md")),
+            inode(&template.join("README.md"))
+        );
+        assert_eq!(mgr.changed_paths(&info.path).unwrap(), vec!["generated.rs"]);
+
+        // Linking again (as on a retry) keeps what is already there.
+        std::fs::remove_file(info.path.join(app)).unwrap();
+        std::fs::write(info.path.join(app), "rebuilt").unwrap();
+        mgr.link_template_outputs(&template, &info.path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(info.path.join(app)).unwrap(),
+            "rebuilt"
+        );
+        assert_eq!(
+            std::fs::read_to_string(template.join(app)).unwrap(),
+            "bin\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_status_z_lists_rename_sources() {
+        let out = "R  new.rs\0old.rs\0 M src/lib.rs\0?? notes.md\0";
+        assert_eq!(
+            parse_status_z(out).unwrap(),
+            vec!["new.rs", "old.rs", "src/lib.rs", "notes.md"]
+        );
+        assert!(parse_status_z("R  new.rs\0").is_err());
+        assert!(parse_status_z("").unwrap().is_empty());
+    }
+
     #[test]
     fn test_commit_worktree_then_merge() {
         let (_dir, repo_path) = create_temp_repo();
@@ -1290,3 +2779,115 @@ branch refs/heads/main";
         assert!(repo_path.join("feature.rs").exists());
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Quote a path the way git does without `-z` (`core.quotePath=true`).
+    fn quote_c_style(path: &str) -> String {
+        let needs_quoting = path
+            .bytes()
+            .any(|b| !(0x20..0x7f).contains(&b) || b == b'"' || b == b'\\');
+        if !needs_quoting {
+            return path.to_string();
+        }
+        let mut out = String::from("\"");
+        for b in path.bytes() {
+            match b {
+                b'\t' => out.push_str("\\t"),
+                b'\n' => out.push_str("\\n"),
+                b'\r' => out.push_str("\\r"),
+                b'"' => out.push_str("\\\""),
+                b'\\' => out.push_str("\\\\"),
+                0x20..0x7f => out.push(b as char),
+                _ => out.push_str(&format!("\\{b:03o}")),
+            }
+        }
+        out.push('"');
+        out
+    }
+
+    fn entry() -> impl Strategy<Value = WorktreeInfo> {
+        (
+            "/[^\0]{0,40}",
+            "[0-9a-f]{40}",
+            proptest::option::of("[a-z][a-z0-9/_.-]{0,20}"),
+        )
+            .prop_map(|(path, head, branch)| WorktreeInfo {
+                path: PathBuf::from(path),
+                branch,
+                head_commit: head,
+            })
+    }
+
+    fn render(entries: &[WorktreeInfo], sep: char, quote: bool) -> String {
+        let mut out = String::new();
+        for wt in entries {
+            let path = wt.path.to_str().unwrap();
+            let path = if quote {
+                quote_c_style(path)
+            } else {
+                path.to_string()
+            };
+            out.push_str(&format!("worktree {path}{sep}HEAD {}{sep}", wt.head_commit));
+            match &wt.branch {
+                Some(b) => out.push_str(&format!("branch refs/heads/{b}{sep}")),
+                None => out.push_str(&format!("detached{sep}")),
+            }
+            out.push(sep);
+        }
+        out
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_input_never_panics(input in any::<String>()) {
+            let _ = parse_porcelain_output(&input);
+        }
+
+        #[test]
+        fn porcelain_like_input_never_panics(
+            input in "((worktree |HEAD |branch |detached|\"|\\\\|[0-7]|[a-f/ ]|\n|\0){0,40})"
+        ) {
+            let _ = parse_porcelain_output(&input);
+        }
+
+        #[test]
+        fn nul_separated_output_roundtrips(entries in prop::collection::vec(entry(), 1..5)) {
+            let parsed = parse_porcelain_output(&render(&entries, '\0', false)).unwrap();
+            prop_assert_eq!(parsed, entries);
+        }
+
+        #[test]
+        fn quoted_output_roundtrips(entries in prop::collection::vec(entry(), 1..5)) {
+            let parsed = parse_porcelain_output(&render(&entries, '\n', true)).unwrap();
+            prop_assert_eq!(parsed, entries);
+        }
+    }
+
+    #[test]
+    fn rejects_control_characters_in_branch_and_bad_head() {
+        let branch = "worktree /repo\0HEAD abc123\0branch refs/heads/main\nevil\0\0";
+        assert!(matches!(
+            parse_porcelain_output(branch),
+            Err(WorktreeError::ParseError(_))
+        ));
+        let head = "worktree /repo\nHEAD not-a-sha\n";
+        assert!(matches!(
+            parse_porcelain_output(head),
+            Err(WorktreeError::ParseError(_))
+        ));
+        let unterminated = "worktree \"/repo\\\n";
+        assert!(parse_porcelain_output(unterminated).is_err());
+    }
+
+    #[test]
+    fn keeps_newlines_in_nul_separated_paths() {
+        let input = "worktree /tmp/a\nb\0HEAD abc123\0detached\0\0";
+        let parsed = parse_porcelain_output(input).unwrap();
+        assert_eq!(parsed[0].path, PathBuf::from("/tmp/a\nb"));
+    }
+}
@@ -0,0 +1,377 @@
+//! Golden-snapshot test of the full dispatch pipeline.
+//!
+//! Dispatches a five-task DAG against a scripted fleet of mock agents, then
+//! merges the passed branches the way `gator merge` does, and compares
+//! everything observable -- each task's status transitions, lifecycle
+//! phases, gate results, and branch contents, plus the merge outcome --
+//! against `tests/snapshots/dispatch_pipeline.snap`. A change to the
+//! orchestrator that alters any of it shows up as a snapshot diff; when the
+//! change is intended, regenerate the snapshot with
+//! `GATOR_UPDATE_SNAPSHOTS=1 cargo test --test dispatch_pipeline_test`.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::Stream;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use gator_db::models::{InvariantKind, InvariantScope};
+use gator_db::queries::attempt_phases;
+use gator_db::queries::gate_results;
+use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+use gator_test_utils::{
+    assert_snapshot, create_temp_git_repo, create_test_db, drop_test_db, record_task_transitions,
+    run_git, task_transitions,
+};
+
+use gator_core::harness::types::{AgentEvent, AgentHandle, MaterializedTask};
+use gator_core::harness::{Harness, HarnessRegistry};
+use gator_core::isolation::{Isolation, worktree::WorktreeIsolation};
+use gator_core::notify::NotificationConfig;
+use gator_core::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, OrchestratorConfig, WatchdogConfig, run_orchestrator,
+};
+use gator_core::token::TokenConfig;
+use gator_core::worktree::{MergeResult, WorktreeManager};
+
+const PLAN_NAME: &str = "pipeline";
+
+/// A file the gate rejects; an agent that leaves it behind fails its attempt.
+const BROKEN_MARKER: &str = "BROKEN";
+
+/// What an agent does to its workspace on one attempt: write each file
+/// (`Some(contents)`) or delete it (`None`).
+type Edits = Vec<(&'static str, Option<&'static str>)>;
+
+/// The plan's tasks in merge order: name, dependencies, and the edits of
+/// each attempt.
+fn tasks() -> Vec<(&'static str, Vec<&'static str>, Vec<Edits>)> {
+    vec![
+        (
+            "schema",
+            vec![],
+            vec![vec![("src/schema.sql", Some("CREATE TABLE items (id);\n"))]],
+        ),
+        (
+            "storage",
+            vec!["schema"],
+            vec![
+                // The first attempt leaves the marker behind and fails the gate.
+                vec![
+                    ("src/storage.rs", Some("// storage\n")),
+                    (BROKEN_MARKER, Some("half done\n")),
+                ],
+                vec![(BROKEN_MARKER, None)],
+            ],
+        ),
+        (
+            "api",
+            vec!["schema"],
+            vec![vec![("src/api.rs", Some("// api\n"))]],
+        ),
+        (
+            "cli",
+            vec!["storage", "api"],
+            vec![vec![("src/cli.rs", Some("// cli\n"))]],
+        ),
+        (
+            "docs",
+            vec!["cli"],
+            vec![vec![("docs/usage.md", Some("# Usage\n"))]],
+        ),
+    ]
+}
+
+// ===========================================================================
+// Scripted fleet
+// ===========================================================================
+
+/// A mock harness whose agents apply a fixed script of edits per task and
+/// attempt, then complete.
+struct ScriptedFleet {
+    scripts: HashMap<String, Vec<Edits>>,
+    /// Spawns so far, per task name.
+    spawns: Mutex<HashMap<String, usize>>,
+}
+
+impl ScriptedFleet {
+    fn new() -> Self {
+        Self {
+            scripts: tasks()
+                .into_iter()
+                .map(|(name, _, attempts)| (name.to_string(), attempts))
+                .collect(),
+            spawns: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Harness for ScriptedFleet {
+    fn name(&self) -> &str {
+        "scripted-fleet"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        let attempt = {
+            let mut spawns = self.spawns.lock().unwrap();
+            let count = spawns.entry(task.name.clone()).or_default();
+            *count += 1;
+            *count - 1
+        };
+        let attempts = &self.scripts[&task.name];
+        for (path, contents) in &attempts[attempt.min(attempts.len() - 1)] {
+            let path = task.working_dir.join(path);
+            match contents {
+                Some(contents) => {
+                    std::fs::create_dir_all(path.parent().unwrap())?;
+                    std::fs::write(&path, contents)?;
+                }
+                None => std::fs::remove_file(&path)?,
+            }
+        }
+
+        Ok(AgentHandle {
+            pid: 99999,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: attempt as u32,
+            harness_name: "scripted-fleet".to_string(),
+        })
+    }
+
+    fn events(&self, _handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        Box::pin(futures::stream::iter(vec![
+            AgentEvent::Message {
+                role: "assistant".to_string(),
+                content: "Done".to_string(),
+            },
+            AgentEvent::Completed,
+        ]))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+// ===========================================================================
+// Helpers
+// ===========================================================================
+
+/// Insert and approve the plan; returns its ID and the task IDs in merge
+/// order.
+async fn create_plan(pool: &SqlitePool, repo_path: &Path) -> (Uuid, Vec<(String, Uuid)>) {
+    let args = vec!["-c".to_string(), format!("test ! -e {BROKEN_MARKER}")];
+    let gate = invariants::insert_invariant(
+        pool,
+        &NewInvariant {
+            name: "no_broken_marker",
+            description: None,
+            kind: InvariantKind::Custom,
+            command: "sh",
+            args: &args,
+            expected_exit_code: 0,
+            threshold: None,
+            scope: InvariantScope::Project,
+            timeout_secs: 60,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
+        },
+    )
+    .await
+    .expect("insert invariant");
+
+    let plan = plan_db::insert_plan(
+        pool,
+        PLAN_NAME,
+        &repo_path.to_string_lossy(),
+        "main",
+        None,
+        "scripted-fleet",
+        "worktree",
+        None,
+    )
+    .await
+    .expect("insert plan");
+    plan_db::approve_plan(pool, plan.id)
+        .await
+        .expect("approve plan");
+
+    let mut ids: Vec<(String, Uuid)> = Vec::new();
+    for (name, deps, _) in tasks() {
+        let task = task_db::insert_task(
+            pool,
+            plan.id,
+            name,
+            &format!("Implement {name}"),
+            "narrow",
+            "auto",
+            1,
+            None,
+        )
+        .await
+        .expect("insert task");
+        task_db::link_task_invariant(pool, task.id, gate.id)
+            .await
+            .unwrap();
+        for dep in deps {
+            let (_, dep_id) = ids.iter().find(|(n, _)| n == dep).unwrap();
+            task_db::insert_task_dependency(pool, task.id, *dep_id)
+                .await
+                .unwrap();
+        }
+        ids.push((name.to_string(), task.id));
+    }
+
+    (plan.id, ids)
+}
+
+/// Files tracked at `rev`, comma-separated.
+fn tree(repo_path: &Path, rev: &str) -> String {
+    run_git(repo_path, &["ls-tree", "-r", "--name-only", rev])
+        .lines()
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// ===========================================================================
+// Test
+// ===========================================================================
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn dispatch_pipeline_matches_snapshot() {
+    let (pool, db_path) = create_test_db().await;
+    let (_repo_dir, repo_path) = create_temp_git_repo();
+    let worktree_base = tempfile::TempDir::new().expect("failed to create worktree base dir");
+    record_task_transitions(&pool).await;
+
+    let (plan_id, task_ids) = create_plan(&pool, &repo_path).await;
+
+    let manager = WorktreeManager::new(&repo_path, Some(worktree_base.path().to_path_buf()))
+        .expect("failed to create WorktreeManager");
+    let isolation: Arc<dyn Isolation> = Arc::new(WorktreeIsolation::new(manager.clone()));
+    let mut registry = HarnessRegistry::new();
+    registry.register(ScriptedFleet::new());
+
+    let result = run_orchestrator(
+        &pool,
+        plan_id,
+        &Arc::new(registry),
+        &isolation,
+        &TokenConfig::new(b"pipeline-test-secret".to_vec()),
+        &OrchestratorConfig {
+            max_agents: 2,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .expect("orchestrator failed");
+
+    let plan = plan_db::get_plan(&pool, plan_id).await.unwrap().unwrap();
+    let mut out = String::new();
+    writeln!(out, "dispatch: {result:?}, plan {}", plan.status).unwrap();
+
+    for (name, id) in &task_ids {
+        let task = task_db::get_task(&pool, *id).await.unwrap().unwrap();
+        writeln!(out, "\ntask {name}: {}", task.status).unwrap();
+        writeln!(
+            out,
+            "  transitions: {}",
+            task_transitions(&pool, *id).await.join(" -> ")
+        )
+        .unwrap();
+        for attempt in 0..=task.attempt {
+            let phases = attempt_phases::list_phases_for_attempt(&pool, *id, attempt)
+                .await
+                .unwrap();
+            let phases: Vec<String> = phases.iter().map(|p| p.phase.to_string()).collect();
+            writeln!(out, "  attempt {attempt} phases: {}", phases.join(", ")).unwrap();
+            for result in gate_results::get_gate_results(&pool, *id, attempt)
+                .await
+                .unwrap()
+            {
+                let invariant = invariants::get_invariant(&pool, result.invariant_id)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                writeln!(
+                    out,
+                    "  attempt {attempt} gate: {} {} (exit {})",
+                    invariant.name,
+                    if result.passed { "passed" } else { "failed" },
+                    result
+                        .exit_code
+                        .map_or("none".to_string(), |c| c.to_string())
+                )
+                .unwrap();
+            }
+        }
+        let branch = WorktreeManager::branch_name(PLAN_NAME, name);
+        writeln!(out, "  branch {branch}: {}", tree(&repo_path, &branch)).unwrap();
+    }
+
+    // Merge the passed branches in dependency order, as `gator merge` does.
+    writeln!(out, "\nmerge:").unwrap();
+    for (name, id) in &task_ids {
+        let branch = WorktreeManager::branch_name(PLAN_NAME, name);
+        let outcome = match manager.merge_branch(&branch).expect("merge failed") {
+            MergeResult::Success => {
+                let commit = manager.head_commit().unwrap();
+                task_db::mark_task_merged(&pool, *id, &commit)
+                    .await
+                    .unwrap();
+                "merged".to_string()
+            }
+            MergeResult::Conflict { .. } => "conflict".to_string(),
+        };
+        writeln!(out, "  {branch}: {outcome}").unwrap();
+    }
+    writeln!(out, "  base tree: {}", tree(&repo_path, "HEAD")).unwrap();
+    let merges = run_git(&repo_path, &["log", "--first-parent", "--format=%s"]);
+    for subject in merges.lines() {
+        writeln!(out, "  base log: {subject}").unwrap();
+    }
+
+    assert_snapshot(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+        "dispatch_pipeline",
+        &out,
+    );
+
+    pool.close().await;
+    drop_test_db(&db_path).await;
+}
@@ -18,11 +18,13 @@ use std::process::Command;
 use sqlx::SqlitePool;
 
 use gator_db::models::{InvariantKind, InvariantScope, PlanStatus, TaskStatus};
+use gator_db::queries::auto_approvals;
 use gator_db::queries::gate_results;
 use gator_db::queries::invariants::{self, NewInvariant};
 use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 
+use gator_core::gate::auto_approve::AutoApproveRule;
 use gator_core::gate::evaluator::{GateAction, evaluate_verdict};
 use gator_core::gate::{GateRunner, GateVerdict};
 use gator_core::plan::{
@@ -151,6 +153,12 @@ async fn insert_invariant(
         threshold: None,
         scope: InvariantScope::Project,
         timeout_secs: 300,
+        diff_base: false,
+        lower_is_better: false,
+        env: &Default::default(),
+        workdir: None,
+        run_after: &[],
+        artifacts: &[],
     };
     invariants::insert_invariant(pool, &new)
         .await
@@ -352,7 +360,7 @@ invariants = ["echo_test", "always_pass"]
     assert_eq!(task.status, TaskStatus::Checking);
 
     // 6c. `gator done` equivalent: evaluate the verdict.
-    let action = evaluate_verdict(pool, task_id, &verdict)
+    let action = evaluate_verdict(pool, task_id, &verdict, None, &[])
         .await
         .expect("evaluate_verdict should succeed");
     assert_eq!(action, GateAction::AutoPassed);
@@ -540,7 +548,7 @@ invariants = ["always_true", "always_false"]
     }
 
     // Evaluate: should auto-fail, can_retry = true.
-    let action = evaluate_verdict(pool, task_id, &verdict)
+    let action = evaluate_verdict(pool, task_id, &verdict, None, &[])
         .await
         .expect("evaluate should succeed");
     assert_eq!(action, GateAction::AutoFailed { can_retry: true });
@@ -623,7 +631,7 @@ invariants = ["always_true", "always_false"]
     );
 
     // Evaluate.
-    let action_2 = evaluate_verdict(pool, task_id, &verdict_2)
+    let action_2 = evaluate_verdict(pool, task_id, &verdict_2, None, &[])
         .await
         .expect("evaluate should succeed on retry");
     assert_eq!(action_2, GateAction::AutoPassed);
@@ -713,7 +721,9 @@ invariants = ["will_fail"]
     assert!(matches!(verdict, GateVerdict::Failed { .. }));
 
     // Evaluate: should auto-fail, can_retry = false.
-    let action = evaluate_verdict(pool, task_id, &verdict).await.unwrap();
+    let action = evaluate_verdict(pool, task_id, &verdict, None, &[])
+        .await
+        .unwrap();
     assert_eq!(action, GateAction::AutoFailed { can_retry: false });
 
     // Task should be failed.
@@ -736,3 +746,94 @@ invariants = ["will_fail"]
     wt_manager.remove_worktree(&wt_info.path).unwrap();
     harness.teardown().await;
 }
+
+// ===========================================================================
+// Test 4: human_review task auto-approved by a rule
+// ===========================================================================
+
+#[tokio::test]
+async fn e2e_human_review_auto_approved_by_rule() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    insert_invariant(pool, "always_pass", "true", &[], 0).await;
+
+    let base_branch = String::from_utf8(
+        Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(harness.repo_path())
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+    let plan_toml_content = format!(
+        r#"
+[plan]
+name = "e2e-auto-approve-plan"
+base_branch = "{}"
+
+[[tasks]]
+name = "add-tests"
+description = "Add a test"
+scope = "medium"
+gate = "human_review"
+invariants = ["always_pass"]
+"#,
+        base_branch.trim()
+    );
+    let plan_toml = parse_plan_toml(&plan_toml_content).unwrap();
+    let project_path = harness.repo_path().to_string_lossy().to_string();
+    let plan = create_plan_from_toml(pool, &plan_toml, &project_path)
+        .await
+        .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+    let (_, tasks) = get_plan_with_tasks(pool, plan.id).await.unwrap();
+    let task_id = tasks[0].id;
+
+    let wt_manager = harness.worktree_manager();
+    let branch_name = WorktreeManager::branch_name("e2e-auto-approve-plan", "add-tests");
+    let wt_info = wt_manager.create_worktree(&branch_name).unwrap();
+    std::fs::create_dir(wt_info.path.join("tests")).unwrap();
+    std::fs::write(wt_info.path.join("tests/new.rs"), "a\nb\nc\n").unwrap();
+
+    dispatch::assign_task(pool, task_id, "test-harness", &wt_info.path)
+        .await
+        .unwrap();
+    dispatch::start_task(pool, task_id).await.unwrap();
+    let verdict = GateRunner::new(pool).run_gate(task_id).await.unwrap();
+    assert!(matches!(verdict, GateVerdict::Passed));
+
+    let rule = |max_diff_lines| AutoApproveRule {
+        name: "small-tests".to_string(),
+        max_diff_lines: Some(max_diff_lines),
+        paths: vec!["tests/".to_string()],
+        plans: vec![],
+    };
+
+    // Three lines are over this rule's limit: a human reviews.
+    let action = evaluate_verdict(pool, task_id, &verdict, None, &[rule(2)])
+        .await
+        .unwrap();
+    assert_eq!(action, GateAction::HumanRequired);
+    let task = task_db::get_task(pool, task_id).await.unwrap().unwrap();
+    assert_eq!(task.status, TaskStatus::Checking);
+
+    let action = evaluate_verdict(pool, task_id, &verdict, None, &[rule(50)])
+        .await
+        .unwrap();
+    assert_eq!(action, GateAction::AutoPassed);
+    let task = task_db::get_task(pool, task_id).await.unwrap().unwrap();
+    assert_eq!(task.status, TaskStatus::Passed);
+
+    let approvals = auto_approvals::list_auto_approvals_for_task(pool, task_id, None)
+        .await
+        .unwrap();
+    assert_eq!(approvals.len(), 1);
+    assert_eq!(approvals[0].rule, "small-tests");
+    assert_eq!(approvals[0].diff_lines, 3);
+    assert_eq!(approvals[0].files.0, vec!["tests/new.rs"]);
+
+    wt_manager.remove_worktree(&wt_info.path).unwrap();
+    harness.teardown().await;
+}
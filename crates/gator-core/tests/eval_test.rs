@@ -0,0 +1,75 @@
+//! Integration test for the eval harness: a mock-harness suite run end to
+//! end through the orchestrator, gate, and scorecard.
+
+use tokio_util::sync::CancellationToken;
+
+use gator_core::eval::{ScriptedHarness, parse_suite, run_suite};
+use gator_core::token::TokenConfig;
+
+const SUITE: &str = r##"
+[suite]
+name = "golden"
+harness = "mock"
+timeout_secs = 30
+
+[[cases]]
+name = "create-file"
+description = "Create hello.txt containing the word hello."
+setup = { "README.md" = "# demo\n" }
+checks = ["grep -q hello hello.txt"]
+
+[cases.mock]
+files = { "hello.txt" = "hello\n" }
+
+[cases.expect]
+files = ["hello.txt", "README.md"]
+contains = { "hello.txt" = "hello" }
+
+[[cases]]
+name = "gate-catches-nothing-done"
+description = "Create hello.txt containing the word hello."
+checks = ["test -f hello.txt"]
+expect = { status = "escalated" }
+
+[[cases]]
+name = "wrong-expectation"
+description = "Create hello.txt."
+checks = ["true"]
+expect = { files = ["hello.txt"] }
+"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn mock_suite_produces_scorecard() {
+    let workdir = tempfile::TempDir::new().unwrap();
+    let suite = parse_suite(SUITE).unwrap();
+
+    let scorecard = run_suite(
+        &suite,
+        workdir.path(),
+        &|case| Ok(Box::new(ScriptedHarness::new(case.mock.clone()))),
+        &TokenConfig::new(b"eval-test-secret".to_vec()),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    let results: Vec<(&str, bool, &str)> = scorecard
+        .cases
+        .iter()
+        .map(|c| (c.name.as_str(), c.passed, c.status.as_str()))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            ("create-file", true, "passed"),
+            ("gate-catches-nothing-done", true, "escalated"),
+            ("wrong-expectation", false, "passed"),
+        ],
+        "{}",
+        scorecard.render()
+    );
+    assert_eq!(
+        scorecard.cases[2].failures,
+        vec!["missing file hello.txt".to_string()]
+    );
+    assert!(scorecard.render().contains("2/3 cases passed"));
+}
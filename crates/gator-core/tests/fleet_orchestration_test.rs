@@ -27,7 +27,10 @@ use gator_test_utils::{create_test_db, drop_test_db};
 use gator_core::harness::types::{AgentEvent, AgentHandle, MaterializedTask};
 use gator_core::harness::{Harness, HarnessRegistry};
 use gator_core::isolation::{Isolation, worktree::WorktreeIsolation};
-use gator_core::orchestrator::{OrchestratorConfig, OrchestratorResult, run_orchestrator};
+use gator_core::notify::NotificationConfig;
+use gator_core::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, OrchestratorConfig, OrchestratorResult, WatchdogConfig, run_orchestrator,
+};
 use gator_core::token::TokenConfig;
 use gator_core::worktree::WorktreeManager;
 
@@ -129,6 +132,9 @@ enum TaskBehavior {
     Hang,
 }
 
+/// Spawn order log: (task name, spawn time).
+type SpawnLog = Arc<Mutex<Vec<(String, chrono::DateTime<chrono::Utc>)>>>;
+
 /// A mock harness where behavior can be configured per task name.
 struct ConfigurableMockHarness {
     behaviors: Arc<Mutex<HashMap<String, TaskBehavior>>>,
@@ -136,7 +142,7 @@ struct ConfigurableMockHarness {
     /// Map task_id -> task_name for resolving behavior in events().
     task_names: Arc<Mutex<HashMap<Uuid, String>>>,
     /// Track spawn order for topological assertions.
-    spawn_log: Arc<Mutex<Vec<(String, chrono::DateTime<chrono::Utc>)>>>,
+    spawn_log: SpawnLog,
 }
 
 impl ConfigurableMockHarness {
@@ -360,6 +366,12 @@ async fn diamond_dag_topological_order() {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -381,7 +393,17 @@ async fn diamond_dag_topological_order() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -455,6 +477,12 @@ async fn retry_with_escalation() {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -502,7 +530,17 @@ async fn retry_with_escalation() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -547,6 +585,12 @@ async fn timeout_kills_agent() {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -596,7 +640,17 @@ async fn timeout_kills_agent() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_millis(200), // short timeout
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -638,6 +692,12 @@ async fn restart_recovery() {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -713,7 +773,17 @@ async fn restart_recovery() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -748,6 +818,12 @@ async fn status_and_log_after_run() {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -795,7 +871,17 @@ async fn status_and_log_after_run() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -3,6 +3,7 @@
 //! Uses a MockHarness that produces configurable event sequences without
 //! spawning real subprocesses.
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::process::Command;
@@ -14,16 +15,22 @@ use futures::Stream;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use gator_db::models::{InvariantKind, InvariantScope, TaskStatus};
+use gator_db::models::{InvariantKind, InvariantScope, LifecyclePhase, TaskStatus};
 use gator_db::queries::agent_events;
+use gator_db::queries::attempt_phases;
+use gator_db::queries::attestations;
 use gator_db::queries::invariants::{self, NewInvariant};
 use gator_db::queries::plans as plan_db;
+use gator_db::queries::prompt_trims;
+use gator_db::queries::setup_results;
 use gator_db::queries::tasks as task_db;
+use gator_db::queries::warmup_results;
 
 use std::sync::Arc;
 
-use gator_core::harness::Harness;
+use gator_core::gate::attestation::{self, ATTESTATION_DIR, AttestationFile};
 use gator_core::harness::types::{AgentEvent, AgentHandle, MaterializedTask};
+use gator_core::harness::{Harness, PromptBudget};
 use gator_core::isolation::{Isolation, worktree::WorktreeIsolation};
 use gator_core::lifecycle::{LifecycleConfig, LifecycleResult, run_agent_lifecycle};
 use gator_core::token::TokenConfig;
@@ -132,6 +139,8 @@ struct MockHarness {
     behavior: MockBehavior,
     /// Track whether work was done (e.g., write a marker file in the worktree).
     do_work: bool,
+    /// Environment the agent was spawned with.
+    spawn_env: std::sync::Mutex<HashMap<String, String>>,
 }
 
 impl MockHarness {
@@ -139,6 +148,7 @@ impl MockHarness {
         Self {
             behavior: MockBehavior::Complete { events },
             do_work,
+            spawn_env: Default::default(),
         }
     }
 
@@ -146,6 +156,7 @@ impl MockHarness {
         Self {
             behavior: MockBehavior::Hang,
             do_work: false,
+            spawn_env: Default::default(),
         }
     }
 }
@@ -157,6 +168,7 @@ impl Harness for MockHarness {
     }
 
     async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        *self.spawn_env.lock().unwrap() = task.env_vars.clone();
         // If do_work is true, create a marker file in the working directory.
         if self.do_work {
             let marker = task.working_dir.join("agent-work.txt");
@@ -247,6 +259,12 @@ async fn setup_passing_task(pool: &SqlitePool, repo_path: &Path) -> (Uuid, gator
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -308,6 +326,12 @@ async fn setup_failing_task(
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -343,15 +367,27 @@ async fn happy_path_lifecycle_passes() {
     );
 
     let isolation = harness.isolation();
+    let run_id = Uuid::new_v4();
     let result = run_agent_lifecycle(
         pool,
         &task,
         "lifecycle-plan",
         &mock,
+        None,
         isolation.as_ref(),
         &test_token_config(),
         &LifecycleConfig {
             timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: Some(run_id),
         },
     )
     .await
@@ -363,6 +399,343 @@ async fn happy_path_lifecycle_passes() {
     let updated = task_db::get_task(pool, task.id).await.unwrap().unwrap();
     assert_eq!(updated.status, TaskStatus::Passed);
 
+    // Events and gate results carry the run and attempt IDs.
+    assert_eq!(updated.run_id, Some(run_id));
+    let attempt_id = updated.attempt_id.expect("attempt ID recorded");
+    let events = agent_events::list_all_events_for_task(pool, task.id)
+        .await
+        .unwrap();
+    assert!(!events.is_empty());
+    assert!(
+        events
+            .iter()
+            .all(|e| e.run_id == Some(run_id) && e.attempt_id == Some(attempt_id))
+    );
+    let gate = gator_db::queries::gate_results::get_gate_results(pool, task.id, 0)
+        .await
+        .unwrap();
+    assert!(!gate.is_empty());
+    assert!(gate.iter().all(|r| r.run_id == Some(run_id)));
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn failed_warmup_is_recorded_and_agent_still_runs() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], false);
+
+    let isolation = harness.isolation();
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &LifecycleConfig {
+            timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: Some("echo warming up; echo broken >&2; exit 3".to_string()),
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
+        },
+    )
+    .await
+    .expect("lifecycle should succeed");
+
+    assert_eq!(result, LifecycleResult::Passed);
+
+    let warmup = warmup_results::get_warmup_result(pool, task.id, 0)
+        .await
+        .unwrap()
+        .expect("warm-up result recorded");
+    assert!(!warmup.passed);
+    assert_eq!(warmup.exit_code, Some(3));
+    assert_eq!(warmup.stdout.as_deref(), Some("warming up\n"));
+    assert_eq!(warmup.stderr.as_deref(), Some("broken\n"));
+    let updated = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    assert_eq!(warmup.attempt_id, updated.attempt_id);
+
+    let phases = attempt_phases::list_phases_for_attempt(pool, task.id, 0)
+        .await
+        .unwrap();
+    assert!(phases.iter().any(|p| p.phase == LifecyclePhase::Warmup));
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn failed_setup_command_fails_attempt_before_agent_starts() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], true);
+
+    let isolation = harness.isolation();
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &LifecycleConfig {
+            timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: vec![
+                "echo ready".to_string(),
+                "echo missing lockfile >&2; exit 4".to_string(),
+                "echo never".to_string(),
+            ],
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
+        },
+    )
+    .await
+    .expect("lifecycle should succeed");
+
+    assert_eq!(result, LifecycleResult::FailedCanRetry);
+    assert!(
+        mock.spawn_env.lock().unwrap().is_empty(),
+        "agent must not be spawned"
+    );
+
+    // Setup stops at the first failing command.
+    let setup = setup_results::list_setup_results(pool, task.id, 0)
+        .await
+        .unwrap();
+    assert_eq!(setup.len(), 2);
+    assert!(setup[0].passed);
+    assert_eq!(setup[0].stdout.as_deref(), Some("ready\n"));
+    assert!(!setup[1].passed);
+    assert_eq!(setup[1].position, 1);
+    assert_eq!(setup[1].exit_code, Some(4));
+    assert_eq!(setup[1].stderr.as_deref(), Some("missing lockfile\n"));
+
+    let updated = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    assert_eq!(updated.status, TaskStatus::Failed);
+    assert_eq!(setup[1].attempt_id, updated.attempt_id);
+
+    let events = agent_events::list_all_events_for_task(pool, task.id)
+        .await
+        .unwrap();
+    assert!(events.iter().any(|e| {
+        e.event_type == "error"
+            && e.payload["message"]
+                .as_str()
+                .is_some_and(|m| m.contains("exited with 4"))
+    }));
+
+    let phases = attempt_phases::list_phases_for_attempt(pool, task.id, 0)
+        .await
+        .unwrap();
+    assert!(phases.iter().any(|p| p.phase == LifecyclePhase::Setup));
+    assert!(!phases.iter().any(|p| p.phase == LifecyclePhase::Agent));
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn prompt_over_budget_records_trimmed_sections() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], false);
+
+    let isolation = harness.isolation();
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &LifecycleConfig {
+            timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: Some(PromptBudget::new(1, vec!["invariants".to_string()]).unwrap()),
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
+        },
+    )
+    .await
+    .expect("lifecycle should succeed");
+    assert_eq!(result, LifecycleResult::Passed);
+
+    // The invariant list is dropped; the gate still runs the invariant.
+    let trims = prompt_trims::list_trims_for_attempt(pool, task.id, 0)
+        .await
+        .unwrap();
+    assert_eq!(trims.len(), 1);
+    assert_eq!(trims[0].section, "invariants");
+    assert!(trims[0].estimated_tokens > 0);
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn attestation_is_recorded_and_committed_with_the_work() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    // The plan's base branch.
+    let git = |args: &[&str], dir: &Path| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    git(&["branch", "-M", "main"], &harness.repo_path);
+    let base_commit = git(&["rev-parse", "HEAD"], &harness.repo_path);
+
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], true);
+
+    let isolation = harness.isolation();
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &LifecycleConfig {
+            timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: true,
+            run_id: None,
+        },
+    )
+    .await
+    .expect("lifecycle should succeed");
+    assert_eq!(result, LifecycleResult::Passed);
+
+    let recorded = attestations::list_attestations_for_task(pool, task.id, None)
+        .await
+        .unwrap();
+    assert_eq!(recorded.len(), 1);
+    let attested = &recorded[0];
+    assert_eq!(attested.harness, "mock-harness");
+    assert_eq!(attested.base_commit, base_commit.trim());
+    assert_eq!(attested.path, ".gator/attestations/lifecycle-task.json");
+    assert_eq!(attested.prompt_sha256.len(), 64);
+
+    // The file is committed to the task's branch with the agent's work.
+    let updated = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    let worktree = PathBuf::from(updated.worktree_path.unwrap());
+    let committed = git(&["show", &format!("HEAD:{}", attested.path)], &worktree);
+    let file: AttestationFile = serde_json::from_str(&committed).unwrap();
+    assert_eq!(file.task_id, task.id);
+    assert_eq!(file.diff_sha256, attested.diff_sha256);
+    assert_eq!(file.prompt_sha256, attested.prompt_sha256);
+    assert!(git(&["status", "--porcelain"], &worktree).is_empty());
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn partial_approval_attests_only_the_approved_change() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let git = |args: &[&str], dir: &Path| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    git(&["branch", "-M", "main"], &harness.repo_path);
+    let base_commit = git(&["rev-parse", "HEAD"], &harness.repo_path);
+
+    // A task waiting on review, with two changed files and an attestation
+    // over both.
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+    let manager = WorktreeManager::new(
+        &harness.repo_path,
+        Some(harness.worktree_base_dir.path().to_path_buf()),
+    )
+    .unwrap();
+    let info = manager
+        .create_worktree(&WorktreeManager::branch_name("lifecycle-plan", &task.name))
+        .unwrap();
+    std::fs::write(info.path.join("keep.txt"), "approved\n").unwrap();
+    std::fs::write(info.path.join("drop.txt"), "rejected\n").unwrap();
+    sqlx::query("UPDATE tasks SET status = 'checking', worktree_path = $1 WHERE id = $2")
+        .bind(info.path.to_string_lossy().to_string())
+        .bind(task.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let task = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    let full = attestation::attest(pool, &task, "mock-harness", Some("1.0"), "abc", &info.path)
+        .await
+        .unwrap();
+
+    let approval =
+        gator_core::state::dispatch::approve_task_paths(pool, task.id, &["keep.txt".to_string()])
+            .await
+            .unwrap();
+    assert_eq!(approval.committed, vec!["keep.txt".to_string()]);
+    assert_eq!(approval.discarded, vec!["drop.txt".to_string()]);
+
+    // The committed attestation hashes what landed, not the full change.
+    let recorded = attestations::list_attestations_for_task(pool, task.id, None)
+        .await
+        .unwrap();
+    assert_eq!(recorded.len(), 2);
+    let approved = &recorded[1];
+    assert_ne!(approved.diff_sha256, full.diff_sha256);
+    assert_eq!(approved.harness_version.as_deref(), Some("1.0"));
+    assert_eq!(approved.prompt_sha256, "abc");
+    assert!(git(&["status", "--porcelain"], &info.path).is_empty());
+    assert_eq!(
+        gator_core::worktree::diff_hash(&info.path, base_commit.trim(), ATTESTATION_DIR).unwrap(),
+        approved.diff_sha256
+    );
+    let committed = git(&["show", &format!("HEAD:{}", approved.path)], &info.path);
+    let file: AttestationFile = serde_json::from_str(&committed).unwrap();
+    assert_eq!(file.diff_sha256, approved.diff_sha256);
+    assert!(!info.path.join("drop.txt").exists());
+
     harness.teardown().await;
 }
 
@@ -381,10 +754,21 @@ async fn failing_invariant_with_retries_returns_failed_can_retry() {
         &task,
         "lifecycle-fail-plan",
         &mock,
+        None,
         isolation.as_ref(),
         &test_token_config(),
         &LifecycleConfig {
             timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
         },
     )
     .await
@@ -398,6 +782,113 @@ async fn failing_invariant_with_retries_returns_failed_can_retry() {
     harness.teardown().await;
 }
 
+#[tokio::test]
+async fn retry_env_reaches_agent_and_gate_of_the_retried_attempt() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let (_plan_id, task) = setup_failing_task(pool, &harness.repo_path, 3).await;
+    // Passes only when the override is set.
+    let args = vec![
+        "-c".to_string(),
+        "test \"$GATOR_TEST_DEBUG\" = on".to_string(),
+    ];
+    let inv = invariants::insert_invariant(
+        pool,
+        &NewInvariant {
+            name: "needs_debug",
+            description: None,
+            kind: InvariantKind::Custom,
+            command: "sh",
+            args: &args,
+            expected_exit_code: 0,
+            threshold: None,
+            scope: InvariantScope::Project,
+            timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
+        },
+    )
+    .await
+    .unwrap();
+    sqlx::query("DELETE FROM task_invariants WHERE task_id = $1")
+        .bind(task.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    task_db::link_task_invariant(pool, task.id, inv.id)
+        .await
+        .unwrap();
+
+    let config = LifecycleConfig {
+        timeout: Duration::from_secs(30),
+        stall_timeout: None,
+        ephemeral_db: None,
+        setup_commands: Vec::new(),
+        warmup_command: None,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        auto_approve: Vec::new(),
+        attestation: false,
+        run_id: None,
+    };
+    let isolation = harness.isolation();
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], false);
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-fail-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &config,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, LifecycleResult::FailedCanRetry);
+    assert!(
+        !mock
+            .spawn_env
+            .lock()
+            .unwrap()
+            .contains_key("GATOR_TEST_DEBUG")
+    );
+
+    let env = BTreeMap::from([("GATOR_TEST_DEBUG".to_string(), "on".to_string())]);
+    gator_core::state::dispatch::operator_retry_task(pool, task.id, false, &env, None)
+        .await
+        .unwrap();
+    let retried = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    assert_eq!(retried.attempt_env(), Some(&env));
+
+    let mock = MockHarness::completing(vec![AgentEvent::Completed], false);
+    let result = run_agent_lifecycle(
+        pool,
+        &retried,
+        "lifecycle-fail-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &config,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, LifecycleResult::Passed);
+    assert_eq!(
+        mock.spawn_env.lock().unwrap().get("GATOR_TEST_DEBUG"),
+        Some(&"on".to_string())
+    );
+
+    harness.teardown().await;
+}
+
 #[tokio::test]
 async fn failing_invariant_no_retries_returns_failed_no_retry() {
     let harness = TestHarness::new().await;
@@ -413,10 +904,21 @@ async fn failing_invariant_no_retries_returns_failed_no_retry() {
         &task,
         "lifecycle-fail-plan",
         &mock,
+        None,
         isolation.as_ref(),
         &test_token_config(),
         &LifecycleConfig {
             timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
         },
     )
     .await
@@ -442,10 +944,21 @@ async fn timeout_returns_timed_out() {
         &task,
         "lifecycle-plan",
         &mock,
+        None,
         isolation.as_ref(),
         &test_token_config(),
         &LifecycleConfig {
             timeout: Duration::from_millis(100),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
         },
     )
     .await
@@ -460,6 +973,85 @@ async fn timeout_returns_timed_out() {
     harness.teardown().await;
 }
 
+#[tokio::test]
+async fn silent_agent_stalls_once_progress_stops() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let (_plan_id, task) = setup_passing_task(pool, &harness.repo_path).await;
+
+    // The agent's output stream stays silent, but it reports progress (as
+    // `gator progress` would) for a while before going quiet.
+    let reporter = {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            for i in 0..6 {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                agent_events::insert_agent_event(
+                    &pool,
+                    &agent_events::NewAgentEvent {
+                        task_id: task.id,
+                        attempt: 0,
+                        event_type: "progress".to_string(),
+                        payload: serde_json::json!({ "message": format!("step {i}") }),
+                    },
+                )
+                .await
+                .unwrap();
+            }
+        })
+    };
+
+    let mock = MockHarness::hanging();
+    let isolation = harness.isolation();
+    let started = std::time::Instant::now();
+    let result = run_agent_lifecycle(
+        pool,
+        &task,
+        "lifecycle-plan",
+        &mock,
+        None,
+        isolation.as_ref(),
+        &test_token_config(),
+        &LifecycleConfig {
+            timeout: Duration::from_secs(30),
+            stall_timeout: Some(Duration::from_secs(1)),
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
+        },
+    )
+    .await
+    .expect("lifecycle should succeed on a stall");
+    reporter.await.unwrap();
+
+    assert_eq!(result, LifecycleResult::Stalled);
+    assert!(
+        started.elapsed() >= Duration::from_millis(2500),
+        "progress reports keep the agent alive: {:?}",
+        started.elapsed()
+    );
+
+    let updated = task_db::get_task(pool, task.id).await.unwrap().unwrap();
+    assert_eq!(updated.status, TaskStatus::Failed);
+    let error = agent_events::get_latest_event_of_type(pool, task.id, 0, "error")
+        .await
+        .unwrap()
+        .expect("stall recorded as an error event");
+    assert_eq!(
+        error.payload["message"],
+        "agent stalled: no activity for 1s"
+    );
+
+    harness.teardown().await;
+}
+
 #[tokio::test]
 async fn events_persisted_to_db() {
     let harness = TestHarness::new().await;
@@ -492,10 +1084,21 @@ async fn events_persisted_to_db() {
         &task,
         "lifecycle-plan",
         &mock,
+        None,
         isolation.as_ref(),
         &test_token_config(),
         &LifecycleConfig {
             timeout: Duration::from_secs(30),
+            stall_timeout: None,
+            ephemeral_db: None,
+            setup_commands: Vec::new(),
+            warmup_command: None,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            auto_approve: Vec::new(),
+            attestation: false,
+            run_id: None,
         },
     )
     .await
@@ -52,6 +52,12 @@ async fn create_test_fixtures(pool: &SqlitePool) -> (Uuid, Uuid) {
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -104,6 +110,11 @@ async fn attempt_one_with_failures_includes_feedback() {
             stdout: Some("test output".to_string()),
             stderr: Some("error: test failed\n  at src/lib.rs:42".to_string()),
             duration_ms: Some(500),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            content_hash: None,
+            cached_from: None,
         },
     )
     .await
@@ -158,6 +169,11 @@ async fn attempt_one_with_all_passed_has_no_feedback() {
             stdout: Some("all tests passed".to_string()),
             stderr: None,
             duration_ms: Some(200),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            content_hash: None,
+            cached_from: None,
         },
     )
     .await
@@ -202,6 +218,11 @@ async fn stderr_truncated_at_2048_bytes() {
             stdout: None,
             stderr: Some(long_stderr),
             duration_ms: Some(100),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            content_hash: None,
+            cached_from: None,
         },
     )
     .await
@@ -234,3 +255,52 @@ async fn stderr_truncated_at_2048_bytes() {
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+#[tokio::test]
+async fn reviewer_rejection_reason_included_in_feedback() {
+    let (pool, db_name) = create_test_db().await;
+    let (task_id, _inv_id) = create_test_fixtures(&pool).await;
+
+    // Awaiting human review at attempt 0; the reviewer rejects it.
+    sqlx::query("UPDATE tasks SET status = 'checking' WHERE id = $1")
+        .bind(task_id)
+        .execute(&pool)
+        .await
+        .expect("set checking");
+    gator_core::state::dispatch::reject_task(
+        &pool,
+        task_id,
+        Some("  Handle the empty-input case.\nAdd a test for it.  "),
+    )
+    .await
+    .expect("reject should succeed");
+
+    let task = task_db::get_task(&pool, task_id)
+        .await
+        .expect("get task")
+        .expect("task exists");
+    assert_eq!(task.status, gator_db::models::TaskStatus::Failed);
+
+    sqlx::query("UPDATE tasks SET attempt = 1 WHERE id = $1")
+        .bind(task_id)
+        .execute(&pool)
+        .await
+        .expect("update attempt");
+
+    let md = materialize_task(&pool, task_id)
+        .await
+        .expect("materialize should succeed");
+
+    assert!(
+        md.contains("Attempt 0 was rejected by a reviewer"),
+        "feedback should say the attempt was rejected"
+    );
+    assert!(
+        md.contains("> Handle the empty-input case.\n> Add a test for it.\n"),
+        "reason should be quoted line by line, trimmed"
+    );
+    assert!(!md.contains("did not pass"), "no check failures to report");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,115 @@
+//! Tests for the long-term memory store and its retrieval into
+//! materialized tasks.
+
+use sqlx::SqlitePool;
+
+use gator_db::models::Task;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+use gator_test_utils::{create_test_db, drop_test_db};
+
+use gator_core::gate::{GateFailure, GateVerdict};
+use gator_core::memory::{self, TfIdfRetriever};
+use gator_core::plan::materialize_task;
+
+async fn create_task(
+    pool: &SqlitePool,
+    project: &str,
+    plan_name: &str,
+    name: &str,
+    description: &str,
+) -> Task {
+    let plan = plan_db::insert_plan(
+        pool,
+        plan_name,
+        project,
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .expect("insert plan");
+    task_db::insert_task(pool, plan.id, name, description, "narrow", "auto", 3, None)
+        .await
+        .expect("insert task")
+}
+
+#[tokio::test]
+async fn materialized_task_recalls_relevant_memories_of_its_project() {
+    let (pool, db_name) = create_test_db().await;
+
+    let migration = create_task(
+        &pool,
+        "/tmp/project",
+        "schema-v1",
+        "add-users-migration",
+        "Add a sqlx migration creating the users table with an email index.",
+    )
+    .await;
+    let docs = create_task(
+        &pool,
+        "/tmp/project",
+        "docs",
+        "readme",
+        "Document installation steps in the README.",
+    )
+    .await;
+    let elsewhere = create_task(
+        &pool,
+        "/tmp/other-project",
+        "schema",
+        "add-orders-migration",
+        "Add a sqlx migration creating the orders table with an index.",
+    )
+    .await;
+
+    let failed = GateVerdict::Failed {
+        failures: vec![GateFailure {
+            invariant_name: "cargo-test".to_string(),
+            exit_code: Some(101),
+            timed_out: false,
+            stderr_snippet: "migration 012 conflicts with an existing index name".to_string(),
+        }],
+    };
+    memory::record_gate_outcome(&pool, &migration, &failed)
+        .await
+        .expect("record failure");
+    memory::record_gate_outcome(&pool, &docs, &GateVerdict::Passed)
+        .await
+        .expect("record success");
+    memory::record_gate_outcome(&pool, &elsewhere, &GateVerdict::Passed)
+        .await
+        .expect("record other project");
+
+    let task = create_task(
+        &pool,
+        "/tmp/project",
+        "schema-v2",
+        "add-orders-migration",
+        "Add a sqlx migration creating the orders table with a customer index.",
+    )
+    .await;
+
+    let recalled = memory::recall(&pool, &task, &TfIdfRetriever, 3)
+        .await
+        .expect("recall");
+    let titles: Vec<&str> = recalled.iter().map(|m| m.title.as_str()).collect();
+    assert_eq!(titles, vec!["schema-v1/add-users-migration"]);
+
+    let md = materialize_task(&pool, task.id).await.expect("materialize");
+    assert!(md.contains("## Relevant Past Experience"));
+    assert!(md.contains("### schema-v1/add-users-migration (failed)"));
+    assert!(md.contains("- cargo-test (exit 101): migration 012 conflicts"));
+    assert!(!md.contains("docs/readme"));
+
+    // A task never recalls its own memories.
+    let md = materialize_task(&pool, migration.id)
+        .await
+        .expect("materialize");
+    assert!(!md.contains("Relevant Past Experience"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
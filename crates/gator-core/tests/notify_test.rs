@@ -0,0 +1,353 @@
+//! Integration tests for webhook notification delivery and dead-lettering.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{InvariantKind, InvariantScope, Plan};
+use gator_db::queries::gate_results::{self, NewGateResult};
+use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::notifications as notify_db;
+use gator_db::queries::plans as plan_db;
+use gator_db::queries::tasks as task_db;
+use gator_test_utils::{create_test_db, drop_test_db};
+
+use gator_core::notify::{
+    ChatConfig, Notification, NotificationConfig, NotificationEvent, Notifier, WebhookConfig,
+    WebhookTransport,
+};
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+/// Long enough for any test delivery; notifications are sent in the
+/// background, so tests wait for them before looking.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport that fails the first `failures` requests, then succeeds.
+/// Records every (url, body) it receives.
+struct FlakyTransport {
+    failures: usize,
+    calls: Mutex<Vec<(String, String)>>,
+}
+
+impl FlakyTransport {
+    fn new(failures: usize) -> Arc<Self> {
+        Arc::new(Self {
+            failures,
+            calls: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn calls(&self) -> Vec<(String, String)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for FlakyTransport {
+    async fn post(&self, url: &str, body: &str) -> Result<()> {
+        let mut calls = self.calls.lock().unwrap();
+        calls.push((url.to_string(), body.to_string()));
+        if calls.len() <= self.failures {
+            bail!("webhook returned HTTP 503");
+        }
+        Ok(())
+    }
+}
+
+async fn create_test_plan(pool: &SqlitePool) -> Plan {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO plans (id, name, project_path, base_branch) \
+         VALUES ($1, 'notify-plan', '/tmp/project', 'main')",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .expect("failed to insert test plan");
+    plan_db::get_plan(pool, id).await.unwrap().unwrap()
+}
+
+fn notifier(
+    pool: &SqlitePool,
+    webhooks: Vec<WebhookConfig>,
+    transport: Arc<FlakyTransport>,
+) -> Notifier {
+    let config = NotificationConfig {
+        webhooks,
+        max_attempts: 3,
+        ..NotificationConfig::default()
+    };
+    Notifier::new(pool.clone(), config)
+        .with_transport(transport)
+        .with_backoff(Duration::ZERO)
+}
+
+fn webhook(url: &str, events: Vec<NotificationEvent>) -> WebhookConfig {
+    WebhookConfig {
+        url: url.to_string(),
+        events,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn delivers_after_transient_failures() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_test_plan(&pool).await;
+    let transport = FlakyTransport::new(2);
+    let notifier = notifier(&pool, vec![webhook("https://a", vec![])], transport.clone());
+
+    notifier
+        .notify(&Notification::for_plan(
+            NotificationEvent::PlanCompleted,
+            &plan,
+            "all tasks passed",
+        ))
+        .await;
+    assert!(notifier.flush(FLUSH_TIMEOUT).await);
+
+    let calls = transport.calls();
+    assert_eq!(calls.len(), 3, "two failures then one success");
+    let body: serde_json::Value = serde_json::from_str(&calls[2].1).unwrap();
+    assert_eq!(body["schema_version"], 1);
+    assert_eq!(body["event"], "plan_completed");
+    assert_eq!(body["plan_name"], "notify-plan");
+    assert!(
+        notify_db::list_dead_letters(&pool)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn exhausted_retries_are_dead_lettered() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_test_plan(&pool).await;
+    let transport = FlakyTransport::new(usize::MAX);
+    let notifier = notifier(
+        &pool,
+        vec![webhook("https://down", vec![])],
+        transport.clone(),
+    );
+    let task_id = Uuid::new_v4();
+
+    notifier
+        .notify(&Notification::for_task(
+            NotificationEvent::TaskEscalated,
+            &plan,
+            task_id,
+            "flaky-task",
+            "escalated",
+        ))
+        .await;
+    assert!(notifier.flush(FLUSH_TIMEOUT).await);
+
+    assert_eq!(transport.calls().len(), 3);
+    let dead = notify_db::list_dead_letters(&pool).await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].event, "task_escalated");
+    assert_eq!(dead[0].url, "https://down");
+    assert_eq!(dead[0].attempts, 3);
+    assert!(dead[0].error.contains("503"));
+    assert_eq!(dead[0].payload["task_id"], task_id.to_string());
+    assert_eq!(dead[0].payload["task_name"], "flaky-task");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn webhooks_only_receive_subscribed_events() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_test_plan(&pool).await;
+    let transport = FlakyTransport::new(0);
+    let notifier = notifier(
+        &pool,
+        vec![
+            webhook("https://all", vec![]),
+            webhook(
+                "https://escalations",
+                vec![NotificationEvent::TaskEscalated],
+            ),
+        ],
+        transport.clone(),
+    );
+
+    notifier
+        .notify(&Notification::for_plan(
+            NotificationEvent::PlanFailed,
+            &plan,
+            "plan failed",
+        ))
+        .await;
+    assert!(notifier.flush(FLUSH_TIMEOUT).await);
+
+    let urls: Vec<String> = transport.calls().into_iter().map(|(u, _)| u).collect();
+    assert_eq!(urls, vec!["https://all".to_string()]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn human_review_posts_chat_message_with_gate_summary() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_test_plan(&pool).await;
+    let task = task_db::insert_task(
+        &pool,
+        plan.id,
+        "review-me",
+        "needs a human",
+        "narrow",
+        "human_review",
+        3,
+        None,
+    )
+    .await
+    .unwrap();
+    let invariant = invariants::insert_invariant(
+        &pool,
+        &NewInvariant {
+            name: "notify_lint",
+            description: None,
+            kind: InvariantKind::Lint,
+            command: "false",
+            args: &[],
+            expected_exit_code: 0,
+            threshold: None,
+            scope: InvariantScope::Project,
+            timeout_secs: 30,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
+        },
+    )
+    .await
+    .unwrap();
+    gate_results::insert_gate_result(
+        &pool,
+        &NewGateResult {
+            task_id: task.id,
+            invariant_id: invariant.id,
+            attempt: task.attempt,
+            passed: false,
+            exit_code: Some(1),
+            stdout: None,
+            stderr: None,
+            duration_ms: Some(5),
+            timed_out: false,
+            base_value: None,
+            task_value: None,
+            content_hash: None,
+            cached_from: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let transport = FlakyTransport::new(0);
+    let config = NotificationConfig {
+        slack: Some(ChatConfig {
+            webhook_url: "https://hooks.slack.test".to_string(),
+        }),
+        ..NotificationConfig::default()
+    };
+    let notifier = Notifier::new(pool.clone(), config)
+        .with_transport(transport.clone())
+        .with_backoff(Duration::ZERO);
+
+    notifier
+        .notify(&Notification::for_task(
+            NotificationEvent::HumanReviewRequired,
+            &plan,
+            task.id,
+            &task.name,
+            "waiting for review",
+        ))
+        .await;
+    // Plan-level events are not sent to chat.
+    notifier
+        .notify(&Notification::for_plan(
+            NotificationEvent::PlanCompleted,
+            &plan,
+            "done",
+        ))
+        .await;
+    assert!(notifier.flush(FLUSH_TIMEOUT).await);
+
+    let calls = transport.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0, "https://hooks.slack.test");
+    let body: serde_json::Value = serde_json::from_str(&calls[0].1).unwrap();
+    let text = body["text"].as_str().expect("slack payload has text");
+    assert!(text.contains("`review-me`"));
+    assert!(text.contains("0/1 invariants passed"));
+    assert!(text.contains("FAIL notify_lint (exit 1)"));
+    assert!(text.contains(&format!("gator approve {}", task.id)));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+/// Transport whose requests never complete, like an endpoint that accepts
+/// the connection and never answers.
+struct HangingTransport;
+
+#[async_trait]
+impl WebhookTransport for HangingTransport {
+    async fn post(&self, _url: &str, _body: &str) -> Result<()> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn hanging_webhook_does_not_hold_up_the_caller() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_test_plan(&pool).await;
+    let config = NotificationConfig {
+        webhooks: vec![webhook("https://hangs", vec![])],
+        ..NotificationConfig::default()
+    };
+    let notifier = Notifier::new(pool.clone(), config).with_transport(Arc::new(HangingTransport));
+
+    // Every notification of a run is queued at once, though none is ever
+    // delivered.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        for _ in 0..10 {
+            notifier
+                .notify(&Notification::for_plan(
+                    NotificationEvent::PlanCompleted,
+                    &plan,
+                    "done",
+                ))
+                .await;
+        }
+    })
+    .await
+    .expect("notify waited on delivery");
+
+    // Waiting for delivery gives up after its timeout.
+    assert!(!notifier.flush(Duration::from_millis(100)).await);
+
+    drop(notifier);
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
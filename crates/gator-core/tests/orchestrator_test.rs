@@ -8,13 +8,15 @@ use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use sqlx::SqlitePool;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use gator_db::models::{InvariantKind, InvariantScope, PlanStatus, TaskStatus};
+use gator_db::models::{InvariantKind, InvariantScope, LifecyclePhase, PlanStatus, TaskStatus};
+use gator_db::queries::attempt_phases;
 use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::plan_throttles;
 use gator_db::queries::plans as plan_db;
 use gator_db::queries::tasks as task_db;
 use gator_test_utils::{create_test_db, drop_test_db};
@@ -22,7 +24,12 @@ use gator_test_utils::{create_test_db, drop_test_db};
 use gator_core::harness::types::{AgentEvent, AgentHandle, MaterializedTask};
 use gator_core::harness::{Harness, HarnessRegistry};
 use gator_core::isolation::{Isolation, worktree::WorktreeIsolation};
-use gator_core::orchestrator::{OrchestratorConfig, OrchestratorResult, run_orchestrator};
+use gator_core::notify::{NotificationConfig, WebhookConfig};
+use gator_core::orchestrator::{
+    DEFAULT_DRAIN_TIMEOUT, DispatcherIdentity, OrchestratorConfig, OrchestratorResult,
+    TaskSelection, WatchdogConfig, cancel_task, preview_dispatch, process_start_time,
+    resolve_selection, run_orchestrator, stop_all,
+};
 use gator_core::token::TokenConfig;
 use gator_core::worktree::WorktreeManager;
 
@@ -177,6 +184,12 @@ async fn create_invariant(
             threshold: None,
             scope: InvariantScope::Project,
             timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &Default::default(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
         },
     )
     .await
@@ -231,7 +244,17 @@ async fn single_task_passes_completes_plan() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -243,6 +266,34 @@ async fn single_task_passes_completes_plan() {
     let plan_final = plan_db::get_plan(pool, plan.id).await.unwrap().unwrap();
     assert_eq!(plan_final.status, PlanStatus::Completed);
 
+    // The run is attributed to this process.
+    assert_eq!(
+        plan_final.dispatched_pid,
+        Some(i64::from(std::process::id()))
+    );
+    assert!(plan_final.dispatched_host.is_some());
+    assert!(plan_final.dispatched_at.is_some());
+
+    // Every phase the attempt went through was timed; no services were
+    // configured, so that phase is absent.
+    let phases: Vec<LifecyclePhase> = attempt_phases::list_phases_for_attempt(pool, task.id, 0)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.phase)
+        .collect();
+    assert_eq!(
+        phases,
+        vec![
+            LifecyclePhase::Workspace,
+            LifecyclePhase::Materialize,
+            LifecyclePhase::Agent,
+            LifecyclePhase::Extract,
+            LifecyclePhase::Gate,
+            LifecyclePhase::Verdict,
+        ]
+    );
+
     harness.teardown().await;
 }
 
@@ -291,7 +342,17 @@ async fn two_independent_tasks_both_pass() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -360,7 +421,17 @@ async fn sequential_dependency_runs_in_order() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -381,6 +452,192 @@ async fn sequential_dependency_runs_in_order() {
     harness.teardown().await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unreachable_webhook_does_not_delay_dispatch() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    // An endpoint that accepts connections and never answers: each delivery
+    // hangs until curl's timeout.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let accept = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+    let plan = plan_db::insert_plan(
+        pool,
+        "webhook-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+    let task_a = task_db::insert_task(pool, plan.id, "task-a", "Task A", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    let task_b = task_db::insert_task(pool, plan.id, "task-b", "Task B", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    for task in [&task_a, &task_b] {
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+    }
+    task_db::insert_task_dependency(pool, task_b.id, task_a.id)
+        .await
+        .unwrap();
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let config = OrchestratorConfig {
+        max_agents: 4,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(30),
+        notifications: NotificationConfig {
+            webhooks: vec![WebhookConfig {
+                url,
+                events: Vec::new(),
+            }],
+            max_attempts: 3,
+            timeout_secs: 10,
+            ..NotificationConfig::default()
+        },
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let token_config = test_token_config();
+    let run = run_orchestrator(
+        pool,
+        plan.id,
+        &registry,
+        &isolation,
+        &token_config,
+        &config,
+        CancellationToken::new(),
+    );
+
+    // Delivering task A's notifications inline would hold task B back for
+    // over 30 seconds. Stop watching once B has passed; the run itself
+    // still waits on the queued notifications.
+    let started = std::time::Instant::now();
+    let b_passed = async {
+        loop {
+            let tb = task_db::get_task(pool, task_b.id).await.unwrap().unwrap();
+            if tb.status == TaskStatus::Passed {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    };
+    tokio::select! {
+        result = run => panic!("run ended before task B passed: {result:?}"),
+        () = b_passed => {}
+    }
+    assert!(
+        started.elapsed() < Duration::from_secs(20),
+        "task B passed after {:?}",
+        started.elapsed()
+    );
+
+    accept.abort();
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn overlapping_paths_run_one_at_a_time() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+
+    let plan = plan_db::insert_plan(
+        pool,
+        "overlap-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+
+    // Independent tasks, but both declared to work under `src/`.
+    let mut ids = Vec::new();
+    for (name, paths) in [("task-a", "src/**"), ("task-b", "src/lib.rs")] {
+        let task = task_db::insert_task(pool, plan.id, name, "Task", "narrow", "auto", 0, None)
+            .await
+            .unwrap();
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE tasks SET paths = $1 WHERE id = $2")
+            .bind(sqlx::types::Json(vec![paths]))
+            .bind(task.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        ids.push(task.id);
+    }
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan.id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 4,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    // Despite free agent slots, one finished before the other started.
+    let ta = task_db::get_task(pool, ids[0]).await.unwrap().unwrap();
+    let tb = task_db::get_task(pool, ids[1]).await.unwrap().unwrap();
+    assert!(
+        ta.completed_at.unwrap() <= tb.started_at.unwrap()
+            || tb.completed_at.unwrap() <= ta.started_at.unwrap()
+    );
+
+    harness.teardown().await;
+}
+
 #[tokio::test]
 async fn fail_no_retry_escalates_to_failed() {
     let harness = TestHarness::new().await;
@@ -429,7 +686,17 @@ async fn fail_no_retry_escalates_to_failed() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -529,7 +796,17 @@ async fn restart_recovery_resets_orphaned_tasks() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -540,8 +817,114 @@ async fn restart_recovery_resets_orphaned_tasks() {
 
     let task_final = task_db::get_task(pool, task.id).await.unwrap().unwrap();
     assert_eq!(task_final.status, TaskStatus::Passed);
-    // Attempt should have been incremented (original 0 -> reset to failed -> retry = 1).
+    // The interrupted attempt was requeued as attempt 1 without using up a
+    // retry.
     assert_eq!(task_final.attempt, 1);
+    assert_eq!(task_final.retry_max, 4);
+
+    harness.teardown().await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn restart_recovery_kills_surviving_agents_but_not_reused_pids() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let plan_id = create_wide_plan(&harness, "survivor-plan", 2).await;
+    plan_db::update_plan_status(pool, plan_id, PlanStatus::Running)
+        .await
+        .unwrap();
+    let host = DispatcherIdentity::current().host;
+
+    // task-0's agent outlived its dispatch; task-1 recorded a process ID
+    // that now belongs to an unrelated process (its start time differs).
+    let mut survivor = Command::new("sleep").arg("300").spawn().unwrap();
+    let mut bystander = Command::new("sleep").arg("300").spawn().unwrap();
+    let tasks = task_db::list_tasks_for_plan(pool, plan_id).await.unwrap();
+    for (task, (pid, started)) in tasks.iter().zip([
+        (survivor.id(), process_start_time(survivor.id())),
+        (bystander.id(), Some("0".to_string())),
+    ]) {
+        task_db::assign_task_metadata(pool, task.id, "mock-harness", "/tmp/fake-worktree")
+            .await
+            .unwrap();
+        task_db::transition_task_status(
+            pool,
+            task.id,
+            TaskStatus::Pending,
+            TaskStatus::Assigned,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        task_db::transition_task_status(
+            pool,
+            task.id,
+            TaskStatus::Assigned,
+            TaskStatus::Running,
+            Some(chrono::Utc::now()),
+            None,
+        )
+        .await
+        .unwrap();
+        let process = task_db::AgentProcess {
+            pid: Some(pid),
+            started: started.as_deref(),
+            host: Some(&host),
+            container_id: None,
+        };
+        task_db::set_agent_process(pool, task.id, &process)
+            .await
+            .unwrap();
+    }
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 4,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    assert!(
+        survivor.try_wait().unwrap().is_some(),
+        "surviving agent should have been killed"
+    );
+    assert!(
+        bystander.try_wait().unwrap().is_none(),
+        "a process reusing an agent's ID must not be killed"
+    );
+    bystander.kill().unwrap();
+    bystander.wait().unwrap();
+
+    // With no retries configured, both interrupted tasks still got a new
+    // attempt instead of being escalated.
+    for task in task_db::list_tasks_for_plan(pool, plan_id).await.unwrap() {
+        assert_eq!(task.status, TaskStatus::Passed);
+        assert_eq!(task.attempt, 1);
+        assert_eq!(task.retry_max, 1);
+    }
 
     harness.teardown().await;
 }
@@ -607,7 +990,17 @@ async fn fail_then_retry_then_pass() {
         &test_token_config(),
         &OrchestratorConfig {
             max_agents: 4,
+            max_total_agents: None,
             task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
         },
         CancellationToken::new(),
     )
@@ -674,7 +1067,17 @@ async fn human_review_pauses_then_resumes_on_approve() {
     let isolation = harness.isolation();
     let config = OrchestratorConfig {
         max_agents: 4,
+        max_total_agents: None,
         task_timeout: Duration::from_secs(30),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
     };
 
     // First dispatch: should return HumanRequired.
@@ -736,3 +1139,1651 @@ async fn human_review_pauses_then_resumes_on_approve() {
 
     harness.teardown().await;
 }
+
+/// Completes like [`PassingMockHarness`], but the first spawn also marks
+/// `victim` as running behind the orchestrator's back -- a task that looks
+/// active but has no agent, which the loop on its own would wait on forever.
+struct OrphaningHarness {
+    pool: SqlitePool,
+    victim: Uuid,
+    fired: std::sync::atomic::AtomicBool,
+}
+
+#[async_trait]
+impl Harness for OrphaningHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        if !self.fired.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            task_db::update_task_status(&self.pool, self.victim, TaskStatus::Running).await?;
+        }
+        PassingMockHarness.spawn(task).await
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        PassingMockHarness.events(handle)
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn watchdog_self_heals_task_running_without_agent() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+
+    let plan = plan_db::insert_plan(
+        pool,
+        "stall-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+
+    let task_a = task_db::insert_task(pool, plan.id, "task-a", "Task A", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    let task_b = task_db::insert_task(pool, plan.id, "task-b", "Task B", "narrow", "auto", 1, None)
+        .await
+        .unwrap();
+    for task in [&task_a, &task_b] {
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+    }
+    task_db::insert_task_dependency(pool, task_b.id, task_a.id)
+        .await
+        .unwrap();
+
+    let registry = make_registry(OrphaningHarness {
+        pool: pool.clone(),
+        victim: task_b.id,
+        fired: std::sync::atomic::AtomicBool::new(false),
+    });
+    let isolation = harness.isolation();
+    let result = tokio::time::timeout(
+        Duration::from_secs(30),
+        run_orchestrator(
+            pool,
+            plan.id,
+            &registry,
+            &isolation,
+            &test_token_config(),
+            &OrchestratorConfig {
+                max_agents: 4,
+                max_total_agents: None,
+                task_timeout: Duration::from_secs(30),
+                notifications: NotificationConfig::default(),
+                watchdog: WatchdogConfig {
+                    stall_after: Some(Duration::from_millis(300)),
+                    self_heal: true,
+                    agent_stall_after: None,
+                },
+                drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+                prompt_budget: None,
+                gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+                gate_cache: true,
+                selection: None,
+                auto_approve: Vec::new(),
+                attestation: false,
+            },
+            CancellationToken::new(),
+        ),
+    )
+    .await
+    .expect("orchestrator stalled despite self-heal")
+    .unwrap();
+
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    // The orphaned task was failed by the watchdog and retried.
+    let tb = task_db::get_task(pool, task_b.id).await.unwrap().unwrap();
+    assert_eq!(tb.status, TaskStatus::Passed);
+    assert_eq!(tb.attempt, 1);
+
+    harness.teardown().await;
+}
+
+/// Spawns agents that never produce an event; each attempt ends at the
+/// task timeout.
+struct HangingMockHarness;
+
+#[async_trait]
+impl Harness for HangingMockHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        PassingMockHarness.spawn(task).await
+    }
+
+    fn events(&self, _handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        Box::pin(futures::stream::pending())
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+/// Insert an approved plan with `count` independent tasks.
+async fn create_wide_plan(harness: &TestHarness, name: &str, count: usize) -> Uuid {
+    let pool = harness.pool();
+    let inv = match invariants::get_invariant_by_name(pool, "pass_inv")
+        .await
+        .unwrap()
+    {
+        Some(inv) => inv,
+        None => create_invariant(pool, "pass_inv", "true").await,
+    };
+    let plan = plan_db::insert_plan(
+        pool,
+        name,
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+    for i in 0..count {
+        let task = task_db::insert_task(
+            pool,
+            plan.id,
+            &format!("task-{i}"),
+            "Independent task",
+            "narrow",
+            "auto",
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+    }
+    plan.id
+}
+
+#[tokio::test]
+async fn dry_run_preview_changes_nothing() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let plan_id = create_wide_plan(&harness, "preview-plan", 3).await;
+    let tasks = task_db::list_tasks_for_plan(pool, plan_id).await.unwrap();
+    task_db::update_task_status(pool, tasks[2].id, TaskStatus::Escalated)
+        .await
+        .unwrap();
+
+    let preview = preview_dispatch(pool, plan_id, 1, &["claude-code"])
+        .await
+        .unwrap();
+
+    let waves: Vec<Vec<&str>> = preview
+        .waves
+        .iter()
+        .map(|w| w.iter().map(|t| t.name.as_str()).collect())
+        .collect();
+    assert_eq!(waves, vec![vec!["task-0"], vec!["task-1"]]);
+    assert!(preview.waves.iter().flatten().all(|t| t.prompt_tokens > 0));
+    assert_eq!(preview.estimated_tokens(), None);
+    assert_eq!(
+        preview.issues,
+        vec!["task-2 is escalated (retry it with gator retry)"]
+    );
+
+    // Nothing was dispatched.
+    let plan = plan_db::get_plan(pool, plan_id).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Approved);
+    let after = task_db::list_tasks_for_plan(pool, plan_id).await.unwrap();
+    assert!(after[..2].iter().all(|t| t.status == TaskStatus::Pending));
+    assert!(after.iter().all(|t| t.worktree_path.is_none()));
+
+    harness.teardown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn selection_runs_only_a_closed_slice() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+
+    let plan = plan_db::insert_plan(
+        pool,
+        "slice-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+
+    // a -> b -> c, and d on its own.
+    let mut ids = Vec::new();
+    for name in ["a", "b", "c", "d"] {
+        let task = task_db::insert_task(pool, plan.id, name, name, "narrow", "auto", 0, None)
+            .await
+            .unwrap();
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+        ids.push(task.id);
+    }
+    task_db::insert_task_dependency(pool, ids[1], ids[0])
+        .await
+        .unwrap();
+    task_db::insert_task_dependency(pool, ids[2], ids[1])
+        .await
+        .unwrap();
+
+    // c's dependency b is neither selected nor passed.
+    let only_c = TaskSelection {
+        only: vec!["c".to_string()],
+        ..Default::default()
+    };
+    let err = resolve_selection(pool, plan.id, &only_c).await.unwrap_err();
+    assert!(err.to_string().contains("c depends on b"), "{err}");
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let run = |selection: Option<std::collections::HashSet<Uuid>>| {
+        let registry = registry.clone();
+        let isolation = isolation.clone();
+        async move {
+            run_orchestrator(
+                pool,
+                plan.id,
+                &registry,
+                &isolation,
+                &test_token_config(),
+                &OrchestratorConfig {
+                    max_agents: 4,
+                    max_total_agents: None,
+                    task_timeout: Duration::from_secs(30),
+                    notifications: NotificationConfig::default(),
+                    watchdog: WatchdogConfig::default(),
+                    drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+                    prompt_budget: None,
+                    gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+                    gate_cache: true,
+                    selection,
+                    auto_approve: Vec::new(),
+                    attestation: false,
+                },
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    // --until b runs a and b and leaves the plan approved.
+    let until_b = TaskSelection {
+        until: Some("b".to_string()),
+        ..Default::default()
+    };
+    let selected = resolve_selection(pool, plan.id, &until_b).await.unwrap();
+    assert_eq!(selected.len(), 2);
+    let result = run(Some(selected)).await;
+    assert_eq!(
+        result,
+        OrchestratorResult::SelectionCompleted { remaining: 2 }
+    );
+    let statuses: Vec<TaskStatus> = task_db::list_tasks_for_plan(pool, plan.id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|t| t.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            TaskStatus::Passed,
+            TaskStatus::Passed,
+            TaskStatus::Pending,
+            TaskStatus::Pending
+        ]
+    );
+    let plan_after = plan_db::get_plan(pool, plan.id).await.unwrap().unwrap();
+    assert_eq!(plan_after.status, PlanStatus::Approved);
+
+    // Now that b passed, c alone is a closed slice.
+    let selected = resolve_selection(pool, plan.id, &only_c).await.unwrap();
+    let result = run(Some(selected)).await;
+    assert_eq!(
+        result,
+        OrchestratorResult::SelectionCompleted { remaining: 1 }
+    );
+
+    let result = run(None).await;
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn single_agent_slot_runs_many_ready_tasks() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let plan_id = create_wide_plan(&harness, "wide-plan", 6).await;
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 1,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result, OrchestratorResult::Completed);
+    let progress = task_db::get_plan_progress(pool, plan_id).await.unwrap();
+    assert_eq!(progress.passed, 6);
+
+    harness.teardown().await;
+}
+
+/// Run a five-task plan with one agent slot, held by a task that only ends
+/// at its 1s timeout, and cancel it shortly after dispatch starts.
+async fn run_saturated_and_cancel(harness: &TestHarness, plan_id: Uuid) -> OrchestratorResult {
+    let registry = make_registry(HangingMockHarness);
+    let isolation = harness.isolation();
+    let cancel = CancellationToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        canceller.cancel();
+    });
+
+    run_orchestrator(
+        harness.pool(),
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 1,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(1),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        cancel,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn saturated_agent_slots_do_not_delay_cancellation() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let plan_id = create_wide_plan(&harness, "saturated-plan", 5).await;
+
+    // Waiting for slots in the spawn loop would start the other four tasks,
+    // one after another, before noticing the cancellation.
+    let result = run_saturated_and_cancel(&harness, plan_id).await;
+    assert_eq!(result, OrchestratorResult::Interrupted);
+
+    let progress = task_db::get_plan_progress(pool, plan_id).await.unwrap();
+    assert_eq!(progress.pending, 4, "only one task should have started");
+    let plan = plan_db::get_plan(pool, plan_id).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Approved);
+    assert!(plan.interrupted_at.is_some());
+
+    harness.teardown().await;
+}
+
+/// Timing check for cancellation with saturated agent slots; wall-clock
+/// bounds are too noisy for the regular suite. Run with
+/// `cargo test -- --ignored`.
+#[tokio::test]
+#[ignore = "timing benchmark"]
+async fn bench_cancellation_with_saturated_agent_slots() {
+    let harness = TestHarness::new().await;
+    let plan_id = create_wide_plan(&harness, "saturated-bench-plan", 5).await;
+
+    let started = std::time::Instant::now();
+    let result = run_saturated_and_cancel(&harness, plan_id).await;
+    assert_eq!(result, OrchestratorResult::Interrupted);
+    assert!(
+        started.elapsed() < Duration::from_secs(3),
+        "cancellation took {:?}",
+        started.elapsed()
+    );
+
+    harness.teardown().await;
+}
+
+/// Like [`PassingMockHarness`], but each event arrives after a short delay.
+struct SlowMockHarness;
+
+#[async_trait]
+impl Harness for SlowMockHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        PassingMockHarness.spawn(task).await
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        Box::pin(PassingMockHarness.events(handle).then(|event| async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            event
+        }))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn cancel_drains_in_flight_work_and_leaves_plan_resumable() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let plan_id = create_wide_plan(&harness, "drain-plan", 3).await;
+
+    let registry = make_registry(SlowMockHarness);
+    let isolation = harness.isolation();
+    let config = OrchestratorConfig {
+        max_agents: 1,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(30),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: Duration::from_secs(10),
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+
+    // Cancel while the first task's agent is still working.
+    let cancel = CancellationToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        canceller.cancel();
+    });
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &config,
+        cancel,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Interrupted);
+
+    // The task that finished during the drain kept its result; the plan is
+    // approved again, marked interrupted, rather than failed.
+    let progress = task_db::get_plan_progress(pool, plan_id).await.unwrap();
+    assert_eq!(progress.passed, 1);
+    assert_eq!(progress.pending, 2);
+    let plan = plan_db::get_plan(pool, plan_id).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Approved);
+    assert!(plan.interrupted_at.is_some());
+
+    // Dispatching again picks up where it left off.
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &config,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+    let plan = plan_db::get_plan(pool, plan_id).await.unwrap().unwrap();
+    assert!(plan.interrupted_at.is_none());
+
+    harness.teardown().await;
+}
+
+/// Spawns a real `sleep` process per agent and never produces an event,
+/// so the only way an attempt ends is by being stopped.
+struct SleepingHarness {
+    children: Arc<std::sync::Mutex<Vec<std::process::Child>>>,
+}
+
+#[async_trait]
+impl Harness for SleepingHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, _task: &MaterializedTask) -> Result<AgentHandle> {
+        let child = Command::new("sleep").arg("60").spawn()?;
+        let pid = child.id();
+        self.children.lock().unwrap().push(child);
+        Ok(AgentHandle {
+            pid,
+            stdin: None,
+            task_id: Uuid::nil(),
+            attempt: 0,
+            harness_name: "mock-harness".to_string(),
+        })
+    }
+
+    fn events(&self, _handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        Box::pin(futures::stream::pending())
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        true
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn stop_all_kills_agents_and_leaves_plan_resumable() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool().clone();
+    let plan_id = create_wide_plan(&harness, "stop-plan", 2).await;
+
+    let children = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let registry = make_registry(SleepingHarness {
+        children: children.clone(),
+    });
+    let isolation = harness.isolation();
+    let config = OrchestratorConfig {
+        max_agents: 1,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(60),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let orchestrator = {
+        let (pool, registry, isolation, config) = (
+            pool.clone(),
+            registry.clone(),
+            isolation.clone(),
+            config.clone(),
+        );
+        tokio::spawn(async move {
+            run_orchestrator(
+                &pool,
+                plan_id,
+                &registry,
+                &isolation,
+                &test_token_config(),
+                &config,
+                CancellationToken::new(),
+            )
+            .await
+        })
+    };
+
+    // Wait for the first agent to be running.
+    let running = loop {
+        let tasks = task_db::list_in_flight_tasks(&pool).await.unwrap();
+        if let Some(task) = tasks
+            .into_iter()
+            .find(|t| t.status == TaskStatus::Running && t.agent_pid.is_some())
+        {
+            break task;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    let pid = running.agent_pid.unwrap() as u32;
+
+    let report = stop_all(&pool).await.unwrap();
+    assert_eq!(report.plans, vec!["stop-plan".to_string()]);
+    assert_eq!(report.tasks, vec![running.name.clone()]);
+    assert_eq!(report.killed_processes, vec![pid]);
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    // The orchestrator notices the stop and returns without waiting on the
+    // agent's 60s timeout.
+    let result = tokio::time::timeout(Duration::from_secs(10), orchestrator)
+        .await
+        .expect("orchestrator did not stop")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, OrchestratorResult::Interrupted);
+
+    // The agent process is dead.
+    let status = children.lock().unwrap()[0].wait().unwrap();
+    assert!(!status.success());
+
+    // The task starts over on the next dispatch; the plan is resumable.
+    let task = task_db::get_task(&pool, running.id).await.unwrap().unwrap();
+    assert_eq!(task.status, TaskStatus::Pending);
+    assert_eq!(task.attempt, 1);
+    assert_eq!(task.agent_pid, None);
+    let plan = plan_db::get_plan(&pool, plan_id).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Approved);
+    assert!(plan.interrupted_at.is_some());
+
+    let result = run_orchestrator(
+        &pool,
+        plan_id,
+        &make_registry(PassingMockHarness),
+        &isolation,
+        &test_token_config(),
+        &config,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    harness.teardown().await;
+}
+
+/// Runs `task-0` as a `sleep` process whose event stream ends when the
+/// process dies, like a real agent's; every other task passes at once.
+struct CancellableHarness {
+    children: Arc<std::sync::Mutex<std::collections::HashMap<u32, std::process::Child>>>,
+}
+
+#[async_trait]
+impl Harness for CancellableHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        let pid = if task.name == "task-0" {
+            let child = Command::new("sleep").arg("60").spawn()?;
+            let pid = child.id();
+            self.children.lock().unwrap().insert(pid, child);
+            pid
+        } else {
+            0
+        };
+        Ok(AgentHandle {
+            pid,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: 0,
+            harness_name: "mock-harness".to_string(),
+        })
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        if handle.pid == 0 {
+            return Box::pin(futures::stream::iter(vec![AgentEvent::Completed]));
+        }
+        let (children, pid) = (self.children.clone(), handle.pid);
+        Box::pin(futures::stream::unfold((), move |()| {
+            let children = children.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    let exited = children
+                        .lock()
+                        .unwrap()
+                        .get_mut(&pid)
+                        .is_none_or(|child| matches!(child.try_wait(), Ok(Some(_))));
+                    if exited {
+                        return None;
+                    }
+                }
+            }
+        }))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn cancelled_task_is_killed_and_plan_completes_without_it() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool().clone();
+    let plan_id = create_wide_plan(&harness, "cancel-plan", 3).await;
+    // task-2 needs task-0's changes, so it goes when task-0 is cancelled.
+    let tasks = task_db::list_tasks_for_plan(&pool, plan_id).await.unwrap();
+    let (task0, task2) = (&tasks[0], &tasks[2]);
+    task_db::insert_task_dependency(&pool, task2.id, task0.id)
+        .await
+        .unwrap();
+
+    let children = Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+    let registry = make_registry(CancellableHarness {
+        children: children.clone(),
+    });
+    let config = OrchestratorConfig {
+        max_agents: 2,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(60),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let orchestrator = {
+        let (pool, isolation) = (pool.clone(), harness.isolation());
+        tokio::spawn(async move {
+            run_orchestrator(
+                &pool,
+                plan_id,
+                &registry,
+                &isolation,
+                &test_token_config(),
+                &config,
+                CancellationToken::new(),
+            )
+            .await
+        })
+    };
+
+    let pid = loop {
+        let task = task_db::get_task(&pool, task0.id).await.unwrap().unwrap();
+        if let Some(pid) = task
+            .agent_pid
+            .filter(|_| task.status == TaskStatus::Running)
+        {
+            break pid as u32;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+
+    let report = cancel_task(&pool, task0.id).await.unwrap();
+    assert_eq!(report.task, "task-0");
+    assert_eq!(report.previous, TaskStatus::Running);
+    assert_eq!(report.dependents, vec!["task-2".to_string()]);
+    assert_eq!(report.killed_process, Some(pid));
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    // The plan finishes with the remaining task rather than waiting out
+    // the agent's 60s timeout.
+    let result = tokio::time::timeout(Duration::from_secs(10), orchestrator)
+        .await
+        .expect("orchestrator did not finish")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    let statuses: Vec<TaskStatus> = task_db::list_tasks_for_plan(&pool, plan_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|t| t.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            TaskStatus::Cancelled,
+            TaskStatus::Passed,
+            TaskStatus::Cancelled
+        ]
+    );
+    let progress = task_db::get_plan_progress(&pool, plan_id).await.unwrap();
+    assert_eq!(progress.cancelled, 2);
+
+    // A finished task cannot be cancelled.
+    assert!(cancel_task(&pool, tasks[1].id).await.is_err());
+
+    harness.teardown().await;
+}
+
+/// Runs `task-0` as an agent that only its harness can stop: its recorded
+/// process ID belongs to a process that already exited, and its event
+/// stream ends once [`Harness::kill`] is called. Other tasks pass at once.
+struct HarnessKilledHarness {
+    killed: Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[async_trait]
+impl Harness for HarnessKilledHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        let pid = if task.name == "task-0" {
+            let mut child = Command::new("true").spawn()?;
+            child.wait()?;
+            child.id()
+        } else {
+            0
+        };
+        Ok(AgentHandle {
+            pid,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: 0,
+            harness_name: "mock-harness".to_string(),
+        })
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        if handle.pid == 0 {
+            return Box::pin(futures::stream::iter(vec![AgentEvent::Completed]));
+        }
+        let killed = self.killed.clone();
+        Box::pin(futures::stream::unfold((), move |()| {
+            let killed = killed.clone();
+            async move {
+                while !killed.load(std::sync::atomic::Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                None
+            }
+        }))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        self.killed.store(true, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        !self.killed.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[tokio::test]
+async fn cancelling_a_running_task_kills_its_agent_through_the_harness_and_frees_its_slot() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool().clone();
+    let plan_id = create_wide_plan(&harness, "cancel-slot-plan", 2).await;
+    let tasks = task_db::list_tasks_for_plan(&pool, plan_id).await.unwrap();
+
+    let killed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let registry = make_registry(HarnessKilledHarness {
+        killed: killed.clone(),
+    });
+    // One slot: task-1 can only run once task-0's agent is gone.
+    let config = OrchestratorConfig {
+        max_agents: 1,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(60),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let orchestrator = {
+        let (pool, isolation) = (pool.clone(), harness.isolation());
+        tokio::spawn(async move {
+            run_orchestrator(
+                &pool,
+                plan_id,
+                &registry,
+                &isolation,
+                &test_token_config(),
+                &config,
+                CancellationToken::new(),
+            )
+            .await
+        })
+    };
+
+    loop {
+        let task = task_db::get_task(&pool, tasks[0].id)
+            .await
+            .unwrap()
+            .unwrap();
+        if task.status == TaskStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    cancel_task(&pool, tasks[0].id).await.unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(10), orchestrator)
+        .await
+        .expect("orchestrator did not finish")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+    assert!(killed.load(std::sync::atomic::Ordering::SeqCst));
+
+    let task0 = task_db::get_task(&pool, tasks[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(task0.status, TaskStatus::Cancelled);
+    let task1 = task_db::get_task(&pool, tasks[1].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(task1.status, TaskStatus::Passed);
+
+    harness.teardown().await;
+}
+
+/// An agent that asks the operator a question in its first turn (as
+/// `gator ask` would) and finishes once its session is resumed.
+struct AskingHarness {
+    pool: SqlitePool,
+    sent: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Harness for AskingHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        Ok(AgentHandle {
+            pid: 99999,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: 0,
+            harness_name: "mock-harness".to_string(),
+        })
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        if !self.sent.lock().unwrap().is_empty() {
+            return Box::pin(futures::stream::iter(vec![AgentEvent::Completed]));
+        }
+        let (pool, task_id) = (self.pool.clone(), handle.task_id);
+        Box::pin(futures::stream::once(async move {
+            gator_core::state::dispatch::ask_question(&pool, task_id, 0, "Which port?")
+                .await
+                .unwrap();
+            AgentEvent::Completed
+        }))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, message: &str) -> Result<()> {
+        self.sent.lock().unwrap().push(message.to_string());
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn question_blocks_task_until_answered_then_resumes_agent() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool().clone();
+    let plan_id = create_wide_plan(&harness, "ask-plan", 1).await;
+    let task = task_db::list_tasks_for_plan(&pool, plan_id).await.unwrap()[0].clone();
+
+    let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let registry = make_registry(AskingHarness {
+        pool: pool.clone(),
+        sent: sent.clone(),
+    });
+    let config = OrchestratorConfig {
+        max_agents: 1,
+        max_total_agents: None,
+        // Waiting on the operator does not count against the agent.
+        task_timeout: Duration::from_secs(1),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let orchestrator = {
+        let (pool, isolation) = (pool.clone(), harness.isolation());
+        tokio::spawn(async move {
+            run_orchestrator(
+                &pool,
+                plan_id,
+                &registry,
+                &isolation,
+                &test_token_config(),
+                &config,
+                CancellationToken::new(),
+            )
+            .await
+        })
+    };
+
+    loop {
+        let t = task_db::get_task(&pool, task.id).await.unwrap().unwrap();
+        if t.status == TaskStatus::BlockedOnHuman {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let question = gator_core::state::dispatch::latest_question(&pool, task.id, 0)
+        .await
+        .unwrap();
+    assert_eq!(question.as_deref(), Some("Which port?"));
+
+    // Still blocked past the agent's timeout.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let t = task_db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::BlockedOnHuman);
+    assert!(sent.lock().unwrap().is_empty());
+
+    gator_core::state::dispatch::answer_question(&pool, task.id, "Use 8080")
+        .await
+        .unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(10), orchestrator)
+        .await
+        .expect("orchestrator did not finish")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    let sent = sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].contains("Use 8080"), "{sent:?}");
+    let t = task_db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Passed);
+    assert_eq!(t.attempt, 0);
+
+    harness.teardown().await;
+}
+
+/// Insert an approved plan `downstream` whose one task depends on task
+/// `api` of a separate plan `upstream` (which is never dispatched).
+/// Returns `(downstream plan id, downstream task id, upstream task id)`.
+async fn create_cross_plan_pair(harness: &TestHarness) -> (Uuid, Uuid, Uuid) {
+    let pool = harness.pool();
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+    let project = harness.repo_path.to_string_lossy();
+
+    let upstream = plan_db::insert_plan(
+        pool,
+        "upstream",
+        &project,
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let api = task_db::insert_task(
+        pool,
+        upstream.id,
+        "api",
+        "Build the API",
+        "narrow",
+        "auto",
+        0,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let downstream = plan_db::insert_plan(
+        pool,
+        "downstream",
+        &project,
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, downstream.id).await.unwrap();
+    let client = task_db::insert_task(
+        pool,
+        downstream.id,
+        "client",
+        "Use the API",
+        "narrow",
+        "auto",
+        0,
+        None,
+    )
+    .await
+    .unwrap();
+    task_db::link_task_invariant(pool, client.id, inv.id)
+        .await
+        .unwrap();
+    task_db::insert_task_dependency(pool, client.id, api.id)
+        .await
+        .unwrap();
+
+    (downstream.id, client.id, api.id)
+}
+
+#[tokio::test]
+async fn cross_plan_dependency_waits_for_other_plan() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let (plan_id, client_id, api_id) = create_cross_plan_pair(&harness).await;
+
+    // The other plan's task passes a little later.
+    let other = pool.clone();
+    let finisher = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let client = task_db::get_task(&other, client_id).await.unwrap().unwrap();
+        task_db::update_task_status(&other, api_id, TaskStatus::Passed)
+            .await
+            .unwrap();
+        client.status
+    });
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 4,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    // Waiting on the other plan is not a deadlock.
+    assert_eq!(finisher.await.unwrap(), TaskStatus::Pending);
+    assert_eq!(result, OrchestratorResult::Completed);
+    let client = task_db::get_task(pool, client_id).await.unwrap().unwrap();
+    assert_eq!(client.status, TaskStatus::Passed);
+
+    harness.teardown().await;
+}
+
+#[tokio::test]
+async fn escalated_cross_plan_dependency_fails_plan() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let (plan_id, _, api_id) = create_cross_plan_pair(&harness).await;
+    task_db::update_task_status(pool, api_id, TaskStatus::Escalated)
+        .await
+        .unwrap();
+
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan_id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 4,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        result,
+        OrchestratorResult::Failed {
+            failed_tasks: vec!["client".to_string()],
+        }
+    );
+
+    harness.teardown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn machine_wide_agent_cap_spans_concurrent_plans() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let first = create_wide_plan(&harness, "first-plan", 2).await;
+    let second = create_wide_plan(&harness, "second-plan", 2).await;
+
+    // A lease left by a dispatch that has since exited is reclaimed.
+    let mut exited = Command::new("true").spawn().unwrap();
+    exited.wait().unwrap();
+    let stale = task_db::list_tasks_for_plan(pool, second).await.unwrap()[1].id;
+    sqlx::query("INSERT INTO agent_leases (task_id, host, pid) VALUES ($1, $2, $3)")
+        .bind(stale)
+        .bind(DispatcherIdentity::current().host)
+        .bind(i64::from(exited.id()))
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let registry = make_registry(SlowMockHarness);
+    let isolation = harness.isolation();
+    let config = OrchestratorConfig {
+        max_agents: 2,
+        max_total_agents: Some(1),
+        task_timeout: Duration::from_secs(30),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let token_config = test_token_config();
+    let (a, b) = tokio::join!(
+        run_orchestrator(
+            pool,
+            first,
+            &registry,
+            &isolation,
+            &token_config,
+            &config,
+            CancellationToken::new(),
+        ),
+        run_orchestrator(
+            pool,
+            second,
+            &registry,
+            &isolation,
+            &token_config,
+            &config,
+            CancellationToken::new(),
+        ),
+    );
+
+    assert_eq!(a.unwrap(), OrchestratorResult::Completed);
+    assert_eq!(b.unwrap(), OrchestratorResult::Completed);
+    // One agent at a time across both plans: no two tasks overlap.
+    let mut spans: Vec<_> = [first, second]
+        .iter()
+        .map(|plan_id| task_db::list_tasks_for_plan(pool, *plan_id))
+        .collect::<futures::future::TryJoinAll<_>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .map(|t| (t.started_at.unwrap(), t.completed_at.unwrap()))
+        .collect();
+    spans.sort();
+    assert_eq!(spans.len(), 4);
+    for pair in spans.windows(2) {
+        assert!(pair[0].1 <= pair[1].0, "tasks overlapped: {pair:?}");
+    }
+    let (leases,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM agent_leases")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(leases, 0, "every lease is released");
+
+    harness.teardown().await;
+}
+
+// ===========================================================================
+// Rate limits
+// ===========================================================================
+
+/// Reports a rate limit from the first agent it spawns, then completes.
+#[derive(Default)]
+struct RateLimitedHarness {
+    spawned: std::sync::atomic::AtomicU32,
+}
+
+#[async_trait]
+impl Harness for RateLimitedHarness {
+    fn name(&self) -> &str {
+        "mock-harness"
+    }
+
+    async fn spawn(&self, task: &MaterializedTask) -> Result<AgentHandle> {
+        let n = self
+            .spawned
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(AgentHandle {
+            pid: 90000 + n,
+            stdin: None,
+            task_id: task.task_id,
+            attempt: 0,
+            harness_name: "mock-harness".to_string(),
+        })
+    }
+
+    fn events(&self, handle: &AgentHandle) -> Pin<Box<dyn Stream<Item = AgentEvent> + Send>> {
+        let mut events = Vec::new();
+        if handle.pid == 90000 {
+            events.push(AgentEvent::Error {
+                message: "429 Too Many Requests; retry-after: 2".to_string(),
+            });
+        }
+        events.push(AgentEvent::Completed);
+        Box::pin(futures::stream::iter(events))
+    }
+
+    async fn send(&self, _handle: &AgentHandle, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn kill(&self, _handle: &AgentHandle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_running(&self, _handle: &AgentHandle) -> bool {
+        false
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn rate_limit_pauses_and_throttles_dispatch() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+    let plan = plan_db::insert_plan(
+        pool,
+        "throttled-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+    let task_a = task_db::insert_task(pool, plan.id, "task-a", "Task A", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    let task_b = task_db::insert_task(pool, plan.id, "task-b", "Task B", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    for task in [&task_a, &task_b] {
+        task_db::link_task_invariant(pool, task.id, inv.id)
+            .await
+            .unwrap();
+    }
+    task_db::insert_task_dependency(pool, task_b.id, task_a.id)
+        .await
+        .unwrap();
+
+    let registry = make_registry(RateLimitedHarness::default());
+    let isolation = harness.isolation();
+    let result = run_orchestrator(
+        pool,
+        plan.id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &OrchestratorConfig {
+            max_agents: 4,
+            max_total_agents: None,
+            task_timeout: Duration::from_secs(30),
+            notifications: NotificationConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            prompt_budget: None,
+            gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+            gate_cache: true,
+            selection: None,
+            auto_approve: Vec::new(),
+            attestation: false,
+        },
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+
+    // Task B became ready once A passed, but waited out the pause A's
+    // agent was asked for.
+    let ta = task_db::get_task(pool, task_a.id).await.unwrap().unwrap();
+    let tb = task_db::get_task(pool, task_b.id).await.unwrap().unwrap();
+    assert!(
+        tb.started_at.unwrap() - ta.started_at.unwrap() >= chrono::Duration::seconds(2),
+        "task-b started {:?} after task-a",
+        tb.started_at.unwrap() - ta.started_at.unwrap()
+    );
+
+    let throttle = plan_throttles::get_throttle(pool, plan.id)
+        .await
+        .unwrap()
+        .expect("the plan should still be throttled");
+    assert_eq!((throttle.agent_limit, throttle.max_agents), (2, 4));
+    assert_eq!(throttle.rate_limits, 1);
+    assert!(throttle.last_error.unwrap().contains("429"));
+
+    harness.teardown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn plan_hooks_run_around_dispatch() {
+    let harness = TestHarness::new().await;
+    let pool = harness.pool();
+    let log_dir = tempfile::tempdir().unwrap();
+    let log = log_dir.path().join("hooks.log");
+
+    let inv = create_invariant(pool, "pass_inv", "true").await;
+    let plan = plan_db::insert_plan(
+        pool,
+        "hooked-plan",
+        &harness.repo_path.to_string_lossy(),
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plan_db::approve_plan(pool, plan.id).await.unwrap();
+    let task = task_db::insert_task(pool, plan.id, "task-a", "Task A", "narrow", "auto", 0, None)
+        .await
+        .unwrap();
+    task_db::link_task_invariant(pool, task.id, inv.id)
+        .await
+        .unwrap();
+
+    let set_hooks = |pre_dispatch: &str| {
+        let hooks = serde_json::json!({
+            "pre_dispatch": pre_dispatch,
+            "post_task": format!(
+                "echo \"post_task $GATOR_TASK_NAME $GATOR_TASK_RESULT\" >> {}",
+                log.display()
+            ),
+            "post_plan": format!(
+                "echo \"post_plan $GATOR_PLAN_NAME $GATOR_PLAN_RESULT\" >> {}",
+                log.display()
+            ),
+        });
+        sqlx::query("UPDATE plans SET hooks = $1 WHERE id = $2")
+            .bind(hooks.to_string())
+            .bind(plan.id)
+            .execute(pool)
+    };
+    let config = OrchestratorConfig {
+        max_agents: 2,
+        max_total_agents: None,
+        task_timeout: Duration::from_secs(30),
+        notifications: NotificationConfig::default(),
+        watchdog: WatchdogConfig::default(),
+        drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        prompt_budget: None,
+        gate_parallelism: gator_core::gate::DEFAULT_GATE_PARALLELISM,
+        gate_cache: true,
+        selection: None,
+        auto_approve: Vec::new(),
+        attestation: false,
+    };
+    let registry = make_registry(PassingMockHarness);
+    let isolation = harness.isolation();
+
+    // A failing pre_dispatch hook stops the dispatch before anything runs.
+    set_hooks(&format!("echo provisioning >> {}; exit 3", log.display()))
+        .await
+        .unwrap();
+    let err = run_orchestrator(
+        pool,
+        plan.id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &config,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("pre_dispatch hook exited with 3"));
+    let plan_now = plan_db::get_plan(pool, plan.id).await.unwrap().unwrap();
+    assert_eq!(plan_now.status, PlanStatus::Approved);
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "provisioning\n");
+
+    // Once it passes, the other hooks see the task and the run end.
+    set_hooks("true").await.unwrap();
+    let result = run_orchestrator(
+        pool,
+        plan.id,
+        &registry,
+        &isolation,
+        &test_token_config(),
+        &config,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, OrchestratorResult::Completed);
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        "provisioning\npost_task task-a passed\npost_plan hooked-plan completed\n"
+    );
+
+    harness.teardown().await;
+}
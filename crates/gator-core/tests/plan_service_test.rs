@@ -3,13 +3,16 @@
 //! Tests `create_plan_from_toml` and `get_plan_with_tasks` against a real
 //! PostgreSQL database. Each test creates an isolated temporary database.
 
+use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use gator_core::plan::{
-    create_plan_from_toml, get_plan_with_tasks, materialize_plan, materialize_task, parse_plan_toml,
+    Change, PlanToml, TaskChanges, TaskToml, add_task, clone_plan, create_plan_from_toml,
+    diff_plan, get_plan_with_tasks, materialize_plan, materialize_task, parse_plan_toml,
+    remove_task, start_scheduled_run, sync_plan, update_task,
 };
-use gator_db::models::PlanStatus;
-use gator_db::queries::tasks;
+use gator_db::models::{PlanStatus, TaskStatus};
+use gator_db::queries::{schedules, tasks};
 use gator_test_utils::{create_test_db, drop_test_db};
 
 #[tokio::test]
@@ -385,6 +388,56 @@ depends_on = ["task-alpha", "task-beta"]
     drop_test_db(&db_name).await;
 }
 
+// -----------------------------------------------------------------------
+// [plan.defaults] are stored per task and survive materialization
+// -----------------------------------------------------------------------
+
+#[tokio::test]
+async fn plan_defaults_are_stored_on_each_task() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Defaults"
+base_branch = "main"
+
+[plan.defaults]
+scope = "narrow"
+gate = "human_review"
+timeout = 600
+
+[[tasks]]
+name = "uses-defaults"
+description = "Takes every default"
+
+[[tasks]]
+name = "overrides"
+description = "Sets its own gate and timeout"
+gate = "auto"
+timeout = 30
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/defaults")
+        .await
+        .expect("create should succeed");
+
+    let rows = tasks::list_tasks_for_plan(&pool, plan.id).await.unwrap();
+    let row = |name: &str| rows.iter().find(|t| t.name == name).unwrap();
+    assert_eq!(row("uses-defaults").gate_policy.to_string(), "human_review");
+    assert_eq!(row("uses-defaults").timeout_secs, Some(600));
+    assert_eq!(row("overrides").gate_policy.to_string(), "auto");
+    assert_eq!(row("overrides").scope_level.to_string(), "narrow");
+    assert_eq!(row("overrides").timeout_secs, Some(30));
+
+    let materialized = materialize_plan(&pool, plan.id).await.unwrap();
+    let reparsed = parse_plan_toml(&materialized).expect("should reparse");
+    assert_eq!(reparsed.tasks[0].timeout, Some(600));
+    assert_eq!(reparsed.tasks[1].timeout, Some(30));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 // -----------------------------------------------------------------------
 // Round-trip test with invariants
 // -----------------------------------------------------------------------
@@ -587,3 +640,764 @@ depends_on = ["root-task"]
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+#[tokio::test]
+async fn issue_reference_survives_create_and_materialize() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Imported issues"
+base_branch = "main"
+
+[[tasks]]
+name = "issue-12-fix-login"
+description = "Fix login"
+scope = "narrow"
+gate = "auto"
+issue = "acme/widgets#12"
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .expect("create_plan_from_toml should succeed");
+
+    let task_list = tasks::list_tasks_for_plan(&pool, plan.id).await.unwrap();
+    assert_eq!(task_list[0].issue.as_deref(), Some("acme/widgets#12"));
+
+    let materialized = materialize_plan(&pool, plan.id).await.unwrap();
+    let reparsed = parse_plan_toml(&materialized).expect("materialized plan should parse");
+    assert_eq!(reparsed.tasks[0].issue.as_deref(), Some("acme/widgets#12"));
+
+    let md = materialize_task(&pool, task_list[0].id).await.unwrap();
+    assert!(md.contains("**Issue:** acme/widgets#12"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn draft_plan_tasks_can_be_added_edited_and_removed() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Editable plan"
+base_branch = "main"
+
+[[tasks]]
+name = "schema"
+description = "Add the schema"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "api"
+description = "Add the API"
+scope = "medium"
+gate = "auto"
+depends_on = ["schema"]
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .unwrap();
+
+    let docs = add_task(
+        &pool,
+        plan.id,
+        TaskToml {
+            name: "docs".to_string(),
+            description: "Document the API".to_string(),
+            scope: "narrow".to_string(),
+            gate: "auto".to_string(),
+            retry_max: 3,
+            depends_on: vec!["api".to_string()],
+            ordering_only: vec![],
+            invariants: vec![],
+            harness: None,
+            issue: None,
+            services: vec![],
+            kind: "code".to_string(),
+            artifacts: vec![],
+            paths: vec![],
+            timeout: None,
+            setup_commands: vec![],
+        },
+    )
+    .await
+    .expect("add should succeed");
+
+    // schema -> api -> docs; making schema depend on docs closes a cycle.
+    let cycle = TaskChanges {
+        add_depends_on: vec!["docs".to_string()],
+        ..Default::default()
+    };
+    let err = update_task(&pool, plan.id, "schema", &cycle)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("cycle"), "got: {err:#}");
+
+    // Renaming keeps dependents pointing at the task.
+    let rename = TaskChanges {
+        rename: Some("http-api".to_string()),
+        description: Some("Add the HTTP API".to_string()),
+        ..Default::default()
+    };
+    let api = update_task(&pool, plan.id, "api", &rename).await.unwrap();
+    assert_eq!(api.name, "http-api");
+    assert_eq!(
+        tasks::get_task_dependencies(&pool, docs.id).await.unwrap(),
+        vec![api.id]
+    );
+
+    // api still has a dependent, so it cannot go; docs can.
+    assert!(remove_task(&pool, plan.id, "http-api").await.is_err());
+    remove_task(&pool, plan.id, "docs").await.unwrap();
+
+    let reparsed = parse_plan_toml(&materialize_plan(&pool, plan.id).await.unwrap()).unwrap();
+    let names: Vec<&str> = reparsed.tasks.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"schema") && names.contains(&"http-api"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn approved_plans_are_not_editable() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Approved plan"
+base_branch = "main"
+
+[[tasks]]
+name = "only"
+description = "The only task"
+scope = "narrow"
+gate = "auto"
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .unwrap();
+    gator_db::queries::plans::approve_plan(&pool, plan.id)
+        .await
+        .unwrap();
+
+    let err = remove_task(&pool, plan.id, "only").await.unwrap_err();
+    assert!(err.to_string().contains("only draft plans"), "got: {err}");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn sync_applies_only_safe_changes_from_the_plan_file() {
+    let (pool, db_name) = create_test_db().await;
+
+    let original = r#"
+[plan]
+name = "Synced plan"
+base_branch = "main"
+
+[[tasks]]
+name = "done"
+description = "Already ran"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "waiting"
+description = "Not started"
+scope = "narrow"
+gate = "auto"
+"#;
+    let plan = create_plan_from_toml(&pool, &parse_plan_toml(original).unwrap(), "/tmp/project")
+        .await
+        .unwrap();
+    gator_db::queries::plans::approve_plan(&pool, plan.id)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tasks SET status = 'passed' WHERE plan_id = $1 AND name = 'done'")
+        .bind(plan.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let edited = format!(
+        r#"
+[plan]
+id = "{}"
+name = "Synced plan"
+base_branch = "develop"
+
+[[tasks]]
+name = "done"
+description = "Already ran, reworded"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "waiting"
+description = "Not started, reworded"
+scope = "narrow"
+gate = "human_review"
+
+[[tasks]]
+name = "follow_up"
+description = "New work"
+scope = "narrow"
+gate = "auto"
+depends_on = ["waiting"]
+
+[[tasks]]
+name = "checked"
+description = "Needs an invariant nobody defined"
+scope = "narrow"
+gate = "auto"
+invariants = ["no_such_invariant"]
+
+[[tasks]]
+name = "after_checked"
+description = "Depends on a task that cannot be added"
+scope = "narrow"
+gate = "auto"
+depends_on = ["checked"]
+"#,
+        plan.id
+    );
+    let file = parse_plan_toml(&edited).unwrap();
+
+    let diff = diff_plan(&pool, plan.id, &file).await.unwrap();
+    let lines: Vec<(String, bool)> = diff
+        .changes
+        .iter()
+        .map(|c| (c.change.to_string(), c.blocked.is_none()))
+        .collect();
+    let expected = [
+        ("~ plan base_branch: main -> develop", false),
+        ("~ task done: description", false),
+        ("~ task waiting: description", true),
+        ("~ task waiting: gate", false),
+        ("+ task follow_up", true),
+        ("+ task checked", false),
+        ("+ task after_checked", false),
+    ];
+    assert_eq!(
+        lines,
+        expected.map(|(l, safe)| (l.to_string(), safe)).to_vec()
+    );
+
+    sync_plan(&pool, plan.id, &file).await.unwrap();
+    let (_, rows) = get_plan_with_tasks(&pool, plan.id).await.unwrap();
+    let names: Vec<&str> = rows.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["done", "waiting", "follow_up"]);
+    assert_eq!(rows[0].description, "Already ran");
+    assert_eq!(rows[1].description, "Not started, reworded");
+    assert_eq!(rows[2].status, TaskStatus::Pending);
+    assert_eq!(
+        tasks::get_task_dependency_names(&pool, rows[2].id)
+            .await
+            .unwrap(),
+        ["waiting"]
+    );
+
+    // Only the blocked changes are left.
+    let diff = diff_plan(&pool, plan.id, &file).await.unwrap();
+    assert!(diff.changes.iter().all(|c| c.blocked.is_some()));
+    assert!(
+        !diff
+            .changes
+            .iter()
+            .any(|c| matches!(&c.change, Change::TaskAdded { name } if name == "follow_up"))
+    );
+
+    gator_db::queries::plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+    let err = sync_plan(&pool, plan.id, &file).await.unwrap_err();
+    assert!(err.to_string().contains("is running"), "got: {err}");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn clone_plan_copies_tasks_deps_and_invariants_as_draft() {
+    let (pool, db_name) = create_test_db().await;
+
+    sqlx::query(
+        "INSERT INTO invariants (id, name, kind, command) VALUES ($1, 'my_check', 'custom', 'true')",
+    )
+    .bind(Uuid::new_v4())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let toml_str = r#"
+[plan]
+name = "Clone me"
+base_branch = "main"
+token_budget = 5000
+
+[[tasks]]
+name = "schema"
+description = "Add the schema"
+scope = "narrow"
+gate = "auto"
+retry_max = 3
+invariants = ["my_check"]
+
+[[tasks]]
+name = "api"
+description = "Add the API"
+scope = "medium"
+gate = "human_review"
+depends_on = ["schema"]
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let original = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .unwrap();
+
+    // Simulate a finished run: schema passed after a retry, api failed.
+    sqlx::query(
+        "UPDATE tasks SET status = 'passed', attempt = 1 WHERE plan_id = $1 AND name = 'schema'",
+    )
+    .bind(original.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tasks SET status = 'escalated' WHERE plan_id = $1 AND name = 'api'")
+        .bind(original.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let clone = clone_plan(&pool, original.id, None, false).await.unwrap();
+    assert_ne!(clone.id, original.id);
+    assert_eq!(clone.name, "Clone me");
+    assert_eq!(clone.status, PlanStatus::Draft);
+    assert_eq!(clone.token_budget, Some(5000));
+    assert_eq!(clone.project_path, "/tmp/project");
+
+    let cloned = tasks::list_tasks_for_plan(&pool, clone.id).await.unwrap();
+    assert_eq!(cloned.len(), 2);
+    assert!(cloned.iter().all(|t| t.status == TaskStatus::Pending));
+    let schema = cloned.iter().find(|t| t.name == "schema").unwrap();
+    let api = cloned.iter().find(|t| t.name == "api").unwrap();
+    assert_eq!(schema.attempt, 1);
+    assert_eq!(schema.retry_max, 3);
+    assert_eq!(
+        tasks::get_task_dependencies(&pool, api.id).await.unwrap(),
+        vec![schema.id]
+    );
+    let linked: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM task_invariants WHERE task_id = $1")
+        .bind(schema.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(linked.0, 1);
+
+    // --reset starts every task over; --name renames the clone.
+    let fresh = clone_plan(&pool, original.id, Some("Clone me again"), true)
+        .await
+        .unwrap();
+    assert_eq!(fresh.name, "Clone me again");
+    let fresh_tasks = tasks::list_tasks_for_plan(&pool, fresh.id).await.unwrap();
+    assert!(fresh_tasks.iter().all(|t| t.attempt == 0));
+
+    // The original is untouched.
+    let original_tasks = tasks::list_tasks_for_plan(&pool, original.id)
+        .await
+        .unwrap();
+    assert!(
+        original_tasks
+            .iter()
+            .any(|t| t.status == TaskStatus::Passed)
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn create_plan_resolves_cross_plan_dependencies() {
+    let (pool, db_name) = create_test_db().await;
+
+    let upstream = parse_plan_toml(
+        r#"
+[plan]
+name = "upstream"
+base_branch = "main"
+
+[[tasks]]
+name = "api"
+description = "Build the API"
+scope = "narrow"
+gate = "auto"
+"#,
+    )
+    .unwrap();
+    let upstream = create_plan_from_toml(&pool, &upstream, "/tmp/project")
+        .await
+        .unwrap();
+    let api = tasks::list_tasks_for_plan(&pool, upstream.id)
+        .await
+        .unwrap()
+        .remove(0);
+
+    let downstream_toml = |dep: &str| {
+        parse_plan_toml(&format!(
+            r#"
+[plan]
+name = "downstream"
+base_branch = "main"
+
+[[tasks]]
+name = "client"
+description = "Use the API"
+scope = "narrow"
+gate = "auto"
+depends_on = ["{dep}"]
+"#
+        ))
+        .unwrap()
+    };
+
+    let downstream = create_plan_from_toml(&pool, &downstream_toml("upstream:api"), "/tmp/project")
+        .await
+        .unwrap();
+    let client = tasks::list_tasks_for_plan(&pool, downstream.id)
+        .await
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        tasks::get_task_dependencies(&pool, client.id)
+            .await
+            .unwrap(),
+        vec![api.id]
+    );
+
+    // The qualified name survives materialization, so the plan round-trips.
+    let toml = materialize_plan(&pool, downstream.id).await.unwrap();
+    assert!(toml.contains(r#"depends_on = ["upstream:api"]"#), "{toml}");
+    let task_md = materialize_task(&pool, client.id).await.unwrap();
+    assert!(task_md.contains("- **upstream:api**: pending"), "{task_md}");
+
+    // Unknown plans and tasks are rejected when the plan is created.
+    let err = create_plan_from_toml(&pool, &downstream_toml("nowhere:api"), "/tmp/project")
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("no plan named \"nowhere\""),
+        "{err:#}"
+    );
+    let err = create_plan_from_toml(&pool, &downstream_toml("upstream:db"), "/tmp/project")
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("plan \"upstream\" has no task \"db\""),
+        "{err:#}"
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn ordering_only_dependencies_round_trip_through_edits_and_clones() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Edge kinds"
+base_branch = "main"
+
+[[tasks]]
+name = "schema"
+description = "Add the schema"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "changelog"
+description = "Update the changelog"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "api"
+description = "Add the API"
+scope = "medium"
+gate = "auto"
+depends_on = ["schema", "changelog"]
+ordering_only = ["changelog"]
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .unwrap();
+    let api = get_plan_with_tasks(&pool, plan.id)
+        .await
+        .unwrap()
+        .1
+        .into_iter()
+        .find(|t| t.name == "api")
+        .unwrap();
+
+    assert_eq!(
+        tasks::get_ordering_only_dependency_names(&pool, api.id)
+            .await
+            .unwrap(),
+        ["changelog"]
+    );
+    let toml = materialize_plan(&pool, plan.id).await.unwrap();
+    assert!(toml.contains(r#"ordering_only = ["changelog"]"#), "{toml}");
+    let task_md = materialize_task(&pool, api.id).await.unwrap();
+    assert!(
+        task_md.contains("- **changelog**: pending (ordering only"),
+        "{task_md}"
+    );
+    assert!(task_md.contains("- **schema**: pending\n"), "{task_md}");
+
+    // Renaming the upstream task keeps the edge kind; flipping swaps it.
+    let rename = TaskChanges {
+        rename: Some("release-notes".to_string()),
+        ..Default::default()
+    };
+    update_task(&pool, plan.id, "changelog", &rename)
+        .await
+        .unwrap();
+    let flip = TaskChanges {
+        add_depends_on: vec!["release-notes".to_string()],
+        add_ordering_only: vec!["schema".to_string()],
+        ..Default::default()
+    };
+    update_task(&pool, plan.id, "api", &flip).await.unwrap();
+    assert_eq!(
+        tasks::get_ordering_only_dependency_names(&pool, api.id)
+            .await
+            .unwrap(),
+        ["schema"]
+    );
+
+    let clone = clone_plan(&pool, plan.id, None, false).await.unwrap();
+    let cloned_api = get_plan_with_tasks(&pool, clone.id)
+        .await
+        .unwrap()
+        .1
+        .into_iter()
+        .find(|t| t.name == "api")
+        .unwrap();
+    assert_eq!(
+        tasks::get_ordering_only_dependency_names(&pool, cloned_api.id)
+            .await
+            .unwrap(),
+        ["schema"]
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+/// A plan of `tasks` tasks in a dependency chain, each checked by a
+/// `tests` invariant, which is created in `pool`.
+async fn generated_chain_plan(pool: &SqlitePool, tasks: usize) -> PlanToml {
+    sqlx::query(
+        "INSERT INTO invariants (id, name, kind, command) VALUES ($1, 'tests', 'custom', 'true')",
+    )
+    .bind(Uuid::new_v4())
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let mut toml_str = "[plan]\nname = \"generated\"\nbase_branch = \"main\"\n".to_string();
+    for i in 0..tasks {
+        toml_str.push_str(&format!(
+            "\n[[tasks]]\nname = \"task-{i:03}\"\ndescription = \"Generated task {i}\"\n\
+             scope = \"narrow\"\ngate = \"auto\"\ninvariants = [\"tests\"]\n"
+        ));
+        if i > 0 {
+            toml_str.push_str(&format!("depends_on = [\"task-{:03}\"]\n", i - 1));
+        }
+    }
+    parse_plan_toml(&toml_str).expect("should parse")
+}
+
+#[tokio::test]
+async fn large_generated_plan_is_created_in_bulk() {
+    let (pool, db_name) = create_test_db().await;
+
+    // 250 tasks in a chain: enough to span several multi-row statements.
+    const TASKS: usize = 250;
+    let plan_toml = generated_chain_plan(&pool, TASKS).await;
+    let plan = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .expect("create_plan_from_toml should succeed");
+
+    // Tasks come back in file order.
+    let task_list = tasks::list_tasks_for_plan(&pool, plan.id).await.unwrap();
+    let names: Vec<String> = task_list.iter().map(|t| t.name.clone()).collect();
+    let expected: Vec<String> = (0..TASKS).map(|i| format!("task-{i:03}")).collect();
+    assert_eq!(names, expected);
+
+    assert_eq!(
+        tasks::count_dependency_edges(&pool, plan.id).await.unwrap(),
+        (TASKS - 1) as i64
+    );
+    let linked: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM task_invariants ti JOIN tasks t ON t.id = ti.task_id \
+         WHERE t.plan_id = $1",
+    )
+    .bind(plan.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(linked.0, TASKS as i64);
+
+    let last_deps = tasks::get_task_dependencies(&pool, task_list[TASKS - 1].id)
+        .await
+        .unwrap();
+    assert_eq!(last_deps, vec![task_list[TASKS - 2].id]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+/// Timing check for bulk plan creation; wall-clock bounds are too noisy
+/// for the regular suite. Run with `cargo test -- --ignored`.
+#[tokio::test]
+#[ignore = "timing benchmark"]
+async fn bench_large_generated_plan_creation() {
+    let (pool, db_name) = create_test_db().await;
+
+    const TASKS: usize = 250;
+    let plan_toml = generated_chain_plan(&pool, TASKS).await;
+    let started = std::time::Instant::now();
+    create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .expect("create_plan_from_toml should succeed");
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed < std::time::Duration::from_secs(5),
+        "creating a {TASKS}-task plan took {elapsed:?}"
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn failed_plan_creation_leaves_nothing_behind() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "half-made"
+base_branch = "main"
+
+[[tasks]]
+name = "ok"
+description = "fine"
+scope = "narrow"
+gate = "auto"
+
+[[tasks]]
+name = "bad"
+description = "references a missing invariant"
+scope = "narrow"
+gate = "auto"
+depends_on = ["ok"]
+invariants = ["nope"]
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    assert!(
+        create_plan_from_toml(&pool, &plan_toml, "/tmp")
+            .await
+            .is_err()
+    );
+
+    for table in ["plans", "tasks", "task_dependencies", "task_invariants"] {
+        let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0, "{table} should be empty after a failed create");
+    }
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn scheduled_run_clones_and_approves_the_plan() {
+    let (pool, db_name) = create_test_db().await;
+
+    let toml_str = r#"
+[plan]
+name = "Nightly bump"
+base_branch = "main"
+
+[[tasks]]
+name = "bump"
+description = "Bump dependencies"
+scope = "narrow"
+gate = "auto"
+"#;
+    let plan_toml = parse_plan_toml(toml_str).expect("should parse");
+    let template = create_plan_from_toml(&pool, &plan_toml, "/tmp/project")
+        .await
+        .unwrap();
+
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-01T02:00:30Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let schedule = schedules::insert_schedule(&pool, template.id, "0 2 * * *", 2, 1800, now)
+        .await
+        .unwrap();
+    assert_eq!(
+        schedules::list_due_schedules(&pool, now)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let run = start_scheduled_run(&pool, &schedule, now).await.unwrap();
+    assert_ne!(run.id, template.id);
+    assert_eq!(run.name, "Nightly bump-20260301-0200");
+    assert_eq!(run.status, PlanStatus::Approved);
+    assert_eq!(
+        tasks::list_tasks_for_plan(&pool, run.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // The schedule moves on to tomorrow and remembers the run.
+    let schedule = schedules::get_schedule(&pool, schedule.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        schedule.next_run_at,
+        chrono::DateTime::parse_from_rfc3339("2026-03-02T02:00:00Z").unwrap()
+    );
+    assert_eq!(schedule.last_plan_id, Some(run.id));
+    assert!(schedule.last_error.is_none());
+    assert!(schedule.last_run_at.is_some());
+    assert!(
+        schedules::list_due_schedules(&pool, now)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,494 @@
+//! Compatibility tests for the public JSON schema (`gator_core::schema`).
+//!
+//! The v1 types must serialize to exactly what `docs/schema/v1.schema.json`
+//! publishes, keep their field names and enumerated values, and read
+//! payloads that carry fields added later.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use gator_core::notify::{Notification, NotificationEvent};
+use gator_core::schema::v1::{
+    Invariant, PendingTask, Plan, PlanDetail, PlanReport, PlanStatus, PlanSummary, Progress,
+    RetryReason, Task, TaskDetail, TaskReport, Throttle, TokenUsage,
+};
+use gator_core::schema::{SCHEMA_VERSION, Versioned};
+use gator_db::models::{
+    self, GatePolicy, InvariantKind, InvariantScope, PlanFailureReason, ScopeLevel, TaskKind,
+    TaskStatus,
+};
+use gator_db::queries::gate_results::GateResultWithName;
+use gator_db::queries::tasks::PlanProgress;
+
+const SCHEMA: &str = include_str!("../../../docs/schema/v1.schema.json");
+
+fn at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap()
+}
+
+fn id(n: u128) -> Uuid {
+    Uuid::from_u128(n)
+}
+
+fn plan() -> Plan {
+    models::Plan {
+        id: id(1),
+        name: "demo".to_string(),
+        project_path: "/repo".to_string(),
+        base_branch: "main".to_string(),
+        status: models::PlanStatus::Failed,
+        token_budget: Some(1000),
+        default_harness: "claude-code".to_string(),
+        isolation: "worktree".to_string(),
+        container_image: None,
+        ephemeral_db: None,
+        template_setup: None,
+        template_hardlinks: false,
+        created_at: at(),
+        approved_at: Some(at()),
+        completed_at: Some(at()),
+        deleted_at: None,
+        archived_at: None,
+        interrupted_at: None,
+        failure_reason: Some(PlanFailureReason::TasksEscalated),
+        dispatched_host: Some("host".to_string()),
+        dispatched_pid: Some(42),
+        dispatched_user: Some("op".to_string()),
+        dispatched_at: Some(at()),
+        warmup_command: None,
+        hooks: Default::default(),
+        setup_commands: Default::default(),
+        caches: Default::default(),
+    }
+    .into()
+}
+
+fn task() -> Task {
+    models::Task {
+        id: id(2),
+        plan_id: id(1),
+        name: "build".to_string(),
+        description: "Build it".to_string(),
+        scope_level: ScopeLevel::Narrow,
+        gate_policy: GatePolicy::HumanReview,
+        retry_max: 3,
+        status: TaskStatus::BlockedOnHuman,
+        assigned_harness: Some("claude-code".to_string()),
+        requested_harness: None,
+        worktree_path: Some("/wt/build".to_string()),
+        attempt: 1,
+        created_at: at(),
+        started_at: Some(at()),
+        completed_at: None,
+        issue: None,
+        deleted_at: None,
+        services: sqlx::types::Json(Vec::new()),
+        kind: TaskKind::Code,
+        artifacts: sqlx::types::Json(Vec::new()),
+        paths: sqlx::types::Json(vec!["src/**".to_string()]),
+        timeout_secs: None,
+        retry_env: sqlx::types::Json(BTreeMap::from([("K".to_string(), "V".to_string())])),
+        retry_env_attempt: Some(1),
+        gate_script: None,
+        agent_pid: Some(7),
+        container_id: None,
+        agent_host: Some("host".to_string()),
+        agent_started: Some("12345".to_string()),
+        llm_review: false,
+        review_harness: None,
+        run_id: Some(id(3)),
+        attempt_id: Some(id(4)),
+        merged_at: None,
+        merge_commit: None,
+        setup_commands: Default::default(),
+        disk_usage_bytes: None,
+        disk_usage_at: None,
+    }
+    .into()
+}
+
+fn invariant() -> Invariant {
+    models::Invariant {
+        id: id(5),
+        name: "tests".to_string(),
+        description: None,
+        kind: InvariantKind::TestSuite,
+        command: "cargo".to_string(),
+        args: sqlx::types::Json(vec!["test".to_string()]),
+        expected_exit_code: 0,
+        threshold: None,
+        scope: InvariantScope::Project,
+        timeout_secs: 300,
+        created_at: at(),
+        deleted_at: None,
+        diff_base: false,
+        lower_is_better: false,
+        disabled_at: None,
+        env: sqlx::types::Json(BTreeMap::new()),
+        workdir: None,
+        run_after: sqlx::types::Json(Vec::new()),
+        artifacts: sqlx::types::Json(vec!["test-results/**/*.png".to_owned()]),
+    }
+    .into()
+}
+
+fn progress() -> Progress {
+    PlanProgress {
+        blocked_on_human: 1,
+        total: 1,
+        ..Default::default()
+    }
+    .into()
+}
+
+fn task_detail() -> Versioned<TaskDetail> {
+    Versioned::new(TaskDetail {
+        task: task(),
+        dependencies: vec![id(9)],
+        invariants: vec![invariant()],
+        events: vec![
+            models::AgentEvent {
+                id: 1,
+                task_id: id(2),
+                attempt: 1,
+                event_type: "message".to_string(),
+                payload: json!({"type": "message", "role": "assistant", "content": "hi"}),
+                recorded_at: at(),
+                run_id: Some(id(3)),
+                attempt_id: Some(id(4)),
+            }
+            .into(),
+        ],
+        gate_results: vec![
+            GateResultWithName {
+                id: id(6),
+                task_id: id(2),
+                invariant_id: id(5),
+                attempt: 1,
+                passed: true,
+                exit_code: Some(0),
+                stdout: Some("ok".to_string()),
+                stderr: None,
+                duration_ms: Some(12),
+                checked_at: at(),
+                timed_out: false,
+                base_value: None,
+                task_value: None,
+                cached_from: None,
+                invariant_name: "tests".to_string(),
+            }
+            .into(),
+        ],
+        token_usage: TokenUsage::new(10, 5),
+    })
+}
+
+fn notification() -> Versioned<Notification> {
+    Versioned::new(Notification {
+        event: NotificationEvent::TaskEscalated,
+        plan_id: id(1),
+        plan_name: "demo".to_string(),
+        task_id: Some(id(2)),
+        task_name: Some("build".to_string()),
+        message: "escalated".to_string(),
+        timestamp: at(),
+    })
+}
+
+/// Every published document, serialized.
+fn documents() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "plan_summary",
+            to_value(Versioned::new(PlanSummary {
+                plan: plan(),
+                progress: progress(),
+            })),
+        ),
+        (
+            "plan_detail",
+            to_value(Versioned::new(PlanDetail {
+                plan: plan(),
+                progress: progress(),
+                token_usage: TokenUsage::new(10, 5),
+                tasks: vec![task()],
+            })),
+        ),
+        ("task_detail", to_value(task_detail())),
+        ("invariant_listing", to_value(Versioned::new(invariant()))),
+        (
+            "plan_status",
+            to_value(Versioned::new(PlanStatus {
+                plan: plan(),
+                progress: progress(),
+                tasks: vec![task()],
+                notes: vec![
+                    models::Note {
+                        id: id(8),
+                        plan_id: id(1),
+                        task_id: None,
+                        body: "note".to_string(),
+                        created_at: at(),
+                    }
+                    .into(),
+                ],
+                why: Some(vec![PendingTask {
+                    task: "build".to_string(),
+                    ready: false,
+                    reasons: vec!["waits on lint".to_string()],
+                }]),
+                throttle: Some(Throttle {
+                    agent_limit: 2,
+                    max_agents: 4,
+                    paused_until: Some(at()),
+                    rate_limits: 1,
+                    last_error: Some("429 Too Many Requests".to_string()),
+                    updated_at: at(),
+                }),
+            })),
+        ),
+        (
+            "plan_report",
+            to_value(Versioned::new(PlanReport {
+                plan: plan(),
+                progress: progress(),
+                token_usage: TokenUsage::new(10, 5),
+                cost_usd: Some(0.15),
+                duration_secs: Some(90),
+                tasks: vec![TaskReport {
+                    id: id(2),
+                    name: "build".to_string(),
+                    status: "passed".to_string(),
+                    attempts: 2,
+                    wall_time_secs: None,
+                    token_usage: TokenUsage::new(10, 5),
+                    cost_usd: None,
+                    gate_checks: 2,
+                    gate_checks_passed: 1,
+                    gate_pass_rate: Some(0.5),
+                    retries: vec![RetryReason {
+                        attempt: 0,
+                        reason: "gate failed: tests".to_string(),
+                    }],
+                }],
+            })),
+        ),
+        ("notification", to_value(notification())),
+    ]
+}
+
+fn to_value(document: impl serde::Serialize) -> Value {
+    serde_json::to_value(document).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Schema conformance
+// ---------------------------------------------------------------------------
+
+fn resolve<'a>(schema: &'a Value, def: &'a Value) -> &'a Value {
+    match def.get("$ref").and_then(Value::as_str) {
+        Some(reference) => {
+            let name = reference.strip_prefix("#/$defs/").unwrap();
+            &schema["$defs"][name]
+        }
+        None => def,
+    }
+}
+
+/// The properties and required keys of an object schema, across `allOf`.
+fn object_shape<'a>(
+    schema: &'a Value,
+    def: &'a Value,
+    props: &mut BTreeMap<String, &'a Value>,
+    required: &mut BTreeSet<String>,
+) {
+    let def = resolve(schema, def);
+    for part in def
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        object_shape(schema, part, props, required);
+    }
+    if let Some(p) = def.get("properties").and_then(Value::as_object) {
+        props.extend(p.iter().map(|(k, v)| (k.clone(), v)));
+    }
+    for key in def
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        required.insert(key.as_str().unwrap().to_string());
+    }
+}
+
+fn type_matches(ty: &str, value: &Value) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => panic!("unknown type {ty}"),
+    }
+}
+
+fn check(schema: &Value, def: &Value, value: &Value, path: &str) {
+    let def = resolve(schema, def);
+    if let Some(expected) = def.get("const") {
+        assert_eq!(value, expected, "{path}");
+        return;
+    }
+    if def.get("allOf").is_some() || def.get("properties").is_some() {
+        let mut props = BTreeMap::new();
+        let mut required = BTreeSet::new();
+        object_shape(schema, def, &mut props, &mut required);
+        let object = value
+            .as_object()
+            .unwrap_or_else(|| panic!("{path}: not an object"));
+        for key in object.keys() {
+            assert!(props.contains_key(key), "{path}.{key} is not in the schema");
+        }
+        for key in &required {
+            assert!(
+                object.contains_key(key),
+                "{path}.{key} is required but missing"
+            );
+        }
+        for (key, value) in object {
+            check(schema, props[key], value, &format!("{path}.{key}"));
+        }
+        return;
+    }
+    let types: Vec<&str> = match &def["type"] {
+        Value::String(ty) => vec![ty.as_str()],
+        Value::Array(tys) => tys.iter().map(|t| t.as_str().unwrap()).collect(),
+        other => panic!("{path}: no type in {other}"),
+    };
+    assert!(
+        types.iter().any(|ty| type_matches(ty, value)),
+        "{path}: {value} is not {types:?}"
+    );
+    if let (Some(items), Some(values)) = (def.get("items"), value.as_array()) {
+        for (i, item) in values.iter().enumerate() {
+            check(schema, items, item, &format!("{path}[{i}]"));
+        }
+    }
+    if let (Some(Value::Object(entry)), Some(map)) =
+        (def.get("additionalProperties"), value.as_object())
+    {
+        for (key, v) in map {
+            check(
+                schema,
+                &Value::Object(entry.clone()),
+                v,
+                &format!("{path}.{key}"),
+            );
+        }
+    }
+}
+
+#[test]
+fn documents_match_the_published_schema() {
+    let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+    for (name, document) in documents() {
+        let def = &schema["$defs"][name];
+        assert!(def.is_object(), "{name} is not published");
+        check(&schema, def, &document, name);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Stability
+// ---------------------------------------------------------------------------
+
+#[test]
+fn v1_names_and_values_are_stable() {
+    assert_eq!(SCHEMA_VERSION, 1);
+
+    let summary = serde_json::to_value(Versioned::new(PlanSummary {
+        plan: plan(),
+        progress: progress(),
+    }))
+    .unwrap();
+    assert_eq!(
+        summary,
+        json!({
+            "schema_version": 1,
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "demo",
+            "project_path": "/repo",
+            "base_branch": "main",
+            "status": "failed",
+            "token_budget": 1000,
+            "default_harness": "claude-code",
+            "isolation": "worktree",
+            "container_image": null,
+            "ephemeral_db": null,
+            "template_setup": null,
+            "setup_commands": [],
+            "warmup_command": null,
+            "created_at": "2026-01-02T03:04:05Z",
+            "approved_at": "2026-01-02T03:04:05Z",
+            "completed_at": "2026-01-02T03:04:05Z",
+            "deleted_at": null,
+            "archived_at": null,
+            "interrupted_at": null,
+            "failure_reason": "tasks_escalated",
+            "dispatched_host": "host",
+            "dispatched_pid": 42,
+            "dispatched_user": "op",
+            "dispatched_at": "2026-01-02T03:04:05Z",
+            "progress": {
+                "pending": 0,
+                "assigned": 0,
+                "running": 0,
+                "blocked_on_human": 1,
+                "checking": 0,
+                "passed": 0,
+                "failed": 0,
+                "escalated": 0,
+                "cancelled": 0,
+                "total": 1
+            }
+        })
+    );
+
+    let task = serde_json::to_value(task()).unwrap();
+    assert_eq!(task["status"], "blocked_on_human");
+    assert_eq!(task["scope_level"], "narrow");
+    assert_eq!(task["gate_policy"], "human_review");
+    assert_eq!(task["kind"], "code");
+    assert_eq!(task["paths"], json!(["src/**"]));
+    // Internal bookkeeping stays out of the schema.
+    assert!(task.get("agent_started").is_none());
+
+    let notification = serde_json::to_value(notification()).unwrap();
+    assert_eq!(notification["schema_version"], 1);
+    assert_eq!(notification["event"], "task_escalated");
+}
+
+#[test]
+fn documents_read_payloads_with_fields_added_later() {
+    let detail = task_detail();
+    let mut value = serde_json::to_value(&detail).unwrap();
+    value["added_in_a_later_release"] = json!({"anything": true});
+    value["invariants"][0]["also_new"] = json!(1);
+
+    let read: Versioned<TaskDetail> = serde_json::from_value(value).unwrap();
+    assert_eq!(read, detail);
+    assert_eq!(read.schema_version, SCHEMA_VERSION);
+
+    // Unknown enumerated values are plain strings, not errors.
+    let mut value = serde_json::to_value(task()).unwrap();
+    value["status"] = json!("some_future_status");
+    let read: Task = serde_json::from_value(value).unwrap();
+    assert_eq!(read.status, "some_future_status");
+}
@@ -72,6 +72,11 @@ fn valid_transitions_accepted() {
         (TaskStatus::Checking, TaskStatus::Failed),
         (TaskStatus::Failed, TaskStatus::Assigned),
         (TaskStatus::Failed, TaskStatus::Escalated),
+        (TaskStatus::Running, TaskStatus::Cancelled),
+        (TaskStatus::Escalated, TaskStatus::Cancelled),
+        (TaskStatus::Running, TaskStatus::BlockedOnHuman),
+        (TaskStatus::BlockedOnHuman, TaskStatus::Running),
+        (TaskStatus::BlockedOnHuman, TaskStatus::Cancelled),
     ];
     for (from, to) in &valid {
         assert!(
@@ -106,6 +111,13 @@ fn invalid_transitions_rejected() {
         (TaskStatus::Failed, TaskStatus::Checking),
         (TaskStatus::Failed, TaskStatus::Passed),
         (TaskStatus::Escalated, TaskStatus::Assigned),
+        (TaskStatus::Passed, TaskStatus::Cancelled),
+        (TaskStatus::Cancelled, TaskStatus::Pending),
+        (TaskStatus::Cancelled, TaskStatus::Assigned),
+        (TaskStatus::Assigned, TaskStatus::BlockedOnHuman),
+        (TaskStatus::Checking, TaskStatus::BlockedOnHuman),
+        (TaskStatus::BlockedOnHuman, TaskStatus::Checking),
+        (TaskStatus::BlockedOnHuman, TaskStatus::Passed),
     ];
     for (from, to) in &invalid {
         assert!(
@@ -204,6 +216,47 @@ async fn failure_and_retry_lifecycle() {
     drop_test_db(&db_name).await;
 }
 
+#[tokio::test]
+async fn operator_retry_with_new_description_records_a_revision() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "revised-task", 3).await;
+    let fail = |pool: SqlitePool| async move {
+        dispatch::assign_task(&pool, task.id, "h", Path::new("/tmp/wt"))
+            .await
+            .unwrap();
+        dispatch::start_task(&pool, task.id).await.unwrap();
+        dispatch::begin_checking(&pool, task.id).await.unwrap();
+        dispatch::fail_task(&pool, task.id).await.unwrap();
+    };
+    let no_env = Default::default();
+
+    fail(pool.clone()).await;
+    dispatch::operator_retry_task(&pool, task.id, false, &no_env, Some("use the v2 API"))
+        .await
+        .unwrap();
+    // An unchanged description is not a revision.
+    fail(pool.clone()).await;
+    dispatch::operator_retry_task(&pool, task.id, false, &no_env, Some("use the v2 API"))
+        .await
+        .unwrap();
+
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.description, "use the v2 API");
+    assert_eq!(t.attempt, 2);
+    let revisions = gator_db::queries::task_revisions::list_task_revisions(&pool, task.id)
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].attempt, 1);
+    assert_eq!(revisions[0].previous, "test description");
+    assert_eq!(revisions[0].description, "use the v2 API");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 #[tokio::test]
 async fn escalation_lifecycle() {
     let (pool, db_name) = create_test_db().await;
@@ -588,3 +641,222 @@ async fn task_not_found_gives_clear_error() {
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+// ---------------------------------------------------------------------------
+// Transactional milestones
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn failed_assignment_leaves_no_metadata() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+    dispatch::assign_task(&pool, task.id, "first", Path::new("/tmp/wt-first"))
+        .await
+        .unwrap();
+
+    // A second assignment fails on the optimistic lock (task is no longer
+    // pending) and must not overwrite the first assignment's metadata.
+    let result = dispatch::assign_task(&pool, task.id, "second", Path::new("/tmp/wt-second")).await;
+    assert!(result.is_err());
+
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Assigned);
+    assert_eq!(t.assigned_harness.as_deref(), Some("first"));
+    assert_eq!(t.worktree_path.as_deref(), Some("/tmp/wt-first"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn crash_mid_assignment_rolls_back() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+
+    // Simulate a crash after the metadata write but before commit.
+    {
+        let mut tx = gator_db::pool::begin_immediate(&pool).await.unwrap();
+        db::assign_task_metadata(&mut *tx, task.id, "h", "/tmp/wt")
+            .await
+            .unwrap();
+        TaskStateMachine::transition_on(
+            &mut tx,
+            task.id,
+            TaskStatus::Pending,
+            TaskStatus::Assigned,
+        )
+        .await
+        .unwrap();
+        drop(tx);
+    }
+
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Pending);
+    assert!(t.assigned_harness.is_none());
+    assert!(t.worktree_path.is_none());
+
+    // Restart recovery has nothing to clean up, and the task is still ready.
+    let orphaned = db::reset_orphaned_tasks(&pool, plan_id).await.unwrap();
+    assert!(orphaned.is_empty());
+    let ready = queries::get_ready_tasks(&pool, plan_id).await.unwrap();
+    assert_eq!(ready.len(), 1);
+
+    dispatch::assign_task(&pool, task.id, "h", Path::new("/tmp/wt"))
+        .await
+        .expect("assignment should succeed after rollback");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn ask_and_answer_round_trip() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+
+    // Only a running task can ask.
+    let result = dispatch::ask_question(&pool, task.id, 0, "Which port?").await;
+    assert!(result.is_err(), "pending task cannot ask");
+
+    dispatch::assign_task(&pool, task.id, "h", Path::new("/tmp/wt"))
+        .await
+        .unwrap();
+    dispatch::start_task(&pool, task.id).await.unwrap();
+
+    // Nothing to answer yet.
+    assert!(
+        dispatch::answer_question(&pool, task.id, "8080")
+            .await
+            .is_err()
+    );
+
+    dispatch::ask_question(&pool, task.id, 0, "Which port?")
+        .await
+        .unwrap();
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::BlockedOnHuman);
+    assert_eq!(
+        dispatch::latest_question(&pool, task.id, 0)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Which port?")
+    );
+
+    assert!(
+        dispatch::answer_question(&pool, task.id, "  ")
+            .await
+            .is_err(),
+        "empty answers are rejected"
+    );
+    dispatch::answer_question(&pool, task.id, "8080")
+        .await
+        .unwrap();
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Running);
+
+    let events = gator_db::queries::agent_events::list_events_for_task(&pool, task.id, 0)
+        .await
+        .unwrap();
+    let kinds: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(kinds, vec!["question", "answer"]);
+    assert_eq!(events[1].payload["answer"], "8080");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn time_out_task_fails_and_records_event() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+    dispatch::assign_task(&pool, task.id, "h", Path::new("/tmp/wt"))
+        .await
+        .unwrap();
+    dispatch::start_task(&pool, task.id).await.unwrap();
+
+    dispatch::time_out_task(&pool, task.id, 0, std::time::Duration::from_secs(30))
+        .await
+        .unwrap();
+
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Failed);
+    assert!(t.completed_at.is_some());
+
+    let events = gator_db::queries::agent_events::list_events_for_task(&pool, task.id, 0)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "error");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn time_out_task_on_wrong_status_changes_nothing() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+
+    let result =
+        dispatch::time_out_task(&pool, task.id, 0, std::time::Duration::from_secs(30)).await;
+    assert!(result.is_err(), "pending task cannot time out");
+
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Pending);
+    let count = gator_db::queries::agent_events::count_events_for_task(&pool, task.id, 0)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn crash_mid_timeout_is_recovered_on_restart() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan_id = create_test_plan(&pool).await;
+    let task = create_test_task(&pool, plan_id, "t", 3).await;
+    dispatch::assign_task(&pool, task.id, "h", Path::new("/tmp/wt"))
+        .await
+        .unwrap();
+    dispatch::start_task(&pool, task.id).await.unwrap();
+
+    // Simulate a crash between running -> checking and checking -> failed.
+    {
+        let mut tx = gator_db::pool::begin_immediate(&pool).await.unwrap();
+        TaskStateMachine::transition_on(
+            &mut tx,
+            task.id,
+            TaskStatus::Running,
+            TaskStatus::Checking,
+        )
+        .await
+        .unwrap();
+        drop(tx);
+    }
+
+    // The half-applied milestone is gone: the task is still running...
+    let t = db::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(t.status, TaskStatus::Running);
+
+    // ...and restart recovery treats it as an orphan.
+    let orphaned = db::reset_orphaned_tasks(&pool, plan_id).await.unwrap();
+    assert_eq!(orphaned.len(), 1);
+    assert_eq!(orphaned[0].status, TaskStatus::Failed);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,3 @@
+-- Link tasks to the issue they were imported from (e.g. "owner/repo#12"),
+-- so the plan's PR can close it.
+ALTER TABLE tasks ADD COLUMN issue TEXT;
@@ -0,0 +1,12 @@
+-- Webhook notifications that could not be delivered after all retries.
+CREATE TABLE notification_dead_letters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event TEXT NOT NULL,
+    url TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_notification_dead_letters_created ON notification_dead_letters(created_at);
@@ -0,0 +1,4 @@
+-- Soft-delete: deleted rows stay restorable until pruned.
+ALTER TABLE plans ADD COLUMN deleted_at TEXT;
+ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+ALTER TABLE invariants ADD COLUMN deleted_at TEXT;
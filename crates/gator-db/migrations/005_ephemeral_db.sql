@@ -0,0 +1,2 @@
+-- Per-attempt ephemeral Postgres: image name, NULL when disabled.
+ALTER TABLE plans ADD COLUMN ephemeral_db TEXT;
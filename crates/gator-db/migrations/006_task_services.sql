@@ -0,0 +1,2 @@
+-- Compose files to bring up around each task attempt (JSON array).
+ALTER TABLE tasks ADD COLUMN services TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,19 @@
+-- Non-code tasks: gated on produced artifacts and bundled instead of merged.
+ALTER TABLE tasks ADD COLUMN kind TEXT NOT NULL DEFAULT 'code'
+    CHECK (kind IN ('code', 'artifact'));
+ALTER TABLE tasks ADD COLUMN artifacts TEXT NOT NULL DEFAULT '[]';
+
+-- Built-in artifact checks, recorded per attempt like gate_results.
+CREATE TABLE artifact_checks (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    check_name TEXT NOT NULL
+        CHECK (check_name IN ('exists', 'markdown', 'links')),
+    passed INTEGER NOT NULL,
+    message TEXT,
+    checked_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_artifact_checks_task ON artifact_checks(task_id, attempt);
@@ -0,0 +1,11 @@
+-- Reviewer feedback given when rejecting a task, recorded per attempt so
+-- the next attempt's prompt can quote it.
+CREATE TABLE review_feedback (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_review_feedback_task ON review_feedback(task_id, attempt);
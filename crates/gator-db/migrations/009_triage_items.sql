@@ -0,0 +1,17 @@
+-- Issues picked up by the triage daemon. One row per issue ever queued, so
+-- an issue is triaged at most once; `status` tracks posting the result back.
+CREATE TABLE triage_items (
+    id TEXT PRIMARY KEY,
+    repo TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    plan_id TEXT NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'posted', 'failed')),
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    posted_at TEXT,
+    UNIQUE (repo, issue_number)
+);
+
+CREATE INDEX idx_triage_items_status ON triage_items(repo, status);
@@ -0,0 +1,41 @@
+-- Pull requests picked up by the review bot, one row per reviewed head
+-- commit, so a PR is reviewed again after new pushes.
+CREATE TABLE pr_reviews (
+    id TEXT PRIMARY KEY,
+    repo TEXT NOT NULL,
+    pr_number INTEGER NOT NULL,
+    head_sha TEXT NOT NULL,
+    plan_id TEXT NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    -- Files the PR changes (JSON array); the review must cover all of them.
+    changed_files TEXT NOT NULL DEFAULT '[]',
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'posted', 'failed')),
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    posted_at TEXT,
+    UNIQUE (repo, pr_number, head_sha)
+);
+
+CREATE INDEX idx_pr_reviews_task ON pr_reviews(task_id);
+CREATE INDEX idx_pr_reviews_status ON pr_reviews(repo, status);
+
+-- Allow the `review` check. SQLite cannot alter a CHECK constraint, so the
+-- table is rebuilt.
+CREATE TABLE artifact_checks_new (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    check_name TEXT NOT NULL
+        CHECK (check_name IN ('exists', 'markdown', 'links', 'review')),
+    passed INTEGER NOT NULL,
+    message TEXT,
+    checked_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+INSERT INTO artifact_checks_new SELECT * FROM artifact_checks;
+DROP TABLE artifact_checks;
+ALTER TABLE artifact_checks_new RENAME TO artifact_checks;
+
+CREATE INDEX idx_artifact_checks_task ON artifact_checks(task_id, attempt);
@@ -0,0 +1,15 @@
+-- Long-term memory: what past tasks in a project did and how their gates
+-- went, kept beyond the life of the plan so later tasks can learn from it.
+CREATE TABLE memories (
+    id TEXT PRIMARY KEY,
+    project_path TEXT NOT NULL,
+    task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+    attempt INTEGER NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('success', 'failure')),
+    -- `<plan>/<task>` at the time of recording.
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_memories_project ON memories(project_path, created_at);
@@ -0,0 +1,2 @@
+-- Archived plans are kept intact but hidden from plan listings.
+ALTER TABLE plans ADD COLUMN archived_at TEXT;
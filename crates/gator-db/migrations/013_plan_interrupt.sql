@@ -0,0 +1,3 @@
+-- Set when a dispatch is cancelled; the plan goes back to `approved` so the
+-- next dispatch resumes it. Cleared when the plan starts running again.
+ALTER TABLE plans ADD COLUMN interrupted_at TEXT;
@@ -0,0 +1,4 @@
+-- Why a failed plan stopped. `budget_exceeded` plans can be resumed with a
+-- higher token budget; the column is cleared whenever the plan leaves
+-- `failed`.
+ALTER TABLE plans ADD COLUMN failure_reason TEXT;
@@ -0,0 +1,10 @@
+-- Wall time of each lifecycle phase, per task attempt.
+CREATE TABLE attempt_phases (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    phase TEXT NOT NULL
+        CHECK (phase IN ('workspace', 'services', 'materialize', 'agent', 'extract', 'gate', 'verdict')),
+    duration_ms INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (task_id, attempt, phase)
+);
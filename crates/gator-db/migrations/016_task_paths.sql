@@ -0,0 +1,23 @@
+-- Paths (gitignore-style patterns) a task may touch: its worktree is a
+-- sparse checkout of them and the gate fails on changes outside them.
+ALTER TABLE tasks ADD COLUMN paths TEXT NOT NULL DEFAULT '[]';
+
+-- Allow the `scope` check. SQLite cannot alter a CHECK constraint, so the
+-- table is rebuilt.
+CREATE TABLE artifact_checks_new (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    check_name TEXT NOT NULL
+        CHECK (check_name IN ('exists', 'markdown', 'links', 'review', 'scope')),
+    passed INTEGER NOT NULL,
+    message TEXT,
+    checked_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+INSERT INTO artifact_checks_new SELECT * FROM artifact_checks;
+DROP TABLE artifact_checks;
+ALTER TABLE artifact_checks_new RENAME TO artifact_checks;
+
+CREATE INDEX idx_artifact_checks_task ON artifact_checks(task_id, attempt);
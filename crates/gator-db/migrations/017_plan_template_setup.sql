@@ -0,0 +1,4 @@
+-- Worktree template: setup command run once in a shared checkout that each
+-- task worktree is cloned from. NULL when task worktrees are checked out
+-- normally.
+ALTER TABLE plans ADD COLUMN template_setup TEXT;
@@ -0,0 +1,9 @@
+-- Prompt sections dropped to fit the prompt budget, per task attempt.
+CREATE TABLE prompt_trims (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    section TEXT NOT NULL,
+    estimated_tokens INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (task_id, attempt, section)
+);
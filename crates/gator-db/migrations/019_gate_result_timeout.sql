@@ -0,0 +1,2 @@
+-- Invariants killed for running past their timeout_secs.
+ALTER TABLE gate_results ADD COLUMN timed_out INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Per-task attempt time limit in seconds, from `timeout` in plan.toml.
+-- NULL means the dispatch timeout applies.
+ALTER TABLE tasks ADD COLUMN timeout_secs INTEGER;
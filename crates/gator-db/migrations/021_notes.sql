@@ -0,0 +1,12 @@
+-- Operator notes on a plan or one of its tasks, e.g. what was done by hand
+-- during a run. Task notes also carry the plan so a plan's notes can be
+-- listed together.
+CREATE TABLE notes (
+    id TEXT PRIMARY KEY,
+    plan_id TEXT NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    task_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_notes_plan ON notes(plan_id, created_at);
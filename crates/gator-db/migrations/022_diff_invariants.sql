@@ -0,0 +1,7 @@
+-- Differential invariants: the gate also runs the command on the commit the
+-- task branched from and fails only when the number it prints gets worse.
+-- Both numbers are kept with the gate result.
+ALTER TABLE invariants ADD COLUMN diff_base INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE invariants ADD COLUMN lower_is_better INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE gate_results ADD COLUMN base_value REAL;
+ALTER TABLE gate_results ADD COLUMN task_value REAL;
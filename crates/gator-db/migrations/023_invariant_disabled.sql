@@ -0,0 +1,3 @@
+-- Disabled invariants stay linked to their tasks but are skipped by the
+-- gate and by `gator check` until re-enabled.
+ALTER TABLE invariants ADD COLUMN disabled_at TEXT;
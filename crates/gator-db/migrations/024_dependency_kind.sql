@@ -0,0 +1,3 @@
+-- Whether a dependency needs the upstream task's changes (`needs_output`)
+-- or only has to run after it (`ordering_only`).
+ALTER TABLE task_dependencies ADD COLUMN kind TEXT NOT NULL DEFAULT 'needs_output';
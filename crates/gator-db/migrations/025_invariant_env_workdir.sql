@@ -0,0 +1,4 @@
+-- Environment variables (a JSON object) set for an invariant's command,
+-- and the directory it runs in, relative to the worktree root.
+ALTER TABLE invariants ADD COLUMN env TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE invariants ADD COLUMN workdir TEXT;
@@ -0,0 +1,3 @@
+-- Names of invariants that must finish before this one starts when both
+-- run in the same gate (e.g. a test suite after the build).
+ALTER TABLE invariants ADD COLUMN run_after TEXT NOT NULL DEFAULT '[]';
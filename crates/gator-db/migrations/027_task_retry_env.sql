@@ -0,0 +1,4 @@
+-- Extra environment variables (a JSON object) for one attempt of a task,
+-- set by `gator retry --env`, and the attempt they apply to.
+ALTER TABLE tasks ADD COLUMN retry_env TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE tasks ADD COLUMN retry_env_attempt INTEGER;
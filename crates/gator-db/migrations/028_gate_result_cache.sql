@@ -0,0 +1,5 @@
+-- Hash of the worktree contents and invariant definition a gate result was
+-- computed for, and, for a result reused from an earlier attempt with the
+-- same hash, the attempt that actually ran the invariant.
+ALTER TABLE gate_results ADD COLUMN content_hash TEXT;
+ALTER TABLE gate_results ADD COLUMN cached_from INTEGER;
@@ -0,0 +1,3 @@
+-- Script that decides the verdict of an `auto` gate (`gate = "script:<path>"`
+-- in a plan file). NULL leaves the verdict to the invariants alone.
+ALTER TABLE tasks ADD COLUMN gate_script TEXT;
@@ -0,0 +1,12 @@
+-- Task descriptions replaced by `gator retry --edit`, so the instructions
+-- each attempt ran with can be recovered.
+CREATE TABLE task_revisions (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    previous TEXT NOT NULL,
+    description TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_task_revisions_task ON task_revisions(task_id, created_at);
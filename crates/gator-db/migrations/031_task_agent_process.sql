@@ -0,0 +1,5 @@
+-- Where a task's current agent runs, so `gator admin stop-all` can kill it
+-- from another process: the agent's process ID and, in container isolation,
+-- its container.
+ALTER TABLE tasks ADD COLUMN agent_pid INTEGER;
+ALTER TABLE tasks ADD COLUMN container_id TEXT;
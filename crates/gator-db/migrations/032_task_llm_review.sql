@@ -0,0 +1,6 @@
+-- `gate = "llm_review"` tasks: after the invariants pass, a reviewer agent
+-- approves the change or requests changes. Stored as the `auto` policy plus
+-- this flag (the gate_policy CHECK constraint predates it), with an
+-- optional harness for the reviewer.
+ALTER TABLE tasks ADD COLUMN llm_review INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tasks ADD COLUMN review_harness TEXT;
@@ -0,0 +1,7 @@
+-- Which orchestrator owns a plan's latest run: the host, process, and user
+-- that dispatched it, and when. Several machines may dispatch against one
+-- database, so `gator status` shows these to tell who owns a stuck run.
+ALTER TABLE plans ADD COLUMN dispatched_host TEXT;
+ALTER TABLE plans ADD COLUMN dispatched_pid INTEGER;
+ALTER TABLE plans ADD COLUMN dispatched_user TEXT;
+ALTER TABLE plans ADD COLUMN dispatched_at TEXT;
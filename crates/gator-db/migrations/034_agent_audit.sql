@@ -0,0 +1,15 @@
+-- Every agent-mode CLI invocation made with a valid token: the command, the
+-- token's claims, and whether the control plane accepted it. Listed by
+-- `gator audit <task-id>`.
+CREATE TABLE agent_audit (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    token_version TEXT NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('ok', 'error', 'denied')),
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_agent_audit_task ON agent_audit(task_id, created_at);
@@ -0,0 +1,16 @@
+-- Correlation IDs for slicing logs of a multi-plan daemon: `run_id` names
+-- one `gator dispatch` of a plan, `attempt_id` one lifecycle of a task
+-- within it. The task row holds the IDs of its current attempt, and events
+-- and gate results recorded for that attempt copy them.
+ALTER TABLE tasks ADD COLUMN run_id TEXT;
+ALTER TABLE tasks ADD COLUMN attempt_id TEXT;
+
+ALTER TABLE agent_events ADD COLUMN run_id TEXT;
+ALTER TABLE agent_events ADD COLUMN attempt_id TEXT;
+
+ALTER TABLE gate_results ADD COLUMN run_id TEXT;
+ALTER TABLE gate_results ADD COLUMN attempt_id TEXT;
+
+CREATE INDEX idx_agent_events_run ON agent_events(run_id);
+CREATE INDEX idx_agent_events_attempt_id ON agent_events(attempt_id);
+CREATE INDEX idx_gate_results_run ON gate_results(run_id);
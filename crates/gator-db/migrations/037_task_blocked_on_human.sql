@@ -0,0 +1,47 @@
+-- `blocked_on_human`: the agent asked the operator a question with `gator
+-- ask` and waits for `gator answer`. The table is rebuilt to widen the
+-- status CHECK constraint, as in 036.
+CREATE TABLE tasks_new (
+    id TEXT PRIMARY KEY,
+    plan_id TEXT NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    scope_level TEXT NOT NULL
+        CHECK (scope_level IN ('narrow', 'medium', 'broad')),
+    gate_policy TEXT NOT NULL
+        CHECK (gate_policy IN ('auto', 'human_review', 'human_approve')),
+    retry_max INTEGER NOT NULL DEFAULT 3,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'assigned', 'running', 'blocked_on_human', 'checking', 'passed', 'failed', 'escalated', 'cancelled')),
+    assigned_harness TEXT,
+    requested_harness TEXT,
+    worktree_path TEXT,
+    attempt INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    started_at TEXT,
+    completed_at TEXT,
+    issue TEXT,
+    deleted_at TEXT,
+    services TEXT NOT NULL DEFAULT '[]',
+    kind TEXT NOT NULL DEFAULT 'code'
+        CHECK (kind IN ('code', 'artifact')),
+    artifacts TEXT NOT NULL DEFAULT '[]',
+    paths TEXT NOT NULL DEFAULT '[]',
+    timeout_secs INTEGER,
+    retry_env TEXT NOT NULL DEFAULT '{}',
+    retry_env_attempt INTEGER,
+    gate_script TEXT,
+    agent_pid INTEGER,
+    container_id TEXT,
+    llm_review INTEGER NOT NULL DEFAULT 0,
+    review_harness TEXT,
+    run_id TEXT,
+    attempt_id TEXT
+);
+
+INSERT INTO tasks_new SELECT * FROM tasks;
+DROP TABLE tasks;
+ALTER TABLE tasks_new RENAME TO tasks;
+
+CREATE INDEX idx_tasks_plan_id ON tasks(plan_id);
+CREATE INDEX idx_tasks_status ON tasks(status);
@@ -0,0 +1,5 @@
+-- Merge state of a task's branch: set by `gator merge` when the branch
+-- lands on the plan's base branch. `merge_commit` is the base branch's
+-- commit after the merge.
+ALTER TABLE tasks ADD COLUMN merged_at TEXT;
+ALTER TABLE tasks ADD COLUMN merge_commit TEXT;
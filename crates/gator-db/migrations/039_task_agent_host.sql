@@ -0,0 +1,6 @@
+-- Identify a task's agent beyond its process ID, so a restarted dispatch
+-- can tell whether the recorded process is still the agent: the host it
+-- runs on and the process's start time (a reused process ID has a
+-- different one).
+ALTER TABLE tasks ADD COLUMN agent_host TEXT;
+ALTER TABLE tasks ADD COLUMN agent_started TEXT;
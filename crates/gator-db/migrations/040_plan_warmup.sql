@@ -0,0 +1,37 @@
+-- Workspace warm-up: a command (e.g. `cargo build`) run in each task's
+-- workspace before its agent starts, so the agent does not wait on the
+-- first build itself. Each run is recorded per attempt like gate_results.
+ALTER TABLE plans ADD COLUMN warmup_command TEXT;
+
+CREATE TABLE warmup_results (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    passed INTEGER NOT NULL,
+    exit_code INTEGER,
+    timed_out INTEGER NOT NULL DEFAULT 0,
+    stdout TEXT,
+    stderr TEXT,
+    duration_ms INTEGER NOT NULL,
+    run_id TEXT,
+    attempt_id TEXT,
+    ran_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_warmup_results_task ON warmup_results(task_id, attempt);
+
+-- Warm-up gets its own lifecycle phase. SQLite cannot alter a CHECK
+-- constraint, so the table is rebuilt.
+CREATE TABLE attempt_phases_new (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    phase TEXT NOT NULL
+        CHECK (phase IN ('workspace', 'services', 'warmup', 'materialize', 'agent', 'extract', 'gate', 'verdict')),
+    duration_ms INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (task_id, attempt, phase)
+);
+INSERT INTO attempt_phases_new SELECT task_id, attempt, phase, duration_ms, recorded_at FROM attempt_phases;
+DROP TABLE attempt_phases;
+ALTER TABLE attempt_phases_new RENAME TO attempt_phases;
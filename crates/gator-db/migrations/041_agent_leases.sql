@@ -0,0 +1,13 @@
+-- Machine-wide agent slots (`dispatch.max_total_agents`), shared by every
+-- orchestrator on a host that uses this database. A dispatch holds one
+-- lease per agent lifecycle it runs; the holder's process ID and start
+-- time let a later dispatch reclaim the leases of one that died.
+CREATE TABLE agent_leases (
+    task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+    host TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    process_started TEXT,
+    acquired_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_agent_leases_host ON agent_leases(host);
@@ -0,0 +1,15 @@
+-- Failure artifacts of UI tests: an invariant may declare globs (e.g.
+-- `test-results/**/*.png`) whose matches are copied out of the worktree
+-- when it fails, and attached to its gate result.
+ALTER TABLE invariants ADD COLUMN artifacts TEXT NOT NULL DEFAULT '[]';
+
+CREATE TABLE gate_artifacts (
+    id TEXT PRIMARY KEY,
+    gate_result_id TEXT NOT NULL REFERENCES gate_results(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    stored_path TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    collected_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_gate_artifacts_result ON gate_artifacts(gate_result_id);
@@ -0,0 +1,14 @@
+-- Adaptive throttling after provider rate limits. While a plan's agents
+-- are being rate-limited, its orchestrator runs fewer of them at once and
+-- pauses before starting more; the current state is kept here so
+-- `gator status` and the dashboard can show it. No row means the plan is
+-- not throttled.
+CREATE TABLE plan_throttles (
+    plan_id TEXT PRIMARY KEY REFERENCES plans(id) ON DELETE CASCADE,
+    agent_limit INTEGER NOT NULL,
+    max_agents INTEGER NOT NULL,
+    paused_until TEXT,
+    rate_limits INTEGER NOT NULL,
+    last_error TEXT,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
@@ -0,0 +1,18 @@
+-- Scheduled dispatch. A schedule names a plan to use as a template and a
+-- cron expression; each time it comes due, `gator schedule run` clones the
+-- plan, approves the clone, and dispatches it. `next_run_at` is when the
+-- schedule is next due; `last_plan_id` is the clone its last run created.
+CREATE TABLE schedules (
+    id TEXT PRIMARY KEY,
+    plan_id TEXT NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    cron TEXT NOT NULL,
+    max_agents INTEGER NOT NULL,
+    timeout_secs INTEGER NOT NULL,
+    next_run_at TEXT NOT NULL,
+    last_run_at TEXT,
+    last_plan_id TEXT REFERENCES plans(id) ON DELETE SET NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_schedules_next_run_at ON schedules(next_run_at);
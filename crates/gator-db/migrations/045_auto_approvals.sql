@@ -0,0 +1,15 @@
+-- Auto-approvals of `human_review` tasks. When a task's gate passes and its
+-- changes match an operator-configured auto-approval rule, the task passes
+-- without a human; the rule and what it was matched against are recorded
+-- here and listed by `gator audit <task-id>`.
+CREATE TABLE auto_approvals (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    rule TEXT NOT NULL,
+    diff_lines INTEGER NOT NULL,
+    files TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_auto_approvals_task ON auto_approvals(task_id, created_at);
@@ -0,0 +1,20 @@
+-- Compliance attestations of generated code. With `gate.attestation` on,
+-- every code task whose invariants pass gets a record of what produced its
+-- change: the harness (and its version), a hash of the prompt the agent was
+-- given, and a hash of the change itself. The same record is written into
+-- the task's branch as a JSON file; `gator audit <task-id>` lists them.
+CREATE TABLE attestations (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    harness TEXT NOT NULL,
+    harness_version TEXT,
+    prompt_sha256 TEXT NOT NULL,
+    base_commit TEXT NOT NULL,
+    diff_sha256 TEXT NOT NULL,
+    -- The attestation file, relative to the worktree root.
+    path TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_attestations_task ON attestations(task_id, created_at);
@@ -0,0 +1,4 @@
+-- Plan-level hooks: shell commands from the plan's `[hooks]` table run at
+-- points of a dispatch or merge (`pre_dispatch`, `post_task`, `post_plan`,
+-- `post_merge`), stored as a JSON object of hook name to command.
+ALTER TABLE plans ADD COLUMN hooks TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,112 @@
+-- Change feed for incremental export (e.g. to a data warehouse). While
+-- enabled, every insert, update, and delete of a plan, task, attempt phase,
+-- or gate result appends a row to `change_log`, so a downstream job can
+-- sync what changed since the last `seq` it read instead of scanning whole
+-- tables. `seq` only grows, and SQLite has one writer, so entries become
+-- visible in `seq` order. An entry names the changed row by `row_id` (for
+-- an attempt phase, its task, with `attempt` and `phase`); the job reads
+-- the row itself, which is gone after a delete.
+--
+-- Off by default: nothing is logged until `gator export cdc enable`.
+--
+-- A migration that rebuilds one of these tables drops its triggers and must
+-- create them again.
+CREATE TABLE change_log (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    attempt INTEGER,
+    phase TEXT,
+    op TEXT NOT NULL CHECK (op IN ('insert', 'update', 'delete')),
+    changed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- At most one row; no row means disabled.
+CREATE TABLE change_log_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TRIGGER change_log_plans_insert AFTER INSERT ON plans
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('plans', NEW.id, 'insert');
+END;
+
+CREATE TRIGGER change_log_plans_update AFTER UPDATE ON plans
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('plans', NEW.id, 'update');
+END;
+
+CREATE TRIGGER change_log_plans_delete AFTER DELETE ON plans
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('plans', OLD.id, 'delete');
+END;
+
+CREATE TRIGGER change_log_tasks_insert AFTER INSERT ON tasks
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('tasks', NEW.id, 'insert');
+END;
+
+CREATE TRIGGER change_log_tasks_update AFTER UPDATE ON tasks
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('tasks', NEW.id, 'update');
+END;
+
+CREATE TRIGGER change_log_tasks_delete AFTER DELETE ON tasks
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('tasks', OLD.id, 'delete');
+END;
+
+CREATE TRIGGER change_log_attempt_phases_insert AFTER INSERT ON attempt_phases
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, attempt, phase, op)
+    VALUES ('attempt_phases', NEW.task_id, NEW.attempt, NEW.phase, 'insert');
+END;
+
+CREATE TRIGGER change_log_attempt_phases_update AFTER UPDATE ON attempt_phases
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, attempt, phase, op)
+    VALUES ('attempt_phases', NEW.task_id, NEW.attempt, NEW.phase, 'update');
+END;
+
+CREATE TRIGGER change_log_attempt_phases_delete AFTER DELETE ON attempt_phases
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, attempt, phase, op)
+    VALUES ('attempt_phases', OLD.task_id, OLD.attempt, OLD.phase, 'delete');
+END;
+
+CREATE TRIGGER change_log_gate_results_insert AFTER INSERT ON gate_results
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('gate_results', NEW.id, 'insert');
+END;
+
+CREATE TRIGGER change_log_gate_results_update AFTER UPDATE ON gate_results
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('gate_results', NEW.id, 'update');
+END;
+
+CREATE TRIGGER change_log_gate_results_delete AFTER DELETE ON gate_results
+WHEN (SELECT enabled FROM change_log_settings WHERE id = 1)
+BEGIN
+    INSERT INTO change_log (table_name, row_id, op)
+    VALUES ('gate_results', OLD.id, 'delete');
+END;
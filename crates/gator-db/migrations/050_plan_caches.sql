@@ -0,0 +1,3 @@
+-- Shared dependency caches (cargo, npm, pnpm, pip, go) every task
+-- workspace of the plan uses, stored as a JSON array of cache kinds.
+ALTER TABLE plans ADD COLUMN caches TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- Whether task worktrees that cannot be cloned from the plan's template
+-- worktree get the template's build outputs hard-linked in instead.
+ALTER TABLE plans ADD COLUMN template_hardlinks INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Disk usage of a task's worktree, measured when its latest attempt
+-- finished. `gator status` sums it per project to warn about the worktree
+-- quota; `gator cleanup` clears it when it removes the worktree.
+ALTER TABLE tasks ADD COLUMN disk_usage_bytes INTEGER;
+ALTER TABLE tasks ADD COLUMN disk_usage_at TEXT;
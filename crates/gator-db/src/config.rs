@@ -1,5 +1,12 @@
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default maximum number of pooled connections.
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+
+/// Default time to wait for a pooled connection before failing.
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Database configuration.
 ///
@@ -10,6 +17,10 @@ use std::path::PathBuf;
 pub struct DbConfig {
     /// Path to the SQLite database file.
     pub db_path: PathBuf,
+    /// Maximum number of connections held by the pool.
+    pub max_connections: u32,
+    /// How long to wait for a free connection before returning an error.
+    pub acquire_timeout: Duration,
 }
 
 impl DbConfig {
@@ -19,25 +30,35 @@ impl DbConfig {
     /// then `~/.config/gator/gator.db`.
     pub fn from_env() -> Self {
         if let Ok(url) = env::var("GATOR_DATABASE_URL") {
-            return Self {
-                db_path: PathBuf::from(url),
-            };
+            return Self::new(url);
         }
         let config_dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("gator");
-        Self {
-            db_path: config_dir.join("gator.db"),
-        }
+        Self::new(config_dir.join("gator.db"))
     }
 
     /// Build a config from an explicit path (useful for tests and CLI flags).
     pub fn new(db_path: impl Into<PathBuf>) -> Self {
         Self {
             db_path: db_path.into(),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT,
         }
     }
 
+    /// Set the maximum pool size.
+    pub fn with_max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections.max(1);
+        self
+    }
+
+    /// Set the connection acquire timeout.
+    pub fn with_acquire_timeout(mut self, acquire_timeout: Duration) -> Self {
+        self.acquire_timeout = acquire_timeout;
+        self
+    }
+
     /// Return the SQLite connection URL for this config.
     pub fn database_url(&self) -> String {
         format!("sqlite://{}?mode=rwc", self.db_path.display())
@@ -58,6 +79,23 @@ mod tests {
     fn new_from_path() {
         let cfg = DbConfig::new("/tmp/test.db");
         assert_eq!(cfg.db_path, PathBuf::from("/tmp/test.db"));
+        assert_eq!(cfg.max_connections, DEFAULT_MAX_CONNECTIONS);
+        assert_eq!(cfg.acquire_timeout, DEFAULT_ACQUIRE_TIMEOUT);
+    }
+
+    #[test]
+    fn pool_settings_builders() {
+        let cfg = DbConfig::new("/tmp/test.db")
+            .with_max_connections(20)
+            .with_acquire_timeout(Duration::from_secs(3));
+        assert_eq!(cfg.max_connections, 20);
+        assert_eq!(cfg.acquire_timeout, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn max_connections_is_at_least_one() {
+        let cfg = DbConfig::new("/tmp/test.db").with_max_connections(0);
+        assert_eq!(cfg.max_connections, 1);
     }
 
     #[test]
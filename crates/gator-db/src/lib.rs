@@ -2,3 +2,4 @@ pub mod config;
 pub mod models;
 pub mod pool;
 pub mod queries;
+pub mod redact;
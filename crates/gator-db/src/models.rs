@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::str::FromStr;
 
@@ -64,6 +65,53 @@ impl std::error::Error for PlanStatusParseError {}
 
 // ---------------------------------------------------------------------------
 
+/// Why a plan ended up `failed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PlanFailureReason {
+    /// The plan's token budget ran out; it can be resumed with a higher one.
+    BudgetExceeded,
+    /// Tasks were escalated (or blocked behind escalated tasks).
+    TasksEscalated,
+}
+
+impl fmt::Display for PlanFailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::TasksEscalated => "tasks_escalated",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for PlanFailureReason {
+    type Err = PlanFailureReasonParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "budget_exceeded" => Ok(Self::BudgetExceeded),
+            "tasks_escalated" => Ok(Self::TasksEscalated),
+            other => Err(PlanFailureReasonParseError(other.to_owned())),
+        }
+    }
+}
+
+/// Error returned when parsing an invalid [`PlanFailureReason`] string.
+#[derive(Debug, Clone)]
+pub struct PlanFailureReasonParseError(pub String);
+
+impl fmt::Display for PlanFailureReasonParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid plan failure reason: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for PlanFailureReasonParseError {}
+
+// ---------------------------------------------------------------------------
+
 /// Status of a task.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text", rename_all = "snake_case")]
@@ -72,10 +120,15 @@ pub enum TaskStatus {
     Pending,
     Assigned,
     Running,
+    /// The agent asked the operator a question (`gator ask`) and waits for
+    /// `gator answer`.
+    BlockedOnHuman,
     Checking,
     Passed,
     Failed,
     Escalated,
+    /// Stopped for good by the operator; the plan goes on without it.
+    Cancelled,
 }
 
 impl fmt::Display for TaskStatus {
@@ -84,10 +137,12 @@ impl fmt::Display for TaskStatus {
             Self::Pending => "pending",
             Self::Assigned => "assigned",
             Self::Running => "running",
+            Self::BlockedOnHuman => "blocked_on_human",
             Self::Checking => "checking",
             Self::Passed => "passed",
             Self::Failed => "failed",
             Self::Escalated => "escalated",
+            Self::Cancelled => "cancelled",
         };
         f.write_str(s)
     }
@@ -101,10 +156,12 @@ impl FromStr for TaskStatus {
             "pending" => Ok(Self::Pending),
             "assigned" => Ok(Self::Assigned),
             "running" => Ok(Self::Running),
+            "blocked_on_human" => Ok(Self::BlockedOnHuman),
             "checking" => Ok(Self::Checking),
             "passed" => Ok(Self::Passed),
             "failed" => Ok(Self::Failed),
             "escalated" => Ok(Self::Escalated),
+            "cancelled" => Ok(Self::Cancelled),
             other => Err(TaskStatusParseError(other.to_owned())),
         }
     }
@@ -206,6 +263,50 @@ impl FromStr for GatePolicy {
     }
 }
 
+/// Prefix of a plan file `gate` value naming a gate script.
+pub const GATE_SCRIPT_PREFIX: &str = "script:";
+
+/// Plan file `gate` value for a reviewer agent's verdict, optionally
+/// followed by `:<harness>`.
+pub const LLM_REVIEW_GATE: &str = "llm_review";
+
+/// A plan file `gate` value split into what is stored for the task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateSpec<'a> {
+    /// The stored gate policy.
+    pub policy: &'a str,
+    /// Script deciding the verdict, for `script:<path>`.
+    pub script: Option<&'a str>,
+    /// Whether a reviewer agent has the last word, for `llm_review`.
+    pub llm_review: bool,
+    /// Harness the reviewer runs on, for `llm_review:<harness>`.
+    pub review_harness: Option<&'a str>,
+}
+
+/// Split a plan file `gate` value into the stored policy and its extras.
+/// `script:<path>` and `llm_review[:<harness>]` are the `auto` policy with a
+/// script or a reviewer agent deciding the verdict.
+pub fn split_gate(gate: &str) -> GateSpec<'_> {
+    let mut spec = GateSpec {
+        policy: gate,
+        script: None,
+        llm_review: false,
+        review_harness: None,
+    };
+    if let Some(script) = gate.strip_prefix(GATE_SCRIPT_PREFIX) {
+        spec.policy = "auto";
+        spec.script = Some(script);
+    } else if let Some(rest) = gate.strip_prefix(LLM_REVIEW_GATE) {
+        let harness = rest.strip_prefix(':');
+        if rest.is_empty() || harness.is_some() {
+            spec.policy = "auto";
+            spec.llm_review = true;
+            spec.review_harness = harness;
+        }
+    }
+    spec
+}
+
 /// Error returned when parsing an invalid [`GatePolicy`] string.
 #[derive(Debug, Clone)]
 pub struct GatePolicyParseError(pub String);
@@ -220,6 +321,98 @@ impl std::error::Error for GatePolicyParseError {}
 
 // ---------------------------------------------------------------------------
 
+/// What a task produces: code merged from its branch, or an artifact bundle
+/// (docs, research notes) gated on the artifacts themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Code,
+    Artifact,
+}
+
+impl fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Code => "code",
+            Self::Artifact => "artifact",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for TaskKind {
+    type Err = TaskKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "code" => Ok(Self::Code),
+            "artifact" => Ok(Self::Artifact),
+            other => Err(TaskKindParseError(other.to_owned())),
+        }
+    }
+}
+
+/// Error returned when parsing an invalid [`TaskKind`] string.
+#[derive(Debug, Clone)]
+pub struct TaskKindParseError(pub String);
+
+impl fmt::Display for TaskKindParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid task kind: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for TaskKindParseError {}
+
+// ---------------------------------------------------------------------------
+
+/// What a dependency edge means: the task builds on the upstream task's
+/// changes, or merely has to run after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    NeedsOutput,
+    OrderingOnly,
+}
+
+impl fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::NeedsOutput => "needs_output",
+            Self::OrderingOnly => "ordering_only",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for DependencyKind {
+    type Err = DependencyKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "needs_output" => Ok(Self::NeedsOutput),
+            "ordering_only" => Ok(Self::OrderingOnly),
+            other => Err(DependencyKindParseError(other.to_owned())),
+        }
+    }
+}
+
+/// Error returned when parsing an invalid [`DependencyKind`] string.
+#[derive(Debug, Clone)]
+pub struct DependencyKindParseError(pub String);
+
+impl fmt::Display for DependencyKindParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dependency kind: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for DependencyKindParseError {}
+
+// ---------------------------------------------------------------------------
+
 /// Kind of invariant check.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text", rename_all = "snake_case")]
@@ -317,6 +510,83 @@ impl fmt::Display for InvariantScopeParseError {
 
 impl std::error::Error for InvariantScopeParseError {}
 
+// ---------------------------------------------------------------------------
+
+/// A timed phase of one task attempt's lifecycle, ordered as they run.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LifecyclePhase {
+    /// Creating the worktree or container.
+    Workspace,
+    /// Starting the ephemeral database and compose services, if any.
+    Services,
+    /// Running the plan's and task's setup commands, if any.
+    Setup,
+    /// Running the plan's warm-up command, if any.
+    Warmup,
+    /// Generating the token and materializing the task prompt.
+    Materialize,
+    /// Spawning the agent and collecting its events.
+    Agent,
+    /// Copying results out of a container.
+    Extract,
+    /// Running the gate's invariants and artifact checks.
+    Gate,
+    /// Evaluating the verdict and committing or bundling the work.
+    Verdict,
+}
+
+impl fmt::Display for LifecyclePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Workspace => "workspace",
+            Self::Services => "services",
+            Self::Setup => "setup",
+            Self::Warmup => "warmup",
+            Self::Materialize => "materialize",
+            Self::Agent => "agent",
+            Self::Extract => "extract",
+            Self::Gate => "gate",
+            Self::Verdict => "verdict",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for LifecyclePhase {
+    type Err = LifecyclePhaseParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "workspace" => Ok(Self::Workspace),
+            "services" => Ok(Self::Services),
+            "setup" => Ok(Self::Setup),
+            "warmup" => Ok(Self::Warmup),
+            "materialize" => Ok(Self::Materialize),
+            "agent" => Ok(Self::Agent),
+            "extract" => Ok(Self::Extract),
+            "gate" => Ok(Self::Gate),
+            "verdict" => Ok(Self::Verdict),
+            other => Err(LifecyclePhaseParseError(other.to_owned())),
+        }
+    }
+}
+
+/// Error returned when parsing an invalid [`LifecyclePhase`] string.
+#[derive(Debug, Clone)]
+pub struct LifecyclePhaseParseError(pub String);
+
+impl fmt::Display for LifecyclePhaseParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid lifecycle phase: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for LifecyclePhaseParseError {}
+
 // ---------------------------------------------------------------------------
 // Row structs
 // ---------------------------------------------------------------------------
@@ -333,9 +603,108 @@ pub struct Plan {
     pub default_harness: String,
     pub isolation: String,
     pub container_image: Option<String>,
+    /// Postgres image for per-attempt ephemeral databases, if enabled.
+    pub ephemeral_db: Option<String>,
+    /// Setup command for the plan's template worktree, if enabled.
+    pub template_setup: Option<String>,
+    /// Hard-link template build outputs into worktrees that cannot be
+    /// cloned from the template.
+    pub template_hardlinks: bool,
     pub created_at: DateTime<Utc>,
     pub approved_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Set when soft-deleted; hard-pruned after the retention window.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Set when archived; archived plans are left out of plan listings.
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Set when a dispatch was cancelled; cleared when it resumes.
+    pub interrupted_at: Option<DateTime<Utc>>,
+    /// Why the plan failed; set only while the status is `failed`.
+    pub failure_reason: Option<PlanFailureReason>,
+    /// Host of the orchestrator that last dispatched the plan.
+    pub dispatched_host: Option<String>,
+    /// Process ID of that orchestrator.
+    pub dispatched_pid: Option<i64>,
+    /// User that orchestrator ran as.
+    pub dispatched_user: Option<String>,
+    /// When that orchestrator started running the plan.
+    pub dispatched_at: Option<DateTime<Utc>>,
+    /// Command run in each task's workspace before its agent starts.
+    pub warmup_command: Option<String>,
+    /// Shell commands run at points of a dispatch or merge.
+    pub hooks: sqlx::types::Json<PlanHooks>,
+    /// Commands every task's workspace must be set up with before its
+    /// agent starts (e.g. `npm ci`); a failure fails the attempt.
+    pub setup_commands: sqlx::types::Json<Vec<String>>,
+    /// Dependency caches shared by every task workspace of the plan.
+    pub caches: sqlx::types::Json<Vec<CacheKind>>,
+}
+
+/// A dependency cache that task workspaces can share, so parallel agents do
+/// not each download the same packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    /// Cargo's registry and git checkouts (`CARGO_HOME`).
+    Cargo,
+    /// npm's package cache.
+    Npm,
+    /// pnpm's content-addressable store.
+    Pnpm,
+    /// pip's wheel and HTTP cache.
+    Pip,
+    /// Go's module and build caches.
+    Go,
+}
+
+impl CacheKind {
+    /// Every cache kind.
+    pub const ALL: [CacheKind; 5] = [
+        CacheKind::Cargo,
+        CacheKind::Npm,
+        CacheKind::Pnpm,
+        CacheKind::Pip,
+        CacheKind::Go,
+    ];
+}
+
+impl fmt::Display for CacheKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Pip => "pip",
+            Self::Go => "go",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A plan's `[hooks]`: shell commands run from the project directory, with
+/// the plan (and task) in `GATOR_*` environment variables.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanHooks {
+    /// Run before a dispatch starts any task; a failure stops the dispatch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_dispatch: Option<String>,
+    /// Run after each task attempt's lifecycle ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_task: Option<String>,
+    /// Run when a dispatch of the plan ends, however it ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_plan: Option<String>,
+    /// Run after `gator merge` merges the plan's branches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_merge: Option<String>,
+}
+
+impl PlanHooks {
+    /// Whether no hook is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// A task -- a unit of work within a plan.
@@ -356,6 +725,75 @@ pub struct Task {
     pub created_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Source issue reference (`owner/repo#N`) for imported tasks.
+    pub issue: Option<String>,
+    /// Set when the owning plan is soft-deleted.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Compose files brought up around each attempt, relative to the worktree.
+    pub services: sqlx::types::Json<Vec<String>>,
+    pub kind: TaskKind,
+    /// Paths (relative to the worktree) an artifact task must produce.
+    pub artifacts: sqlx::types::Json<Vec<String>>,
+    /// Patterns the task may modify; empty means the whole repository.
+    pub paths: sqlx::types::Json<Vec<String>>,
+    /// Per-attempt time limit in seconds; the dispatch timeout when NULL.
+    pub timeout_secs: Option<i64>,
+    /// Extra environment variables for the agent and gate of one attempt,
+    /// set by `gator retry --env`.
+    pub retry_env: sqlx::types::Json<BTreeMap<String, String>>,
+    /// The attempt `retry_env` applies to.
+    pub retry_env_attempt: Option<i32>,
+    /// Script that decides an `auto` gate's verdict from its results,
+    /// relative to the project unless absolute.
+    pub gate_script: Option<String>,
+    /// Process ID of the agent of the current attempt, once spawned.
+    pub agent_pid: Option<i64>,
+    /// Container the current attempt's agent runs in, for container
+    /// isolation.
+    pub container_id: Option<String>,
+    /// Host the current attempt's agent runs on.
+    pub agent_host: Option<String>,
+    /// Start time of the agent's process, as reported by the OS; tells the
+    /// agent apart from a later process that reuses its ID.
+    pub agent_started: Option<String>,
+    /// Whether a reviewer agent decides an `auto` gate once the invariants
+    /// pass.
+    pub llm_review: bool,
+    /// Harness of the reviewer; the task's own harness when NULL.
+    pub review_harness: Option<String>,
+    /// The `gator dispatch` run of the current attempt, if dispatched.
+    pub run_id: Option<Uuid>,
+    /// Identifies the current attempt's lifecycle across logs and records.
+    pub attempt_id: Option<Uuid>,
+    /// When `gator merge` merged the task's branch into the base branch.
+    pub merged_at: Option<DateTime<Utc>>,
+    /// The base branch's commit after the task's branch was merged.
+    pub merge_commit: Option<String>,
+    /// Setup commands run after the plan's, before the agent starts.
+    pub setup_commands: sqlx::types::Json<Vec<String>>,
+    /// Bytes the task's worktree took on disk when it was last measured.
+    pub disk_usage_bytes: Option<i64>,
+    /// When `disk_usage_bytes` was measured.
+    pub disk_usage_at: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    /// The `retry_env` overrides, if they apply to the current attempt.
+    pub fn attempt_env(&self) -> Option<&BTreeMap<String, String>> {
+        (self.retry_env_attempt == Some(self.attempt) && !self.retry_env.is_empty())
+            .then_some(&*self.retry_env)
+    }
+    /// The task's gate as a plan file writes it: the policy,
+    /// `script:<path>` when a gate script decides the verdict, or
+    /// `llm_review[:<harness>]` when a reviewer agent does.
+    pub fn gate(&self) -> String {
+        match (&self.gate_script, &self.review_harness) {
+            (Some(script), _) => format!("{GATE_SCRIPT_PREFIX}{script}"),
+            (None, Some(harness)) if self.llm_review => format!("{LLM_REVIEW_GATE}:{harness}"),
+            (None, None) if self.llm_review => LLM_REVIEW_GATE.to_string(),
+            _ => self.gate_policy.to_string(),
+        }
+    }
 }
 
 /// An edge in the task dependency DAG.
@@ -363,6 +801,7 @@ pub struct Task {
 pub struct TaskDependency {
     pub task_id: Uuid,
     pub depends_on: Uuid,
+    pub kind: DependencyKind,
 }
 
 /// A reusable invariant definition.
@@ -379,6 +818,26 @@ pub struct Invariant {
     pub scope: InvariantScope,
     pub timeout_secs: i32,
     pub created_at: DateTime<Utc>,
+    /// Set when soft-deleted; hard-pruned after the retention window.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Also run on the commit the task branched from, and fail only when
+    /// the number the command prints regresses (by more than `threshold`).
+    pub diff_base: bool,
+    /// For `diff_base`: a smaller number is better (e.g. binary size).
+    pub lower_is_better: bool,
+    /// Set while disabled: the gate skips the invariant but its task links
+    /// are kept.
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Environment variables set for the command.
+    pub env: sqlx::types::Json<BTreeMap<String, String>>,
+    /// Directory the command runs in, relative to the worktree root.
+    pub workdir: Option<String>,
+    /// Invariants that must finish before this one starts, when the same
+    /// gate runs them.
+    pub run_after: sqlx::types::Json<Vec<String>>,
+    /// Globs, relative to the worktree root, of files (screenshots, videos)
+    /// collected from the worktree when the invariant fails.
+    pub artifacts: sqlx::types::Json<Vec<String>>,
 }
 
 /// Join row linking a task to an invariant.
@@ -401,6 +860,294 @@ pub struct GateResult {
     pub stderr: Option<String>,
     pub duration_ms: Option<i32>,
     pub checked_at: DateTime<Utc>,
+    /// The invariant ran past its timeout and was killed.
+    pub timed_out: bool,
+    /// For `diff_base` invariants: the number printed on the base commit.
+    pub base_value: Option<f64>,
+    /// For `diff_base` invariants: the number printed on the task branch.
+    pub task_value: Option<f64>,
+    /// Hash of the worktree contents and invariant definition the result
+    /// was computed for; `None` when the worktree could not be hashed.
+    pub content_hash: Option<String>,
+    /// The earlier attempt whose result was reused because the hash
+    /// matched; `None` when the invariant actually ran.
+    pub cached_from: Option<i32>,
+    /// The `gator dispatch` run the result was recorded in.
+    pub run_id: Option<Uuid>,
+    /// The task lifecycle the result was recorded in.
+    pub attempt_id: Option<Uuid>,
+}
+
+/// A file collected from the worktree when an invariant failed, attached
+/// to its gate result.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GateArtifact {
+    pub id: Uuid,
+    pub gate_result_id: Uuid,
+    /// Where the file was, relative to the worktree root.
+    pub path: String,
+    /// Where the copy is kept, outside the worktree.
+    pub stored_path: String,
+    pub size_bytes: i64,
+    pub collected_at: DateTime<Utc>,
+}
+
+/// A `human_review` task passed by an auto-approval rule instead of a human.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AutoApproval {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// Name of the rule that matched.
+    pub rule: String,
+    /// Lines added plus lines removed by the task.
+    pub diff_lines: i64,
+    /// The files the task changed, relative to the worktree root.
+    pub files: sqlx::types::Json<Vec<String>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A compliance attestation of the code one attempt of a task generated.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Attestation {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// Harness the agent ran on, e.g. `claude-code`.
+    pub harness: String,
+    /// Version the harness's CLI reported, if any.
+    pub harness_version: Option<String>,
+    /// SHA-256 of the prompt the agent was given.
+    pub prompt_sha256: String,
+    /// The commit the task branched from.
+    pub base_commit: String,
+    /// SHA-256 of the task's change relative to `base_commit`.
+    pub diff_sha256: String,
+    /// The attestation file, relative to the worktree root.
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a plan's orchestrator is holding back after provider rate limits.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PlanThrottle {
+    pub plan_id: Uuid,
+    /// Agents the orchestrator runs at once for now.
+    pub agent_limit: i64,
+    /// Agents it runs when not throttled.
+    pub max_agents: i64,
+    /// No new agents start before this time.
+    pub paused_until: Option<DateTime<Utc>>,
+    /// Rate-limit errors seen since throttling began.
+    pub rate_limits: i64,
+    pub last_error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A plan cloned and dispatched on a cron schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Schedule {
+    pub id: Uuid,
+    /// The plan each run clones.
+    pub plan_id: Uuid,
+    /// Five-field cron expression, evaluated in UTC.
+    pub cron: String,
+    pub max_agents: i64,
+    /// Per-task timeout of each run, in seconds.
+    pub timeout_secs: i64,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// The clone the last run created.
+    pub last_plan_id: Option<Uuid>,
+    /// Why the last run could not be started or dispatched.
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The run of a plan's warm-up command in one attempt's workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WarmupResult {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub command: String,
+    /// The command exited 0 within the timeout.
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    /// The command ran past the task timeout and was killed.
+    pub timed_out: bool,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub duration_ms: i64,
+    /// The `gator dispatch` run the result was recorded in.
+    pub run_id: Option<Uuid>,
+    /// The task lifecycle the result was recorded in.
+    pub attempt_id: Option<Uuid>,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// The run of one setup command in one attempt's workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SetupResult {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// Where the command comes in the attempt's setup, from 0.
+    pub position: i32,
+    pub command: String,
+    /// The command exited 0 within the timeout.
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    /// The command ran past the task timeout and was killed.
+    pub timed_out: bool,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub duration_ms: i64,
+    /// The `gator dispatch` run the result was recorded in.
+    pub run_id: Option<Uuid>,
+    /// The task lifecycle the result was recorded in.
+    pub attempt_id: Option<Uuid>,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// One entry of the change feed: a row of a tracked table was inserted,
+/// updated, or deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChangeLogEntry {
+    /// Position in the feed; only grows.
+    pub seq: i64,
+    /// `plans`, `tasks`, `attempt_phases`, or `gate_results`.
+    pub table_name: String,
+    /// The changed row's ID; for an attempt phase, its task's.
+    pub row_id: Uuid,
+    /// The attempt of a changed attempt phase.
+    pub attempt: Option<i32>,
+    /// The phase of a changed attempt phase.
+    pub phase: Option<String>,
+    /// `insert`, `update`, or `delete`.
+    pub op: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// The result of one built-in artifact check for one attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ArtifactCheck {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub path: String,
+    /// `exists`, `markdown`, `links`, `review`, or `scope`.
+    pub check_name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// An operator note on a plan, or on one of its tasks when `task_id` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Note {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub task_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How long one lifecycle phase of a task attempt took.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AttemptPhase {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub phase: LifecyclePhase,
+    pub duration_ms: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A prompt section dropped from a task attempt to fit the prompt budget.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PromptTrim {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// Section key, e.g. `memories` or `feedback`.
+    pub section: String,
+    /// Estimated size of the dropped section.
+    pub estimated_tokens: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A remembered task outcome, retrieved into later tasks of the project.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Memory {
+    pub id: Uuid,
+    pub project_path: String,
+    /// The task it came from; `None` once that task's plan is deleted.
+    pub task_id: Option<Uuid>,
+    pub attempt: i32,
+    /// `success` or `failure`.
+    pub kind: String,
+    /// `<plan>/<task>` at the time of recording.
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A reviewer's reason for rejecting one attempt of a task.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReviewFeedback {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A task description replaced by `gator retry --edit`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskRevision {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// The first attempt that runs with `description`.
+    pub attempt: i32,
+    /// The description before the revision.
+    pub previous: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An issue queued by the triage daemon and its posting state.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TriageItem {
+    pub id: Uuid,
+    /// Repository as `owner/name`.
+    pub repo: String,
+    pub issue_number: i64,
+    pub plan_id: Uuid,
+    pub task_id: Uuid,
+    /// `queued`, `posted`, or `failed`.
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub posted_at: Option<DateTime<Utc>>,
+}
+
+/// A pull request head queued by the review bot and its posting state.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PrReview {
+    pub id: Uuid,
+    /// Repository as `owner/name`.
+    pub repo: String,
+    pub pr_number: i64,
+    /// Reviewed head commit.
+    pub head_sha: String,
+    pub plan_id: Uuid,
+    pub task_id: Uuid,
+    /// Files the PR changes; the review must cover each.
+    pub changed_files: sqlx::types::Json<Vec<String>>,
+    /// `queued`, `posted`, or `failed`.
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub posted_at: Option<DateTime<Utc>>,
 }
 
 /// An event recorded from an agent's execution stream.
@@ -412,6 +1159,63 @@ pub struct AgentEvent {
     pub event_type: String,
     pub payload: serde_json::Value,
     pub recorded_at: DateTime<Utc>,
+    /// The `gator dispatch` run the event was recorded in.
+    pub run_id: Option<Uuid>,
+    /// The task lifecycle the event was recorded in.
+    pub attempt_id: Option<Uuid>,
+}
+
+/// How the control plane answered an agent-mode command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The command succeeded.
+    Ok,
+    /// The command ran and failed.
+    Error,
+    /// The command is not available in agent mode.
+    Denied,
+}
+
+impl fmt::Display for AuditOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+            Self::Denied => "denied",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One agent-mode CLI invocation made with a valid token.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AgentAudit {
+    pub id: Uuid,
+    /// Task and attempt from the token's claims.
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// The command the agent ran, e.g. `done`.
+    pub command: String,
+    /// Format of the token it presented (`v1` or `v2`).
+    pub token_version: String,
+    pub outcome: AuditOutcome,
+    /// Why the command failed or was denied.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A webhook notification that exhausted its delivery attempts.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationDeadLetter {
+    pub id: i64,
+    pub event: String,
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub error: String,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
 }
 
 // ---------------------------------------------------------------------------
@@ -450,10 +1254,12 @@ mod tests {
             TaskStatus::Pending,
             TaskStatus::Assigned,
             TaskStatus::Running,
+            TaskStatus::BlockedOnHuman,
             TaskStatus::Checking,
             TaskStatus::Passed,
             TaskStatus::Failed,
             TaskStatus::Escalated,
+            TaskStatus::Cancelled,
         ];
         for v in &variants {
             let s = v.to_string();
@@ -504,6 +1310,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn split_gate_separates_script_and_review() {
+        let human = split_gate("human_review");
+        assert_eq!((human.policy, human.script), ("human_review", None));
+        assert!(!human.llm_review);
+
+        let script = split_gate("script:ci/gate.sh");
+        assert_eq!((script.policy, script.script), ("auto", Some("ci/gate.sh")));
+
+        let review = split_gate("llm_review");
+        assert_eq!((review.policy, review.llm_review), ("auto", true));
+        assert_eq!(review.review_harness, None);
+        let review = split_gate("llm_review:codex");
+        assert_eq!(review.review_harness, Some("codex"));
+
+        // Not a review gate; left for policy validation to reject.
+        assert_eq!(split_gate("llm_reviewer").policy, "llm_reviewer");
+    }
+
     #[test]
     fn invariant_kind_display_roundtrip() {
         let variants = [
@@ -1,6 +1,6 @@
-use anyhow::{Context, Result};
-use sqlx::SqlitePool;
+use anyhow::{Context, Result, bail};
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{Sqlite, SqlitePool, Transaction};
 use std::str::FromStr;
 use tracing::info;
 
@@ -23,20 +23,174 @@ pub async fn create_pool(config: &DbConfig) -> Result<SqlitePool> {
         .create_if_missing(true);
 
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
         .connect_with(options)
         .await
         .with_context(|| format!("failed to connect to database at {}", url))?;
     Ok(pool)
 }
 
+/// Create a read-only connection pool.
+///
+/// Used for dashboards and reporting queries when a read replica is
+/// configured, so long-running reads never hold connections the
+/// orchestrator needs for writes. The database file must already exist;
+/// any attempt to write through this pool fails with `SQLITE_READONLY`.
+pub async fn create_read_only_pool(config: &DbConfig) -> Result<SqlitePool> {
+    let url = config.database_url();
+    let options = SqliteConnectOptions::from_str(&url)
+        .with_context(|| format!("invalid database URL: {}", url))?
+        .read_only(true)
+        .create_if_missing(false)
+        .busy_timeout(std::time::Duration::from_secs(5));
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("failed to open read-only database at {}", url))?;
+    Ok(pool)
+}
+
+/// Begin a write transaction that takes the SQLite write lock up front.
+///
+/// A deferred `BEGIN` that reads before writing can fail with
+/// `SQLITE_BUSY` when another writer commits in between; `BEGIN IMMEDIATE`
+/// instead waits (up to the busy timeout) for the lock before any reads.
+/// Use this for read-check-write sequences that must be atomic.
+pub async fn begin_immediate(pool: &SqlitePool) -> Result<Transaction<'static, Sqlite>> {
+    pool.begin_with("BEGIN IMMEDIATE")
+        .await
+        .context("failed to begin immediate transaction")
+}
+
+/// Recommended pool size for a fleet of `max_agents` concurrent agents.
+///
+/// Each in-flight lifecycle writes events and gate results while the
+/// orchestrator loop polls progress, so budget two connections per agent
+/// plus headroom for the orchestrator itself. Never smaller than the default.
+pub fn recommended_max_connections(max_agents: usize) -> u32 {
+    let wanted = (max_agents as u32).saturating_mul(2).saturating_add(2);
+    wanted.max(crate::config::DEFAULT_MAX_CONNECTIONS)
+}
+
+/// Point-in-time snapshot of pool utilization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Connections currently open (idle + in use).
+    pub size: u32,
+    /// Open connections not currently checked out.
+    pub idle: u32,
+    /// Configured upper bound on open connections.
+    pub max_connections: u32,
+}
+
+impl PoolStats {
+    /// Connections currently checked out.
+    pub fn in_use(&self) -> u32 {
+        self.size.saturating_sub(self.idle)
+    }
+
+    /// True when every allowed connection is open and checked out, so the
+    /// next acquire has to wait.
+    pub fn is_saturated(&self) -> bool {
+        self.size >= self.max_connections && self.idle == 0
+    }
+}
+
+/// Take a utilization snapshot of `pool`.
+pub fn pool_stats(pool: &SqlitePool) -> PoolStats {
+    PoolStats {
+        size: pool.size(),
+        idle: pool.num_idle() as u32,
+        max_connections: pool.options().get_max_connections(),
+    }
+}
+
 /// Run all pending embedded migrations against the pool.
+///
+/// Migrations run on one connection with foreign key enforcement off, as
+/// SQLite requires for rebuilding a table: with it on, dropping the old
+/// table would delete every row referencing it. The references are checked
+/// once the migrations are done.
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
-    MIGRATOR
-        .run(pool)
+    let mut conn = pool
+        .acquire()
+        .await
+        .context("failed to acquire database connection")?;
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .context("failed to disable foreign keys for migrations")?;
+    let migrated = MIGRATOR
+        .run(&mut *conn)
+        .await
+        .context("failed to run database migrations");
+    let violations = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut *conn)
         .await
-        .context("failed to run database migrations")?;
+        .context("failed to check foreign keys after migrations");
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .context("failed to re-enable foreign keys after migrations")?;
+    migrated?;
+    let violations = violations?.len();
+    if violations > 0 {
+        bail!("migrations left {violations} rows with dangling foreign keys");
+    }
 
     info!("migrations applied successfully");
     Ok(())
 }
+
+/// The newest migration applied to the database, if any.
+pub async fn schema_version(pool: &SqlitePool) -> Result<Option<i64>> {
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(pool)
+            .await
+            .context("failed to read schema version")?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommended_size_never_below_default() {
+        assert_eq!(recommended_max_connections(0), 5);
+        assert_eq!(recommended_max_connections(1), 5);
+        assert_eq!(recommended_max_connections(4), 10);
+        assert_eq!(recommended_max_connections(16), 34);
+    }
+
+    #[test]
+    fn pool_stats_saturation() {
+        let busy = PoolStats {
+            size: 5,
+            idle: 0,
+            max_connections: 5,
+        };
+        assert!(busy.is_saturated());
+        assert_eq!(busy.in_use(), 5);
+
+        let idle = PoolStats {
+            size: 5,
+            idle: 2,
+            max_connections: 5,
+        };
+        assert!(!idle.is_saturated());
+        assert_eq!(idle.in_use(), 3);
+
+        let growing = PoolStats {
+            size: 3,
+            idle: 0,
+            max_connections: 5,
+        };
+        assert!(!growing.is_saturated());
+    }
+}
@@ -0,0 +1,69 @@
+//! Database query functions for the `agent_audit` table.
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::{AgentAudit, AuditOutcome};
+use crate::redact;
+
+/// Parameters for recording an agent-mode invocation.
+#[derive(Debug, Clone)]
+pub struct NewAgentAudit<'a> {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub command: &'a str,
+    pub token_version: &'a str,
+    pub outcome: AuditOutcome,
+    pub error: Option<&'a str>,
+}
+
+/// Record an agent-mode invocation, with secrets in its error redacted.
+pub async fn insert_agent_audit<'e>(
+    executor: impl SqliteExecutor<'e>,
+    new: &NewAgentAudit<'_>,
+) -> Result<AgentAudit> {
+    let audit = sqlx::query_as::<_, AgentAudit>(
+        "INSERT INTO agent_audit (id, task_id, attempt, command, token_version, outcome, error) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.task_id)
+    .bind(new.attempt)
+    .bind(new.command)
+    .bind(new.token_version)
+    .bind(new.outcome)
+    .bind(new.error.map(|e| redact::redact(e).into_owned()))
+    .fetch_one(executor)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to record agent {} for task {}",
+            new.command, new.task_id
+        )
+    })?;
+
+    Ok(audit)
+}
+
+/// A task's agent-mode invocations, oldest first, optionally only those of
+/// one attempt.
+pub async fn list_agent_audit_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: Option<i32>,
+) -> Result<Vec<AgentAudit>> {
+    let audit = sqlx::query_as::<_, AgentAudit>(
+        "SELECT * FROM agent_audit \
+         WHERE task_id = $1 AND ($2 IS NULL OR attempt = $2) \
+         ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list agent audit for task {task_id}"))?;
+
+    Ok(audit)
+}
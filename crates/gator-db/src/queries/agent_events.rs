@@ -1,11 +1,15 @@
 //! Database query functions for the `agent_events` table.
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde_json::Value;
-use sqlx::SqlitePool;
+use sqlx::{SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
 use crate::models::AgentEvent;
+use crate::redact;
+
+use super::sqlite_timestamp;
 
 /// Parameters for inserting a new agent event row.
 #[derive(Debug, Clone)]
@@ -16,19 +20,26 @@ pub struct NewAgentEvent {
     pub payload: Value,
 }
 
-/// Insert a new agent event row. Returns the inserted row with
+/// Insert a new agent event row, with secrets in its payload redacted.
+/// The event takes the run and attempt IDs of the task's current attempt
+/// when `attempt` is that attempt. Returns the inserted row with
 /// server-generated defaults (id, recorded_at).
-pub async fn insert_agent_event(pool: &SqlitePool, new: &NewAgentEvent) -> Result<AgentEvent> {
+pub async fn insert_agent_event<'e>(
+    executor: impl SqliteExecutor<'e>,
+    new: &NewAgentEvent,
+) -> Result<AgentEvent> {
     let event = sqlx::query_as::<_, AgentEvent>(
-        "INSERT INTO agent_events (task_id, attempt, event_type, payload) \
-         VALUES ($1, $2, $3, $4) \
+        "INSERT INTO agent_events (task_id, attempt, event_type, payload, run_id, attempt_id) \
+         VALUES ($1, $2, $3, $4, \
+                 (SELECT run_id FROM tasks WHERE id = $1 AND attempt = $2), \
+                 (SELECT attempt_id FROM tasks WHERE id = $1 AND attempt = $2)) \
          RETURNING *",
     )
     .bind(new.task_id)
     .bind(new.attempt)
     .bind(&new.event_type)
-    .bind(&new.payload)
-    .fetch_one(pool)
+    .bind(redact::redact_json(&new.payload))
+    .fetch_one(executor)
     .await
     .with_context(|| {
         format!(
@@ -82,6 +93,41 @@ pub async fn list_all_events_for_task(pool: &SqlitePool, task_id: Uuid) -> Resul
     Ok(events)
 }
 
+/// Which agent events [`list_matching_events`] returns. Unset fields match
+/// everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventFilter {
+    pub task_id: Option<Uuid>,
+    pub attempt: Option<i32>,
+    pub run_id: Option<Uuid>,
+    pub attempt_id: Option<Uuid>,
+}
+
+/// Get the agent events matching `filter`, across tasks, ordered by
+/// recorded_at ASC.
+pub async fn list_matching_events(
+    pool: &SqlitePool,
+    filter: &EventFilter,
+) -> Result<Vec<AgentEvent>> {
+    let events = sqlx::query_as::<_, AgentEvent>(
+        "SELECT * FROM agent_events \
+         WHERE ($1 IS NULL OR task_id = $1) \
+           AND ($2 IS NULL OR attempt = $2) \
+           AND ($3 IS NULL OR run_id = $3) \
+           AND ($4 IS NULL OR attempt_id = $4) \
+         ORDER BY recorded_at ASC, id ASC",
+    )
+    .bind(filter.task_id)
+    .bind(filter.attempt)
+    .bind(filter.run_id)
+    .bind(filter.attempt_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list agent events matching {filter:?}"))?;
+
+    Ok(events)
+}
+
 /// Get aggregate token usage for an entire plan.
 ///
 /// Sums `input_tokens` and `output_tokens` from token_usage events across all
@@ -123,6 +169,33 @@ pub async fn get_token_usage_for_task(pool: &SqlitePool, task_id: Uuid) -> Resul
     Ok((row.0.unwrap_or(0), row.1.unwrap_or(0)))
 }
 
+/// Average token usage of the agent attempts run for a project, over every
+/// attempt that reported any. Returns `(attempts, average_tokens)`, input
+/// and output combined; `(0, 0)` when no attempt has.
+pub async fn average_tokens_per_attempt(
+    pool: &SqlitePool,
+    project_path: &str,
+) -> Result<(i64, i64)> {
+    let row: (i64, Option<f64>) = sqlx::query_as(
+        "SELECT COUNT(*), AVG(total) FROM ( \
+             SELECT SUM(COALESCE(CAST(json_extract(ae.payload, '$.input_tokens') AS INTEGER), 0) \
+                      + COALESCE(CAST(json_extract(ae.payload, '$.output_tokens') AS INTEGER), 0)) \
+                    AS total \
+             FROM agent_events ae \
+             JOIN tasks t ON t.id = ae.task_id \
+             JOIN plans p ON p.id = t.plan_id \
+             WHERE p.project_path = $1 AND ae.event_type = 'token_usage' \
+             GROUP BY ae.task_id, ae.attempt \
+         )",
+    )
+    .bind(project_path)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to average token usage for project {project_path}"))?;
+
+    Ok((row.0, row.1.map_or(0, |avg| avg.round() as i64)))
+}
+
 /// Get the most recent agent events for a task, optionally filtered by
 /// attempt. Returns at most `limit` events (newest first).
 pub async fn get_recent_events_for_task(
@@ -160,6 +233,108 @@ pub async fn get_recent_events_for_task(
     Ok(events)
 }
 
+/// Get the latest event of `event_type` recorded for a task's attempt, if
+/// any.
+pub async fn get_latest_event_of_type(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    event_type: &str,
+) -> Result<Option<AgentEvent>> {
+    let event = sqlx::query_as::<_, AgentEvent>(
+        "SELECT * FROM agent_events \
+         WHERE task_id = $1 AND attempt = $2 AND event_type = $3 \
+         ORDER BY recorded_at DESC, id DESC \
+         LIMIT 1",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .bind(event_type)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to get latest {event_type} event for task {task_id}"))?;
+
+    Ok(event)
+}
+
+/// When the latest event of a task's attempt was recorded, if it has any.
+/// Events come from the agent's output stream and from its `gator progress`
+/// calls alike, so this is the attempt's last sign of activity.
+pub async fn last_event_at(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Option<DateTime<Utc>>> {
+    let row: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        "SELECT recorded_at FROM agent_events \
+         WHERE task_id = $1 AND attempt = $2 \
+         ORDER BY recorded_at DESC \
+         LIMIT 1",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to get last event time for task {task_id}"))?;
+
+    Ok(row.map(|(at,)| at))
+}
+
+/// Get the most recent `error` events of a plan's tasks, across all
+/// attempts. Returns at most `limit` events (newest first).
+pub async fn get_recent_errors_for_plan(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    limit: i64,
+) -> Result<Vec<AgentEvent>> {
+    let events = sqlx::query_as::<_, AgentEvent>(
+        "SELECT ae.* FROM agent_events ae \
+         JOIN tasks t ON t.id = ae.task_id \
+         WHERE t.plan_id = $1 AND ae.event_type = 'error' \
+         ORDER BY ae.recorded_at DESC, ae.id DESC \
+         LIMIT $2",
+    )
+    .bind(plan_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get recent errors for plan {plan_id}"))?;
+
+    Ok(events)
+}
+
+/// `error` events of a plan's tasks with an ID above `after_id`, oldest
+/// first.
+pub async fn list_errors_for_plan_after(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    after_id: i64,
+) -> Result<Vec<AgentEvent>> {
+    let events = sqlx::query_as::<_, AgentEvent>(
+        "SELECT ae.* FROM agent_events ae \
+         JOIN tasks t ON t.id = ae.task_id \
+         WHERE t.plan_id = $1 AND ae.event_type = 'error' AND ae.id > $2 \
+         ORDER BY ae.id ASC",
+    )
+    .bind(plan_id)
+    .bind(after_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list new errors for plan {plan_id}"))?;
+
+    Ok(events)
+}
+
+/// The highest agent event ID, or 0 when there are no events.
+pub async fn max_event_id(pool: &SqlitePool) -> Result<i64> {
+    let row: (Option<i64>,) = sqlx::query_as("SELECT MAX(id) FROM agent_events")
+        .fetch_one(pool)
+        .await
+        .context("failed to get the latest agent event id")?;
+
+    Ok(row.0.unwrap_or(0))
+}
+
 /// Count the number of agent events for a given task and attempt.
 pub async fn count_events_for_task(pool: &SqlitePool, task_id: Uuid, attempt: i32) -> Result<i64> {
     let row: (i64,) = sqlx::query_as(
@@ -179,3 +354,68 @@ pub async fn count_events_for_task(pool: &SqlitePool, task_id: Uuid, attempt: i3
 
     Ok(row.0)
 }
+
+/// Count the agent events of a plan's tasks, across all attempts.
+pub async fn count_events_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<i64> {
+    let row: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM agent_events ae \
+         JOIN tasks t ON t.id = ae.task_id \
+         WHERE t.plan_id = $1",
+    )
+    .bind(plan_id)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to count agent events for plan {plan_id}"))?;
+
+    Ok(row.0)
+}
+
+/// Delete every agent event of a plan's tasks, across all attempts.
+///
+/// Returns the number of events removed.
+pub async fn delete_events_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM agent_events \
+         WHERE task_id IN (SELECT id FROM tasks WHERE plan_id = $1)",
+    )
+    .bind(plan_id)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to delete agent events for plan {plan_id}"))?;
+
+    Ok(result.rows_affected())
+}
+
+/// Events `prune_events` deletes: recorded before `$1`, not token usage,
+/// and not of a task still in flight.
+const PRUNABLE_EVENTS: &str = "FROM agent_events \
+     WHERE recorded_at < $1 \
+       AND event_type != 'token_usage' \
+       AND task_id NOT IN (SELECT id FROM tasks \
+           WHERE status IN ('assigned', 'running', 'checking', 'blocked_on_human'))";
+
+/// Count the events [`prune_events`] would delete.
+pub async fn count_prunable_events(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<i64> {
+    let row: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) {PRUNABLE_EVENTS}"))
+        .bind(sqlite_timestamp(cutoff))
+        .fetch_one(pool)
+        .await
+        .context("failed to count expired agent events")?;
+
+    Ok(row.0)
+}
+
+/// Delete agent events recorded before `cutoff`. Token usage events are
+/// kept, since budgets and reports sum them, as are all events of tasks
+/// still in flight.
+///
+/// Returns the number of events removed.
+pub async fn prune_events(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query(&format!("DELETE {PRUNABLE_EVENTS}"))
+        .bind(sqlite_timestamp(cutoff))
+        .execute(pool)
+        .await
+        .context("failed to delete expired agent events")?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,102 @@
+//! Database query functions for the `agent_leases` table.
+
+use anyhow::{Context, Result};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// The process holding a lease: a `gator dispatch` on some host.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct LeaseHolder {
+    pub pid: i64,
+    pub process_started: Option<String>,
+}
+
+/// Take one of `max` agent slots on `host` for `task_id`, held by the
+/// process `pid` started at `process_started`.
+///
+/// Returns `false` when all `max` slots are taken. A lease left for the task
+/// by an earlier lifecycle is replaced.
+pub async fn try_acquire_lease(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    host: &str,
+    pid: i64,
+    process_started: Option<&str>,
+    max: usize,
+) -> Result<bool> {
+    release_lease(pool, task_id).await?;
+    // A single statement, so concurrent dispatches cannot both take the
+    // last slot.
+    let result = sqlx::query(
+        "INSERT INTO agent_leases (task_id, host, pid, process_started) \
+         SELECT $1, $2, $3, $4 \
+         WHERE (SELECT COUNT(*) FROM agent_leases WHERE host = $2) < $5",
+    )
+    .bind(task_id)
+    .bind(host)
+    .bind(pid)
+    .bind(process_started)
+    .bind(i64::try_from(max).unwrap_or(i64::MAX))
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to acquire an agent lease for task {task_id}"))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give back the lease held for `task_id`, if any.
+pub async fn release_lease(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM agent_leases WHERE task_id = $1")
+        .bind(task_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to release the agent lease for task {task_id}"))?;
+
+    Ok(())
+}
+
+/// Count the leases held on `host`.
+pub async fn count_leases(pool: &SqlitePool, host: &str) -> Result<i64> {
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM agent_leases WHERE host = $1")
+        .bind(host)
+        .fetch_one(pool)
+        .await
+        .with_context(|| format!("failed to count agent leases on {host}"))?;
+
+    Ok(row.0)
+}
+
+/// The distinct processes holding leases on `host`.
+pub async fn list_lease_holders(pool: &SqlitePool, host: &str) -> Result<Vec<LeaseHolder>> {
+    let holders = sqlx::query_as::<_, LeaseHolder>(
+        "SELECT DISTINCT pid, process_started FROM agent_leases WHERE host = $1",
+    )
+    .bind(host)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list agent lease holders on {host}"))?;
+
+    Ok(holders)
+}
+
+/// Drop every lease on `host` held by `holder`. Returns how many were
+/// dropped.
+pub async fn release_leases_of(pool: &SqlitePool, host: &str, holder: &LeaseHolder) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM agent_leases \
+         WHERE host = $1 AND pid = $2 AND process_started IS $3",
+    )
+    .bind(host)
+    .bind(holder.pid)
+    .bind(&holder.process_started)
+    .execute(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to release the agent leases of process {}",
+            holder.pid
+        )
+    })?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,72 @@
+//! Database query functions for the `artifact_checks` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::ArtifactCheck;
+
+/// Parameters for inserting a new artifact check row.
+#[derive(Debug, Clone)]
+pub struct NewArtifactCheck {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub path: String,
+    pub check_name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+/// Insert a new artifact check row.
+pub async fn insert_artifact_check(
+    pool: &SqlitePool,
+    new: &NewArtifactCheck,
+) -> Result<ArtifactCheck> {
+    let check = sqlx::query_as::<_, ArtifactCheck>(
+        "INSERT INTO artifact_checks (id, task_id, attempt, path, check_name, passed, message) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.task_id)
+    .bind(new.attempt)
+    .bind(&new.path)
+    .bind(&new.check_name)
+    .bind(new.passed)
+    .bind(&new.message)
+    .fetch_one(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to insert {} check for artifact {:?} of task {}",
+            new.check_name, new.path, new.task_id
+        )
+    })?;
+
+    Ok(check)
+}
+
+/// Get all artifact checks for a task attempt, in insertion order.
+pub async fn get_artifact_checks(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<ArtifactCheck>> {
+    let checks = sqlx::query_as::<_, ArtifactCheck>(
+        "SELECT * FROM artifact_checks \
+         WHERE task_id = $1 AND attempt = $2 \
+         ORDER BY checked_at ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to get artifact checks for task {} attempt {}",
+            task_id, attempt
+        )
+    })?;
+
+    Ok(checks)
+}
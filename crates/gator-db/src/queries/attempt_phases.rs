@@ -0,0 +1,87 @@
+//! Database query functions for the `attempt_phases` table.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{AttemptPhase, LifecyclePhase};
+
+/// Record how long a lifecycle phase of a task attempt took. Recording the
+/// same phase of the same attempt again replaces the earlier duration.
+pub async fn record_phase(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    phase: LifecyclePhase,
+    duration: Duration,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO attempt_phases (task_id, attempt, phase, duration_ms) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (task_id, attempt, phase) DO UPDATE SET \
+           duration_ms = excluded.duration_ms, \
+           recorded_at = excluded.recorded_at",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .bind(phase)
+    .bind(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX))
+    .execute(pool)
+    .await
+    .with_context(|| {
+        format!("failed to record {phase} phase of task {task_id} attempt {attempt}")
+    })?;
+
+    Ok(())
+}
+
+/// Get the recorded phases of a task attempt, in lifecycle order.
+pub async fn list_phases_for_attempt(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<AttemptPhase>> {
+    let mut phases = sqlx::query_as::<_, AttemptPhase>(
+        "SELECT * FROM attempt_phases WHERE task_id = $1 AND attempt = $2",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get phases of task {task_id} attempt {attempt}"))?;
+
+    phases.sort_by_key(|p| p.phase);
+    Ok(phases)
+}
+
+/// Time spent in one lifecycle phase across a plan's attempts.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct PhaseTotal {
+    pub phase: LifecyclePhase,
+    /// Number of attempts that recorded this phase.
+    pub attempts: i64,
+    pub total_ms: i64,
+    pub max_ms: i64,
+}
+
+/// Per-phase totals over every attempt of every task in a plan, in
+/// lifecycle order. Phases no attempt reached are left out.
+pub async fn phase_totals_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<PhaseTotal>> {
+    let mut totals = sqlx::query_as::<_, PhaseTotal>(
+        "SELECT ap.phase, COUNT(*) AS attempts, \
+                SUM(ap.duration_ms) AS total_ms, MAX(ap.duration_ms) AS max_ms \
+         FROM attempt_phases ap \
+         JOIN tasks t ON t.id = ap.task_id \
+         WHERE t.plan_id = $1 \
+         GROUP BY ap.phase",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to sum lifecycle phases")?;
+
+    totals.sort_by_key(|t| t.phase);
+    Ok(totals)
+}
@@ -0,0 +1,68 @@
+//! Database query functions for the `attestations` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::Attestation;
+
+/// Fields of a new attestation.
+#[derive(Debug, Clone)]
+pub struct NewAttestation<'a> {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub harness: &'a str,
+    pub harness_version: Option<&'a str>,
+    pub prompt_sha256: &'a str,
+    pub base_commit: &'a str,
+    pub diff_sha256: &'a str,
+    pub path: &'a str,
+}
+
+/// Record an attestation of an attempt's change.
+pub async fn insert_attestation(
+    pool: &SqlitePool,
+    new: &NewAttestation<'_>,
+) -> Result<Attestation> {
+    let attestation = sqlx::query_as::<_, Attestation>(
+        "INSERT INTO attestations \
+         (id, task_id, attempt, harness, harness_version, prompt_sha256, base_commit, diff_sha256, path) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.task_id)
+    .bind(new.attempt)
+    .bind(new.harness)
+    .bind(new.harness_version)
+    .bind(new.prompt_sha256)
+    .bind(new.base_commit)
+    .bind(new.diff_sha256)
+    .bind(new.path)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to record attestation of task {}", new.task_id))?;
+
+    Ok(attestation)
+}
+
+/// A task's attestations, oldest first, optionally only those of one
+/// attempt.
+pub async fn list_attestations_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: Option<i32>,
+) -> Result<Vec<Attestation>> {
+    let attestations = sqlx::query_as::<_, Attestation>(
+        "SELECT * FROM attestations \
+         WHERE task_id = $1 AND ($2 IS NULL OR attempt = $2) \
+         ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list attestations of task {task_id}"))?;
+
+    Ok(attestations)
+}
@@ -0,0 +1,55 @@
+//! Database query functions for the `auto_approvals` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::AutoApproval;
+
+/// Record that `rule` approved attempt `attempt` of a task.
+pub async fn insert_auto_approval(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    rule: &str,
+    diff_lines: i64,
+    files: &[String],
+) -> Result<AutoApproval> {
+    let approval = sqlx::query_as::<_, AutoApproval>(
+        "INSERT INTO auto_approvals (id, task_id, attempt, rule, diff_lines, files) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(task_id)
+    .bind(attempt)
+    .bind(rule)
+    .bind(diff_lines)
+    .bind(sqlx::types::Json(files))
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to record auto-approval of task {task_id}"))?;
+
+    Ok(approval)
+}
+
+/// A task's auto-approvals, oldest first, optionally only that of one
+/// attempt.
+pub async fn list_auto_approvals_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: Option<i32>,
+) -> Result<Vec<AutoApproval>> {
+    let approvals = sqlx::query_as::<_, AutoApproval>(
+        "SELECT * FROM auto_approvals \
+         WHERE task_id = $1 AND ($2 IS NULL OR attempt = $2) \
+         ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list auto-approvals of task {task_id}"))?;
+
+    Ok(approvals)
+}
@@ -0,0 +1,88 @@
+//! Database query functions for the `change_log` change feed.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+use crate::models::ChangeLogEntry;
+
+/// Where the change feed stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeLogStatus {
+    pub enabled: bool,
+    /// Entries not yet pruned.
+    pub entries: i64,
+    pub first_seq: Option<i64>,
+    pub last_seq: Option<i64>,
+}
+
+/// Whether changes are being logged.
+pub async fn is_enabled(pool: &SqlitePool) -> Result<bool> {
+    let enabled: Option<bool> =
+        sqlx::query_scalar("SELECT enabled FROM change_log_settings WHERE id = 1")
+            .fetch_optional(pool)
+            .await
+            .context("failed to read change log settings")?;
+
+    Ok(enabled.unwrap_or(false))
+}
+
+/// Start or stop logging changes. Entries already logged are kept.
+pub async fn set_enabled(pool: &SqlitePool, enabled: bool) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO change_log_settings (id, enabled) VALUES (1, $1) \
+         ON CONFLICT (id) DO UPDATE SET enabled = excluded.enabled",
+    )
+    .bind(enabled)
+    .execute(pool)
+    .await
+    .context("failed to update change log settings")?;
+
+    Ok(())
+}
+
+/// Whether logging is on, and which entries are kept.
+pub async fn status(pool: &SqlitePool) -> Result<ChangeLogStatus> {
+    let enabled = is_enabled(pool).await?;
+    let (entries, first_seq, last_seq): (i64, Option<i64>, Option<i64>) =
+        sqlx::query_as("SELECT COUNT(*), MIN(seq), MAX(seq) FROM change_log")
+            .fetch_one(pool)
+            .await
+            .context("failed to summarize change log")?;
+
+    Ok(ChangeLogStatus {
+        enabled,
+        entries,
+        first_seq,
+        last_seq,
+    })
+}
+
+/// Entries after `after_seq`, oldest first, at most `limit` of them.
+pub async fn list_changes(
+    pool: &SqlitePool,
+    after_seq: i64,
+    limit: i64,
+) -> Result<Vec<ChangeLogEntry>> {
+    let entries = sqlx::query_as::<_, ChangeLogEntry>(
+        "SELECT * FROM change_log WHERE seq > $1 ORDER BY seq LIMIT $2",
+    )
+    .bind(after_seq)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list changes after {after_seq}"))?;
+
+    Ok(entries)
+}
+
+/// Delete entries up to and including `through_seq`, once a downstream job
+/// has synced them. Returns how many were deleted.
+pub async fn prune_changes(pool: &SqlitePool, through_seq: i64) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM change_log WHERE seq <= $1")
+        .bind(through_seq)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to prune changes through {through_seq}"))?;
+
+    Ok(result.rows_affected())
+}
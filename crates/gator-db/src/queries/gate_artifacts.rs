@@ -0,0 +1,95 @@
+//! Database query functions for the `gate_artifacts` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::GateArtifact;
+
+/// Parameters for recording a collected failure artifact.
+#[derive(Debug, Clone)]
+pub struct NewGateArtifact {
+    pub gate_result_id: Uuid,
+    pub path: String,
+    pub stored_path: String,
+    pub size_bytes: i64,
+}
+
+/// Record a file collected for a gate result.
+pub async fn insert_gate_artifact(
+    pool: &SqlitePool,
+    new: &NewGateArtifact,
+) -> Result<GateArtifact> {
+    let artifact = sqlx::query_as::<_, GateArtifact>(
+        "INSERT INTO gate_artifacts (id, gate_result_id, path, stored_path, size_bytes) \
+         VALUES ($1, $2, $3, $4, $5) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.gate_result_id)
+    .bind(&new.path)
+    .bind(&new.stored_path)
+    .bind(new.size_bytes)
+    .fetch_one(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to record artifact {} for gate result {}",
+            new.path, new.gate_result_id
+        )
+    })?;
+
+    Ok(artifact)
+}
+
+/// The artifacts collected for a task's gate results in `attempt`, ordered
+/// by path within each result.
+pub async fn list_gate_artifacts(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<GateArtifact>> {
+    let artifacts = sqlx::query_as::<_, GateArtifact>(
+        "SELECT ga.* FROM gate_artifacts ga \
+         JOIN gate_results gr ON gr.id = ga.gate_result_id \
+         WHERE gr.task_id = $1 AND gr.attempt = $2 \
+         ORDER BY gr.checked_at ASC, ga.path ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| {
+        format!("failed to list gate artifacts for task {task_id} attempt {attempt}")
+    })?;
+
+    Ok(artifacts)
+}
+
+/// Attach the artifacts of gate result `from` to gate result `to` as well,
+/// for a result reused from an earlier attempt. Returns how many were
+/// attached.
+pub async fn copy_gate_artifacts(pool: &SqlitePool, from: Uuid, to: Uuid) -> Result<u64> {
+    let artifacts = sqlx::query_as::<_, GateArtifact>(
+        "SELECT * FROM gate_artifacts WHERE gate_result_id = $1 ORDER BY path ASC",
+    )
+    .bind(from)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list artifacts of gate result {from}"))?;
+
+    for artifact in &artifacts {
+        insert_gate_artifact(
+            pool,
+            &NewGateArtifact {
+                gate_result_id: to,
+                path: artifact.path.clone(),
+                stored_path: artifact.stored_path.clone(),
+                size_bytes: artifact.size_bytes,
+            },
+        )
+        .await?;
+    }
+
+    Ok(artifacts.len() as u64)
+}
@@ -9,6 +9,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 use crate::models::GateResult;
+use crate::redact;
+
+use super::sqlite_timestamp;
 
 /// Gate result with the invariant name included (for display).
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -23,6 +26,10 @@ pub struct GateResultWithName {
     pub stderr: Option<String>,
     pub duration_ms: Option<i32>,
     pub checked_at: DateTime<Utc>,
+    pub timed_out: bool,
+    pub base_value: Option<f64>,
+    pub task_value: Option<f64>,
+    pub cached_from: Option<i32>,
     pub invariant_name: String,
 }
 
@@ -37,16 +44,26 @@ pub struct NewGateResult {
     pub stdout: Option<String>,
     pub stderr: Option<String>,
     pub duration_ms: Option<i32>,
+    pub timed_out: bool,
+    pub base_value: Option<f64>,
+    pub task_value: Option<f64>,
+    pub content_hash: Option<String>,
+    pub cached_from: Option<i32>,
 }
 
-/// Insert a new gate result row. Returns the inserted row with
-/// server-generated defaults (id, checked_at).
+/// Insert a new gate result row, with secrets in its output redacted.
+/// Like agent events, it takes the run and attempt IDs of the task's
+/// current attempt. Returns the inserted row with server-generated defaults
+/// (id, checked_at).
 pub async fn insert_gate_result(pool: &SqlitePool, new: &NewGateResult) -> Result<GateResult> {
     let id = Uuid::new_v4();
     let result = sqlx::query_as::<_, GateResult>(
         "INSERT INTO gate_results \
-         (id, task_id, invariant_id, attempt, passed, exit_code, stdout, stderr, duration_ms) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+         (id, task_id, invariant_id, attempt, passed, exit_code, stdout, stderr, duration_ms, timed_out, \
+          base_value, task_value, content_hash, cached_from, run_id, attempt_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, \
+                 (SELECT run_id FROM tasks WHERE id = $2 AND attempt = $4), \
+                 (SELECT attempt_id FROM tasks WHERE id = $2 AND attempt = $4)) \
          RETURNING *",
     )
     .bind(id)
@@ -55,9 +72,14 @@ pub async fn insert_gate_result(pool: &SqlitePool, new: &NewGateResult) -> Resul
     .bind(new.attempt)
     .bind(new.passed)
     .bind(new.exit_code)
-    .bind(&new.stdout)
-    .bind(&new.stderr)
+    .bind(new.stdout.as_deref().map(redact::redact))
+    .bind(new.stderr.as_deref().map(redact::redact))
     .bind(new.duration_ms)
+    .bind(new.timed_out)
+    .bind(new.base_value)
+    .bind(new.task_value)
+    .bind(&new.content_hash)
+    .bind(new.cached_from)
     .fetch_one(pool)
     .await
     .with_context(|| {
@@ -78,7 +100,8 @@ pub async fn get_latest_gate_results(
 ) -> Result<Vec<GateResultWithName>> {
     let results = sqlx::query_as::<_, GateResultWithName>(
         "SELECT gr.id, gr.task_id, gr.invariant_id, gr.attempt, gr.passed, \
-                gr.exit_code, gr.stdout, gr.stderr, gr.duration_ms, gr.checked_at, \
+                gr.exit_code, gr.stdout, gr.stderr, gr.duration_ms, gr.checked_at, gr.timed_out, \
+                gr.base_value, gr.task_value, gr.cached_from, \
                 i.name AS invariant_name \
          FROM gate_results gr \
          JOIN invariants i ON i.id = gr.invariant_id \
@@ -94,6 +117,30 @@ pub async fn get_latest_gate_results(
     Ok(results)
 }
 
+/// Get the gate results of every attempt of a task, with invariant names,
+/// ordered by attempt and then checked_at.
+pub async fn list_gate_results_with_names(
+    pool: &SqlitePool,
+    task_id: Uuid,
+) -> Result<Vec<GateResultWithName>> {
+    let results = sqlx::query_as::<_, GateResultWithName>(
+        "SELECT gr.id, gr.task_id, gr.invariant_id, gr.attempt, gr.passed, \
+                gr.exit_code, gr.stdout, gr.stderr, gr.duration_ms, gr.checked_at, gr.timed_out, \
+                gr.base_value, gr.task_value, gr.cached_from, \
+                i.name AS invariant_name \
+         FROM gate_results gr \
+         JOIN invariants i ON i.id = gr.invariant_id \
+         WHERE gr.task_id = $1 \
+         ORDER BY gr.attempt ASC, gr.checked_at ASC",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list gate results for task {task_id}"))?;
+
+    Ok(results)
+}
+
 /// Get all gate results for a given task and attempt, ordered by
 /// invariant name (via checked_at as a proxy for insertion order).
 pub async fn get_gate_results(
@@ -119,3 +166,62 @@ pub async fn get_gate_results(
 
     Ok(results)
 }
+
+/// Find the latest result of `invariant_id` for `task_id` from an attempt
+/// before `attempt` that was computed for `content_hash`.
+pub async fn find_cached_gate_result(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    invariant_id: Uuid,
+    attempt: i32,
+    content_hash: &str,
+) -> Result<Option<GateResult>> {
+    let result = sqlx::query_as::<_, GateResult>(
+        "SELECT * FROM gate_results \
+         WHERE task_id = $1 AND invariant_id = $2 AND attempt < $3 AND content_hash = $4 \
+         ORDER BY attempt DESC, checked_at DESC \
+         LIMIT 1",
+    )
+    .bind(task_id)
+    .bind(invariant_id)
+    .bind(attempt)
+    .bind(content_hash)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| {
+        format!("failed to look up cached gate result for task {task_id} invariant {invariant_id}")
+    })?;
+
+    Ok(result)
+}
+
+/// Count the gate results recorded for an invariant, across all tasks.
+pub async fn count_gate_results_for_invariant(
+    pool: &SqlitePool,
+    invariant_id: Uuid,
+) -> Result<i64> {
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM gate_results WHERE invariant_id = $1")
+        .bind(invariant_id)
+        .fetch_one(pool)
+        .await
+        .with_context(|| format!("failed to count gate results for invariant {invariant_id}"))?;
+
+    Ok(row.0)
+}
+
+/// Drop the stdout and stderr of gate results checked before `cutoff`,
+/// keeping the rows themselves: the verdict, exit code, and timing stay.
+///
+/// Returns the number of results stripped.
+pub async fn strip_gate_output(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE gate_results SET stdout = NULL, stderr = NULL \
+         WHERE checked_at < $1 AND (stdout IS NOT NULL OR stderr IS NOT NULL)",
+    )
+    .bind(sqlite_timestamp(cutoff))
+    .execute(pool)
+    .await
+    .context("failed to strip old gate output")?;
+
+    Ok(result.rows_affected())
+}
@@ -1,11 +1,26 @@
 //! Database query functions for the `invariants` table.
 
+use std::collections::BTreeMap;
+
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use super::sqlite_timestamp;
 use crate::models::{Invariant, InvariantKind, InvariantScope};
 
+/// A live task linked to a removed or disabled invariant, as reported by
+/// `gator invariant audit`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TaskInvariantLink {
+    pub plan_id: Uuid,
+    pub plan_name: String,
+    pub task_id: Uuid,
+    pub task_name: String,
+    pub invariant_name: String,
+}
+
 /// Parameters for inserting a new invariant row.
 #[derive(Debug, Clone)]
 pub struct NewInvariant<'a> {
@@ -18,6 +33,12 @@ pub struct NewInvariant<'a> {
     pub threshold: Option<f32>,
     pub scope: InvariantScope,
     pub timeout_secs: i32,
+    pub diff_base: bool,
+    pub lower_is_better: bool,
+    pub env: &'a BTreeMap<String, String>,
+    pub workdir: Option<&'a str>,
+    pub run_after: &'a [String],
+    pub artifacts: &'a [String],
 }
 
 /// Insert a new invariant. Returns the inserted row with server-generated
@@ -29,8 +50,9 @@ pub async fn insert_invariant(pool: &SqlitePool, new: &NewInvariant<'_>) -> Resu
     let id = Uuid::new_v4();
     let invariant = sqlx::query_as::<_, Invariant>(
         "INSERT INTO invariants (id, name, description, kind, command, args, \
-         expected_exit_code, threshold, scope, timeout_secs) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+         expected_exit_code, threshold, scope, timeout_secs, diff_base, lower_is_better, \
+         env, workdir, run_after, artifacts) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16) \
          RETURNING *",
     )
     .bind(id)
@@ -43,6 +65,12 @@ pub async fn insert_invariant(pool: &SqlitePool, new: &NewInvariant<'_>) -> Resu
     .bind(new.threshold)
     .bind(new.scope)
     .bind(new.timeout_secs)
+    .bind(new.diff_base)
+    .bind(new.lower_is_better)
+    .bind(sqlx::types::Json(new.env))
+    .bind(new.workdir)
+    .bind(sqlx::types::Json(new.run_after))
+    .bind(sqlx::types::Json(new.artifacts))
     .fetch_one(pool)
     .await
     .with_context(|| format!("failed to insert invariant {:?}", new.name))?;
@@ -50,6 +78,125 @@ pub async fn insert_invariant(pool: &SqlitePool, new: &NewInvariant<'_>) -> Resu
     Ok(invariant)
 }
 
+/// Overwrite every field of an invariant except its id, creation time, and
+/// soft-delete and disabled stamps. Task links and gate results are kept,
+/// and on a rename other invariants' `run_after` follow the new name.
+pub async fn update_invariant(
+    pool: &SqlitePool,
+    id: Uuid,
+    new: &NewInvariant<'_>,
+) -> Result<Invariant> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let old_name: String = sqlx::query_scalar("SELECT name FROM invariants WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .with_context(|| format!("failed to fetch invariant {id}"))?
+        .with_context(|| format!("invariant {id} not found"))?;
+
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "UPDATE invariants SET name = $2, description = $3, kind = $4, command = $5, \
+         args = $6, expected_exit_code = $7, threshold = $8, scope = $9, \
+         timeout_secs = $10, diff_base = $11, lower_is_better = $12, env = $13, \
+         workdir = $14, run_after = $15, artifacts = $16 \
+         WHERE id = $1 \
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(new.name)
+    .bind(new.description)
+    .bind(new.kind)
+    .bind(new.command)
+    .bind(sqlx::types::Json(new.args))
+    .bind(new.expected_exit_code)
+    .bind(new.threshold)
+    .bind(new.scope)
+    .bind(new.timeout_secs)
+    .bind(new.diff_base)
+    .bind(new.lower_is_better)
+    .bind(sqlx::types::Json(new.env))
+    .bind(new.workdir)
+    .bind(sqlx::types::Json(new.run_after))
+    .bind(sqlx::types::Json(new.artifacts))
+    .fetch_one(&mut *tx)
+    .await
+    .with_context(|| format!("failed to update invariant {:?}", new.name))?;
+
+    if old_name != new.name {
+        sqlx::query(
+            "UPDATE invariants SET run_after = ( \
+                 SELECT json_group_array(CASE WHEN value = $1 THEN $2 ELSE value END) \
+                 FROM json_each(invariants.run_after)) \
+             WHERE EXISTS (SELECT 1 FROM json_each(invariants.run_after) WHERE value = $1)",
+        )
+        .bind(&old_name)
+        .bind(new.name)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("failed to rename run_after references to {old_name:?}"))?;
+    }
+
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(invariant)
+}
+
+/// Disable or re-enable an invariant by name. Disabling an already
+/// disabled invariant keeps its original stamp.
+pub async fn set_invariant_disabled(
+    pool: &SqlitePool,
+    name: &str,
+    disabled: bool,
+) -> Result<Invariant> {
+    let sql = if disabled {
+        "UPDATE invariants \
+         SET disabled_at = COALESCE(disabled_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) \
+         WHERE name = $1 AND deleted_at IS NULL \
+         RETURNING *"
+    } else {
+        "UPDATE invariants SET disabled_at = NULL \
+         WHERE name = $1 AND deleted_at IS NULL \
+         RETURNING *"
+    };
+    let invariant = sqlx::query_as::<_, Invariant>(sql)
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .with_context(|| format!("failed to update invariant {name:?}"))?
+        .with_context(|| format!("invariant {name:?} not found"))?;
+
+    Ok(invariant)
+}
+
+/// Overwrite what an invariant runs: its description, kind, command, and
+/// args. Expected exit code, threshold, scope, timeout, and links to tasks
+/// are kept.
+pub async fn update_invariant_definition(
+    pool: &SqlitePool,
+    id: Uuid,
+    description: Option<&str>,
+    kind: InvariantKind,
+    command: &str,
+    args: &[String],
+) -> Result<Invariant> {
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "UPDATE invariants SET description = $2, kind = $3, command = $4, args = $5 \
+         WHERE id = $1 \
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(description)
+    .bind(kind)
+    .bind(command)
+    .bind(sqlx::types::Json(args))
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to update invariant {id}"))?
+    .with_context(|| format!("invariant {id} not found"))?;
+
+    Ok(invariant)
+}
+
 /// Fetch an invariant by its UUID.
 pub async fn get_invariant(pool: &SqlitePool, id: Uuid) -> Result<Option<Invariant>> {
     let invariant = sqlx::query_as::<_, Invariant>("SELECT * FROM invariants WHERE id = $1")
@@ -61,23 +208,28 @@ pub async fn get_invariant(pool: &SqlitePool, id: Uuid) -> Result<Option<Invaria
     Ok(invariant)
 }
 
-/// Fetch an invariant by its unique name.
+/// Fetch an invariant by its unique name. Soft-deleted invariants are not
+/// returned.
 pub async fn get_invariant_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Invariant>> {
-    let invariant = sqlx::query_as::<_, Invariant>("SELECT * FROM invariants WHERE name = $1")
-        .bind(name)
-        .fetch_optional(pool)
-        .await
-        .with_context(|| format!("failed to fetch invariant by name {:?}", name))?;
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "SELECT * FROM invariants WHERE name = $1 AND deleted_at IS NULL",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to fetch invariant by name {:?}", name))?;
 
     Ok(invariant)
 }
 
-/// List all invariants, ordered by name.
+/// List all invariants that are not soft-deleted, ordered by name.
 pub async fn list_invariants(pool: &SqlitePool) -> Result<Vec<Invariant>> {
-    let invariants = sqlx::query_as::<_, Invariant>("SELECT * FROM invariants ORDER BY name")
-        .fetch_all(pool)
-        .await
-        .context("failed to list invariants")?;
+    let invariants = sqlx::query_as::<_, Invariant>(
+        "SELECT * FROM invariants WHERE deleted_at IS NULL ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list invariants")?;
 
     Ok(invariants)
 }
@@ -132,6 +284,61 @@ pub async fn get_invariants_for_task(pool: &SqlitePool, task_id: Uuid) -> Result
     Ok(invariants)
 }
 
+/// A [`list_invariants_for_plan`] row: an invariant and a task linked to it.
+#[derive(sqlx::FromRow)]
+struct TaskInvariantRow {
+    task_id: Uuid,
+    #[sqlx(flatten)]
+    invariant: Invariant,
+}
+
+/// The invariants linked to every task of a plan, in one query: task id to
+/// its invariants, ordered by name as in [`get_invariants_for_task`]. Tasks
+/// without invariants are absent.
+pub async fn list_invariants_for_plan(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+) -> Result<std::collections::HashMap<Uuid, Vec<Invariant>>> {
+    let rows = sqlx::query_as::<_, TaskInvariantRow>(
+        "SELECT ti.task_id, i.* FROM invariants i \
+         JOIN task_invariants ti ON ti.invariant_id = i.id \
+         JOIN tasks t ON t.id = ti.task_id \
+         WHERE t.plan_id = $1 \
+         ORDER BY i.name",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get invariants for plan {plan_id}"))?;
+
+    let mut by_task: std::collections::HashMap<Uuid, Vec<Invariant>> =
+        std::collections::HashMap::new();
+    for row in rows {
+        by_task.entry(row.task_id).or_default().push(row.invariant);
+    }
+    Ok(by_task)
+}
+
+/// Get the invariants linked to a task that are not disabled, ordered by
+/// name. These are the ones the gate runs.
+pub async fn get_enabled_invariants_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+) -> Result<Vec<Invariant>> {
+    let invariants = sqlx::query_as::<_, Invariant>(
+        "SELECT i.* FROM invariants i \
+         JOIN task_invariants ti ON ti.invariant_id = i.id \
+         WHERE ti.task_id = $1 AND i.disabled_at IS NULL \
+         ORDER BY i.name",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get enabled invariants for task {task_id}"))?;
+
+    Ok(invariants)
+}
+
 /// Link a task to an invariant. Idempotent (ON CONFLICT DO NOTHING).
 pub async fn link_task_invariant(
     pool: &SqlitePool,
@@ -151,3 +358,162 @@ pub async fn link_task_invariant(
 
     Ok(())
 }
+
+/// Soft-delete an invariant by name.
+///
+/// Refuses while the invariant is linked to tasks of any plan that has not
+/// itself been deleted. The row stays restorable with [`restore_invariant`]
+/// until [`purge_deleted_invariants`] removes it; its name stays reserved
+/// meanwhile.
+pub async fn soft_delete_invariant(pool: &SqlitePool, name: &str) -> Result<Invariant> {
+    let invariant = get_invariant_by_name(pool, name)
+        .await?
+        .with_context(|| format!("invariant {name:?} not found"))?;
+
+    let linked: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM task_invariants ti \
+         JOIN tasks t ON t.id = ti.task_id \
+         WHERE ti.invariant_id = $1 AND t.deleted_at IS NULL",
+    )
+    .bind(invariant.id)
+    .fetch_one(pool)
+    .await
+    .context("failed to check invariant task links")?;
+
+    if linked.0 > 0 {
+        anyhow::bail!(
+            "cannot delete invariant {name:?}: it is linked to {} task(s)",
+            linked.0,
+        );
+    }
+
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "UPDATE invariants SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1 \
+         RETURNING *",
+    )
+    .bind(invariant.id)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to delete invariant {name:?}"))?;
+
+    Ok(invariant)
+}
+
+/// List live invariants that no live task links to, ordered by name.
+pub async fn list_unreferenced_invariants(pool: &SqlitePool) -> Result<Vec<Invariant>> {
+    let invariants = sqlx::query_as::<_, Invariant>(
+        "SELECT i.* FROM invariants i \
+         WHERE i.deleted_at IS NULL AND NOT EXISTS ( \
+             SELECT 1 FROM task_invariants ti \
+             JOIN tasks t ON t.id = ti.task_id \
+             WHERE ti.invariant_id = i.id AND t.deleted_at IS NULL) \
+         ORDER BY i.name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list unreferenced invariants")?;
+
+    Ok(invariants)
+}
+
+/// List live tasks linked to soft-deleted invariants, e.g. tasks of a plan
+/// restored after an invariant it uses was removed. Ordered by plan, task,
+/// and invariant name.
+pub async fn list_links_to_deleted_invariants(pool: &SqlitePool) -> Result<Vec<TaskInvariantLink>> {
+    let links = sqlx::query_as::<_, TaskInvariantLink>(
+        "SELECT p.id AS plan_id, p.name AS plan_name, t.id AS task_id, \
+                t.name AS task_name, i.name AS invariant_name \
+         FROM task_invariants ti \
+         JOIN tasks t ON t.id = ti.task_id \
+         JOIN plans p ON p.id = t.plan_id \
+         JOIN invariants i ON i.id = ti.invariant_id \
+         WHERE t.deleted_at IS NULL AND i.deleted_at IS NOT NULL \
+         ORDER BY p.name, t.name, i.name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list tasks linked to deleted invariants")?;
+
+    Ok(links)
+}
+
+/// List live tasks linked to disabled invariants, ordered like
+/// [`list_links_to_deleted_invariants`].
+pub async fn list_links_to_disabled_invariants(
+    pool: &SqlitePool,
+) -> Result<Vec<TaskInvariantLink>> {
+    let links = sqlx::query_as::<_, TaskInvariantLink>(
+        "SELECT p.id AS plan_id, p.name AS plan_name, t.id AS task_id, \
+                t.name AS task_name, i.name AS invariant_name \
+         FROM task_invariants ti \
+         JOIN tasks t ON t.id = ti.task_id \
+         JOIN plans p ON p.id = t.plan_id \
+         JOIN invariants i ON i.id = ti.invariant_id \
+         WHERE t.deleted_at IS NULL AND i.deleted_at IS NULL AND i.disabled_at IS NOT NULL \
+         ORDER BY p.name, t.name, i.name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list tasks linked to disabled invariants")?;
+
+    Ok(links)
+}
+
+/// Fetch a soft-deleted invariant by name.
+pub async fn get_deleted_invariant_by_name(
+    pool: &SqlitePool,
+    name: &str,
+) -> Result<Option<Invariant>> {
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "SELECT * FROM invariants WHERE name = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to fetch deleted invariant {:?}", name))?;
+
+    Ok(invariant)
+}
+
+/// Undo [`soft_delete_invariant`].
+pub async fn restore_invariant(pool: &SqlitePool, name: &str) -> Result<Invariant> {
+    let invariant = sqlx::query_as::<_, Invariant>(
+        "UPDATE invariants SET deleted_at = NULL \
+         WHERE name = $1 AND deleted_at IS NOT NULL \
+         RETURNING *",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to restore invariant {name:?}"))?
+    .with_context(|| format!("no deleted invariant named {name:?}"))?;
+
+    Ok(invariant)
+}
+
+/// List soft-deleted invariants, most recently deleted first.
+pub async fn list_deleted_invariants(pool: &SqlitePool) -> Result<Vec<Invariant>> {
+    let invariants = sqlx::query_as::<_, Invariant>(
+        "SELECT * FROM invariants WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list deleted invariants")?;
+
+    Ok(invariants)
+}
+
+/// Permanently remove invariants soft-deleted before `cutoff`.
+///
+/// Returns the number of invariants removed.
+pub async fn purge_deleted_invariants(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result =
+        sqlx::query("DELETE FROM invariants WHERE deleted_at IS NOT NULL AND deleted_at < $1")
+            .bind(sqlite_timestamp(cutoff))
+            .execute(pool)
+            .await
+            .context("failed to purge deleted invariants")?;
+
+    Ok(result.rows_affected())
+}
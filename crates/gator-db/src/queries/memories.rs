@@ -0,0 +1,64 @@
+//! Database query functions for the `memories` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::Memory;
+
+/// Parameters for inserting a new memory.
+#[derive(Debug, Clone)]
+pub struct NewMemory<'a> {
+    pub project_path: &'a str,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// `success` or `failure`.
+    pub kind: &'a str,
+    pub title: &'a str,
+    pub content: &'a str,
+}
+
+/// Insert a new memory.
+pub async fn insert_memory(pool: &SqlitePool, new: &NewMemory<'_>) -> Result<Memory> {
+    let memory = sqlx::query_as::<_, Memory>(
+        "INSERT INTO memories (id, project_path, task_id, attempt, kind, title, content) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.project_path)
+    .bind(new.task_id)
+    .bind(new.attempt)
+    .bind(new.kind)
+    .bind(new.title)
+    .bind(new.content)
+    .fetch_one(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to insert {} memory for task {}",
+            new.kind, new.task_id
+        )
+    })?;
+
+    Ok(memory)
+}
+
+/// The newest `limit` memories of a project, newest first.
+pub async fn list_memories_for_project(
+    pool: &SqlitePool,
+    project_path: &str,
+    limit: i64,
+) -> Result<Vec<Memory>> {
+    let memories = sqlx::query_as::<_, Memory>(
+        "SELECT * FROM memories WHERE project_path = $1 \
+         ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(project_path)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list memories of {project_path}"))?;
+
+    Ok(memories)
+}
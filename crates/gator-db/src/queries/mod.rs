@@ -1,5 +1,32 @@
+pub mod agent_audit;
 pub mod agent_events;
+pub mod agent_leases;
+pub mod artifact_checks;
+pub mod attempt_phases;
+pub mod attestations;
+pub mod auto_approvals;
+pub mod change_log;
+pub mod gate_artifacts;
 pub mod gate_results;
 pub mod invariants;
+pub mod memories;
+pub mod notes;
+pub mod notifications;
+pub mod plan_throttles;
 pub mod plans;
+pub mod pr_reviews;
+pub mod prompt_trims;
+pub mod review_feedback;
+pub mod schedules;
+pub mod setup_results;
+pub mod stats;
+pub mod task_revisions;
 pub mod tasks;
+pub mod triage_items;
+pub mod warmup_results;
+
+/// Format a timestamp the way SQLite's `strftime('%Y-%m-%dT%H:%M:%fZ', 'now')`
+/// does, so it compares correctly against stored TEXT columns.
+pub(crate) fn sqlite_timestamp(t: chrono::DateTime<chrono::Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
@@ -0,0 +1,56 @@
+//! Database query functions for the `notes` table.
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::Note;
+
+/// Add a note to a plan, or to one of its tasks when `task_id` is set.
+pub async fn insert_note<'e>(
+    executor: impl SqliteExecutor<'e>,
+    plan_id: Uuid,
+    task_id: Option<Uuid>,
+    body: &str,
+) -> Result<Note> {
+    let note = sqlx::query_as::<_, Note>(
+        "INSERT INTO notes (id, plan_id, task_id, body) \
+         VALUES ($1, $2, $3, $4) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(plan_id)
+    .bind(task_id)
+    .bind(body)
+    .fetch_one(executor)
+    .await
+    .with_context(|| format!("failed to insert note for plan {plan_id}"))?;
+
+    Ok(note)
+}
+
+/// All notes of a plan, including those on its tasks, oldest first.
+pub async fn list_notes_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<Note>> {
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT * FROM notes WHERE plan_id = $1 ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list notes for plan {plan_id}"))?;
+
+    Ok(notes)
+}
+
+/// Notes on a single task, oldest first.
+pub async fn list_notes_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Note>> {
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT * FROM notes WHERE task_id = $1 ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list notes for task {task_id}"))?;
+
+    Ok(notes)
+}
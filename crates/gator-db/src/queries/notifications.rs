@@ -0,0 +1,57 @@
+//! Database query functions for the `notification_dead_letters` table.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::models::NotificationDeadLetter;
+
+/// Parameters for recording an undeliverable notification.
+#[derive(Debug, Clone)]
+pub struct NewDeadLetter {
+    pub event: String,
+    pub url: String,
+    pub payload: Value,
+    pub error: String,
+    pub attempts: i32,
+}
+
+/// Record a notification that could not be delivered. Returns the inserted
+/// row with server-generated defaults (id, created_at).
+pub async fn insert_dead_letter(
+    pool: &SqlitePool,
+    new: &NewDeadLetter,
+) -> Result<NotificationDeadLetter> {
+    let row = sqlx::query_as::<_, NotificationDeadLetter>(
+        "INSERT INTO notification_dead_letters (event, url, payload, error, attempts) \
+         VALUES ($1, $2, $3, $4, $5) \
+         RETURNING *",
+    )
+    .bind(&new.event)
+    .bind(&new.url)
+    .bind(&new.payload)
+    .bind(&new.error)
+    .bind(new.attempts)
+    .fetch_one(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to record dead-lettered {} notification for {}",
+            new.event, new.url
+        )
+    })?;
+
+    Ok(row)
+}
+
+/// List dead-lettered notifications, newest first.
+pub async fn list_dead_letters(pool: &SqlitePool) -> Result<Vec<NotificationDeadLetter>> {
+    let rows = sqlx::query_as::<_, NotificationDeadLetter>(
+        "SELECT * FROM notification_dead_letters ORDER BY created_at DESC, id DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list dead-lettered notifications")?;
+
+    Ok(rows)
+}
@@ -0,0 +1,77 @@
+//! Database query functions for the `plan_throttles` table.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::sqlite_timestamp;
+use crate::models::PlanThrottle;
+
+/// Record the throttle state of a plan, replacing any earlier one.
+pub async fn set_throttle(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    agent_limit: usize,
+    max_agents: usize,
+    paused_until: Option<DateTime<Utc>>,
+    rate_limits: i64,
+    last_error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO plan_throttles \
+         (plan_id, agent_limit, max_agents, paused_until, rate_limits, last_error) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT (plan_id) DO UPDATE SET \
+             agent_limit = excluded.agent_limit, \
+             max_agents = excluded.max_agents, \
+             paused_until = excluded.paused_until, \
+             rate_limits = excluded.rate_limits, \
+             last_error = excluded.last_error, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')",
+    )
+    .bind(plan_id)
+    .bind(i64::try_from(agent_limit).unwrap_or(i64::MAX))
+    .bind(i64::try_from(max_agents).unwrap_or(i64::MAX))
+    .bind(paused_until.map(sqlite_timestamp))
+    .bind(rate_limits)
+    .bind(last_error)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to record throttle for plan {plan_id}"))?;
+
+    Ok(())
+}
+
+/// Forget a plan's throttle state, once it runs at full concurrency again.
+pub async fn clear_throttle(pool: &SqlitePool, plan_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM plan_throttles WHERE plan_id = $1")
+        .bind(plan_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to clear throttle for plan {plan_id}"))?;
+
+    Ok(())
+}
+
+/// The throttle state of a plan, if it is throttled.
+pub async fn get_throttle(pool: &SqlitePool, plan_id: Uuid) -> Result<Option<PlanThrottle>> {
+    let throttle =
+        sqlx::query_as::<_, PlanThrottle>("SELECT * FROM plan_throttles WHERE plan_id = $1")
+            .bind(plan_id)
+            .fetch_optional(pool)
+            .await
+            .with_context(|| format!("failed to get throttle for plan {plan_id}"))?;
+
+    Ok(throttle)
+}
+
+/// The throttle state of every throttled plan.
+pub async fn list_throttles(pool: &SqlitePool) -> Result<Vec<PlanThrottle>> {
+    let throttles = sqlx::query_as::<_, PlanThrottle>("SELECT * FROM plan_throttles")
+        .fetch_all(pool)
+        .await
+        .context("failed to list plan throttles")?;
+
+    Ok(throttles)
+}
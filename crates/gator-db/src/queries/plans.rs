@@ -1,10 +1,12 @@
 //! Database query functions for the `plans` table.
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::models::{Plan, PlanStatus};
+use super::sqlite_timestamp;
+use crate::models::{Plan, PlanFailureReason, PlanStatus};
 
 /// Insert a new plan row. Returns the inserted plan with server-generated
 /// defaults (id, created_at, status).
@@ -40,23 +42,28 @@ pub async fn insert_plan(
     Ok(plan)
 }
 
-/// Fetch a plan by its ID.
+/// Fetch a plan by its ID. Soft-deleted plans are not returned.
 pub async fn get_plan(pool: &SqlitePool, id: Uuid) -> Result<Option<Plan>> {
-    let plan = sqlx::query_as::<_, Plan>("SELECT * FROM plans WHERE id = $1")
-        .bind(id)
-        .fetch_optional(pool)
-        .await
-        .context("failed to fetch plan")?;
+    let plan =
+        sqlx::query_as::<_, Plan>("SELECT * FROM plans WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .context("failed to fetch plan")?;
 
     Ok(plan)
 }
 
-/// List all plans, ordered by creation time (newest first).
+/// List all plans that are neither soft-deleted nor archived, ordered by
+/// creation time (newest first).
 pub async fn list_plans(pool: &SqlitePool) -> Result<Vec<Plan>> {
-    let plans = sqlx::query_as::<_, Plan>("SELECT * FROM plans ORDER BY created_at DESC")
-        .fetch_all(pool)
-        .await
-        .context("failed to list plans")?;
+    let plans = sqlx::query_as::<_, Plan>(
+        "SELECT * FROM plans WHERE deleted_at IS NULL AND archived_at IS NULL \
+         ORDER BY created_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list plans")?;
 
     Ok(plans)
 }
@@ -66,12 +73,18 @@ pub async fn list_plans(pool: &SqlitePool) -> Result<Vec<Plan>> {
 /// Conditionally sets timestamps:
 /// - `approved_at` when transitioning to `approved` (if not already set)
 /// - `completed_at` when transitioning to `completed` or `failed` (if not already set)
+///
+/// Also clears `interrupted_at` when the plan starts running again, and
+/// `failure_reason` whenever it leaves `failed`. Use [`fail_plan`] to record
+/// why a plan failed.
 pub async fn update_plan_status(pool: &SqlitePool, id: Uuid, status: PlanStatus) -> Result<()> {
     let result = sqlx::query(
         "UPDATE plans SET \
            status = $1, \
            approved_at = CASE WHEN $1 = 'approved' THEN COALESCE(approved_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) ELSE approved_at END, \
-           completed_at = CASE WHEN $1 IN ('completed', 'failed') THEN COALESCE(completed_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) ELSE completed_at END \
+           completed_at = CASE WHEN $1 IN ('completed', 'failed') THEN COALESCE(completed_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) ELSE completed_at END, \
+           interrupted_at = CASE WHEN $1 = 'running' THEN NULL ELSE interrupted_at END, \
+           failure_reason = CASE WHEN $1 = 'failed' THEN failure_reason ELSE NULL END \
          WHERE id = $2",
     )
     .bind(status)
@@ -87,6 +100,29 @@ pub async fn update_plan_status(pool: &SqlitePool, id: Uuid, status: PlanStatus)
     Ok(())
 }
 
+/// Mark a plan `failed` for `reason`, setting `completed_at` (if not
+/// already set).
+pub async fn fail_plan(pool: &SqlitePool, id: Uuid, reason: PlanFailureReason) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE plans SET \
+           status = 'failed', \
+           failure_reason = $1, \
+           completed_at = COALESCE(completed_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) \
+         WHERE id = $2",
+    )
+    .bind(reason)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("failed to mark plan failed")?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("plan {id} not found");
+    }
+
+    Ok(())
+}
+
 /// Transition a plan from `draft` to `approved`, setting `approved_at` to now.
 ///
 /// Returns the updated plan. Fails if the plan is not found or is not in
@@ -121,12 +157,12 @@ pub async fn approve_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
 
 /// Reset a failed plan back to `approved` so it can be re-dispatched.
 ///
-/// Clears `completed_at`. Fails if the plan is not found or is not in
-/// `failed` status.
+/// Clears `completed_at` and `failure_reason`. Fails if the plan is not
+/// found or is not in `failed` status.
 pub async fn reset_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
     let plan = sqlx::query_as::<_, Plan>(
         "UPDATE plans \
-         SET status = 'approved', completed_at = NULL \
+         SET status = 'approved', completed_at = NULL, failure_reason = NULL \
          WHERE id = $1 AND status = 'failed' \
          RETURNING *",
     )
@@ -151,7 +187,43 @@ pub async fn reset_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
     }
 }
 
-/// Count tasks in a plan that have zero linked invariants.
+/// Resume a plan that stopped on its token budget: back to `approved`,
+/// with its budget raised to `token_budget`. Tasks keep their state, so the
+/// next dispatch continues where the plan stopped.
+///
+/// Fails if the plan is not found or did not fail on its budget.
+pub async fn resume_plan(pool: &SqlitePool, id: Uuid, token_budget: i64) -> Result<Plan> {
+    let plan = sqlx::query_as::<_, Plan>(
+        "UPDATE plans \
+         SET status = 'approved', completed_at = NULL, failure_reason = NULL, \
+             token_budget = $2 \
+         WHERE id = $1 AND status = 'failed' AND failure_reason = 'budget_exceeded' \
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(token_budget)
+    .fetch_optional(pool)
+    .await
+    .context("failed to resume plan")?;
+
+    match plan {
+        Some(p) => Ok(p),
+        None => match get_plan(pool, id).await? {
+            None => anyhow::bail!("plan {id} not found"),
+            Some(p) if p.status == PlanStatus::Failed => anyhow::bail!(
+                "plan {id} cannot be resumed: it did not stop on its token budget \
+                 (use `gator plan reset` to retry it)"
+            ),
+            Some(p) => anyhow::bail!(
+                "plan {id} cannot be resumed: current status is {:?} (must have failed on its token budget)",
+                p.status.to_string()
+            ),
+        },
+    }
+}
+
+/// Count tasks in a plan that have zero linked invariants. Artifact tasks
+/// are exempt: their built-in artifact checks act as the gate.
 pub async fn count_tasks_without_invariants(
     pool: &SqlitePool,
     plan_id: Uuid,
@@ -159,6 +231,7 @@ pub async fn count_tasks_without_invariants(
     let rows: Vec<(String,)> = sqlx::query_as(
         "SELECT t.name FROM tasks t \
          WHERE t.plan_id = $1 \
+           AND t.kind != 'artifact' \
            AND NOT EXISTS ( \
                SELECT 1 FROM task_invariants ti WHERE ti.task_id = t.id \
            ) \
@@ -171,3 +244,197 @@ pub async fn count_tasks_without_invariants(
 
     Ok(rows.into_iter().map(|(name,)| name).collect())
 }
+
+/// Mark a running plan as interrupted: it goes back to `approved`, with
+/// `interrupted_at` stamped, so the next dispatch resumes it. Tasks keep
+/// their state; any left assigned or running are reset when it resumes.
+pub async fn interrupt_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
+    sqlx::query_as::<_, Plan>(
+        "UPDATE plans \
+         SET status = 'approved', interrupted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1 AND status = 'running' \
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .context("failed to interrupt plan")?
+    .with_context(|| format!("no running plan with id {id}"))
+}
+
+/// Record the orchestrator now running a plan: its host, process ID, and
+/// user, stamped with the current time. Each dispatch overwrites the last.
+pub async fn record_dispatcher(
+    pool: &SqlitePool,
+    id: Uuid,
+    host: &str,
+    pid: u32,
+    user: &str,
+) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE plans SET \
+           dispatched_host = $1, \
+           dispatched_pid = $2, \
+           dispatched_user = $3, \
+           dispatched_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $4",
+    )
+    .bind(host)
+    .bind(i64::from(pid))
+    .bind(user)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("failed to record plan dispatcher")?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("plan {id} not found");
+    }
+
+    Ok(())
+}
+
+/// Archive a plan by stamping `archived_at`: it stays intact (and can be
+/// shown, exported, or merged by ID) but drops out of [`list_plans`].
+/// Running plans cannot be archived.
+pub async fn archive_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
+    let plan = sqlx::query_as::<_, Plan>(
+        "UPDATE plans SET archived_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1 AND deleted_at IS NULL AND archived_at IS NULL AND status != 'running' \
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .context("failed to archive plan")?;
+
+    match plan {
+        Some(plan) => Ok(plan),
+        None => match get_plan(pool, id).await? {
+            None => anyhow::bail!("plan {id} not found"),
+            Some(p) if p.archived_at.is_some() => anyhow::bail!("plan {id} is already archived"),
+            Some(_) => anyhow::bail!("plan {id} is running; stop it before archiving"),
+        },
+    }
+}
+
+/// Undo [`archive_plan`].
+pub async fn unarchive_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
+    sqlx::query_as::<_, Plan>(
+        "UPDATE plans SET archived_at = NULL \
+         WHERE id = $1 AND deleted_at IS NULL AND archived_at IS NOT NULL \
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .context("failed to unarchive plan")?
+    .with_context(|| format!("no archived plan with id {id}"))
+}
+
+/// List archived plans that are not soft-deleted, most recently archived
+/// first.
+pub async fn list_archived_plans(pool: &SqlitePool) -> Result<Vec<Plan>> {
+    let plans = sqlx::query_as::<_, Plan>(
+        "SELECT * FROM plans WHERE deleted_at IS NULL AND archived_at IS NOT NULL \
+         ORDER BY archived_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list archived plans")?;
+
+    Ok(plans)
+}
+
+/// Soft-delete a plan and its tasks by stamping `deleted_at`.
+///
+/// The rows stay in the database (and can be brought back with
+/// [`restore_plan`]) until [`purge_deleted_plans`] removes them. Running
+/// plans cannot be deleted.
+pub async fn soft_delete_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let plan = sqlx::query_as::<_, Plan>(
+        "UPDATE plans SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1 AND deleted_at IS NULL AND status != 'running' \
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("failed to delete plan")?;
+
+    let Some(plan) = plan else {
+        drop(tx);
+        // Distinguish between "not found" and "running".
+        match get_plan(pool, id).await? {
+            None => anyhow::bail!("plan {id} not found"),
+            Some(_) => anyhow::bail!("plan {id} is running; stop it before deleting"),
+        }
+    };
+
+    sqlx::query(
+        "UPDATE tasks SET deleted_at = (SELECT deleted_at FROM plans WHERE id = $1) \
+         WHERE plan_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("failed to delete plan tasks")?;
+
+    tx.commit()
+        .await
+        .context("failed to commit plan deletion")?;
+    Ok(plan)
+}
+
+/// Undo [`soft_delete_plan`]: clear `deleted_at` on the plan and its tasks.
+pub async fn restore_plan(pool: &SqlitePool, id: Uuid) -> Result<Plan> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let plan = sqlx::query_as::<_, Plan>(
+        "UPDATE plans SET deleted_at = NULL \
+         WHERE id = $1 AND deleted_at IS NOT NULL \
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("failed to restore plan")?
+    .with_context(|| format!("no deleted plan with id {id}"))?;
+
+    sqlx::query("UPDATE tasks SET deleted_at = NULL WHERE plan_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("failed to restore plan tasks")?;
+
+    tx.commit().await.context("failed to commit plan restore")?;
+    Ok(plan)
+}
+
+/// List soft-deleted plans, most recently deleted first.
+pub async fn list_deleted_plans(pool: &SqlitePool) -> Result<Vec<Plan>> {
+    let plans = sqlx::query_as::<_, Plan>(
+        "SELECT * FROM plans WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list deleted plans")?;
+
+    Ok(plans)
+}
+
+/// Permanently remove plans soft-deleted before `cutoff`. Tasks, events,
+/// and gate results go with them via `ON DELETE CASCADE`.
+///
+/// Returns the number of plans removed.
+pub async fn purge_deleted_plans(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM plans WHERE deleted_at IS NOT NULL AND deleted_at < $1")
+        .bind(sqlite_timestamp(cutoff))
+        .execute(pool)
+        .await
+        .context("failed to purge deleted plans")?;
+
+    Ok(result.rows_affected())
+}
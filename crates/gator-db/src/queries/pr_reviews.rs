@@ -0,0 +1,112 @@
+//! Database query functions for the `pr_reviews` table.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::PrReview;
+
+/// Record head `head_sha` of pull request `pr_number` as queued for review
+/// by `task_id`.
+pub async fn insert_pr_review<'e>(
+    executor: impl SqliteExecutor<'e>,
+    repo: &str,
+    pr_number: i64,
+    head_sha: &str,
+    plan_id: Uuid,
+    task_id: Uuid,
+    changed_files: &[String],
+) -> Result<PrReview> {
+    let review = sqlx::query_as::<_, PrReview>(
+        "INSERT INTO pr_reviews \
+             (id, repo, pr_number, head_sha, plan_id, task_id, changed_files) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(repo)
+    .bind(pr_number)
+    .bind(head_sha)
+    .bind(plan_id)
+    .bind(task_id)
+    .bind(sqlx::types::Json(changed_files))
+    .fetch_one(executor)
+    .await
+    .with_context(|| format!("failed to queue {repo}#{pr_number} for review"))?;
+
+    Ok(review)
+}
+
+/// `(pr_number, head_sha)` pairs of `repo` that have ever been queued, in
+/// any status.
+pub async fn reviewed_heads(pool: &SqlitePool, repo: &str) -> Result<HashSet<(i64, String)>> {
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT pr_number, head_sha FROM pr_reviews WHERE repo = $1")
+            .bind(repo)
+            .fetch_all(pool)
+            .await
+            .with_context(|| format!("failed to list reviewed PRs of {repo}"))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// The review queued for `task_id`, if the task is a PR review.
+pub async fn get_pr_review_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<Option<PrReview>> {
+    let review = sqlx::query_as::<_, PrReview>("SELECT * FROM pr_reviews WHERE task_id = $1")
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await
+        .with_context(|| format!("failed to get PR review for task {task_id}"))?;
+
+    Ok(review)
+}
+
+/// PR reviews, newest first, optionally filtered by repository and status.
+pub async fn list_pr_reviews(
+    pool: &SqlitePool,
+    repo: Option<&str>,
+    status: Option<&str>,
+) -> Result<Vec<PrReview>> {
+    let reviews = sqlx::query_as::<_, PrReview>(
+        "SELECT * FROM pr_reviews \
+         WHERE ($1 IS NULL OR repo = $1) AND ($2 IS NULL OR status = $2) \
+         ORDER BY created_at DESC, pr_number DESC",
+    )
+    .bind(repo)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .context("failed to list PR reviews")?;
+
+    Ok(reviews)
+}
+
+/// Mark a PR review as posted.
+pub async fn mark_pr_review_posted(pool: &SqlitePool, id: Uuid) -> Result<()> {
+    sqlx::query(
+        "UPDATE pr_reviews \
+         SET status = 'posted', error = NULL, \
+             posted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to mark PR review {id} posted"))?;
+
+    Ok(())
+}
+
+/// Mark a PR review as failed with `error`.
+pub async fn mark_pr_review_failed(pool: &SqlitePool, id: Uuid, error: &str) -> Result<()> {
+    sqlx::query("UPDATE pr_reviews SET status = 'failed', error = $2 WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to mark PR review {id} failed"))?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Database query functions for the `prompt_trims` table.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::PromptTrim;
+
+/// Record that `section` was dropped from the prompt of a task attempt.
+/// Recording the same section of the same attempt again replaces the
+/// earlier estimate.
+pub async fn record_trim(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    section: &str,
+    estimated_tokens: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO prompt_trims (task_id, attempt, section, estimated_tokens) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (task_id, attempt, section) DO UPDATE SET \
+           estimated_tokens = excluded.estimated_tokens, \
+           recorded_at = excluded.recorded_at",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .bind(section)
+    .bind(estimated_tokens)
+    .execute(pool)
+    .await
+    .with_context(|| {
+        format!("failed to record trimmed {section} section of task {task_id} attempt {attempt}")
+    })?;
+
+    Ok(())
+}
+
+/// Get the sections trimmed from a task attempt's prompt, in trim order.
+pub async fn list_trims_for_attempt(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<PromptTrim>> {
+    sqlx::query_as::<_, PromptTrim>(
+        "SELECT * FROM prompt_trims WHERE task_id = $1 AND attempt = $2 \
+         ORDER BY recorded_at, rowid",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get prompt trims of task {task_id} attempt {attempt}"))
+}
+
+/// Get every trimmed section of every attempt of a plan's tasks, ordered
+/// by task, attempt, and trim order.
+pub async fn list_trims_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<PromptTrim>> {
+    sqlx::query_as::<_, PromptTrim>(
+        "SELECT pt.* FROM prompt_trims pt \
+         JOIN tasks t ON t.id = pt.task_id \
+         WHERE t.plan_id = $1 \
+         ORDER BY t.name, pt.attempt, pt.recorded_at, pt.rowid",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to get prompt trims of plan {plan_id}"))
+}
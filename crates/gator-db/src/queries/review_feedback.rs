@@ -0,0 +1,60 @@
+//! Database query functions for the `review_feedback` table.
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::ReviewFeedback;
+
+/// Record a reviewer's rejection reason for a task attempt.
+pub async fn insert_review_feedback<'e>(
+    executor: impl SqliteExecutor<'e>,
+    task_id: Uuid,
+    attempt: i32,
+    reason: &str,
+) -> Result<ReviewFeedback> {
+    let feedback = sqlx::query_as::<_, ReviewFeedback>(
+        "INSERT INTO review_feedback (id, task_id, attempt, reason) \
+         VALUES ($1, $2, $3, $4) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(task_id)
+    .bind(attempt)
+    .bind(reason)
+    .fetch_one(executor)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to insert review feedback for task {} attempt {}",
+            task_id, attempt
+        )
+    })?;
+
+    Ok(feedback)
+}
+
+/// Get the review feedback for a task attempt, oldest first.
+pub async fn get_review_feedback(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<ReviewFeedback>> {
+    let feedback = sqlx::query_as::<_, ReviewFeedback>(
+        "SELECT * FROM review_feedback \
+         WHERE task_id = $1 AND attempt = $2 \
+         ORDER BY created_at ASC",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to get review feedback for task {} attempt {}",
+            task_id, attempt
+        )
+    })?;
+
+    Ok(feedback)
+}
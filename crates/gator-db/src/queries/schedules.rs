@@ -0,0 +1,122 @@
+//! Database query functions for the `schedules` table.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::sqlite_timestamp;
+use crate::models::Schedule;
+
+/// Schedule `plan_id` to be cloned and dispatched on `cron`, first at
+/// `next_run_at`.
+pub async fn insert_schedule(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    cron: &str,
+    max_agents: usize,
+    timeout_secs: u64,
+    next_run_at: DateTime<Utc>,
+) -> Result<Schedule> {
+    let schedule = sqlx::query_as::<_, Schedule>(
+        "INSERT INTO schedules (id, plan_id, cron, max_agents, timeout_secs, next_run_at) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(plan_id)
+    .bind(cron)
+    .bind(i64::try_from(max_agents).unwrap_or(i64::MAX))
+    .bind(i64::try_from(timeout_secs).unwrap_or(i64::MAX))
+    .bind(sqlite_timestamp(next_run_at))
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to schedule plan {plan_id}"))?;
+
+    Ok(schedule)
+}
+
+/// Get a schedule by ID.
+pub async fn get_schedule(pool: &SqlitePool, id: Uuid) -> Result<Option<Schedule>> {
+    let schedule = sqlx::query_as::<_, Schedule>("SELECT * FROM schedules WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .with_context(|| format!("failed to get schedule {id}"))?;
+
+    Ok(schedule)
+}
+
+/// All schedules, soonest due first.
+pub async fn list_schedules(pool: &SqlitePool) -> Result<Vec<Schedule>> {
+    let schedules =
+        sqlx::query_as::<_, Schedule>("SELECT * FROM schedules ORDER BY next_run_at, created_at")
+            .fetch_all(pool)
+            .await
+            .context("failed to list schedules")?;
+
+    Ok(schedules)
+}
+
+/// Schedules due at `now`, soonest due first.
+pub async fn list_due_schedules(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<Schedule>> {
+    let schedules = sqlx::query_as::<_, Schedule>(
+        "SELECT * FROM schedules WHERE next_run_at <= $1 ORDER BY next_run_at, created_at",
+    )
+    .bind(sqlite_timestamp(now))
+    .fetch_all(pool)
+    .await
+    .context("failed to list due schedules")?;
+
+    Ok(schedules)
+}
+
+/// Record that a schedule ran now and is next due at `next_run_at`.
+/// `last_plan_id` is the clone it created, if it got that far; `last_error`
+/// says why the run failed, if it did.
+pub async fn record_schedule_run(
+    pool: &SqlitePool,
+    id: Uuid,
+    next_run_at: DateTime<Utc>,
+    last_plan_id: Option<Uuid>,
+    last_error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE schedules \
+         SET next_run_at = $2, last_plan_id = $3, last_error = $4, \
+             last_run_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(sqlite_timestamp(next_run_at))
+    .bind(last_plan_id)
+    .bind(last_error)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to record run of schedule {id}"))?;
+
+    Ok(())
+}
+
+/// Record why the dispatch of a schedule's last run failed.
+pub async fn set_schedule_error(pool: &SqlitePool, id: Uuid, error: Option<&str>) -> Result<()> {
+    sqlx::query("UPDATE schedules SET last_error = $2 WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to record error of schedule {id}"))?;
+
+    Ok(())
+}
+
+/// Delete a schedule. Returns whether it existed.
+pub async fn delete_schedule(pool: &SqlitePool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM schedules WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to delete schedule {id}"))?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,105 @@
+//! Database query functions for the `setup_results` table.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::SetupResult;
+use crate::redact;
+
+use super::sqlite_timestamp;
+
+/// Parameters for inserting a new setup result row.
+#[derive(Debug, Clone)]
+pub struct NewSetupResult {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub position: i32,
+    pub command: String,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// Insert a setup result, with secrets in its output redacted. Like gate
+/// results, it takes the run and attempt IDs of the task's current attempt.
+pub async fn insert_setup_result(pool: &SqlitePool, new: &NewSetupResult) -> Result<SetupResult> {
+    let result = sqlx::query_as::<_, SetupResult>(
+        "INSERT INTO setup_results \
+         (id, task_id, attempt, position, command, passed, exit_code, timed_out, stdout, stderr, \
+          duration_ms, run_id, attempt_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, \
+                 (SELECT run_id FROM tasks WHERE id = $2 AND attempt = $3), \
+                 (SELECT attempt_id FROM tasks WHERE id = $2 AND attempt = $3)) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.task_id)
+    .bind(new.attempt)
+    .bind(new.position)
+    .bind(&new.command)
+    .bind(new.passed)
+    .bind(new.exit_code)
+    .bind(new.timed_out)
+    .bind(new.stdout.as_deref().map(redact::redact))
+    .bind(new.stderr.as_deref().map(redact::redact))
+    .bind(new.duration_ms)
+    .fetch_one(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to insert setup result for task {} attempt {}",
+            new.task_id, new.attempt
+        )
+    })?;
+
+    Ok(result)
+}
+
+/// List the setup results of a task attempt, in the order the commands
+/// ran. Empty if setup did not run.
+pub async fn list_setup_results(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+) -> Result<Vec<SetupResult>> {
+    let results = sqlx::query_as::<_, SetupResult>(
+        "SELECT * FROM setup_results \
+         WHERE task_id = $1 AND attempt = $2 \
+         ORDER BY ran_at, position",
+    )
+    .bind(task_id)
+    .bind(attempt)
+    .fetch_all(pool)
+    .await
+    .with_context(|| {
+        format!(
+            "failed to list setup results for task {} attempt {}",
+            task_id, attempt
+        )
+    })?;
+
+    Ok(results)
+}
+
+/// Drop the stdout and stderr of setup results recorded before `cutoff`,
+/// keeping the rows, as [`super::gate_results::strip_gate_output`] does for
+/// gate results.
+///
+/// Returns the number of results stripped.
+pub async fn strip_setup_output(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE setup_results SET stdout = NULL, stderr = NULL \
+         WHERE ran_at < $1 AND (stdout IS NOT NULL OR stderr IS NOT NULL)",
+    )
+    .bind(sqlite_timestamp(cutoff))
+    .execute(pool)
+    .await
+    .context("failed to strip old setup output")?;
+
+    Ok(result.rows_affected())
+}
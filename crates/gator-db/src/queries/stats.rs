@@ -0,0 +1,226 @@
+//! Aggregate queries across all plans, for `gator stats`.
+//!
+//! Deleted plans are left out; archived ones still count. Each query takes
+//! an optional `since`: only tasks finished, tokens reported, and gates
+//! checked from then on are counted.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use super::sqlite_timestamp;
+
+/// Stored timestamps sort as text, so the epoch stands in for "no cutoff".
+fn cutoff(since: Option<DateTime<Utc>>) -> String {
+    sqlite_timestamp(since.unwrap_or(DateTime::UNIX_EPOCH))
+}
+
+/// How the finished tasks of one harness turned out.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct HarnessStats {
+    pub harness: String,
+    pub passed: i64,
+    /// `failed` or `escalated`.
+    pub failed: i64,
+}
+
+impl HarnessStats {
+    /// Passed tasks over finished ones.
+    pub fn success_rate(&self) -> f64 {
+        let finished = self.passed + self.failed;
+        if finished == 0 {
+            0.0
+        } else {
+            self.passed as f64 / finished as f64
+        }
+    }
+}
+
+/// Passed and failed tasks per assigned harness, most used first.
+/// Cancelled tasks never got a verdict and are not counted.
+pub async fn harness_stats(
+    pool: &SqlitePool,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<HarnessStats>> {
+    let stats = sqlx::query_as::<_, HarnessStats>(
+        "SELECT COALESCE(t.assigned_harness, p.default_harness) AS harness, \
+                SUM(t.status = 'passed') AS passed, \
+                SUM(t.status IN ('failed', 'escalated')) AS failed \
+         FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.deleted_at IS NULL \
+           AND t.status IN ('passed', 'failed', 'escalated') \
+           AND t.completed_at >= $1 \
+         GROUP BY harness \
+         ORDER BY COUNT(*) DESC, harness",
+    )
+    .bind(cutoff(since))
+    .fetch_all(pool)
+    .await
+    .context("failed to aggregate task outcomes by harness")?;
+
+    Ok(stats)
+}
+
+/// How many attempts the finished tasks of one scope level took.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct ScopeStats {
+    /// `narrow`, `medium`, or `broad`.
+    pub scope_level: String,
+    pub tasks: i64,
+    pub avg_attempts: f64,
+    pub max_attempts: i64,
+}
+
+/// Attempts per finished task (passed, failed, or escalated), by scope
+/// level, narrowest first.
+pub async fn scope_stats(
+    pool: &SqlitePool,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<ScopeStats>> {
+    let stats = sqlx::query_as::<_, ScopeStats>(
+        "SELECT t.scope_level, COUNT(*) AS tasks, \
+                AVG(t.attempt + 1.0) AS avg_attempts, \
+                MAX(t.attempt + 1) AS max_attempts \
+         FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.deleted_at IS NULL \
+           AND t.status IN ('passed', 'failed', 'escalated') \
+           AND t.completed_at >= $1 \
+         GROUP BY t.scope_level \
+         ORDER BY CASE t.scope_level \
+             WHEN 'narrow' THEN 0 WHEN 'medium' THEN 1 WHEN 'broad' THEN 2 ELSE 3 END",
+    )
+    .bind(cutoff(since))
+    .fetch_all(pool)
+    .await
+    .context("failed to aggregate attempts by scope")?;
+
+    Ok(stats)
+}
+
+/// The span of time token usage is summed over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatsPeriod {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl StatsPeriod {
+    /// SQLite `strftime` layout naming the period a timestamp falls in.
+    fn layout(self) -> &'static str {
+        match self {
+            Self::Day => "%Y-%m-%d",
+            Self::Week => "%Y-W%W",
+            Self::Month => "%Y-%m",
+        }
+    }
+}
+
+impl fmt::Display for StatsPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        })
+    }
+}
+
+impl FromStr for StatsPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            other => Err(format!("invalid period {other:?}: use day, week, or month")),
+        }
+    }
+}
+
+/// Tokens reported by agents in one period.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct TokenPeriod {
+    /// e.g. `2026-03-14`, `2026-W11`, or `2026-03`.
+    pub period: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Token usage summed per `period`, oldest first.
+pub async fn token_usage_by_period(
+    pool: &SqlitePool,
+    period: StatsPeriod,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<TokenPeriod>> {
+    let usage = sqlx::query_as::<_, TokenPeriod>(
+        "SELECT strftime($1, ae.recorded_at) AS period, \
+                COALESCE(SUM(CAST(json_extract(ae.payload, '$.input_tokens') AS INTEGER)), 0) AS input_tokens, \
+                COALESCE(SUM(CAST(json_extract(ae.payload, '$.output_tokens') AS INTEGER)), 0) AS output_tokens \
+         FROM agent_events ae \
+         JOIN tasks t ON t.id = ae.task_id \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.deleted_at IS NULL \
+           AND ae.event_type = 'token_usage' \
+           AND ae.recorded_at >= $2 \
+         GROUP BY period \
+         ORDER BY period",
+    )
+    .bind(period.layout())
+    .bind(cutoff(since))
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to sum token usage by {period}"))?;
+
+    Ok(usage)
+}
+
+/// How often one invariant failed.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct InvariantFailures {
+    pub name: String,
+    pub failures: i64,
+    pub checks: i64,
+    /// Distinct tasks it failed at least once for.
+    pub tasks: i64,
+}
+
+/// The `limit` invariants that failed most often, with how often each ran.
+/// Results reused from an earlier attempt's cache are not counted again.
+pub async fn failing_invariants(
+    pool: &SqlitePool,
+    since: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<InvariantFailures>> {
+    let failures = sqlx::query_as::<_, InvariantFailures>(
+        "SELECT i.name, \
+                SUM(NOT gr.passed) AS failures, \
+                COUNT(*) AS checks, \
+                COUNT(DISTINCT CASE WHEN NOT gr.passed THEN gr.task_id END) AS tasks \
+         FROM gate_results gr \
+         JOIN invariants i ON i.id = gr.invariant_id \
+         JOIN tasks t ON t.id = gr.task_id \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.deleted_at IS NULL \
+           AND gr.cached_from IS NULL \
+           AND gr.checked_at >= $1 \
+         GROUP BY i.id \
+         HAVING failures > 0 \
+         ORDER BY failures DESC, i.name \
+         LIMIT $2",
+    )
+    .bind(cutoff(since))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("failed to rank failing invariants")?;
+
+    Ok(failures)
+}
@@ -0,0 +1,52 @@
+//! Database query functions for the `task_revisions` table.
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::TaskRevision;
+
+/// Replace a task's description, recording the old one as a revision that
+/// takes effect from `attempt`.
+pub async fn revise_task_description(
+    conn: &mut SqliteConnection,
+    task_id: Uuid,
+    attempt: i32,
+    description: &str,
+) -> Result<TaskRevision> {
+    let revision = sqlx::query_as::<_, TaskRevision>(
+        "INSERT INTO task_revisions (id, task_id, attempt, previous, description) \
+         SELECT $1, id, $3, description, $4 FROM tasks WHERE id = $2 \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(task_id)
+    .bind(attempt)
+    .bind(description)
+    .fetch_optional(&mut *conn)
+    .await
+    .with_context(|| format!("failed to record revision of task {task_id}"))?
+    .with_context(|| format!("task {task_id} not found"))?;
+
+    sqlx::query("UPDATE tasks SET description = $1 WHERE id = $2")
+        .bind(description)
+        .bind(task_id)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("failed to update description of task {task_id}"))?;
+
+    Ok(revision)
+}
+
+/// A task's description revisions, oldest first.
+pub async fn list_task_revisions(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<TaskRevision>> {
+    let revisions = sqlx::query_as::<_, TaskRevision>(
+        "SELECT * FROM task_revisions WHERE task_id = $1 ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("failed to list revisions of task {task_id}"))?;
+
+    Ok(revisions)
+}
@@ -1,11 +1,17 @@
 //! Database query functions for the `tasks`, `task_dependencies`, and
 //! `task_invariants` tables.
 
+use std::collections::BTreeMap;
+
 use anyhow::{Context, Result};
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
-use crate::models::{Task, TaskStatus};
+use crate::models::{DependencyKind, Task, TaskStatus};
+
+/// Most bind parameters put in one statement by the bulk inserts, below
+/// SQLite's historical limit of 999 so any build accepts them.
+const MAX_BULK_PARAMS: usize = 900;
 
 /// Insert a new task row. Returns the inserted task with server-generated
 /// defaults (id, created_at, status, attempt).
@@ -44,11 +50,122 @@ pub async fn insert_task(
     Ok(task)
 }
 
+/// A task row for [`insert_tasks`]. The caller picks the id, so dependency
+/// edges and invariant links can be built before anything is inserted.
+#[derive(Debug, Clone)]
+pub struct NewTask<'a> {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub scope_level: &'a str,
+    pub gate_policy: &'a str,
+    pub retry_max: i32,
+    pub requested_harness: Option<&'a str>,
+    pub issue: Option<&'a str>,
+    pub services: &'a [String],
+    pub kind: &'a str,
+    pub artifacts: &'a [String],
+    pub paths: &'a [String],
+    pub timeout_secs: Option<i64>,
+    pub gate_script: Option<&'a str>,
+    pub llm_review: bool,
+    pub review_harness: Option<&'a str>,
+    pub setup_commands: &'a [String],
+}
+
+/// Insert many tasks with multi-row `INSERT`s. Rows keep their order, so
+/// [`list_tasks_for_plan`] returns them as given.
+///
+/// Takes a connection rather than a pool: it runs several statements, which
+/// should share the caller's transaction.
+pub async fn insert_tasks(conn: &mut SqliteConnection, tasks: &[NewTask<'_>]) -> Result<()> {
+    const COLUMNS: usize = 18;
+    for chunk in tasks.chunks(MAX_BULK_PARAMS / COLUMNS) {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy, retry_max, requested_harness, issue, services, kind, artifacts, paths, timeout_secs, gate_script, llm_review, review_harness, setup_commands) ",
+        );
+        query.push_values(chunk, |mut row, task| {
+            row.push_bind(task.id)
+                .push_bind(task.plan_id)
+                .push_bind(task.name)
+                .push_bind(task.description)
+                .push_bind(task.scope_level)
+                .push_bind(task.gate_policy)
+                .push_bind(task.retry_max)
+                .push_bind(task.requested_harness)
+                .push_bind(task.issue)
+                .push_bind(sqlx::types::Json(task.services))
+                .push_bind(task.kind)
+                .push_bind(sqlx::types::Json(task.artifacts))
+                .push_bind(sqlx::types::Json(task.paths))
+                .push_bind(task.timeout_secs)
+                .push_bind(task.gate_script)
+                .push_bind(task.llm_review)
+                .push_bind(task.review_harness)
+                .push_bind(sqlx::types::Json(task.setup_commands));
+        });
+        query
+            .build()
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to insert {} tasks", chunk.len()))?;
+    }
+    Ok(())
+}
+
+/// Insert many dependency edges, each `(task_id, depends_on, kind)`, with
+/// multi-row `INSERT`s. Existing edges are left alone.
+pub async fn insert_task_dependencies(
+    conn: &mut SqliteConnection,
+    edges: &[(Uuid, Uuid, DependencyKind)],
+) -> Result<()> {
+    for chunk in edges.chunks(MAX_BULK_PARAMS / 3) {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO task_dependencies (task_id, depends_on, kind) ",
+        );
+        query.push_values(chunk, |mut row, (task_id, depends_on, kind)| {
+            row.push_bind(*task_id)
+                .push_bind(*depends_on)
+                .push_bind(*kind);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        query
+            .build()
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to insert {} task dependencies", chunk.len()))?;
+    }
+    Ok(())
+}
+
+/// Link many tasks to invariants, each `(task_id, invariant_id)`, with
+/// multi-row `INSERT`s. Existing links are left alone.
+pub async fn link_task_invariants(
+    conn: &mut SqliteConnection,
+    links: &[(Uuid, Uuid)],
+) -> Result<()> {
+    for chunk in links.chunks(MAX_BULK_PARAMS / 2) {
+        let mut query =
+            QueryBuilder::<Sqlite>::new("INSERT INTO task_invariants (task_id, invariant_id) ");
+        query.push_values(chunk, |mut row, (task_id, invariant_id)| {
+            row.push_bind(*task_id).push_bind(*invariant_id);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        query
+            .build()
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to link {} task invariants", chunk.len()))?;
+    }
+    Ok(())
+}
+
 /// Fetch a single task by ID.
-pub async fn get_task(pool: &SqlitePool, id: Uuid) -> Result<Option<Task>> {
+pub async fn get_task<'e>(executor: impl SqliteExecutor<'e>, id: Uuid) -> Result<Option<Task>> {
     let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = $1")
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await
         .context("failed to fetch task")?;
 
@@ -57,12 +174,13 @@ pub async fn get_task(pool: &SqlitePool, id: Uuid) -> Result<Option<Task>> {
 
 /// List all tasks for a given plan, ordered by creation time.
 pub async fn list_tasks_for_plan(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<Task>> {
-    let tasks =
-        sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE plan_id = $1 ORDER BY created_at ASC")
-            .bind(plan_id)
-            .fetch_all(pool)
-            .await
-            .context("failed to list tasks for plan")?;
+    let tasks = sqlx::query_as::<_, Task>(
+        "SELECT * FROM tasks WHERE plan_id = $1 ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to list tasks for plan")?;
 
     Ok(tasks)
 }
@@ -105,11 +223,14 @@ pub async fn insert_task_dependency(
 }
 
 /// Get the IDs of all tasks that a given task depends on.
-pub async fn get_task_dependencies(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Uuid>> {
+pub async fn get_task_dependencies<'e>(
+    executor: impl SqliteExecutor<'e>,
+    task_id: Uuid,
+) -> Result<Vec<Uuid>> {
     let rows: Vec<(Uuid,)> =
         sqlx::query_as("SELECT depends_on FROM task_dependencies WHERE task_id = $1")
             .bind(task_id)
-            .fetch_all(pool)
+            .fetch_all(executor)
             .await
             .context("failed to get task dependencies")?;
 
@@ -117,22 +238,145 @@ pub async fn get_task_dependencies(pool: &SqlitePool, task_id: Uuid) -> Result<V
 }
 
 /// Get the names of all tasks that a given task depends on (resolving through
-/// the tasks table).
+/// the tasks table). Tasks of another plan are named `plan-name:task-name`,
+/// as in `depends_on`.
 pub async fn get_task_dependency_names(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<String>> {
-    let rows: Vec<(String,)> = sqlx::query_as(
-        "SELECT dep.name FROM task_dependencies td \
+    Ok(get_task_dependency_statuses(pool, task_id)
+        .await?
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Like [`get_task_dependency_names`], with each dependency's status.
+pub async fn get_task_dependency_statuses(
+    pool: &SqlitePool,
+    task_id: Uuid,
+) -> Result<Vec<(String, TaskStatus)>> {
+    sqlx::query_as(
+        "SELECT CASE WHEN dep.plan_id = t.plan_id THEN dep.name \
+                     ELSE p.name || ':' || dep.name END AS dep_name, \
+                dep.status \
+         FROM task_dependencies td \
+         JOIN tasks t ON t.id = td.task_id \
          JOIN tasks dep ON dep.id = td.depends_on \
+         JOIN plans p ON p.id = dep.plan_id \
          WHERE td.task_id = $1 \
-         ORDER BY dep.name",
+         ORDER BY dep_name",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to get task dependency names")
+}
+
+/// Every dependency of every task in a plan, in one query: task id to its
+/// dependencies' names (as in [`get_task_dependency_names`]) and edge kinds,
+/// sorted by name. Tasks without dependencies are absent.
+pub async fn list_plan_dependency_names(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+) -> Result<std::collections::HashMap<Uuid, Vec<(String, DependencyKind)>>> {
+    let rows: Vec<(Uuid, String, DependencyKind)> = sqlx::query_as(
+        "SELECT td.task_id, \
+                CASE WHEN dep.plan_id = t.plan_id THEN dep.name \
+                     ELSE p.name || ':' || dep.name END AS dep_name, \
+                td.kind \
+         FROM task_dependencies td \
+         JOIN tasks t ON t.id = td.task_id \
+         JOIN tasks dep ON dep.id = td.depends_on \
+         JOIN plans p ON p.id = dep.plan_id \
+         WHERE t.plan_id = $1 \
+         ORDER BY dep_name",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to list plan dependencies")?;
+
+    let mut by_task: std::collections::HashMap<Uuid, Vec<(String, DependencyKind)>> =
+        std::collections::HashMap::new();
+    for (task_id, name, kind) in rows {
+        by_task.entry(task_id).or_default().push((name, kind));
+    }
+    Ok(by_task)
+}
+
+/// The dependencies of a task that only order it (`ordering_only` edges),
+/// named as in [`get_task_dependency_names`].
+pub async fn get_ordering_only_dependency_names(
+    pool: &SqlitePool,
+    task_id: Uuid,
+) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT CASE WHEN dep.plan_id = t.plan_id THEN dep.name \
+                     ELSE p.name || ':' || dep.name END AS dep_name \
+         FROM task_dependencies td \
+         JOIN tasks t ON t.id = td.task_id \
+         JOIN tasks dep ON dep.id = td.depends_on \
+         JOIN plans p ON p.id = dep.plan_id \
+         WHERE td.task_id = $1 AND td.kind = 'ordering_only' \
+         ORDER BY dep_name",
     )
     .bind(task_id)
     .fetch_all(pool)
     .await
-    .context("failed to get task dependency names")?;
+    .context("failed to get ordering-only dependencies")?;
 
     Ok(rows.into_iter().map(|(name,)| name).collect())
 }
 
+/// Dependencies of a plan's pending tasks on tasks of other plans that have
+/// not passed yet but still can (they are not escalated or cancelled and
+/// their plan is not deleted), as `(task name, "plan-name:task-name")`.
+pub async fn list_open_cross_plan_dependencies(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+) -> Result<Vec<(String, String)>> {
+    sqlx::query_as(
+        "SELECT t.name, p.name || ':' || dep.name \
+         FROM tasks t \
+         JOIN task_dependencies td ON td.task_id = t.id \
+         JOIN tasks dep ON dep.id = td.depends_on \
+         JOIN plans p ON p.id = dep.plan_id \
+         WHERE t.plan_id = $1 \
+           AND t.status = 'pending' \
+           AND dep.plan_id != t.plan_id \
+           AND dep.status NOT IN ('passed', 'escalated', 'cancelled') \
+           AND p.deleted_at IS NULL \
+         ORDER BY 1, 2",
+    )
+    .bind(plan_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to list cross-plan dependencies")
+}
+
+/// Finished tasks (passed, failed, or escalated) of the other plans for
+/// `project_path`, oldest first. Tasks and plans that were deleted are left
+/// out.
+pub async fn list_finished_tasks_for_project(
+    pool: &SqlitePool,
+    project_path: &str,
+    exclude_plan_id: Uuid,
+) -> Result<Vec<Task>> {
+    sqlx::query_as::<_, Task>(
+        "SELECT t.* FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.project_path = $1 \
+           AND p.id != $2 \
+           AND p.deleted_at IS NULL \
+           AND t.deleted_at IS NULL \
+           AND t.status IN ('passed', 'failed', 'escalated') \
+         ORDER BY t.created_at ASC, t.rowid ASC",
+    )
+    .bind(project_path)
+    .bind(exclude_plan_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to list finished tasks for project")
+}
+
 /// Count total dependency edges for a plan.
 pub async fn count_dependency_edges(pool: &SqlitePool, plan_id: Uuid) -> Result<i64> {
     let row: (i64,) = sqlx::query_as(
@@ -179,8 +423,8 @@ pub async fn link_task_invariant(
 /// `status = $from`, so the row is only updated if the current status
 /// matches the expected `from` value. Returns the number of rows
 /// affected (0 means the status did not match).
-pub async fn transition_task_status(
-    pool: &SqlitePool,
+pub async fn transition_task_status<'e>(
+    executor: impl SqliteExecutor<'e>,
     task_id: Uuid,
     from: TaskStatus,
     to: TaskStatus,
@@ -199,7 +443,7 @@ pub async fn transition_task_status(
     .bind(completed_at)
     .bind(task_id)
     .bind(from)
-    .execute(pool)
+    .execute(executor)
     .await
     .context("failed to transition task status")?;
 
@@ -209,8 +453,8 @@ pub async fn transition_task_status(
 /// Atomically transition a task from `failed` to `assigned` (retry),
 /// incrementing the attempt counter and clearing timestamps. Uses
 /// optimistic locking on both status and the current attempt value.
-pub async fn transition_task_retry(
-    pool: &SqlitePool,
+pub async fn transition_task_retry<'e>(
+    executor: impl SqliteExecutor<'e>,
     task_id: Uuid,
     current_attempt: i32,
 ) -> Result<u64> {
@@ -224,7 +468,7 @@ pub async fn transition_task_retry(
     )
     .bind(task_id)
     .bind(current_attempt)
-    .execute(pool)
+    .execute(executor)
     .await
     .context("failed to retry task")?;
 
@@ -232,8 +476,8 @@ pub async fn transition_task_retry(
 }
 
 /// Set the assigned harness and worktree path on a task.
-pub async fn assign_task_metadata(
-    pool: &SqlitePool,
+pub async fn assign_task_metadata<'e>(
+    executor: impl SqliteExecutor<'e>,
     task_id: Uuid,
     harness: &str,
     worktree_path: &str,
@@ -246,7 +490,7 @@ pub async fn assign_task_metadata(
     .bind(harness)
     .bind(worktree_path)
     .bind(task_id)
-    .execute(pool)
+    .execute(executor)
     .await
     .context("failed to assign task metadata")?;
 
@@ -256,23 +500,79 @@ pub async fn assign_task_metadata(
 /// Get all tasks in a plan whose dependencies are all in `passed` status
 /// and whose own status is `pending` (i.e. ready to be assigned).
 pub async fn get_ready_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<Task>> {
-    let tasks = sqlx::query_as::<_, Task>(
-        "SELECT t.* \
-         FROM tasks t \
-         WHERE t.plan_id = $1 \
-           AND t.status = 'pending' \
-           AND NOT EXISTS ( \
-               SELECT 1 FROM task_dependencies td \
-               JOIN tasks dep ON dep.id = td.depends_on \
-               WHERE td.task_id = t.id AND dep.status != 'passed' \
-           )",
+    Ok(explain_pending_tasks(pool, plan_id)
+        .await?
+        .into_iter()
+        .filter(PendingTask::is_ready)
+        .map(|p| p.task)
+        .collect())
+}
+
+/// A dependency that has not passed yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmetDependency {
+    /// Task name; `plan-name:task-name` for a task of another plan.
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+/// A pending task with the dependencies it is still waiting for.
+#[derive(Debug, Clone)]
+pub struct PendingTask {
+    pub task: Task,
+    pub unmet_dependencies: Vec<UnmetDependency>,
+}
+
+impl PendingTask {
+    /// Whether every dependency has passed.
+    pub fn is_ready(&self) -> bool {
+        self.unmet_dependencies.is_empty()
+    }
+}
+
+/// Every `pending` task of a plan with the dependencies that have not
+/// passed yet. Tasks with none are ready (see [`get_ready_tasks`]).
+pub async fn explain_pending_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<PendingTask>> {
+    let tasks =
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE plan_id = $1 AND status = 'pending'")
+            .bind(plan_id)
+            .fetch_all(pool)
+            .await
+            .context("failed to get pending tasks")?;
+
+    let unmet: Vec<(Uuid, String, TaskStatus)> = sqlx::query_as(
+        "SELECT td.task_id, \
+                CASE WHEN dep.plan_id = t.plan_id THEN dep.name \
+                     ELSE p.name || ':' || dep.name END AS dep_name, \
+                dep.status \
+         FROM task_dependencies td \
+         JOIN tasks t ON t.id = td.task_id \
+         JOIN tasks dep ON dep.id = td.depends_on \
+         JOIN plans p ON p.id = dep.plan_id \
+         WHERE t.plan_id = $1 AND t.status = 'pending' AND dep.status != 'passed' \
+         ORDER BY dep_name",
     )
     .bind(plan_id)
     .fetch_all(pool)
     .await
-    .context("failed to get ready tasks")?;
+    .context("failed to get unmet dependencies")?;
 
-    Ok(tasks)
+    let mut by_task: std::collections::HashMap<Uuid, Vec<UnmetDependency>> =
+        std::collections::HashMap::new();
+    for (task_id, name, status) in unmet {
+        by_task
+            .entry(task_id)
+            .or_default()
+            .push(UnmetDependency { name, status });
+    }
+
+    Ok(tasks
+        .into_iter()
+        .map(|task| PendingTask {
+            unmet_dependencies: by_task.remove(&task.id).unwrap_or_default(),
+            task,
+        })
+        .collect())
 }
 
 /// Status counts for a plan's tasks.
@@ -281,10 +581,12 @@ pub struct PlanProgress {
     pub pending: i64,
     pub assigned: i64,
     pub running: i64,
+    pub blocked_on_human: i64,
     pub checking: i64,
     pub passed: i64,
     pub failed: i64,
     pub escalated: i64,
+    pub cancelled: i64,
     pub total: i64,
 }
 
@@ -307,10 +609,12 @@ pub async fn get_plan_progress(pool: &SqlitePool, plan_id: Uuid) -> Result<PlanP
             "pending" => progress.pending = *count,
             "assigned" => progress.assigned = *count,
             "running" => progress.running = *count,
+            "blocked_on_human" => progress.blocked_on_human = *count,
             "checking" => progress.checking = *count,
             "passed" => progress.passed = *count,
             "failed" => progress.failed = *count,
             "escalated" => progress.escalated = *count,
+            "cancelled" => progress.cancelled = *count,
             _ => {}
         }
         progress.total += count;
@@ -318,11 +622,11 @@ pub async fn get_plan_progress(pool: &SqlitePool, plan_id: Uuid) -> Result<PlanP
     Ok(progress)
 }
 
-/// Check whether all tasks in a plan have status `passed`.
+/// Check whether every task in a plan has either passed or been cancelled.
 pub async fn is_plan_complete(pool: &SqlitePool, plan_id: Uuid) -> Result<bool> {
     let row: (i64,) = sqlx::query_as(
         "SELECT COUNT(*) FROM tasks \
-         WHERE plan_id = $1 AND status != 'passed'",
+         WHERE plan_id = $1 AND status NOT IN ('passed', 'cancelled')",
     )
     .bind(plan_id)
     .fetch_one(pool)
@@ -356,12 +660,12 @@ pub async fn reset_non_passed_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<
     Ok(result.rows_affected())
 }
 
-/// Reset tasks stuck in intermediate states (assigned, running, checking)
-/// back to `failed` so they can be retried or escalated.
+/// Reset tasks stuck in intermediate states (assigned, running,
+/// blocked_on_human, checking) back to `failed` so they can be retried or
+/// escalated.
 ///
-/// This is used for restart recovery: if the orchestrator crashes mid-run,
-/// tasks that were in progress are left in limbo. This function resets them
-/// so the orchestrator can decide whether to retry or escalate.
+/// Restart recovery no longer charges the interrupted attempt this way; see
+/// [`requeue_interrupted_task`].
 ///
 /// Returns the tasks that were reset.
 pub async fn reset_orphaned_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<Vec<Task>> {
@@ -370,7 +674,7 @@ pub async fn reset_orphaned_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<Ve
          SET status = 'failed', \
              completed_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE plan_id = $1 \
-           AND status IN ('assigned', 'running', 'checking') \
+           AND status IN ('assigned', 'running', 'blocked_on_human', 'checking') \
          RETURNING *",
     )
     .bind(plan_id)
@@ -385,8 +689,8 @@ pub async fn reset_orphaned_tasks(pool: &SqlitePool, plan_id: Uuid) -> Result<Ve
 ///
 /// This is the operator override path: escalated tasks have exhausted their
 /// normal retry budget, but the operator can force a retry.
-pub async fn retry_escalated_to_pending(
-    pool: &SqlitePool,
+pub async fn retry_escalated_to_pending<'e>(
+    executor: impl SqliteExecutor<'e>,
     task_id: Uuid,
     current_attempt: i32,
 ) -> Result<u64> {
@@ -402,13 +706,36 @@ pub async fn retry_escalated_to_pending(
     )
     .bind(task_id)
     .bind(current_attempt)
-    .execute(pool)
+    .execute(executor)
     .await
     .context("failed to retry escalated task to pending")?;
 
     Ok(result.rows_affected())
 }
 
+/// Set environment overrides for the attempt after `current_attempt`, the
+/// one a retry starts. Returns the number of rows affected (0 when the
+/// task has moved on from `current_attempt`).
+pub async fn set_retry_env<'e>(
+    executor: impl SqliteExecutor<'e>,
+    task_id: Uuid,
+    current_attempt: i32,
+    env: &BTreeMap<String, String>,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE tasks SET retry_env = $3, retry_env_attempt = $2 + 1 \
+         WHERE id = $1 AND attempt = $2",
+    )
+    .bind(task_id)
+    .bind(current_attempt)
+    .bind(sqlx::types::Json(env))
+    .execute(executor)
+    .await
+    .context("failed to set retry env")?;
+
+    Ok(result.rows_affected())
+}
+
 /// A task with its plan name (for cross-plan views like the review queue).
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct TaskWithPlanName {
@@ -428,6 +755,7 @@ pub struct TaskWithPlanName {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub issue: Option<String>,
     // Extra
     pub plan_name: String,
 }
@@ -438,11 +766,11 @@ pub async fn list_checking_tasks(pool: &SqlitePool) -> Result<Vec<TaskWithPlanNa
         "SELECT t.id, t.plan_id, t.name, t.description, t.scope_level, t.gate_policy, \
                 t.retry_max, t.status, t.assigned_harness, t.requested_harness, \
                 t.worktree_path, t.attempt, \
-                t.created_at, t.started_at, t.completed_at, \
+                t.created_at, t.started_at, t.completed_at, t.issue, \
                 p.name AS plan_name \
          FROM tasks t \
          JOIN plans p ON p.id = t.plan_id \
-         WHERE t.status = 'checking' \
+         WHERE t.status = 'checking' AND t.deleted_at IS NULL \
          ORDER BY t.created_at ASC",
     )
     .fetch_all(pool)
@@ -457,8 +785,8 @@ pub async fn list_checking_tasks(pool: &SqlitePool) -> Result<Vec<TaskWithPlanNa
 /// Unlike `transition_task_retry` (which sets status to `assigned`), this
 /// resets to `pending` so the orchestrator's DAG scheduler can pick it up
 /// through the normal `get_ready_tasks` path.
-pub async fn retry_task_to_pending(
-    pool: &SqlitePool,
+pub async fn retry_task_to_pending<'e>(
+    executor: impl SqliteExecutor<'e>,
     task_id: Uuid,
     current_attempt: i32,
 ) -> Result<u64> {
@@ -474,9 +802,328 @@ pub async fn retry_task_to_pending(
     )
     .bind(task_id)
     .bind(current_attempt)
-    .execute(pool)
+    .execute(executor)
     .await
     .context("failed to retry task to pending")?;
 
     Ok(result.rows_affected())
 }
+
+/// Where a task's agent runs, as recorded by [`set_agent_process`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AgentProcess<'a> {
+    /// Process ID of the agent.
+    pub pid: Option<u32>,
+    /// Start time of that process, as reported by the OS.
+    pub started: Option<&'a str>,
+    /// Host the agent runs on.
+    pub host: Option<&'a str>,
+    /// Container the agent runs in, for container isolation.
+    pub container_id: Option<&'a str>,
+}
+
+/// Record where a task's agent runs: its host, process ID and start time
+/// and, for container isolation, its container. Only an `assigned` or
+/// `running` task is updated, so a task stopped while its agent spawned
+/// keeps no stale process ID.
+pub async fn set_agent_process(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    process: &AgentProcess<'_>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE tasks \
+         SET agent_pid = $2, agent_started = $3, agent_host = $4, container_id = $5 \
+         WHERE id = $1 AND status IN ('assigned', 'running')",
+    )
+    .bind(task_id)
+    .bind(process.pid.map(i64::from))
+    .bind(process.started)
+    .bind(process.host)
+    .bind(process.container_id)
+    .execute(pool)
+    .await
+    .context("failed to set agent process")?;
+
+    Ok(())
+}
+
+/// Record the correlation IDs of a task's current attempt: the dispatch
+/// run it belongs to and the attempt's own ID. Events and gate results
+/// recorded for the attempt copy them.
+pub async fn set_correlation(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    run_id: Option<Uuid>,
+    attempt_id: Uuid,
+) -> Result<()> {
+    sqlx::query("UPDATE tasks SET run_id = $2, attempt_id = $3 WHERE id = $1")
+        .bind(task_id)
+        .bind(run_id)
+        .bind(attempt_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to set correlation IDs of task {task_id}"))?;
+
+    Ok(())
+}
+
+/// Record that `gator merge` merged a task's branch, leaving the base
+/// branch at `merge_commit`.
+pub async fn mark_task_merged(pool: &SqlitePool, task_id: Uuid, merge_commit: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE tasks SET merged_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), merge_commit = $2 \
+         WHERE id = $1",
+    )
+    .bind(task_id)
+    .bind(merge_commit)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to mark task {task_id} merged"))?;
+
+    Ok(())
+}
+
+/// Record that a task's worktree takes `bytes` on disk.
+pub async fn set_disk_usage(pool: &SqlitePool, task_id: Uuid, bytes: u64) -> Result<()> {
+    sqlx::query(
+        "UPDATE tasks SET disk_usage_bytes = $2, \
+             disk_usage_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1",
+    )
+    .bind(task_id)
+    .bind(i64::try_from(bytes).unwrap_or(i64::MAX))
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to record disk usage of task {task_id}"))?;
+
+    Ok(())
+}
+
+/// Forget a task's disk usage once its worktree is removed.
+pub async fn clear_disk_usage(pool: &SqlitePool, task_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE tasks SET disk_usage_bytes = NULL, disk_usage_at = NULL WHERE id = $1")
+        .bind(task_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to clear disk usage of task {task_id}"))?;
+
+    Ok(())
+}
+
+/// Total measured disk usage of the worktrees of `project_path`'s tasks.
+pub async fn worktree_disk_usage(pool: &SqlitePool, project_path: &str) -> Result<u64> {
+    let total: Option<i64> = sqlx::query_scalar(
+        "SELECT SUM(t.disk_usage_bytes) FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE p.project_path = $1 AND t.worktree_path IS NOT NULL",
+    )
+    .bind(project_path)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("failed to sum worktree disk usage of {project_path}"))?;
+
+    Ok(total.map_or(0, |t| u64::try_from(t).unwrap_or(0)))
+}
+
+/// A task worktree that `gator cleanup` may garbage-collect.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FinishedWorktree {
+    pub task_id: Uuid,
+    pub task_name: String,
+    pub plan_name: String,
+    pub project_path: String,
+    pub worktree_path: String,
+    pub disk_usage_bytes: Option<i64>,
+    /// When the task last finished (or started, or was created).
+    pub last_active_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Worktrees of tasks that are done with them -- `passed`, `failed`,
+/// `escalated`, or `cancelled` -- across all plans, least recently active
+/// first.
+pub async fn list_finished_worktrees(pool: &SqlitePool) -> Result<Vec<FinishedWorktree>> {
+    let worktrees = sqlx::query_as::<_, FinishedWorktree>(
+        "SELECT t.id AS task_id, t.name AS task_name, p.name AS plan_name, \
+                p.project_path, t.worktree_path, t.disk_usage_bytes, \
+                COALESCE(t.completed_at, t.started_at, t.created_at) AS last_active_at \
+         FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE t.worktree_path IS NOT NULL \
+           AND t.status IN ('passed', 'failed', 'escalated', 'cancelled') \
+         ORDER BY last_active_at ASC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list finished worktrees")?;
+
+    Ok(worktrees)
+}
+
+/// A task, of any plan, as `gator gc` sees it: what it may own on disk and
+/// in Docker, and whether it is still using any of it.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TaskRef {
+    pub task_id: Uuid,
+    pub task_name: String,
+    pub plan_name: String,
+    pub project_path: String,
+    /// The plan's base branch.
+    pub base_branch: String,
+    pub status: TaskStatus,
+    pub attempt: i32,
+    pub worktree_path: Option<String>,
+    pub merged_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `assigned`, `running`, `checking`, or `blocked_on_human`.
+    pub in_flight: bool,
+    /// Whether the plan is `running`.
+    pub plan_running: bool,
+    /// The plan's last dispatcher.
+    pub dispatched_host: Option<String>,
+    pub dispatched_pid: Option<i64>,
+}
+
+/// The [`TaskRef`] columns of `tasks t JOIN plans p`.
+const TASK_REF_COLUMNS: &str = "t.id AS task_id, t.name AS task_name, p.name AS plan_name, \
+     p.project_path, p.base_branch, t.status, t.attempt, t.worktree_path, t.merged_at, \
+     t.status IN ('assigned', 'running', 'checking', 'blocked_on_human') AS in_flight, \
+     p.status = 'running' AS plan_running, p.dispatched_host, p.dispatched_pid";
+
+/// Every task of every plan, archived and deleted plans included, grouped
+/// by project.
+pub async fn list_task_refs(pool: &SqlitePool) -> Result<Vec<TaskRef>> {
+    let tasks = sqlx::query_as::<_, TaskRef>(&format!(
+        "SELECT {TASK_REF_COLUMNS} \
+         FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         ORDER BY p.project_path, p.name, t.name"
+    ))
+    .fetch_all(pool)
+    .await
+    .context("failed to list tasks of all plans")?;
+
+    Ok(tasks)
+}
+
+/// One task as [`list_task_refs`] sees it, read afresh.
+pub async fn get_task_ref(pool: &SqlitePool, task_id: Uuid) -> Result<Option<TaskRef>> {
+    let task = sqlx::query_as::<_, TaskRef>(&format!(
+        "SELECT {TASK_REF_COLUMNS} \
+         FROM tasks t \
+         JOIN plans p ON p.id = t.plan_id \
+         WHERE t.id = $1"
+    ))
+    .bind(task_id)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("failed to get task {task_id}"))?;
+
+    Ok(task)
+}
+
+/// Cancel every `pending` task of the same plan that depends on `task_id`,
+/// directly or transitively: without it they could never run. Returns the
+/// cancelled tasks.
+pub async fn cancel_pending_dependents<'e>(
+    executor: impl SqliteExecutor<'e>,
+    task_id: Uuid,
+) -> Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>(
+        "WITH RECURSIVE dependents(id) AS ( \
+             SELECT task_id FROM task_dependencies WHERE depends_on = $1 \
+             UNION \
+             SELECT td.task_id FROM task_dependencies td \
+             JOIN dependents d ON td.depends_on = d.id \
+         ) \
+         UPDATE tasks \
+         SET status = 'cancelled', \
+             completed_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id IN (SELECT id FROM dependents) \
+           AND plan_id = (SELECT plan_id FROM tasks WHERE id = $1) \
+           AND status = 'pending' \
+         RETURNING *",
+    )
+    .bind(task_id)
+    .fetch_all(executor)
+    .await
+    .with_context(|| format!("failed to cancel dependents of task {task_id}"))?;
+
+    Ok(tasks)
+}
+
+/// List tasks in `assigned`, `running`, `blocked_on_human`, or `checking`
+/// status across all plans: the ones with an agent or gate in flight.
+pub async fn list_in_flight_tasks(pool: &SqlitePool) -> Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>(
+        "SELECT * FROM tasks \
+         WHERE status IN ('assigned', 'running', 'blocked_on_human', 'checking') \
+         ORDER BY created_at ASC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to list in-flight tasks")?;
+
+    Ok(tasks)
+}
+
+/// Stop an in-flight task: reset it to `pending` with an incremented
+/// attempt counter and forget its agent, so the next dispatch starts it
+/// fresh. Returns the number of rows affected (0 when the task is no longer
+/// in flight).
+pub async fn stop_task<'e>(executor: impl SqliteExecutor<'e>, task_id: Uuid) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE tasks \
+         SET status = 'pending', \
+             attempt = attempt + 1, \
+             assigned_harness = NULL, \
+             worktree_path = NULL, \
+             agent_pid = NULL, \
+             agent_started = NULL, \
+             agent_host = NULL, \
+             container_id = NULL, \
+             started_at = NULL, \
+             completed_at = NULL \
+         WHERE id = $1 AND status IN ('assigned', 'running', 'blocked_on_human', 'checking')",
+    )
+    .bind(task_id)
+    .execute(executor)
+    .await
+    .context("failed to stop task")?;
+
+    Ok(result.rows_affected())
+}
+
+/// Requeue a task whose attempt was cut short by a dispatch that died:
+/// reset it to `pending` with an incremented attempt counter, like
+/// [`stop_task`], and raise its retry budget by one so the interrupted
+/// attempt is not charged to it. Only an in-flight task still on
+/// `current_attempt` is updated; returns the number of rows affected.
+pub async fn requeue_interrupted_task<'e>(
+    executor: impl SqliteExecutor<'e>,
+    task_id: Uuid,
+    current_attempt: i32,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE tasks \
+         SET status = 'pending', \
+             attempt = attempt + 1, \
+             retry_max = retry_max + 1, \
+             assigned_harness = NULL, \
+             worktree_path = NULL, \
+             agent_pid = NULL, \
+             agent_started = NULL, \
+             agent_host = NULL, \
+             container_id = NULL, \
+             started_at = NULL, \
+             completed_at = NULL \
+         WHERE id = $1 AND attempt = $2 \
+           AND status IN ('assigned', 'running', 'blocked_on_human', 'checking')",
+    )
+    .bind(task_id)
+    .bind(current_attempt)
+    .execute(executor)
+    .await
+    .context("failed to requeue interrupted task")?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,93 @@
+//! Database query functions for the `triage_items` table.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::TriageItem;
+
+/// Record an issue as queued for triage by `task_id`.
+pub async fn insert_triage_item<'e>(
+    executor: impl SqliteExecutor<'e>,
+    repo: &str,
+    issue_number: i64,
+    plan_id: Uuid,
+    task_id: Uuid,
+) -> Result<TriageItem> {
+    let item = sqlx::query_as::<_, TriageItem>(
+        "INSERT INTO triage_items (id, repo, issue_number, plan_id, task_id) \
+         VALUES ($1, $2, $3, $4, $5) \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(repo)
+    .bind(issue_number)
+    .bind(plan_id)
+    .bind(task_id)
+    .fetch_one(executor)
+    .await
+    .with_context(|| format!("failed to queue {repo}#{issue_number} for triage"))?;
+
+    Ok(item)
+}
+
+/// Issue numbers of `repo` that have ever been queued, in any status.
+pub async fn triaged_issue_numbers(pool: &SqlitePool, repo: &str) -> Result<HashSet<i64>> {
+    let rows: Vec<(i64,)> = sqlx::query_as("SELECT issue_number FROM triage_items WHERE repo = $1")
+        .bind(repo)
+        .fetch_all(pool)
+        .await
+        .with_context(|| format!("failed to list triaged issues of {repo}"))?;
+
+    Ok(rows.into_iter().map(|(n,)| n).collect())
+}
+
+/// Triage items, newest first, optionally filtered by repository and status.
+pub async fn list_triage_items(
+    pool: &SqlitePool,
+    repo: Option<&str>,
+    status: Option<&str>,
+) -> Result<Vec<TriageItem>> {
+    let items = sqlx::query_as::<_, TriageItem>(
+        "SELECT * FROM triage_items \
+         WHERE ($1 IS NULL OR repo = $1) AND ($2 IS NULL OR status = $2) \
+         ORDER BY created_at DESC, issue_number DESC",
+    )
+    .bind(repo)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .context("failed to list triage items")?;
+
+    Ok(items)
+}
+
+/// Mark a triage item as posted back to its issue.
+pub async fn mark_triage_posted(pool: &SqlitePool, id: Uuid) -> Result<()> {
+    sqlx::query(
+        "UPDATE triage_items \
+         SET status = 'posted', error = NULL, \
+             posted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to mark triage item {id} posted"))?;
+
+    Ok(())
+}
+
+/// Mark a triage item as failed with `error`.
+pub async fn mark_triage_failed(pool: &SqlitePool, id: Uuid, error: &str) -> Result<()> {
+    sqlx::query("UPDATE triage_items SET status = 'failed', error = $2 WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to mark triage item {id} failed"))?;
+
+    Ok(())
+}
@@ -235,3 +235,305 @@ async fn multiple_event_types_insert_correctly() {
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+#[tokio::test]
+async fn delete_events_for_plan_removes_all_attempts() {
+    let (pool, db_name) = create_test_db().await;
+    let task_id = create_test_task(&pool).await;
+
+    for attempt in 0..2 {
+        let new = NewAgentEvent {
+            task_id,
+            attempt,
+            event_type: "message".to_string(),
+            payload: serde_json::json!({"role": "assistant", "content": "hi"}),
+        };
+        agent_events::insert_agent_event(&pool, &new)
+            .await
+            .expect("insert should succeed");
+    }
+    let plan_id = gator_db::queries::tasks::get_task(&pool, task_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .plan_id;
+    assert_eq!(
+        agent_events::count_events_for_plan(&pool, plan_id)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let removed = agent_events::delete_events_for_plan(&pool, plan_id)
+        .await
+        .expect("delete should succeed");
+
+    assert_eq!(removed, 2);
+    assert!(
+        agent_events::list_all_events_for_task(&pool, task_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn prune_events_keeps_token_usage_recent_and_in_flight_events() {
+    let (pool, db_name) = create_test_db().await;
+    let done = create_test_task(&pool).await;
+    let running = create_test_task(&pool).await;
+    sqlx::query("UPDATE tasks SET status = 'running' WHERE id = $1")
+        .bind(running)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    for (task_id, event_type) in [
+        (done, "message"),
+        (done, "token_usage"),
+        (done, "message"),
+        (running, "message"),
+    ] {
+        let new = NewAgentEvent {
+            task_id,
+            attempt: 0,
+            event_type: event_type.to_string(),
+            payload: serde_json::json!({}),
+        };
+        agent_events::insert_agent_event(&pool, &new).await.unwrap();
+    }
+    // Age all but the last event of the finished task.
+    sqlx::query(
+        "UPDATE agent_events SET recorded_at = '2000-01-01T00:00:00.000Z' \
+         WHERE id NOT IN (SELECT MAX(id) FROM agent_events WHERE event_type = 'message' \
+                          AND task_id = $1)",
+    )
+    .bind(done)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(1);
+    assert_eq!(
+        agent_events::count_prunable_events(&pool, cutoff)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(agent_events::prune_events(&pool, cutoff).await.unwrap(), 1);
+
+    let kept: Vec<String> = agent_events::list_all_events_for_task(&pool, done)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|e| e.event_type)
+        .collect();
+    assert_eq!(kept, vec!["token_usage", "message"]);
+    assert_eq!(
+        agent_events::count_events_for_task(&pool, running, 0)
+            .await
+            .unwrap(),
+        1
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn recent_errors_for_plan_are_newest_first_and_limited() {
+    let (pool, db_name) = create_test_db().await;
+    let task_id = create_test_task(&pool).await;
+
+    for (attempt, event_type, message) in [
+        (0, "error", "first"),
+        (0, "message", "not an error"),
+        (1, "error", "second"),
+        (2, "error", "third"),
+    ] {
+        let new = NewAgentEvent {
+            task_id,
+            attempt,
+            event_type: event_type.to_string(),
+            payload: serde_json::json!({"message": message}),
+        };
+        agent_events::insert_agent_event(&pool, &new)
+            .await
+            .expect("insert should succeed");
+    }
+    let plan_id = gator_db::queries::tasks::get_task(&pool, task_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .plan_id;
+
+    let errors = agent_events::get_recent_errors_for_plan(&pool, plan_id, 2)
+        .await
+        .expect("query should succeed");
+
+    let messages: Vec<_> = errors
+        .iter()
+        .map(|e| e.payload["message"].clone())
+        .collect();
+    assert_eq!(messages, ["third", "second"]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn secrets_in_payloads_are_redacted_before_insert() {
+    let (pool, db_name) = create_test_db().await;
+    let task_id = create_test_task(&pool).await;
+    gator_db::redact::set_patterns(&["acme-[0-9]{6}".to_string()]).unwrap();
+
+    let new = NewAgentEvent {
+        task_id,
+        attempt: 0,
+        event_type: "tool_result".to_string(),
+        payload: serde_json::json!({
+            "tool": "Bash",
+            "output": "OPENAI_API_KEY=sk-proj-abcdefghijklmnopqrstuvwxyz\nlicense acme-123456\n",
+        }),
+    };
+    let inserted = agent_events::insert_agent_event(&pool, &new)
+        .await
+        .expect("insert should succeed");
+    assert_eq!(
+        inserted.payload["output"],
+        "OPENAI_API_KEY=[REDACTED]\nlicense [REDACTED]\n"
+    );
+
+    let stored = agent_events::list_events_for_task(&pool, task_id, 0)
+        .await
+        .expect("list should succeed");
+    assert!(!stored[0].payload.to_string().contains("sk-proj"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn agent_audit_is_redacted_and_filtered_by_attempt() {
+    use gator_db::models::AuditOutcome;
+    use gator_db::queries::agent_audit::{self, NewAgentAudit};
+
+    let (pool, db_name) = create_test_db().await;
+    let task_id = create_test_task(&pool).await;
+
+    let entries = [
+        (0, "task", AuditOutcome::Ok, None),
+        (
+            0,
+            "check",
+            AuditOutcome::Error,
+            Some("export GATOR_TOKEN_SECRET=0123456789abcdef"),
+        ),
+        (1, "plan", AuditOutcome::Denied, Some("not available")),
+    ];
+    for (attempt, command, outcome, error) in entries {
+        agent_audit::insert_agent_audit(
+            &pool,
+            &NewAgentAudit {
+                task_id,
+                attempt,
+                command,
+                token_version: "v2",
+                outcome,
+                error,
+            },
+        )
+        .await
+        .expect("insert audit");
+    }
+
+    let all = agent_audit::list_agent_audit_for_task(&pool, task_id, None)
+        .await
+        .unwrap();
+    let commands: Vec<&str> = all.iter().map(|a| a.command.as_str()).collect();
+    assert_eq!(commands, vec!["task", "check", "plan"]);
+    assert_eq!(
+        all[1].error.as_deref(),
+        Some("export GATOR_TOKEN_SECRET=[REDACTED]")
+    );
+    assert_eq!(all[2].outcome, AuditOutcome::Denied);
+
+    let retry = agent_audit::list_agent_audit_for_task(&pool, task_id, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(retry.len(), 1);
+    assert_eq!(retry[0].command, "plan");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn events_carry_the_correlation_ids_of_the_current_attempt() {
+    use gator_db::queries::agent_events::EventFilter;
+
+    let (pool, db_name) = create_test_db().await;
+    let task_id = create_test_task(&pool).await;
+    let other_task = create_test_task(&pool).await;
+
+    let run_id = Uuid::new_v4();
+    let attempt_id = Uuid::new_v4();
+    gator_db::queries::tasks::set_correlation(&pool, task_id, Some(run_id), attempt_id)
+        .await
+        .unwrap();
+    gator_db::queries::tasks::set_correlation(&pool, other_task, Some(run_id), Uuid::new_v4())
+        .await
+        .unwrap();
+
+    let insert = |task_id, attempt, event_type: &str| NewAgentEvent {
+        task_id,
+        attempt,
+        event_type: event_type.to_string(),
+        payload: serde_json::json!({}),
+    };
+    let current = agent_events::insert_agent_event(&pool, &insert(task_id, 0, "message"))
+        .await
+        .unwrap();
+    assert_eq!(current.run_id, Some(run_id));
+    assert_eq!(current.attempt_id, Some(attempt_id));
+
+    // An event for an attempt other than the current one is not attributed.
+    let stale = agent_events::insert_agent_event(&pool, &insert(task_id, 1, "message"))
+        .await
+        .unwrap();
+    assert_eq!(stale.run_id, None);
+    assert_eq!(stale.attempt_id, None);
+
+    agent_events::insert_agent_event(&pool, &insert(other_task, 0, "completed"))
+        .await
+        .unwrap();
+
+    let run = agent_events::list_matching_events(
+        &pool,
+        &EventFilter {
+            run_id: Some(run_id),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(run.len(), 2);
+
+    let attempt = agent_events::list_matching_events(
+        &pool,
+        &EventFilter {
+            attempt_id: Some(attempt_id),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(attempt.len(), 1);
+    assert_eq!(attempt[0].id, current.id);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,98 @@
+//! Integration tests for the `change_log` change feed.
+
+use uuid::Uuid;
+
+use gator_db::models::PlanStatus;
+use gator_db::queries::{change_log, plans, tasks};
+
+use gator_test_utils::{create_test_db, drop_test_db};
+
+#[tokio::test]
+async fn changes_are_logged_only_while_enabled() {
+    let (pool, db_name) = create_test_db().await;
+
+    let ignored = plans::insert_plan(
+        &pool,
+        "before",
+        "/tmp/project",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(!change_log::is_enabled(&pool).await.unwrap());
+    assert_eq!(change_log::status(&pool).await.unwrap().entries, 0);
+
+    change_log::set_enabled(&pool, true).await.unwrap();
+    let plan = plans::insert_plan(
+        &pool,
+        "after",
+        "/tmp/project",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(
+        &pool, plan.id, "task-one", "desc", "narrow", "auto", 3, None,
+    )
+    .await
+    .unwrap();
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Approved)
+        .await
+        .unwrap();
+    // Deleting the plan cascades to its task, and both are logged.
+    sqlx::query("DELETE FROM plans WHERE id = $1")
+        .bind(plan.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    change_log::set_enabled(&pool, false).await.unwrap();
+    plans::update_plan_status(&pool, ignored.id, PlanStatus::Approved)
+        .await
+        .unwrap();
+
+    let changes = change_log::list_changes(&pool, 0, 100).await.unwrap();
+    let summary: Vec<(&str, &str, Uuid)> = changes
+        .iter()
+        .map(|c| (c.table_name.as_str(), c.op.as_str(), c.row_id))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("plans", "insert", plan.id),
+            ("tasks", "insert", task.id),
+            ("plans", "update", plan.id),
+            ("tasks", "delete", task.id),
+            ("plans", "delete", plan.id),
+        ]
+    );
+    assert!(changes.windows(2).all(|w| w[0].seq < w[1].seq));
+
+    // Reading resumes after the last seq seen; pruning drops what was read.
+    let rest = change_log::list_changes(&pool, changes[1].seq, 2)
+        .await
+        .unwrap();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(rest[0].seq, changes[2].seq);
+    assert_eq!(
+        change_log::prune_changes(&pool, changes[2].seq)
+            .await
+            .unwrap(),
+        3
+    );
+    let status = change_log::status(&pool).await.unwrap();
+    assert!(!status.enabled);
+    assert_eq!(status.entries, 2);
+    assert_eq!(status.first_seq, Some(changes[3].seq));
+    assert_eq!(status.last_seq, Some(changes[4].seq));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,258 @@
+//! Tests for the `gate_results` query module.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{InvariantKind, InvariantScope};
+use gator_db::queries::gate_results::{self, NewGateResult};
+use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::setup_results::{self, NewSetupResult};
+use gator_db::queries::warmup_results::{self, NewWarmupResult};
+
+use gator_test_utils::{create_test_db, drop_test_db};
+
+/// Create a plan, task, and invariant so gate results satisfy their FKs.
+async fn create_fixtures(pool: &SqlitePool) -> (Uuid, Uuid) {
+    let plan = gator_db::queries::plans::insert_plan(
+        pool,
+        "test-plan",
+        "/tmp/test",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .expect("insert plan");
+    let task = gator_db::queries::tasks::insert_task(
+        pool,
+        plan.id,
+        "test-task",
+        "A test task",
+        "narrow",
+        "auto",
+        3,
+        None,
+    )
+    .await
+    .expect("insert task");
+    let invariant = invariants::insert_invariant(
+        pool,
+        &NewInvariant {
+            name: "tests",
+            description: None,
+            kind: InvariantKind::Custom,
+            command: "true",
+            args: &[],
+            expected_exit_code: 0,
+            threshold: None,
+            scope: InvariantScope::Project,
+            timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &BTreeMap::new(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
+        },
+    )
+    .await
+    .expect("insert invariant");
+
+    (task.id, invariant.id)
+}
+
+fn new_result(task_id: Uuid, invariant_id: Uuid, attempt: i32, passed: bool) -> NewGateResult {
+    NewGateResult {
+        task_id,
+        invariant_id,
+        attempt,
+        passed,
+        exit_code: Some(if passed { 0 } else { 1 }),
+        stdout: Some("running 12 tests".to_string()),
+        stderr: Some("warning: unused import".to_string()),
+        duration_ms: Some(1200),
+        timed_out: false,
+        base_value: None,
+        task_value: None,
+        content_hash: None,
+        cached_from: None,
+    }
+}
+
+#[tokio::test]
+async fn strip_gate_output_keeps_old_verdicts() {
+    let (pool, db_name) = create_test_db().await;
+    let (task_id, invariant_id) = create_fixtures(&pool).await;
+
+    let old = gate_results::insert_gate_result(&pool, &new_result(task_id, invariant_id, 0, false))
+        .await
+        .expect("insert old result");
+    gate_results::insert_gate_result(&pool, &new_result(task_id, invariant_id, 1, true))
+        .await
+        .expect("insert recent result");
+    sqlx::query("UPDATE gate_results SET checked_at = '2020-01-01T00:00:00.000Z' WHERE id = $1")
+        .bind(old.id)
+        .execute(&pool)
+        .await
+        .expect("backdate result");
+
+    let cutoff = Utc::now() - Duration::days(30);
+    let stripped = gate_results::strip_gate_output(&pool, cutoff)
+        .await
+        .expect("strip should succeed");
+    assert_eq!(stripped, 1);
+
+    let results = gate_results::get_gate_results(&pool, task_id, 0)
+        .await
+        .expect("get attempt 0");
+    assert_eq!(results.len(), 1, "the old row is kept");
+    assert!(!results[0].passed);
+    assert_eq!(results[0].exit_code, Some(1));
+    assert_eq!(results[0].duration_ms, Some(1200));
+    assert!(results[0].stdout.is_none());
+    assert!(results[0].stderr.is_none());
+
+    let recent = gate_results::get_gate_results(&pool, task_id, 1)
+        .await
+        .expect("get attempt 1");
+    assert_eq!(recent[0].stdout.as_deref(), Some("running 12 tests"));
+
+    // Already stripped rows are not counted again.
+    let again = gate_results::strip_gate_output(&pool, cutoff)
+        .await
+        .expect("strip should succeed");
+    assert_eq!(again, 0);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn setup_and_warmup_output_is_stripped_like_gate_output() {
+    let (pool, db_name) = create_test_db().await;
+    let (task_id, _) = create_fixtures(&pool).await;
+
+    for attempt in [0, 1] {
+        setup_results::insert_setup_result(
+            &pool,
+            &NewSetupResult {
+                task_id,
+                attempt,
+                position: 0,
+                command: "npm ci".to_string(),
+                passed: true,
+                exit_code: Some(0),
+                timed_out: false,
+                stdout: Some("added 12 packages".to_string()),
+                stderr: Some("npm warn deprecated".to_string()),
+                duration_ms: 900,
+            },
+        )
+        .await
+        .expect("insert setup result");
+        warmup_results::insert_warmup_result(
+            &pool,
+            &NewWarmupResult {
+                task_id,
+                attempt,
+                command: "cargo build".to_string(),
+                passed: true,
+                exit_code: Some(0),
+                timed_out: false,
+                stdout: Some("Compiling gator".to_string()),
+                stderr: None,
+                duration_ms: 4000,
+            },
+        )
+        .await
+        .expect("insert warm-up result");
+    }
+    for table in ["setup_results", "warmup_results"] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET ran_at = '2020-01-01T00:00:00.000Z' WHERE attempt = 0"
+        ))
+        .execute(&pool)
+        .await
+        .expect("backdate results");
+    }
+
+    let cutoff = Utc::now() - Duration::days(30);
+    assert_eq!(
+        setup_results::strip_setup_output(&pool, cutoff)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        warmup_results::strip_warmup_output(&pool, cutoff)
+            .await
+            .unwrap(),
+        1
+    );
+
+    let old = setup_results::list_setup_results(&pool, task_id, 0)
+        .await
+        .unwrap();
+    assert_eq!(old.len(), 1, "the old row is kept");
+    assert!(old[0].passed);
+    assert!(old[0].stdout.is_none() && old[0].stderr.is_none());
+    let old = warmup_results::get_warmup_result(&pool, task_id, 0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(old.duration_ms, 4000);
+    assert!(old.stdout.is_none());
+
+    let recent = setup_results::list_setup_results(&pool, task_id, 1)
+        .await
+        .unwrap();
+    assert_eq!(recent[0].stdout.as_deref(), Some("added 12 packages"));
+    let recent = warmup_results::get_warmup_result(&pool, task_id, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recent.stdout.as_deref(), Some("Compiling gator"));
+
+    // Already stripped rows are not counted again.
+    assert_eq!(
+        setup_results::strip_setup_output(&pool, cutoff)
+            .await
+            .unwrap(),
+        0
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn gate_results_of_all_attempts_carry_invariant_names() {
+    let (pool, db_name) = create_test_db().await;
+    let (task_id, invariant_id) = create_fixtures(&pool).await;
+
+    for (attempt, passed) in [(1, true), (0, false)] {
+        gate_results::insert_gate_result(
+            &pool,
+            &new_result(task_id, invariant_id, attempt, passed),
+        )
+        .await
+        .expect("insert result");
+    }
+
+    let results = gate_results::list_gate_results_with_names(&pool, task_id)
+        .await
+        .expect("list results");
+    let summary: Vec<(i32, bool, &str)> = results
+        .iter()
+        .map(|r| (r.attempt, r.passed, r.invariant_name.as_str()))
+        .collect();
+    assert_eq!(summary, vec![(0, false, "tests"), (1, true, "tests")]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -4,6 +4,8 @@
 //! PostgreSQL instance (via testcontainers), runs migrations, and drops it on
 //! completion so tests are fully isolated and idempotent.
 
+use std::collections::BTreeMap;
+
 use uuid::Uuid;
 
 use gator_db::models::{InvariantKind, InvariantScope};
@@ -11,6 +13,9 @@ use gator_db::queries::invariants::{self, NewInvariant};
 
 use gator_test_utils::{create_test_db, drop_test_db};
 
+/// Env for invariants that set none.
+const NO_ENV: &BTreeMap<String, String> = &BTreeMap::new();
+
 /// Helper: build a NewInvariant with sensible defaults for testing.
 fn test_new_invariant(name: &str) -> NewInvariant<'_> {
     NewInvariant {
@@ -23,6 +28,12 @@ fn test_new_invariant(name: &str) -> NewInvariant<'_> {
         threshold: None,
         scope: InvariantScope::Project,
         timeout_secs: 300,
+        diff_base: false,
+        lower_is_better: false,
+        env: NO_ENV,
+        workdir: None,
+        run_after: &[],
+        artifacts: &[],
     }
 }
 
@@ -343,6 +354,12 @@ async fn insert_invariant_with_all_fields() {
         threshold: Some(80.0),
         scope: InvariantScope::Global,
         timeout_secs: 300,
+        diff_base: false,
+        lower_is_better: false,
+        env: &Default::default(),
+        workdir: None,
+        run_after: &[],
+        artifacts: &[],
     };
 
     let inserted = invariants::insert_invariant(&pool, &new)
@@ -365,6 +382,40 @@ async fn insert_invariant_with_all_fields() {
     drop_test_db(&db_name).await;
 }
 
+#[tokio::test]
+async fn env_and_workdir_round_trip() {
+    let (pool, db_name) = create_test_db().await;
+
+    let env = BTreeMap::from([
+        ("CI".to_owned(), "1".to_owned()),
+        ("NODE_ENV".to_owned(), "test".to_owned()),
+    ]);
+    let mut new = test_new_invariant("web_test");
+    new.env = &env;
+    new.workdir = Some("web");
+    let inserted = invariants::insert_invariant(&pool, &new)
+        .await
+        .expect("insert should succeed");
+    assert_eq!(*inserted.env, env);
+    assert_eq!(inserted.workdir.as_deref(), Some("web"));
+
+    let plain = invariants::insert_invariant(&pool, &test_new_invariant("plain"))
+        .await
+        .expect("insert should succeed");
+    assert!(plain.env.is_empty());
+    assert_eq!(plain.workdir, None);
+
+    let cleared = test_new_invariant("web_test");
+    let updated = invariants::update_invariant(&pool, inserted.id, &cleared)
+        .await
+        .expect("update should succeed");
+    assert!(updated.env.is_empty());
+    assert_eq!(updated.workdir, None);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 #[tokio::test]
 async fn get_invariant_nonexistent_returns_none() {
     let (pool, db_name) = create_test_db().await;
@@ -411,3 +462,374 @@ async fn get_invariants_for_task_with_no_links() {
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+#[tokio::test]
+async fn soft_delete_and_restore_invariant() {
+    let (pool, db_name) = create_test_db().await;
+
+    invariants::insert_invariant(&pool, &test_new_invariant("flaky_lint"))
+        .await
+        .expect("insert invariant");
+
+    let deleted = invariants::soft_delete_invariant(&pool, "flaky_lint")
+        .await
+        .expect("soft delete should succeed");
+    assert!(deleted.deleted_at.is_some());
+    assert!(
+        invariants::get_invariant_by_name(&pool, "flaky_lint")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(invariants::list_invariants(&pool).await.unwrap().is_empty());
+    assert_eq!(
+        invariants::list_deleted_invariants(&pool)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let restored = invariants::restore_invariant(&pool, "flaky_lint")
+        .await
+        .expect("restore should succeed");
+    assert!(restored.deleted_at.is_none());
+    assert!(
+        invariants::get_invariant_by_name(&pool, "flaky_lint")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // Purging with a future cutoff only touches deleted rows.
+    let future = chrono::Utc::now() + chrono::Duration::days(1);
+    assert_eq!(
+        invariants::purge_deleted_invariants(&pool, future)
+            .await
+            .unwrap(),
+        0
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn soft_delete_refuses_invariant_linked_to_live_task() {
+    let (pool, db_name) = create_test_db().await;
+
+    let inv = invariants::insert_invariant(&pool, &test_new_invariant("in_use"))
+        .await
+        .expect("insert invariant");
+
+    let plan_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO plans (id, name, project_path, base_branch) \
+         VALUES ($1, 'test plan', '/tmp', 'main')",
+    )
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert plan");
+    let task_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy) \
+         VALUES ($1, $2, 'test task', 'desc', 'narrow', 'auto')",
+    )
+    .bind(task_id)
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert task");
+    invariants::link_task_invariant(&pool, task_id, inv.id)
+        .await
+        .expect("link should succeed");
+
+    let err = invariants::soft_delete_invariant(&pool, "in_use")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("linked to"), "got: {err}");
+
+    // Once the plan itself is deleted, the link no longer blocks.
+    gator_db::queries::plans::soft_delete_plan(&pool, plan_id)
+        .await
+        .expect("delete plan");
+    invariants::soft_delete_invariant(&pool, "in_use")
+        .await
+        .expect("soft delete should succeed after plan deletion");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn update_invariant_definition_keeps_tuning() {
+    let (pool, db_name) = create_test_db().await;
+
+    let mut new = test_new_invariant("tuned");
+    new.timeout_secs = 900;
+    new.threshold = Some(80.0);
+    let inv = invariants::insert_invariant(&pool, &new)
+        .await
+        .expect("insert should succeed");
+
+    let args = vec!["clippy".to_string(), "--workspace".to_string()];
+    let updated = invariants::update_invariant_definition(
+        &pool,
+        inv.id,
+        Some("lint the workspace"),
+        InvariantKind::Lint,
+        "cargo",
+        &args,
+    )
+    .await
+    .expect("update should succeed");
+
+    assert_eq!(updated.id, inv.id);
+    assert_eq!(updated.description.as_deref(), Some("lint the workspace"));
+    assert_eq!(updated.kind, InvariantKind::Lint);
+    assert_eq!(updated.command, "cargo");
+    assert_eq!(updated.args.0, args);
+    assert_eq!(updated.timeout_secs, 900);
+    assert_eq!(updated.threshold, Some(80.0));
+
+    let missing = invariants::update_invariant_definition(
+        &pool,
+        Uuid::new_v4(),
+        None,
+        InvariantKind::Custom,
+        "true",
+        &[],
+    )
+    .await;
+    assert!(missing.is_err());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn audit_queries_find_unused_invariants_and_links_to_removed_ones() {
+    let (pool, db_name) = create_test_db().await;
+
+    let used = invariants::insert_invariant(&pool, &test_new_invariant("used"))
+        .await
+        .expect("insert invariant");
+    invariants::insert_invariant(&pool, &test_new_invariant("unused"))
+        .await
+        .expect("insert invariant");
+
+    let plan_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO plans (id, name, project_path, base_branch) \
+         VALUES ($1, 'audited', '/tmp', 'main')",
+    )
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert plan");
+    let task_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy) \
+         VALUES ($1, $2, 'build', 'desc', 'narrow', 'auto')",
+    )
+    .bind(task_id)
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert task");
+    invariants::link_task_invariant(&pool, task_id, used.id)
+        .await
+        .expect("link should succeed");
+
+    let names = |invs: Vec<gator_db::models::Invariant>| {
+        invs.into_iter().map(|i| i.name).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(
+            invariants::list_unreferenced_invariants(&pool)
+                .await
+                .unwrap()
+        ),
+        ["unused"]
+    );
+    assert!(
+        invariants::list_links_to_deleted_invariants(&pool)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Deleting the plan frees the invariant; restoring the plan after the
+    // invariant was removed leaves the task pointing at it.
+    gator_db::queries::plans::soft_delete_plan(&pool, plan_id)
+        .await
+        .expect("delete plan");
+    assert_eq!(
+        names(
+            invariants::list_unreferenced_invariants(&pool)
+                .await
+                .unwrap()
+        ),
+        ["unused", "used"]
+    );
+    invariants::soft_delete_invariant(&pool, "used")
+        .await
+        .expect("delete invariant");
+    gator_db::queries::plans::restore_plan(&pool, plan_id)
+        .await
+        .expect("restore plan");
+
+    let links = invariants::list_links_to_deleted_invariants(&pool)
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].plan_name, "audited");
+    assert_eq!(links[0].task_name, "build");
+    assert_eq!(links[0].invariant_name, "used");
+    assert_eq!(
+        names(
+            invariants::list_unreferenced_invariants(&pool)
+                .await
+                .unwrap()
+        ),
+        ["unused"]
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn disabled_invariants_stay_linked_but_are_not_enabled() {
+    let (pool, db_name) = create_test_db().await;
+
+    let build = invariants::insert_invariant(&pool, &test_new_invariant("build"))
+        .await
+        .expect("insert invariant");
+    let flaky = invariants::insert_invariant(&pool, &test_new_invariant("flaky"))
+        .await
+        .expect("insert invariant");
+
+    let plan_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO plans (id, name, project_path, base_branch) \
+         VALUES ($1, 'p', '/tmp', 'main')",
+    )
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert plan");
+    let task_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO tasks (id, plan_id, name, description, scope_level, gate_policy) \
+         VALUES ($1, $2, 't', 'desc', 'narrow', 'auto')",
+    )
+    .bind(task_id)
+    .bind(plan_id)
+    .execute(&pool)
+    .await
+    .expect("insert task");
+    for id in [build.id, flaky.id] {
+        invariants::link_task_invariant(&pool, task_id, id)
+            .await
+            .expect("link should succeed");
+    }
+
+    let disabled = invariants::set_invariant_disabled(&pool, "flaky", true)
+        .await
+        .expect("disable should succeed");
+    let stamp = disabled.disabled_at.expect("disabled_at should be set");
+    let again = invariants::set_invariant_disabled(&pool, "flaky", true)
+        .await
+        .unwrap();
+    assert_eq!(again.disabled_at, Some(stamp), "the first stamp is kept");
+
+    let names = |invs: Vec<gator_db::models::Invariant>| {
+        invs.into_iter().map(|i| i.name).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(
+            invariants::get_invariants_for_task(&pool, task_id)
+                .await
+                .unwrap()
+        ),
+        ["build", "flaky"]
+    );
+    assert_eq!(
+        names(
+            invariants::get_enabled_invariants_for_task(&pool, task_id)
+                .await
+                .unwrap()
+        ),
+        ["build"]
+    );
+    let links = invariants::list_links_to_disabled_invariants(&pool)
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].invariant_name, "flaky");
+
+    let enabled = invariants::set_invariant_disabled(&pool, "flaky", false)
+        .await
+        .unwrap();
+    assert!(enabled.disabled_at.is_none());
+    assert!(
+        invariants::set_invariant_disabled(&pool, "missing", true)
+            .await
+            .is_err()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn update_invariant_rewrites_definition_in_place() {
+    let (pool, db_name) = create_test_db().await;
+
+    let inv = invariants::insert_invariant(&pool, &test_new_invariant("tests"))
+        .await
+        .expect("insert invariant");
+
+    let args = vec!["nextest".to_string(), "run".to_string()];
+    let mut new = test_new_invariant("unit_tests");
+    new.kind = InvariantKind::TestSuite;
+    new.command = "cargo";
+    new.args = &args;
+    new.timeout_secs = 1200;
+    let updated = invariants::update_invariant(&pool, inv.id, &new)
+        .await
+        .expect("update should succeed");
+
+    assert_eq!(updated.id, inv.id);
+    assert_eq!(updated.created_at, inv.created_at);
+    assert_eq!(updated.name, "unit_tests");
+    assert_eq!(updated.kind, InvariantKind::TestSuite);
+    assert_eq!(updated.args.0, args);
+    assert_eq!(updated.timeout_secs, 1200);
+    assert!(
+        invariants::get_invariant_by_name(&pool, "tests")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let run_after = vec!["unit_tests".to_string()];
+    let mut coverage = test_new_invariant("coverage");
+    coverage.run_after = &run_after;
+    let coverage = invariants::insert_invariant(&pool, &coverage)
+        .await
+        .expect("insert invariant");
+    invariants::update_invariant(&pool, inv.id, &test_new_invariant("tests"))
+        .await
+        .expect("rename should succeed");
+    let coverage = invariants::get_invariant(&pool, coverage.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(coverage.run_after.0, ["tests"]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -5,19 +5,41 @@
 
 use sqlx::Row;
 
+use gator_db::config::DbConfig;
 use gator_db::pool;
 
 use gator_test_utils::{create_test_db, drop_test_db};
 
 /// Expected tables created by the migrations.
 const EXPECTED_TABLES: &[&str] = &[
+    "agent_audit",
     "agent_events",
+    "agent_leases",
+    "artifact_checks",
+    "attempt_phases",
+    "attestations",
+    "auto_approvals",
+    "change_log",
+    "change_log_settings",
+    "gate_artifacts",
     "gate_results",
     "invariants",
+    "memories",
+    "notes",
+    "notification_dead_letters",
+    "plan_throttles",
     "plans",
+    "pr_reviews",
+    "prompt_trims",
+    "review_feedback",
+    "schedules",
+    "setup_results",
     "task_dependencies",
     "task_invariants",
+    "task_revisions",
     "tasks",
+    "triage_items",
+    "warmup_results",
 ];
 
 #[tokio::test]
@@ -77,6 +99,25 @@ async fn migrations_are_idempotent() {
     drop_test_db(&db_path).await;
 }
 
+#[tokio::test]
+async fn schema_version_is_the_newest_migration() {
+    let (temp_pool, db_path) = create_test_db().await;
+
+    pool::run_migrations(&temp_pool)
+        .await
+        .expect("migrations should succeed");
+
+    let newest = pool::MIGRATOR.iter().map(|m| m.version).max();
+    let version = pool::schema_version(&temp_pool)
+        .await
+        .expect("schema version should be readable");
+    assert!(newest.is_some());
+    assert_eq!(version, newest);
+
+    temp_pool.close().await;
+    drop_test_db(&db_path).await;
+}
+
 #[tokio::test]
 async fn pool_creates_and_destroys_cleanly() {
     let (temp_pool, db_path) = create_test_db().await;
@@ -97,3 +138,41 @@ async fn pool_creates_and_destroys_cleanly() {
 
     drop_test_db(&db_path).await;
 }
+
+#[tokio::test]
+async fn read_only_pool_reads_but_rejects_writes() {
+    let (temp_pool, db_path) = create_test_db().await;
+
+    let ro_pool = pool::create_read_only_pool(&DbConfig::new(db_path.clone()))
+        .await
+        .expect("read-only pool should open an existing database");
+
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM plans")
+        .fetch_one(&ro_pool)
+        .await
+        .expect("reads should succeed on a read-only pool");
+    assert_eq!(count.0, 0);
+
+    let write = sqlx::query(
+        "INSERT INTO plans (id, name, project_path, base_branch) VALUES ('x', 'x', '/tmp', 'main')",
+    )
+    .execute(&ro_pool)
+    .await;
+    assert!(write.is_err(), "writes should fail on a read-only pool");
+
+    ro_pool.close().await;
+    temp_pool.close().await;
+    drop_test_db(&db_path).await;
+}
+
+#[tokio::test]
+async fn read_only_pool_does_not_create_missing_database() {
+    let db_path = std::env::temp_dir().join(format!(
+        "gator_missing_{}.db",
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let result = pool::create_read_only_pool(&DbConfig::new(db_path.clone())).await;
+    assert!(result.is_err(), "read-only pool must not create a database");
+    assert!(!db_path.exists());
+}
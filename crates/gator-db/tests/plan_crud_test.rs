@@ -4,10 +4,14 @@
 //! PostgreSQL instance (via testcontainers), runs migrations, and drops it on
 //! completion so tests are fully isolated.
 
+use std::time::Duration;
+
 use uuid::Uuid;
 
-use gator_db::models::{PlanStatus, TaskStatus};
-use gator_db::queries::{plans, tasks};
+use gator_db::models::{DependencyKind, LifecyclePhase, PlanFailureReason, PlanStatus, TaskStatus};
+use gator_db::queries::{
+    artifact_checks, attempt_phases, invariants, notes, plans, pr_reviews, tasks, triage_items,
+};
 
 use gator_test_utils::{create_test_db, drop_test_db};
 
@@ -297,6 +301,146 @@ async fn update_task_status_succeeds() {
     drop_test_db(&db_name).await;
 }
 
+#[tokio::test]
+async fn mark_task_merged_records_commit() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "p",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "t", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    assert_eq!(task.merged_at, None);
+    assert_eq!(task.merge_commit, None);
+
+    tasks::mark_task_merged(&pool, task.id, "0123456789abcdef")
+        .await
+        .expect("mark should succeed");
+
+    let merged = tasks::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert!(merged.merged_at.is_some());
+    assert_eq!(merged.merge_commit.as_deref(), Some("0123456789abcdef"));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn worktree_disk_usage_sums_and_lists_finished_worktrees() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "p",
+        "/tmp/project",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let done = tasks::insert_task(&pool, plan.id, "done", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    let busy = tasks::insert_task(&pool, plan.id, "busy", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    for (task, path) in [(&done, "/wt/done"), (&busy, "/wt/busy")] {
+        tasks::assign_task_metadata(&pool, task.id, "claude-code", path)
+            .await
+            .unwrap();
+    }
+    sqlx::query("UPDATE tasks SET status = 'passed' WHERE id = $1")
+        .bind(done.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    tasks::set_disk_usage(&pool, done.id, 3 << 20)
+        .await
+        .unwrap();
+    tasks::set_disk_usage(&pool, busy.id, 1 << 20)
+        .await
+        .unwrap();
+    let measured = tasks::get_task(&pool, done.id).await.unwrap().unwrap();
+    assert_eq!(measured.disk_usage_bytes, Some(3 << 20));
+    assert!(measured.disk_usage_at.is_some());
+    assert_eq!(
+        tasks::worktree_disk_usage(&pool, "/tmp/project")
+            .await
+            .unwrap(),
+        4 << 20
+    );
+    assert_eq!(
+        tasks::worktree_disk_usage(&pool, "/elsewhere")
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Only the finished task's worktree may be collected.
+    let finished = tasks::list_finished_worktrees(&pool).await.unwrap();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].task_name, "done");
+    assert_eq!(finished[0].plan_name, "p");
+    assert_eq!(finished[0].worktree_path, "/wt/done");
+    assert_eq!(finished[0].disk_usage_bytes, Some(3 << 20));
+
+    tasks::clear_disk_usage(&pool, done.id).await.unwrap();
+    assert_eq!(
+        tasks::worktree_disk_usage(&pool, "/tmp/project")
+            .await
+            .unwrap(),
+        1 << 20
+    );
+
+    // gc sees every task, and which ones are still at work.
+    sqlx::query("UPDATE tasks SET status = 'running' WHERE id = $1")
+        .bind(busy.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let refs = tasks::list_task_refs(&pool).await.unwrap();
+    assert_eq!(refs.len(), 2);
+    assert_eq!(refs[0].task_name, "busy");
+    assert!(refs[0].in_flight);
+    assert_eq!(refs[1].task_name, "done");
+    assert!(!refs[1].in_flight);
+    assert_eq!(refs[1].worktree_path.as_deref(), Some("/wt/done"));
+    assert!(!refs[1].plan_running);
+    assert_eq!(refs[1].dispatched_pid, None);
+
+    // ... and reads one again, with its plan's dispatcher, before acting.
+    plans::record_dispatcher(&pool, plan.id, "build-1", 4242, "alice")
+        .await
+        .unwrap();
+    let fresh = tasks::get_task_ref(&pool, done.id).await.unwrap().unwrap();
+    assert_eq!(fresh.task_name, "done");
+    assert_eq!(fresh.dispatched_host.as_deref(), Some("build-1"));
+    assert_eq!(fresh.dispatched_pid, Some(4242));
+    assert!(
+        tasks::get_task_ref(&pool, Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 #[tokio::test]
 async fn task_dependencies_roundtrip() {
     let (pool, db_name) = create_test_db().await;
@@ -357,6 +501,85 @@ async fn task_dependencies_roundtrip() {
     drop_test_db(&db_name).await;
 }
 
+#[tokio::test]
+async fn explain_pending_tasks_lists_unmet_dependencies() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "explain-test",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+
+    let task_a = tasks::insert_task(&pool, plan.id, "a", "first", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    let task_b = tasks::insert_task(&pool, plan.id, "b", "second", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    let task_c = tasks::insert_task(&pool, plan.id, "c", "third", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    tasks::insert_task_dependency(&pool, task_b.id, task_a.id)
+        .await
+        .unwrap();
+    tasks::insert_task_dependency(&pool, task_c.id, task_a.id)
+        .await
+        .unwrap();
+    tasks::insert_task_dependency(&pool, task_c.id, task_b.id)
+        .await
+        .unwrap();
+    tasks::update_task_status(&pool, task_a.id, TaskStatus::Running)
+        .await
+        .unwrap();
+
+    // a is no longer pending; b waits on a; c waits on a and b.
+    let mut pending = tasks::explain_pending_tasks(&pool, plan.id).await.unwrap();
+    pending.sort_by(|x, y| x.task.name.cmp(&y.task.name));
+    assert_eq!(pending.len(), 2);
+    assert_eq!(
+        pending[0].unmet_dependencies,
+        vec![tasks::UnmetDependency {
+            name: "a".to_string(),
+            status: TaskStatus::Running,
+        }]
+    );
+    let c_unmet: Vec<&str> = pending[1]
+        .unmet_dependencies
+        .iter()
+        .map(|d| d.name.as_str())
+        .collect();
+    assert_eq!(c_unmet, vec!["a", "b"]);
+    assert!(
+        tasks::get_ready_tasks(&pool, plan.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Once a passes, b is ready and c still waits on b.
+    tasks::update_task_status(&pool, task_a.id, TaskStatus::Passed)
+        .await
+        .unwrap();
+    let mut pending = tasks::explain_pending_tasks(&pool, plan.id).await.unwrap();
+    pending.sort_by(|x, y| x.task.name.cmp(&y.task.name));
+    assert!(pending[0].is_ready());
+    assert_eq!(pending[1].unmet_dependencies.len(), 1);
+    let ready = tasks::get_ready_tasks(&pool, plan.id).await.unwrap();
+    assert_eq!(ready.len(), 1);
+    assert_eq!(ready[0].id, task_b.id);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 #[tokio::test]
 async fn task_dependency_is_idempotent() {
     let (pool, db_name) = create_test_db().await;
@@ -395,6 +618,85 @@ async fn task_dependency_is_idempotent() {
     drop_test_db(&db_name).await;
 }
 
+#[tokio::test]
+async fn plan_wide_dependency_and_invariant_queries_group_by_task() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "plan-wide",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let a = tasks::insert_task(&pool, plan.id, "a", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    let b = tasks::insert_task(&pool, plan.id, "b", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    let c = tasks::insert_task(&pool, plan.id, "c", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    tasks::insert_task_dependencies(
+        &mut conn,
+        &[
+            (c.id, b.id, DependencyKind::OrderingOnly),
+            (c.id, a.id, DependencyKind::NeedsOutput),
+            (b.id, a.id, DependencyKind::NeedsOutput),
+        ],
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    let inv_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    for (id, name) in inv_ids.iter().zip(["lint", "build"]) {
+        sqlx::query(
+            "INSERT INTO invariants (id, name, kind, command) VALUES ($1, $2, 'custom', 'true')",
+        )
+        .bind(id)
+        .bind(name)
+        .execute(&pool)
+        .await
+        .unwrap();
+        tasks::link_task_invariant(&pool, a.id, *id).await.unwrap();
+    }
+
+    let deps = tasks::list_plan_dependency_names(&pool, plan.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        deps[&c.id],
+        vec![
+            ("a".to_string(), DependencyKind::NeedsOutput),
+            ("b".to_string(), DependencyKind::OrderingOnly),
+        ]
+    );
+    assert_eq!(
+        deps[&b.id],
+        vec![("a".to_string(), DependencyKind::NeedsOutput)]
+    );
+    assert!(!deps.contains_key(&a.id));
+
+    let invs = invariants::list_invariants_for_plan(&pool, plan.id)
+        .await
+        .unwrap();
+    let names: Vec<&str> = invs[&a.id].iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["build", "lint"]);
+    assert!(!invs.contains_key(&b.id));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
 #[tokio::test]
 async fn link_task_invariant_roundtrip() {
     let (pool, db_name) = create_test_db().await;
@@ -640,3 +942,631 @@ async fn update_plan_status_to_running_does_not_set_timestamps() {
     pool.close().await;
     drop_test_db(&db_name).await;
 }
+
+// -----------------------------------------------------------------------
+// Soft delete / restore
+// -----------------------------------------------------------------------
+
+#[tokio::test]
+async fn soft_delete_hides_plan_until_restored() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "doomed",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "t1", "desc", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+
+    let deleted = plans::soft_delete_plan(&pool, plan.id).await.unwrap();
+    assert!(deleted.deleted_at.is_some());
+    assert!(plans::get_plan(&pool, plan.id).await.unwrap().is_none());
+    assert!(plans::list_plans(&pool).await.unwrap().is_empty());
+    let deleted_task = tasks::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert_eq!(deleted_task.deleted_at, deleted.deleted_at);
+
+    let listed = plans::list_deleted_plans(&pool).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, plan.id);
+
+    // Deleting twice is "not found".
+    assert!(plans::soft_delete_plan(&pool, plan.id).await.is_err());
+
+    let restored = plans::restore_plan(&pool, plan.id).await.unwrap();
+    assert!(restored.deleted_at.is_none());
+    assert!(plans::get_plan(&pool, plan.id).await.unwrap().is_some());
+    let restored_task = tasks::get_task(&pool, task.id).await.unwrap().unwrap();
+    assert!(restored_task.deleted_at.is_none());
+
+    // Restoring a live plan fails.
+    assert!(plans::restore_plan(&pool, plan.id).await.is_err());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn interrupted_plan_is_resumable_and_cleared_on_resume() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "cancelled",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Only running plans can be interrupted.
+    assert!(plans::interrupt_plan(&pool, plan.id).await.is_err());
+
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+    let interrupted = plans::interrupt_plan(&pool, plan.id).await.unwrap();
+    assert_eq!(interrupted.status, PlanStatus::Approved);
+    assert!(interrupted.interrupted_at.is_some());
+    assert!(interrupted.completed_at.is_none());
+
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+    let resumed = plans::get_plan(&pool, plan.id).await.unwrap().unwrap();
+    assert!(resumed.interrupted_at.is_none());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn record_dispatcher_overwrites_the_previous_run() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "owned",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(plan.dispatched_host.is_none());
+    assert!(plan.dispatched_at.is_none());
+
+    plans::record_dispatcher(&pool, plan.id, "build-1", 100, "alice")
+        .await
+        .unwrap();
+    plans::record_dispatcher(&pool, plan.id, "build-2", 200, "bob")
+        .await
+        .unwrap();
+
+    let owned = plans::get_plan(&pool, plan.id).await.unwrap().unwrap();
+    assert_eq!(owned.dispatched_host.as_deref(), Some("build-2"));
+    assert_eq!(owned.dispatched_pid, Some(200));
+    assert_eq!(owned.dispatched_user.as_deref(), Some("bob"));
+    assert!(owned.dispatched_at.is_some());
+
+    assert!(
+        plans::record_dispatcher(&pool, Uuid::new_v4(), "h", 1, "u")
+            .await
+            .is_err()
+    );
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn budget_failed_plan_resumes_with_raised_budget() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "over-budget",
+        "/tmp",
+        "main",
+        Some(1000),
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+
+    plans::fail_plan(&pool, plan.id, PlanFailureReason::BudgetExceeded)
+        .await
+        .unwrap();
+    let failed = plans::get_plan(&pool, plan.id).await.unwrap().unwrap();
+    assert_eq!(failed.status, PlanStatus::Failed);
+    assert_eq!(
+        failed.failure_reason,
+        Some(PlanFailureReason::BudgetExceeded)
+    );
+    assert!(failed.completed_at.is_some());
+
+    let resumed = plans::resume_plan(&pool, plan.id, 5000).await.unwrap();
+    assert_eq!(resumed.status, PlanStatus::Approved);
+    assert_eq!(resumed.token_budget, Some(5000));
+    assert!(resumed.failure_reason.is_none());
+    assert!(resumed.completed_at.is_none());
+
+    // Plans that failed for another reason go through `plan reset` instead.
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+    plans::fail_plan(&pool, plan.id, PlanFailureReason::TasksEscalated)
+        .await
+        .unwrap();
+    let err = plans::resume_plan(&pool, plan.id, 9000).await.unwrap_err();
+    assert!(err.to_string().contains("did not stop on its token budget"));
+    let reset = plans::reset_plan(&pool, plan.id).await.unwrap();
+    assert!(reset.failure_reason.is_none());
+    assert_eq!(reset.token_budget, Some(5000));
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn archive_hides_plan_from_listing_until_unarchived() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "finished",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+
+    let archived = plans::archive_plan(&pool, plan.id).await.unwrap();
+    assert!(archived.archived_at.is_some());
+    assert!(plans::list_plans(&pool).await.unwrap().is_empty());
+    // Archived plans stay reachable by ID.
+    assert!(plans::get_plan(&pool, plan.id).await.unwrap().is_some());
+    let listed = plans::list_archived_plans(&pool).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, plan.id);
+
+    let err = plans::archive_plan(&pool, plan.id).await.unwrap_err();
+    assert!(err.to_string().contains("already archived"), "got: {err}");
+
+    let unarchived = plans::unarchive_plan(&pool, plan.id).await.unwrap();
+    assert!(unarchived.archived_at.is_none());
+    assert_eq!(plans::list_plans(&pool).await.unwrap().len(), 1);
+    assert!(plans::unarchive_plan(&pool, plan.id).await.is_err());
+
+    // Running plans cannot be archived.
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+    let err = plans::archive_plan(&pool, plan.id).await.unwrap_err();
+    assert!(err.to_string().contains("running"), "got: {err}");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn soft_delete_refuses_running_plan() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "busy",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    plans::update_plan_status(&pool, plan.id, PlanStatus::Running)
+        .await
+        .unwrap();
+
+    let err = plans::soft_delete_plan(&pool, plan.id).await.unwrap_err();
+    assert!(err.to_string().contains("running"), "got: {err}");
+    assert!(plans::get_plan(&pool, plan.id).await.unwrap().is_some());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn purge_removes_only_plans_past_cutoff() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "old",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "t1", "desc", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    plans::soft_delete_plan(&pool, plan.id).await.unwrap();
+
+    // Deleted just now: a cutoff in the past keeps it.
+    let past = chrono::Utc::now() - chrono::Duration::days(1);
+    assert_eq!(plans::purge_deleted_plans(&pool, past).await.unwrap(), 0);
+    assert_eq!(plans::list_deleted_plans(&pool).await.unwrap().len(), 1);
+
+    // A cutoff in the future removes it, and its tasks cascade.
+    let future = chrono::Utc::now() + chrono::Duration::days(1);
+    assert_eq!(plans::purge_deleted_plans(&pool, future).await.unwrap(), 1);
+    assert!(plans::list_deleted_plans(&pool).await.unwrap().is_empty());
+    assert!(tasks::get_task(&pool, task.id).await.unwrap().is_none());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+// -----------------------------------------------------------------------
+// Triage item tests
+// -----------------------------------------------------------------------
+
+#[tokio::test]
+async fn triage_items_queue_each_issue_once() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "widgets-triage",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "issue-4", "desc", "narrow", "auto", 2, None)
+        .await
+        .unwrap();
+
+    let item = triage_items::insert_triage_item(&pool, "acme/widgets", 4, plan.id, task.id)
+        .await
+        .unwrap();
+    assert_eq!(item.status, "queued");
+    assert!(
+        triage_items::insert_triage_item(&pool, "acme/widgets", 4, plan.id, task.id)
+            .await
+            .is_err(),
+        "an issue is queued at most once"
+    );
+
+    let seen = triage_items::triaged_issue_numbers(&pool, "acme/widgets")
+        .await
+        .unwrap();
+    assert!(seen.contains(&4));
+    assert!(
+        triage_items::triaged_issue_numbers(&pool, "acme/other")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    triage_items::mark_triage_posted(&pool, item.id)
+        .await
+        .unwrap();
+    let queued = triage_items::list_triage_items(&pool, Some("acme/widgets"), Some("queued"))
+        .await
+        .unwrap();
+    assert!(queued.is_empty());
+    let all = triage_items::list_triage_items(&pool, None, None)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].status, "posted");
+    assert!(all[0].posted_at.is_some());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn pr_reviews_queue_each_head_once() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "widgets-review-pr-7",
+        "/tmp",
+        "gator-review/pr-7",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(
+        &pool,
+        plan.id,
+        "review-pr-7",
+        "desc",
+        "narrow",
+        "auto",
+        2,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let changed = vec!["src/a.rs".to_string()];
+    let review =
+        pr_reviews::insert_pr_review(&pool, "acme/widgets", 7, "abc", plan.id, task.id, &changed)
+            .await
+            .unwrap();
+    assert_eq!(review.status, "queued");
+    assert_eq!(review.changed_files.0, changed);
+    assert!(
+        pr_reviews::insert_pr_review(&pool, "acme/widgets", 7, "abc", plan.id, task.id, &[])
+            .await
+            .is_err(),
+        "a head is queued at most once"
+    );
+    pr_reviews::insert_pr_review(&pool, "acme/widgets", 7, "def", plan.id, task.id, &[])
+        .await
+        .unwrap();
+
+    let seen = pr_reviews::reviewed_heads(&pool, "acme/widgets")
+        .await
+        .unwrap();
+    assert!(seen.contains(&(7, "abc".to_string())));
+    assert!(seen.contains(&(7, "def".to_string())));
+
+    let found = pr_reviews::get_pr_review_for_task(&pool, task.id)
+        .await
+        .unwrap();
+    assert!(found.is_some());
+
+    pr_reviews::mark_pr_review_failed(&pool, review.id, "gh failed")
+        .await
+        .unwrap();
+    let failed = pr_reviews::list_pr_reviews(&pool, Some("acme/widgets"), Some("failed"))
+        .await
+        .unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].error.as_deref(), Some("gh failed"));
+
+    // The review check is recorded alongside the other artifact checks.
+    artifact_checks::insert_artifact_check(
+        &pool,
+        &artifact_checks::NewArtifactCheck {
+            task_id: task.id,
+            attempt: 0,
+            path: ".gator-review/review.json".to_string(),
+            check_name: "review".to_string(),
+            passed: true,
+            message: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn attempt_phases_sum_per_plan() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "timed",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "t1", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+
+    for (attempt, gate_ms) in [(0, 4000), (1, 6000)] {
+        attempt_phases::record_phase(
+            &pool,
+            task.id,
+            attempt,
+            LifecyclePhase::Workspace,
+            Duration::from_millis(500),
+        )
+        .await
+        .unwrap();
+        attempt_phases::record_phase(
+            &pool,
+            task.id,
+            attempt,
+            LifecyclePhase::Gate,
+            Duration::from_millis(gate_ms),
+        )
+        .await
+        .unwrap();
+    }
+    // Recording a phase again replaces its duration.
+    attempt_phases::record_phase(
+        &pool,
+        task.id,
+        1,
+        LifecyclePhase::Gate,
+        Duration::from_millis(2000),
+    )
+    .await
+    .unwrap();
+
+    let phases = attempt_phases::list_phases_for_attempt(&pool, task.id, 1)
+        .await
+        .unwrap();
+    assert_eq!(phases.len(), 2);
+    assert_eq!(phases[0].phase, LifecyclePhase::Workspace);
+    assert_eq!(phases[1].duration_ms, 2000);
+
+    let totals = attempt_phases::phase_totals_for_plan(&pool, plan.id)
+        .await
+        .unwrap();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].phase, LifecyclePhase::Workspace);
+    assert_eq!(totals[0].attempts, 2);
+    assert_eq!(totals[0].total_ms, 1000);
+    assert_eq!(totals[1].phase, LifecyclePhase::Gate);
+    assert_eq!(totals[1].total_ms, 6000);
+    assert_eq!(totals[1].max_ms, 4000);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn notes_are_listed_per_plan_and_per_task() {
+    let (pool, db_name) = create_test_db().await;
+
+    let plan = plans::insert_plan(
+        &pool,
+        "p",
+        "/tmp",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .unwrap();
+    let task = tasks::insert_task(&pool, plan.id, "t", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+
+    notes::insert_note(&pool, plan.id, None, "paused for a deploy freeze")
+        .await
+        .unwrap();
+    let task_note = notes::insert_note(&pool, plan.id, Some(task.id), "fixed the lockfile by hand")
+        .await
+        .unwrap();
+    assert_eq!(task_note.task_id, Some(task.id));
+
+    let plan_notes = notes::list_notes_for_plan(&pool, plan.id).await.unwrap();
+    let bodies: Vec<&str> = plan_notes.iter().map(|n| n.body.as_str()).collect();
+    assert_eq!(
+        bodies,
+        ["paused for a deploy freeze", "fixed the lockfile by hand"]
+    );
+
+    let task_notes = notes::list_notes_for_task(&pool, task.id).await.unwrap();
+    assert_eq!(task_notes.len(), 1);
+    assert_eq!(task_notes[0].id, task_note.id);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn finished_tasks_for_project_skip_the_plan_and_other_projects() {
+    let (pool, db_name) = create_test_db().await;
+
+    let insert = |name: &'static str, project: &'static str| {
+        let pool = pool.clone();
+        async move {
+            plans::insert_plan(
+                &pool,
+                name,
+                project,
+                "main",
+                None,
+                "claude-code",
+                "worktree",
+                None,
+            )
+            .await
+            .unwrap()
+        }
+    };
+    let earlier = insert("earlier", "/tmp/a").await;
+    let current = insert("current", "/tmp/a").await;
+    let elsewhere = insert("elsewhere", "/tmp/b").await;
+
+    let passed = tasks::insert_task(&pool, earlier.id, "passed", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    tasks::update_task_status(&pool, passed.id, TaskStatus::Passed)
+        .await
+        .unwrap();
+    let escalated = tasks::insert_task(
+        &pool,
+        earlier.id,
+        "escalated",
+        "d",
+        "narrow",
+        "auto",
+        3,
+        None,
+    )
+    .await
+    .unwrap();
+    tasks::update_task_status(&pool, escalated.id, TaskStatus::Escalated)
+        .await
+        .unwrap();
+    // Still pending, in the plan itself, or in another project: left out.
+    tasks::insert_task(&pool, earlier.id, "pending", "d", "narrow", "auto", 3, None)
+        .await
+        .unwrap();
+    for plan in [&current, &elsewhere] {
+        let task = tasks::insert_task(&pool, plan.id, "other", "d", "narrow", "auto", 3, None)
+            .await
+            .unwrap();
+        tasks::update_task_status(&pool, task.id, TaskStatus::Failed)
+            .await
+            .unwrap();
+    }
+
+    let finished = tasks::list_finished_tasks_for_project(&pool, "/tmp/a", current.id)
+        .await
+        .unwrap();
+    let names: Vec<&str> = finished.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["passed", "escalated"]);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
@@ -0,0 +1,296 @@
+//! Tests for the `stats` query module.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use gator_db::models::{InvariantKind, InvariantScope};
+use gator_db::queries::agent_events::{self, NewAgentEvent};
+use gator_db::queries::gate_results::{self, NewGateResult};
+use gator_db::queries::invariants::{self, NewInvariant};
+use gator_db::queries::stats::{self, StatsPeriod};
+
+use gator_test_utils::{create_test_db, drop_test_db};
+
+// ===========================================================================
+// Test harness
+// ===========================================================================
+
+async fn create_plan(pool: &SqlitePool, name: &str) -> Uuid {
+    gator_db::queries::plans::insert_plan(
+        pool,
+        name,
+        "/tmp/test",
+        "main",
+        None,
+        "claude-code",
+        "worktree",
+        None,
+    )
+    .await
+    .expect("insert plan")
+    .id
+}
+
+/// When every test task finished.
+const COMPLETED_AT: &str = "2026-03-14T12:00:00.000Z";
+
+/// Insert a task finished with `status` after `attempt` retries, on
+/// `harness` if given (else the plan default).
+async fn finished_task(
+    pool: &SqlitePool,
+    plan_id: Uuid,
+    name: &str,
+    scope: &str,
+    status: &str,
+    attempt: i32,
+    harness: Option<&str>,
+) -> Uuid {
+    let task = gator_db::queries::tasks::insert_task(
+        pool,
+        plan_id,
+        name,
+        "A test task",
+        scope,
+        "auto",
+        3,
+        None,
+    )
+    .await
+    .expect("insert task");
+    sqlx::query(
+        "UPDATE tasks SET status = $1, attempt = $2, assigned_harness = $3, completed_at = $4 \
+         WHERE id = $5",
+    )
+    .bind(status)
+    .bind(attempt)
+    .bind(harness)
+    .bind(COMPLETED_AT)
+    .bind(task.id)
+    .execute(pool)
+    .await
+    .expect("finish task");
+    task.id
+}
+
+async fn create_invariant(pool: &SqlitePool, name: &str) -> Uuid {
+    invariants::insert_invariant(
+        pool,
+        &NewInvariant {
+            name,
+            description: None,
+            kind: InvariantKind::Custom,
+            command: "true",
+            args: &[],
+            expected_exit_code: 0,
+            threshold: None,
+            scope: InvariantScope::Project,
+            timeout_secs: 300,
+            diff_base: false,
+            lower_is_better: false,
+            env: &BTreeMap::new(),
+            workdir: None,
+            run_after: &[],
+            artifacts: &[],
+        },
+    )
+    .await
+    .expect("insert invariant")
+    .id
+}
+
+async fn record_gate(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    invariant_id: Uuid,
+    passed: bool,
+    cached_from: Option<i32>,
+) {
+    let new = NewGateResult {
+        task_id,
+        invariant_id,
+        attempt: 0,
+        passed,
+        exit_code: Some(if passed { 0 } else { 1 }),
+        stdout: None,
+        stderr: None,
+        duration_ms: None,
+        timed_out: false,
+        base_value: None,
+        task_value: None,
+        content_hash: None,
+        cached_from,
+    };
+    gate_results::insert_gate_result(pool, &new)
+        .await
+        .expect("insert gate result");
+}
+
+async fn record_tokens(pool: &SqlitePool, task_id: Uuid, input: i64, output: i64, at: &str) {
+    let event = agent_events::insert_agent_event(
+        pool,
+        &NewAgentEvent {
+            task_id,
+            attempt: 0,
+            event_type: "token_usage".to_string(),
+            payload: serde_json::json!({"input_tokens": input, "output_tokens": output}),
+        },
+    )
+    .await
+    .expect("insert event");
+    sqlx::query("UPDATE agent_events SET recorded_at = $1 WHERE id = $2")
+        .bind(at)
+        .bind(event.id)
+        .execute(pool)
+        .await
+        .expect("backdate event");
+}
+
+// ===========================================================================
+// Tests
+// ===========================================================================
+
+#[tokio::test]
+async fn harness_and_scope_stats_count_finished_tasks_of_live_plans() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_plan(&pool, "live").await;
+    let deleted = create_plan(&pool, "deleted").await;
+
+    finished_task(&pool, plan, "a", "narrow", "passed", 0, None).await;
+    finished_task(&pool, plan, "b", "narrow", "failed", 2, None).await;
+    finished_task(&pool, plan, "c", "broad", "passed", 1, Some("codex")).await;
+    finished_task(&pool, plan, "d", "broad", "escalated", 2, Some("codex")).await;
+    finished_task(&pool, plan, "e", "broad", "passed", 0, Some("codex")).await;
+    // Neither cancelled tasks nor deleted plans count.
+    finished_task(&pool, plan, "f", "narrow", "cancelled", 0, None).await;
+    finished_task(&pool, deleted, "g", "narrow", "failed", 0, None).await;
+    sqlx::query("UPDATE plans SET deleted_at = $1 WHERE id = $2")
+        .bind(COMPLETED_AT)
+        .bind(deleted)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let harnesses = stats::harness_stats(&pool, None).await.unwrap();
+    assert_eq!(
+        harnesses
+            .iter()
+            .map(|h| (h.harness.as_str(), h.passed, h.failed))
+            .collect::<Vec<_>>(),
+        vec![("codex", 2, 1), ("claude-code", 1, 1)]
+    );
+    assert!((harnesses[0].success_rate() - 2.0 / 3.0).abs() < 1e-9);
+
+    let scopes = stats::scope_stats(&pool, None).await.unwrap();
+    assert_eq!(scopes.len(), 2);
+    assert_eq!(scopes[0].scope_level, "narrow");
+    assert_eq!(scopes[0].tasks, 2);
+    assert!((scopes[0].avg_attempts - 2.0).abs() < 1e-9);
+    assert_eq!(scopes[0].max_attempts, 3);
+    assert_eq!(scopes[1].scope_level, "broad");
+    assert!((scopes[1].avg_attempts - 2.0).abs() < 1e-9);
+
+    // Everything finished before the cutoff.
+    let since = Some(Utc::now() + Duration::days(1));
+    assert!(stats::harness_stats(&pool, since).await.unwrap().is_empty());
+    assert!(stats::scope_stats(&pool, since).await.unwrap().is_empty());
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn token_usage_is_summed_per_period() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_plan(&pool, "tokens").await;
+    let task = finished_task(&pool, plan, "t", "narrow", "passed", 0, None).await;
+
+    record_tokens(&pool, task, 100, 10, "2026-02-27T09:00:00.000Z").await;
+    record_tokens(&pool, task, 200, 20, "2026-03-02T09:00:00.000Z").await;
+    record_tokens(&pool, task, 300, 30, "2026-03-02T18:00:00.000Z").await;
+
+    let daily = stats::token_usage_by_period(&pool, StatsPeriod::Day, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        daily
+            .iter()
+            .map(|u| (u.period.as_str(), u.input_tokens, u.output_tokens))
+            .collect::<Vec<_>>(),
+        vec![("2026-02-27", 100, 10), ("2026-03-02", 500, 50)]
+    );
+
+    let monthly = stats::token_usage_by_period(&pool, StatsPeriod::Month, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        monthly
+            .iter()
+            .map(|u| u.period.as_str())
+            .collect::<Vec<_>>(),
+        vec!["2026-02", "2026-03"]
+    );
+
+    let weekly = stats::token_usage_by_period(&pool, StatsPeriod::Week, None)
+        .await
+        .unwrap();
+    assert_eq!(weekly.len(), 2);
+    assert!(weekly[0].period.starts_with("2026-W"));
+
+    let since = "2026-03-01T00:00:00Z".parse().unwrap();
+    let recent = stats::token_usage_by_period(&pool, StatsPeriod::Month, Some(since))
+        .await
+        .unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].input_tokens, 500);
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[tokio::test]
+async fn failing_invariants_rank_by_failures_without_cached_results() {
+    let (pool, db_name) = create_test_db().await;
+    let plan = create_plan(&pool, "gates").await;
+    let first = finished_task(&pool, plan, "a", "narrow", "failed", 0, None).await;
+    let second = finished_task(&pool, plan, "b", "narrow", "failed", 0, None).await;
+    let lint = create_invariant(&pool, "lint").await;
+    let tests = create_invariant(&pool, "tests").await;
+    let build = create_invariant(&pool, "build").await;
+
+    record_gate(&pool, first, tests, false, None).await;
+    record_gate(&pool, first, tests, false, None).await;
+    record_gate(&pool, second, tests, false, None).await;
+    record_gate(&pool, second, tests, true, None).await;
+    record_gate(&pool, first, lint, false, None).await;
+    // A cached failure is the same failure again.
+    record_gate(&pool, second, lint, false, Some(0)).await;
+    record_gate(&pool, first, build, true, None).await;
+
+    let ranked = stats::failing_invariants(&pool, None, 10).await.unwrap();
+    assert_eq!(
+        ranked
+            .iter()
+            .map(|i| (i.name.as_str(), i.failures, i.checks, i.tasks))
+            .collect::<Vec<_>>(),
+        vec![("tests", 3, 4, 2), ("lint", 1, 1, 1)]
+    );
+
+    let top = stats::failing_invariants(&pool, None, 1).await.unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].name, "tests");
+
+    pool.close().await;
+    drop_test_db(&db_name).await;
+}
+
+#[test]
+fn periods_parse_and_display() {
+    for period in [StatsPeriod::Day, StatsPeriod::Week, StatsPeriod::Month] {
+        assert_eq!(period.to_string().parse::<StatsPeriod>(), Ok(period));
+    }
+    assert_eq!(StatsPeriod::default(), StatsPeriod::Week);
+    assert!("year".parse::<StatsPeriod>().is_err());
+}
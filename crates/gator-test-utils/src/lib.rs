@@ -1,8 +1,13 @@
 use std::path::PathBuf;
+use std::process::Command;
 
 use gator_db::{config::DbConfig, pool};
 use sqlx::SqlitePool;
 
+mod snapshot;
+
+pub use snapshot::assert_snapshot;
+
 /// Create a temporary SQLite database with migrations applied.
 ///
 /// Returns `(pool, db_path)`. Each test gets its own isolated database file
@@ -36,3 +41,80 @@ pub async fn drop_test_db(db_path: &std::path::Path) {
     let _ = std::fs::remove_file(wal);
     let _ = std::fs::remove_file(shm);
 }
+
+/// Create a temporary git repository with one commit (`README.md`).
+///
+/// Returns `(dir, repo_path)`; the repository is deleted when `dir` is
+/// dropped.
+pub fn create_temp_git_repo() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::TempDir::new().expect("failed to create temp dir");
+    let repo_path = dir.path().to_path_buf();
+
+    run_git(&repo_path, &["init", "-q"]);
+    run_git(&repo_path, &["config", "user.email", "test@gator.dev"]);
+    run_git(&repo_path, &["config", "user.name", "Gator Test"]);
+    std::fs::write(repo_path.join("README.md"), "# Test repo\n").expect("failed to write README");
+    run_git(&repo_path, &["add", "."]);
+    run_git(&repo_path, &["commit", "-q", "-m", "Initial commit"]);
+
+    (dir, repo_path)
+}
+
+/// Run git in `repo_path`, panicking unless it succeeds, and return its
+/// trimmed stdout.
+pub fn run_git(repo_path: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .unwrap_or_else(|e| panic!("failed to run git {}: {e}", args.join(" ")));
+    assert!(
+        output.status.success(),
+        "git {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Start recording every task status change in the test database, for
+/// [`task_transitions`]. Call it before anything updates a task.
+pub async fn record_task_transitions(pool: &SqlitePool) {
+    sqlx::raw_sql(
+        "CREATE TABLE test_task_transitions (\
+             seq INTEGER PRIMARY KEY AUTOINCREMENT, \
+             task_id BLOB NOT NULL, \
+             from_status TEXT NOT NULL, \
+             to_status TEXT NOT NULL); \
+         CREATE TRIGGER test_task_transitions AFTER UPDATE OF status ON tasks \
+         WHEN OLD.status IS NOT NEW.status \
+         BEGIN \
+             INSERT INTO test_task_transitions (task_id, from_status, to_status) \
+             VALUES (NEW.id, OLD.status, NEW.status); \
+         END;",
+    )
+    .execute(pool)
+    .await
+    .expect("failed to install the task transition recorder");
+}
+
+/// The statuses `task_id` went through since [`record_task_transitions`],
+/// starting with the one it had then.
+pub async fn task_transitions(pool: &SqlitePool, task_id: uuid::Uuid) -> Vec<String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT from_status, to_status FROM test_task_transitions \
+         WHERE task_id = $1 ORDER BY seq",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+    .expect("failed to read task transitions");
+
+    let mut statuses: Vec<String> = rows
+        .first()
+        .map(|(from, _)| from.clone())
+        .into_iter()
+        .collect();
+    statuses.extend(rows.into_iter().map(|(_, to)| to));
+    statuses
+}
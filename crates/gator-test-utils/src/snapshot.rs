@@ -0,0 +1,80 @@
+//! Golden snapshot assertions.
+//!
+//! A snapshot is a plain-text file checked in next to the test that
+//! produced it. When behavior changes on purpose, regenerate the files with
+//! `GATOR_UPDATE_SNAPSHOTS=1 cargo test` and review the diff.
+
+use std::path::Path;
+
+/// Environment variable that makes [`assert_snapshot`] write snapshots
+/// instead of comparing against them.
+const UPDATE_VAR: &str = "GATOR_UPDATE_SNAPSHOTS";
+
+/// Assert that `actual` matches the snapshot `<dir>/<name>.snap`.
+///
+/// With `GATOR_UPDATE_SNAPSHOTS=1`, the snapshot is (re)written instead. A
+/// missing snapshot fails the test, so a new one has to be written and
+/// reviewed deliberately.
+pub fn assert_snapshot(dir: impl AsRef<Path>, name: &str, actual: &str) {
+    let path = dir.as_ref().join(format!("{name}.snap"));
+    let actual = format!("{}\n", actual.trim_end());
+
+    if std::env::var_os(UPDATE_VAR).is_some_and(|v| v != "0") {
+        std::fs::create_dir_all(dir.as_ref()).expect("failed to create snapshot directory");
+        std::fs::write(&path, &actual)
+            .unwrap_or_else(|e| panic!("failed to write {}: {e}", path.display()));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read snapshot {}: {e}\n\
+             Run with {UPDATE_VAR}=1 to create it. Actual:\n{actual}",
+            path.display()
+        )
+    });
+    if expected != actual {
+        panic!(
+            "snapshot {} does not match.\n\
+             Run with {UPDATE_VAR}=1 to update it if the change is intended.\n\
+             {}",
+            path.display(),
+            line_diff(&expected, &actual)
+        );
+    }
+}
+
+/// A line-by-line diff: `-` lines only in `expected`, `+` lines only in
+/// `actual`, in order.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let (old, new): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        }
+    }
+    out
+}
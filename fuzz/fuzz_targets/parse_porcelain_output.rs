@@ -0,0 +1,15 @@
+#![no_main]
+
+use gator_core::worktree::parse_porcelain_output;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // list_worktrees decodes git's output lossily, so do the same here.
+    let output = String::from_utf8_lossy(data);
+    if let Ok(worktrees) = parse_porcelain_output(&output) {
+        for wt in worktrees {
+            assert!(!wt.head_commit.is_empty());
+            assert!(!wt.branch.is_some_and(|b| b.contains('\n')));
+        }
+    }
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use gator_core::token::{
+    TokenConfig, TokenVersion, generate_token, generate_token_v1, validate_token,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(token) = std::str::from_utf8(data) else {
+        return;
+    };
+    let config = TokenConfig::new(b"fuzz-secret".to_vec());
+    // Anything accepted must be exactly what generate_token would produce.
+    if let Ok(claims) = validate_token(&config, token) {
+        let generate = match claims.version {
+            TokenVersion::V1 => generate_token_v1,
+            TokenVersion::V2 => generate_token,
+        };
+        assert_eq!(generate(&config, claims.task_id, claims.attempt), token);
+    }
+});